@@ -5,6 +5,7 @@ Generate nim-usage-scanner repos.yaml from NGC blueprint endpoints.
 
 import argparse
 import json
+import os
 import re
 from concurrent.futures import ThreadPoolExecutor, as_completed
 from pathlib import Path
@@ -12,23 +13,25 @@ from urllib.parse import quote
 from urllib.request import Request, urlopen
 
 
+NGC_API_URL = "https://api.ngc.nvidia.com"
+GITHUB_API_URL = "https://api.github.com"
 # List all blueprints: /v2/search/catalog/resources/BLUEPRINT with query "" and pageSize 1000 (returns all in one response).
-NGC_BLUEPRINT_LIST_URL = "https://api.ngc.nvidia.com/v2/search/catalog/resources/BLUEPRINT"
+NGC_BLUEPRINT_LIST_PATH = "/v2/search/catalog/resources/BLUEPRINT"
 # Spec URL pattern: https://api.ngc.nvidia.com/v2/blueprints/{orgName}/{name}/spec
-NGC_BLUEPRINTS_SPEC_URL_TEMPLATE = "https://api.ngc.nvidia.com/v2/blueprints/{org_name}/{name}/spec"
+NGC_BLUEPRINTS_SPEC_PATH_TEMPLATE = "/v2/blueprints/{org_name}/{name}/spec"
 
 
-def fetch_json(url: str) -> dict:
-    req = Request(url, headers={"User-Agent": "nim-usage-scanner/1.0"})
+def fetch_json(url: str, headers: dict[str, str] | None = None) -> dict:
+    req = Request(url, headers={"User-Agent": "nim-usage-scanner/1.0", **(headers or {})})
     with urlopen(req, timeout=30) as resp:
         data = resp.read().decode("utf-8")
     return json.loads(data)
 
 
-def build_blueprint_list_url(page_size: int = 1000) -> str:
+def build_blueprint_list_url(page_size: int = 1000, api_url: str = NGC_API_URL) -> str:
     """Build URL for resources/BLUEPRINT list API; returns all blueprints in one response."""
     payload = {"query": "", "pageSize": page_size}
-    return f"{NGC_BLUEPRINT_LIST_URL}?q={quote(json.dumps(payload))}"
+    return f"{api_url}{NGC_BLUEPRINT_LIST_PATH}?q={quote(json.dumps(payload))}"
 
 
 def find_github_url(payload: object) -> str | None:
@@ -138,11 +141,40 @@ def repo_name_from_github_url(url: str) -> str | None:
     return f"{owner}/{repo}"
 
 
+def fetch_github_repo_meta(repo_name: str, api_url: str, token: str | None) -> dict | None:
+    """Fetch default branch and archived flag from GitHub's /repos/{owner}/{repo}; None on failure."""
+    headers = {"Accept": "application/vnd.github+json"}
+    if token:
+        headers["Authorization"] = f"Bearer {token}"
+    try:
+        data = fetch_json(f"{api_url}/repos/{repo_name}", headers)
+    except Exception as exc:
+        print(f"[GitHub] Failed to fetch metadata for {repo_name}: {exc}")
+        return None
+    branch = data.get("default_branch")
+    return {
+        "default_branch": branch if isinstance(branch, str) and branch else None,
+        "archived": data.get("archived") is True,
+    }
+
+
+def fetch_github_meta(
+    repo_names: list[str],
+    api_url: str,
+    token: str | None,
+    workers: int,
+) -> dict[str, dict | None]:
+    with ThreadPoolExecutor(max_workers=workers) as executor:
+        futures = {executor.submit(fetch_github_repo_meta, name, api_url, token): name for name in repo_names}
+        return {futures[future]: future.result() for future in as_completed(futures)}
+
+
 def fetch_blueprint_repos(
     org_name: str,
     label: str,
     page_size: int,
     workers: int,
+    api_url: str = NGC_API_URL,
 ) -> tuple[
     list[str],
     list[str],
@@ -151,7 +183,7 @@ def fetch_blueprint_repos(
     int,
 ]:
     """List all blueprints via /v2/search/catalog/resources/BLUEPRINT, then fetch each spec from /v2/blueprints/{orgName}/{name}/spec."""
-    url = build_blueprint_list_url(page_size)
+    url = build_blueprint_list_url(page_size, api_url)
     data = fetch_json(url)
 
     total = data.get("resultTotal")
@@ -189,7 +221,7 @@ def fetch_blueprint_repos(
     def fetch_spec(item: tuple[str, str]) -> tuple[str, dict] | tuple[str, None]:
         org, name = item
         resource_id = f"{org}/{name}"
-        spec_url = NGC_BLUEPRINTS_SPEC_URL_TEMPLATE.format(org_name=org, name=name)
+        spec_url = api_url + NGC_BLUEPRINTS_SPEC_PATH_TEMPLATE.format(org_name=org, name=name)
         try:
             return resource_id, fetch_json(spec_url)
         except Exception as exc:
@@ -226,7 +258,11 @@ def render_repos_yaml(
     repo_names: list[str],
     branch: str,
     depth: int,
+    github_meta: dict[str, dict | None] | None = None,
 ) -> str:
+    """Render repos.yaml; each repo gets its GitHub default branch when known
+    (otherwise no branch field, so defaults.branch applies) and archived repos
+    are disabled."""
     lines: list[str] = [
         "# NIM Usage Scanner Configuration",
         "# This file defines the repositories to scan for NIM usage",
@@ -244,13 +280,21 @@ def render_repos_yaml(
 
     for name in repo_names:
         url = f"https://github.com/{name}.git"
+        meta = (github_meta or {}).get(name)
         lines.extend([
             f"  - name: {name}",
             f"    url: {url}",
-            f"    branch: {branch}",
-            "    enabled: true",
-            "",
         ])
+        if meta and meta["default_branch"]:
+            lines.append(f"    branch: {meta['default_branch']}")
+        if meta and meta["archived"]:
+            lines.extend([
+                "    # Archived on GitHub: disabled by generate_repos_from_ngc.py",
+                "    enabled: false",
+            ])
+        else:
+            lines.append("    enabled: true")
+        lines.append("")
 
     if lines[-1] == "":
         lines.pop()
@@ -270,13 +314,20 @@ def parse_args() -> argparse.Namespace:
     parser.add_argument("--label", default="blueprint", help="(Unused with v2/blueprints API; kept for CLI compatibility)")
     parser.add_argument("--page-size", type=int, default=1000, help="NGC page size")
     parser.add_argument("--workers", type=int, default=8, help="Spec fetch workers")
-    parser.add_argument("--branch", default="main", help="Default branch")
+    parser.add_argument("--branch", default="main", help="Default branch (defaults.branch; used for repos whose GitHub metadata can't be fetched)")
     parser.add_argument("--depth", type=int, default=1, help="Git clone depth")
     parser.add_argument(
         "--output",
         default="config/repos.yaml",
         help="Output repos.yaml path",
     )
+    parser.add_argument("--ngc-api-url", default=NGC_API_URL, help="NGC API base URL")
+    parser.add_argument("--github-api-url", default=GITHUB_API_URL, help="GitHub API base URL")
+    parser.add_argument(
+        "--github-token",
+        default=os.environ.get("GITHUB_TOKEN"),
+        help="GitHub token for repo metadata (default: GITHUB_TOKEN env var)",
+    )
     return parser.parse_args()
 
 
@@ -287,6 +338,7 @@ def main() -> None:
         args.label,
         args.page_size,
         args.workers,
+        args.ngc_api_url,
     )
     if not repos:
         print("Error: No repositories found from NGC API.")
@@ -294,10 +346,16 @@ def main() -> None:
 
     output_path = Path(args.output)
     output_path.parent.mkdir(parents=True, exist_ok=True)
-    content = render_repos_yaml(repos, args.branch, args.depth)
+    github_meta = fetch_github_meta(repos, args.github_api_url, args.github_token, args.workers)
+    content = render_repos_yaml(repos, args.branch, args.depth, github_meta)
     output_path.write_text(content, encoding="utf-8")
     print(f"[Build Page] Total resources processed: {total_resources}")
     print(f"[Build Page] Wrote {len(repos)} repos to {output_path}")
+    archived = sorted(name for name, meta in github_meta.items() if meta and meta["archived"])
+    if archived:
+        print("[GitHub] Archived repos (written with enabled: false):")
+        for name in archived:
+            print(f"  - {name}")
     if missing:
         print("[Build Page] Missing GitHub URL for:")
         for resource_id in missing:
@@ -0,0 +1,1085 @@
+//! Post-enrichment analysis passes
+//!
+//! This module derives cross-finding insights (e.g. version skew between
+//! self-hosted images and hosted functions) from enriched scan results, and
+//! compares a report against a baseline.
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::Path;
+
+use crate::models::{
+    AggregatedLocalNim, DetectorStats, DirectoryCount, EnrichmentError, Framework, FrameworkUsage, HostedNimMatch, LocalNimMatch, NimFindings, NimIdentityUsage, NimKindUsage, PossibleRetirement,
+    RepoHotspots, ReportDelta, Resolution, ResolutionCounts, ScanReport, TagUsage, VersionSkew, VersionSpread,
+};
+use crate::scanner::canonicalize_url_model;
+
+// ============================================================================
+// Image Reference Helpers
+// ============================================================================
+
+/// Split an image reference into (repository path, tag)
+///
+/// Digests (`@sha256:...`) are dropped and yield no tag.
+///
+/// Examples:
+/// - nvcr.io/nim/meta/llama-3.3-70b-instruct:1.1 -> ("nvcr.io/nim/meta/llama-3.3-70b-instruct", Some("1.1"))
+/// - nvcr.io/nim/meta/llama-3.3-70b-instruct@sha256:abc -> ("nvcr.io/nim/meta/llama-3.3-70b-instruct", None)
+pub fn split_image_ref(image: &str) -> (&str, Option<&str>) {
+    let image = image.split('@').next().unwrap_or(image);
+    let last_slash = image.rfind('/').map(|i| i + 1).unwrap_or(0);
+    match image[last_slash..].rfind(':') {
+        Some(i) => (&image[..last_slash + i], Some(&image[last_slash + i + 1..])),
+        None => (image, None),
+    }
+}
+
+/// Last path component of an image reference, without tag (the "image name")
+pub fn image_name(image: &str) -> &str {
+    let (path, _) = split_image_ref(image);
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+// ============================================================================
+// Version Comparison
+// ============================================================================
+
+/// Compare two version tags leniently
+///
+/// Accepts an optional `v` prefix and any number of dot-separated numeric
+/// components (`1.3`, `v1.10.0`, `2`); missing components count as zero.
+/// A `-suffix` (e.g. `-rc1`) ranks below the same version without suffix.
+/// Returns None when either tag isn't version-like (e.g. `latest`).
+pub fn compare_versions(a: &str, b: &str) -> Option<Ordering> {
+    let (a_nums, a_pre) = parse_version(a)?;
+    let (b_nums, b_pre) = parse_version(b)?;
+
+    let len = a_nums.len().max(b_nums.len());
+    for i in 0..len {
+        let x = a_nums.get(i).copied().unwrap_or(0);
+        let y = b_nums.get(i).copied().unwrap_or(0);
+        match x.cmp(&y) {
+            Ordering::Equal => continue,
+            other => return Some(other),
+        }
+    }
+
+    Some(match (a_pre, b_pre) {
+        (None, None) => Ordering::Equal,
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(x), Some(y)) => x.cmp(y),
+    })
+}
+
+fn parse_version(tag: &str) -> Option<(Vec<u64>, Option<&str>)> {
+    let tag = tag.trim();
+    let tag = tag.strip_prefix('v').or_else(|| tag.strip_prefix('V')).unwrap_or(tag);
+    let (core, pre) = match tag.split_once('-') {
+        Some((core, pre)) => (core, Some(pre)),
+        None => (tag, None),
+    };
+    if core.is_empty() {
+        return None;
+    }
+    let nums = core
+        .split('.')
+        .map(|part| part.parse::<u64>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    Some((nums, pre))
+}
+
+// ============================================================================
+// Version Skew
+// ============================================================================
+
+/// Pair Local NIM images with Hosted NIM findings in the same repository whose
+/// enriched container_image has the same image name, and compare their tags.
+///
+/// Local tags of `latest` use the resolved tag when enrichment provided one.
+pub fn compute_version_skew(source_code: &NimFindings, actions_workflow: &NimFindings) -> Vec<VersionSkew> {
+    // (repository, local image_url, local tag)
+    let mut locals: BTreeSet<(&str, &str, &str)> = BTreeSet::new();
+    for m in source_code.local_nim.iter().chain(&actions_workflow.local_nim) {
+        let tag = match m.resolved_tag.as_deref() {
+            Some(resolved) if m.tag == "latest" => resolved,
+            _ => m.tag.as_str(),
+        };
+        locals.insert((&m.repository, &m.image_url, tag));
+    }
+
+    // (repository, model name, container image)
+    let mut hosted: BTreeSet<(&str, &str, &str)> = BTreeSet::new();
+    for m in source_code.hosted_nim.iter().chain(&actions_workflow.hosted_nim) {
+        if let Some(ref container_image) = m.container_image {
+            hosted.insert((&m.repository, m.model_name.as_deref().unwrap_or(""), container_image));
+        }
+    }
+
+    let mut skew = Vec::new();
+    for &(repo, image_url, local_tag) in &locals {
+        for &(hosted_repo, model_name, container_image) in &hosted {
+            if hosted_repo != repo || image_name(container_image) != image_name(image_url) {
+                continue;
+            }
+            let hosted_tag = split_image_ref(container_image).1;
+            let hosted_uses_newer = hosted_tag
+                .and_then(|h| compare_versions(h, local_tag))
+                .map(|ord| ord == Ordering::Greater);
+
+            skew.push(VersionSkew {
+                repository: repo.to_string(),
+                image_name: image_name(image_url).to_string(),
+                local_image: image_url.to_string(),
+                local_tag: local_tag.to_string(),
+                hosted_model: (!model_name.is_empty()).then(|| model_name.to_string()),
+                hosted_container_image: container_image.to_string(),
+                hosted_tag: hosted_tag.map(|t| t.to_string()),
+                hosted_uses_newer,
+            });
+        }
+    }
+
+    skew
+}
+
+// ============================================================================
+// Baseline Delta
+// ============================================================================
+
+/// Tags seen per Local NIM image across both categories
+fn local_image_tags(report: &ScanReport) -> BTreeMap<&str, BTreeSet<&str>> {
+    let mut images: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    for m in report.source_code.local_nim.iter().chain(&report.actions_workflow.local_nim) {
+        images.entry(&m.image_url).or_default().insert(&m.tag);
+    }
+    images
+}
+
+/// Hosted NIM model names across both categories
+fn hosted_models(report: &ScanReport) -> BTreeSet<&str> {
+    report.source_code.hosted_nim.iter()
+        .chain(&report.actions_workflow.hosted_nim)
+        .filter_map(|m| m.model_name.as_deref())
+        .collect()
+}
+
+/// Fingerprints of all findings, recomputed so baselines written before
+/// fingerprints (or with an older fingerprint version) compare correctly
+fn fingerprints(report: &ScanReport) -> HashSet<String> {
+    let local = report.source_code.local_nim.iter()
+        .chain(&report.actions_workflow.local_nim)
+        .map(LocalNimMatch::fingerprint);
+    let hosted = report.source_code.hosted_nim.iter()
+        .chain(&report.actions_workflow.hosted_nim)
+        .map(HostedNimMatch::fingerprint);
+    local.chain(hosted).collect()
+}
+
+/// Repositories with at least one Hosted NIM finding
+fn hosted_repos(report: &ScanReport) -> BTreeSet<&str> {
+    report.source_code.hosted_nim.iter()
+        .chain(&report.actions_workflow.hosted_nim)
+        .map(|m| m.repository.as_str())
+        .collect()
+}
+
+fn sorted_difference(a: &BTreeSet<&str>, b: &BTreeSet<&str>) -> Vec<String> {
+    a.difference(b).map(|s| s.to_string()).collect()
+}
+
+/// Compare a report against a baseline: new/removed Local NIM images, tag
+/// changes on images present in both, new/removed Hosted NIM models and
+/// repositories that started using Hosted NIMs, plus how many individual
+/// findings (by fingerprint) appeared and disappeared
+pub fn compute_delta(baseline: &ScanReport, current: &ScanReport) -> ReportDelta {
+    let old_images = local_image_tags(baseline);
+    let new_images = local_image_tags(current);
+    let old_image_names: BTreeSet<&str> = old_images.keys().copied().collect();
+    let new_image_names: BTreeSet<&str> = new_images.keys().copied().collect();
+
+    let local_tag_changes = new_images
+        .iter()
+        .filter_map(|(image, tags)| {
+            let old_tags = old_images.get(image)?;
+            (old_tags != tags).then(|| {
+                let join = |t: &BTreeSet<&str>| t.iter().copied().collect::<Vec<_>>().join(", ");
+                format!("{}: {} -> {}", image, join(old_tags), join(tags))
+            })
+        })
+        .collect();
+    let old_fingerprints = fingerprints(baseline);
+    let new_fingerprints = fingerprints(current);
+
+    ReportDelta {
+        baseline_scan_time: baseline.scan_time.clone(),
+        new_local_images: sorted_difference(&new_image_names, &old_image_names),
+        removed_local_images: sorted_difference(&old_image_names, &new_image_names),
+        local_tag_changes,
+        new_hosted_models: sorted_difference(&hosted_models(current), &hosted_models(baseline)),
+        removed_hosted_models: sorted_difference(&hosted_models(baseline), &hosted_models(current)),
+        new_hosted_repos: sorted_difference(&hosted_repos(current), &hosted_repos(baseline)),
+        new_findings: new_fingerprints.difference(&old_fingerprints).count(),
+        removed_findings: old_fingerprints.difference(&new_fingerprints).count(),
+    }
+}
+
+// ============================================================================
+// Hosted NIM Resolution
+// ============================================================================
+
+/// Classify aggregated Hosted NIMs by whether enrichment found their NVCF function
+///
+/// Only meaningful after enrichment ran. An entry is `resolved` when it has a
+/// function and no finding came back not-found. A not-found entry is
+/// `newly_unresolved` when the baseline resolved the same key (it is also listed
+/// in `possible_retirements`), `never_resolved` otherwise. Entries enrichment
+/// didn't look up (staging, failed requests) stay unclassified.
+pub fn classify_resolution(report: &mut ScanReport, baseline: Option<&ScanReport>) {
+    let not_found: HashSet<String> = report.source_code.hosted_nim.iter()
+        .chain(&report.actions_workflow.hosted_nim)
+        .filter(|m| m.enrichment_error == Some(EnrichmentError::NotFound))
+        .map(HostedNimMatch::aggregation_key)
+        .collect();
+    let baseline_functions: HashMap<&str, &str> = baseline
+        .map(|b| b.aggregated.hosted_nim.iter()
+            .filter_map(|e| Some((e.aggregation_key()?, e.function_id.as_deref()?)))
+            .collect())
+        .unwrap_or_default();
+
+    let mut counts = ResolutionCounts::default();
+    let mut retirements = Vec::new();
+    for entry in &mut report.aggregated.hosted_nim {
+        let key = match entry.aggregation_key() {
+            Some(k) => k.to_string(),
+            None => continue,
+        };
+        entry.resolution = if not_found.contains(&key) {
+            match baseline_functions.get(key.as_str()) {
+                Some(function_id) => {
+                    retirements.push(PossibleRetirement {
+                        model: key,
+                        baseline_function_id: function_id.to_string(),
+                        locations: entry.locations.clone(),
+                    });
+                    Some(Resolution::NewlyUnresolved)
+                }
+                None => Some(Resolution::NeverResolved),
+            }
+        } else if entry.function_id.is_some() {
+            Some(Resolution::Resolved)
+        } else {
+            None
+        };
+        match entry.resolution {
+            Some(Resolution::Resolved) => counts.resolved += 1,
+            Some(Resolution::NeverResolved) => counts.never_resolved += 1,
+            Some(Resolution::NewlyUnresolved) => counts.newly_unresolved += 1,
+            None => {}
+        }
+    }
+
+    retirements.sort_by(|a, b| a.model.cmp(&b.model));
+    report.possible_retirements = retirements;
+    report.summary.resolution = counts;
+}
+
+// ============================================================================
+// Detector Effectiveness
+// ============================================================================
+
+/// File extension a finding is bucketed under (lowercase, `none` without one)
+fn extension_bucket(file_path: &str) -> String {
+    Path::new(file_path)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_else(|| "none".to_string())
+}
+
+/// Count findings per detection rule (and per file extension) by enrichment outcome
+///
+/// Rules whose findings mostly fail enrichment are likely producing false
+/// positives. Without an API key every finding counts as `not_attempted`.
+pub fn compute_detector_stats(
+    source_code: &NimFindings,
+    actions_workflow: &NimFindings,
+) -> BTreeMap<String, DetectorStats> {
+    let local = source_code.local_nim.iter()
+        .chain(&actions_workflow.local_nim)
+        .map(|m| (&m.detection_rule, &m.file_path, m.enrichment_outcome()));
+    let hosted = source_code.hosted_nim.iter()
+        .chain(&actions_workflow.hosted_nim)
+        .map(|m| (&m.detection_rule, &m.file_path, m.enrichment_outcome()));
+
+    let mut stats: BTreeMap<String, DetectorStats> = BTreeMap::new();
+    for (rule, file_path, outcome) in local.chain(hosted) {
+        let entry = stats.entry(rule.clone()).or_default();
+        entry.counts.record(outcome);
+        entry.by_extension.entry(extension_bucket(file_path)).or_default().record(outcome);
+    }
+    stats
+}
+
+// ============================================================================
+// Framework Adoption
+// ============================================================================
+
+/// Group Hosted NIM findings by the framework calling them
+///
+/// Each framework lists the models called through it and counts the
+/// repositories using it, most repositories first. Findings whose framework is
+/// `unknown` are left out.
+pub fn compute_framework_breakdown(
+    source_code: &NimFindings,
+    actions_workflow: &NimFindings,
+) -> Vec<FrameworkUsage> {
+    let mut groups: BTreeMap<Framework, (BTreeSet<&str>, BTreeSet<&str>, usize)> = BTreeMap::new();
+    for m in source_code.hosted_nim.iter().chain(&actions_workflow.hosted_nim) {
+        if m.framework == Framework::Unknown {
+            continue;
+        }
+        let (models, repos, findings) = groups.entry(m.framework).or_default();
+        models.extend(m.model_name.as_deref());
+        repos.insert(&m.repository);
+        *findings += 1;
+    }
+
+    let mut breakdown: Vec<FrameworkUsage> = groups
+        .into_iter()
+        .map(|(framework, (models, repos, findings))| FrameworkUsage {
+            framework,
+            models: models.into_iter().map(str::to_string).collect(),
+            repo_count: repos.len(),
+            findings,
+        })
+        .collect();
+    breakdown.sort_by(|a, b| b.repo_count.cmp(&a.repo_count).then(a.framework.cmp(&b.framework)));
+    breakdown
+}
+
+// ============================================================================
+// Version Spread
+// ============================================================================
+
+/// An image with more tags than this in use at once is `fragmented`
+pub const FRAGMENTED_TAG_COUNT: usize = 3;
+
+/// Tags in use per Local NIM image (tag ignored), with the repositories using each
+///
+/// Tags are taken as written, so `latest` counts as a tag of its own. Images
+/// are ordered by distinct tags, then repositories, most first, then by URL;
+/// the tags of an image by repositories, most first, then by tag.
+pub fn compute_version_spread(local_nim: &[AggregatedLocalNim]) -> Vec<VersionSpread> {
+    // image -> tag -> repositories
+    let mut images: BTreeMap<&str, BTreeMap<&str, BTreeSet<&str>>> = BTreeMap::new();
+    for entry in local_nim {
+        let repos = images.entry(&entry.image_url).or_default().entry(&entry.tag).or_default();
+        repos.extend(entry.locations.iter().map(|l| l.repository.as_str()));
+    }
+
+    let mut spread: Vec<VersionSpread> = images
+        .into_iter()
+        .map(|(image_url, tags)| {
+            let repo_count = tags.values().flatten().collect::<BTreeSet<_>>().len();
+            let mut tags: Vec<TagUsage> = tags
+                .into_iter()
+                .map(|(tag, repos)| TagUsage { tag: tag.to_string(), repo_count: repos.len() })
+                .collect();
+            tags.sort_by(|a, b| b.repo_count.cmp(&a.repo_count).then_with(|| a.tag.cmp(&b.tag)));
+            VersionSpread {
+                image_url: image_url.to_string(),
+                distinct_tags: tags.len(),
+                fragmented: tags.len() > FRAGMENTED_TAG_COUNT,
+                repo_count,
+                tags,
+            }
+        })
+        .collect();
+    spread.sort_by(|a, b| {
+        b.distinct_tags.cmp(&a.distinct_tags)
+            .then(b.repo_count.cmp(&a.repo_count))
+            .then_with(|| a.image_url.cmp(&b.image_url))
+    });
+    spread
+}
+
+// ============================================================================
+// Directory Hotspots
+// ============================================================================
+
+/// Top- and second-level directories of a repository-relative file path
+/// (`.` alone for a file at the root)
+fn rollup_directories(file_path: &str) -> Vec<String> {
+    let normalized = file_path.replace('\\', "/");
+    let mut dirs: Vec<&str> = normalized.split('/').filter(|c| !c.is_empty() && *c != ".").collect();
+    dirs.pop();
+    match dirs.as_slice() {
+        [] => vec![".".to_string()],
+        [top] => vec![top.to_string()],
+        [top, second, ..] => vec![top.to_string(), format!("{}/{}", top, second)],
+    }
+}
+
+/// Count each repository's findings per top- and second-level directory
+///
+/// Only `file_path` is used, so the rollup can be rebuilt from any report.
+/// A finding counts once in its top-level directory and once in its
+/// second-level one; files at the repository root go to `.`.
+pub fn compute_hotspots(source_code: &NimFindings, actions_workflow: &NimFindings) -> Vec<RepoHotspots> {
+    let mut counts: BTreeMap<&str, BTreeMap<String, (usize, usize)>> = BTreeMap::new();
+    let local = source_code.local_nim.iter().chain(&actions_workflow.local_nim).map(|m| (&m.repository, &m.file_path, true));
+    let hosted = source_code.hosted_nim.iter().chain(&actions_workflow.hosted_nim).map(|m| (&m.repository, &m.file_path, false));
+    for (repository, file_path, is_local) in local.chain(hosted) {
+        let dirs = counts.entry(repository).or_default();
+        for dir in rollup_directories(file_path) {
+            let (local_count, hosted_count) = dirs.entry(dir).or_default();
+            match is_local {
+                true => *local_count += 1,
+                false => *hosted_count += 1,
+            }
+        }
+    }
+    counts
+        .into_iter()
+        .map(|(repository, dirs)| RepoHotspots {
+            repository: repository.to_string(),
+            directories: dirs
+                .into_iter()
+                .map(|(directory, (local_count, hosted_count))| DirectoryCount { directory, local_count, hosted_count })
+                .collect(),
+        })
+        .collect()
+}
+
+// ============================================================================
+// NIM Identity
+// ============================================================================
+
+/// Registry prefix of NIM images; what follows it is the NIM identity
+const NIM_IMAGE_PREFIX: &str = "nvcr.io/nim/";
+
+/// NIM identity of a Local NIM image: the path after `nvcr.io/nim/`, lowercase,
+/// without tag, spelled like URL model names (`llama-3_1` is `llama-3.1`)
+///
+/// Examples:
+/// - nvcr.io/nim/meta/llama-3.1-70b-instruct:1.3 -> meta/llama-3.1-70b-instruct
+/// - nvcr.io/nim/nvidia/nemo-retriever/embedqa -> nvidia/nemo-retriever/embedqa
+pub fn nim_identity_for_image(image_url: &str) -> String {
+    let (path, _) = split_image_ref(image_url.trim());
+    let path = path.to_ascii_lowercase();
+    canonicalize_url_model(path.strip_prefix(NIM_IMAGE_PREFIX).unwrap_or(&path))
+}
+
+/// NIM identity of a Hosted NIM model name (normalized like the model policy: lowercase, no `stg/`)
+pub fn nim_identity_for_model(model_name: &str) -> String {
+    canonicalize_url_model(&crate::policy::normalize_model_name(model_name))
+}
+
+/// Record each finding's NIM identity (Hosted NIMs without a model name have none)
+pub fn assign_nim_identities(findings: &mut NimFindings) {
+    for m in &mut findings.local_nim {
+        m.nim_identity = nim_identity_for_image(&m.image_url);
+    }
+    for m in &mut findings.hosted_nim {
+        m.nim_identity = m.model_name.as_deref().map(nim_identity_for_model);
+    }
+}
+
+/// Distinct repositories and spellings seen for one usage kind of an identity
+#[derive(Default)]
+struct KindTally<'a> {
+    references: usize,
+    repositories: BTreeSet<&'a str>,
+    variants: BTreeSet<String>,
+}
+
+impl<'a> KindTally<'a> {
+    fn record(&mut self, repository: &'a str, variant: String) {
+        self.references += 1;
+        self.repositories.insert(repository);
+        self.variants.insert(variant);
+    }
+
+    fn into_usage(self) -> Option<NimKindUsage> {
+        (self.references > 0).then(|| NimKindUsage {
+            references: self.references,
+            repositories: self.repositories.len(),
+            variants: self.variants.into_iter().collect(),
+        })
+    }
+}
+
+/// Group local and hosted findings by NIM identity, sorted by identity
+///
+/// A team that self-hosts `nvcr.io/nim/meta/llama-3.1-70b-instruct` and calls
+/// `meta/llama-3.1-70b-instruct` on the API gets one entry with both kinds.
+pub fn compute_nim_usage(source_code: &NimFindings, actions_workflow: &NimFindings) -> Vec<NimIdentityUsage> {
+    let mut tallies: BTreeMap<String, (KindTally, KindTally)> = BTreeMap::new();
+    for m in source_code.local_nim.iter().chain(&actions_workflow.local_nim) {
+        tallies.entry(nim_identity_for_image(&m.image_url))
+            .or_default()
+            .0
+            .record(&m.repository, format!("{}:{}", m.image_url, m.tag));
+    }
+    for m in source_code.hosted_nim.iter().chain(&actions_workflow.hosted_nim) {
+        if let Some(model) = m.model_name.as_deref() {
+            tallies.entry(nim_identity_for_model(model))
+                .or_default()
+                .1
+                .record(&m.repository, model.to_string());
+        }
+    }
+
+    tallies.into_iter()
+        .map(|(nim_identity, (local, hosted))| {
+            let repositories: BTreeSet<&str> = local.repositories.union(&hosted.repositories).copied().collect();
+            NimIdentityUsage {
+                nim_identity,
+                repositories: repositories.into_iter().map(str::to_string).collect(),
+                local: local.into_usage(),
+                hosted: hosted.into_usage(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Environment, FileKind, HostedNimMatch, InvokeStyle, LocalIntent, LocalNimMatch, OutcomeCounts, UsageKind};
+
+    #[test]
+    fn test_split_image_ref() {
+        assert_eq!(
+            split_image_ref("nvcr.io/nim/meta/llama-3.3-70b-instruct:1.1"),
+            ("nvcr.io/nim/meta/llama-3.3-70b-instruct", Some("1.1"))
+        );
+        assert_eq!(
+            split_image_ref("localhost:5000/nim/model"),
+            ("localhost:5000/nim/model", None)
+        );
+        assert_eq!(
+            split_image_ref("nvcr.io/nim/meta/model@sha256:abcd"),
+            ("nvcr.io/nim/meta/model", None)
+        );
+        assert_eq!(image_name("nvcr.io/qc69jvmznzxy/meta/llama-3.3-70b-instruct:1.3"), "llama-3.3-70b-instruct");
+    }
+
+    #[test]
+    fn test_compare_versions() {
+        assert_eq!(compare_versions("1.3", "1.1"), Some(Ordering::Greater));
+        assert_eq!(compare_versions("1.10.0", "1.9"), Some(Ordering::Greater));
+        assert_eq!(compare_versions("v1.2", "1.2.0"), Some(Ordering::Equal));
+        assert_eq!(compare_versions("1.2.0-rc1", "1.2.0"), Some(Ordering::Less));
+        assert_eq!(compare_versions("latest", "1.0"), None);
+        assert_eq!(compare_versions("1.x", "1.0"), None);
+    }
+
+    #[test]
+    fn test_compute_version_skew() {
+        let source_code = NimFindings {
+            local_nim: vec![LocalNimMatch {
+                repository: "test/repo".to_string(),
+                image_url: "nvcr.io/nim/meta/llama-3.3-70b-instruct".to_string(),
+                tag: "1.1".to_string(),
+                resolved_tag: None,
+                tag_exists: None,
+                file_path: "docker-compose.yaml".to_string(),
+                line_number: 4,
+                match_context: "image: nvcr.io/nim/meta/llama-3.3-70b-instruct:1.1".to_string(),
+                policy: None,
+                enrichment_error: None,
+                enriched: false,
+                detection_rule: String::new(),
+                workflow_triggers: Vec::new(),
+                nim_identity: String::new(),
+                enrichment_skipped: None,
+                context_truncated: None,
+                compose: None,
+                intent: LocalIntent::Reference,
+                usage_kind: UsageKind::Other,
+                blame: None,
+                notebook_cell: None,
+                file_kind: FileKind::Source,
+                resolved_from: None,
+                fixture: false,
+                fingerprint: String::new(),
+            }],
+            hosted_nim: vec![
+                HostedNimMatch {
+                    repository: "test/repo".to_string(),
+                    endpoint_url: None,
+                    model_name: Some("meta/llama-3.3-70b-instruct".to_string()),
+                    file_path: "client.py".to_string(),
+                    line_number: 8,
+                    match_context: r#"model="meta/llama-3.3-70b-instruct""#.to_string(),
+                    function_id: Some("fn-1".to_string()),
+                    status: Some("ACTIVE".to_string()),
+                    container_image: Some("nvcr.io/qc69jvmznzxy/meta/llama-3.3-70b-instruct:1.3".to_string()),
+                    environment: Environment::Production,
+                    endpoint_host: None,
+                    invoke_style: InvokeStyle::Unknown,
+                    framework: Framework::Unknown,
+                    call_params: None,
+                    policy: None,
+                    model_policy: None,
+                    enrichment_error: None,
+                    enriched: false,
+                    detection_rule: String::new(),
+                    workflow_triggers: Vec::new(),
+                    nim_identity: None,
+                    enrichment_skipped: None,
+                    browser_exposed: false,
+                    did_you_mean: Vec::new(),
+                    credential_source: None,
+                    context_truncated: None,
+                    canonical_endpoint: None,
+                    endpoint_mismatch: false,
+                    endpoint_status: None,
+                    suggested_endpoint: None,
+                    blame: None,
+                    notebook_cell: None,
+                    file_kind: FileKind::Source,
+                    resolved_from: None,
+                    fixture: false,
+                    fingerprint: String::new(),
+                },
+                // Different repository: must not pair
+                HostedNimMatch {
+                    repository: "other/repo".to_string(),
+                    endpoint_url: None,
+                    model_name: Some("meta/llama-3.3-70b-instruct".to_string()),
+                    file_path: "client.py".to_string(),
+                    line_number: 8,
+                    match_context: r#"model="meta/llama-3.3-70b-instruct""#.to_string(),
+                    function_id: Some("fn-1".to_string()),
+                    status: Some("ACTIVE".to_string()),
+                    container_image: Some("nvcr.io/qc69jvmznzxy/meta/llama-3.3-70b-instruct:1.3".to_string()),
+                    environment: Environment::Production,
+                    endpoint_host: None,
+                    invoke_style: InvokeStyle::Unknown,
+                    framework: Framework::Unknown,
+                    call_params: None,
+                    policy: None,
+                    model_policy: None,
+                    enrichment_error: None,
+                    enriched: false,
+                    detection_rule: String::new(),
+                    workflow_triggers: Vec::new(),
+                    nim_identity: None,
+                    enrichment_skipped: None,
+                    browser_exposed: false,
+                    did_you_mean: Vec::new(),
+                    credential_source: None,
+                    context_truncated: None,
+                    canonical_endpoint: None,
+                    endpoint_mismatch: false,
+                    endpoint_status: None,
+                    suggested_endpoint: None,
+                    blame: None,
+                    notebook_cell: None,
+                    file_kind: FileKind::Source,
+                    resolved_from: None,
+                    fixture: false,
+                    fingerprint: String::new(),
+                },
+            ],
+            sdk_dependencies: vec![],
+        };
+
+        let skew = compute_version_skew(&source_code, &NimFindings::default());
+        assert_eq!(skew.len(), 1);
+        assert_eq!(skew[0].repository, "test/repo");
+        assert_eq!(skew[0].local_tag, "1.1");
+        assert_eq!(skew[0].hosted_tag.as_deref(), Some("1.3"));
+        assert_eq!(skew[0].hosted_uses_newer, Some(true));
+    }
+
+    fn local(repository: &str, image: &str, tag: &str) -> LocalNimMatch {
+        LocalNimMatch {
+            repository: repository.to_string(),
+            image_url: format!("nvcr.io/nim/{}", image),
+            tag: tag.to_string(),
+            resolved_tag: None,
+            tag_exists: None,
+            file_path: "docker-compose.yaml".to_string(),
+            line_number: 1,
+            match_context: String::new(),
+            policy: None,
+            enrichment_error: None,
+            enriched: false,
+            detection_rule: String::new(),
+            workflow_triggers: Vec::new(),
+            nim_identity: String::new(),
+            enrichment_skipped: None,
+            context_truncated: None,
+            compose: None,
+            intent: LocalIntent::Reference,
+            usage_kind: UsageKind::Other,
+            blame: None,
+            notebook_cell: None,
+            file_kind: FileKind::Source,
+            resolved_from: None,
+            fixture: false,
+            fingerprint: String::new(),
+        }
+    }
+
+    fn hosted(repository: &str, model: &str) -> HostedNimMatch {
+        HostedNimMatch {
+            repository: repository.to_string(),
+            endpoint_url: None,
+            model_name: Some(model.to_string()),
+            file_path: "client.py".to_string(),
+            line_number: 1,
+            match_context: String::new(),
+            function_id: None,
+            status: None,
+            container_image: None,
+            environment: Environment::Production,
+            endpoint_host: None,
+            invoke_style: InvokeStyle::Unknown,
+            framework: Framework::Unknown,
+            call_params: None,
+            policy: None,
+            model_policy: None,
+            enrichment_error: None,
+            enriched: false,
+            detection_rule: String::new(),
+            workflow_triggers: Vec::new(),
+            nim_identity: None,
+            enrichment_skipped: None,
+            browser_exposed: false,
+            did_you_mean: Vec::new(),
+            credential_source: None,
+            context_truncated: None,
+            canonical_endpoint: None,
+            endpoint_mismatch: false,
+            endpoint_status: None,
+            suggested_endpoint: None,
+            blame: None,
+            notebook_cell: None,
+            file_kind: FileKind::Source,
+            resolved_from: None,
+            fixture: false,
+            fingerprint: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_compute_delta() {
+        let baseline = ScanReport::new(
+            3,
+            NimFindings {
+                local_nim: vec![
+                    local("org/a", "meta/llama-3.1-8b-instruct", "1.2.0"),
+                    local("org/a", "nvidia/nv-embedqa-e5-v5", "1.0.0"),
+                    local("org/b", "nvidia/nv-rerankqa-mistral-4b-v3", "1.0.2"),
+                    local("org/b", "meta/llama-3.3-70b-instruct", "1.3"),
+                ],
+                hosted_nim: vec![hosted("org/a", "meta/llama-3.1-70b-instruct")],
+                sdk_dependencies: vec![],
+            },
+            NimFindings::default(),
+        );
+        let current = ScanReport::new(
+            3,
+            NimFindings {
+                local_nim: vec![
+                    local("org/a", "meta/llama-3.1-8b-instruct", "1.3.0"),
+                    local("org/a", "nvidia/nv-embedqa-e5-v5", "1.1.0"),
+                    local("org/b", "nvidia/nv-rerankqa-mistral-4b-v3", "1.0.2"),
+                    local("org/c", "nvidia/llama-3.2-nv-embedqa-1b-v2", "1.5.0"),
+                    local("org/c", "mistralai/mixtral-8x7b-instruct-v0.1", "1.0"),
+                ],
+                hosted_nim: vec![hosted("org/a", "meta/llama-3.1-70b-instruct")],
+                sdk_dependencies: vec![],
+            },
+            NimFindings {
+                local_nim: vec![local("org/b", "meta/llama-3.3-70b-instruct", "1.3")],
+                hosted_nim: vec![hosted("org/c", "nvidia/llama-3.3-nemotron-super-49b-v1")],
+                sdk_dependencies: vec![],
+            },
+        );
+
+        let delta = compute_delta(&baseline, &current);
+        assert_eq!(delta.baseline_scan_time, baseline.scan_time);
+        assert_eq!(delta.new_local_images, vec![
+            "nvcr.io/nim/mistralai/mixtral-8x7b-instruct-v0.1",
+            "nvcr.io/nim/nvidia/llama-3.2-nv-embedqa-1b-v2",
+        ]);
+        assert!(delta.removed_local_images.is_empty());
+        assert_eq!(delta.local_tag_changes, vec![
+            "nvcr.io/nim/meta/llama-3.1-8b-instruct: 1.2.0 -> 1.3.0",
+            "nvcr.io/nim/nvidia/nv-embedqa-e5-v5: 1.0.0 -> 1.1.0",
+        ]);
+        assert_eq!(delta.new_hosted_models, vec!["nvidia/llama-3.3-nemotron-super-49b-v1"]);
+        assert!(delta.removed_hosted_models.is_empty());
+        assert_eq!(delta.new_hosted_repos, vec!["org/c"]);
+        // Two retagged and two new images, one new hosted model; the image
+        // that moved to a workflow keeps its file path and fingerprint
+        assert_eq!((delta.new_findings, delta.removed_findings), (5, 2));
+
+        // Reversed, additions become removals
+        let reverse = compute_delta(&current, &baseline);
+        assert_eq!(reverse.removed_local_images, delta.new_local_images);
+        assert_eq!(reverse.removed_hosted_models, delta.new_hosted_models);
+        assert!(reverse.new_hosted_repos.is_empty());
+        assert_eq!((reverse.new_findings, reverse.removed_findings), (2, 5));
+        assert_eq!(reverse.local_tag_changes[0], "nvcr.io/nim/meta/llama-3.1-8b-instruct: 1.3.0 -> 1.2.0");
+    }
+
+    #[test]
+    fn test_compute_detector_stats() {
+        let finding = |rule: &str, file_path: &str, enriched: bool, error: Option<EnrichmentError>| {
+            let mut m = hosted("org/a", "meta/llama-3.1-8b-instruct");
+            m.detection_rule = rule.to_string();
+            m.file_path = file_path.to_string();
+            m.enriched = enriched;
+            m.enrichment_error = error;
+            m
+        };
+        let mut tagged = local("org/a", "meta/llama-3.1-8b-instruct", "latest");
+        tagged.detection_rule = "local_nim_full".to_string();
+        tagged.enriched = true;
+
+        let source_code = NimFindings {
+            local_nim: vec![tagged],
+            hosted_nim: vec![
+                finding("chatnvidia", "app/chain.py", true, None),
+                finding("chatnvidia", "app/llm.py", false, Some(EnrichmentError::Failed)),
+                finding("doc_prose_org_model", "README.md", false, Some(EnrichmentError::NotFound)),
+                finding("doc_prose_org_model", "docs/Guide.MD", false, Some(EnrichmentError::NotFound)),
+            ],
+            sdk_dependencies: vec![],
+        };
+        let actions_workflow = NimFindings {
+            local_nim: Vec::new(),
+            hosted_nim: vec![
+                finding("chatnvidia", ".github/workflows/nightly.yml", false, None),
+                finding("model_assign", "Makefile", true, None),
+            ],
+            sdk_dependencies: vec![],
+        };
+
+        let stats = compute_detector_stats(&source_code, &actions_workflow);
+        assert_eq!(
+            stats.keys().map(String::as_str).collect::<Vec<_>>(),
+            vec!["chatnvidia", "doc_prose_org_model", "local_nim_full", "model_assign"]
+        );
+
+        let chatnvidia = &stats["chatnvidia"];
+        assert_eq!(chatnvidia.counts, OutcomeCounts { total: 3, enriched: 1, not_attempted: 1, failed: 1 });
+        assert_eq!(chatnvidia.by_extension["py"], OutcomeCounts { total: 2, enriched: 1, not_attempted: 0, failed: 1 });
+        assert_eq!(chatnvidia.by_extension["yml"].not_attempted, 1);
+
+        // Extensions are lowercased; files without one are bucketed as `none`
+        let prose = &stats["doc_prose_org_model"];
+        assert_eq!(prose.counts.failed, 2);
+        assert_eq!(prose.by_extension.len(), 1);
+        assert_eq!(prose.by_extension["md"].total, 2);
+        assert_eq!(stats["model_assign"].by_extension["none"].enriched, 1);
+        assert_eq!(stats["local_nim_full"].counts.enriched, 1);
+
+        // Carried on the report built from the same findings
+        let report = ScanReport::new(1, source_code, actions_workflow);
+        assert_eq!(report.detector_stats, stats);
+    }
+
+    #[test]
+    fn test_compute_framework_breakdown() {
+        let finding = |repository: &str, model: Option<&str>, framework: Framework| {
+            let mut m = hosted(repository, "");
+            m.model_name = model.map(str::to_string);
+            m.framework = framework;
+            m
+        };
+        let source_code = NimFindings {
+            local_nim: Vec::new(),
+            hosted_nim: vec![
+                finding("org/a", Some("meta/llama-3.1-8b-instruct"), Framework::Langchain),
+                finding("org/a", Some("meta/llama-3.1-8b-instruct"), Framework::Langchain),
+                finding("org/b", Some("nvidia/nv-embedqa-e5-v5"), Framework::Langchain),
+                finding("org/a", Some("meta/llama-3.1-8b-instruct"), Framework::OpenaiSdk),
+                finding("org/c", Some("mistralai/mixtral-8x7b-instruct-v0.1"), Framework::RawHttp),
+                finding("org/c", None, Framework::RawHttp),
+                finding("org/d", Some("meta/llama-3.1-70b-instruct"), Framework::Unknown),
+            ],
+            sdk_dependencies: vec![],
+        };
+        let actions_workflow = NimFindings {
+            local_nim: Vec::new(),
+            hosted_nim: vec![finding("org/e", Some("meta/llama-3.1-70b-instruct"), Framework::Curl)],
+            sdk_dependencies: vec![],
+        };
+
+        let breakdown = compute_framework_breakdown(&source_code, &actions_workflow);
+        let summary: Vec<(Framework, usize, usize)> = breakdown.iter().map(|u| (u.framework, u.repo_count, u.findings)).collect();
+        // Most repositories first, ties in framework order; `unknown` left out
+        assert_eq!(summary, vec![
+            (Framework::Langchain, 2, 3),
+            (Framework::OpenaiSdk, 1, 1),
+            (Framework::RawHttp, 1, 2),
+            (Framework::Curl, 1, 1),
+        ]);
+        assert_eq!(breakdown[0].models, vec!["meta/llama-3.1-8b-instruct", "nvidia/nv-embedqa-e5-v5"]);
+        // A finding without a model still counts, but lists no model
+        assert_eq!(breakdown[2].models, vec!["mistralai/mixtral-8x7b-instruct-v0.1"]);
+
+        let report = ScanReport::new(5, source_code, actions_workflow);
+        assert_eq!(report.framework_breakdown, breakdown);
+    }
+
+    #[test]
+    fn test_compute_version_spread() {
+        let mut findings = Vec::new();
+        // llama: five tags, 1.0.0 in three repositories (twice in repo-a)
+        for (repo, tag) in [
+            ("repo-a", "1.0.0"), ("repo-a", "1.0.0"), ("repo-b", "1.0.0"), ("repo-c", "1.0.0"),
+            ("repo-a", "1.3.3"), ("repo-d", "1.3.3"), ("repo-e", "latest"), ("repo-e", "1.1.0"), ("repo-f", "1.2.0"),
+        ] {
+            findings.push(local(repo, "meta/llama-3.1-8b-instruct", tag));
+        }
+        // embedder: two tags in one repository, rerank: one tag
+        findings.push(local("repo-a", "nvidia/nv-embedqa-e5-v5", "1.0.0"));
+        findings.push(local("repo-a", "nvidia/nv-embedqa-e5-v5", "1.1.0"));
+        findings.push(local("repo-b", "nvidia/nv-rerankqa-mistral-4b-v3", "1.0.2"));
+        let source_code = NimFindings { local_nim: findings, hosted_nim: vec![], sdk_dependencies: vec![] };
+        let aggregated = crate::models::AggregatedFindings::from_findings(&source_code, &NimFindings::default());
+
+        let spread = compute_version_spread(&aggregated.local_nim);
+        let images: Vec<(&str, usize, bool, usize)> = spread.iter()
+            .map(|s| (s.image_url.as_str(), s.distinct_tags, s.fragmented, s.repo_count))
+            .collect();
+        assert_eq!(images, vec![
+            ("nvcr.io/nim/meta/llama-3.1-8b-instruct", 5, true, 6),
+            ("nvcr.io/nim/nvidia/nv-embedqa-e5-v5", 2, false, 1),
+            ("nvcr.io/nim/nvidia/nv-rerankqa-mistral-4b-v3", 1, false, 1),
+        ]);
+        let tags: Vec<(&str, usize)> = spread[0].tags.iter().map(|t| (t.tag.as_str(), t.repo_count)).collect();
+        assert_eq!(tags, vec![("1.0.0", 3), ("1.3.3", 2), ("1.1.0", 1), ("1.2.0", 1), ("latest", 1)]);
+
+        // Same result whatever order the aggregates come in
+        let mut reversed = aggregated.local_nim.clone();
+        reversed.reverse();
+        assert_eq!(compute_version_spread(&reversed), spread);
+        assert!(compute_version_spread(&[]).is_empty());
+
+        let report = ScanReport::new(6, source_code, NimFindings::default());
+        assert_eq!(report.version_spread, spread);
+    }
+
+    #[test]
+    fn test_compute_hotspots() {
+        let at_local = |repository: &str, path: &str| {
+            let mut m = local(repository, "meta/llama-3.1-8b-instruct", "1.0.0");
+            m.file_path = path.to_string();
+            m
+        };
+        let at_hosted = |repository: &str, path: &str| {
+            let mut m = hosted(repository, "meta/llama-3.1-8b-instruct");
+            m.file_path = path.to_string();
+            m
+        };
+        let source_code = NimFindings {
+            local_nim: vec![
+                at_local("org/a", "deploy/helm/values.yaml"),
+                at_local("org/a", "deploy/helm/charts/nim/values.yaml"),
+                at_local("org/a", "deploy/compose.yaml"),
+                at_local("org/a", "docker-compose.yaml"),
+            ],
+            hosted_nim: vec![
+                at_hosted("org/b", "./client.py"),
+                at_hosted("org/a", "src\\app\\chat.py"),
+                at_hosted("org/a", "README.md"),
+            ],
+            sdk_dependencies: vec![],
+        };
+        let actions_workflow = NimFindings {
+            local_nim: vec![at_local("org/a", ".github/workflows/ci.yml")],
+            hosted_nim: Vec::new(),
+            sdk_dependencies: vec![],
+        };
+
+        let hotspots = compute_hotspots(&source_code, &actions_workflow);
+        let count = |directory: &str, local_count: usize, hosted_count: usize| DirectoryCount {
+            directory: directory.to_string(),
+            local_count,
+            hosted_count,
+        };
+        // Nested files count in their top- and second-level directories; root files in `.`
+        assert_eq!(hotspots, vec![
+            RepoHotspots {
+                repository: "org/a".to_string(),
+                directories: vec![
+                    count(".", 1, 1),
+                    count(".github", 1, 0),
+                    count(".github/workflows", 1, 0),
+                    count("deploy", 3, 0),
+                    count("deploy/helm", 2, 0),
+                    count("src", 0, 1),
+                    count("src/app", 0, 1),
+                ],
+            },
+            RepoHotspots { repository: "org/b".to_string(), directories: vec![count(".", 0, 1)] },
+        ]);
+        assert_eq!(ScanReport::new(2, source_code, actions_workflow).hotspots, hotspots);
+    }
+
+    #[test]
+    fn test_nim_identity() {
+        assert_eq!(nim_identity_for_image("nvcr.io/nim/meta/llama-3.1-70b-instruct:1.3"), "meta/llama-3.1-70b-instruct");
+        assert_eq!(nim_identity_for_image("NVCR.IO/nim/nvidia/nemo-retriever/embedqa@sha256:abcd"), "nvidia/nemo-retriever/embedqa");
+        assert_eq!(nim_identity_for_model("meta/llama-3.1-70b-instruct"), "meta/llama-3.1-70b-instruct");
+        assert_eq!(nim_identity_for_model("stg/Meta/Llama-3.1-70B-Instruct"), "meta/llama-3.1-70b-instruct");
+        assert_eq!(nim_identity_for_model("nvidia/llama-3_2-nv-rerankqa-1b-v2"), "nvidia/llama-3.2-nv-rerankqa-1b-v2");
+        assert_eq!(nim_identity_for_model("nvidia/nv_embed_v1"), "nvidia/nv_embed_v1");
+    }
+
+    #[test]
+    fn test_compute_nim_usage() {
+        let mut no_model = hosted("org/c", "unused");
+        no_model.model_name = None;
+        no_model.function_id = Some("fn-123".to_string());
+        let source_code = NimFindings {
+            local_nim: vec![
+                local("org/a", "meta/llama-3.1-70b-instruct", "1.3"),
+                local("org/b", "meta/llama-3.1-70b-instruct", "latest"),
+            ],
+            hosted_nim: vec![
+                hosted("org/a", "meta/llama-3.1-70b-instruct"),
+                hosted("org/a", "stg/meta/llama-3.1-70b-instruct"),
+                hosted("org/c", "nvidia/nv-embedqa-e5-v5"),
+                no_model,
+            ],
+            sdk_dependencies: vec![],
+        };
+        let report = ScanReport::new(3, source_code, NimFindings::default());
+
+        let by_nim = &report.aggregated.by_nim;
+        assert_eq!(
+            by_nim.iter().map(|u| u.nim_identity.as_str()).collect::<Vec<_>>(),
+            vec!["meta/llama-3.1-70b-instruct", "nvidia/nv-embedqa-e5-v5"]
+        );
+        let llama = &by_nim[0];
+        assert_eq!(llama.repositories, vec!["org/a", "org/b"]);
+        let local = llama.local.as_ref().unwrap();
+        assert_eq!((local.references, local.repositories), (2, 2));
+        assert_eq!(local.variants, vec![
+            "nvcr.io/nim/meta/llama-3.1-70b-instruct:1.3",
+            "nvcr.io/nim/meta/llama-3.1-70b-instruct:latest",
+        ]);
+        let hosted = llama.hosted.as_ref().unwrap();
+        assert_eq!((hosted.references, hosted.repositories), (2, 1));
+        assert_eq!(hosted.variants, vec!["meta/llama-3.1-70b-instruct", "stg/meta/llama-3.1-70b-instruct"]);
+
+        // Hosted-only model: no local usage
+        assert!(by_nim[1].local.is_none());
+        assert_eq!(by_nim[1].hosted.as_ref().unwrap().references, 1);
+
+        assert_eq!(report.summary.distinct_nims, 2);
+        assert_eq!(report.source_code.local_nim[0].nim_identity, "meta/llama-3.1-70b-instruct");
+        assert_eq!(report.source_code.hosted_nim[1].nim_identity.as_deref(), Some("meta/llama-3.1-70b-instruct"));
+        assert!(report.source_code.hosted_nim[3].nim_identity.is_none());
+    }
+}
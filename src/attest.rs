@@ -0,0 +1,226 @@
+//! Manifest of a scan's output files, for proving reports weren't altered
+//!
+//! After a scan has written its reports (and result.json), `MANIFEST.sha256`
+//! lists every artifact in the output directory with its SHA-256 digest, in
+//! the `sha256sum` format so `sha256sum -c` can check it too. With a signing
+//! key the manifest is signed with Ed25519 and the signature written next to
+//! it as `MANIFEST.sha256.sig` (hex). The manifest is written last and never
+//! lists itself or its signature. `verify-output` recomputes the digests and,
+//! given the public key, checks the signature.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use anyhow::{anyhow, bail, Context, Result};
+use ed25519_dalek::pkcs8::{DecodePrivateKey, DecodePublicKey};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use log::warn;
+use sha2::{Digest, Sha256};
+
+/// Manifest file name (before any --output-prefix)
+pub const MANIFEST_FILE: &str = "MANIFEST.sha256";
+
+/// Signature written next to a manifest (`MANIFEST.sha256.sig`)
+pub fn signature_path(manifest: &Path) -> PathBuf {
+    let mut name = manifest.as_os_str().to_owned();
+    name.push(".sig");
+    PathBuf::from(name)
+}
+
+/// Ed25519 private key from a PKCS#8 PEM file (`openssl genpkey -algorithm ed25519`)
+pub fn load_signing_key(path: &Path) -> Result<SigningKey> {
+    let pem = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read signing key: {}", path.display()))?;
+    SigningKey::from_pkcs8_pem(&pem)
+        .map_err(|e| anyhow!("Invalid Ed25519 signing key {}: {}", path.display(), e))
+}
+
+/// Ed25519 public key from a PEM file (`openssl pkey -in key.pem -pubout`)
+pub fn load_verifying_key(path: &Path) -> Result<VerifyingKey> {
+    let pem = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read public key: {}", path.display()))?;
+    VerifyingKey::from_public_key_pem(&pem)
+        .map_err(|e| anyhow!("Invalid Ed25519 public key {}: {}", path.display(), e))
+}
+
+fn sha256_file(path: &Path) -> Result<String> {
+    let bytes = std::fs::read(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(format!("{:x}", Sha256::digest(&bytes)))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok()).collect()
+}
+
+/// Write the manifest of `files` (and its signature, with a key), returning the files written
+///
+/// Entries are relative to the manifest's directory; files outside it are left
+/// out with a warning. The manifest and signature themselves are never listed.
+pub fn write_manifest(manifest: &Path, files: &[PathBuf], key: Option<&SigningKey>) -> Result<Vec<PathBuf>> {
+    let dir = manifest.parent().unwrap_or(Path::new(""));
+    let signature = signature_path(manifest);
+    let mut entries = BTreeMap::new();
+    for file in files.iter().filter(|f| **f != manifest && **f != signature) {
+        let name = match file.strip_prefix(dir) {
+            Ok(name) => name.to_string_lossy().replace('\\', "/"),
+            Err(_) => {
+                warn!("{} is outside {}; not listed in {}", file.display(), dir.display(), MANIFEST_FILE);
+                continue;
+            }
+        };
+        entries.insert(name, sha256_file(file)?);
+    }
+    let content: String = entries.iter().map(|(name, digest)| format!("{}  {}\n", digest, name)).collect();
+    std::fs::write(manifest, &content)
+        .with_context(|| format!("Failed to write manifest: {}", manifest.display()))?;
+
+    let mut written = vec![manifest.to_path_buf()];
+    if let Some(key) = key {
+        let sig = key.sign(content.as_bytes());
+        std::fs::write(&signature, format!("{}\n", to_hex(&sig.to_bytes())))
+            .with_context(|| format!("Failed to write signature: {}", signature.display()))?;
+        written.push(signature);
+    }
+    Ok(written)
+}
+
+/// Result of checking an output directory against its manifest
+#[derive(Debug)]
+pub struct Verification {
+    /// Files whose digest matched
+    pub verified: usize,
+    /// Whether the signature was checked (a public key was given)
+    pub signature_checked: bool,
+}
+
+/// Check the files listed in a manifest, and its signature when a key is given
+///
+/// Fails on a bad signature, or naming every file that is missing or whose
+/// digest changed.
+pub fn verify_manifest(manifest: &Path, key: Option<&VerifyingKey>) -> Result<Verification> {
+    let content = std::fs::read_to_string(manifest)
+        .with_context(|| format!("Failed to read manifest: {}", manifest.display()))?;
+    let signature = signature_path(manifest);
+    match key {
+        Some(key) => {
+            let hex = std::fs::read_to_string(&signature)
+                .with_context(|| format!("Failed to read signature: {}", signature.display()))?;
+            let sig = from_hex(hex.trim())
+                .and_then(|bytes| Signature::from_slice(&bytes).ok())
+                .ok_or_else(|| anyhow!("Malformed signature: {}", signature.display()))?;
+            if key.verify(content.as_bytes(), &sig).is_err() {
+                bail!("Signature check failed: {} does not match {}", signature.display(), manifest.display());
+            }
+        }
+        None if signature.exists() => {
+            warn!("{} not checked: no public key given", signature.display());
+        }
+        None => {}
+    }
+
+    let dir = manifest.parent().unwrap_or(Path::new(""));
+    let mut verified = 0;
+    let mut failures = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        let (digest, name) = match line.split_once("  ") {
+            Some((digest, name)) if digest.len() == 64 && crate::scanner::is_repo_relative(name) => (digest, name),
+            _ => bail!("{}:{}: not a `<sha256>  <file>` line", manifest.display(), i + 1),
+        };
+        match sha256_file(&dir.join(name)) {
+            Ok(actual) if actual == digest => verified += 1,
+            Ok(_) => failures.push(format!("{} (modified)", name)),
+            Err(_) => failures.push(format!("{} (missing)", name)),
+        }
+    }
+    if !failures.is_empty() {
+        bail!("Output verification failed for {}: {}", dir.display(), failures.join(", "));
+    }
+    Ok(Verification { verified, signature_checked: key.is_some() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::pkcs8::{EncodePrivateKey, EncodePublicKey};
+    use ed25519_dalek::pkcs8::spki::der::pem::LineEnding;
+    use tempfile::TempDir;
+
+    fn output_dir() -> (TempDir, Vec<PathBuf>) {
+        let temp_dir = TempDir::new().unwrap();
+        let files: Vec<PathBuf> = ["report.json", "report.csv", "result.json"]
+            .iter()
+            .map(|name| temp_dir.path().join(name))
+            .collect();
+        for file in &files {
+            std::fs::write(file, format!("content of {}\n", file.display())).unwrap();
+        }
+        (temp_dir, files)
+    }
+
+    #[test]
+    fn test_manifest_verify_and_tamper() {
+        let (temp_dir, files) = output_dir();
+        let manifest = temp_dir.path().join(MANIFEST_FILE);
+        let mut listed = files.clone();
+        listed.push(manifest.clone());
+        assert_eq!(write_manifest(&manifest, &listed, None).unwrap(), vec![manifest.clone()]);
+
+        let content = std::fs::read_to_string(&manifest).unwrap();
+        let names: Vec<&str> = content.lines().map(|l| l.split_once("  ").unwrap().1).collect();
+        assert_eq!(names, vec!["report.csv", "report.json", "result.json"]);
+        let verification = verify_manifest(&manifest, None).unwrap();
+        assert_eq!(verification.verified, 3);
+        assert!(!verification.signature_checked);
+
+        // Flip one byte of report.csv
+        let csv = temp_dir.path().join("report.csv");
+        let mut bytes = std::fs::read(&csv).unwrap();
+        bytes[0] ^= 1;
+        std::fs::write(&csv, bytes).unwrap();
+        let err = verify_manifest(&manifest, None).unwrap_err().to_string();
+        assert!(err.contains("report.csv (modified)") && !err.contains("report.json"), "{}", err);
+
+        std::fs::remove_file(temp_dir.path().join("result.json")).unwrap();
+        let err = verify_manifest(&manifest, None).unwrap_err().to_string();
+        assert!(err.contains("result.json (missing)"), "{}", err);
+    }
+
+    #[test]
+    fn test_signed_manifest() {
+        let (temp_dir, files) = output_dir();
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let key_path = temp_dir.path().join("signing.pem");
+        std::fs::write(&key_path, key.to_pkcs8_pem(LineEnding::LF).unwrap().as_bytes()).unwrap();
+        let public_path = temp_dir.path().join("signing.pub.pem");
+        std::fs::write(&public_path, key.verifying_key().to_public_key_pem(LineEnding::LF).unwrap()).unwrap();
+
+        let manifest = temp_dir.path().join(MANIFEST_FILE);
+        let signing_key = load_signing_key(&key_path).unwrap();
+        let written = write_manifest(&manifest, &files, Some(&signing_key)).unwrap();
+        assert_eq!(written, vec![manifest.clone(), temp_dir.path().join("MANIFEST.sha256.sig")]);
+
+        let public = load_verifying_key(&public_path).unwrap();
+        assert!(verify_manifest(&manifest, Some(&public)).unwrap().signature_checked);
+
+        // Another key's signature doesn't verify
+        let other = SigningKey::from_bytes(&[8; 32]).verifying_key();
+        let err = verify_manifest(&manifest, Some(&other)).unwrap_err().to_string();
+        assert!(err.contains("Signature check failed"), "{}", err);
+
+        // Neither does an edited manifest, even with digests that match the files
+        let content = std::fs::read_to_string(&manifest).unwrap();
+        std::fs::write(&manifest, content.lines().take(2).map(|l| format!("{}\n", l)).collect::<String>()).unwrap();
+        assert!(verify_manifest(&manifest, None).is_ok());
+        let err = verify_manifest(&manifest, Some(&public)).unwrap_err().to_string();
+        assert!(err.contains("Signature check failed"), "{}", err);
+
+        assert!(load_signing_key(&public_path).is_err());
+    }
+}
@@ -0,0 +1,255 @@
+//! Audit trail of what the scanner evaluated
+//!
+//! Writes a JSONL file: one `scan` header record, then for each repository one
+//! `file` record per scanned file (content hash and per-rule counters) followed
+//! by a `repository` record with the totals. Paths ending in `.gz` are gzipped.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use anyhow::{Context, Result};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use log::info;
+use serde::Serialize;
+
+use crate::git_ops::CheckoutSource;
+use crate::scanner::{FileStats, RuleStats, ScanOutcome};
+
+/// One line of the audit log
+#[derive(Serialize)]
+#[serde(tag = "record", rename_all = "snake_case")]
+enum AuditRecord<'a> {
+    Scan {
+        scan_time: String,
+        scanner_version: &'static str,
+    },
+    File {
+        repository: &'a str,
+        #[serde(flatten)]
+        stats: &'a FileStats,
+    },
+    Repository {
+        repository: &'a str,
+        checkout: CheckoutSource,
+        files_scanned: usize,
+        files_prefiltered: usize,
+        #[serde(skip_serializing_if = "is_zero")]
+        files_own_reports: usize,
+        #[serde(skip_serializing_if = "is_zero")]
+        files_lfs_pointers: usize,
+        #[serde(skip_serializing_if = "is_zero")]
+        files_generated: usize,
+        local_nim_matches: usize,
+        hosted_nim_matches: usize,
+        rules: BTreeMap<&'static str, RuleStats>,
+        #[serde(skip_serializing_if = "is_zero")]
+        dirs_beyond_max_depth: usize,
+        #[serde(skip_serializing_if = "std::ops::Not::not")]
+        file_limit_reached: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<&'a str>,
+    },
+}
+
+fn is_zero(n: &usize) -> bool {
+    *n == 0
+}
+
+enum Sink {
+    Plain(BufWriter<File>),
+    Gzip(GzEncoder<BufWriter<File>>),
+}
+
+/// JSONL audit log writer
+pub struct AuditLog {
+    sink: Sink,
+}
+
+impl AuditLog {
+    /// Create the audit log and write the header record (gzip when the path ends in .gz)
+    pub fn create(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+        }
+        let file = File::create(path)
+            .with_context(|| format!("Failed to create audit log: {}", path.display()))?;
+        let writer = BufWriter::new(file);
+
+        let sink = if path.extension().is_some_and(|ext| ext == "gz") {
+            Sink::Gzip(GzEncoder::new(writer, Compression::default()))
+        } else {
+            Sink::Plain(writer)
+        };
+
+        let mut log = AuditLog { sink };
+        log.write_record(&AuditRecord::Scan {
+            scan_time: chrono::Utc::now().to_rfc3339(),
+            scanner_version: env!("CARGO_PKG_VERSION"),
+        })?;
+        info!("Writing audit log to {}", path.display());
+        Ok(log)
+    }
+
+    /// Record the files and rule counters for a scanned repository
+    pub fn write_repository(&mut self, repository: &str, checkout: CheckoutSource, outcome: &ScanOutcome) -> Result<()> {
+        for stats in &outcome.files {
+            self.write_record(&AuditRecord::File { repository, stats })?;
+        }
+        self.write_record(&AuditRecord::Repository {
+            repository,
+            checkout,
+            files_scanned: outcome.files.len(),
+            files_prefiltered: outcome.prefiltered_files(),
+            files_own_reports: outcome.own_reports_skipped(),
+            files_lfs_pointers: outcome.lfs_pointer_paths().len(),
+            files_generated: outcome.generated_skipped(),
+            local_nim_matches: outcome.local.len(),
+            hosted_nim_matches: outcome.hosted.len(),
+            rules: outcome.rule_totals(),
+            dirs_beyond_max_depth: outcome.dirs_beyond_max_depth,
+            file_limit_reached: outcome.file_limit_reached,
+            error: None,
+        })
+    }
+
+    /// Record a repository that could not be scanned (e.g. clone failure)
+    pub fn write_repository_error(&mut self, repository: &str, checkout: CheckoutSource, error: &str) -> Result<()> {
+        self.write_record(&AuditRecord::Repository {
+            repository,
+            checkout,
+            files_scanned: 0,
+            files_prefiltered: 0,
+            files_own_reports: 0,
+            files_lfs_pointers: 0,
+            files_generated: 0,
+            local_nim_matches: 0,
+            hosted_nim_matches: 0,
+            rules: BTreeMap::new(),
+            dirs_beyond_max_depth: 0,
+            file_limit_reached: false,
+            error: Some(error),
+        })
+    }
+
+    /// Flush buffered output (and write the gzip trailer)
+    pub fn finish(self) -> Result<()> {
+        match self.sink {
+            Sink::Plain(mut w) => w.flush(),
+            Sink::Gzip(gz) => gz.finish().and_then(|mut w| w.flush()),
+        }
+        .context("Failed to finish audit log")
+    }
+
+    fn write_record(&mut self, record: &AuditRecord) -> Result<()> {
+        let mut line = serde_json::to_vec(record).context("Failed to serialize audit record")?;
+        line.push(b'\n');
+        let writer: &mut dyn Write = match self.sink {
+            Sink::Plain(ref mut w) => w,
+            Sink::Gzip(ref mut w) => w,
+        };
+        writer.write_all(&line).context("Failed to write audit log")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use flate2::read::GzDecoder;
+    use serde_json::Value;
+    use tempfile::TempDir;
+    use crate::scanner::{self, ScanOptions, RULE_HOSTED_NIM, RULE_HOSTED_NIM_DOCS, RULE_LOCAL_NIM};
+
+    fn fixture_repo(root: &Path) {
+        std::fs::write(
+            root.join("docker-compose.yaml"),
+            "services:\n  llm:\n    image: nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3.0\n  embed:\n    image: nvcr.io/nim/nvidia/nv-embedqa-e5-v5:1.0.0\n",
+        ).unwrap();
+        std::fs::write(
+            root.join("client.py"),
+            "from langchain_nvidia_ai_endpoints import ChatNVIDIA\nllm = ChatNVIDIA(model=\"meta/llama-3.1-70b-instruct\")\n",
+        ).unwrap();
+        std::fs::write(root.join("README.md"), "Uses the `nvidia/nv-embedqa-e5-v5` model.\n").unwrap();
+        std::fs::write(root.join("empty.py"), "print('hello')\n").unwrap();
+    }
+
+    fn read_records(path: &Path) -> Vec<Value> {
+        let mut text = String::new();
+        let file = File::open(path).unwrap();
+        if path.extension().is_some_and(|ext| ext == "gz") {
+            GzDecoder::new(file).read_to_string(&mut text).unwrap();
+        } else {
+            std::io::BufReader::new(file).read_to_string(&mut text).unwrap();
+        }
+        text.lines().map(|l| serde_json::from_str(l).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_rule_counts_line_up_with_findings() {
+        let temp_dir = TempDir::new().unwrap();
+        fixture_repo(temp_dir.path());
+
+        let outcome = scanner::scan_directory(temp_dir.path(), "test/repo", &ScanOptions::default());
+        let totals = outcome.rule_totals();
+
+        assert_eq!(outcome.files.len(), 4);
+        assert_eq!(outcome.local.len(), 2);
+        assert_eq!(totals[RULE_LOCAL_NIM].matches, outcome.local.len());
+        assert_eq!(
+            totals[RULE_HOSTED_NIM].matches + totals[RULE_HOSTED_NIM_DOCS].matches,
+            outcome.hosted.len()
+        );
+        let total_lines: usize = outcome.files.iter().map(|f| f.lines).sum();
+        assert_eq!(totals[RULE_LOCAL_NIM].lines_evaluated, total_lines);
+        assert!(outcome.files.iter().all(|f| f.sha256.as_ref().is_some_and(|h| h.len() == 64)));
+    }
+
+    #[test]
+    fn test_write_audit_log_gzip() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = temp_dir.path().join("repo");
+        std::fs::create_dir(&repo).unwrap();
+        fixture_repo(&repo);
+        let outcome = scanner::scan_directory(&repo, "test/repo", &ScanOptions::default());
+
+        let path = temp_dir.path().join("audit/audit.jsonl.gz");
+        let mut log = AuditLog::create(&path).unwrap();
+        log.write_repository("test/repo", CheckoutSource::Cloned, &outcome).unwrap();
+        log.write_repository_error("test/missing", CheckoutSource::PreProvisioned, "not found").unwrap();
+        log.finish().unwrap();
+
+        let records = read_records(&path);
+        assert_eq!(records[0]["record"], "scan");
+        let files: Vec<&Value> = records.iter().filter(|r| r["record"] == "file").collect();
+        assert_eq!(files.len(), 4);
+        let compose = files.iter().find(|r| r["file_path"] == "docker-compose.yaml").unwrap();
+        assert_eq!(compose["repository"], "test/repo");
+        assert_eq!(compose["lines"], 5);
+        assert_eq!(compose["rules"][RULE_LOCAL_NIM]["lines_matched"], 2);
+
+        let repos: Vec<&Value> = records.iter().filter(|r| r["record"] == "repository").collect();
+        assert_eq!(repos.len(), 2);
+        assert_eq!(repos[0]["files_scanned"], 4);
+        assert_eq!(repos[0]["files_prefiltered"], 1);
+        assert_eq!(repos[0]["local_nim_matches"], outcome.local.len());
+        assert_eq!(repos[0]["rules"][RULE_LOCAL_NIM]["matches"], outcome.local.len());
+        assert_eq!(repos[0]["checkout"], "cloned");
+        assert_eq!(repos[1]["checkout"], "pre-provisioned");
+        assert_eq!(repos[1]["error"], "not found");
+    }
+
+    #[test]
+    fn test_write_audit_log_plain() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("audit.jsonl");
+        let log = AuditLog::create(&path).unwrap();
+        log.finish().unwrap();
+
+        let records = read_records(&path);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0]["scanner_version"], env!("CARGO_PKG_VERSION"));
+    }
+}
@@ -0,0 +1,229 @@
+//! Cleanup that still happens when a repository's task panics
+//!
+//! Each repository is cloned and scanned on a rayon worker. A panic there (a
+//! bug tripped by one odd repository) is caught per repository with
+//! [`catch_panic`], so the rest of the run goes on, and unwinding drops what
+//! the task held: a [`PartialDir`] removes a half-built checkout unless it was
+//! moved into place, and git started with [`GroupCommand::group_output`] runs
+//! in a process group of its own that is killed if nothing waits for it any
+//! more. A separate group no longer gets the terminal's Ctrl-C, so SIGINT and
+//! SIGTERM are passed on to every live group before the scanner exits.
+
+use std::any::Any;
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use log::warn;
+
+/// Message of a caught panic (the `panic!` text when there is one)
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    match payload.downcast_ref::<&str>() {
+        Some(message) => message.to_string(),
+        None => payload.downcast_ref::<String>().cloned().unwrap_or_else(|| "unknown panic".to_string()),
+    }
+}
+
+/// Run one repository's task, turning a panic into an error with its message
+pub fn catch_panic<T>(task: impl FnOnce() -> T) -> Result<T, String> {
+    panic::catch_unwind(AssertUnwindSafe(task)).map_err(|payload| panic_message(payload.as_ref()))
+}
+
+// ============================================================================
+// Partial Checkouts
+// ============================================================================
+
+/// A checkout being built, removed when dropped unless it was moved into place
+pub struct PartialDir {
+    path: PathBuf,
+    kept: bool,
+}
+
+impl PartialDir {
+    /// Claim `path`, removing whatever an earlier attempt left there
+    pub fn new(path: PathBuf) -> io::Result<Self> {
+        if path.exists() {
+            std::fs::remove_dir_all(&path)?;
+        }
+        Ok(Self { path, kept: false })
+    }
+
+    /// Rename the directory to `target` and keep it from then on
+    pub fn persist(mut self, target: &Path) -> io::Result<()> {
+        std::fs::rename(&self.path, target)?;
+        self.kept = true;
+        Ok(())
+    }
+}
+
+impl Drop for PartialDir {
+    fn drop(&mut self) {
+        if !self.kept && self.path.exists() {
+            if let Err(e) = std::fs::remove_dir_all(&self.path) {
+                warn!("Failed to remove partial checkout {}: {}", self.path.display(), e);
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Git Process Groups
+// ============================================================================
+
+/// Running git so that it can't outlive the task waiting for it
+pub trait GroupCommand {
+    /// `Command::output` with the process (and any helpers it starts) in a new
+    /// process group, killed if waiting ends early
+    ///
+    /// A background process group stops when it reads from the terminal, so
+    /// git's credential prompt is turned off: a private repository without a
+    /// token fails instead of hanging.
+    fn group_output(&mut self) -> io::Result<Output>;
+}
+
+impl GroupCommand for Command {
+    #[cfg(unix)]
+    fn group_output(&mut self) -> io::Result<Output> {
+        use std::os::unix::process::CommandExt;
+        use std::process::Stdio;
+        let child = self
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .process_group(0)
+            .spawn()?;
+        let mut group = ProcessGroup::new(child.id() as i32);
+        let output = child.wait_with_output()?;
+        group.reaped = true;
+        Ok(output)
+    }
+
+    #[cfg(not(unix))]
+    fn group_output(&mut self) -> io::Result<Output> {
+        self.env("GIT_TERMINAL_PROMPT", "0").output()
+    }
+}
+
+/// A process group led by a spawned child, killed on drop unless the child was reaped
+#[cfg(unix)]
+struct ProcessGroup {
+    pgid: i32,
+    slot: Option<usize>,
+    reaped: bool,
+}
+
+#[cfg(unix)]
+impl ProcessGroup {
+    fn new(pgid: i32) -> Self {
+        Self { pgid, slot: live_groups::register(pgid), reaped: false }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for ProcessGroup {
+    fn drop(&mut self) {
+        if let Some(slot) = self.slot {
+            live_groups::unregister(slot);
+        }
+        if !self.reaped {
+            // SAFETY: killpg has no memory-safety preconditions
+            unsafe { libc::killpg(self.pgid, libc::SIGKILL) };
+        }
+    }
+}
+
+/// Process groups with git still running, for passing on SIGINT and SIGTERM
+#[cfg(unix)]
+mod live_groups {
+    use std::sync::atomic::{AtomicI32, Ordering};
+    use std::sync::Once;
+
+    /// More than any thread pool runs at once; a group without a slot just isn't signalled
+    const SLOTS: usize = 256;
+    static GROUPS: [AtomicI32; SLOTS] = [const { AtomicI32::new(0) }; SLOTS];
+    static HANDLERS: Once = Once::new();
+
+    pub fn register(pgid: i32) -> Option<usize> {
+        HANDLERS.call_once(install_handlers);
+        GROUPS.iter().position(|slot| slot.compare_exchange(0, pgid, Ordering::SeqCst, Ordering::SeqCst).is_ok())
+    }
+
+    pub fn unregister(slot: usize) {
+        GROUPS[slot].store(0, Ordering::SeqCst);
+    }
+
+    fn install_handlers() {
+        for signal in [libc::SIGINT, libc::SIGTERM] {
+            // SAFETY: `forward` only makes async-signal-safe calls
+            unsafe {
+                let previous = libc::signal(signal, forward as extern "C" fn(libc::c_int) as libc::sighandler_t);
+                // A signal the parent chose to ignore (nohup, background jobs) stays ignored
+                if previous == libc::SIG_IGN {
+                    libc::signal(signal, libc::SIG_IGN);
+                }
+            }
+        }
+    }
+
+    /// Signal every live group, then die of the signal as if there were no handler
+    extern "C" fn forward(signal: libc::c_int) {
+        for slot in &GROUPS {
+            let pgid = slot.load(Ordering::SeqCst);
+            if pgid > 0 {
+                // SAFETY: killpg is async-signal-safe
+                unsafe { libc::killpg(pgid, signal) };
+            }
+        }
+        // SAFETY: signal and raise are async-signal-safe
+        unsafe {
+            libc::signal(signal, libc::SIG_DFL);
+            libc::raise(signal);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_partial_dir_removed_on_panic() {
+        let temp_dir = TempDir::new().unwrap();
+        let partial = temp_dir.path().join("org_repo.partial-1");
+        let err = catch_panic(|| {
+            let _dir = PartialDir::new(partial.clone()).unwrap();
+            std::fs::create_dir_all(partial.join("src")).unwrap();
+            panic!("unpacking {} failed", partial.display());
+        })
+        .unwrap_err();
+        assert!(err.starts_with("unpacking") && err.ends_with("failed"), "{}", err);
+        assert!(!partial.exists());
+
+        // Persisted directories stay
+        let dir = PartialDir::new(partial.clone()).unwrap();
+        std::fs::create_dir_all(&partial).unwrap();
+        let target = temp_dir.path().join("org_repo");
+        dir.persist(&target).unwrap();
+        assert!(target.is_dir() && !partial.exists());
+        assert_eq!(catch_panic(|| 7), Ok(7));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_process_group_killed_when_not_reaped() {
+        use std::os::unix::process::{CommandExt, ExitStatusExt};
+        let mut child = Command::new("sh")
+            .args(["-c", "sleep 30 & wait"])
+            .process_group(0)
+            .spawn()
+            .unwrap();
+        drop(ProcessGroup::new(child.id() as i32));
+        assert_eq!(child.wait().unwrap().signal(), Some(libc::SIGKILL));
+
+        let output = Command::new("sh").args(["-c", "echo $GIT_TERMINAL_PROMPT"]).group_output().unwrap();
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "0");
+    }
+}
@@ -0,0 +1,450 @@
+//! Docker Compose files merged per directory
+//!
+//! Projects often split one service across `docker-compose.yaml` and overrides
+//! such as `docker-compose.gpu.yaml`: the base declares the service, its
+//! profiles and replicas, and an override supplies `image: nvcr.io/nim/...`.
+//! Scanning each file alone finds the image but not the service it belongs
+//! to, so after the line-by-line pass the Compose files of each directory are
+//! merged by service name, the way `docker compose -f base -f override` does,
+//! and the merged service is attached to the image finding.
+//!
+//! Each Compose file is also read as YAML on its own: an image spread over
+//! several keys (`registry:` / `repository:` / `tag:`), pulled into a service
+//! through a YAML anchor or merge key, or passed as a build arg has no single
+//! line the line pass can match. [`compose_images`] walks `services.*.image`,
+//! `services.*.build.args` and the `x-` extension fields for NIM images instead.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use log::debug;
+use serde_yaml::Value;
+
+use crate::models::{ComposeService, LocalNimMatch};
+
+/// Whether a file name is a Compose file (`compose*.y(a)ml`, `docker-compose*.y(a)ml`)
+pub fn is_compose_file(path: &Path) -> bool {
+    let name = match path.file_name().and_then(|n| n.to_str()) {
+        Some(name) => name.to_lowercase(),
+        None => return false,
+    };
+    let stem = match name.strip_suffix(".yaml").or_else(|| name.strip_suffix(".yml")) {
+        Some(stem) => stem,
+        None => return false,
+    };
+    let rest = stem.strip_prefix("docker-").unwrap_or(stem);
+    rest == "compose" || rest.starts_with("compose.") || rest.starts_with("compose-")
+}
+
+/// Merge position: the base file first, `*.override.*` last (as `docker compose`
+/// loads it after the base), other fragments in between by name
+fn merge_rank(path: &Path) -> (u8, String) {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("").to_lowercase();
+    let stem = name.rsplit_once('.').map(|(stem, _)| stem).unwrap_or(&name);
+    let rank = match stem {
+        "compose" | "docker-compose" => 0,
+        _ if stem.ends_with(".override") => 2,
+        _ => 1,
+    };
+    (rank, name)
+}
+
+/// One service while merging: each value with the file that set it
+#[derive(Default)]
+struct MergedService {
+    image: Option<(String, String)>,
+    profiles: Option<(Vec<String>, String)>,
+    replicas: Option<(u64, String)>,
+}
+
+/// Services of a parsed Compose file
+fn services(doc: &Value) -> Vec<(&str, &Value)> {
+    doc.get("services")
+        .and_then(Value::as_mapping)
+        .map(|services| services.iter().filter_map(|(name, service)| Some((name.as_str()?, service))).collect())
+        .unwrap_or_default()
+}
+
+/// `KEY=value` pairs of every service's `environment:` (list or map form) in a Compose file
+///
+/// Entries without a value (`- KEY`, `KEY:`) pass the host's variable through and are left out.
+pub fn service_environment(content: &str) -> Vec<(String, String)> {
+    let doc: Value = match serde_yaml::from_str(content) {
+        Ok(doc) => doc,
+        Err(_) => return Vec::new(),
+    };
+    let mut pairs = Vec::new();
+    for (_, service) in services(&doc) {
+        match service.get("environment") {
+            Some(Value::Sequence(entries)) => {
+                for entry in entries.iter().filter_map(Value::as_str) {
+                    if let Some((key, value)) = entry.split_once('=') {
+                        pairs.push((key.trim().to_string(), value.to_string()));
+                    }
+                }
+            }
+            Some(Value::Mapping(entries)) => {
+                for (key, value) in entries {
+                    let value = match value {
+                        Value::String(s) => s.clone(),
+                        Value::Number(n) => n.to_string(),
+                        Value::Bool(b) => b.to_string(),
+                        _ => continue,
+                    };
+                    if let Some(key) = key.as_str() {
+                        pairs.push((key.to_string(), value));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    pairs
+}
+
+/// A NIM image found by walking a parsed Compose file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComposeImage {
+    /// Dotted key path of the value (`services.llm.image`, `services.llm.build.args.NIM_IMAGE`, `x-nim`)
+    pub key_path: String,
+    /// Service the value belongs to (None for an `x-` field)
+    pub service: Option<String>,
+    /// Image reference, assembled from its parts when they are in separate keys
+    pub image: String,
+    /// 1-indexed line holding the value (or its repository part), best effort
+    pub line: usize,
+}
+
+/// Registry every NIM image lives under
+const NIM_REGISTRY: &str = "nvcr.io/nim/";
+
+/// Text of a scalar (`tag: 1.5` is a number to YAML)
+fn scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// `KEY=value` list or `KEY: value` map (build args), as pairs
+fn key_values(value: &Value) -> Vec<(String, String)> {
+    match value {
+        Value::Sequence(entries) => entries
+            .iter()
+            .filter_map(Value::as_str)
+            .filter_map(|entry| entry.split_once('='))
+            .map(|(key, value)| (key.trim().to_string(), value.to_string()))
+            .collect(),
+        Value::Mapping(entries) => entries
+            .iter()
+            .filter_map(|(key, value)| Some((key.as_str()?.to_string(), scalar(value)?)))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Image assembled from separate registry / repository / tag keys (`NIM_REGISTRY`,
+/// `repository`, `image_tag`, ...), when that makes a NIM image
+fn assemble_image(pairs: &[(String, String)]) -> Option<(String, String)> {
+    let part = |suffixes: &[&str]| {
+        pairs.iter().find(|(key, value)| {
+            let key = key.to_lowercase();
+            !value.is_empty() && suffixes.iter().any(|suffix| key == *suffix || key.ends_with(&format!("_{}", suffix)))
+        })
+    };
+    let (_, repository) = part(&["repository", "repo", "image", "image_name"])?;
+    // `nvcr.io/nim/meta/...` names its registry already, `meta/llama-3.1-8b-instruct` doesn't
+    let has_registry = repository.split_once('/').is_some_and(|(host, _)| host.contains('.'));
+    let mut image = match part(&["registry"]) {
+        Some((_, registry)) if !has_registry => {
+            format!("{}/{}", registry.trim_end_matches('/'), repository.trim_start_matches('/'))
+        }
+        _ => repository.clone(),
+    };
+    if let Some((_, tag)) = part(&["tag", "version"]) {
+        if !image.rsplit('/').next().is_some_and(|name| name.contains(':')) {
+            image = format!("{}:{}", image, tag);
+        }
+    }
+    (image.contains(NIM_REGISTRY) && image != *repository).then(|| (image, repository.clone()))
+}
+
+/// Walks a parsed Compose file, collecting image values and the text to locate each by
+struct ImageWalk<'a> {
+    lines: Vec<&'a str>,
+    found: Vec<ComposeImage>,
+}
+
+impl ImageWalk<'_> {
+    /// Record `image` at `key_path`, located by the first line holding `needle`
+    /// (looked for from the service's own block first)
+    fn push(&mut self, key_path: String, service: Option<&str>, image: String, needle: &str) {
+        let from = service.and_then(|name| service_line(&self.lines, name)).unwrap_or(0);
+        let holds = |line: &&str| line.contains(needle);
+        let line = self.lines[from..].iter().position(holds).map(|i| i + from)
+            .or_else(|| self.lines.iter().position(holds))
+            .map_or(from + 1, |i| i + 1);
+        self.found.push(ComposeImage { key_path, service: service.map(str::to_string), image, line });
+    }
+
+    /// Image values and assembled images under an `x-` field, at any depth
+    fn extension(&mut self, key_path: String, value: &Value) {
+        match value {
+            Value::String(s) if s.contains(NIM_REGISTRY) => self.push(key_path, None, s.clone(), s),
+            Value::Mapping(entries) => {
+                let assembled = assemble_image(&key_values(value));
+                let part = assembled.as_ref().map(|(_, repository)| repository.clone());
+                if let Some((image, repository)) = assembled {
+                    self.push(key_path.clone(), None, image, &repository);
+                }
+                for (key, child) in entries {
+                    // The repository part is reported with its tag, not again alone
+                    if part.is_some() && child.as_str() == part.as_deref() {
+                        continue;
+                    }
+                    if let Some(key) = key.as_str() {
+                        self.extension(format!("{}.{}", key_path, key), child);
+                    }
+                }
+            }
+            Value::Sequence(items) => {
+                for (i, child) in items.iter().enumerate() {
+                    self.extension(format!("{}.{}", key_path, i), child);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// NIM images of a Compose file, from `services.*.image`, `services.*.build.args`
+/// and `x-` extension fields, with anchors and `<<` merge keys applied
+///
+/// An `x-` value a service also uses is reported for the service only. None
+/// when the file doesn't parse as YAML.
+pub fn compose_images(content: &str) -> Option<Vec<ComposeImage>> {
+    let mut doc: Value = serde_yaml::from_str(content).ok()?;
+    if let Err(e) = doc.apply_merge() {
+        debug!("Compose merge keys not applied: {}", e);
+    }
+    let mut walk = ImageWalk { lines: content.lines().collect(), found: Vec::new() };
+    for (name, service) in services(&doc) {
+        if let Some(image) = service.get("image").and_then(Value::as_str).filter(|i| i.contains(NIM_REGISTRY)) {
+            walk.push(format!("services.{}.image", name), Some(name), image.to_string(), image);
+        }
+        let args = key_values(service.get("build").and_then(|b| b.get("args")).unwrap_or(&Value::Null));
+        let assembled = assemble_image(&args);
+        let part = assembled.as_ref().map(|(_, repository)| repository);
+        for (key, value) in args.iter().filter(|(_, value)| value.contains(NIM_REGISTRY) && Some(value) != part) {
+            walk.push(format!("services.{}.build.args.{}", name, key), Some(name), value.clone(), value);
+        }
+        if let Some((image, repository)) = assembled {
+            walk.push(format!("services.{}.build.args", name), Some(name), image, &repository);
+        }
+    }
+    let used: Vec<(String, usize)> = walk.found.iter().map(|f| (f.image.clone(), f.line)).collect();
+    let extensions = doc.as_mapping().into_iter().flatten()
+        .filter_map(|(key, value)| Some((key.as_str()?, value)))
+        .filter(|(key, _)| key.starts_with("x-"));
+    let services_found = walk.found.len();
+    for (key, value) in extensions {
+        walk.extension(key.to_string(), value);
+    }
+    let mut extension_found = walk.found.split_off(services_found);
+    extension_found.retain(|f| !used.contains(&(f.image.clone(), f.line)));
+    walk.found.extend(extension_found);
+    Some(walk.found)
+}
+
+/// Merge the Compose files of each directory and attach the merged service to
+/// the Local NIM finding on the line that supplied the service's image
+///
+/// Only directories with more than one Compose file are merged. A file that
+/// doesn't parse is left out of the merge; its findings from the line pass stay
+/// as they are.
+pub fn annotate_services(repo_path: &Path, files: &[PathBuf], local: &mut [LocalNimMatch]) {
+    let mut by_dir: BTreeMap<&Path, Vec<&PathBuf>> = BTreeMap::new();
+    for path in files.iter().filter(|p| is_compose_file(p)) {
+        by_dir.entry(path.parent().unwrap_or(repo_path)).or_default().push(path);
+    }
+    for (_, mut dir_files) in by_dir.into_iter().filter(|(_, f)| f.len() > 1) {
+        dir_files.sort_by_key(|path| merge_rank(path));
+        annotate_directory(repo_path, &dir_files, local);
+    }
+}
+
+fn annotate_directory(repo_path: &Path, dir_files: &[&PathBuf], local: &mut [LocalNimMatch]) {
+    let relative = |path: &Path| {
+        path.strip_prefix(repo_path).unwrap_or(path).to_string_lossy().replace('\\', "/")
+    };
+    let mut merged_files = Vec::new();
+    let mut contents = BTreeMap::new();
+    let mut merged: BTreeMap<String, MergedService> = BTreeMap::new();
+    for path in dir_files {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+        let doc: Value = match serde_yaml::from_str(&content) {
+            Ok(doc) => doc,
+            Err(e) => {
+                debug!("Not merging Compose file {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        let file = relative(path);
+        for (name, service) in services(&doc) {
+            let entry = merged.entry(name.to_string()).or_default();
+            if let Some(image) = service.get("image").and_then(Value::as_str) {
+                entry.image = Some((image.to_string(), file.clone()));
+            }
+            if let Some(profiles) = service.get("profiles").and_then(Value::as_sequence) {
+                let profiles = profiles.iter().filter_map(|p| p.as_str().map(str::to_string)).collect();
+                entry.profiles = Some((profiles, file.clone()));
+            }
+            let replicas = service.get("deploy").and_then(|d| d.get("replicas")).or_else(|| service.get("scale"));
+            if let Some(replicas) = replicas.and_then(Value::as_u64) {
+                entry.replicas = Some((replicas, file.clone()));
+            }
+        }
+        merged_files.push(file.clone());
+        contents.insert(file, content);
+    }
+
+    for (name, service) in merged {
+        let (image, image_file) = match service.image {
+            Some(image) => image,
+            None => continue,
+        };
+        let line = match contents.get(&image_file).and_then(|content| service_image_line(content, &name, &image)) {
+            Some(line) => line,
+            None => continue,
+        };
+        let mut hint_sources = BTreeMap::new();
+        if let Some((_, ref file)) = service.profiles {
+            hint_sources.insert("profiles".to_string(), file.clone());
+        }
+        if let Some((_, ref file)) = service.replicas {
+            hint_sources.insert("replicas".to_string(), file.clone());
+        }
+        let compose = ComposeService {
+            service: name.clone(),
+            merged_files: merged_files.clone(),
+            profiles: service.profiles.map(|(profiles, _)| profiles).unwrap_or_default(),
+            replicas: service.replicas.map(|(replicas, _)| replicas),
+            hint_sources,
+        };
+        for m in local.iter_mut().filter(|m| m.file_path == image_file && m.line_number == line) {
+            m.compose = Some(compose.clone());
+        }
+    }
+}
+
+fn indent(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// 0-indexed line of the `<service>:` key under `services:`
+fn service_line(lines: &[&str], service: &str) -> Option<usize> {
+    let is_key = |line: &str| {
+        let key = line.trim().strip_suffix(':').unwrap_or("");
+        key.trim_matches(|c| c == '"' || c == '\'') == service
+    };
+    let services_at = lines.iter().position(|line| line.trim_end() == "services:")?;
+    Some(services_at + 1 + lines[services_at + 1..].iter().position(|line| indent(line) > 0 && is_key(line))?)
+}
+
+/// 1-indexed line of `image: <image>` inside the `<service>:` block of a Compose file
+fn service_image_line(content: &str, service: &str, image: &str) -> Option<usize> {
+    let lines: Vec<&str> = content.lines().collect();
+    let start = service_line(&lines, service)?;
+    let service_indent = indent(lines[start]);
+    lines[start + 1..]
+        .iter()
+        .take_while(|line| line.trim().is_empty() || indent(line) > service_indent)
+        .position(|line| line.trim_start().starts_with("image:") && line.contains(image))
+        .map(|offset| start + offset + 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_compose_file() {
+        for name in ["docker-compose.yaml", "compose.yml", "docker-compose.override.yml", "docker-compose.gpu.yaml", "compose-dev.yaml"] {
+            assert!(is_compose_file(Path::new(name)), "{}", name);
+        }
+        for name in ["values.yaml", "composer.yaml", "docker-compose.json", "my-compose.yaml"] {
+            assert!(!is_compose_file(Path::new(name)), "{}", name);
+        }
+        let mut files = vec![
+            PathBuf::from("docker-compose.override.yaml"),
+            PathBuf::from("docker-compose.gpu.yaml"),
+            PathBuf::from("docker-compose.yaml"),
+        ];
+        files.sort_by_key(|path| merge_rank(path));
+        assert_eq!(files, vec![
+            PathBuf::from("docker-compose.yaml"),
+            PathBuf::from("docker-compose.gpu.yaml"),
+            PathBuf::from("docker-compose.override.yaml"),
+        ]);
+    }
+
+    #[test]
+    fn test_compose_images() {
+        let content = "\
+x-nim: &nim
+  image: nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3.3
+  restart: always
+x-embed:
+  registry: nvcr.io/nim
+  repository: nvidia/nv-embedqa-e5-v5
+  tag: 1.5
+services:
+  llm:
+    <<: *nim
+  chat:
+    image: nvcr.io/nim/meta/llama-3.1-70b-instruct
+  builder:
+    build:
+      context: .
+      args:
+        - BASE_IMAGE=nvcr.io/nim/nvidia/nemoretriever-parse
+        - BASE_TAG=1.2.0
+  web:
+    image: nginx:1.27
+";
+        let images = compose_images(content).unwrap();
+        let found: Vec<(&str, Option<&str>, &str, usize)> = images.iter()
+            .map(|f| (f.key_path.as_str(), f.service.as_deref(), f.image.as_str(), f.line))
+            .collect();
+        assert_eq!(found, vec![
+            ("services.llm.image", Some("llm"), "nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3.3", 2),
+            ("services.chat.image", Some("chat"), "nvcr.io/nim/meta/llama-3.1-70b-instruct", 12),
+            ("services.builder.build.args", Some("builder"), "nvcr.io/nim/nvidia/nemoretriever-parse:1.2.0", 17),
+            // The anchor's own image is reported for the service using it only
+            ("x-embed", None, "nvcr.io/nim/nvidia/nv-embedqa-e5-v5:1.5", 6),
+        ]);
+        assert_eq!(compose_images("services: [unclosed"), None);
+    }
+
+    #[test]
+    fn test_service_image_line() {
+        let content = "\
+x-defaults:
+  nim:
+    image: nvcr.io/nim/meta/llama-3.1-8b-instruct:1.0.0
+services:
+  web:
+    image: nvcr.io/nim/meta/llama-3.1-8b-instruct:1.0.0
+  nim:
+    profiles: [gpu]
+
+    image: nvcr.io/nim/meta/llama-3.1-8b-instruct:1.0.0
+";
+        assert_eq!(service_image_line(content, "web", "nvcr.io/nim/meta/llama-3.1-8b-instruct:1.0.0"), Some(6));
+        assert_eq!(service_image_line(content, "nim", "nvcr.io/nim/meta/llama-3.1-8b-instruct:1.0.0"), Some(10));
+        assert_eq!(service_image_line(content, "db", "postgres"), None);
+    }
+}
@@ -29,6 +29,21 @@ pub fn load_config<P: AsRef<Path>>(path: P) -> Result<Config> {
     Ok(config)
 }
 
+/// Load a `--files-from` list: one repository-relative path per line
+///
+/// Blank lines and lines starting with `#` are ignored.
+pub fn load_file_list<P: AsRef<Path>>(path: P) -> Result<Vec<String>> {
+    let path = path.as_ref();
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read file list: {}", path.display()))?;
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect())
+}
+
 /// If `repos.githubonly.yaml` exists in the same directory as `path`, merge its repos
 /// into the config (by name: only add extra repos not already present). Writes
 /// the merged config back to `path`. No-op if the extra file does not exist.
@@ -89,6 +104,21 @@ pub enum ValidationError {
     
     #[error("Empty URL for repository '{name}'")]
     EmptyUrl { name: String },
+    
+    #[error("Invalid skip_dirs entry for '{name}': '{entry}' (must be a single directory name)")]
+    InvalidSkipDir { name: String, entry: String },
+    
+    #[error("Invalid only_files entry for '{name}': '{entry}' (must be a path inside the repository)")]
+    InvalidOnlyFile { name: String, entry: String },
+    
+    #[error("Invalid condition for policy '{name}': {reason}")]
+    InvalidPolicy { name: String, reason: String },
+    
+    #[error("Invalid endpoint rule '{path}': {reason}")]
+    InvalidEndpointRule { path: String, reason: String },
+    
+    #[error("Invalid scan.{key}: {reason}")]
+    InvalidScanSetting { key: &'static str, reason: String },
 }
 
 /// Validate the configuration
@@ -98,11 +128,27 @@ pub enum ValidationError {
 /// - Valid URL formats (https:// or git@)
 /// - Unique repository names
 /// - Non-empty names and URLs
+/// - skip_dirs entries are bare directory names (no path separators)
+/// - only_files entries are relative paths that stay inside the repository
+/// - policy conditions parse
+/// - endpoint rule regexes compile
+/// - scan settings are usable
 ///
 /// # Returns
 /// * `Ok(())` if valid
 /// * `Err` with list of validation errors
 pub fn validate_config(config: &Config) -> Result<()> {
+    let errors = validation_errors(config);
+    if !errors.is_empty() {
+        let error_messages: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+        bail!("Configuration validation failed:\n  - {}", error_messages.join("\n  - "));
+    }
+    
+    Ok(())
+}
+
+/// Every problem `validate_config` reports
+fn validation_errors(config: &Config) -> Vec<ValidationError> {
     let mut errors: Vec<ValidationError> = Vec::new();
     
     // Check for empty repo list
@@ -110,6 +156,38 @@ pub fn validate_config(config: &Config) -> Result<()> {
         errors.push(ValidationError::EmptyRepoList);
     }
     
+    // Check default skip_dirs lists
+    let d = &config.defaults;
+    validate_skip_dirs("defaults", d.skip_dirs.as_deref(), &d.skip_dirs_add, &d.skip_dirs_remove, &mut errors);
+    
+    // Check policy conditions
+    for rule in &config.policies {
+        if let Err(e) = crate::policy::Condition::parse(&rule.when) {
+            errors.push(ValidationError::InvalidPolicy {
+                name: rule.name.clone(),
+                reason: e.to_string(),
+            });
+        }
+    }
+    
+    // Check endpoint rules
+    for rule in &config.endpoint_rules {
+        if let Err(e) = crate::policy::EndpointPattern::compile(rule) {
+            errors.push(ValidationError::InvalidEndpointRule {
+                path: rule.path.clone(),
+                reason: e.to_string(),
+            });
+        }
+    }
+    
+    // Check scan settings (unknown keys and values are rejected while parsing)
+    if config.scan.output_formats.as_ref().is_some_and(|formats| formats.is_empty()) {
+        errors.push(ValidationError::InvalidScanSetting {
+            key: "output_formats",
+            reason: "list at least one report artifact".to_string(),
+        });
+    }
+    
     // Track names for duplicate detection
     let mut seen_names = std::collections::HashSet::new();
     
@@ -127,6 +205,17 @@ pub fn validate_config(config: &Config) -> Result<()> {
             });
         }
         
+        // Check skip_dirs lists
+        validate_skip_dirs(&repo.name, repo.skip_dirs.as_deref(), &repo.skip_dirs_add, &repo.skip_dirs_remove, &mut errors);
+        
+        // Check the explicit file list
+        for entry in repo.only_files.iter().filter(|entry| !crate::scanner::is_repo_relative(entry)) {
+            errors.push(ValidationError::InvalidOnlyFile {
+                name: repo.name.clone(),
+                entry: entry.clone(),
+            });
+        }
+        
         // Check for empty URL
         if repo.url.trim().is_empty() {
             errors.push(ValidationError::EmptyUrl {
@@ -144,12 +233,27 @@ pub fn validate_config(config: &Config) -> Result<()> {
         }
     }
     
-    if !errors.is_empty() {
-        let error_messages: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
-        bail!("Configuration validation failed:\n  - {}", error_messages.join("\n  - "));
+    errors
+}
+
+/// Reject skip_dirs entries that are empty or contain path separators
+/// (entries are matched against single path components)
+fn validate_skip_dirs(
+    name: &str,
+    skip_dirs: Option<&[String]>,
+    add: &[String],
+    remove: &[String],
+    errors: &mut Vec<ValidationError>,
+) {
+    let entries = skip_dirs.unwrap_or(&[]).iter().chain(add).chain(remove);
+    for entry in entries {
+        if entry.trim().is_empty() || entry.contains('/') || entry.contains('\\') {
+            errors.push(ValidationError::InvalidSkipDir {
+                name: name.to_string(),
+                entry: entry.clone(),
+            });
+        }
     }
-    
-    Ok(())
 }
 
 /// Check if a URL is a valid Git URL
@@ -186,6 +290,183 @@ pub fn filter_enabled(repos: Vec<RepoConfig>) -> Vec<RepoConfig> {
     repos.into_iter().filter(|r| r.enabled).collect()
 }
 
+// ============================================================================
+// Config Check
+// ============================================================================
+
+impl ValidationError {
+    /// 1-indexed line of `content` the error most likely refers to (found by searching the text)
+    fn line_hint(&self, content: &str) -> Option<usize> {
+        match self {
+            ValidationError::EmptyRepoList => find_value_line(content, "repos", "", 1),
+            ValidationError::InvalidUrl { url, .. } => find_value_line(content, "url", url, 1),
+            // The second occurrence is the duplicate
+            ValidationError::DuplicateName { name } => find_value_line(content, "name", name, 2),
+            ValidationError::EmptyName { .. } => None,
+            ValidationError::EmptyUrl { name }
+            | ValidationError::InvalidPolicy { name, .. } => find_value_line(content, "name", name, 1),
+            ValidationError::InvalidEndpointRule { path, .. } => find_value_line(content, "path", path, 1),
+            ValidationError::InvalidSkipDir { entry, .. }
+            | ValidationError::InvalidOnlyFile { entry, .. } => find_value_line(content, "", entry, 1),
+            ValidationError::InvalidScanSetting { key, .. } => content
+                .lines()
+                .position(|line| line.trim_start().strip_prefix(key).is_some_and(|rest| rest.starts_with(':')))
+                .map(|index| index + 1),
+        }
+    }
+}
+
+/// Line number of the `nth` line holding `key: value` (or a `- value` list item when `key` is empty)
+fn find_value_line(content: &str, key: &str, value: &str, nth: usize) -> Option<usize> {
+    let unquote = |v: &str| v.trim().trim_matches(|c| c == '"' || c == '\'').to_string();
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| {
+            let line = line.trim_start();
+            let item = line.strip_prefix("- ").unwrap_or(line);
+            let found = if key.is_empty() {
+                line.starts_with("- ").then_some(item)
+            } else {
+                item.strip_prefix(key).and_then(|rest| rest.strip_prefix(':'))
+            };
+            found.is_some_and(|v| unquote(v) == value)
+        })
+        .nth(nth - 1)
+        .map(|(index, _)| index + 1)
+}
+
+/// Load and validate a repos.yaml (`config check`)
+///
+/// Returns `OK (N repos, M enabled)`, or an error listing every validation
+/// problem with the line it most likely refers to.
+pub fn check_config<P: AsRef<Path>>(path: P) -> Result<String> {
+    let path = path.as_ref();
+    // Parse errors from serde_yaml already carry a line and column
+    let config = load_config(path)?;
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+
+    let errors = validation_errors(&config);
+    if !errors.is_empty() {
+        let error_messages: Vec<String> = errors
+            .iter()
+            .map(|e| match e.line_hint(&content) {
+                Some(line) => format!("{} (line {})", e, line),
+                None => e.to_string(),
+            })
+            .collect();
+        bail!("{}: configuration validation failed:\n  - {}", path.display(), error_messages.join("\n  - "));
+    }
+
+    let enabled = config.repos.iter().filter(|r| r.enabled).count();
+    Ok(format!("OK ({} repos, {} enabled)", config.repos.len(), enabled))
+}
+
+// ============================================================================
+// Starter Config
+// ============================================================================
+
+/// Top of a starter repos.yaml (`init`)
+const STARTER_HEADER: &str = r#"# Repositories scanned by nim-usage-scanner.
+#
+# Check this file with:  nim-usage-scanner config check <path>
+# Scan with:             nim-usage-scanner scan --config <path>
+version: "1.0"
+
+# Applied to every repository that doesn't set its own value
+defaults:
+  # Branch to clone
+  branch: main
+  # Clone depth (1 = latest commit only)
+  depth: 1
+  # Extra directory names to skip (on top of the built-in list: node_modules, .git, vendor, ...)
+  # skip_dirs_add: [fixtures]
+
+# Optional policy rules, evaluated before the built-in defaults (first match wins)
+# policies:
+#   - name: no-latest-in-workflows
+#     severity: error
+#     when: "nim_type == local_nim && source_type == actions_workflow && tag == latest"
+
+# Optional endpoint path rules, checked before the built-in table (first match wins)
+# endpoint_rules:
+#   - path: "^/v1/completions$"
+#     model: "(?i)instruct|chat"
+#     status: deprecated
+#     suggested: /v1/chat/completions
+
+# Optional scan options; the matching command-line flags take precedence
+# scan:
+#   output_formats: [detail-json, detail-csv, aggregate-json, triage]
+#   enrich: [local, hosted]
+#   fail_on:
+#     severity: error
+
+repos:
+"#;
+
+/// Example entries used when no repositories were discovered
+const STARTER_EXAMPLE_REPOS: &str = r#"  # Uses the defaults above
+  - name: my-org/my-app
+    url: https://github.com/my-org/my-app.git
+
+  # Overrides: another branch, a deeper clone, more skipped directories,
+  # a tarball download and a size limit
+  - name: my-org/my-service
+    url: https://github.com/my-org/my-service.git
+    branch: develop
+    depth: 5
+    skip_dirs_add: [testdata]
+    acquire: tarball
+    max_repo_size_mb: 500
+
+  # Listed but not scanned
+  - name: my-org/legacy-tools
+    url: git@github.com:my-org/legacy-tools.git
+    enabled: false
+"#;
+
+/// Text of a commented starter repos.yaml
+///
+/// With discovered repositories (`init --github-org`) those are listed instead
+/// of the examples.
+pub fn starter_config(discovered: &[RepoConfig]) -> String {
+    let mut yaml = STARTER_HEADER.to_string();
+    if discovered.is_empty() {
+        yaml.push_str(STARTER_EXAMPLE_REPOS);
+        return yaml;
+    }
+    for repo in discovered {
+        if !repo.enabled {
+            yaml.push_str("  # Archived on GitHub\n");
+        }
+        yaml.push_str(&format!("  - name: {:?}\n    url: {:?}\n", repo.name, repo.url));
+        if let Some(branch) = &repo.branch {
+            yaml.push_str(&format!("    branch: {:?}\n", branch));
+        }
+        if !repo.enabled {
+            yaml.push_str("    enabled: false\n");
+        }
+    }
+    yaml
+}
+
+/// Write a starter repos.yaml, refusing to replace an existing file unless `force` is set
+pub fn write_starter_config(path: &Path, discovered: &[RepoConfig], force: bool) -> Result<()> {
+    if path.exists() && !force {
+        bail!("{} already exists (use --force to overwrite it)", path.display());
+    }
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+    std::fs::write(path, starter_config(discovered))
+        .with_context(|| format!("Failed to write config file: {}", path.display()))?;
+    log::info!("Wrote starter config to {}", path.display());
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -209,6 +490,9 @@ mod tests {
             version: "1.0".to_string(),
             defaults: Defaults::default(),
             repos: vec![],
+            policies: vec![],
+            endpoint_rules: vec![],
+            scan: Default::default(),
         };
         
         assert!(validate_config(&config).is_err());
@@ -226,6 +510,7 @@ mod tests {
                     branch: None,
                     depth: None,
                     enabled: true,
+                    ..Default::default()
                 },
                 RepoConfig {
                     name: "test".to_string(),
@@ -233,8 +518,12 @@ mod tests {
                     branch: None,
                     depth: None,
                     enabled: true,
+                    ..Default::default()
                 },
             ],
+            policies: vec![],
+            endpoint_rules: vec![],
+            scan: Default::default(),
         };
         
         assert!(validate_config(&config).is_err());
@@ -252,6 +541,7 @@ mod tests {
                     branch: None,
                     depth: None,
                     enabled: true,
+                    ..Default::default()
                 },
                 RepoConfig {
                     name: "repo2".to_string(),
@@ -259,13 +549,105 @@ mod tests {
                     branch: Some("develop".to_string()),
                     depth: Some(5),
                     enabled: true,
+                    ..Default::default()
                 },
             ],
+            policies: vec![],
+            endpoint_rules: vec![],
+            scan: Default::default(),
         };
         
         assert!(validate_config(&config).is_ok());
     }
 
+    #[test]
+    fn test_validate_skip_dirs_rejects_paths() {
+        let config = Config {
+            version: "1.0".to_string(),
+            defaults: Defaults {
+                skip_dirs_add: vec!["fixtures".to_string()],
+                ..Default::default()
+            },
+            repos: vec![
+                RepoConfig {
+                    name: "repo1".to_string(),
+                    url: "https://github.com/test/repo1.git".to_string(),
+                    skip_dirs_remove: vec!["deploy/dist".to_string()],
+                    ..Default::default()
+                },
+            ],
+            policies: vec![],
+            endpoint_rules: vec![],
+            scan: Default::default(),
+        };
+        let err = validate_config(&config).unwrap_err().to_string();
+        assert!(err.contains("deploy/dist"));
+        assert!(!err.contains("'fixtures'"));
+    }
+
+    #[test]
+    fn test_validate_only_files_rejects_escapes() {
+        let config = Config {
+            version: "1.0".to_string(),
+            defaults: Default::default(),
+            repos: vec![
+                RepoConfig {
+                    name: "repo1".to_string(),
+                    url: "https://github.com/test/repo1.git".to_string(),
+                    only_files: vec![
+                        ".github/workflows/ci.yml".to_string(),
+                        "../other/app.py".to_string(),
+                        "/etc/passwd".to_string(),
+                    ],
+                    ..Default::default()
+                },
+            ],
+            policies: vec![],
+            endpoint_rules: vec![],
+            scan: Default::default(),
+        };
+        let err = validate_config(&config).unwrap_err().to_string();
+        assert!(err.contains("'../other/app.py'"), "{}", err);
+        assert!(err.contains("'/etc/passwd'"), "{}", err);
+        assert!(!err.contains("ci.yml"), "{}", err);
+    }
+
+    #[test]
+    fn test_load_file_list() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let list = temp_dir.path().join("files.txt");
+        std::fs::write(&list, "# changed in this PR\n.github/workflows/ci.yml\n\n  src/app.py  \n").unwrap();
+        assert_eq!(load_file_list(&list).unwrap(), vec![".github/workflows/ci.yml", "src/app.py"]);
+    }
+
+    #[test]
+    fn test_apply_defaults_skip_dirs() {
+        let config = Config {
+            version: "1.0".to_string(),
+            defaults: Defaults {
+                branch: "main".to_string(),
+                depth: 1,
+                skip_dirs_add: vec!["fixtures".to_string()],
+                ..Default::default()
+            },
+            repos: vec![
+                RepoConfig {
+                    name: "repo1".to_string(),
+                    url: "https://github.com/test/repo1.git".to_string(),
+                    skip_dirs_remove: vec!["dist".to_string()],
+                    ..Default::default()
+                },
+            ],
+            policies: vec![],
+            endpoint_rules: vec![],
+            scan: Default::default(),
+        };
+        let repos = apply_defaults(&config);
+        assert_eq!(repos[0].skip_dirs_add, vec!["fixtures".to_string()]);
+        assert_eq!(repos[0].skip_dirs_remove, vec!["dist".to_string()]);
+        assert!(repos[0].skip_dirs.is_none());
+    }
+
     #[test]
     fn test_apply_defaults() {
         let config = Config {
@@ -273,6 +655,7 @@ mod tests {
             defaults: Defaults {
                 branch: "develop".to_string(),
                 depth: 10,
+                ..Default::default()
             },
             repos: vec![
                 RepoConfig {
@@ -281,6 +664,7 @@ mod tests {
                     branch: None,
                     depth: None,
                     enabled: true,
+                    ..Default::default()
                 },
                 RepoConfig {
                     name: "repo2".to_string(),
@@ -288,8 +672,12 @@ mod tests {
                     branch: Some("main".to_string()),
                     depth: Some(1),
                     enabled: true,
+                    ..Default::default()
                 },
             ],
+            policies: vec![],
+            endpoint_rules: vec![],
+            scan: Default::default(),
         };
         
         let repos = apply_defaults(&config);
@@ -309,6 +697,7 @@ mod tests {
                 branch: None,
                 depth: None,
                 enabled: true,
+                ..Default::default()
             },
             RepoConfig {
                 name: "disabled".to_string(),
@@ -316,6 +705,7 @@ mod tests {
                 branch: None,
                 depth: None,
                 enabled: false,
+                ..Default::default()
             },
         ];
         
@@ -323,4 +713,239 @@ mod tests {
         assert_eq!(filtered.len(), 1);
         assert_eq!(filtered[0].name, "enabled");
     }
+
+    /// Serve canned JSON by request path (404 for unknown paths) until the test exits
+    fn serve_routes(routes: Vec<(&'static str, &'static str)>) -> String {
+        use std::io::{Read, Write};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut buf = [0u8; 4096];
+                let mut request = Vec::new();
+                while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                    let n = stream.read(&mut buf).unwrap();
+                    if n == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buf[..n]);
+                }
+                let request = String::from_utf8_lossy(&request);
+                let path = request.split_whitespace().nth(1).unwrap_or("");
+                let path = path.split('?').next().unwrap_or("");
+                let (status, body) = match routes.iter().find(|(p, _)| *p == path) {
+                    Some((_, body)) => ("200 OK", *body),
+                    None => ("404 Not Found", "{}"),
+                };
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    #[test]
+    fn test_starter_config_parses() {
+        let config: Config = serde_yaml::from_str(&starter_config(&[])).unwrap();
+        validate_config(&config).unwrap();
+        assert_eq!(config.repos.len(), 3);
+        assert_eq!(filter_enabled(apply_defaults(&config)).len(), 2);
+        let service = &config.repos[1];
+        assert_eq!(service.branch.as_deref(), Some("develop"));
+        assert_eq!(service.max_repo_size_mb, Some(500));
+
+        let discovered = vec![
+            RepoConfig {
+                name: "acme/app".to_string(),
+                url: "https://github.com/acme/app.git".to_string(),
+                branch: Some("master".to_string()),
+                ..Default::default()
+            },
+            RepoConfig {
+                name: "acme/old".to_string(),
+                url: "https://github.com/acme/old.git".to_string(),
+                enabled: false,
+                ..Default::default()
+            },
+        ];
+        let yaml = starter_config(&discovered);
+        assert!(!yaml.contains("my-org/"));
+        let config: Config = serde_yaml::from_str(&yaml).unwrap();
+        validate_config(&config).unwrap();
+        assert_eq!(config.repos[0].branch.as_deref(), Some("master"));
+        assert!(!config.repos[1].enabled);
+    }
+
+    #[test]
+    fn test_write_starter_config_refuses_overwrite() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("config/repos.yaml");
+        write_starter_config(&path, &[], false).unwrap();
+        assert_eq!(check_config(&path).unwrap(), "OK (3 repos, 2 enabled)");
+
+        std::fs::write(&path, "edited").unwrap();
+        assert!(write_starter_config(&path, &[], false).is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "edited");
+        write_starter_config(&path, &[], true).unwrap();
+        assert!(check_config(&path).is_ok());
+    }
+
+    #[test]
+    fn test_check_config_reports_lines() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("repos.yaml");
+        std::fs::write(&path, "\
+version: \"1.0\"
+repos:
+  - name: acme/app
+    url: https://github.com/acme/app.git
+  - name: acme/tools
+    url: ftp://example.com/tools.git
+    skip_dirs_add:
+      - fixtures/data
+  - name: acme/app
+    url: https://github.com/acme/app2.git
+").unwrap();
+
+        let message = format!("{:#}", check_config(&path).unwrap_err());
+        assert!(message.contains("Invalid URL for repository 'acme/tools': ftp://example.com/tools.git (line 6)"), "{}", message);
+        assert!(message.contains("'fixtures/data' (must be a single directory name) (line 8)"), "{}", message);
+        assert!(message.contains("Duplicate repository name: acme/app (line 9)"), "{}", message);
+
+        // Syntax errors keep serde_yaml's position
+        std::fs::write(&path, "version: \"1.0\"\nrepos:\n  - name: [unclosed\n").unwrap();
+        let message = format!("{:#}", check_config(&path).unwrap_err());
+        assert!(message.contains("line"), "{}", message);
+    }
+
+    #[test]
+    fn test_scan_settings_parse() {
+        use crate::models::{EnrichStage, ScanScope, Severity};
+        use crate::report::ReportArtifact;
+
+        let config: Config = serde_yaml::from_str("\
+version: \"1.0\"
+repos:
+  - name: acme/app
+    url: https://github.com/acme/app.git
+scan:
+  output_formats: [detail-json, aggregate-csv]
+  enrich: [hosted, image-meta]
+  capture_call_params: true
+  scope: ci
+  max_depth: 0
+  fail_on:
+    severity: warning
+    staging_in_source: true
+").unwrap();
+        let scan = &config.scan;
+        assert_eq!(scan.output_formats, Some(vec![ReportArtifact::DetailJson, ReportArtifact::AggregateCsv]));
+        assert_eq!(scan.enrich, Some(vec![EnrichStage::Hosted, EnrichStage::ImageMeta]));
+        assert_eq!(scan.capture_call_params, Some(true));
+        assert_eq!(scan.scope, Some(ScanScope::Workflows));
+        assert_eq!(scan.max_depth, Some(0));
+        assert_eq!(scan.max_files_per_repo, None);
+        assert_eq!(scan.fail_on.severity, Some(Severity::Warning));
+        assert_eq!(scan.fail_on.staging_in_source, Some(true));
+        assert_eq!(scan.fail_on.policy_violations, None);
+
+        // Without a scan section nothing is set, and none is written back
+        let config: Config = serde_yaml::from_str("version: \"1.0\"\nrepos: []\n").unwrap();
+        assert!(config.scan.is_empty());
+        assert!(!serde_yaml::to_string(&config).unwrap().contains("scan"));
+    }
+
+    #[test]
+    fn test_scan_settings_rejects_unknown_values() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("repos.yaml");
+        let check = |scan: &str| {
+            std::fs::write(&path, format!("\
+version: \"1.0\"
+repos:
+  - name: acme/app
+    url: https://github.com/acme/app.git
+scan:
+{}
+", scan)).unwrap();
+            format!("{:#}", check_config(&path).unwrap_err())
+        };
+
+        let message = check("  enrich: [local, remote]");
+        assert!(message.contains("unknown variant `remote`, expected one of `local`, `hosted`, `image-meta`"), "{}", message);
+        assert!(message.contains("line 6"), "{}", message);
+
+        let message = check("  fail_on:\n    severity: fatal");
+        assert!(message.contains("unknown variant `fatal`, expected one of `info`, `warning`, `error`"), "{}", message);
+
+        let message = check("  output_formats: [report-json]");
+        assert!(message.contains("unknown variant `report-json`"), "{}", message);
+
+        // Misspelled keys are errors, not silently ignored
+        let message = check("  max_dept: 4");
+        assert!(message.contains("unknown field `max_dept`"), "{}", message);
+
+        let message = check("  output_formats: []");
+        assert!(message.contains("Invalid scan.output_formats: list at least one report artifact (line 6)"), "{}", message);
+    }
+
+    #[test]
+    fn test_generated_config_uses_github_default_branches() {
+        let org = "NVIDIA-AI-Blueprints";
+        let base = serve_routes(vec![
+            ("/v2/search/catalog/resources/BLUEPRINT", r#"{"resultTotal": 4, "results": [{"resources": [
+                {"orgName": "bp", "name": "main-repo"},
+                {"orgName": "bp", "name": "master-repo"},
+                {"orgName": "bp", "name": "archived-repo"},
+                {"orgName": "bp", "name": "unknown-repo"}
+            ]}]}"#),
+            ("/v2/blueprints/bp/main-repo/spec", r#"{"cta": {"text": "View GitHub", "url": "https://github.com/NVIDIA-AI-Blueprints/main-repo"}}"#),
+            ("/v2/blueprints/bp/master-repo/spec", r#"{"cta": {"text": "View GitHub", "url": "https://github.com/NVIDIA-AI-Blueprints/master-repo"}}"#),
+            ("/v2/blueprints/bp/archived-repo/spec", r#"{"cta": {"text": "View GitHub", "url": "https://github.com/NVIDIA-AI-Blueprints/archived-repo"}}"#),
+            ("/v2/blueprints/bp/unknown-repo/spec", r#"{"cta": {"text": "View GitHub", "url": "https://github.com/NVIDIA-AI-Blueprints/unknown-repo"}}"#),
+            ("/repos/NVIDIA-AI-Blueprints/main-repo", r#"{"default_branch": "main", "archived": false}"#),
+            ("/repos/NVIDIA-AI-Blueprints/master-repo", r#"{"default_branch": "master", "archived": false}"#),
+            ("/repos/NVIDIA-AI-Blueprints/archived-repo", r#"{"default_branch": "release", "archived": true}"#),
+        ]);
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let output = temp_dir.path().join("repos.yaml");
+        let status = std::process::Command::new("python3")
+            .arg(concat!(env!("CARGO_MANIFEST_DIR"), "/scripts/generate_repos_from_ngc.py"))
+            .args(["--org", "bp", "--workers", "2", "--ngc-api-url", &base, "--github-api-url", &base])
+            .arg("--output")
+            .arg(&output)
+            .env_remove("GITHUB_TOKEN")
+            .stdout(std::process::Stdio::null())
+            .status()
+            .expect("running scripts/generate_repos_from_ngc.py needs python3");
+        assert!(status.success());
+
+        let yaml = std::fs::read_to_string(&output).unwrap();
+        assert!(yaml.contains("# Archived on GitHub"));
+        let config = load_config(&output).unwrap();
+        validate_config(&config).unwrap();
+        let repo = |name: &str| {
+            let full = format!("{}/{}", org, name);
+            config.repos.iter().find(|r| r.name == full).unwrap().clone()
+        };
+
+        assert_eq!(repo("main-repo").branch.as_deref(), Some("main"));
+        assert_eq!(repo("master-repo").branch.as_deref(), Some("master"));
+        assert_eq!(repo("archived-repo").branch.as_deref(), Some("release"));
+        assert!(repo("master-repo").enabled);
+        assert!(!repo("archived-repo").enabled);
+        // Metadata lookup failed: no branch field, defaults.branch applies
+        assert_eq!(repo("unknown-repo").branch, None);
+        assert!(repo("unknown-repo").enabled);
+        assert_eq!(filter_enabled(apply_defaults(&config)).len(), 3);
+    }
 }
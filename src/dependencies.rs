@@ -0,0 +1,611 @@
+//! NVIDIA AI endpoint SDKs declared in dependency manifests
+//!
+//! A repository depending on `langchain-nvidia-ai-endpoints` or
+//! `llama-index-llms-nvidia` calls NVIDIA endpoints even when no model name
+//! survives in its code (a model picked at runtime, or the SDK's default).
+//! Manifests are read with the parser of their format rather than line
+//! patterns, so extras, optional dependencies and Poetry groups come out as
+//! the package manager sees them:
+//!
+//! - requirements files (`.txt` / `.in` named `*requirements*`, or under `requirements/`)
+//! - `pyproject.toml`: PEP 621 `project` dependencies and extras, PEP 735
+//!   `dependency-groups`, Poetry dependencies and groups, PDM dev dependencies
+//! - `setup.cfg`: `install_requires`, `tests_require` and `extras_require`
+//! - `package.json`: dependencies, dev, peer and optional dependencies
+//! - Conda `environment*.yml`, including its `pip:` list
+//!
+//! The `openai` package talks to any OpenAI-compatible API, so it is only
+//! reported for a repository whose code points an OpenAI client at an NVIDIA
+//! endpoint. Parsed formats carry no positions; the line of a declaration is
+//! the first line naming the package that no earlier declaration took.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use anyhow::Result;
+use log::debug;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::models::{Framework, HostedNimMatch, PackageEcosystem, SdkDependencyMatch};
+
+/// Packages that only talk to NVIDIA endpoints (normalized PyPI / Conda names)
+const NVIDIA_SDK_PACKAGES: &[(&str, Framework)] = &[
+    ("langchain-nvidia-ai-endpoints", Framework::Langchain),
+    ("llama-index-llms-nvidia", Framework::LlamaIndex),
+    ("llama-index-embeddings-nvidia", Framework::LlamaIndex),
+    ("llama-index-postprocessor-nvidia-rerank", Framework::LlamaIndex),
+    ("nvidia-haystack", Framework::Haystack),
+];
+
+/// The OpenAI SDK (PyPI and npm), reported only alongside an NVIDIA-pointed client
+const OPENAI_PACKAGE: &str = "openai";
+
+/// `package.json` keys holding dependencies
+const NPM_SECTIONS: &[&str] = &["dependencies", "devDependencies", "peerDependencies", "optionalDependencies"];
+
+/// PEP 508 requirement: name, optional `[extras]`, then the version (or `@ url`) up to the markers
+static REQUIREMENT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*([A-Za-z0-9](?:[A-Za-z0-9._-]*[A-Za-z0-9])?)\s*(?:\[([^\]]*)\])?\s*([^;]*)")
+        .expect("Invalid REQUIREMENT regex")
+});
+
+/// Kinds of dependency manifest
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Manifest {
+    Requirements,
+    Pyproject,
+    SetupCfg,
+    PackageJson,
+    CondaEnvironment,
+}
+
+impl Manifest {
+    fn of(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?.to_lowercase();
+        let in_requirements_dir = path.parent().and_then(|dir| dir.file_name()).is_some_and(|dir| dir == "requirements");
+        match name.as_str() {
+            "pyproject.toml" => Some(Manifest::Pyproject),
+            "setup.cfg" => Some(Manifest::SetupCfg),
+            "package.json" => Some(Manifest::PackageJson),
+            _ if (name.ends_with(".txt") || name.ends_with(".in")) && (name.contains("requirements") || in_requirements_dir) => {
+                Some(Manifest::Requirements)
+            }
+            _ if name.starts_with("environment") && (name.ends_with(".yml") || name.ends_with(".yaml")) => {
+                Some(Manifest::CondaEnvironment)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Whether a file is a dependency manifest this module reads
+pub fn is_manifest(path: &Path) -> bool {
+    Manifest::of(path).is_some()
+}
+
+/// One dependency as the manifest declares it
+#[derive(Debug, Clone, PartialEq)]
+struct Declared {
+    name: String,
+    ecosystem: PackageEcosystem,
+    constraint: Option<String>,
+    extras: Vec<String>,
+    section: Option<String>,
+    /// 0-indexed line, when the format gives it
+    line: Option<usize>,
+}
+
+/// PyPI name normalization (PEP 503): lowercase, runs of `-`, `_`, `.` become `-`
+fn normalize_name(name: &str) -> String {
+    let mut normalized = String::with_capacity(name.len());
+    for c in name.chars() {
+        match c {
+            '-' | '_' | '.' if normalized.ends_with('-') => {}
+            '-' | '_' | '.' => normalized.push('-'),
+            c => normalized.push(c.to_ascii_lowercase()),
+        }
+    }
+    normalized
+}
+
+/// Framework of a package this module reports
+fn sdk_framework(ecosystem: PackageEcosystem, name: &str) -> Option<Framework> {
+    let name = match ecosystem {
+        PackageEcosystem::Npm => name.to_string(),
+        PackageEcosystem::Pypi | PackageEcosystem::Conda => normalize_name(name),
+    };
+    if name == OPENAI_PACKAGE {
+        return Some(Framework::OpenaiSdk);
+    }
+    match ecosystem {
+        PackageEcosystem::Npm => None,
+        PackageEcosystem::Pypi | PackageEcosystem::Conda => {
+            NVIDIA_SDK_PACKAGES.iter().find(|(package, _)| *package == name).map(|(_, framework)| *framework)
+        }
+    }
+}
+
+/// Parse a PEP 508 requirement (`name[extra]>=1.0; python_version > "3.8"`)
+fn requirement(spec: &str, ecosystem: PackageEcosystem, section: Option<&str>, line: Option<usize>) -> Option<Declared> {
+    let caps = REQUIREMENT.captures(spec)?;
+    let rest = caps[3].trim();
+    // A bare URL is no requirement
+    if rest.starts_with(':') {
+        return None;
+    }
+    let constraint = rest.trim_start_matches('(').trim_end_matches(')').trim();
+    Some(Declared {
+        name: caps[1].to_string(),
+        ecosystem,
+        constraint: (!constraint.is_empty()).then(|| constraint.to_string()),
+        extras: caps.get(2)
+            .map(|extras| extras.as_str().split(',').map(str::trim).filter(|e| !e.is_empty()).map(String::from).collect())
+            .unwrap_or_default(),
+        section: section.map(String::from),
+        line,
+    })
+}
+
+/// `text` up to a `#` comment (one at the start, or after whitespace)
+fn strip_comment(text: &str) -> &str {
+    if text.trim_start().starts_with('#') {
+        return "";
+    }
+    match text.find(" #").or_else(|| text.find("\t#")) {
+        Some(at) => &text[..at],
+        None => text,
+    }
+}
+
+/// Requirements file: one requirement per logical line (`\` continues a line)
+fn requirements(content: &str) -> Vec<Declared> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut declared = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let start = i;
+        let mut text = String::new();
+        while i < lines.len() {
+            let line = lines[i];
+            i += 1;
+            match line.trim_end().strip_suffix('\\') {
+                Some(continued) => {
+                    text.push_str(continued);
+                    text.push(' ');
+                }
+                None => {
+                    text.push_str(line);
+                    break;
+                }
+            }
+        }
+        let text = strip_comment(&text).trim();
+        // Options (`-r`, `-e`, `--index-url`) name no package of the file's own
+        if text.is_empty() || text.starts_with('-') {
+            continue;
+        }
+        // Per-requirement options such as `--hash=...`
+        let text = text.split(" --").next().unwrap_or(text);
+        declared.extend(requirement(text, PackageEcosystem::Pypi, None, Some(start)));
+    }
+    declared
+}
+
+/// Table at a dotted key path
+fn toml_table<'a>(doc: &'a toml::Table, path: &[&str]) -> Option<&'a toml::Table> {
+    let mut table = doc;
+    for key in path {
+        table = table.get(*key)?.as_table()?;
+    }
+    Some(table)
+}
+
+/// Array of PEP 508 strings (PEP 621, PEP 735, PDM); entries that aren't strings are skipped
+fn pep508_array(value: Option<&toml::Value>, section: &str, declared: &mut Vec<Declared>) {
+    let specs = value.and_then(|v| v.as_array()).into_iter().flatten().filter_map(|v| v.as_str());
+    declared.extend(specs.filter_map(|spec| requirement(spec, PackageEcosystem::Pypi, Some(section), None)));
+}
+
+/// Poetry dependency table: `name = "^1.0"`, `name = { version = "^1.0", extras = [...] }`
+/// or a list of such tables with markers
+fn poetry_table(table: Option<&toml::Table>, section: &str, declared: &mut Vec<Declared>) {
+    let version = |value: &toml::Value| -> Option<String> {
+        let version = match value {
+            toml::Value::String(version) => version.as_str(),
+            toml::Value::Table(table) => table.get("version")?.as_str()?,
+            _ => return None,
+        };
+        (!version.is_empty() && version != "*").then(|| version.to_string())
+    };
+    for (name, value) in table.into_iter().flatten() {
+        if name == "python" {
+            continue;
+        }
+        let alternatives: Vec<&toml::Value> = match value {
+            toml::Value::Array(items) => items.iter().collect(),
+            value => vec![value],
+        };
+        let versions: Vec<String> = alternatives.iter().filter_map(|value| version(value)).collect();
+        let mut extras: Vec<String> = alternatives.iter()
+            .filter_map(|value| value.get("extras")?.as_array())
+            .flatten()
+            .filter_map(|extra| extra.as_str().map(String::from))
+            .collect();
+        extras.dedup();
+        declared.push(Declared {
+            name: name.clone(),
+            ecosystem: PackageEcosystem::Pypi,
+            constraint: (!versions.is_empty()).then(|| versions.join(", ")),
+            extras,
+            section: Some(section.to_string()),
+            line: None,
+        });
+    }
+}
+
+/// `pyproject.toml`: PEP 621, PEP 735, Poetry and PDM dependency lists
+fn pyproject(content: &str) -> Result<Vec<Declared>> {
+    let doc: toml::Table = content.parse()?;
+    let mut declared = Vec::new();
+    pep508_array(toml_table(&doc, &["project"]).and_then(|t| t.get("dependencies")), "project.dependencies", &mut declared);
+    for (list_path, key_prefix) in [
+        (&["project", "optional-dependencies"][..], "project.optional-dependencies"),
+        (&["dependency-groups"][..], "dependency-groups"),
+        (&["tool", "pdm", "dev-dependencies"][..], "tool.pdm.dev-dependencies"),
+    ] {
+        for (name, list) in toml_table(&doc, list_path).into_iter().flatten() {
+            pep508_array(Some(list), &format!("{}.{}", key_prefix, name), &mut declared);
+        }
+    }
+    for path in [&["tool", "poetry", "dependencies"][..], &["tool", "poetry", "dev-dependencies"][..]] {
+        poetry_table(toml_table(&doc, path), &path.join("."), &mut declared);
+    }
+    for (group, table) in toml_table(&doc, &["tool", "poetry", "group"]).into_iter().flatten() {
+        let dependencies = table.get("dependencies").and_then(|d| d.as_table());
+        poetry_table(dependencies, &format!("tool.poetry.group.{}.dependencies", group), &mut declared);
+    }
+    Ok(declared)
+}
+
+/// `setup.cfg`: the requirement lists of `[options]` and `[options.extras_require]`
+fn setup_cfg(content: &str) -> Vec<Declared> {
+    let mut declared = Vec::new();
+    let mut section = String::new();
+    let mut key: Option<String> = None;
+    for (i, line) in content.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with(';') {
+            continue;
+        }
+        if let Some(name) = trimmed.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+            section = name.trim().to_lowercase();
+            key = None;
+            continue;
+        }
+        // Indented lines continue the value of the last key
+        let value = match (line.starts_with(char::is_whitespace), &key) {
+            (true, Some(_)) => trimmed,
+            _ => {
+                let Some((name, value)) = trimmed.split_once('=') else {
+                    key = None;
+                    continue;
+                };
+                key = Some(name.trim().to_lowercase());
+                value.trim()
+            }
+        };
+        let list = match (section.as_str(), key.as_deref()) {
+            ("options", Some(name @ ("install_requires" | "tests_require"))) => format!("options.{}", name),
+            ("options.extras_require", Some(extra)) => format!("options.extras_require.{}", extra),
+            _ => continue,
+        };
+        let value = strip_comment(value).trim();
+        if !value.is_empty() {
+            declared.extend(requirement(value, PackageEcosystem::Pypi, Some(&list), Some(i)));
+        }
+    }
+    declared
+}
+
+/// `package.json`: every dependency map
+fn package_json(content: &str) -> Result<Vec<Declared>> {
+    let doc: serde_json::Value = serde_json::from_str(content)?;
+    let mut declared = Vec::new();
+    for section in NPM_SECTIONS {
+        for (name, version) in doc.get(section).and_then(|d| d.as_object()).into_iter().flatten() {
+            let version = version.as_str().unwrap_or("").trim();
+            declared.push(Declared {
+                name: name.clone(),
+                ecosystem: PackageEcosystem::Npm,
+                constraint: (!version.is_empty() && version != "*").then(|| version.to_string()),
+                extras: Vec::new(),
+                section: Some(section.to_string()),
+                line: None,
+            });
+        }
+    }
+    Ok(declared)
+}
+
+/// Conda `environment.yml`: `dependencies` (Conda match specs) and its `pip:` list
+fn conda_environment(content: &str) -> Result<Vec<Declared>> {
+    let doc: serde_yaml::Value = serde_yaml::from_str(content)?;
+    let mut declared = Vec::new();
+    for dependency in doc.get("dependencies").and_then(|d| d.as_sequence()).into_iter().flatten() {
+        if let Some(spec) = dependency.as_str() {
+            // `conda-forge::name=1.0`: the channel is not part of the name
+            let spec = spec.rsplit_once("::").map(|(_, spec)| spec).unwrap_or(spec);
+            declared.extend(requirement(spec, PackageEcosystem::Conda, Some("dependencies"), None));
+        } else if let Some(pip) = dependency.get("pip").and_then(|p| p.as_sequence()) {
+            let specs = pip.iter().filter_map(|spec| spec.as_str());
+            declared.extend(specs.filter_map(|spec| requirement(spec, PackageEcosystem::Pypi, Some("dependencies.pip"), None)));
+        }
+    }
+    Ok(declared)
+}
+
+/// Whether `line` names `name` as a whole package name (not a prefix of a longer one)
+fn names_package(line: &str, name: &str) -> bool {
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.');
+    line.match_indices(name).any(|(at, _)| {
+        !line[..at].chars().next_back().is_some_and(is_name_char)
+            && !line[at + name.len()..].chars().next().is_some_and(is_name_char)
+    })
+}
+
+/// SDK dependencies declared in one manifest (`openai` included; see [`scan_manifests`])
+fn scan_manifest(path: &Path, content: &str, relative_path: &str, repository: &str) -> Vec<SdkDependencyMatch> {
+    let Some(manifest) = Manifest::of(path) else {
+        return Vec::new();
+    };
+    let declared = match manifest {
+        Manifest::Requirements => Ok(requirements(content)),
+        Manifest::Pyproject => pyproject(content),
+        Manifest::SetupCfg => Ok(setup_cfg(content)),
+        Manifest::PackageJson => package_json(content),
+        Manifest::CondaEnvironment => conda_environment(content),
+    };
+    let declared = match declared {
+        Ok(declared) => declared,
+        Err(e) => {
+            debug!("[{}] Not reading dependencies of {}: {}", repository, relative_path, e);
+            return Vec::new();
+        }
+    };
+    let lines: Vec<&str> = content.lines().collect();
+    let mut taken: HashSet<usize> = HashSet::new();
+    let mut matches = Vec::new();
+    for dependency in declared {
+        let Some(framework) = sdk_framework(dependency.ecosystem, &dependency.name) else {
+            continue;
+        };
+        // Several packages may share a line (`extra = ["a", "b"]`), so a taken line is the fallback
+        let naming: Vec<usize> = lines.iter().enumerate()
+            .filter(|(_, line)| !line.trim_start().starts_with('#') && names_package(line, &dependency.name))
+            .map(|(i, _)| i)
+            .collect();
+        let line = dependency.line
+            .or_else(|| naming.iter().copied().find(|i| !taken.contains(i)))
+            .or_else(|| naming.first().copied())
+            .unwrap_or_default();
+        taken.insert(line);
+        matches.push(SdkDependencyMatch {
+            repository: repository.to_string(),
+            package_name: dependency.name,
+            ecosystem: dependency.ecosystem,
+            version_constraint: dependency.constraint,
+            extras: dependency.extras,
+            section: dependency.section,
+            framework,
+            file_path: relative_path.to_string(),
+            line_number: line + 1,
+            match_context: lines.get(line).map(|l| l.trim().to_string()).unwrap_or_default(),
+            fixture: false,
+        });
+    }
+    matches.sort_by_key(|m| m.line_number);
+    matches
+}
+
+/// SDK dependencies declared in the manifests among a repository's collected files
+///
+/// `openai` is kept only when `hosted` has a finding made through an OpenAI
+/// client, i.e. one pointed at an NVIDIA endpoint.
+pub fn scan_manifests(files: &[PathBuf], repository: &str, repo_root: &Path, hosted: &[HostedNimMatch]) -> Vec<SdkDependencyMatch> {
+    let nvidia_openai_client = hosted.iter().any(|m| m.framework == Framework::OpenaiSdk);
+    let mut matches = Vec::new();
+    for path in files.iter().filter(|path| is_manifest(path)) {
+        let relative_path = path.strip_prefix(repo_root).unwrap_or(path).to_string_lossy().to_string();
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) => {
+                debug!("[{}] Failed to read {}: {}", repository, relative_path, e);
+                continue;
+            }
+        };
+        matches.extend(
+            scan_manifest(path, &content, &relative_path, repository)
+                .into_iter()
+                .filter(|m| m.framework != Framework::OpenaiSdk || nvidia_openai_client),
+        );
+    }
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn scan(file_path: &str, content: &str) -> Vec<SdkDependencyMatch> {
+        scan_manifest(Path::new(file_path), content, file_path, "org/repo")
+    }
+
+    /// (package, constraint, section, line) of each match
+    fn summary(matches: &[SdkDependencyMatch]) -> Vec<(&str, Option<&str>, Option<&str>, usize)> {
+        matches.iter()
+            .map(|m| (m.package_name.as_str(), m.version_constraint.as_deref(), m.section.as_deref(), m.line_number))
+            .collect()
+    }
+
+    #[test]
+    fn test_manifest_kinds() {
+        for path in [
+            "requirements.txt", "requirements-dev.txt", "dev-requirements.in", "requirements/base.txt",
+            "pyproject.toml", "setup.cfg", "web/package.json", "environment.yml", "environment-gpu.yaml",
+        ] {
+            assert!(is_manifest(Path::new(path)), "{}", path);
+        }
+        for path in ["notes.txt", "docs/requirements.md", "package-lock.json", "tsconfig.json", "env.yml"] {
+            assert!(!is_manifest(Path::new(path)), "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_requirements_txt() {
+        let content = "\
+# LLM stack
+langchain-nvidia-ai-endpoints[all]>=0.3.0,<0.4 ; python_version >= \"3.9\"
+langchain_NVIDIA.ai-endpoints  # normalized name
+llama-index-llms-nvidia==0.3.1 \\
+    --hash=sha256:0123abcd
+-r base.txt
+-e git+https://github.com/org/nvidia-sdk.git#egg=langchain-nvidia-ai-endpoints
+llama-index-llms-nvidia-extra==1.0
+https://example.com/llama-index-llms-nvidia.tar.gz
+openai>=1.0
+";
+        let matches = scan("requirements.txt", content);
+        assert_eq!(summary(&matches), vec![
+            ("langchain-nvidia-ai-endpoints", Some(">=0.3.0,<0.4"), None, 2),
+            ("langchain_NVIDIA.ai-endpoints", None, None, 3),
+            ("llama-index-llms-nvidia", Some("==0.3.1"), None, 4),
+            ("openai", Some(">=1.0"), None, 10),
+        ]);
+        assert_eq!(matches[0].extras, vec!["all"]);
+        assert_eq!(matches[0].framework, Framework::Langchain);
+        assert_eq!(matches[2].framework, Framework::LlamaIndex);
+        assert_eq!(matches[2].match_context, "llama-index-llms-nvidia==0.3.1 \\");
+        assert_eq!(matches[3].framework, Framework::OpenaiSdk);
+        assert!(matches.iter().all(|m| m.ecosystem == PackageEcosystem::Pypi));
+    }
+
+    #[test]
+    fn test_pyproject_toml() {
+        let content = r#"[project]
+name = "rag-agent"
+dependencies = [
+    "fastapi>=0.110",
+    "langchain-nvidia-ai-endpoints~=0.3",
+]
+
+[project.optional-dependencies]
+llamaindex = ["llama-index-llms-nvidia>=0.2", "llama-index-embeddings-nvidia"]
+
+[dependency-groups]
+eval = ["nvidia-haystack", { include-group = "test" }]
+
+[tool.poetry.dependencies]
+python = "^3.10"
+langchain-nvidia-ai-endpoints = { version = "^0.3.0", extras = ["all"] }
+
+[tool.poetry.group.rerank.dependencies]
+llama-index-postprocessor-nvidia-rerank = "*"
+"#;
+        let matches = scan("pyproject.toml", content);
+        assert_eq!(summary(&matches), vec![
+            ("langchain-nvidia-ai-endpoints", Some("~=0.3"), Some("project.dependencies"), 5),
+            ("llama-index-llms-nvidia", Some(">=0.2"), Some("project.optional-dependencies.llamaindex"), 9),
+            ("llama-index-embeddings-nvidia", None, Some("project.optional-dependencies.llamaindex"), 9),
+            ("nvidia-haystack", None, Some("dependency-groups.eval"), 12),
+            ("langchain-nvidia-ai-endpoints", Some("^0.3.0"), Some("tool.poetry.dependencies"), 16),
+            ("llama-index-postprocessor-nvidia-rerank", None, Some("tool.poetry.group.rerank.dependencies"), 19),
+        ]);
+        assert_eq!(matches[4].extras, vec!["all"]);
+        assert!(scan("pyproject.toml", "[project\nname = ").is_empty());
+    }
+
+    #[test]
+    fn test_setup_cfg() {
+        let content = "\
+[metadata]
+name = rag-agent
+
+[options]
+install_requires =
+    requests
+    langchain-nvidia-ai-endpoints>=0.3  # chat and embeddings
+tests_require = openai
+
+[options.extras_require]
+llamaindex =
+    llama-index-llms-nvidia
+";
+        let matches = scan("setup.cfg", content);
+        assert_eq!(summary(&matches), vec![
+            ("langchain-nvidia-ai-endpoints", Some(">=0.3"), Some("options.install_requires"), 7),
+            ("openai", None, Some("options.tests_require"), 8),
+            ("llama-index-llms-nvidia", None, Some("options.extras_require.llamaindex"), 12),
+        ]);
+    }
+
+    #[test]
+    fn test_package_json_and_conda_environment() {
+        let content = r#"{
+  "name": "chat-ui",
+  "dependencies": {
+    "openai-edge": "^1.2.0",
+    "openai": "^4.52.0"
+  },
+  "devDependencies": {
+    "typescript": "^5.4.0"
+  }
+}"#;
+        let matches = scan("package.json", content);
+        assert_eq!(summary(&matches), vec![("openai", Some("^4.52.0"), Some("dependencies"), 5)]);
+        assert_eq!(matches[0].ecosystem, PackageEcosystem::Npm);
+        assert!(scan("package.json", "{ \"dependencies\": ").is_empty());
+
+        let content = "\
+name: rag
+channels: [conda-forge]
+dependencies:
+  - python=3.11
+  - conda-forge::langchain-nvidia-ai-endpoints=0.3.*
+  - pip
+  - pip:
+      - llama-index-llms-nvidia>=0.2
+";
+        let matches = scan("environment.yml", content);
+        assert_eq!(summary(&matches), vec![
+            ("langchain-nvidia-ai-endpoints", Some("=0.3.*"), Some("dependencies"), 5),
+            ("llama-index-llms-nvidia", Some(">=0.2"), Some("dependencies.pip"), 8),
+        ]);
+        assert_eq!(matches[0].ecosystem, PackageEcosystem::Conda);
+        assert_eq!(matches[1].ecosystem, PackageEcosystem::Pypi);
+    }
+
+    #[test]
+    fn test_scan_manifests_openai_needs_nvidia_client() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::write(root.join("requirements.txt"), "openai>=1.0\nlangchain-nvidia-ai-endpoints\n").unwrap();
+        std::fs::write(root.join("app.py"), "import openai\n").unwrap();
+        let files = vec![root.join("requirements.txt"), root.join("app.py")];
+
+        let matches = scan_manifests(&files, "org/repo", root, &[]);
+        assert_eq!(summary(&matches), vec![("langchain-nvidia-ai-endpoints", None, None, 2)]);
+        assert_eq!(matches[0].file_path, "requirements.txt");
+
+        let client: HostedNimMatch = serde_json::from_value(serde_json::json!({
+            "repository": "org/repo",
+            "endpoint_url": "https://integrate.api.nvidia.com/v1",
+            "file_path": "app.py",
+            "line_number": 3,
+            "match_context": "client = OpenAI(base_url=\"https://integrate.api.nvidia.com/v1\")",
+            "framework": "openai_sdk",
+        })).unwrap();
+        let matches = scan_manifests(&files, "org/repo", root, &[client]);
+        assert_eq!(matches.iter().map(|m| m.package_name.as_str()).collect::<Vec<_>>(), vec!["openai", "langchain-nvidia-ai-endpoints"]);
+    }
+}
@@ -0,0 +1,586 @@
+//! Enriching an existing report.json (enrich-report)
+//!
+//! A scan without an NGC API key leaves its findings unenriched. enrich-report
+//! runs the scan's enrichment over such a report in batches, then recomputes
+//! everything derived from the findings (aggregated view, summary, version skew,
+//! detector statistics); the rest of the report is kept as it was.
+//!
+//! After each batch the report so far is written to the output path, with a
+//! progress file next to it (`<output>.progress.json`) listing the findings not
+//! yet looked up. `--resume` continues from those two files, so a killed run
+//! loses at most one batch, and a run that spent its budget picks up with the
+//! findings it marked `budget_exhausted`. The progress file is removed once every
+//! finding has been looked up.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use anyhow::{bail, Context, Result};
+use log::info;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::merge::{self, CarriedEnrichment};
+use crate::models::{EnrichStage, EnrichmentSkip, LocalNimMatch, NimFindings, ScanReport, Summary};
+use crate::ngc_api::{self, EnrichmentBudget, NgcClient, RepoEnrichment};
+
+/// Suffix of the progress file, appended to the output path
+pub const PROGRESS_SUFFIX: &str = ".progress.json";
+
+/// Findings enriched between two checkpoints when --batch-size is not given
+pub const DEFAULT_BATCH_SIZE: usize = 500;
+
+/// How enrich-report enriches (the scan's enrichment options)
+#[derive(Debug, Clone)]
+pub struct EnrichSettings {
+    pub api_key: Option<String>,
+    /// Repositories enriched with their own key or not at all (repos.yaml)
+    pub repos: HashMap<String, RepoEnrichment>,
+    pub stages: Vec<EnrichStage>,
+    /// Budget of the whole run, not of each batch
+    pub budget: EnrichmentBudget,
+    /// Findings looked up between two checkpoints
+    pub batch_size: usize,
+}
+
+/// Contents of the progress file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Progress {
+    /// Report being enriched
+    input: String,
+    /// SHA-256 of the input, so a run is never resumed against a different report
+    input_sha256: String,
+    stages: Vec<EnrichStage>,
+    /// Positions of the findings not yet looked up (see [`list_lengths`])
+    pending: Vec<usize>,
+}
+
+/// Path of the progress file kept next to `output`
+pub fn progress_path(output: &Path) -> PathBuf {
+    let mut path = output.as_os_str().to_owned();
+    path.push(PROGRESS_SUFFIX);
+    PathBuf::from(path)
+}
+
+/// Lengths of a report's four finding lists, in position order: source code
+/// Local NIM, source code Hosted NIM, workflow Local NIM, workflow Hosted NIM
+fn list_lengths(report: &ScanReport) -> [usize; 4] {
+    [
+        report.source_code.local_nim.len(),
+        report.source_code.hosted_nim.len(),
+        report.actions_workflow.local_nim.len(),
+        report.actions_workflow.hosted_nim.len(),
+    ]
+}
+
+/// List (index into [`list_lengths`]) and index in it of the finding at
+/// `position`, or `None` past the last finding
+fn locate(lengths: &[usize; 4], mut position: usize) -> Option<(usize, usize)> {
+    for (list, len) in lengths.iter().enumerate() {
+        if position < *len {
+            return Some((list, position));
+        }
+        position -= len;
+    }
+    None
+}
+
+/// Position as `locate` wants it, or an error naming it
+fn locate_or_fail(lengths: &[usize; 4], position: usize) -> Result<(usize, usize)> {
+    locate(lengths, position).with_context(|| format!("finding position {} is out of range", position))
+}
+
+/// Copies of the findings at `positions`, as source code and workflow
+/// findings, with the outcome of any earlier lookup cleared
+fn take_batch(report: &ScanReport, positions: &[usize]) -> Result<[NimFindings; 2]> {
+    let lengths = list_lengths(report);
+    let mut batch = [NimFindings::new(), NimFindings::new()];
+    for &position in positions {
+        let (list, i) = locate_or_fail(&lengths, position)?;
+        let findings = match list / 2 {
+            0 => &report.source_code,
+            _ => &report.actions_workflow,
+        };
+        let target = &mut batch[list / 2];
+        if list % 2 == 0 {
+            let mut m = findings.local_nim[i].clone();
+            m.enrichment_skipped = None;
+            m.enrichment_error = None;
+            target.local_nim.push(m);
+        } else {
+            let mut m = findings.hosted_nim[i].clone();
+            m.enrichment_skipped = None;
+            m.enrichment_error = None;
+            target.hosted_nim.push(m);
+        }
+    }
+    Ok(batch)
+}
+
+/// Put a batch from [`take_batch`] back, enriched; returns the positions of the
+/// findings still to look up (`local_pending` decides for Local NIM findings)
+fn put_batch(
+    report: &mut ScanReport,
+    positions: &[usize],
+    batch: [NimFindings; 2],
+    local_pending: impl Fn(&LocalNimMatch) -> bool,
+) -> Result<Vec<usize>> {
+    let lengths = list_lengths(report);
+    let [source, workflow] = batch;
+    let mut local = [source.local_nim.into_iter(), workflow.local_nim.into_iter()];
+    let mut hosted = [source.hosted_nim.into_iter(), workflow.hosted_nim.into_iter()];
+    let mut pending = Vec::new();
+    for &position in positions {
+        let (list, i) = locate_or_fail(&lengths, position)?;
+        let findings = match list / 2 {
+            0 => &mut report.source_code,
+            _ => &mut report.actions_workflow,
+        };
+        let still_pending = if list % 2 == 0 {
+            let m = local[list / 2].next().expect("enrichment keeps every finding");
+            let still_pending = local_pending(&m);
+            findings.local_nim[i] = m;
+            still_pending
+        } else {
+            let m = hosted[list / 2].next().expect("enrichment keeps every finding");
+            let still_pending = m.enrichment_skipped == Some(EnrichmentSkip::BudgetExhausted);
+            findings.hosted_nim[i] = m;
+            still_pending
+        };
+        if still_pending {
+            pending.push(position);
+        }
+    }
+    Ok(pending)
+}
+
+/// Refuse a progress file whose pending positions don't fit `report`: past its
+/// last finding, or listed twice
+fn check_pending(pending: &[usize], report: &ScanReport, sidecar: &Path) -> Result<()> {
+    let total: usize = list_lengths(report).iter().sum();
+    let mut seen = HashSet::new();
+    for &position in pending {
+        if position >= total {
+            bail!("{} lists finding position {}, but the report has {} finding(s); start over without --resume",
+                  sidecar.display(), position, total);
+        }
+        if !seen.insert(position) {
+            bail!("{} lists finding position {} twice; start over without --resume", sidecar.display(), position);
+        }
+    }
+    Ok(())
+}
+
+/// `report` with everything derived from its findings recomputed, and the
+/// enrichment in `carried` attached; everything else is kept
+pub fn rebuild(report: ScanReport, carried: &CarriedEnrichment) -> ScanReport {
+    // A scan run with --include-fixtures leaves nothing out of its summary
+    let include_fixtures = report.summary.fixtures_excluded == 0;
+    let mut rebuilt = ScanReport::new(report.total_repos, report.source_code, report.actions_workflow);
+    if include_fixtures {
+        rebuilt.summary = Summary::calculate_all(&rebuilt.source_code, &rebuilt.actions_workflow);
+    }
+    carried.apply(&mut rebuilt);
+    rebuilt.scan_time = report.scan_time;
+    rebuilt.delta = report.delta;
+    rebuilt.truncated = report.truncated;
+    rebuilt.scope = report.scope;
+    rebuilt.scope_paths = report.scope_paths;
+    rebuilt.possible_retirements = report.possible_retirements;
+    rebuilt.checkouts = report.checkouts;
+    rebuilt.skipped_oversize = report.skipped_oversize;
+    rebuilt.lfs_pointers = report.lfs_pointers;
+    rebuilt.listed_file_errors = report.listed_file_errors;
+    rebuilt.merged_from = report.merged_from;
+    rebuilt.repository_collisions = report.repository_collisions;
+    rebuilt.metadata_hints = report.metadata_hints;
+    rebuilt.panicked = report.panicked;
+    rebuilt.sampling = report.sampling;
+    rebuilt
+}
+
+/// Write `value` as JSON to a temporary file next to `path`, then rename it over `path`
+fn write_json_atomic<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create output directory: {}", dir.display()))?;
+    }
+    let json = serde_json::to_string_pretty(value).context("Failed to serialize JSON")?;
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    std::fs::write(&tmp, json)
+        .with_context(|| format!("Failed to write {}", tmp.display()))?;
+    std::fs::rename(&tmp, path)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(())
+}
+
+/// A report being enriched, with the findings still to look up
+pub struct EnrichJob {
+    report: ScanReport,
+    progress: Progress,
+    carried: CarriedEnrichment,
+    output: PathBuf,
+}
+
+impl EnrichJob {
+    /// Start enriching the report at `input` into `output`, or with `resume`
+    /// continue the run recorded in `output`'s progress file
+    ///
+    /// A fresh start looks up every finding again, even in a report that was
+    /// already enriched. It is refused while `output` has a progress file, so
+    /// an unfinished run is never overwritten by accident.
+    pub fn open(input: &Path, output: &Path, stages: &[EnrichStage], resume: bool) -> Result<Self> {
+        if output.exists() && input.canonicalize().ok() == output.canonicalize().ok() {
+            bail!("--output must differ from the input report {}", input.display());
+        }
+        let content = std::fs::read(input)
+            .with_context(|| format!("Failed to read report: {}", input.display()))?;
+        let input_sha256 = format!("{:x}", Sha256::digest(&content));
+        let sidecar = progress_path(output);
+
+        let (report, progress) = if resume {
+            let text = std::fs::read_to_string(&sidecar)
+                .with_context(|| format!("Nothing to resume: failed to read {}", sidecar.display()))?;
+            let progress: Progress = serde_json::from_str(&text)
+                .with_context(|| format!("Failed to parse {}", sidecar.display()))?;
+            if progress.input_sha256 != input_sha256 {
+                bail!("{} belongs to a different report than {} (or it changed since); start over without --resume",
+                      sidecar.display(), input.display());
+            }
+            let same_stages = progress.stages.len() == stages.len() && stages.iter().all(|s| progress.stages.contains(s));
+            if !same_stages {
+                bail!("{} was started with other enrichment stages; resume with the same --enrich options", sidecar.display());
+            }
+            let report = crate::report::load_json_report(output)?;
+            check_pending(&progress.pending, &report, &sidecar)?;
+            info!("Resuming: {} finding(s) left to look up", progress.pending.len());
+            (report, progress)
+        } else {
+            if sidecar.exists() {
+                bail!("{} holds an unfinished enrichment; continue it with --resume or delete it", sidecar.display());
+            }
+            let report: ScanReport = serde_json::from_slice(&content)
+                .with_context(|| format!("Failed to parse report: {}", input.display()))?;
+            merge::check_schema_version(&report, &input.display().to_string())?;
+            let total: usize = list_lengths(&report).iter().sum();
+            let progress = Progress {
+                input: input.display().to_string(),
+                input_sha256,
+                stages: stages.to_vec(),
+                pending: (0..total).collect(),
+            };
+            (report, progress)
+        };
+
+        let mut carried = CarriedEnrichment::default();
+        carried.collect(&report);
+        Ok(Self { report, progress, carried, output: output.to_path_buf() })
+    }
+
+    /// Findings not yet looked up
+    pub fn pending(&self) -> usize {
+        self.progress.pending.len()
+    }
+
+    /// Look up the pending findings `batch_size` at a time, writing a checkpoint
+    /// after each batch
+    ///
+    /// Each batch starts with empty caches, so a larger batch sends fewer
+    /// requests and a smaller one loses less to a kill. Once the budget is
+    /// spent, the findings left are marked `budget_exhausted` and stay pending
+    /// for `--resume`. A rejected API key stops the run, keeping nothing of the
+    /// batch it rejected.
+    pub fn run(&mut self, settings: &EnrichSettings, new_client: impl Fn(&str) -> Result<NgcClient>) -> Result<()> {
+        let started = Instant::now();
+        let total = self.progress.pending.len();
+        let mut requests = 0;
+        let mut out_of_budget = false;
+        let meta_stage = settings.stages.contains(&EnrichStage::ImageMeta);
+        while !self.progress.pending.is_empty() {
+            let budget = match out_of_budget {
+                // Marks what is left without sending anything
+                true => EnrichmentBudget { max_duration: None, max_requests: Some(0) },
+                false => settings.budget.remaining(started.elapsed(), requests),
+            };
+            let last = budget.is_spent();
+            let size = match last {
+                true => self.progress.pending.len(),
+                false => settings.batch_size.max(1).min(self.progress.pending.len()),
+            };
+            let positions: Vec<usize> = self.progress.pending[..size].to_vec();
+            let [mut source, mut workflow] = take_batch(&self.report, &positions)?;
+            let run = ngc_api::enrich_by_repo(
+                settings.api_key.as_deref(),
+                &settings.repos,
+                &mut source,
+                &mut workflow,
+                &settings.stages,
+                budget,
+                &new_client,
+            );
+            if run.key_rejected {
+                bail!("NGC API key rejected; {} of {} finding(s) looked up (see {})",
+                      total - self.progress.pending.len(), total, progress_path(&self.output).display());
+            }
+            requests += run.requests;
+            self.carried.image_meta.extend(run.image_meta);
+
+            // Without its image metadata (budget spent first), a Local NIM finding is looked up again
+            let image_meta = &self.carried.image_meta;
+            let local_pending = |m: &LocalNimMatch| {
+                m.enrichment_skipped == Some(EnrichmentSkip::BudgetExhausted)
+                    || (meta_stage && ngc_api::is_registry_image(m) && !image_meta.contains_key(&m.image_url))
+            };
+            let mut pending = put_batch(&mut self.report, &positions, [source, workflow], local_pending)?;
+            out_of_budget = !pending.is_empty();
+            pending.extend_from_slice(&self.progress.pending[size..]);
+            self.progress.pending = pending;
+            self.checkpoint()?;
+            info!("Enrichment progress: {} of {} finding(s) looked up, {} request(s)",
+                  total - self.progress.pending.len(), total, requests);
+            if last {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the report so far and the progress file
+    fn checkpoint(&self) -> Result<()> {
+        write_json_atomic(&self.output, &rebuild(self.report.clone(), &self.carried))?;
+        write_json_atomic(&progress_path(&self.output), &self.progress)
+    }
+
+    /// Write the recomputed report to the output path and return it
+    ///
+    /// `prepare` runs first, on the findings and the enrichment carried into the
+    /// aggregated view (model cards, policies). The progress file is removed
+    /// when nothing is pending and kept for `--resume` otherwise.
+    pub fn finish(mut self, prepare: impl FnOnce(&mut ScanReport, &mut CarriedEnrichment)) -> Result<ScanReport> {
+        prepare(&mut self.report, &mut self.carried);
+        let report = rebuild(self.report, &self.carried);
+        write_json_atomic(&self.output, &report)?;
+        let sidecar = progress_path(&self.output);
+        if self.progress.pending.is_empty() {
+            if sidecar.exists() {
+                std::fs::remove_file(&sidecar)
+                    .with_context(|| format!("Failed to remove {}", sidecar.display()))?;
+            }
+        } else {
+            write_json_atomic(&sidecar, &self.progress)?;
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tempfile::TempDir;
+
+    /// Serve canned JSON by request path (404 otherwise) until the test exits,
+    /// recording every requested path
+    fn serve_routes(routes: Vec<(&'static str, &'static str)>) -> (String, Arc<Mutex<Vec<String>>>) {
+        use std::io::{Read, Write};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let hits = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&hits);
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut buf = [0u8; 4096];
+                let mut request = Vec::new();
+                while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                    let n = stream.read(&mut buf).unwrap();
+                    if n == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buf[..n]);
+                }
+                let request = String::from_utf8_lossy(&request);
+                let path = request.split_whitespace().nth(1).unwrap_or("").to_string();
+                let (status, body) = match routes.iter().find(|(p, _)| *p == path) {
+                    Some((_, body)) => ("200 OK", *body),
+                    None => ("404 Not Found", r#"{"detail": "not found"}"#),
+                };
+                recorded.lock().unwrap().push(path);
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        (format!("http://{}", addr), hits)
+    }
+
+    fn mock_registry() -> (String, Arc<Mutex<Vec<String>>>) {
+        serve_routes(vec![
+            ("/nvidia/repos/embedqa", r#"{"name": "embedqa", "displayName": "NV EmbedQA", "publisher": "NVIDIA", "latestTag": "1.5.0"}"#),
+            ("/meta/repos/llama-3.1-8b-instruct", r#"{"name": "llama-3.1-8b-instruct", "displayName": "Llama 3.1 8B", "publisher": "Meta", "latestTag": "1.3.3"}"#),
+            ("/nvidia/repos/rerankqa", r#"{"name": "rerankqa", "displayName": "NV RerankQA", "publisher": "NVIDIA", "latestTag": "1.0.2"}"#),
+            ("/functions", r#"{"functions": [
+                {"id": "fn-70b", "name": "ai-llama-3_1-70b-instruct", "status": "ACTIVE"}
+            ]}"#),
+            ("/functions/fn-70b/versions", r#"{"functions": [
+                {"id": "fn-70b", "name": "ai-llama-3_1-70b-instruct", "status": "ACTIVE",
+                 "containerImage": "nvcr.io/nim/meta/llama-3.1-70b-instruct:1.1",
+                 "inferenceUrl": "https://integrate.api.nvidia.com/v1"}
+            ]}"#),
+        ])
+    }
+
+    /// Report of a keyless scan: `latest` images in two repositories, a pinned
+    /// image, and Hosted NIM calls (one to a model NVCF doesn't serve)
+    fn write_unenriched_report(path: &Path) {
+        let local = |repository: &str, image: &str, tag: &str, file_path: &str| serde_json::json!({
+            "repository": repository,
+            "image_url": format!("nvcr.io/nim/{}", image),
+            "tag": tag,
+            "file_path": file_path,
+            "line_number": 3,
+            "match_context": format!("image: nvcr.io/nim/{}:{}", image, tag),
+            "detection_rule": "local_nim_full",
+        });
+        let hosted = |repository: &str, model: &str, file_path: &str| serde_json::json!({
+            "repository": repository,
+            "endpoint_url": "https://integrate.api.nvidia.com/v1",
+            "model_name": model,
+            "file_path": file_path,
+            "line_number": 8,
+            "match_context": format!("model=\"{}\"", model),
+            "detection_rule": "chatnvidia",
+        });
+        let source_code: NimFindings = serde_json::from_value(serde_json::json!({
+            "local_nim": [
+                local("acme/rag", "nvidia/embedqa", "latest", "deploy/compose.yaml"),
+                local("acme/rag", "meta/llama-3.1-8b-instruct", "latest", "deploy/compose.yaml"),
+                local("acme/rag", "nvidia/rerankqa", "1.0.2", "deploy/compose.yaml"),
+                local("acme/agent", "nvidia/embedqa", "latest", "docker-compose.yaml"),
+                local("acme/agent", "meta/llama-3.1-8b-instruct", "latest", "k8s/llm.yaml"),
+            ],
+            "hosted_nim": [
+                hosted("acme/rag", "meta/llama-3.1-70b-instruct", "src/chain.py"),
+                hosted("acme/agent", "meta/llama-3.1-70b-instruct", "agent/llm.py"),
+                hosted("acme/agent", "acme/not-a-nim", "agent/other.py"),
+            ],
+        })).unwrap();
+        let actions_workflow: NimFindings = serde_json::from_value(serde_json::json!({
+            "local_nim": [local("acme/rag", "nvidia/rerankqa", "latest", ".github/workflows/ci.yml")],
+            "hosted_nim": [],
+        })).unwrap();
+        let mut report = ScanReport::new(2, source_code, actions_workflow);
+        report.scan_time = "2026-01-05T10:00:00+00:00".to_string();
+        std::fs::write(path, serde_json::to_string_pretty(&report).unwrap()).unwrap();
+    }
+
+    fn settings(max_requests: usize, batch_size: usize) -> EnrichSettings {
+        EnrichSettings {
+            api_key: Some("test-key".to_string()),
+            repos: HashMap::new(),
+            stages: vec![EnrichStage::Local, EnrichStage::Hosted, EnrichStage::ImageMeta],
+            budget: EnrichmentBudget::from_limits(0, max_requests),
+            batch_size,
+        }
+    }
+
+    fn enrich(input: &Path, output: &Path, resume: bool, settings: &EnrichSettings, base: &str) -> EnrichJob {
+        let mut job = EnrichJob::open(input, output, &settings.stages, resume).unwrap();
+        job.run(settings, |key| NgcClient::with_base_urls(key.to_string(), base, base)).unwrap();
+        job
+    }
+
+    #[test]
+    fn test_enrich_report_resume_matches_single_pass() {
+        let temp_dir = TempDir::new().unwrap();
+        let input = temp_dir.path().join("report.json");
+        write_unenriched_report(&input);
+        let (base, _) = mock_registry();
+
+        // One pass, no budget
+        let single = temp_dir.path().join("single").join("report.json");
+        let job = enrich(&input, &single, false, &settings(0, 4), &base);
+        assert_eq!(job.pending(), 0);
+        let report = job.finish(|_, _| {}).unwrap();
+        assert!(!progress_path(&single).exists());
+        assert!(report.source_code.local_nim.iter().all(|m| m.enrichment_skipped.is_none()));
+        assert_eq!(report.source_code.local_nim[0].resolved_tag.as_deref(), Some("1.5.0"));
+        assert_eq!(report.source_code.hosted_nim[0].function_id.as_deref(), Some("fn-70b"));
+        assert_eq!(report.scan_time, "2026-01-05T10:00:00+00:00");
+        let embedqa = report.aggregated.local_nim.iter().find(|e| e.image_url.ends_with("embedqa")).unwrap();
+        assert_eq!(embedqa.display_name.as_deref(), Some("NV EmbedQA"));
+
+        // Interrupted halfway by a request cap, then resumed without one
+        let resumed = temp_dir.path().join("resumed").join("report.json");
+        let job = enrich(&input, &resumed, false, &settings(3, 4), &base);
+        assert!(job.pending() > 0);
+        let partial = job.finish(|_, _| {}).unwrap();
+        assert!(progress_path(&resumed).exists());
+        assert!(partial.summary.enrichment_skipped > 0);
+        assert!(partial.source_code.local_nim.iter().any(|m| m.enriched));
+
+        // Starting over on top of an unfinished run is refused
+        let err = EnrichJob::open(&input, &resumed, &settings(0, 4).stages, false).err().unwrap();
+        assert!(err.to_string().contains("--resume"), "{}", err);
+
+        let job = enrich(&input, &resumed, true, &settings(0, 4), &base);
+        assert_eq!(job.pending(), 0);
+        job.finish(|_, _| {}).unwrap();
+        assert!(!progress_path(&resumed).exists());
+
+        assert_eq!(std::fs::read_to_string(&resumed).unwrap(), std::fs::read_to_string(&single).unwrap());
+    }
+
+    #[test]
+    fn test_enrich_report_checkpoints_each_batch() {
+        let temp_dir = TempDir::new().unwrap();
+        let input = temp_dir.path().join("report.json");
+        write_unenriched_report(&input);
+        let (base, hits) = mock_registry();
+        let output = temp_dir.path().join("enriched.json");
+
+        // The first batch is written before a kill would lose it
+        let mut job = EnrichJob::open(&input, &output, &[EnrichStage::Local], false).unwrap();
+        let settings = EnrichSettings { stages: vec![EnrichStage::Local], ..settings(0, 2) };
+        assert_eq!(job.pending(), 9);
+        job.run(&settings, |key| NgcClient::with_base_urls(key.to_string(), &base, &base)).unwrap();
+        assert_eq!(job.pending(), 0);
+        // One batch per two findings, each with its own cache
+        assert_eq!(hits.lock().unwrap().iter().filter(|p| *p == "/nvidia/repos/embedqa").count(), 2);
+        let progress: Progress = serde_json::from_str(&std::fs::read_to_string(progress_path(&output)).unwrap()).unwrap();
+        assert!(progress.pending.is_empty());
+        assert_eq!(progress.input, input.display().to_string());
+
+        // A progress file is only resumed against the report it was made from
+        job.finish(|_, _| {}).unwrap();
+        let err = EnrichJob::open(&input, &output, &[EnrichStage::Local], true).err().unwrap();
+        assert!(err.to_string().contains("Nothing to resume"), "{}", err);
+        write_json_atomic(&progress_path(&output), &Progress { input_sha256: "0".repeat(64), ..progress.clone() }).unwrap();
+        let err = EnrichJob::open(&input, &output, &[EnrichStage::Local], true).err().unwrap();
+        assert!(err.to_string().contains("different report"), "{}", err);
+        write_json_atomic(&progress_path(&output), &progress).unwrap();
+        let err = EnrichJob::open(&input, &output, &[EnrichStage::Hosted], true).err().unwrap();
+        assert!(err.to_string().contains("same --enrich"), "{}", err);
+
+        // Pending positions are checked against the report, not trusted
+        write_json_atomic(&progress_path(&output), &Progress { pending: vec![3, 9], ..progress.clone() }).unwrap();
+        let err = EnrichJob::open(&input, &output, &[EnrichStage::Local], true).err().unwrap();
+        assert!(err.to_string().contains("position 9, but the report has 9"), "{}", err);
+        write_json_atomic(&progress_path(&output), &Progress { pending: vec![3, 3], ..progress.clone() }).unwrap();
+        let err = EnrichJob::open(&input, &output, &[EnrichStage::Local], true).err().unwrap();
+        assert!(err.to_string().contains("position 3 twice"), "{}", err);
+        assert!(err.to_string().contains(PROGRESS_SUFFIX), "{}", err);
+
+        // The input is never overwritten
+        let err = EnrichJob::open(&input, &input, &[EnrichStage::Local], false).err().unwrap();
+        assert!(err.to_string().contains("must differ"), "{}", err);
+    }
+}
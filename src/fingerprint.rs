@@ -0,0 +1,230 @@
+//! Stable identity of a finding across scans
+//!
+//! Baselines and any other feature that asks "is this the same finding?" use
+//! [`fingerprint`], never a key of their own. A fingerprint is a hash over
+//! the normalized repository, file path, NIM identity, tag (Local NIM) or
+//! model (Hosted NIM) and detection rule. Line numbers and `match_context` are
+//! left out, so a finding keeps its fingerprint when lines are added above it
+//! or the line is reformatted. Several references to the same NIM and tag in
+//! one file share a fingerprint.
+//!
+//! The string is `fp<version>:<32 hex digits>`. Any change to the fields or
+//! their normalization must bump [`FINGERPRINT_VERSION`], so fingerprints of
+//! different versions never compare equal by accident.
+
+use sha2::{Digest, Sha256};
+
+use crate::models::NimFindings;
+
+/// Version of the fingerprint algorithm, embedded in every fingerprint
+pub const FINGERPRINT_VERSION: u32 = 1;
+
+/// Fields a fingerprint covers, as found on a finding
+#[derive(Debug, Clone, Copy)]
+pub struct FingerprintFields<'a> {
+    /// `local_nim` or `hosted_nim`
+    pub nim_type: &'a str,
+    pub repository: &'a str,
+    pub file_path: &'a str,
+    /// NIM identity (`meta/llama-3.1-8b-instruct`); empty when there is none
+    pub nim_identity: &'a str,
+    /// Tag of a Local NIM; model (else function ID, else endpoint) of a Hosted NIM
+    pub tag_or_model: &'a str,
+    pub detection_rule: &'a str,
+}
+
+/// Fingerprint of a finding: `fp1:` and the first 128 bits of a SHA-256, in hex
+pub fn fingerprint(fields: &FingerprintFields) -> String {
+    let normalized = [
+        fields.nim_type.trim().to_string(),
+        fields.repository.trim().to_ascii_lowercase(),
+        normalize_path(fields.file_path),
+        fields.nim_identity.trim().to_ascii_lowercase(),
+        fields.tag_or_model.trim().to_string(),
+        fields.detection_rule.trim().to_string(),
+    ];
+    let mut hasher = Sha256::new();
+    hasher.update(format!("fp{}", FINGERPRINT_VERSION));
+    // Each field is length-prefixed, so no value can run into the next one
+    for field in &normalized {
+        hasher.update((field.len() as u64).to_le_bytes());
+        hasher.update(field.as_bytes());
+    }
+    let digest = format!("{:x}", hasher.finalize());
+    format!("fp{}:{}", FINGERPRINT_VERSION, &digest[..32])
+}
+
+/// Repository-relative path with `/` separators, no leading `./` or `/` and no empty components
+fn normalize_path(file_path: &str) -> String {
+    file_path
+        .trim()
+        .replace('\\', "/")
+        .split('/')
+        .filter(|c| !c.is_empty() && *c != ".")
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Record each finding's fingerprint in its `fingerprint` field
+pub fn assign_fingerprints(findings: &mut NimFindings) {
+    for m in &mut findings.local_nim {
+        m.fingerprint = m.fingerprint();
+    }
+    for m in &mut findings.hosted_nim {
+        m.fingerprint = m.fingerprint();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{HostedNimMatch, LocalNimMatch, NimFindings};
+
+    fn local_json() -> serde_json::Value {
+        serde_json::json!({
+            "repository": "NVIDIA/GenerativeAIExamples",
+            "image_url": "nvcr.io/nim/meta/llama-3.1-8b-instruct",
+            "tag": "1.3.3",
+            "file_path": "deploy/compose/docker-compose.yaml",
+            "line_number": 12,
+            "match_context": "image: nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3.3",
+            "detection_rule": "local_nim_full",
+        })
+    }
+
+    fn hosted_json() -> serde_json::Value {
+        serde_json::json!({
+            "repository": "NVIDIA/GenerativeAIExamples",
+            "endpoint_url": "https://integrate.api.nvidia.com/v1",
+            "model_name": "meta/llama-3.1-70b-instruct",
+            "file_path": "src/chain.py",
+            "line_number": 4,
+            "match_context": "llm = ChatNVIDIA(model=\"meta/llama-3.1-70b-instruct\")",
+            "detection_rule": "chatnvidia",
+        })
+    }
+
+    fn local(value: serde_json::Value) -> LocalNimMatch {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn hosted(value: serde_json::Value) -> HostedNimMatch {
+        serde_json::from_value(value).unwrap()
+    }
+
+    /// `value` with `key` set to `new`
+    fn with(value: &serde_json::Value, key: &str, new: serde_json::Value) -> serde_json::Value {
+        let mut value = value.clone();
+        value[key] = new;
+        value
+    }
+
+    #[test]
+    fn test_fingerprint_format() {
+        let fp = local(local_json()).fingerprint();
+        assert!(fp.starts_with(&format!("fp{}:", FINGERPRINT_VERSION)), "{}", fp);
+        assert_eq!(fp.len(), "fp1:".len() + 32);
+        assert!(fp[4..].chars().all(|c| c.is_ascii_hexdigit()));
+    }
+
+    #[test]
+    fn test_fingerprint_stable_across_field_order() {
+        // The same finding written with its keys in every rotation
+        for base in [local_json(), hosted_json()] {
+            let pairs: Vec<(String, serde_json::Value)> = base.as_object().unwrap().clone().into_iter().collect();
+            let expected = match base.get("image_url") {
+                Some(_) => local(base.clone()).fingerprint(),
+                None => hosted(base.clone()).fingerprint(),
+            };
+            for shift in 0..pairs.len() {
+                let mut rotated = pairs.clone();
+                rotated.rotate_left(shift);
+                let body: Vec<String> = rotated.iter()
+                    .map(|(k, v)| format!("{}: {}", serde_json::to_string(k).unwrap(), v))
+                    .collect();
+                let text = format!("{{{}}}", body.join(", "));
+                let fp = match base.get("image_url") {
+                    Some(_) => serde_json::from_str::<LocalNimMatch>(&text).unwrap().fingerprint(),
+                    None => serde_json::from_str::<HostedNimMatch>(&text).unwrap().fingerprint(),
+                };
+                assert_eq!(fp, expected, "{}", text);
+            }
+        }
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_line_and_context() {
+        let expected = local(local_json()).fingerprint();
+        for line_number in [1, 13, 500, 10_000] {
+            let moved = with(&local_json(), "line_number", line_number.into());
+            let moved = with(&moved, "match_context", format!("    image: \"nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3.3\"  # line {}", line_number).into());
+            assert_eq!(local(moved).fingerprint(), expected);
+        }
+        let expected = hosted(hosted_json()).fingerprint();
+        let moved = with(&hosted_json(), "line_number", 40.into());
+        assert_eq!(hosted(with(&moved, "match_context", "model=\"meta/llama-3.1-70b-instruct\",".into())).fingerprint(), expected);
+    }
+
+    #[test]
+    fn test_fingerprint_normalizes_paths_and_case() {
+        let expected = local(local_json()).fingerprint();
+        for path in [
+            "deploy\\compose\\docker-compose.yaml",
+            "./deploy/compose/docker-compose.yaml",
+            "/deploy//compose/./docker-compose.yaml",
+            " deploy/compose/docker-compose.yaml ",
+        ] {
+            assert_eq!(local(with(&local_json(), "file_path", path.into())).fingerprint(), expected, "{}", path);
+        }
+        let renamed = with(&local_json(), "repository", "nvidia/generativeaiexamples".into());
+        assert_eq!(local(renamed).fingerprint(), expected);
+        let image = with(&local_json(), "image_url", "NVCR.IO/NIM/Meta/Llama-3.1-8B-Instruct".into());
+        assert_eq!(local(image).fingerprint(), expected);
+    }
+
+    #[test]
+    fn test_fingerprint_unique_across_findings() {
+        let local_variants = [
+            ("repository", "NVIDIA/other-repo"),
+            ("file_path", "deploy/compose/docker-compose.prod.yaml"),
+            ("file_path", "Deploy/compose/docker-compose.yaml"),
+            ("image_url", "nvcr.io/nim/meta/llama-3.1-70b-instruct"),
+            ("tag", "1.3.4"),
+            ("tag", "latest"),
+            ("detection_rule", "local_nim_compose"),
+        ];
+        let hosted_variants = [
+            ("repository", "NVIDIA/other-repo"),
+            ("file_path", "src/chain_test.py"),
+            ("model_name", "meta/llama-3.1-8b-instruct"),
+            ("model_name", "stg/meta/llama-3.1-70b-instruct"),
+            ("detection_rule", "model_assign"),
+        ];
+        let mut seen: Vec<String> = vec![local(local_json()).fingerprint(), hosted(hosted_json()).fingerprint()];
+        for (key, value) in local_variants {
+            seen.push(local(with(&local_json(), key, value.into())).fingerprint());
+        }
+        for (key, value) in hosted_variants {
+            seen.push(hosted(with(&hosted_json(), key, value.into())).fingerprint());
+        }
+        // Without a model: function ID, then endpoint
+        let no_model = with(&hosted_json(), "model_name", serde_json::Value::Null);
+        seen.push(hosted(no_model.clone()).fingerprint());
+        seen.push(hosted(with(&no_model, "function_id", "0f2b54e3-8c6e-4d6a-9d5c-0a1b2c3d4e5f".into())).fingerprint());
+        seen.push(hosted(with(&no_model, "endpoint_url", "https://ai.api.nvidia.com/v1".into())).fingerprint());
+
+        let distinct: std::collections::BTreeSet<&String> = seen.iter().collect();
+        assert_eq!(distinct.len(), seen.len(), "{:#?}", seen);
+    }
+
+    #[test]
+    fn test_assign_fingerprints() {
+        let mut findings = NimFindings { local_nim: vec![local(local_json())], hosted_nim: vec![hosted(hosted_json())], sdk_dependencies: vec![] };
+        assert!(findings.local_nim[0].fingerprint.is_empty());
+        assign_fingerprints(&mut findings);
+        assert_eq!(findings.local_nim[0].fingerprint, findings.local_nim[0].fingerprint());
+        assert_eq!(findings.hosted_nim[0].fingerprint, findings.hosted_nim[0].fingerprint());
+        let json = serde_json::to_value(&findings).unwrap();
+        assert_eq!(json["hosted_nim"][0]["fingerprint"], findings.hosted_nim[0].fingerprint().as_str());
+    }
+}
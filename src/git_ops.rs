@@ -2,13 +2,20 @@
 //!
 //! This module handles cloning repositories and managing temporary directories.
 
-use std::path::{Path, PathBuf};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
 use std::process::Command;
 use anyhow::{Context, Result, bail};
 use log::{info, warn, debug};
 use rayon::prelude::*;
 
-use crate::models::RepoConfig;
+use crate::models::{
+    AcquireMode, Acquisition, CheckoutReset, LineBlame, MetadataField, NimFindings, OversizeSkip, RepoConfig,
+    SizeSource, StaleCheckout,
+};
+use crate::cleanup::{self, GroupCommand, PartialDir};
+use crate::paths;
 
 /// Inject GitHub token into HTTPS URL for private repo access
 ///
@@ -26,6 +33,35 @@ fn inject_github_token(url: &str, token: &str) -> String {
     }
 }
 
+/// CI configuration and deployment manifest paths checked out for `--scope workflows`
+/// (gitignore syntax, as used by `git sparse-checkout --no-cone`)
+pub const DEFAULT_SCOPE_PATHS: &[&str] = &[
+    "/.github/",
+    "/.gitlab-ci.yml",
+    "/.circleci/",
+    "/.buildkite/",
+    "/Jenkinsfile",
+    "/azure-pipelines.yml",
+    "/deploy/",
+    "/deployment/",
+    "/deployments/",
+    "/k8s/",
+    "/kubernetes/",
+    "/helm/",
+    "/charts/",
+    "/manifests/",
+];
+
+/// How a repository checkout was obtained
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CheckoutSource {
+    /// Cloned (or updated) by the scanner
+    Cloned,
+    /// Already present in the workdir (--no-clone); never modified or cleaned up
+    PreProvisioned,
+}
+
 /// Result of a clone operation
 #[derive(Debug)]
 pub struct CloneResult {
@@ -35,6 +71,27 @@ pub struct CloneResult {
     pub path: Option<PathBuf>,
     /// Error message (if failed)
     pub error: Option<String>,
+    /// How the checkout was obtained
+    pub source: CheckoutSource,
+    /// Whether the files came from git, a tarball or an existing directory
+    pub acquisition: Acquisition,
+    /// Commit of the checkout, when known
+    pub commit_sha: Option<String>,
+    /// Set when the repository was skipped for exceeding the size limit (not a failure)
+    pub skipped_oversize: Option<OversizeSkip>,
+    /// What updating a checkout reused from an earlier run did
+    pub update: CheckoutUpdate,
+    /// Panic message, when checking out panicked
+    pub panic: Option<String>,
+}
+
+/// Outcome of updating a checkout reused from an earlier run, beyond a plain fast-forward
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CheckoutUpdate {
+    /// The branch was rewritten upstream and the checkout reset to it
+    pub reset: Option<CheckoutReset>,
+    /// The checkout could not be updated and keeps its old commit
+    pub stale: Option<StaleCheckout>,
 }
 
 impl CloneResult {
@@ -42,34 +99,97 @@ impl CloneResult {
     pub fn is_success(&self) -> bool {
         self.path.is_some()
     }
+
+    /// Result for a repository skipped by the size limit
+    fn skipped(repo: &RepoConfig, acquisition: Acquisition, skip: OversizeSkip) -> Self {
+        Self {
+            repo: repo.clone(),
+            path: None,
+            error: None,
+            source: CheckoutSource::Cloned,
+            acquisition,
+            commit_sha: None,
+            skipped_oversize: Some(skip),
+            update: CheckoutUpdate::default(),
+            panic: None,
+        }
+    }
+
+    /// Result for a repository whose checkout panicked
+    fn panicked(repo: &RepoConfig, acquisition: Acquisition, message: String) -> Self {
+        Self {
+            repo: repo.clone(),
+            path: None,
+            error: Some(format!("panicked: {}", message)),
+            source: CheckoutSource::Cloned,
+            acquisition,
+            commit_sha: None,
+            skipped_oversize: None,
+            update: CheckoutUpdate::default(),
+            panic: Some(message),
+        }
+    }
 }
 
 /// Clone a single repository
 ///
+/// The clone goes into a `<name>.partial-<pid>` sibling and is renamed into
+/// place only once git succeeds, so a failed clone never leaves a directory
+/// that a later run would mistake for a checkout.
+///
 /// # Arguments
 /// * `repo` - Repository configuration
 /// * `workdir` - Working directory to clone into
 /// * `github_token` - Optional GitHub token for private repos
+/// * `sparse_paths` - Sparse-checkout patterns; empty for a full checkout
+/// * `allow_reset` - Reset a reused checkout whose branch was force-pushed
 ///
 /// # Returns
-/// * `Result<PathBuf>` - Path to the cloned repository
-pub fn clone_repo(repo: &RepoConfig, workdir: &Path, github_token: Option<&str>) -> Result<PathBuf> {
-    // Create a safe directory name from the repo name
-    let dir_name = repo.name.replace('/', "_").replace('\\', "_");
-    let target_dir = workdir.join(&dir_name);
+/// * `Result<(PathBuf, CheckoutUpdate)>` - Path to the cloned repository, and
+///   what updating it did when it was reused
+pub fn clone_repo(
+    repo: &RepoConfig,
+    workdir: &Path,
+    github_token: Option<&str>,
+    sparse_paths: &[String],
+    allow_reset: bool,
+) -> Result<(PathBuf, CheckoutUpdate)> {
+    clone_repo_with(GIT, repo, workdir, github_token, sparse_paths, allow_reset)
+}
+
+/// Git executable used for clone and update
+const GIT: &str = "git";
+
+/// `clone_repo` with an explicit git executable
+fn clone_repo_with(
+    git: &str,
+    repo: &RepoConfig,
+    workdir: &Path,
+    github_token: Option<&str>,
+    sparse_paths: &[String],
+    allow_reset: bool,
+) -> Result<(PathBuf, CheckoutUpdate)> {
+    let target_dir = paths::checkout_dir(workdir, &repo.name);
     
     // Reuse existing directory if present (e.g. second run with same --workdir and --keep-repos)
     if target_dir.exists() {
-        debug!("Reusing existing directory: {}", target_dir.display());
-        if let Err(e) = update_existing_repo(repo, &target_dir) {
-            warn!("Failed to update existing repo {}: {}", repo.name, e);
-            // Fall back to using the existing checkout to avoid blocking scans
-            return Ok(target_dir);
+        if is_valid_checkout(git, &target_dir) {
+            debug!("[{}] Reusing existing directory: {}", repo.name, target_dir.display());
+            return match update_existing_repo(git, repo, &target_dir, allow_reset) {
+                Ok(update) => Ok((target_dir, update)),
+                Err(e) => {
+                    warn!("[{}] Failed to update existing checkout: {}", repo.name, e);
+                    // Fall back to using the existing checkout to avoid blocking scans
+                    Ok((target_dir, CheckoutUpdate::default()))
+                }
+            };
         }
-        return Ok(target_dir);
+        warn!("[{}] Discarding {}: not a git checkout with a valid HEAD, re-cloning", repo.name, target_dir.display());
+        std::fs::remove_dir_all(&target_dir)
+            .with_context(|| format!("Failed to remove stale directory: {}", target_dir.display()))?;
     }
     
-    info!("Cloning {} into {}", repo.name, target_dir.display());
+    info!("[{}] Cloning into {}", repo.name, target_dir.display());
     
     // Build clone URL (inject token for private repos if provided)
     let clone_url = if let Some(token) = github_token {
@@ -78,24 +198,35 @@ pub fn clone_repo(repo: &RepoConfig, workdir: &Path, github_token: Option<&str>)
         repo.url.clone()
     };
     
+    // Clone next to the target; a leftover partial dir from this pid is stale.
+    // Whatever git leaves behind is removed on any failure, panics included.
+    let partial_dir = paths::partial_dir(workdir, &repo.name);
+    let partial = PartialDir::new(partial_dir.clone())
+        .with_context(|| format!("Failed to remove stale directory: {}", partial_dir.display()))?;
+    
     // Build git clone command
-    let mut cmd = Command::new("git");
+    let mut cmd = Command::new(git);
     cmd.arg("clone")
         .arg("--depth")
         .arg(repo.depth().to_string())
         .arg("--branch")
         .arg(repo.branch())
-        .arg("--single-branch")
-        .arg(&clone_url)
-        .arg(&target_dir);
+        .arg("--single-branch");
+    if !sparse_paths.is_empty() {
+        // Files are checked out after the sparse patterns are set; blobs are fetched on demand
+        cmd.arg("--no-checkout").arg("--filter=blob:none");
+    }
+    cmd.arg(&clone_url).arg(&partial_dir);
     
     // Log without exposing token
-    debug!("Running: git clone --depth {} --branch {} --single-branch {} {}",
-           repo.depth(), repo.branch(), repo.url, target_dir.display());
+    debug!("[{}] Running: git clone --depth {} --branch {} --single-branch{} {} {}",
+           repo.name, repo.depth(), repo.branch(),
+           if sparse_paths.is_empty() { "" } else { " --no-checkout --filter=blob:none" },
+           repo.url, partial_dir.display());
     
     // Execute the command
     let output = cmd
-        .output()
+        .group_output()
         .with_context(|| format!("Failed to execute git clone for {}", repo.name))?;
     
     if !output.status.success() {
@@ -103,17 +234,87 @@ pub fn clone_repo(repo: &RepoConfig, workdir: &Path, github_token: Option<&str>)
         bail!("Git clone failed for {}: {}", repo.name, stderr.trim());
     }
     
-    info!("Successfully cloned {}", repo.name);
-    Ok(target_dir)
+    if !sparse_paths.is_empty() {
+        sparse_checkout(git, repo, &partial_dir, sparse_paths)?;
+    }
+    
+    partial
+        .persist(&target_dir)
+        .with_context(|| format!("Failed to move clone into place: {}", target_dir.display()))?;
+    
+    info!("[{}] Cloned", repo.name);
+    Ok((target_dir, CheckoutUpdate::default()))
+}
+
+/// Check out only `sparse_paths` in a `--no-checkout` clone
+///
+/// Falls back to checking out every file when git can't set up a sparse
+/// checkout (e.g. git older than 2.25).
+fn sparse_checkout(git: &str, repo: &RepoConfig, dir: &Path, sparse_paths: &[String]) -> Result<()> {
+    let sparse_output = Command::new(git)
+        .arg("-C")
+        .arg(dir)
+        .args(["sparse-checkout", "set", "--no-cone"])
+        .args(sparse_paths)
+        .group_output()
+        .with_context(|| format!("Failed to execute git sparse-checkout for {}", repo.name))?;
+    if !sparse_output.status.success() {
+        let stderr = String::from_utf8_lossy(&sparse_output.stderr);
+        warn!("[{}] Sparse checkout unavailable ({}); checking out the full repository",
+              repo.name, stderr.trim());
+    }
+
+    let checkout_output = Command::new(git)
+        .arg("-C")
+        .arg(dir)
+        .arg("checkout")
+        .arg(repo.branch())
+        .group_output()
+        .with_context(|| format!("Failed to checkout {} {}", repo.name, repo.branch()))?;
+    if !checkout_output.status.success() {
+        let stderr = String::from_utf8_lossy(&checkout_output.stderr);
+        bail!("Git checkout failed for {}: {}", repo.name, stderr.trim());
+    }
+    Ok(())
+}
+
+/// Whether a directory is a git checkout whose HEAD resolves to a commit
+fn is_valid_checkout(git: &str, dir: &Path) -> bool {
+    if !dir.join(".git").exists() {
+        return false;
+    }
+    Command::new(git)
+        .arg("-C")
+        .arg(dir)
+        .args(["rev-parse", "--verify", "--quiet", "HEAD"])
+        .group_output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+/// Commit a checkout's HEAD points at
+fn head_commit(git: &str, dir: &Path) -> Option<String> {
+    let output = Command::new(git)
+        .arg("-C")
+        .arg(dir)
+        .args(["rev-parse", "HEAD"])
+        .group_output()
+        .ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
 }
 
 /// Update an existing repository checkout
-fn update_existing_repo(repo: &RepoConfig, target_dir: &Path) -> Result<()> {
+///
+/// A branch that can't be fast-forwarded was rewritten upstream (e.g. by a
+/// force-push): with `allow_reset` the checkout is reset to the remote branch,
+/// otherwise it keeps its old commit and is marked stale, as it is when the
+/// branch no longer exists on the remote.
+fn update_existing_repo(git: &str, repo: &RepoConfig, target_dir: &Path, allow_reset: bool) -> Result<CheckoutUpdate> {
     let branch = repo.branch();
     let depth = repo.depth();
 
     // Fetch latest changes (shallow fetch if depth provided)
-    let mut fetch_cmd = Command::new("git");
+    let mut fetch_cmd = Command::new(git);
     fetch_cmd
         .arg("-C")
         .arg(target_dir)
@@ -124,42 +325,144 @@ fn update_existing_repo(repo: &RepoConfig, target_dir: &Path) -> Result<()> {
         fetch_cmd.arg("--depth").arg(depth.to_string());
     }
     let fetch_output = fetch_cmd
-        .output()
+        .group_output()
         .with_context(|| format!("Failed to fetch {}", repo.name))?;
     if !fetch_output.status.success() {
         let stderr = String::from_utf8_lossy(&fetch_output.stderr);
-        warn!("Git fetch failed for {}: {}", repo.name, stderr.trim());
+        if is_missing_remote_branch(&stderr) {
+            info!("[{}] Branch {} no longer exists on the remote; scanning the checkout at its old commit",
+                  repo.name, branch);
+            return Ok(CheckoutUpdate { stale: Some(StaleCheckout::BranchMissing), ..Default::default() });
+        }
+        warn!("[{}] Git fetch failed: {}", repo.name, stderr.trim());
     }
 
     // Ensure we are on the intended branch
-    let checkout_output = Command::new("git")
+    let checkout_output = Command::new(git)
         .arg("-C")
         .arg(target_dir)
         .arg("checkout")
         .arg(branch)
-        .output()
+        .group_output()
         .with_context(|| format!("Failed to checkout {} {}", repo.name, branch))?;
     if !checkout_output.status.success() {
         let stderr = String::from_utf8_lossy(&checkout_output.stderr);
-        warn!("Git checkout failed for {}: {}", repo.name, stderr.trim());
+        warn!("[{}] Git checkout failed: {}", repo.name, stderr.trim());
     }
 
     // Pull fast-forward only
-    let pull_output = Command::new("git")
+    let pull_output = Command::new(git)
         .arg("-C")
         .arg(target_dir)
         .arg("pull")
         .arg("--ff-only")
         .arg("origin")
         .arg(branch)
-        .output()
+        .group_output()
         .with_context(|| format!("Failed to pull {}", repo.name))?;
     if !pull_output.status.success() {
         let stderr = String::from_utf8_lossy(&pull_output.stderr);
-        warn!("Git pull failed for {}: {}", repo.name, stderr.trim());
+        if !is_non_fast_forward(&stderr) {
+            warn!("[{}] Git pull failed: {}", repo.name, stderr.trim());
+        } else if !allow_reset {
+            info!("[{}] Branch {} was rewritten upstream; scanning the checkout at its old commit (--allow-reset is off)",
+                  repo.name, branch);
+            return Ok(CheckoutUpdate { stale: Some(StaleCheckout::Diverged), ..Default::default() });
+        } else {
+            return Ok(match reset_to_remote(git, repo, target_dir) {
+                Ok(reset) => CheckoutUpdate { reset: Some(reset), ..Default::default() },
+                Err(e) => {
+                    warn!("[{}] Failed to reset rewritten branch {}: {:#}", repo.name, branch, e);
+                    CheckoutUpdate { stale: Some(StaleCheckout::Diverged), ..Default::default() }
+                }
+            });
+        }
     }
 
-    Ok(())
+    Ok(CheckoutUpdate::default())
+}
+
+/// Whether `git fetch` failed because the branch doesn't exist on the remote
+fn is_missing_remote_branch(stderr: &str) -> bool {
+    stderr.to_lowercase().contains("couldn't find remote ref")
+}
+
+/// Whether `git pull --ff-only` failed because the local branch is not an
+/// ancestor of the remote one (history rewritten upstream)
+fn is_non_fast_forward(stderr: &str) -> bool {
+    const MARKERS: &[&str] = &[
+        "not possible to fast-forward",
+        "diverging branches",
+        "non-fast-forward",
+        "refusing to merge unrelated histories",
+    ];
+    let stderr = stderr.to_lowercase();
+    MARKERS.iter().any(|marker| stderr.contains(marker))
+}
+
+/// Move a checkout to its rewritten remote branch (`git fetch` + `git reset --hard origin/<branch>`)
+fn reset_to_remote(git: &str, repo: &RepoConfig, target_dir: &Path) -> Result<CheckoutReset> {
+    let branch = repo.branch();
+    let depth = repo.depth();
+    let old_head = head_commit(git, target_dir).unwrap_or_default();
+
+    // Update origin/<branch> explicitly: the clone's refspec may not cover it
+    let mut fetch_cmd = Command::new(git);
+    fetch_cmd
+        .arg("-C")
+        .arg(target_dir)
+        .arg("fetch")
+        .arg("origin")
+        .arg(format!("+refs/heads/{0}:refs/remotes/origin/{0}", branch));
+    if depth > 0 {
+        fetch_cmd.arg("--depth").arg(depth.to_string());
+    }
+    let fetch_output = fetch_cmd
+        .group_output()
+        .with_context(|| format!("Failed to fetch {}", repo.name))?;
+    if !fetch_output.status.success() {
+        let stderr = String::from_utf8_lossy(&fetch_output.stderr);
+        bail!("Git fetch failed for {}: {}", repo.name, stderr.trim());
+    }
+
+    let reset_output = Command::new(git)
+        .arg("-C")
+        .arg(target_dir)
+        .arg("reset")
+        .arg("--hard")
+        .arg(format!("origin/{}", branch))
+        .group_output()
+        .with_context(|| format!("Failed to reset {}", repo.name))?;
+    if !reset_output.status.success() {
+        let stderr = String::from_utf8_lossy(&reset_output.stderr);
+        bail!("Git reset failed for {}: {}", repo.name, stderr.trim());
+    }
+
+    let new_head = head_commit(git, target_dir).unwrap_or_default();
+    info!("[{}] Branch {} was rewritten upstream; reset checkout from {} to {}",
+          repo.name, branch, old_head, new_head);
+    Ok(CheckoutReset { old_head, new_head })
+}
+
+/// Fetch one repository with git or as a tarball, as configured
+fn acquire_repo(
+    repo: &RepoConfig,
+    workdir: &Path,
+    github_token: Option<&str>,
+    sparse_paths: &[String],
+    acquire: AcquireMode,
+    allow_reset: bool,
+) -> Result<(PathBuf, Acquisition, Option<String>, CheckoutUpdate)> {
+    if repo.acquire.unwrap_or(acquire) == AcquireMode::Tarball {
+        if github_repo_path(&repo.url).is_some() {
+            let (path, commit_sha) = download_tarball(repo, workdir, github_token)?;
+            return Ok((path, Acquisition::Tarball, commit_sha, CheckoutUpdate::default()));
+        }
+        warn!("[{}] Tarball download needs a GitHub URL, cloning with git instead", repo.name);
+    }
+    let (path, update) = clone_repo(repo, workdir, github_token, sparse_paths, allow_reset)?;
+    let commit_sha = head_commit(GIT, &path);
+    Ok((path, Acquisition::Git, commit_sha, update))
 }
 
 /// Clone all repositories in parallel
@@ -168,31 +471,456 @@ fn update_existing_repo(repo: &RepoConfig, target_dir: &Path) -> Result<()> {
 /// * `repos` - List of repository configurations
 /// * `workdir` - Working directory to clone into
 /// * `github_token` - Optional GitHub token for private repos
+/// * `sparse_paths` - Sparse-checkout patterns; empty for full checkouts
+/// * `acquire` - How to fetch repositories that don't set `acquire` themselves
+/// * `max_repo_size_mb` - Size limit for repositories that don't set their own (None or 0 = unlimited)
+/// * `allow_reset` - Reset reused checkouts whose branch was force-pushed
 ///
 /// # Returns
 /// * Vector of CloneResult for each repository
-pub fn clone_all_repos(repos: &[RepoConfig], workdir: &Path, github_token: Option<&str>) -> Vec<CloneResult> {
+pub fn clone_all_repos(
+    repos: &[RepoConfig],
+    workdir: &Path,
+    github_token: Option<&str>,
+    sparse_paths: &[String],
+    acquire: AcquireMode,
+    max_repo_size_mb: Option<u64>,
+    allow_reset: bool,
+) -> Vec<CloneResult> {
     // Ensure workdir exists
     if let Err(e) = std::fs::create_dir_all(workdir) {
         warn!("Failed to create workdir {}: {}", workdir.display(), e);
     }
+    // Sizes are looked up before cloning only with a token (the API's anonymous rate limit is tiny)
+    let size_limited = sparse_paths.is_empty()
+        && repos.iter().any(|r| r.max_repo_size_mb.or(max_repo_size_mb).is_some_and(|mb| mb > 0));
+    let size_client = match (size_limited && github_token.is_some()).then(size_lookup_client) {
+        Some(Ok(c)) => Some(c),
+        Some(Err(e)) => {
+            warn!("Repository sizes can't be looked up before cloning: {}", e);
+            None
+        }
+        None => None,
+    };
     
-    repos
+    let results: Vec<CloneResult> = repos
         .par_iter()
         .map(|repo| {
-            match clone_repo(repo, workdir, github_token) {
-                Ok(path) => CloneResult {
+            let acquisition = match repo.acquire.unwrap_or(acquire) {
+                AcquireMode::Git => Acquisition::Git,
+                AcquireMode::Tarball => Acquisition::Tarball,
+            };
+            // A panic while checking out one repository fails only that repository
+            cleanup::catch_panic(|| {
+                // Sparse checkouts are blobless partial clones: only the scoped files are downloaded
+                let limit_mb = repo.max_repo_size_mb.or(max_repo_size_mb).filter(|&mb| mb > 0 && sparse_paths.is_empty());
+                let known_size = match (limit_mb, &size_client, github_token) {
+                    (Some(_), Some(client), Some(token)) => github_repo_size(client, GITHUB_API_URL, repo, token),
+                    _ => None,
+                };
+                if let Some(limit) = limit_mb {
+                    if let Some(skip) = known_size.and_then(|size| oversize_skip(repo, size, limit, SizeSource::GithubApi)) {
+                        return CloneResult::skipped(repo, acquisition, skip);
+                    }
+                }
+            
+                match acquire_repo(repo, workdir, github_token, sparse_paths, acquire, allow_reset) {
+                    Ok((path, acquisition, commit_sha, update)) => {
+                        // Size unknown before cloning: measure the checkout and drop it if it's too large
+                        if let (Some(limit), None) = (limit_mb, known_size) {
+                            if let Some(skip) = oversize_skip(repo, dir_size(&path), limit, SizeSource::Checkout) {
+                                if let Err(e) = std::fs::remove_dir_all(&path) {
+                                    warn!("[{}] Failed to remove oversize checkout {}: {}", repo.name, path.display(), e);
+                                }
+                                return CloneResult::skipped(repo, acquisition, skip);
+                            }
+                        }
+                        CloneResult {
+                            repo: repo.clone(),
+                            path: Some(path),
+                            error: None,
+                            source: CheckoutSource::Cloned,
+                            acquisition,
+                            commit_sha,
+                            skipped_oversize: None,
+                            update,
+                            panic: None,
+                        }
+                    }
+                    Err(e) => {
+                        warn!("[{}] Failed to clone: {}", repo.name, e);
+                        CloneResult {
+                            repo: repo.clone(),
+                            path: None,
+                            error: Some(e.to_string()),
+                            source: CheckoutSource::Cloned,
+                            acquisition,
+                            commit_sha: None,
+                            skipped_oversize: None,
+                            update: CheckoutUpdate::default(),
+                            panic: None,
+                        }
+                    }
+                }
+            })
+            .unwrap_or_else(|message| {
+                warn!("[{}] Checkout panicked: {}", repo.name, message);
+                CloneResult::panicked(repo, acquisition, message)
+            })
+        })
+        .collect();
+    
+    // Remember what was checked out, so `clean` can remove exactly that later
+    let checkouts: Vec<PathBuf> = results.iter().filter_map(|r| r.path.clone()).collect();
+    if !checkouts.is_empty() {
+        if let Err(e) = paths::record_checkouts(workdir, &checkouts) {
+            warn!("{:#}", e);
+        }
+    }
+    results
+}
+
+// ============================================================================
+// Repository Size Limit
+// ============================================================================
+
+/// GitHub REST API base URL
+const GITHUB_API_URL: &str = "https://api.github.com";
+
+/// Timeout for one repository size lookup
+const SIZE_LOOKUP_TIMEOUT_SECS: u64 = 30;
+
+/// HTTP client for repository size lookups
+fn size_lookup_client() -> Result<reqwest::blocking::Client> {
+    reqwest::blocking::Client::builder()
+        .user_agent(concat!("nim-usage-scanner/", env!("CARGO_PKG_VERSION")))
+        .timeout(std::time::Duration::from_secs(SIZE_LOOKUP_TIMEOUT_SECS))
+        .build()
+        .context("Failed to create HTTP client")
+}
+
+/// Size in bytes of a GitHub repository, from the REST API `size` field (KiB)
+///
+/// None for repositories not on GitHub or when the lookup fails; the caller
+/// then measures the checkout instead.
+fn github_repo_size(
+    client: &reqwest::blocking::Client,
+    api_base: &str,
+    repo: &RepoConfig,
+    github_token: &str,
+) -> Option<u64> {
+    let (owner, name) = github_repo_path(&repo.url)?;
+    let url = format!("{}/repos/{}/{}", api_base.trim_end_matches('/'), owner, name);
+    let response = client
+        .get(&url)
+        .header(reqwest::header::AUTHORIZATION, format!("token {}", github_token))
+        .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+        .send();
+    let size_kib = match response {
+        Ok(r) if r.status().is_success() => r.json::<serde_json::Value>().ok()?.get("size")?.as_u64(),
+        Ok(r) => {
+            debug!("[{}] Size lookup failed: HTTP {}", repo.name, r.status());
+            None
+        }
+        Err(e) => {
+            debug!("[{}] Size lookup failed: {}", repo.name, e);
+            None
+        }
+    }?;
+    Some(size_kib * 1024)
+}
+
+/// Total size in bytes of the files under a directory, `.git` included (links not followed)
+pub fn dir_size(dir: &Path) -> u64 {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return 0,
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(t) if t.is_dir() => dir_size(&entry.path()),
+            Ok(t) if t.is_file() => entry.metadata().map(|m| m.len()).unwrap_or(0),
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Size in bytes of a checkout's files, `.git` excluded: an estimate of the work to scan it
+pub fn checkout_size(dir: &Path) -> u64 {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return 0,
+    };
+    entries
+        .flatten()
+        .filter(|entry| entry.file_name() != ".git")
+        .map(|entry| match entry.file_type() {
+            Ok(t) if t.is_dir() => dir_size(&entry.path()),
+            Ok(t) if t.is_file() => entry.metadata().map(|m| m.len()).unwrap_or(0),
+            _ => 0,
+        })
+        .sum()
+}
+
+/// The skip record for a repository over its size limit, None if it fits
+fn oversize_skip(repo: &RepoConfig, size_bytes: u64, limit_mb: u64, measured: SizeSource) -> Option<OversizeSkip> {
+    const MB: u64 = 1024 * 1024;
+    if size_bytes <= limit_mb * MB {
+        return None;
+    }
+    let size_mb = size_bytes.div_ceil(MB);
+    warn!("[{}] Skipping: {} MB is over the {} MB size limit", repo.name, size_mb, limit_mb);
+    Some(OversizeSkip {
+        repository: repo.name.clone(),
+        size_mb,
+        limit_mb,
+        measured,
+    })
+}
+
+// ============================================================================
+// Tarball Download
+// ============================================================================
+
+/// Base URL for GitHub branch tarballs
+const CODELOAD_URL: &str = "https://codeload.github.com";
+
+/// Largest compressed tarball accepted
+const MAX_TARBALL_BYTES: u64 = 512 * 1024 * 1024;
+
+/// Largest total size of the files unpacked from one tarball
+const MAX_UNPACKED_BYTES: u64 = 4 * 1024 * 1024 * 1024;
+
+/// Redirects followed when downloading a tarball
+const MAX_TARBALL_REDIRECTS: usize = 5;
+
+/// Timeout for one tarball download
+const TARBALL_TIMEOUT_SECS: u64 = 600;
+
+/// `(owner, repo)` of a GitHub HTTPS or SSH URL
+fn github_repo_path(url: &str) -> Option<(String, String)> {
+    let path = ["https://github.com/", "http://github.com/", "ssh://git@github.com/", "git@github.com:"]
+        .iter()
+        .find_map(|prefix| url.strip_prefix(prefix))?;
+    let path = path.trim_end_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    match path.split('/').collect::<Vec<_>>().as_slice() {
+        [owner, name] if !owner.is_empty() && !name.is_empty() => Some((owner.to_string(), name.to_string())),
+        _ => None,
+    }
+}
+
+/// HTTP client for tarball downloads (bounded redirects)
+fn tarball_client() -> Result<reqwest::blocking::Client> {
+    reqwest::blocking::Client::builder()
+        .redirect(reqwest::redirect::Policy::limited(MAX_TARBALL_REDIRECTS))
+        .timeout(std::time::Duration::from_secs(TARBALL_TIMEOUT_SECS))
+        .build()
+        .context("Failed to create HTTP client")
+}
+
+/// Download the branch tarball of a GitHub repository and unpack it into the workdir
+///
+/// The files land in `workdir/<sanitized name>`, like a clone. Returns the
+/// checkout path and the commit SHA when the tarball records one.
+pub fn download_tarball(repo: &RepoConfig, workdir: &Path, github_token: Option<&str>) -> Result<(PathBuf, Option<String>)> {
+    download_tarball_with(&tarball_client()?, CODELOAD_URL, repo, workdir, github_token, MAX_TARBALL_BYTES)
+}
+
+/// `download_tarball` with an explicit client, base URL and download size limit
+fn download_tarball_with(
+    client: &reqwest::blocking::Client,
+    base_url: &str,
+    repo: &RepoConfig,
+    workdir: &Path,
+    github_token: Option<&str>,
+    max_bytes: u64,
+) -> Result<(PathBuf, Option<String>)> {
+    let (owner, name) = match github_repo_path(&repo.url) {
+        Some(p) => p,
+        None => bail!("Not a GitHub repository URL: {}", repo.url),
+    };
+    let url = format!("{}/{}/{}/tar.gz/refs/heads/{}", base_url.trim_end_matches('/'), owner, name, repo.branch());
+    info!("[{}] Downloading {}", repo.name, url);
+
+    let mut request = client.get(&url);
+    if let Some(token) = github_token {
+        // reqwest drops this header when a redirect leaves the host
+        request = request.header(reqwest::header::AUTHORIZATION, format!("token {}", token));
+    }
+    let response = request
+        .send()
+        .with_context(|| format!("Failed to download tarball for {}", repo.name))?;
+    let status = response.status();
+    if !status.is_success() {
+        bail!("Tarball download failed for {}: HTTP {}", repo.name, status);
+    }
+    if let Some(len) = response.content_length().filter(|&len| len > max_bytes) {
+        bail!("Tarball for {} is {} bytes, over the {} byte limit", repo.name, len, max_bytes);
+    }
+
+    let target_dir = paths::checkout_dir(workdir, &repo.name);
+    let partial_dir = paths::partial_dir(workdir, &repo.name);
+    let partial = PartialDir::new(partial_dir.clone())
+        .with_context(|| format!("Failed to remove stale directory: {}", partial_dir.display()))?;
+    std::fs::create_dir_all(&partial_dir)
+        .with_context(|| format!("Failed to create directory: {}", partial_dir.display()))?;
+
+    let reader = LimitedReader { inner: response, remaining: max_bytes };
+    let commit_sha = unpack_tarball(reader, &partial_dir, MAX_UNPACKED_BYTES)
+        .with_context(|| format!("Failed to unpack tarball for {}", repo.name))?;
+
+    // A tarball always replaces the previous download (or checkout)
+    if target_dir.exists() {
+        std::fs::remove_dir_all(&target_dir)
+            .with_context(|| format!("Failed to remove previous checkout: {}", target_dir.display()))?;
+    }
+    partial
+        .persist(&target_dir)
+        .with_context(|| format!("Failed to move download into place: {}", target_dir.display()))?;
+
+    if commit_sha.is_none() {
+        warn!("[{}] Tarball does not record a commit SHA", repo.name);
+    }
+    info!("[{}] Downloaded", repo.name);
+    Ok((target_dir, commit_sha))
+}
+
+/// Reader that fails once more than `remaining` bytes have been read
+struct LimitedReader<R> {
+    inner: R,
+    remaining: u64,
+}
+
+impl<R: Read> Read for LimitedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        if n as u64 > self.remaining {
+            return Err(std::io::Error::other("download exceeds the size limit"));
+        }
+        self.remaining -= n as u64;
+        Ok(n)
+    }
+}
+
+/// Whether a string is a full hex commit SHA
+fn is_commit_sha(s: &str) -> bool {
+    s.len() == 40 && s.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Unpack a gzipped tarball into `dest`, dropping its top-level directory
+///
+/// Only regular files and directories are written; links and special files are
+/// skipped, and any path that is absolute or contains `..` is an error. Returns
+/// the commit SHA from the pax global header (`git archive` writes it as
+/// `comment`) or, failing that, from a top-level directory named `<repo>-<sha>`.
+fn unpack_tarball<R: Read>(reader: R, dest: &Path, max_unpacked: u64) -> Result<Option<String>> {
+    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(reader));
+    let mut commit_sha = None;
+    let mut top_dir: Option<String> = None;
+    let mut unpacked = 0u64;
+
+    for entry in archive.entries().context("Failed to read tarball")? {
+        let mut entry = entry.context("Failed to read tarball entry")?;
+        let entry_type = entry.header().entry_type();
+        if entry_type == tar::EntryType::XGlobalHeader {
+            if let Some(extensions) = entry.pax_extensions()? {
+                for ext in extensions.flatten() {
+                    if ext.key() == Ok("comment") {
+                        commit_sha = ext.value().ok().map(str::trim).filter(|v| is_commit_sha(v)).map(str::to_string);
+                    }
+                }
+            }
+            continue;
+        }
+
+        let path = entry.path().context("Invalid path in tarball")?.into_owned();
+        let mut components = path.components();
+        match components.next() {
+            Some(Component::Normal(first)) => {
+                top_dir.get_or_insert_with(|| first.to_string_lossy().to_string());
+            }
+            _ => bail!("Unsafe path in tarball: {}", path.display()),
+        }
+        let mut relative = PathBuf::new();
+        for component in components {
+            match component {
+                Component::Normal(part) => relative.push(part),
+                Component::CurDir => {}
+                _ => bail!("Unsafe path in tarball: {}", path.display()),
+            }
+        }
+        if relative.as_os_str().is_empty() {
+            continue;
+        }
+
+        let target = dest.join(&relative);
+        if entry_type.is_dir() {
+            std::fs::create_dir_all(&target)
+                .with_context(|| format!("Failed to create directory: {}", target.display()))?;
+        } else if entry_type.is_file() {
+            unpacked += entry.size();
+            if unpacked > max_unpacked {
+                bail!("Tarball unpacks to more than {} bytes", max_unpacked);
+            }
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+            }
+            let mut file = std::fs::File::create(&target)
+                .with_context(|| format!("Failed to create file: {}", target.display()))?;
+            std::io::copy(&mut entry, &mut file)
+                .with_context(|| format!("Failed to write file: {}", target.display()))?;
+        } else {
+            debug!("Skipping {:?} entry in tarball: {}", entry_type, path.display());
+        }
+    }
+
+    if commit_sha.is_none() {
+        commit_sha = top_dir
+            .as_deref()
+            .and_then(|dir| dir.rsplit('-').next())
+            .filter(|suffix| is_commit_sha(suffix))
+            .map(str::to_string);
+    }
+    Ok(commit_sha)
+}
+
+/// Use checkouts already present in the workdir instead of cloning
+///
+/// Each repository is expected at `workdir/<sanitized name>` (the same layout
+/// clone_repo produces). No git commands are run; a missing directory is
+/// reported as an error for that repository only.
+pub fn use_existing_checkouts(repos: &[RepoConfig], workdir: &Path) -> Vec<CloneResult> {
+    repos
+        .iter()
+        .map(|repo| {
+            let target_dir = paths::checkout_dir(workdir, &repo.name);
+            if target_dir.is_dir() {
+                debug!("[{}] Using pre-provisioned checkout: {}", repo.name, target_dir.display());
+                CloneResult {
                     repo: repo.clone(),
-                    path: Some(path),
+                    path: Some(target_dir),
                     error: None,
-                },
-                Err(e) => {
-                    warn!("Failed to clone {}: {}", repo.name, e);
-                    CloneResult {
-                        repo: repo.clone(),
-                        path: None,
-                        error: Some(e.to_string()),
-                    }
+                    source: CheckoutSource::PreProvisioned,
+                    acquisition: Acquisition::Local,
+                    commit_sha: None,
+                    skipped_oversize: None,
+                    update: CheckoutUpdate::default(),
+                    panic: None,
+                }
+            } else {
+                CloneResult {
+                    repo: repo.clone(),
+                    path: None,
+                    error: Some(format!("Pre-provisioned checkout not found: {}", target_dir.display())),
+                    source: CheckoutSource::PreProvisioned,
+                    acquisition: Acquisition::Local,
+                    commit_sha: None,
+                    skipped_oversize: None,
+                    update: CheckoutUpdate::default(),
+                    panic: None,
                 }
             }
         })
@@ -212,13 +940,351 @@ pub fn cleanup_repos(workdir: &Path) -> Result<()> {
     Ok(())
 }
 
-/// Get statistics about clone results
+/// Get statistics about clone results: (succeeded, failed); size-limit skips are neither
 pub fn clone_stats(results: &[CloneResult]) -> (usize, usize) {
     let success = results.iter().filter(|r| r.is_success()).count();
-    let failed = results.len() - success;
+    let skipped = results.iter().filter(|r| r.skipped_oversize.is_some()).count();
+    let failed = results.len() - success - skipped;
     (success, failed)
 }
 
+// ============================================================================
+// Git LFS
+// ============================================================================
+
+/// Fetch the Git LFS content of the given files into a checkout (`--fetch-lfs`)
+///
+/// Only the listed paths are downloaded (`git lfs pull --include`), so the
+/// rest of the repository's LFS objects stay on the server.
+pub fn lfs_pull(repo_dir: &Path, paths: &[String]) -> Result<()> {
+    lfs_pull_with(GIT, repo_dir, paths)
+}
+
+fn lfs_pull_with(git: &str, repo_dir: &Path, paths: &[String]) -> Result<()> {
+    info!("Fetching {} Git LFS file(s) in {}", paths.len(), repo_dir.display());
+    let output = Command::new(git)
+        .arg("-C")
+        .arg(repo_dir)
+        .args(["lfs", "pull", "--include"])
+        .arg(paths.join(","))
+        .group_output()
+        .context("Failed to execute git lfs pull")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("git lfs pull failed in {}: {}", repo_dir.display(), stderr.trim());
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Git Blame
+// ============================================================================
+
+/// Note on a line whose last change may predate a shallow clone's history
+const SHALLOW_BLAME_NOTE: &str = "shallow clone: the line may be older than the fetched history (raise `depth`)";
+
+/// Record who last changed each finding's line (`--blame`), in git clones only
+///
+/// Lines are grouped per file, so each file costs one git process, and files
+/// are blamed in parallel. Findings in tarball downloads and pre-provisioned
+/// checkouts get no blame. Returns the number of files blamed.
+pub fn blame_findings(clones: &[CloneResult], findings: &mut [&mut NimFindings]) -> usize {
+    let checkouts: HashMap<&str, &Path> = clones
+        .iter()
+        .filter(|r| r.acquisition == Acquisition::Git)
+        .filter_map(|r| Some((r.repo.name.as_str(), r.path.as_deref()?)))
+        .collect();
+    let mut wanted: BTreeMap<(String, String), BTreeSet<usize>> = BTreeMap::new();
+    for category in findings.iter() {
+        let lines = category.local_nim.iter().map(|m| (&m.repository, &m.file_path, m.line_number))
+            .chain(category.hosted_nim.iter().map(|m| (&m.repository, &m.file_path, m.line_number)));
+        for (repository, file_path, line) in lines.filter(|(repository, _, _)| checkouts.contains_key(repository.as_str())) {
+            wanted.entry((repository.clone(), file_path.clone())).or_default().insert(line);
+        }
+    }
+    let shallow: HashMap<&str, HashSet<String>> = wanted
+        .keys()
+        .map(|(repository, _)| repository.as_str())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(|repository| (repository, shallow_commits(GIT, checkouts[repository])))
+        .collect();
+    
+    let blamed: HashMap<(String, String), BTreeMap<usize, LineBlame>> = wanted
+        .par_iter()
+        .map(|((repository, file_path), lines)| {
+            let lines: Vec<usize> = lines.iter().copied().collect();
+            let dir = checkouts[repository.as_str()];
+            let blame = blame_lines(GIT, dir, file_path, &lines, &shallow[repository.as_str()])
+                .unwrap_or_else(|e| {
+                    warn!("[{}] {:#}", repository, e);
+                    let failed = LineBlame { note: Some(format!("blame failed: {:#}", e)), ..Default::default() };
+                    lines.iter().map(|&line| (line, failed.clone())).collect()
+                });
+            ((repository.clone(), file_path.clone()), blame)
+        })
+        .collect();
+    
+    let blame_of = |repository: &str, file_path: &str, line: usize| {
+        blamed.get(&(repository.to_string(), file_path.to_string())).and_then(|b| b.get(&line)).cloned()
+    };
+    for category in findings.iter_mut() {
+        for m in &mut category.local_nim {
+            m.blame = blame_of(&m.repository, &m.file_path, m.line_number);
+        }
+        for m in &mut category.hosted_nim {
+            m.blame = blame_of(&m.repository, &m.file_path, m.line_number);
+        }
+    }
+    blamed.len()
+}
+
+/// Commits at the edge of a shallow clone's history (empty for a full clone)
+fn shallow_commits(git: &str, repo_dir: &Path) -> HashSet<String> {
+    let output = Command::new(git)
+        .arg("-C")
+        .arg(repo_dir)
+        .args(["rev-parse", "--git-path", "shallow"])
+        .group_output();
+    let path = match output {
+        Ok(o) if o.status.success() => repo_dir.join(String::from_utf8_lossy(&o.stdout).trim()),
+        _ => return HashSet::new(),
+    };
+    std::fs::read_to_string(path)
+        .map(|content| content.lines().map(|sha| sha.trim().to_string()).filter(|sha| !sha.is_empty()).collect())
+        .unwrap_or_default()
+}
+
+/// Last change to each of `lines` (1-indexed) of a file, from one `git blame --porcelain`
+///
+/// A line attributed to a commit in `shallow` gets empty fields and a note: it
+/// may have been changed earlier, in history the clone doesn't have.
+fn blame_lines(
+    git: &str,
+    repo_dir: &Path,
+    file_path: &str,
+    lines: &[usize],
+    shallow: &HashSet<String>,
+) -> Result<BTreeMap<usize, LineBlame>> {
+    let mut cmd = Command::new(git);
+    cmd.arg("-C").arg(repo_dir).args(["blame", "--porcelain"]);
+    for line in lines {
+        cmd.arg("-L").arg(format!("{},{}", line, line));
+    }
+    let output = cmd
+        .args(["HEAD", "--", file_path])
+        .group_output()
+        .with_context(|| format!("Failed to execute git blame for {}", file_path))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("git blame failed for {}: {}", file_path, stderr.trim());
+    }
+    
+    let porcelain = parse_blame_porcelain(&String::from_utf8_lossy(&output.stdout));
+    Ok(porcelain
+        .into_iter()
+        .map(|(line, sha, commit)| {
+            let blame = match shallow.contains(&sha) {
+                true => LineBlame { note: Some(SHALLOW_BLAME_NOTE.to_string()), ..Default::default() },
+                false => LineBlame {
+                    author_email: commit.author_email,
+                    commit_sha: Some(sha),
+                    author_date: commit.author_date,
+                    note: None,
+                },
+            };
+            (line, blame)
+        })
+        .collect())
+}
+
+/// Author of a commit as given in blame output
+#[derive(Debug, Clone, Default)]
+struct BlameCommit {
+    author_email: Option<String>,
+    author_time: Option<i64>,
+    author_date: Option<String>,
+}
+
+/// (final line, commit SHA, author) for each line of `git blame --porcelain` output
+///
+/// Each line starts with a `<sha> <orig line> <final line> [<count>]` header;
+/// the commit's `author-*` headers follow only the first time it appears.
+fn parse_blame_porcelain(output: &str) -> Vec<(usize, String, BlameCommit)> {
+    let mut commits: HashMap<String, BlameCommit> = HashMap::new();
+    let mut lines = Vec::new();
+    let mut current: Option<String> = None;
+    for line in output.lines() {
+        if line.starts_with('\t') {
+            current = None;
+            continue;
+        }
+        match current {
+            None => {
+                let mut fields = line.split(' ');
+                let (sha, final_line) = match (fields.next(), fields.nth(1).and_then(|n| n.parse().ok())) {
+                    (Some(sha), Some(final_line)) => (sha.to_string(), final_line),
+                    _ => continue,
+                };
+                commits.entry(sha.clone()).or_default();
+                lines.push((final_line, sha.clone()));
+                current = Some(sha);
+            }
+            Some(ref sha) => {
+                let commit = commits.entry(sha.clone()).or_default();
+                match line.split_once(' ') {
+                    Some(("author-mail", mail)) => {
+                        commit.author_email = Some(mail.trim_start_matches('<').trim_end_matches('>').to_string());
+                    }
+                    Some(("author-time", time)) => commit.author_time = time.parse().ok(),
+                    Some(("author-tz", tz)) => commit.author_date = commit.author_time.and_then(|t| blame_date(t, tz)),
+                    _ => {}
+                }
+            }
+        }
+    }
+    lines
+        .into_iter()
+        .map(|(line, sha)| {
+            let commit = commits.get(&sha).cloned().unwrap_or_default();
+            (line, sha, commit)
+        })
+        .collect()
+}
+
+/// RFC 3339 date of a blame `author-time` (Unix seconds) in its `author-tz` (`+0200`)
+fn blame_date(time: i64, tz: &str) -> Option<String> {
+    let sign = match tz.get(..1)? {
+        "-" => -1,
+        _ => 1,
+    };
+    let hours: i32 = tz.get(1..3)?.parse().ok()?;
+    let minutes: i32 = tz.get(3..5)?.parse().ok()?;
+    let offset = chrono::FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))?;
+    Some(chrono::DateTime::from_timestamp(time, 0)?.with_timezone(&offset).to_rfc3339())
+}
+
+// ============================================================================
+// GitHub Organization Discovery
+// ============================================================================
+
+/// Repositories requested per page when listing an organization
+const ORG_REPOS_PER_PAGE: usize = 100;
+
+/// Repositories of a GitHub organization as repos.yaml entries (`init --github-org`)
+///
+/// Each entry uses the repository's default branch. Archived repositories are
+/// listed but disabled, so they can be switched on by hand.
+pub fn discover_org_repos(org: &str, github_token: &str) -> Result<Vec<RepoConfig>> {
+    list_org_repos(&size_lookup_client()?, GITHUB_API_URL, org, github_token)
+}
+
+fn list_org_repos(
+    client: &reqwest::blocking::Client,
+    api_base: &str,
+    org: &str,
+    github_token: &str,
+) -> Result<Vec<RepoConfig>> {
+    let mut repos = Vec::new();
+    for page in 1.. {
+        let url = format!(
+            "{}/orgs/{}/repos?per_page={}&page={}",
+            api_base.trim_end_matches('/'), org, ORG_REPOS_PER_PAGE, page
+        );
+        let response = client
+            .get(&url)
+            .header(reqwest::header::AUTHORIZATION, format!("token {}", github_token))
+            .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+            .send()
+            .with_context(|| format!("Failed to list repositories of {}", org))?;
+        if !response.status().is_success() {
+            bail!("Failed to list repositories of {}: HTTP {}", org, response.status());
+        }
+        let listed: Vec<serde_json::Value> = response
+            .json()
+            .with_context(|| format!("Failed to parse repository list of {}", org))?;
+
+        for entry in &listed {
+            let (full_name, url) = match (entry["full_name"].as_str(), entry["clone_url"].as_str()) {
+                (Some(full_name), Some(url)) => (full_name, url),
+                _ => continue,
+            };
+            repos.push(RepoConfig {
+                name: full_name.to_string(),
+                url: url.to_string(),
+                branch: entry["default_branch"].as_str().map(str::to_string),
+                enabled: !entry["archived"].as_bool().unwrap_or(false),
+                ..Default::default()
+            });
+        }
+        if listed.len() < ORG_REPOS_PER_PAGE {
+            break;
+        }
+    }
+    repos.sort_by(|a, b| a.name.cmp(&b.name));
+    info!("Discovered {} repositories in {}", repos.len(), org);
+    Ok(repos)
+}
+
+// ============================================================================
+// GitHub Repository Metadata
+// ============================================================================
+
+/// Description, topics and homepage of each GitHub repository (--github-metadata)
+///
+/// Returns `(repository name, fields)` for the repositories whose metadata
+/// could be fetched; others (not on GitHub, lookup failed) are left out.
+pub fn fetch_repo_metadata(repos: &[RepoConfig], github_token: Option<&str>) -> Vec<(String, Vec<(MetadataField, String)>)> {
+    let client = match size_lookup_client() {
+        Ok(client) => client,
+        Err(e) => {
+            warn!("GitHub metadata can't be fetched: {}", e);
+            return Vec::new();
+        }
+    };
+    if github_token.is_none() {
+        warn!("No GitHub token provided; metadata lookups are subject to the anonymous API rate limit");
+    }
+    repos
+        .par_iter()
+        .filter_map(|repo| Some((repo.name.clone(), github_repo_metadata(&client, GITHUB_API_URL, repo, github_token)?)))
+        .collect()
+}
+
+/// Metadata fields of one repository, from the REST API repository object
+fn github_repo_metadata(
+    client: &reqwest::blocking::Client,
+    api_base: &str,
+    repo: &RepoConfig,
+    github_token: Option<&str>,
+) -> Option<Vec<(MetadataField, String)>> {
+    let (owner, name) = github_repo_path(&repo.url)?;
+    let url = format!("{}/repos/{}/{}", api_base.trim_end_matches('/'), owner, name);
+    let mut request = client.get(&url).header(reqwest::header::ACCEPT, "application/vnd.github+json");
+    if let Some(token) = github_token {
+        request = request.header(reqwest::header::AUTHORIZATION, format!("token {}", token));
+    }
+    let entry = match request.send() {
+        Ok(r) if r.status().is_success() => r.json::<serde_json::Value>().ok()?,
+        Ok(r) => {
+            warn!("[{}] Metadata lookup failed: HTTP {}", repo.name, r.status());
+            return None;
+        }
+        Err(e) => {
+            warn!("[{}] Metadata lookup failed: {}", repo.name, e);
+            return None;
+        }
+    };
+    let text = |key: &str| entry[key].as_str().map(str::trim).filter(|s| !s.is_empty()).map(str::to_string);
+    let mut fields = Vec::new();
+    fields.extend(text("description").map(|d| (MetadataField::Description, d)));
+    fields.extend(entry["topics"].as_array().into_iter().flatten()
+        .filter_map(|t| t.as_str())
+        .map(|t| (MetadataField::Topic, t.to_string())));
+    fields.extend(text("homepage").map(|h| (MetadataField::Homepage, h)));
+    Some(fields)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -233,9 +1299,16 @@ mod tests {
                 branch: None,
                 depth: None,
                 enabled: true,
+                ..Default::default()
             },
             path: Some(PathBuf::from("/tmp/test")),
             error: None,
+            source: CheckoutSource::Cloned,
+            acquisition: Acquisition::Git,
+            commit_sha: None,
+            skipped_oversize: None,
+            update: CheckoutUpdate::default(),
+            panic: None,
         };
         assert!(success.is_success());
 
@@ -246,9 +1319,16 @@ mod tests {
                 branch: None,
                 depth: None,
                 enabled: true,
+                ..Default::default()
             },
             path: None,
             error: Some("Clone failed".to_string()),
+            source: CheckoutSource::Cloned,
+            acquisition: Acquisition::Git,
+            commit_sha: None,
+            skipped_oversize: None,
+            update: CheckoutUpdate::default(),
+            panic: None,
         };
         assert!(!failure.is_success());
     }
@@ -263,9 +1343,16 @@ mod tests {
                     branch: None,
                     depth: None,
                     enabled: true,
+                    ..Default::default()
                 },
                 path: Some(PathBuf::from("/tmp/repo1")),
                 error: None,
+                source: CheckoutSource::Cloned,
+                acquisition: Acquisition::Git,
+                commit_sha: None,
+                skipped_oversize: None,
+                update: CheckoutUpdate::default(),
+                panic: None,
             },
             CloneResult {
                 repo: RepoConfig {
@@ -274,9 +1361,16 @@ mod tests {
                     branch: None,
                     depth: None,
                     enabled: true,
+                    ..Default::default()
                 },
                 path: None,
                 error: Some("Failed".to_string()),
+                source: CheckoutSource::Cloned,
+                acquisition: Acquisition::Git,
+                commit_sha: None,
+                skipped_oversize: None,
+                update: CheckoutUpdate::default(),
+                panic: None,
             },
         ];
 
@@ -296,16 +1390,659 @@ mod tests {
             branch: Some("master".to_string()),
             depth: Some(1),
             enabled: true,
+            ..Default::default()
         };
 
-        let result = clone_repo(&repo, temp_dir.path(), None);
+        let result = clone_repo(&repo, temp_dir.path(), None, &[], true);
         assert!(result.is_ok());
         
-        let path = result.unwrap();
+        let (path, _) = result.unwrap();
         assert!(path.exists());
         assert!(path.join(".git").exists());
     }
 
+    /// Fake git that creates the clone target, then fails like an interrupted clone
+    fn failing_git(dir: &Path) -> String {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = dir.join("fake-git");
+        std::fs::write(&path, "#!/bin/sh\nfor last; do :; done\nmkdir -p \"$last/.git\"\necho 'fatal: could not read Username' >&2\nexit 1\n").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path.to_string_lossy().to_string()
+    }
+
+    /// Local source repository with one commit on `main`
+    fn source_repo(dir: &Path) -> RepoConfig {
+        source_repo_with(dir, &[("Dockerfile", "FROM nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3.0\n")])
+    }
+
+    /// Local source repository with the given files committed on `main`
+    fn source_repo_with(dir: &Path, files: &[(&str, &str)]) -> RepoConfig {
+        let src = dir.join("source");
+        for (path, content) in files {
+            let path = src.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        run_git(&src, &["init", "-q", "-b", "main"]);
+        run_git(&src, &["add", "."]);
+        run_git(&src, &["commit", "-q", "-m", "init"]);
+        RepoConfig {
+            name: "org/repo".to_string(),
+            url: format!("file://{}", src.display()),
+            ..Default::default()
+        }
+    }
+
+    /// Run git in `dir`, asserting it succeeds
+    fn run_git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .arg("-C").arg(dir)
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {:?}", args);
+    }
+
+    fn workdir_entries(workdir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(workdir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_failed_clone_leaves_no_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let workdir = temp_dir.path().join("work");
+        std::fs::create_dir(&workdir).unwrap();
+        let repo = source_repo(temp_dir.path());
+
+        let err = clone_repo_with(&failing_git(temp_dir.path()), &repo, &workdir, None, &[], true).unwrap_err();
+        assert!(err.to_string().contains("could not read Username"));
+        assert!(workdir_entries(&workdir).is_empty());
+
+        // The next run clones normally
+        let path = clone_repo(&repo, &workdir, None, &[], true).unwrap().0;
+        assert_eq!(path, workdir.join("org_repo"));
+        assert!(path.join("Dockerfile").exists());
+        assert_eq!(workdir_entries(&workdir), vec!["org_repo"]);
+    }
+
+    #[test]
+    fn test_stale_directory_is_recloned() {
+        let temp_dir = TempDir::new().unwrap();
+        let workdir = temp_dir.path().join("work");
+        let repo = source_repo(temp_dir.path());
+
+        // Leftover from an interrupted clone: .git without a resolvable HEAD
+        let stale = workdir.join("org_repo");
+        std::fs::create_dir_all(stale.join(".git")).unwrap();
+        std::fs::write(stale.join("leftover.txt"), "partial").unwrap();
+        assert!(!is_valid_checkout(GIT, &stale));
+
+        let path = clone_repo(&repo, &workdir, None, &[], true).unwrap().0;
+        assert!(is_valid_checkout(GIT, &path));
+        assert!(path.join("Dockerfile").exists());
+        assert!(!path.join("leftover.txt").exists());
+
+        // A valid checkout is reused as-is
+        assert_eq!(clone_repo(&repo, &workdir, None, &[], true).unwrap().0, path);
+    }
+
+    #[test]
+    fn test_reused_checkout_after_force_push() {
+        let temp_dir = TempDir::new().unwrap();
+        let workdir = temp_dir.path().join("work");
+        let repo = source_repo(temp_dir.path());
+        let source = temp_dir.path().join("source");
+
+        let (path, update) = clone_repo(&repo, &workdir, None, &[], true).unwrap();
+        assert_eq!(update, CheckoutUpdate::default());
+        let old_head = head_commit(GIT, &path).unwrap();
+
+        // Rewrite the only commit upstream, as a force-push would
+        std::fs::write(source.join("Dockerfile"), "FROM nvcr.io/nim/meta/llama-3.1-8b-instruct:1.4.0\n").unwrap();
+        run_git(&source, &["commit", "-q", "-a", "--amend", "-m", "rewritten"]);
+        let new_head = head_commit(GIT, &source).unwrap();
+
+        // Without --allow-reset the old commit is kept and marked stale
+        let (_, update) = clone_repo(&repo, &workdir, None, &[], false).unwrap();
+        assert_eq!(update.stale, Some(StaleCheckout::Diverged));
+        assert_eq!(head_commit(GIT, &path).unwrap(), old_head);
+
+        let (_, update) = clone_repo(&repo, &workdir, None, &[], true).unwrap();
+        assert_eq!(update.reset, Some(CheckoutReset { old_head, new_head: new_head.clone() }));
+        assert_eq!(update.stale, None);
+        assert_eq!(head_commit(GIT, &path).unwrap(), new_head);
+        assert!(std::fs::read_to_string(path.join("Dockerfile")).unwrap().contains("1.4.0"));
+
+        // Up to date now: a plain fast-forward (no-op) records nothing
+        assert_eq!(clone_repo(&repo, &workdir, None, &[], true).unwrap().1, CheckoutUpdate::default());
+    }
+
+    #[test]
+    fn test_reused_checkout_branch_deleted() {
+        let temp_dir = TempDir::new().unwrap();
+        let workdir = temp_dir.path().join("work");
+        let repo = source_repo(temp_dir.path());
+
+        let (path, _) = clone_repo(&repo, &workdir, None, &[], true).unwrap();
+        let old_head = head_commit(GIT, &path).unwrap();
+        run_git(&temp_dir.path().join("source"), &["branch", "-q", "-m", "main", "trunk"]);
+
+        let (reused, update) = clone_repo(&repo, &workdir, None, &[], true).unwrap();
+        assert_eq!(reused, path);
+        assert_eq!(update.stale, Some(StaleCheckout::BranchMissing));
+        assert_eq!(update.reset, None);
+        assert_eq!(head_commit(GIT, &path).unwrap(), old_head);
+    }
+
+    #[test]
+    fn test_git_error_classification() {
+        assert!(is_non_fast_forward("hint: Diverging branches can't be fast-forwarded\nfatal: Not possible to fast-forward, aborting."));
+        assert!(is_non_fast_forward("fatal: refusing to merge unrelated histories"));
+        assert!(!is_non_fast_forward("fatal: unable to access 'https://github.com/org/repo.git/': Could not resolve host"));
+        assert!(is_missing_remote_branch("fatal: couldn't find remote ref main"));
+        assert!(!is_missing_remote_branch("fatal: repository 'https://github.com/org/gone.git/' not found"));
+    }
+
+    /// Files in a checkout (outside .git), relative and sorted
+    fn checkout_files(dir: &Path) -> Vec<String> {
+        let mut files: Vec<String> = ignore::WalkBuilder::new(dir)
+            .hidden(false)
+            .filter_entry(|e| e.file_name() != ".git")
+            .build()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_some_and(|t| t.is_file()))
+            .map(|e| e.path().strip_prefix(dir).unwrap().to_string_lossy().to_string())
+            .collect();
+        files.sort();
+        files
+    }
+
+    fn ci_repo(dir: &Path) -> RepoConfig {
+        source_repo_with(dir, &[
+            (".github/workflows/ci.yml", "on: push\n"),
+            ("deploy/helm/values.yaml", "image: nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3.0\n"),
+            ("src/client.py", "model = \"meta/llama-3.1-8b-instruct\"\n"),
+            ("Dockerfile", "FROM nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3.0\n"),
+        ])
+    }
+
+    fn default_scope_paths() -> Vec<String> {
+        DEFAULT_SCOPE_PATHS.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn test_sparse_clone_checks_out_scope_paths_only() {
+        let temp_dir = TempDir::new().unwrap();
+        let workdir = temp_dir.path().join("work");
+        std::fs::create_dir(&workdir).unwrap();
+        let repo = ci_repo(temp_dir.path());
+
+        let path = clone_repo(&repo, &workdir, None, &default_scope_paths(), true).unwrap().0;
+        assert_eq!(checkout_files(&path), vec![".github/workflows/ci.yml", "deploy/helm/values.yaml"]);
+        assert!(is_valid_checkout(GIT, &path));
+    }
+
+    #[test]
+    fn test_sparse_clone_falls_back_to_full_checkout() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let workdir = temp_dir.path().join("work");
+        std::fs::create_dir(&workdir).unwrap();
+        let repo = ci_repo(temp_dir.path());
+
+        // A git without the sparse-checkout command
+        let old_git = temp_dir.path().join("old-git");
+        std::fs::write(&old_git, "#!/bin/sh\n[ \"$3\" = sparse-checkout ] && { echo \"git: 'sparse-checkout' is not a git command\" >&2; exit 1; }\nexec git \"$@\"\n").unwrap();
+        std::fs::set_permissions(&old_git, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let path = clone_repo_with(old_git.to_str().unwrap(), &repo, &workdir, None, &default_scope_paths(), true).unwrap().0;
+        assert_eq!(
+            checkout_files(&path),
+            vec![".github/workflows/ci.yml", "Dockerfile", "deploy/helm/values.yaml", "src/client.py"]
+        );
+    }
+
+    #[test]
+    fn test_use_existing_checkouts() {
+        let temp_dir = TempDir::new().unwrap();
+        let present = temp_dir.path().join("org_present");
+        std::fs::create_dir(&present).unwrap();
+        std::fs::write(
+            present.join("docker-compose.yaml"),
+            "services:\n  llm:\n    image: nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3.0\n",
+        ).unwrap();
+
+        let repos: Vec<RepoConfig> = ["org/present", "org/missing"]
+            .iter()
+            .map(|name| RepoConfig {
+                name: name.to_string(),
+                url: format!("https://github.com/{}.git", name),
+                ..Default::default()
+            })
+            .collect();
+
+        let results = use_existing_checkouts(&repos, temp_dir.path());
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r.source == CheckoutSource::PreProvisioned));
+
+        assert_eq!(results[0].path.as_deref(), Some(present.as_path()));
+        let outcome = crate::scanner::scan_directory(&present, "org/present", &Default::default());
+        assert_eq!(outcome.local.len(), 1);
+        assert_eq!(outcome.local[0].tag, "1.3.0");
+
+        assert!(!results[1].is_success());
+        assert!(results[1].error.as_deref().unwrap().contains("org_missing"));
+        assert_eq!(clone_stats(&results), (1, 1));
+    }
+
+    /// Serve canned bodies by request path (404 otherwise) until the test exits,
+    /// recording each request's head; a body starting with `redirect:` is a 302
+    fn serve_routes(routes: Vec<(&'static str, Vec<u8>)>) -> (String, std::sync::Arc<std::sync::Mutex<Vec<String>>>) {
+        use std::io::Write;
+        use std::net::TcpListener;
+        use std::sync::{Arc, Mutex};
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&requests);
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut buf = [0u8; 4096];
+                let mut request = Vec::new();
+                while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                    let n = stream.read(&mut buf).unwrap();
+                    if n == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buf[..n]);
+                }
+                let request = String::from_utf8_lossy(&request).to_string();
+                let path = request.split_whitespace().nth(1).unwrap_or("").to_string();
+                recorded.lock().unwrap().push(request);
+                let head = match routes.iter().find(|(p, _)| *p == path) {
+                    Some((_, body)) => match body.strip_prefix(b"redirect:") {
+                        Some(location) => format!(
+                            "HTTP/1.1 302 Found\r\nLocation: {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                            String::from_utf8_lossy(location)
+                        ),
+                        None => {
+                            let head = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", body.len());
+                            let _ = stream.write_all(head.as_bytes());
+                            let _ = stream.write_all(body);
+                            continue;
+                        }
+                    },
+                    None => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+                };
+                let _ = stream.write_all(head.as_bytes());
+            }
+        });
+        (format!("http://{}", addr), requests)
+    }
+
+    fn test_client() -> reqwest::blocking::Client {
+        reqwest::blocking::Client::builder()
+            .redirect(reqwest::redirect::Policy::limited(MAX_TARBALL_REDIRECTS))
+            .no_proxy()
+            .build()
+            .unwrap()
+    }
+
+    /// `git archive` tarball of a source repository, as GitHub serves it; returns (tarball, commit)
+    fn archive_tarball(repo: &RepoConfig) -> (Vec<u8>, String) {
+        let src = PathBuf::from(repo.url.strip_prefix("file://").unwrap());
+        let commit = head_commit(GIT, &src).unwrap();
+        let output = Command::new(GIT)
+            .arg("-C").arg(&src)
+            .args(["archive", "--format=tar.gz", "--prefix=repo-main/", "HEAD"])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        (output.stdout, commit)
+    }
+
+    /// Gzipped tarball with raw entry names (bypassing the tar crate's path checks)
+    fn raw_tarball(entries: &[(&str, tar::EntryType, &[u8])]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default()));
+        for (name, entry_type, data) in entries {
+            let mut header = tar::Header::new_old();
+            header.as_old_mut().name[..name.len()].copy_from_slice(name.as_bytes());
+            header.set_entry_type(*entry_type);
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            if *entry_type == tar::EntryType::Symlink {
+                header.set_link_name("/etc/passwd").unwrap();
+            }
+            header.set_cksum();
+            builder.append(&header, *data).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[test]
+    fn test_github_repo_path() {
+        let expected = Some(("org".to_string(), "repo".to_string()));
+        assert_eq!(github_repo_path("https://github.com/org/repo.git"), expected);
+        assert_eq!(github_repo_path("https://github.com/org/repo/"), expected);
+        assert_eq!(github_repo_path("git@github.com:org/repo.git"), expected);
+        assert_eq!(github_repo_path("https://gitlab.com/org/repo.git"), None);
+        assert_eq!(github_repo_path("https://github.com/org"), None);
+    }
+
+    #[test]
+    fn test_lfs_pull_rescans_pointer_files() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let checkout = temp_dir.path().join("checkout");
+        std::fs::create_dir_all(checkout.join("deploy")).unwrap();
+        std::fs::write(
+            checkout.join("deploy/values.yaml"),
+            "version https://git-lfs.github.com/spec/v1\noid sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393\nsize 58\n",
+        ).unwrap();
+        std::fs::write(checkout.join("client.py"), "model = \"meta/llama-3.1-8b-instruct\"\n").unwrap();
+
+        let opts = crate::scanner::ScanOptions::default();
+        let mut outcome = crate::scanner::scan_directory(&checkout, "org/repo", &opts);
+        assert_eq!(outcome.lfs_pointer_paths(), vec!["deploy/values.yaml"]);
+        assert!(outcome.local.is_empty());
+
+        // A git whose `lfs pull` replaces the pointer with the real file
+        let lfs_git = temp_dir.path().join("lfs-git");
+        std::fs::write(&lfs_git, "#!/bin/sh\n[ \"$3 $4 $5\" = \"lfs pull --include\" ] || exit 1\nprintf 'image: nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3.0\\n' > \"$2/$6\"\n").unwrap();
+        std::fs::set_permissions(&lfs_git, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        lfs_pull_with(lfs_git.to_str().unwrap(), &checkout, &outcome.lfs_pointer_paths()).unwrap();
+        outcome.rescan_lfs_pointers(&checkout, "org/repo", &opts);
+        assert!(outcome.lfs_pointer_paths().is_empty());
+        assert_eq!(outcome.files.len(), 2);
+        assert_eq!(outcome.local.len(), 1);
+        assert_eq!(outcome.local[0].file_path, "deploy/values.yaml");
+        assert_eq!(outcome.hosted.len(), 1);
+
+        // A failing pull is reported, not ignored
+        let broken_git = temp_dir.path().join("broken-git");
+        std::fs::write(&broken_git, "#!/bin/sh\nexit 1\n").unwrap();
+        std::fs::set_permissions(&broken_git, std::fs::Permissions::from_mode(0o755)).unwrap();
+        assert!(lfs_pull_with(broken_git.to_str().unwrap(), &checkout, &["a.yaml".to_string()]).is_err());
+    }
+
+    /// Source repository where alice wrote two image lines and bob later bumped the second
+    fn two_author_repo(dir: &Path, depth: u32) -> RepoConfig {
+        let src = dir.join("source");
+        std::fs::create_dir_all(&src).unwrap();
+        run_git(&src, &["init", "-q", "-b", "main"]);
+        let commit = |email: &str, date: &str, tag: &str| {
+            let content = format!(
+                "FROM nvcr.io/nim/meta/llama-3.1-8b-instruct:1.0.0\nFROM nvcr.io/nim/nvidia/nv-embedqa-e5-v5:{}\n",
+                tag,
+            );
+            std::fs::write(src.join("Dockerfile"), content).unwrap();
+            run_git(&src, &["add", "."]);
+            run_git(&src, &["-c", &format!("user.email={}", email), "commit", "-q", "--date", date, "-m", tag]);
+        };
+        commit("alice@example.com", "2023-03-04T05:06:07-0130", "1.0.0");
+        commit("bob@example.com", "2024-01-02T03:04:05+0200", "1.1.0");
+        RepoConfig {
+            name: "org/repo".to_string(),
+            url: format!("file://{}", src.display()),
+            depth: Some(depth),
+            ..Default::default()
+        }
+    }
+
+    /// Clone `repo`, scan it and blame its findings, in line order
+    fn blamed_lines(workdir: &Path, repo: &RepoConfig) -> Vec<LineBlame> {
+        let clones = clone_all_repos(std::slice::from_ref(repo), workdir, None, &[], AcquireMode::Git, None, true);
+        let outcome = crate::scanner::scan_directory(clones[0].path.as_ref().unwrap(), &repo.name, &Default::default());
+        let mut findings = NimFindings { local_nim: outcome.local, hosted_nim: outcome.hosted, sdk_dependencies: vec![] };
+        assert_eq!(blame_findings(&clones, &mut [&mut findings]), 1);
+        findings.local_nim.sort_by_key(|m| m.line_number);
+        findings.local_nim.into_iter().map(|m| m.blame.unwrap()).collect()
+    }
+
+    #[test]
+    fn test_blame_findings() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = two_author_repo(temp_dir.path(), 10);
+        let blame = blamed_lines(&temp_dir.path().join("full"), &repo);
+        assert_eq!(blame.len(), 2);
+        assert_eq!(blame[0].author_email.as_deref(), Some("alice@example.com"));
+        assert_eq!(blame[0].author_date.as_deref(), Some("2023-03-04T05:06:07-01:30"));
+        assert_eq!(blame[1].author_email.as_deref(), Some("bob@example.com"));
+        assert_eq!(blame[1].author_date.as_deref(), Some("2024-01-02T03:04:05+02:00"));
+        assert_ne!(blame[0].commit_sha, blame[1].commit_sha);
+        assert_eq!(blame[0].commit_sha.as_ref().unwrap().len(), 40);
+        assert!(blame.iter().all(|b| b.note.is_none()));
+
+        // One commit deep, every line looks like bob's: the shallow boundary hides alice's commit
+        let blame = blamed_lines(&temp_dir.path().join("depth1"), &RepoConfig { depth: Some(1), ..repo.clone() });
+        assert!(blame.iter().all(|b| b.author_email.is_none() && b.commit_sha.is_none() && b.author_date.is_none()));
+        assert_eq!(blame[0].note.as_deref(), Some(SHALLOW_BLAME_NOTE));
+    }
+
+    #[test]
+    fn test_parse_blame_porcelain() {
+        let sha = "a".repeat(40);
+        let output = format!(
+            "{sha} 3 1 1\nauthor A\nauthor-mail <a@example.com>\nauthor-time 0\nauthor-tz -0130\nfilename f\n\tline one\n\
+             {sha} 7 5\n\tline five\n"
+        );
+        let lines = parse_blame_porcelain(&output);
+        assert_eq!(lines.iter().map(|(line, sha, _)| (*line, sha.len())).collect::<Vec<_>>(), vec![(1, 40), (5, 40)]);
+        assert_eq!(lines[1].2.author_email.as_deref(), Some("a@example.com"));
+        assert_eq!(lines[1].2.author_date.as_deref(), Some("1969-12-31T22:30:00-01:30"));
+        assert_eq!(blame_date(0, "bogus"), None);
+    }
+
+    #[test]
+    fn test_list_org_repos() {
+        // A full first page makes the listing ask for the next one
+        let first_page: Vec<serde_json::Value> = (0..ORG_REPOS_PER_PAGE)
+            .map(|i| serde_json::json!({
+                "full_name": format!("acme/repo-{:03}", i),
+                "clone_url": format!("https://github.com/acme/repo-{:03}.git", i),
+                "default_branch": "main",
+                "archived": false,
+            }))
+            .collect();
+        let second_page = br#"[
+            {"full_name": "acme/legacy", "clone_url": "https://github.com/acme/legacy.git", "default_branch": "master", "archived": true},
+            {"full_name": "acme/broken"}
+        ]"#;
+        let (base, requests) = serve_routes(vec![
+            ("/orgs/acme/repos?per_page=100&page=1", serde_json::to_vec(&first_page).unwrap()),
+            ("/orgs/acme/repos?per_page=100&page=2", second_page.to_vec()),
+        ]);
+
+        let repos = list_org_repos(&test_client(), &base, "acme", "secret").unwrap();
+        assert_eq!(repos.len(), ORG_REPOS_PER_PAGE + 1);
+        assert_eq!(requests.lock().unwrap().len(), 2);
+        let legacy = &repos[0];
+        assert_eq!(legacy.name, "acme/legacy");
+        assert_eq!(legacy.branch.as_deref(), Some("master"));
+        assert!(!legacy.enabled);
+        assert!(repos[1..].iter().all(|r| r.enabled && r.branch.as_deref() == Some("main")));
+
+        assert!(list_org_repos(&test_client(), &base, "missing", "secret").is_err());
+    }
+
+    #[test]
+    fn test_github_repo_metadata() {
+        let (base, requests) = serve_routes(vec![
+            ("/repos/org/rag", br#"{"description": "Blueprint using the meta/llama-3.1-70b-instruct NIM ", "topics": ["rag", "nim"], "homepage": ""}"#.to_vec()),
+            ("/repos/org/bare", br#"{"description": null, "topics": [], "homepage": null}"#.to_vec()),
+        ]);
+        let repo = |name: &str| RepoConfig {
+            name: format!("org/{}", name),
+            url: format!("https://github.com/org/{}.git", name),
+            ..Default::default()
+        };
+        let client = test_client();
+        assert_eq!(github_repo_metadata(&client, &base, &repo("rag"), Some("secret")).unwrap(), vec![
+            (MetadataField::Description, "Blueprint using the meta/llama-3.1-70b-instruct NIM".to_string()),
+            (MetadataField::Topic, "rag".to_string()),
+            (MetadataField::Topic, "nim".to_string()),
+        ]);
+        assert!(requests.lock().unwrap()[0].to_lowercase().contains("authorization: token secret"));
+        assert_eq!(github_repo_metadata(&client, &base, &repo("bare"), None).unwrap(), vec![]);
+        assert!(!requests.lock().unwrap()[1].to_lowercase().contains("authorization"));
+        assert_eq!(github_repo_metadata(&client, &base, &repo("missing"), None), None);
+    }
+
+    #[test]
+    fn test_github_repo_size_skip_decision() {
+        let (base, requests) = serve_routes(vec![
+            ("/repos/org/huge", br#"{"name": "huge", "size": 9000000}"#.to_vec()),
+            ("/repos/org/small", br#"{"name": "small", "size": 2048}"#.to_vec()),
+        ]);
+        let repo = |name: &str| RepoConfig {
+            name: format!("org/{}", name),
+            url: format!("https://github.com/org/{}.git", name),
+            ..Default::default()
+        };
+        let client = test_client();
+
+        let huge = repo("huge");
+        let size = github_repo_size(&client, &base, &huge, "secret").unwrap();
+        assert_eq!(size, 9_000_000 * 1024);
+        assert_eq!(oversize_skip(&huge, size, 4096, SizeSource::GithubApi), Some(OversizeSkip {
+            repository: "org/huge".to_string(),
+            size_mb: 8790,
+            limit_mb: 4096,
+            measured: SizeSource::GithubApi,
+        }));
+        assert!(requests.lock().unwrap()[0].to_lowercase().contains("authorization: token secret"));
+
+        let small = repo("small");
+        let size = github_repo_size(&client, &base, &small, "secret").unwrap();
+        assert_eq!(oversize_skip(&small, size, 2, SizeSource::GithubApi), None);
+        assert!(oversize_skip(&small, size, 1, SizeSource::GithubApi).is_some());
+
+        // Unknown size: the caller measures the checkout instead
+        assert_eq!(github_repo_size(&client, &base, &repo("missing"), "secret"), None);
+        let gitlab = RepoConfig { url: "https://gitlab.com/org/huge.git".to_string(), ..huge };
+        assert_eq!(github_repo_size(&client, &base, &gitlab, "secret"), None);
+        assert_eq!(requests.lock().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_clone_all_repos_skips_oversized_checkout() {
+        let temp_dir = TempDir::new().unwrap();
+        let big_file = "0123456789abcdef".repeat(128 * 1024);
+        let repo = source_repo_with(temp_dir.path(), &[("data/blob.txt", &big_file), ("Dockerfile", "FROM scratch\n")]);
+        let workdir = temp_dir.path().join("work");
+
+        // 2 MiB of files against a 1 MB per-repo limit: cloned, measured, removed
+        let limited = RepoConfig { max_repo_size_mb: Some(1), ..repo.clone() };
+        let results = clone_all_repos(&[limited], &workdir, None, &[], AcquireMode::Git, None, true);
+        assert!(!results[0].is_success());
+        assert!(results[0].error.is_none());
+        let skip = results[0].skipped_oversize.as_ref().unwrap();
+        assert_eq!((skip.limit_mb, skip.measured), (1, SizeSource::Checkout));
+        assert!(skip.size_mb >= 2);
+        assert_eq!(clone_stats(&results), (0, 0));
+        assert!(workdir_entries(&workdir).is_empty());
+
+        // The per-repo setting overrides the global limit; 0 means unlimited
+        let unlimited = RepoConfig { max_repo_size_mb: Some(0), ..repo };
+        let results = clone_all_repos(&[unlimited], &workdir, None, &[], AcquireMode::Git, Some(1), true);
+        assert!(results[0].is_success());
+        assert!(results[0].skipped_oversize.is_none());
+
+        // Recorded for `clean`; the skipped attempt above left nothing to record
+        let path = results[0].path.as_ref().unwrap();
+        assert_eq!(paths::workdir_removals(&workdir).unwrap(), vec![path.clone(), workdir.join(paths::WORKDIR_MARKER)]);
+
+        // The scan-work estimate leaves git's own objects out
+        assert!(checkout_size(path) >= big_file.len() as u64);
+        assert!(checkout_size(path) < dir_size(path));
+    }
+
+    #[test]
+    fn test_download_tarball() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = ci_repo(temp_dir.path());
+        let (tarball, commit) = archive_tarball(&source);
+        let (base, requests) = serve_routes(vec![
+            ("/org/repo/tar.gz/refs/heads/main", b"redirect:/archive/org-repo-main.tar.gz".to_vec()),
+            ("/archive/org-repo-main.tar.gz", tarball),
+        ]);
+        let workdir = temp_dir.path().join("work");
+        std::fs::create_dir(&workdir).unwrap();
+        let repo = RepoConfig {
+            name: "org/repo".to_string(),
+            url: "https://github.com/org/repo.git".to_string(),
+            ..Default::default()
+        };
+
+        let (path, commit_sha) = download_tarball_with(&test_client(), &base, &repo, &workdir, Some("secret"), MAX_TARBALL_BYTES).unwrap();
+        assert_eq!(path, workdir.join("org_repo"));
+        assert_eq!(commit_sha.as_deref(), Some(commit.as_str()));
+        assert_eq!(
+            checkout_files(&path),
+            vec![".github/workflows/ci.yml", "Dockerfile", "deploy/helm/values.yaml", "src/client.py"]
+        );
+        assert_eq!(workdir_entries(&workdir), vec!["org_repo"]);
+        {
+            let requests = requests.lock().unwrap();
+            assert_eq!(requests.len(), 2);
+            assert!(requests[0].to_lowercase().contains("authorization: token secret"));
+        }
+
+        // Over the size limit: nothing is left behind
+        let err = download_tarball_with(&test_client(), &base, &repo, &workdir, None, 64).unwrap_err();
+        assert!(format!("{:#}", err).contains("limit"), "{:#}", err);
+        assert_eq!(workdir_entries(&workdir), vec!["org_repo"]);
+
+        // Missing branch
+        let branch = RepoConfig { branch: Some("gone".to_string()), ..repo };
+        let err = download_tarball_with(&test_client(), &base, &branch, &workdir, None, MAX_TARBALL_BYTES).unwrap_err();
+        assert!(err.to_string().contains("404"), "{}", err);
+    }
+
+    #[test]
+    fn test_unpack_tarball_is_path_safe() {
+        let temp_dir = TempDir::new().unwrap();
+        let sha = "0123456789abcdef0123456789abcdef01234567";
+        let top = format!("repo-{}/", sha);
+
+        // Links are skipped; the commit comes from the top-level directory name
+        let tarball = raw_tarball(&[
+            (&top, tar::EntryType::Directory, b""),
+            (&format!("{}Dockerfile", top), tar::EntryType::Regular, b"FROM nvcr.io/nim/meta/llama:1.0\n"),
+            (&format!("{}passwd", top), tar::EntryType::Symlink, b""),
+        ]);
+        let dest = temp_dir.path().join("ok");
+        std::fs::create_dir(&dest).unwrap();
+        assert_eq!(unpack_tarball(tarball.as_slice(), &dest, MAX_UNPACKED_BYTES).unwrap().as_deref(), Some(sha));
+        assert_eq!(checkout_files(&dest), vec!["Dockerfile"]);
+
+        for name in ["repo/../../evil", "/etc/evil", "../evil"] {
+            let tarball = raw_tarball(&[(name, tar::EntryType::Regular, b"x")]);
+            let dest = temp_dir.path().join("bad");
+            std::fs::create_dir_all(&dest).unwrap();
+            assert!(unpack_tarball(tarball.as_slice(), &dest, MAX_UNPACKED_BYTES).is_err(), "{}", name);
+            assert!(!temp_dir.path().join("evil").exists());
+        }
+
+        // Unpacked size limit
+        let tarball = raw_tarball(&[("repo/big", tar::EntryType::Regular, &[0u8; 1024])]);
+        assert!(unpack_tarball(tarball.as_slice(), &dest, 100).is_err());
+    }
+
+
     #[test]
     fn test_inject_github_token() {
         let url = "https://github.com/org/repo.git";
@@ -0,0 +1,355 @@
+//! Container images of Kubernetes manifests
+//!
+//! A manifest file often holds several resources separated by `---`, and the
+//! line-by-line pass sees each `image:` line without knowing which Deployment
+//! or Job it belongs to. Here every document of the file is parsed and each
+//! container of a pod spec (`containers` and `initContainers`, wherever the
+//! workload nests its pod template) is reported with its resource, its
+//! container name and the pod's `imagePullSecrets`.
+//!
+//! Resources of the NIM Operator (`NIMService`, `NIMCache`, `NIMPipeline`)
+//! have no pod spec: they name the NIM image in `spec.image` (`repository`
+//! and `tag` keys) or, for a cache, in `spec.source.ngc.modelPuller`, and
+//! may name the model they serve. Those images are reported the same way,
+//! with the model names alongside.
+
+use serde::Deserialize;
+use serde_yaml::Value;
+
+/// Kinds of the NIM Operator's custom resources
+const NIM_OPERATOR_KINDS: [&str; 3] = ["NIMService", "NIMCache", "NIMPipeline"];
+
+/// A container image found in a Kubernetes manifest
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestImage {
+    /// Kind and name of the resource holding the pod spec (`Deployment/nim-embedder`)
+    pub resource: String,
+    /// `containers` or `initContainers`; the spec field for NIM Operator resources (`spec.image`)
+    pub field: &'static str,
+    /// Container name (empty when the container has none); the service of a `NIMPipeline`
+    pub container: String,
+    pub image: String,
+    /// Secret names in the pod's `imagePullSecrets` (the image's pull secrets for NIM Operator resources)
+    pub pull_secrets: Vec<String>,
+    /// Model names a NIM Operator resource references
+    pub models: Vec<String>,
+    /// 1-indexed line holding the image, best effort
+    pub line: usize,
+}
+
+impl ManifestImage {
+    /// `Deployment/nim-embedder containers[embedder]: <image>`, with the pull secrets and models after it
+    ///
+    /// A NIM Operator field without a service reads `NIMService/llama spec.image: <image>`.
+    pub fn context(&self) -> String {
+        let mut context = match self.is_operator() && self.container.is_empty() {
+            true => format!("{} {}: {}", self.resource, self.field, self.image),
+            false => format!("{} {}[{}]: {}", self.resource, self.field, self.container, self.image),
+        };
+        if !self.pull_secrets.is_empty() {
+            context.push_str(&format!(" (imagePullSecrets: {})", self.pull_secrets.join(", ")));
+        }
+        if !self.models.is_empty() {
+            context.push_str(&format!(" (model: {})", self.models.join(", ")));
+        }
+        context
+    }
+
+    /// Whether the image comes from a NIM Operator resource rather than a pod spec
+    pub fn is_operator(&self) -> bool {
+        self.resource.split_once('/').is_some_and(|(kind, _)| NIM_OPERATOR_KINDS.contains(&kind))
+    }
+}
+
+/// Text of a scalar (`tag: 1.0` reads as a number)
+fn scalar(value: Option<&Value>) -> Option<String> {
+    match value? {
+        Value::String(s) => Some(s.trim().to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Strings of a scalar or a sequence of scalars (`pullSecrets: [ngc-secret]`, `pullSecret: ngc-secret`)
+fn strings(value: Option<&Value>) -> Vec<String> {
+    let all: Vec<String> = match value {
+        Some(Value::Sequence(items)) => items.iter().filter_map(|item| scalar(Some(item))).collect(),
+        other => scalar(other).into_iter().collect(),
+    };
+    all.into_iter().filter(|s| !s.is_empty()).collect()
+}
+
+/// Values of `model` and `modelName` keys that are text, anywhere under `value`
+fn referenced_models(value: &Value, models: &mut Vec<String>) {
+    match value {
+        Value::Mapping(entries) => {
+            for (key, child) in entries {
+                if matches!(key.as_str(), Some("model" | "modelName")) {
+                    if let Some(model) = scalar(Some(child)).filter(|m| !m.is_empty() && !models.contains(m)) {
+                        models.push(model);
+                    }
+                }
+                referenced_models(child, models);
+            }
+        }
+        Value::Sequence(items) => items.iter().for_each(|child| referenced_models(child, models)),
+        _ => {}
+    }
+}
+
+/// `kind/name` of a resource, if the mapping is one
+fn resource_of(value: &Value) -> Option<String> {
+    let kind = value.get("kind")?.as_str()?;
+    let name = value.get("metadata").and_then(|m| m.get("name")).and_then(Value::as_str).unwrap_or("");
+    Some(format!("{}/{}", kind, name))
+}
+
+/// Walks the documents of a manifest for pod specs, locating each image in the text
+struct ManifestWalk<'a> {
+    lines: Vec<&'a str>,
+    /// Line after the last image found: images are found in file order
+    cursor: usize,
+    found: Vec<ManifestImage>,
+}
+
+impl ManifestWalk<'_> {
+    fn walk(&mut self, resource: &str, value: &Value) {
+        match value {
+            Value::Mapping(entries) => {
+                let resource = resource_of(value).unwrap_or_else(|| resource.to_string());
+                let kind = value.get("kind").and_then(Value::as_str);
+                if kind.is_some_and(|kind| NIM_OPERATOR_KINDS.contains(&kind)) {
+                    self.operator_resource(&resource, value);
+                    return;
+                }
+                let pull_secrets: Vec<String> = value
+                    .get("imagePullSecrets")
+                    .and_then(Value::as_sequence)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|secret| secret.get("name")?.as_str().map(str::to_string))
+                    .collect();
+                for field in ["initContainers", "containers"] {
+                    for container in value.get(field).and_then(Value::as_sequence).into_iter().flatten() {
+                        if let Some(image) = container.get("image").and_then(Value::as_str) {
+                            let name = container.get("name").and_then(Value::as_str).unwrap_or("");
+                            self.push(("image", image), ManifestImage {
+                                resource: resource.clone(),
+                                field,
+                                container: name.to_string(),
+                                image: image.to_string(),
+                                pull_secrets: pull_secrets.clone(),
+                                models: Vec::new(),
+                                line: 0,
+                            });
+                        }
+                    }
+                }
+                for (key, child) in entries {
+                    if !matches!(key.as_str(), Some("containers" | "initContainers")) {
+                        self.walk(&resource, child);
+                    }
+                }
+            }
+            Value::Sequence(items) => {
+                for child in items {
+                    self.walk(resource, child);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Images of a NIM Operator resource; a `NIMPipeline` holds a `NIMService` spec per service
+    fn operator_resource(&mut self, resource: &str, value: &Value) {
+        let Some(spec) = value.get("spec") else {
+            return;
+        };
+        if value.get("kind").and_then(Value::as_str) != Some("NIMPipeline") {
+            self.nim_spec(resource, "spec.image", "", spec);
+            return;
+        }
+        for service in spec.get("services").and_then(Value::as_sequence).into_iter().flatten() {
+            let name = service.get("name").and_then(Value::as_str).unwrap_or("");
+            if let Some(spec) = service.get("spec") {
+                self.nim_spec(resource, "spec.services", name, spec);
+            }
+        }
+    }
+
+    /// `spec.image` (repository and tag) and a cache's `spec.source.ngc.modelPuller`
+    fn nim_spec(&mut self, resource: &str, field: &'static str, service: &str, spec: &Value) {
+        let mut models = Vec::new();
+        referenced_models(spec, &mut models);
+        let image = spec.get("image");
+        if let Some(repository) = scalar(image.and_then(|i| i.get("repository"))).filter(|r| !r.is_empty()) {
+            let full = match scalar(image.and_then(|i| i.get("tag"))).filter(|t| !t.is_empty()) {
+                Some(tag) => format!("{}:{}", repository, tag),
+                None => repository.clone(),
+            };
+            self.push(("repository", &repository), ManifestImage {
+                resource: resource.to_string(),
+                field,
+                container: service.to_string(),
+                image: full,
+                pull_secrets: strings(image.and_then(|i| i.get("pullSecrets"))),
+                models: models.clone(),
+                line: 0,
+            });
+        }
+        let ngc = spec.get("source").and_then(|s| s.get("ngc"));
+        if let Some(puller) = scalar(ngc.and_then(|n| n.get("modelPuller"))).filter(|p| !p.is_empty()) {
+            self.push(("modelPuller", &puller), ManifestImage {
+                resource: resource.to_string(),
+                field: "spec.source.ngc.modelPuller",
+                container: service.to_string(),
+                image: puller.clone(),
+                pull_secrets: strings(ngc.and_then(|n| n.get("pullSecret"))),
+                models,
+                line: 0,
+            });
+        }
+    }
+
+    /// Record an image on the next line holding `key` and `text` (else the first, else line 1)
+    fn push(&mut self, (key, text): (&str, &str), mut found: ManifestImage) {
+        let holds = |line: &&str| line.contains(key) && line.contains(text);
+        let start = self.cursor.min(self.lines.len());
+        let index = self.lines[start..].iter().position(holds).map(|i| i + start)
+            .or_else(|| self.lines.iter().position(holds));
+        if let Some(index) = index {
+            self.cursor = index + 1;
+        }
+        found.line = index.map_or(1, |i| i + 1);
+        self.found.push(found);
+    }
+}
+
+/// Container images of every document of a manifest file, in file order
+///
+/// Documents after one that doesn't parse are not read; None when the first doesn't.
+pub fn manifest_images(content: &str) -> Option<Vec<ManifestImage>> {
+    let mut walk = ManifestWalk { lines: content.lines().collect(), cursor: 0, found: Vec::new() };
+    let mut parsed = false;
+    for document in serde_yaml::Deserializer::from_str(content) {
+        let Ok(doc) = Value::deserialize(document) else {
+            break;
+        };
+        parsed = true;
+        walk.walk("", &doc);
+    }
+    parsed.then_some(walk.found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest_images() {
+        let content = "\
+apiVersion: apps/v1
+kind: Deployment
+metadata:
+  name: nim-embedder
+spec:
+  template:
+    spec:
+      imagePullSecrets:
+        - name: ngc-secret
+      initContainers:
+        - name: download
+          image: nvcr.io/nim/nvidia/nv-embedqa-e5-v5:1.0.0
+      containers:
+        - name: embedder
+          image: nvcr.io/nim/nvidia/nv-embedqa-e5-v5:1.0.0
+        - image: busybox
+---
+apiVersion: batch/v1
+kind: CronJob
+metadata:
+  name: warmup
+spec:
+  jobTemplate:
+    spec:
+      template:
+        spec:
+          containers:
+            - name: warm
+              image: \"nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3.3\"
+---
+apiVersion: v1
+kind: List
+items:
+  - apiVersion: v1
+    kind: Pod
+    metadata:
+      name: probe
+    spec:
+      containers: [{name: probe, image: nvcr.io/nim/meta/llama-3.1-8b-instruct}]
+";
+        let images = manifest_images(content).unwrap();
+        let summary: Vec<(&str, &str, &str, usize)> = images
+            .iter()
+            .map(|i| (i.resource.as_str(), i.field, i.container.as_str(), i.line))
+            .collect();
+        assert_eq!(summary, vec![
+            ("Deployment/nim-embedder", "initContainers", "download", 12),
+            ("Deployment/nim-embedder", "containers", "embedder", 15),
+            ("Deployment/nim-embedder", "containers", "", 16),
+            ("CronJob/warmup", "containers", "warm", 29),
+            ("Pod/probe", "containers", "probe", 39),
+        ]);
+        assert_eq!(
+            images[1].context(),
+            "Deployment/nim-embedder containers[embedder]: nvcr.io/nim/nvidia/nv-embedqa-e5-v5:1.0.0 (imagePullSecrets: ngc-secret)"
+        );
+        assert!(images[3].pull_secrets.is_empty());
+
+        assert_eq!(manifest_images("kind: Pod\nspec: [\n"), None);
+    }
+
+    #[test]
+    fn test_manifest_images_nim_operator() {
+        let content = "\
+apiVersion: apps.nvidia.com/v1alpha1
+kind: NIMCache
+metadata:
+  name: llama
+spec:
+  source:
+    ngc:
+      modelPuller: nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3.3
+      pullSecret: ngc-secret
+---
+apiVersion: apps.nvidia.com/v1alpha1
+kind: NIMService
+metadata:
+  name: llama
+spec:
+  image:
+    repository: nvcr.io/nim/meta/llama-3.1-8b-instruct
+    tag: 1.3
+  model: meta/llama-3.1-8b-instruct
+  containers:
+    - image: ignored
+";
+        let images = manifest_images(content).unwrap();
+        let summary: Vec<(&str, &str, &str, usize)> = images
+            .iter()
+            .map(|i| (i.resource.as_str(), i.field, i.image.as_str(), i.line))
+            .collect();
+        // A numeric tag reads as written; nothing under an operator resource is a pod spec
+        assert_eq!(summary, vec![
+            ("NIMCache/llama", "spec.source.ngc.modelPuller", "nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3.3", 8),
+            ("NIMService/llama", "spec.image", "nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3", 17),
+        ]);
+        assert!(images.iter().all(ManifestImage::is_operator));
+        assert_eq!(images[0].pull_secrets, vec!["ngc-secret"]);
+        assert_eq!(images[1].models, vec!["meta/llama-3.1-8b-instruct"]);
+        assert_eq!(
+            images[1].context(),
+            "NIMService/llama spec.image: nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3 (model: meta/llama-3.1-8b-instruct)"
+        );
+    }
+}
@@ -3,17 +3,38 @@
 //! A static code analyzer that scans repositories to discover and catalog
 //! NVIDIA NIM usage (Local NIM containers and Hosted NIM endpoints).
 
+mod analysis;
+mod attest;
+mod audit;
+mod cleanup;
+mod compose;
 mod config;
+mod dependencies;
+mod enrich_report;
+mod fingerprint;
 mod git_ops;
+mod kubernetes;
+mod merge;
 mod models;
 mod ngc_api;
+mod notebook;
+mod paths;
+mod policy;
 mod report;
+mod sampling;
 mod scanner;
+mod status;
+mod telemetry;
+mod triage;
+mod variables;
 
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 use anyhow::{bail, Context, Result};
 use clap::{Parser, Subcommand};
 use log::{info, warn, error, LevelFilter};
+use rayon::iter::{ParallelBridge, ParallelIterator};
 use std::process::Command;
 use tempfile::TempDir;
 
@@ -33,10 +54,31 @@ struct Cli {
 #[derive(Subcommand, Debug)]
 enum Commands {
     /// Scan repositories for NIM usage
-    Scan(ScanArgs),
+    Scan(Box<ScanArgs>),
     
     /// Query Hosted NIM information by model name
     Query(QueryArgs),
+
+    /// Combine report.json files from separate scans (e.g. different repos.yaml) into one report
+    MergeReports(MergeReportsArgs),
+
+    /// Enrich the findings of an existing report.json with NGC API lookups, without re-scanning
+    EnrichReport(EnrichReportArgs),
+
+    /// Write a commented starter repos.yaml
+    Init(InitArgs),
+
+    /// Work with a repos.yaml configuration file
+    Config(ConfigArgs),
+
+    /// Summarize a finished scan's report.json
+    Report(ReportArgs),
+
+    /// Remove the checkouts a scan left in a --keep-repos workdir (dry run unless --yes)
+    Clean(CleanArgs),
+
+    /// Check a scan's output directory against its MANIFEST.sha256 (and signature)
+    VerifyOutput(VerifyOutputArgs),
 }
 
 /// Arguments for the scan subcommand
@@ -66,6 +108,22 @@ struct ScanArgs {
     #[arg(long, default_value = "false")]
     keep_repos: bool,
 
+    /// When a kept checkout's branch was force-pushed, reset it to the remote branch (`--allow-reset false` scans the old commit and records it as stale)
+    #[arg(long, default_value_t = true, action = clap::ArgAction::Set)]
+    allow_reset: bool,
+
+    /// How to fetch repositories (a repo's `acquire` in repos.yaml overrides this): git clone, or tarball download over HTTPS for GitHub URLs (when git/ssh is blocked)
+    #[arg(long, value_enum, default_value_t = models::AcquireMode::Git)]
+    acquire: models::AcquireMode,
+
+    /// Skip repositories larger than this many MB instead of scanning them (a repo's `max_repo_size_mb` in repos.yaml overrides this; 0 = unlimited). Sized via the GitHub API before cloning when a token is set, otherwise by measuring the checkout
+    #[arg(long)]
+    max_repo_size_mb: Option<u64>,
+
+    /// Skip all git operations and scan checkouts already present in --workdir (<org>_<repo>); they are never cleaned up
+    #[arg(long, default_value = "false", requires = "workdir")]
+    no_clone: bool,
+
     /// Increase logging verbosity (-v, -vv, -vvv)
     #[arg(short, long, action = clap::ArgAction::Count)]
     verbose: u8,
@@ -77,6 +135,536 @@ struct ScanArgs {
     /// Regenerate repos.yaml from Build Page before scanning
     #[arg(long, default_value = "false")]
     refresh_repos: bool,
+
+    /// NGC enrichment stages to run when an API key is set (comma-separated): local, hosted, image-meta, tags [default: local,hosted]
+    #[arg(long, value_enum, value_delimiter = ',')]
+    enrich: Vec<models::EnrichStage>,
+
+    /// Fetch display name and publisher for each Local NIM image (one registry call per unique image); adds image-meta to --enrich
+    #[arg(long, default_value = "false")]
+    enrich_image_meta: bool,
+
+    /// Fetch publisher, modalities and context length of each Hosted NIM model from the public model catalog (one call per unique model; no API key needed)
+    #[arg(long, default_value = "false")]
+    enrich_model_cards: bool,
+
+    /// Check that every pinned Local NIM tag exists in NGC (one tag-list fetch per unique image); adds tags to --enrich
+    #[arg(long, default_value = "false")]
+    verify_tags: bool,
+
+    /// Stop NGC enrichment after this many seconds; findings not yet looked up are marked budget_exhausted (0 = unlimited)
+    #[arg(long, default_value_t = 0)]
+    enrichment_budget_secs: u64,
+
+    /// Stop NGC enrichment after this many API requests, retries included (0 = unlimited)
+    #[arg(long, default_value_t = 0)]
+    enrichment_max_requests: usize,
+
+    /// Record literal temperature/max_tokens/stream/top_p next to Hosted NIM calls (grows the report)
+    #[arg(long, default_value = "false")]
+    capture_call_params: bool,
+
+    /// Exit with an error after writing reports if any finding has at least this policy severity [default: error]
+    #[arg(long, value_enum, conflicts_with = "no_fail_on_severity")]
+    fail_on_severity: Option<models::Severity>,
+
+    /// Don't fail the run on policy severities, even with scan.fail_on.severity in the configuration
+    #[arg(long, default_value = "false")]
+    no_fail_on_severity: bool,
+
+    /// Exit with an error after writing reports if source code references a staging NVIDIA API endpoint or stg/ model
+    #[arg(long, default_value = "false")]
+    fail_on_staging_in_source: bool,
+
+    /// YAML file with allowed/denied Hosted NIM model patterns (globs, or /regex/); violations are flagged per finding
+    #[arg(long)]
+    model_policy: Option<PathBuf>,
+
+    /// Exit with an error after writing reports if any Hosted NIM finding violates --model-policy
+    #[arg(long, default_value = "false", requires = "model_policy")]
+    fail_on_policy_violations: bool,
+
+    /// Exit with an error after writing reports if nothing was found at all (often a broken clone or filter)
+    #[arg(long, default_value = "false", conflicts_with = "empty_ok")]
+    fail_on_empty: bool,
+
+    /// Treat a scan that finds nothing as success, even with scan.fail_on.empty in the configuration
+    #[arg(long, default_value = "false")]
+    empty_ok: bool,
+
+    /// Run the full line-by-line pass on every file instead of skipping files without anchor literals
+    #[arg(long, default_value = "false")]
+    no_prefilter: bool,
+
+    /// Scan report.json / report.csv files written by this scanner (skipped by default so committed reports aren't counted as usage)
+    #[arg(long, default_value = "false")]
+    scan_own_reports: bool,
+
+    /// Run `git lfs pull` for files checked out as Git LFS pointers and scan their real content
+    #[arg(long, default_value = "false")]
+    fetch_lfs: bool,
+
+    /// Scan lockfiles (yarn.lock, go.sum, ...) and generated code (`*_pb2.py`, files marked `@generated` or `DO NOT EDIT`), which are skipped by default
+    #[arg(long, default_value = "false")]
+    include_generated: bool,
+
+    /// Count findings in test fixtures (`scan.test_fixture_globs`) and in this scanner's own repository in the summary; they are marked `fixture` and left out by default
+    #[arg(long, default_value = "false")]
+    include_fixtures: bool,
+
+    /// Leave out documentation (Markdown, reStructuredText, HTML and text files, and notebooks' Markdown cells) to report executable code only
+    #[arg(long, default_value = "false")]
+    skip_docs: bool,
+
+    /// Also treat https:// URLs on this host as Hosted NIM endpoints (repeatable): a host name such as api.nvidia.eu, `*.` for every subdomain, and `=staging` for a staging host; added to `scan.hosted_hosts`
+    #[arg(long = "extra-hosted-host", value_name = "HOST[=ENV]")]
+    extra_hosted_hosts: Vec<String>,
+
+    /// Look for NIM mentions in each GitHub repository's description, topics and homepage, reported as low-confidence metadata_hints
+    #[arg(long, default_value = "false")]
+    github_metadata: bool,
+
+    /// Record the author, commit and date of each finding's line with git blame (git clones only; shallow clones need a larger `depth`)
+    #[arg(long, default_value = "false")]
+    blame: bool,
+
+    /// Deepest directory level scanned in a repository (root is 0; 0 = unlimited); deeper directories are skipped and recorded in the report [default: 32]
+    #[arg(long)]
+    max_depth: Option<usize>,
+
+    /// Stop collecting files in a repository after this many (0 = unlimited); the cutoff is recorded in the report [default: 100000]
+    #[arg(long)]
+    max_files_per_repo: Option<usize>,
+
+    /// Cut match_context to this many characters around the match (0 = whole line); cut contexts end with …[truncated] and record the line length in context_truncated [default: 2000]
+    #[arg(long)]
+    max_context_chars: Option<usize>,
+
+    /// Parts of each repository to fetch and scan: full, or workflows (alias: ci) for a sparse checkout of CI config and deployment manifests only [default: full]
+    #[arg(long, value_enum)]
+    scope: Option<models::ScanScope>,
+
+    /// Path patterns (gitignore syntax, comma-separated) checked out and scanned with --scope workflows [default: .github/, common CI files, deploy/k8s/helm manifest dirs]
+    #[arg(long, value_delimiter = ',')]
+    scope_paths: Vec<String>,
+
+    /// Scan only the files listed in this file (one repository-relative path per line) instead of walking the repository; needs exactly one enabled repository and replaces its `only_files`
+    #[arg(long)]
+    files_from: Option<PathBuf>,
+
+    /// Scan only this many enabled repositories, picked at random, and report the summary scaled to all of them as estimates
+    #[arg(long)]
+    sample: Option<usize>,
+
+    /// Seed of the --sample selection; the same seed and configuration pick the same repositories (default: random, logged and recorded in the report)
+    #[arg(long, requires = "sample")]
+    sample_seed: Option<u64>,
+
+    /// Prefix every report artifact file name with <PREFIX>_ (e.g. acme_report.json) so scans can share one output directory
+    #[arg(long)]
+    output_prefix: Option<String>,
+
+    /// Report artifacts to write (comma-separated): detail-json, detail-csv, detail-tsv, aggregate-json, aggregate-csv, triage [default: detail-json,detail-csv,aggregate-json,triage]
+    #[arg(long, value_enum, value_delimiter = ',')]
+    reports: Vec<report::ReportArtifact>,
+
+    /// Also write report.tsv (tab-separated, one line per finding) for pasting into spreadsheets
+    #[arg(long, default_value = "false")]
+    tsv: bool,
+
+    /// Previous report.json to compare against; changes are printed in the summary and added to report.json as "delta"
+    #[arg(long, visible_alias = "previous-report")]
+    baseline: Option<PathBuf>,
+
+    /// Write a shields.io endpoint badge per repository (plus index.json) into this directory
+    #[arg(long)]
+    badges_dir: Option<PathBuf>,
+
+    /// Badge color thresholds on unique NIMs per repo: LOW,HIGH (grey below LOW, bright green at HIGH)
+    #[arg(long, value_delimiter = ',', default_value = "1,5")]
+    badge_thresholds: Vec<usize>,
+
+    /// Replace newlines in CSV match_context with literal \n (for tools that can't parse quoted newlines)
+    #[arg(long, default_value = "false")]
+    csv_flatten_newlines: bool,
+
+    /// Prefix CSV cells starting with =, +, - or @ with ' so spreadsheets don't evaluate them as formulas
+    #[arg(long, default_value = "false")]
+    csv_safe_formulas: bool,
+
+    /// Write a JSONL audit trail (files scanned, content hashes, per-rule counters); gzipped if the path ends in .gz
+    #[arg(long)]
+    audit_log: Option<PathBuf>,
+
+    /// After all other artifacts, write MANIFEST.sha256 with the SHA-256 digest of each file in the output directory
+    #[arg(long, default_value = "false")]
+    manifest: bool,
+
+    /// Sign the manifest with this Ed25519 private key (PKCS#8 PEM) into MANIFEST.sha256.sig; implies --manifest
+    #[arg(long)]
+    sign_key: Option<PathBuf>,
+
+    /// Export spans of each scan phase to this OTLP/HTTP collector (e.g. http://localhost:4318); needs the `otel` feature
+    #[arg(long)]
+    otlp_endpoint: Option<String>,
+}
+
+/// Scan options after merging the command line over the `scan:` section of repos.yaml
+struct EffectiveOptions {
+    output: PathBuf,
+    output_prefix: Option<String>,
+    /// Non-empty NGC API key, when enrichment can run
+    ngc_api_key: Option<String>,
+    github_token: Option<String>,
+    workdir: Option<PathBuf>,
+    keep_repos: bool,
+    allow_reset: bool,
+    no_clone: bool,
+    acquire: models::AcquireMode,
+    max_repo_size_mb: Option<u64>,
+    verbose: u8,
+    reports: Vec<report::ReportArtifact>,
+    enrich: Vec<models::EnrichStage>,
+    enrichment_budget: ngc_api::EnrichmentBudget,
+    enrich_model_cards: bool,
+    capture_call_params: bool,
+    no_prefilter: bool,
+    scan_own_reports: bool,
+    fetch_lfs: bool,
+    include_generated: bool,
+    include_fixtures: bool,
+    skip_docs: bool,
+    github_metadata: bool,
+    blame: bool,
+    /// Extra generated-file patterns (`scan.generated_files`)
+    generated_files: Vec<String>,
+    /// Extra org-less model names (`scan.bare_models`, lowercase)
+    bare_models: Vec<String>,
+    /// Fixture path patterns (`scan.test_fixture_globs`, else the defaults)
+    test_fixture_globs: Vec<String>,
+    /// Built-in Hosted NIM hosts plus `scan.hosted_hosts` and --extra-hosted-host
+    hosted_hosts: scanner::HostedHosts,
+    triage_weights: models::TriageWeights,
+    /// None = unlimited
+    max_depth: Option<usize>,
+    /// None = unlimited
+    max_files_per_repo: Option<usize>,
+    /// None = unlimited
+    max_context_chars: Option<usize>,
+    scope: models::ScanScope,
+    scope_paths: Vec<String>,
+    files_from: Option<PathBuf>,
+    sample: Option<usize>,
+    sample_seed: Option<u64>,
+    model_policy: Option<PathBuf>,
+    fail_on_severity: Option<models::Severity>,
+    fail_on_staging_in_source: bool,
+    fail_on_policy_violations: bool,
+    fail_on_empty: bool,
+    baseline: Option<PathBuf>,
+    badges_dir: Option<PathBuf>,
+    badge_thresholds: report::BadgeThresholds,
+    csv_options: report::CsvOptions,
+    audit_log: Option<PathBuf>,
+}
+
+impl EffectiveOptions {
+    /// Merge the command line over repos.yaml's `scan:` section
+    ///
+    /// For each option a command-line value wins, then `scan:`, then the built-in
+    /// default. Boolean flags can only turn an option on: a `true` in `scan:`
+    /// can't be switched off from the command line.
+    fn resolve(args: ScanArgs, file: &models::ScanSettings) -> Result<Self> {
+        let badge_thresholds = match args.badge_thresholds.as_slice() {
+            [low, high] => report::BadgeThresholds { low: *low, high: *high },
+            _ => bail!("--badge-thresholds expects two values: LOW,HIGH"),
+        };
+        if let Some(ref prefix) = args.output_prefix {
+            report::validate_output_prefix(prefix)?;
+        }
+        
+        let mut reports = match args.reports.is_empty() {
+            false => args.reports,
+            true => file.output_formats.clone().unwrap_or_else(|| report::ReportArtifact::DEFAULT_SET.to_vec()),
+        };
+        if args.tsv && !reports.contains(&report::ReportArtifact::DetailTsv) {
+            reports.push(report::ReportArtifact::DetailTsv);
+        }
+        let mut enrich = match args.enrich.is_empty() {
+            false => args.enrich,
+            true => file.enrich.clone().unwrap_or_else(|| models::EnrichStage::DEFAULT_SET.to_vec()),
+        };
+        if args.enrich_image_meta && !enrich.contains(&models::EnrichStage::ImageMeta) {
+            enrich.push(models::EnrichStage::ImageMeta);
+        }
+        if args.verify_tags && !enrich.contains(&models::EnrichStage::Tags) {
+            enrich.push(models::EnrichStage::Tags);
+        }
+        
+        let fail_on = &file.fail_on;
+        let fail_on_policy_violations = args.fail_on_policy_violations || fail_on.policy_violations.unwrap_or(false);
+        if fail_on_policy_violations && args.model_policy.is_none() {
+            bail!("scan.fail_on.policy_violations in the configuration needs --model-policy");
+        }
+        if args.sample == Some(0) {
+            bail!("--sample needs at least 1 repository");
+        }
+        let limit = |n: usize| Some(n).filter(|&n| n > 0);
+        let mut extra_hosts: Vec<(String, models::Environment)> = file.hosted_hosts.iter()
+            .map(models::HostedHostEntry::host_and_environment)
+            .collect();
+        for value in &args.extra_hosted_hosts {
+            extra_hosts.push(parse_extra_hosted_host(value)?);
+        }
+        let hosted_hosts = scanner::HostedHosts::new(&extra_hosts)?;
+        
+        Ok(Self {
+            output: args.output,
+            output_prefix: args.output_prefix,
+            ngc_api_key: args.ngc_api_key.filter(|key| !key.is_empty()),
+            github_token: args.github_token,
+            workdir: args.workdir,
+            keep_repos: args.keep_repos,
+            allow_reset: args.allow_reset,
+            no_clone: args.no_clone,
+            acquire: args.acquire,
+            max_repo_size_mb: args.max_repo_size_mb,
+            verbose: args.verbose,
+            reports,
+            enrich,
+            enrichment_budget: ngc_api::EnrichmentBudget::from_limits(
+                args.enrichment_budget_secs,
+                args.enrichment_max_requests,
+            ),
+            enrich_model_cards: args.enrich_model_cards,
+            capture_call_params: args.capture_call_params || file.capture_call_params.unwrap_or(false),
+            no_prefilter: args.no_prefilter,
+            scan_own_reports: args.scan_own_reports,
+            fetch_lfs: args.fetch_lfs,
+            include_generated: args.include_generated,
+            include_fixtures: args.include_fixtures,
+            skip_docs: args.skip_docs || file.skip_docs.unwrap_or(false),
+            github_metadata: args.github_metadata,
+            blame: args.blame,
+            generated_files: file.generated_files.clone(),
+            bare_models: file.bare_models.iter()
+                .map(|name| name.trim().to_lowercase())
+                .filter(|name| !name.is_empty())
+                .collect(),
+            test_fixture_globs: file.test_fixture_globs.clone().unwrap_or_else(|| {
+                scanner::DEFAULT_TEST_FIXTURE_GLOBS.iter().map(|glob| glob.to_string()).collect()
+            }),
+            hosted_hosts,
+            triage_weights: file.triage_weights.unwrap_or_default(),
+            max_depth: limit(args.max_depth.or(file.max_depth).unwrap_or(scanner::DEFAULT_MAX_DEPTH)),
+            max_files_per_repo: limit(args.max_files_per_repo.or(file.max_files_per_repo)
+                .unwrap_or(scanner::DEFAULT_MAX_FILES_PER_REPO)),
+            max_context_chars: limit(args.max_context_chars.or(file.max_context_chars)
+                .unwrap_or(scanner::DEFAULT_MAX_CONTEXT_CHARS)),
+            scope: args.scope.or(file.scope).unwrap_or_default(),
+            scope_paths: args.scope_paths,
+            files_from: args.files_from,
+            sample: args.sample,
+            sample_seed: args.sample_seed,
+            model_policy: args.model_policy,
+            fail_on_severity: match args.no_fail_on_severity {
+                true => None,
+                false => Some(args.fail_on_severity.or(fail_on.severity).unwrap_or(models::Severity::Error)),
+            },
+            fail_on_staging_in_source: args.fail_on_staging_in_source || fail_on.staging_in_source.unwrap_or(false),
+            fail_on_policy_violations,
+            fail_on_empty: !args.empty_ok && (args.fail_on_empty || fail_on.empty.unwrap_or(false)),
+            baseline: args.baseline,
+            badges_dir: args.badges_dir,
+            badge_thresholds,
+            csv_options: report::CsvOptions {
+                flatten_newlines: args.csv_flatten_newlines,
+                safe_formulas: args.csv_safe_formulas,
+            },
+            audit_log: args.audit_log,
+        })
+    }
+}
+
+/// `--extra-hosted-host` value: `HOST`, serving production, or `HOST=staging` / `HOST=production`
+fn parse_extra_hosted_host(value: &str) -> Result<(String, models::Environment)> {
+    let (host, environment) = match value.split_once('=') {
+        None => (value, models::Environment::Production),
+        Some((host, "production")) => (host, models::Environment::Production),
+        Some((host, "staging")) => (host, models::Environment::Staging),
+        Some((_, other)) => bail!("--extra-hosted-host {}: unknown environment {:?}, expected production or staging", value, other),
+    };
+    Ok((host.to_string(), environment))
+}
+
+/// Arguments for the merge-reports subcommand
+#[derive(Parser, Debug)]
+struct MergeReportsArgs {
+    /// report.json files to merge (two or more)
+    #[arg(required = true, num_args = 2..)]
+    reports: Vec<PathBuf>,
+
+    /// Output directory for the merged report.json and report.csv
+    #[arg(short, long, default_value = "./output")]
+    output: PathBuf,
+
+    /// Prefix the merged artifact file names with <PREFIX>_ (e.g. combined_report.json)
+    #[arg(long)]
+    output_prefix: Option<String>,
+
+    /// Increase logging verbosity (-v, -vv, -vvv)
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    verbose: u8,
+}
+
+/// Arguments for the enrich-report subcommand
+#[derive(Parser, Debug)]
+struct EnrichReportArgs {
+    /// report.json written by scan (e.g. without an NGC API key)
+    report: PathBuf,
+
+    /// Where to write the enriched report.json (not the input); progress is kept next to it in <OUTPUT>.progress.json
+    #[arg(short, long)]
+    output: PathBuf,
+
+    /// Continue the run recorded in <OUTPUT>.progress.json (killed, or stopped by its budget)
+    #[arg(long, default_value = "false")]
+    resume: bool,
+
+    /// repos.yaml of the scan: per-repository `enrich` and `ngc_api_key_env`, `scan.enrich`, policies and endpoint rules
+    #[arg(short, long)]
+    config: Option<PathBuf>,
+
+    /// NGC API key (required, or use NVIDIA_API_KEY env var, or --ngc-api-key-file)
+    #[arg(long, env = "NVIDIA_API_KEY", required_unless_present = "ngc_api_key_file")]
+    ngc_api_key: Option<String>,
+
+    /// Read the NGC API key from a file, e.g. a mounted secret (trailing newline dropped; takes precedence over --ngc-api-key)
+    #[arg(long)]
+    ngc_api_key_file: Option<PathBuf>,
+
+    /// NGC enrichment stages to run (comma-separated): local, hosted, image-meta, tags [default: local,hosted]
+    #[arg(long, value_enum, value_delimiter = ',')]
+    enrich: Vec<models::EnrichStage>,
+
+    /// Fetch display name and publisher for each Local NIM image; adds image-meta to --enrich
+    #[arg(long, default_value = "false")]
+    enrich_image_meta: bool,
+
+    /// Fetch publisher, modalities and context length of each Hosted NIM model from the public model catalog
+    #[arg(long, default_value = "false")]
+    enrich_model_cards: bool,
+
+    /// Check that every pinned Local NIM tag exists in NGC; adds tags to --enrich
+    #[arg(long, default_value = "false")]
+    verify_tags: bool,
+
+    /// Stop after this many seconds; findings not yet looked up are marked budget_exhausted and left for --resume (0 = unlimited)
+    #[arg(long, default_value_t = 0)]
+    enrichment_budget_secs: u64,
+
+    /// Stop after this many API requests, retries included; the rest is left for --resume (0 = unlimited)
+    #[arg(long, default_value_t = 0)]
+    enrichment_max_requests: usize,
+
+    /// Findings looked up between two checkpoints of the output and progress file (each batch starts with empty caches)
+    #[arg(long, default_value_t = enrich_report::DEFAULT_BATCH_SIZE)]
+    batch_size: usize,
+
+    /// Increase logging verbosity (-v, -vv, -vvv)
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    verbose: u8,
+}
+
+/// Arguments for the init subcommand
+#[derive(Parser, Debug)]
+struct InitArgs {
+    /// Where to write the starter configuration
+    #[arg(short, long, default_value = "repos.yaml")]
+    output: PathBuf,
+
+    /// Overwrite the output file if it already exists
+    #[arg(long, default_value = "false")]
+    force: bool,
+
+    /// List this GitHub organization's repositories instead of the examples (needs a GitHub token)
+    #[arg(long)]
+    github_org: Option<String>,
+
+    /// GitHub token for --github-org (or use GITHUB_TOKEN env var)
+    #[arg(long, env = "GITHUB_TOKEN")]
+    github_token: Option<String>,
+}
+
+/// Arguments for the clean subcommand
+#[derive(Parser, Debug)]
+struct CleanArgs {
+    /// Workdir a scan cloned into (the --workdir of `scan --keep-repos`)
+    #[arg(long)]
+    workdir: PathBuf,
+
+    /// Delete the listed checkouts; without it, only print what would be removed
+    #[arg(long, default_value = "false")]
+    yes: bool,
+}
+
+/// Arguments for the verify-output subcommand
+#[derive(Parser, Debug)]
+struct VerifyOutputArgs {
+    /// Output directory of the scan (holding MANIFEST.sha256)
+    dir: PathBuf,
+
+    /// The --output-prefix the scan used
+    #[arg(long)]
+    output_prefix: Option<String>,
+
+    /// Ed25519 public key (PEM) to check MANIFEST.sha256.sig against
+    #[arg(long)]
+    public_key: Option<PathBuf>,
+}
+
+/// Arguments for the config subcommand
+#[derive(Parser, Debug)]
+struct ConfigArgs {
+    #[command(subcommand)]
+    command: ConfigCommand,
+}
+
+#[derive(Subcommand, Debug)]
+enum ConfigCommand {
+    /// Load and validate a repos.yaml, printing the repository count or every problem found
+    Check(ConfigCheckArgs),
+}
+
+/// Arguments for config check
+#[derive(Parser, Debug)]
+struct ConfigCheckArgs {
+    /// Path to the repos.yaml configuration file
+    path: PathBuf,
+}
+
+/// Arguments for the report subcommand
+#[derive(Parser, Debug)]
+struct ReportArgs {
+    #[command(subcommand)]
+    command: ReportCommand,
+}
+
+#[derive(Subcommand, Debug)]
+enum ReportCommand {
+    /// Print findings per top- and second-level directory of each repository
+    Hotspots(HotspotsArgs),
+}
+
+/// Arguments for report hotspots
+#[derive(Parser, Debug)]
+struct HotspotsArgs {
+    /// Path to a report.json written by scan
+    report: PathBuf,
+
+    /// Output format: json, table or csv
+    #[arg(long, value_enum, default_value_t = report::OutputFormat::Json)]
+    format: report::OutputFormat,
 }
 
 /// Arguments for the query subcommand
@@ -94,6 +682,9 @@ enum QueryType {
     
     /// Query Local NIM information (latest tag, description, etc.)
     LocalNim(LocalNimQueryArgs),
+    
+    /// Find the NVCF functions running a container image
+    FunctionsByImage(FunctionsByImageQueryArgs),
 }
 
 /// Arguments for querying Hosted NIM
@@ -103,9 +694,17 @@ struct HostedNimQueryArgs {
     #[arg(short, long)]
     model: String,
 
-    /// NGC API key (required, or use NVIDIA_API_KEY env var)
-    #[arg(long, env = "NVIDIA_API_KEY", required = true)]
-    ngc_api_key: String,
+    /// NGC API key (required, or use NVIDIA_API_KEY env var, or --ngc-api-key-file)
+    #[arg(long, env = "NVIDIA_API_KEY", required_unless_present = "ngc_api_key_file")]
+    ngc_api_key: Option<String>,
+
+    /// Read the NGC API key from a file, e.g. a mounted secret (trailing newline dropped; takes precedence over --ngc-api-key)
+    #[arg(long)]
+    ngc_api_key_file: Option<PathBuf>,
+
+    /// Output format: json (all fields), table or csv (key fields only)
+    #[arg(long, value_enum, default_value_t = report::OutputFormat::Json)]
+    format: report::OutputFormat,
 
     /// Increase logging verbosity (-v, -vv, -vvv)
     #[arg(short, long, action = clap::ArgAction::Count)]
@@ -116,13 +715,53 @@ struct HostedNimQueryArgs {
 #[derive(Parser, Debug)]
 struct LocalNimQueryArgs {
     /// Image name to query (e.g., "nvidia/llama-3.2-nv-embedqa-1b-v2")
-    /// Format: <team>/<model-name> (without nvcr.io/nim/ prefix)
+    /// Format: <team>/<model-name>[:<tag>][@sha256:<digest>] (nvcr.io/nim/ prefix optional)
     #[arg(short, long)]
     image: String,
 
-    /// NGC API key (required, or use NVIDIA_API_KEY env var)
-    #[arg(long, env = "NVIDIA_API_KEY", required = true)]
-    ngc_api_key: String,
+    /// NGC API key (required, or use NVIDIA_API_KEY env var, or --ngc-api-key-file)
+    #[arg(long, env = "NVIDIA_API_KEY", required_unless_present = "ngc_api_key_file")]
+    ngc_api_key: Option<String>,
+
+    /// Read the NGC API key from a file, e.g. a mounted secret (trailing newline dropped; takes precedence over --ngc-api-key)
+    #[arg(long)]
+    ngc_api_key_file: Option<PathBuf>,
+
+    /// Also list every tag of the image (paginated registry requests)
+    #[arg(long, default_value = "false")]
+    tags: bool,
+
+    /// Output format: json (all fields), table or csv (key fields only)
+    #[arg(long, value_enum, default_value_t = report::OutputFormat::Json)]
+    format: report::OutputFormat,
+
+    /// Increase logging verbosity (-v, -vv, -vvv)
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    verbose: u8,
+}
+
+/// Arguments for finding functions by container image
+#[derive(Parser, Debug)]
+struct FunctionsByImageQueryArgs {
+    /// Container image to look up (e.g., "nvcr.io/nim/meta/llama-3.1-70b-instruct:1.1")
+    #[arg(short, long)]
+    image: String,
+
+    /// Match functions running any tag (or digest) of the image
+    #[arg(long, default_value = "false")]
+    ignore_tag: bool,
+
+    /// NGC API key (required, or use NVIDIA_API_KEY env var, or --ngc-api-key-file)
+    #[arg(long, env = "NVIDIA_API_KEY", required_unless_present = "ngc_api_key_file")]
+    ngc_api_key: Option<String>,
+
+    /// Read the NGC API key from a file, e.g. a mounted secret (trailing newline dropped; takes precedence over --ngc-api-key)
+    #[arg(long)]
+    ngc_api_key_file: Option<PathBuf>,
+
+    /// Output format: json (all fields), table or csv (key fields only)
+    #[arg(long, value_enum, default_value_t = report::OutputFormat::Json)]
+    format: report::OutputFormat,
 
     /// Increase logging verbosity (-v, -vv, -vvv)
     #[arg(short, long, action = clap::ArgAction::Count)]
@@ -137,45 +776,238 @@ fn init_logging(verbosity: u8) {
         _ => LevelFilter::Trace,
     };
 
-    env_logger::Builder::new()
+    // A logger may already be installed (embedding application, repeated runs)
+    let _ = env_logger::Builder::new()
         .filter_level(level)
         .format_timestamp_secs()
-        .init();
+        .try_init();
+}
+
+/// Process-wide logger for tests that assert on warnings
+///
+/// Every test that reaches `init_logging` installs it first, so whichever test
+/// runs first, env_logger finds the slot taken and the capture stays in place.
+#[cfg(test)]
+mod test_log {
+    use std::sync::{Mutex, Once};
+
+    /// Every warning or error logged so far, by any test
+    static RECORDS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    struct Capture;
+
+    impl log::Log for Capture {
+        fn enabled(&self, metadata: &log::Metadata) -> bool {
+            metadata.level() <= log::Level::Warn
+        }
+
+        fn log(&self, record: &log::Record) {
+            if self.enabled(record.metadata()) {
+                RECORDS.lock().unwrap().push(record.args().to_string());
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    static CAPTURE: Capture = Capture;
+
+    /// Install the capturing logger (once per test process)
+    pub fn install() {
+        static ONCE: Once = Once::new();
+        ONCE.call_once(|| {
+            log::set_logger(&CAPTURE).expect("a logger was installed before the test logger");
+            log::set_max_level(log::LevelFilter::Warn);
+        });
+    }
+
+    /// Captured warnings and errors, oldest first
+    pub fn warnings() -> Vec<String> {
+        RECORDS.lock().unwrap().clone()
+    }
+}
+
+/// Build the thread pool used for cloning and scanning, sized by --jobs
+/// (rayon's default of one thread per CPU when unset)
+///
+/// This is a local pool rather than rayon's global one, so --jobs takes effect
+/// even when the global pool was already initialized by the host process or an
+/// earlier scan.
+fn build_thread_pool(jobs: Option<usize>) -> Result<rayon::ThreadPool> {
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(jobs.unwrap_or(0))
+        .build()
+        .context("Failed to build thread pool")
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
     
     match cli.command {
-        Commands::Scan(args) => run_scan(args),
+        Commands::Scan(args) => run_scan(*args),
         Commands::Query(args) => run_query(args),
+        Commands::MergeReports(args) => run_merge_reports(args),
+        Commands::EnrichReport(args) => run_enrich_report(args),
+        Commands::Init(args) => run_init(args),
+        Commands::Clean(args) => run_clean(args),
+        Commands::VerifyOutput(args) => run_verify_output(args),
+        Commands::Config(args) => match args.command {
+            ConfigCommand::Check(args) => run_config_check(args),
+        },
+        Commands::Report(args) => match args.command {
+            ReportCommand::Hotspots(args) => run_report_hotspots(args),
+        },
     }
 }
 
 /// Run the scan subcommand
+///
+/// While it runs, status.json in the output directory shows its progress.
+/// However the run ends, result.json is written to the output directory
+/// afterwards, with the exit code the process returns. With --manifest (or
+/// --sign-key) MANIFEST.sha256 follows, covering result.json too.
 fn run_scan(args: ScanArgs) -> Result<()> {
     // Initialize logging (info level by default for scan)
     init_logging(args.verbose + 1);
     
+    let started = Instant::now();
+    let output = args.output.clone();
+    // An invalid prefix fails the run; result.json then goes without it
+    let prefix = args.output_prefix.clone().filter(|p| report::validate_output_prefix(p).is_ok());
+    let manifest = args.manifest || args.sign_key.is_some();
+    let writer = report::ReportWriter::new(&output, &[], report::CsvOptions::default()).with_prefix(prefix.as_deref());
+    let status = status::StatusReporter::new(writer.artifact_path(status::STATUS_FILE));
+    let mut run = models::RunResult::default();
+    // A key that can't be loaded, or a trace exporter that can't be set up,
+    // fails the run before anything is scanned
+    let mut signing_key = None;
+    let setup = args.sign_key.as_deref().map(attest::load_signing_key).transpose()
+        .and_then(|key| Ok((key, telemetry::Telemetry::init(args.otlp_endpoint.as_deref())?)));
+    let mut outcome = match setup {
+        Ok((key, telemetry)) => {
+            signing_key = key;
+            let outcome = scan_pipeline(args, &mut run, &telemetry, &status);
+            telemetry.shutdown();
+            outcome
+        }
+        Err(e) => Err(e),
+    };
+    
+    run.duration_ms = started.elapsed().as_millis() as u64;
+    match outcome {
+        Ok(()) => run.exit_code = 0,
+        Err(ref e) => {
+            run.exit_code = 1;
+            if run.status != models::RunStatus::ChecksFailed {
+                run.status = models::RunStatus::Error;
+            }
+            run.error = Some(format!("{:#}", e));
+        }
+    }
+    status.finish(run.error.clone());
+    let mut artifacts: Vec<PathBuf> = run.artifacts.iter().map(PathBuf::from).collect();
+    match writer.write_run_result(&run) {
+        Ok(path) => {
+            info!("Run result written to {}", path.display());
+            artifacts.push(path);
+        }
+        Err(e) => warn!("{:#}", e),
+    }
+    
+    // Last, so the manifest covers every other file
+    if manifest {
+        let manifest_path = writer.artifact_path(attest::MANIFEST_FILE);
+        match attest::write_manifest(&manifest_path, &artifacts, signing_key.as_ref()) {
+            Ok(written) => {
+                for path in written {
+                    info!("Manifest written to {}", path.display());
+                }
+            }
+            Err(e) => {
+                error!("{:#}", e);
+                outcome = outcome.and(Err(e));
+            }
+        }
+    }
+    outcome
+}
+
+/// One `enrichment_failure` event on `span` per finding NGC enrichment failed for
+fn record_enrichment_failures(span: &mut telemetry::PhaseSpan, findings: &[&models::NimFindings]) {
+    if !span.is_recording() {
+        return;
+    }
+    let mut failures = 0;
+    for f in findings {
+        for m in &f.local_nim {
+            if let Some(error) = m.enrichment_error {
+                let line = m.line_number.to_string();
+                span.event("enrichment_failure", &[
+                    ("repository", &m.repository), ("file", &m.file_path), ("line", &line),
+                    ("image", &m.image_url), ("error", error.as_str()),
+                ]);
+                failures += 1;
+            }
+        }
+        for m in &f.hosted_nim {
+            if let Some(error) = m.enrichment_error {
+                let line = m.line_number.to_string();
+                let model = m.model_name.as_deref().or(m.function_id.as_deref()).unwrap_or("");
+                span.event("enrichment_failure", &[
+                    ("repository", &m.repository), ("file", &m.file_path), ("line", &line),
+                    ("model", model), ("error", error.as_str()),
+                ]);
+                failures += 1;
+            }
+        }
+    }
+    span.set_int("findings.failed", failures);
+}
+
+/// Repository whose scan panics in tests, standing in for a scanner bug
+#[cfg(test)]
+const PANICKING_TEST_REPO: &str = "test/panics-on-scan";
+
+/// Scan a checkout: only the files its configuration lists, or all of it
+fn scan_checkout(repo: &models::RepoConfig, path: &std::path::Path, options: &scanner::ScanOptions) -> scanner::ScanOutcome {
+    #[cfg(test)]
+    if repo.name == PANICKING_TEST_REPO {
+        panic!("injected panic scanning {}", repo.name);
+    }
+    match repo.only_files.is_empty() {
+        true => scanner::scan_directory(path, &repo.name, options),
+        false => scanner::scan_files(path, &repo.name, &repo.only_files, options),
+    }
+}
+
+/// Every stage of a scan, recording counts and artifacts in `run` as they are known
+fn scan_pipeline(
+    args: ScanArgs,
+    run: &mut models::RunResult,
+    telemetry: &telemetry::Telemetry,
+    status: &status::StatusReporter,
+) -> Result<()> {
     info!("NIM Usage Scanner starting...");
+    let mut trace = telemetry.span("scan");
     info!("Config file: {}", args.config.display());
     info!("Output directory: {}", args.output.display());
     
-    // Set rayon thread pool size if specified
-    if let Some(jobs) = args.jobs {
-        rayon::ThreadPoolBuilder::new()
-            .num_threads(jobs)
-            .build_global()
-            .context("Failed to set thread pool size")?;
-        info!("Using {} parallel jobs", jobs);
-    }
+    // Local thread pool for cloning and scanning (the global pool may already be set up)
+    let pool = build_thread_pool(args.jobs)?;
+    info!("Using {} parallel jobs", pool.current_num_threads());
     
     if args.refresh_repos {
         info!("Refreshing repos from Build Page...");
-        let status = Command::new("python3")
+        let mut command = Command::new("python3");
+        command
             .arg("scripts/generate_repos_from_ngc.py")
             .arg("--output")
-            .arg(&args.config)
+            .arg(&args.config);
+        // Repo metadata (default branch, archived) is fetched from the GitHub API
+        if let Some(ref token) = args.github_token {
+            command.env("GITHUB_TOKEN", token);
+        }
+        let status = command
             .status()
             .context("Failed to run Build Page repo generation script")?;
         if !status.success() {
@@ -192,21 +1024,76 @@ fn run_scan(args: ScanArgs) -> Result<()> {
     
     config::validate_config(&config)
         .context("Configuration validation failed")?;
+    let policy_engine = policy::PolicyEngine::new(&config.policies)?;
+    let endpoint_rules = policy::EndpointRules::new(&config.endpoint_rules)?;
+    let opts = EffectiveOptions::resolve(args, &config.scan)?;
+    let scope_paths: Vec<String> = match opts.scope {
+        models::ScanScope::Full => {
+            if !opts.scope_paths.is_empty() {
+                warn!("--scope-paths is ignored without --scope workflows");
+            }
+            Vec::new()
+        }
+        models::ScanScope::Workflows if opts.scope_paths.is_empty() => {
+            git_ops::DEFAULT_SCOPE_PATHS.iter().map(|p| p.to_string()).collect()
+        }
+        models::ScanScope::Workflows => opts.scope_paths.clone(),
+    };
+    let scope_matcher = if scope_paths.is_empty() {
+        None
+    } else {
+        info!("Scope: CI workflows and manifests only ({})", scope_paths.join(", "));
+        Some(scanner::build_scope_matcher(&scope_paths)?)
+    };
+    let generated_matcher = match opts.include_generated {
+        true => None,
+        false => Some(scanner::build_generated_matcher(&opts.generated_files)?),
+    };
+    let fixture_matcher = match opts.test_fixture_globs.is_empty() {
+        true => None,
+        false => Some(scanner::build_fixture_matcher(&opts.test_fixture_globs)?),
+    };
+    let model_policy = opts.model_policy.as_deref().map(policy::ModelPolicy::load).transpose()?;
     
     // Apply defaults and filter enabled repos
     let repos = config::apply_defaults(&config);
-    let repos = config::filter_enabled(repos);
+    let mut repos = config::filter_enabled(repos);
     
     if repos.is_empty() {
         warn!("No enabled repositories found in configuration");
         return Ok(());
     }
+    run.repos.configured = repos.len();
+    trace.set_int("repos.configured", repos.len());
+    let sample = match opts.sample {
+        Some(size) if size < repos.len() => {
+            let seed = opts.sample_seed.unwrap_or_else(sampling::seed_from_clock);
+            let population = repos.len();
+            let picked = sampling::select(population, size, seed);
+            repos = picked.into_iter().map(|i| repos[i].clone()).collect();
+            info!("Sampling {} of {} enabled repositories (--sample-seed {})", size, population, seed);
+            Some((seed, population))
+        }
+        Some(size) => {
+            info!("--sample {} covers all {} enabled repositories; scanning them all", size, repos.len());
+            None
+        }
+        None => None,
+    };
+    status.set_total(repos.len());
+    if let Some(ref path) = opts.files_from {
+        if repos.len() != 1 {
+            bail!("--files-from needs exactly one enabled repository, found {}", repos.len());
+        }
+        repos[0].only_files = config::load_file_list(path)?;
+        info!("[{}] Scanning {} listed file(s) from {}", repos[0].name, repos[0].only_files.len(), path.display());
+    }
     
     info!("Found {} enabled repositories to scan", repos.len());
     
     // Create working directory
     let temp_dir: Option<TempDir>;
-    let workdir = if let Some(ref dir) = args.workdir {
+    let workdir = if let Some(ref dir) = opts.workdir {
         std::fs::create_dir_all(dir)
             .with_context(|| format!("Failed to create workdir: {}", dir.display()))?;
         temp_dir = None;
@@ -220,94 +1107,386 @@ fn run_scan(args: ScanArgs) -> Result<()> {
     
     info!("Working directory: {}", workdir.display());
     
-    if args.github_token.is_none() {
-        warn!("No GitHub token provided; private repositories may fail to clone");
-    }
+    status.phase(status::Phase::Cloning);
+    let mut clone_span = trace.child("clone");
+    let clone_results = if opts.no_clone {
+        // Pre-provisioned checkouts (e.g. air-gapped hosts): no git at all
+        info!("Using pre-provisioned checkouts (--no-clone)...");
+        git_ops::use_existing_checkouts(&repos, &workdir)
+    } else {
+        if opts.github_token.is_none() {
+            warn!("No GitHub token provided; private repositories may fail to clone");
+        }
 
-    // Clone repositories
-    info!("Cloning repositories...");
-    let clone_results = git_ops::clone_all_repos(&repos, &workdir, args.github_token.as_deref());
+        // Clone repositories
+        info!("Cloning repositories...");
+        pool.install(|| git_ops::clone_all_repos(
+            &repos,
+            &workdir,
+            opts.github_token.as_deref(),
+            &scope_paths,
+            opts.acquire,
+            opts.max_repo_size_mb,
+            opts.allow_reset,
+        ))
+    };
     
     let (success_count, failed_count) = git_ops::clone_stats(&clone_results);
-    info!("Clone complete: {} succeeded, {} failed", success_count, failed_count);
+    let skipped_oversize: Vec<models::OversizeSkip> = clone_results
+        .iter()
+        .filter_map(|r| r.skipped_oversize.clone())
+        .collect();
+    info!("Checkouts ready: {} succeeded, {} failed, {} skipped over the size limit",
+          success_count, failed_count, skipped_oversize.len());
+    run.repos.cloned = success_count;
+    run.repos.failed = failed_count;
+    run.repos.skipped_oversize = skipped_oversize.len();
+    clone_span.set_int("repos.cloned", success_count);
+    clone_span.set_int("repos.failed", failed_count);
+    clone_span.set_int("repos.skipped_oversize", skipped_oversize.len());
+    clone_span.end();
     
     // Log failed clones
     for result in &clone_results {
         if let Some(ref err) = result.error {
-            error!("Failed to clone {}: {}", result.repo.name, err);
+            error!("[{}] Failed to check out: {}", result.repo.name, err);
+            status.error(format!("[{}] Failed to check out: {}", result.repo.name, err));
         }
     }
+    let mut panicked: Vec<models::RepoPanic> = clone_results
+        .iter()
+        .filter_map(|r| Some(models::RepoPanic {
+            repository: r.repo.name.clone(),
+            stage: models::PanicStage::Checkout,
+            message: r.panic.clone()?,
+        }))
+        .collect();
     
     // Scan repositories
     info!("Scanning repositories for NIM references...");
+    status.phase(status::Phase::Scanning);
+    // Repositories without a checkout are done before their scan would start
+    for _ in clone_results.iter().filter(|r| r.path.is_none()) {
+        status.repo_done(0);
+    }
     let mut all_local = Vec::new();
     let mut all_hosted = Vec::new();
+    let mut all_dependencies = Vec::new();
+    let mut truncated = Vec::new();
+    let mut lfs_pointers = Vec::new();
+    let mut listed_file_errors = Vec::new();
+    let mut scan_ms = HashMap::new();
+    let mut audit_log = match opts.audit_log {
+        Some(ref path) => Some(audit::AuditLog::create(path)?),
+        None => None,
+    };
+    let scan_options_for = |repo: &models::RepoConfig| scanner::ScanOptions {
+        skip_dirs: scanner::resolve_skip_dirs(repo),
+        capture_call_params: opts.capture_call_params,
+        disable_prefilter: opts.no_prefilter,
+        max_depth: opts.max_depth,
+        max_files: opts.max_files_per_repo,
+        scope_paths: scope_matcher.clone(),
+        scan_own_reports: opts.scan_own_reports,
+        max_context_chars: opts.max_context_chars,
+        generated_files: generated_matcher.clone(),
+        bare_models: opts.bare_models.clone(),
+        // Collected from the repository's files when its scan starts
+        variables: Default::default(),
+        helm_values: false,
+        fixture_files: fixture_matcher.clone(),
+        skip_docs: opts.skip_docs,
+        hosted_hosts: opts.hosted_hosts.clone(),
+    };
     
-    for result in &clone_results {
-        if let Some(ref path) = result.path {
-            info!("Scanning {}...", result.repo.name);
-            let (local, hosted) = scanner::scan_directory(path, &result.repo.name);
+    // Biggest checkouts first, handed out one at a time, so no large repository
+    // is left running alone at the end of the scan
+    let scan_started = Instant::now();
+    let mut scan_span = trace.child("scan");
+    let sizes: Vec<u64> = clone_results
+        .iter()
+        .map(|r| r.path.as_deref().map(git_ops::checkout_size).unwrap_or(0))
+        .collect();
+    let mut scans: Vec<Option<(scanner::ScanOutcome, Duration)>> = clone_results.iter().map(|_| None).collect();
+    let scanned: Vec<_> = pool.install(|| {
+        scanner::largest_first(&sizes)
+            .into_iter()
+            .par_bridge()
+            .filter_map(|i| {
+                let result = &clone_results[i];
+                let path = result.path.as_ref()?;
+                info!("[{}] Scanning...", result.repo.name);
+                let mut span = scan_span.child("scan_repository");
+                span.set_str("repository", &result.repo.name);
+                let started = Instant::now();
+                let scan_options = scan_options_for(&result.repo);
+                // A panic while scanning one repository leaves only that repository out
+                let outcome = match cleanup::catch_panic(|| scan_checkout(&result.repo, path, &scan_options)) {
+                    Ok(outcome) => outcome,
+                    Err(message) => {
+                        error!("[{}] Scan panicked: {}", result.repo.name, message);
+                        span.event("panic", &[("message", &message)]);
+                        status.error(format!("[{}] Scan panicked: {}", result.repo.name, message));
+                        status.repo_done(0);
+                        return Some((i, Err(message)));
+                    }
+                };
+                let elapsed = started.elapsed();
+                span.set_int("files.scanned", outcome.files.len());
+                span.set_int("files.prefiltered", outcome.prefiltered_files());
+                span.set_int("findings.local_nim", outcome.local.len());
+                span.set_int("findings.hosted_nim", outcome.hosted.len());
+                span.set_int("duration_ms", elapsed.as_millis() as usize);
+                status.repo_done(outcome.local.len() + outcome.hosted.len());
+                Some((i, Ok((outcome, elapsed))))
+            })
+            .collect()
+    });
+    for (i, scan) in scanned {
+        match scan {
+            Ok(scan) => scans[i] = Some(scan),
+            Err(message) => panicked.push(models::RepoPanic {
+                repository: clone_results[i].repo.name.clone(),
+                stage: models::PanicStage::Scan,
+                message,
+            }),
+        }
+    }
+    
+    for (result, scan) in clone_results.iter().zip(scans) {
+        if let (Some(path), Some((mut outcome, elapsed))) = (result.path.as_ref(), scan) {
+            let scan_options = scan_options_for(&result.repo);
+            truncated.extend(outcome.truncation(&result.repo.name, &scan_options));
             
-            info!("  Found {} Local NIM, {} Hosted NIM references",
-                  local.len(), hosted.len());
+            let pointers = outcome.lfs_pointer_paths();
+            if opts.fetch_lfs && !pointers.is_empty() {
+                // Pre-provisioned checkouts are never modified, and tarballs have no LFS remote
+                if result.acquisition != models::Acquisition::Git {
+                    warn!("[{}] Not fetching Git LFS files: not a git clone made by the scanner", result.repo.name);
+                } else {
+                    match git_ops::lfs_pull(path, &pointers) {
+                        Ok(()) => pool.install(|| outcome.rescan_lfs_pointers(path, &result.repo.name, &scan_options)),
+                        Err(e) => warn!("[{}] {:#}", result.repo.name, e),
+                    }
+                }
+            }
+            lfs_pointers.extend(outcome.lfs_pointer_paths().into_iter().map(|file_path| models::LfsPointerFile {
+                repository: result.repo.name.clone(),
+                file_path,
+            }));
+            listed_file_errors.append(&mut outcome.listed_file_errors);
             
-            all_local.extend(local);
-            all_hosted.extend(hosted);
+            info!("[{}] {} Local NIM, {} Hosted NIM references in {:.1}s ({} of {} files skipped by prefilter)",
+                  result.repo.name, outcome.local.len(), outcome.hosted.len(), elapsed.as_secs_f64(),
+                  outcome.prefiltered_files(), outcome.files.len());
+            if outcome.own_reports_skipped() > 0 {
+                info!("[{}] Skipped {} report file(s) written by this scanner",
+                      result.repo.name, outcome.own_reports_skipped());
+            }
+            if outcome.generated_skipped() > 0 {
+                info!("[{}] Skipped {} lockfile(s) and generated file(s) (see --include-generated)",
+                      result.repo.name, outcome.generated_skipped());
+            }
+            scan_ms.insert(result.repo.name.clone(), elapsed.as_millis() as u64);
+            
+            if let Some(ref mut log) = audit_log {
+                log.write_repository(&result.repo.name, result.source, &outcome)?;
+            }
+            all_local.extend(outcome.local);
+            all_hosted.extend(outcome.hosted);
+            all_dependencies.extend(outcome.sdk_dependencies);
+        } else if let Some(ref mut log) = audit_log {
+            let scan_panic = panicked
+                .iter()
+                .find(|p| p.stage == models::PanicStage::Scan && p.repository == result.repo.name);
+            let reason = match (&result.skipped_oversize, scan_panic) {
+                (Some(skip), _) => format!("skipped: {} MB is over the {} MB size limit", skip.size_mb, skip.limit_mb),
+                (None, Some(panic)) => format!("scan panicked: {}", panic.message),
+                (None, None) => result.error.clone().unwrap_or_else(|| "not scanned".to_string()),
+            };
+            log.write_repository_error(&result.repo.name, result.source, &reason)?;
         }
     }
+    info!("Scanned {} repositories in {:.1}s", scan_ms.len(), scan_started.elapsed().as_secs_f64());
+    run.repos.scanned = scan_ms.len();
+    scan_span.set_int("repos.scanned", scan_ms.len());
+    scan_span.set_int("duration_ms", scan_started.elapsed().as_millis() as usize);
+    scan_span.end();
+    if let Some(log) = audit_log {
+        log.finish()?;
+        run.artifacts.extend(opts.audit_log.iter().map(|path| path.display().to_string()));
+    }
+    
+    // Code pulled from a config service at runtime leaves nothing to scan;
+    // GitHub metadata may still name the NIM (never counted as usage)
+    let metadata_hints = if opts.github_metadata {
+        info!("Fetching GitHub repository metadata...");
+        let metadata_options = scanner::ScanOptions { max_context_chars: opts.max_context_chars, ..Default::default() };
+        let metadata = pool.install(|| git_ops::fetch_repo_metadata(&repos, opts.github_token.as_deref()));
+        let hints: Vec<models::MetadataHint> = metadata
+            .iter()
+            .flat_map(|(repository, fields)| scanner::scan_metadata(repository, fields, &metadata_options))
+            .collect();
+        info!("{} NIM hint(s) in the metadata of {} repositories", hints.len(), metadata.len());
+        hints
+    } else {
+        Vec::new()
+    };
     
     // Categorize results
     info!("Categorizing results...");
     let (mut source_code, mut actions_workflow) = scanner::categorize_results(all_local, all_hosted);
+    // Dependency manifests are never workflow files
+    source_code.sdk_dependencies = all_dependencies;
     
     // Deduplicate
     scanner::deduplicate_results(&mut source_code);
     scanner::deduplicate_results(&mut actions_workflow);
     
-    info!("Source code: {} Local NIM, {} Hosted NIM",
-          source_code.local_nim.len(), source_code.hosted_nim.len());
+    info!("Source code: {} Local NIM, {} Hosted NIM, {} SDK dependencies",
+          source_code.local_nim.len(), source_code.hosted_nim.len(), source_code.sdk_dependencies.len());
     info!("Actions workflow: {} Local NIM, {} Hosted NIM",
           actions_workflow.local_nim.len(), actions_workflow.hosted_nim.len());
     
+    status.set_findings(source_code.total_count() + actions_workflow.total_count());
+    
     // Enrich with NGC API
     info!("Enriching findings with NGC API...");
-    ngc_api::enrich_all_findings(
-        args.ngc_api_key.as_deref(),
+    status.phase(status::Phase::Enriching);
+    let mut enrich_span = trace.child("enrich");
+    let enrich_started = Instant::now();
+    let repo_enrichment: HashMap<String, ngc_api::RepoEnrichment> = repos
+        .iter()
+        .filter_map(|repo| Some((repo.name.clone(), ngc_api::RepoEnrichment::from_config(repo)?)))
+        .collect();
+    let image_meta = ngc_api::enrich_all_findings(
+        opts.ngc_api_key.as_deref(),
+        &repo_enrichment,
         &mut source_code,
         &mut actions_workflow,
-    );
+        &opts.enrich,
+        opts.enrichment_budget,
+    ).image_meta;
+    let model_cards = match opts.enrich_model_cards {
+        true => ngc_api::fetch_model_cards(&[&source_code, &actions_workflow]),
+        false => HashMap::new(),
+    };
+    enrich_span.set_int("duration_ms", enrich_started.elapsed().as_millis() as usize);
+    record_enrichment_failures(&mut enrich_span, &[&source_code, &actions_workflow]);
+    enrich_span.end();
     
-    // Generate report
-    let report = ScanReport::new(repos.len(), source_code, actions_workflow);
+    // Mark deprecated endpoint paths, then assign policy severities (after
+    // enrichment, so status/resolved_tag are known)
+    endpoint_rules.apply(&mut source_code);
+    endpoint_rules.apply(&mut actions_workflow);
+    policy_engine.apply(&mut source_code, "source_code");
+    policy_engine.apply(&mut actions_workflow, "actions_workflow");
+    if let Some(ref model_policy) = model_policy {
+        model_policy.apply(&mut source_code);
+        model_policy.apply(&mut actions_workflow);
+    }
     
-    // Create output directory
-    std::fs::create_dir_all(&args.output)
-        .with_context(|| format!("Failed to create output directory: {}", args.output.display()))?;
+    // Blame is slow, so only the findings that made it into the report are blamed
+    if opts.blame {
+        if opts.no_clone {
+            warn!("--blame only covers git clones made by the scanner; pre-provisioned checkouts are not blamed");
+        }
+        info!("Running git blame on findings...");
+        let files = pool.install(|| git_ops::blame_findings(&clone_results, &mut [&mut source_code, &mut actions_workflow]));
+        info!("Blamed findings in {} file(s)", files);
+    }
+    
+    // Generate report
+    status.phase(status::Phase::Reporting);
+    let mut report = ScanReport::new(repos.len(), source_code, actions_workflow);
+    if opts.include_fixtures {
+        report.summary = models::Summary::calculate_all(&report.source_code, &report.actions_workflow);
+    } else if report.summary.fixtures_excluded > 0 {
+        info!("{} finding(s) in test fixtures left out of the summary (see --include-fixtures)",
+              report.summary.fixtures_excluded);
+    }
+    report.aggregated.apply_image_meta(&image_meta);
+    report.aggregated.apply_model_cards(&model_cards);
+    report.summary.modality = report.aggregated.modality_counts();
+    report.truncated = truncated;
+    report.checkouts = clone_results
+        .iter()
+        .filter(|r| r.is_success())
+        .map(|r| models::RepoCheckout {
+            repository: r.repo.name.clone(),
+            acquisition: r.acquisition,
+            commit_sha: r.commit_sha.clone(),
+            scan_ms: scan_ms.get(&r.repo.name).copied(),
+            reset: r.update.reset.clone(),
+            stale: r.update.stale,
+        })
+        .collect();
+    report.skipped_oversize = skipped_oversize;
+    if !lfs_pointers.is_empty() {
+        warn!("{} file(s) are Git LFS pointers and were not scanned (see --fetch-lfs)", lfs_pointers.len());
+    }
+    report.lfs_pointers = lfs_pointers;
+    report.listed_file_errors = listed_file_errors;
+    report.metadata_hints = metadata_hints;
+    report.panicked = panicked;
+    report.scope = opts.scope;
+    report.scope_paths = scope_paths;
+    if let Some((seed, population)) = sample {
+        let names = repos.iter().map(|r| r.name.clone()).collect();
+        report.sampling = Some(sampling::sampling(seed, population, names, &report.summary));
+    }
+    let baseline = match opts.baseline {
+        Some(ref path) => Some(report::load_json_report(path).context("Failed to load baseline report")?),
+        None => None,
+    };
+    if let Some(ref baseline) = baseline {
+        if baseline.scope != report.scope {
+            warn!("Baseline was scanned with a different --scope; the delta compares different coverage");
+        }
+        report.delta = Some(analysis::compute_delta(baseline, &report));
+    }
+    // Models whose function disappeared since the baseline (needs enrichment)
+    if opts.ngc_api_key.is_some() && opts.enrich.contains(&models::EnrichStage::Hosted) {
+        analysis::classify_resolution(&mut report, baseline.as_ref());
+    }
     
-    // Generate JSON report
-    let json_path = args.output.join("report.json");
-    report::generate_json_report(&report, &json_path)
-        .context("Failed to generate JSON report")?;
+    // Write selected report artifacts
+    let mut report_span = trace.child("report");
+    let written = report::ReportWriter::new(&opts.output, &opts.reports, opts.csv_options)
+        .with_prefix(opts.output_prefix.as_deref())
+        .with_triage_weights(opts.triage_weights)
+        .write(&report)
+        .context("Failed to write reports")?;
+    report_span.set_int("artifacts", written.len());
+    report_span.set_int("findings.local_nim", report.summary.total_local_nim);
+    report_span.set_int("findings.hosted_nim", report.summary.total_hosted_nim);
+    report_span.end();
+    run.findings = models::RunFindingCounts {
+        local_nim: report.summary.total_local_nim,
+        hosted_nim: report.summary.total_hosted_nim,
+    };
+    run.artifacts.extend(written.iter().map(|path| path.display().to_string()));
     
-    // Generate CSV reports
-    report::generate_csv_reports(&report, &args.output)
-        .context("Failed to generate CSV reports")?;
-
-    // Generate aggregate report
-    let aggregate_path = args.output.join("report_aggregate.json");
-    report::generate_aggregate_report(&report, &aggregate_path)
-        .context("Failed to generate aggregate report")?;
+    // Generate per-repository badges
+    if let Some(ref badges_dir) = opts.badges_dir {
+        let repo_names: Vec<String> = repos.iter().map(|r| r.name.clone()).collect();
+        let badges = report::generate_badges(&report, &repo_names, badges_dir, &opts.badge_thresholds)
+            .context("Failed to generate badges")?;
+        run.artifacts.extend(badges.iter().map(|path| path.display().to_string()));
+    }
     
-    // Print summary
-    report::print_summary(&report);
+    // Print summary (per-rule detector statistics with -vv)
+    report::print_summary(&report, report.delta.as_ref(), opts.verbose >= 2);
     
-    // Cleanup
-    if !args.keep_repos {
+    // Cleanup (pre-provisioned checkouts are never removed)
+    if opts.no_clone {
+        info!("Leaving pre-provisioned checkouts in {}", workdir.display());
+    } else if !opts.keep_repos {
         info!("Cleaning up cloned repositories...");
         if let Some(td) = temp_dir {
             // TempDir will clean up on drop
             drop(td);
-        } else if let Some(ref dir) = args.workdir {
+        } else if let Some(ref dir) = opts.workdir {
             if let Err(e) = git_ops::cleanup_repos(dir) {
                 warn!("Failed to cleanup workdir: {}", e);
             }
@@ -317,16 +1496,277 @@ fn run_scan(args: ScanArgs) -> Result<()> {
     }
     
     info!("Scan complete!");
-    info!("Reports written to: {}", args.output.display());
+    info!("Reports written to: {}", opts.output.display());
+    for path in &written {
+        info!("  {}", path.display());
+    }
+    
+    match failed_check(&opts, &report) {
+        Some(failure) => {
+            run.status = models::RunStatus::ChecksFailed;
+            bail!(failure)
+        }
+        None => Ok(()),
+    }
+}
+
+/// The first --fail-on condition the report meets, as the error to exit with
+fn failed_check(opts: &EffectiveOptions, report: &ScanReport) -> Option<String> {
+    if let Some(threshold) = opts.fail_on_severity {
+        let count = report.summary.severity.at_least(threshold);
+        if count > 0 {
+            return Some(format!("{} finding(s) with severity {} or higher", count, threshold.as_str()));
+        }
+    }
+    
+    if opts.fail_on_staging_in_source {
+        let count = report.source_code.hosted_nim
+            .iter()
+            .filter(|m| m.environment == models::Environment::Staging)
+            .count();
+        if count > 0 {
+            return Some(format!("{} Hosted NIM reference(s) to staging in source code", count));
+        }
+    }
+    
+    if opts.fail_on_policy_violations {
+        let count: usize = report.summary.policy_violations.values().sum();
+        if count > 0 {
+            return Some(format!("{} Hosted NIM finding(s) violate the model policy", count));
+        }
+    }
+    
+    if opts.fail_on_empty && report.summary.total_local_nim + report.summary.total_hosted_nim == 0 {
+        return Some(format!(
+            "no NIM findings in {} repositories (check the clone and filter settings, or pass --empty-ok)",
+            report.total_repos
+        ));
+    }
+    
+    None
+}
+
+/// Run the merge-reports subcommand
+fn run_merge_reports(args: MergeReportsArgs) -> Result<()> {
+    init_logging(args.verbose + 1);
+    if let Some(ref prefix) = args.output_prefix {
+        report::validate_output_prefix(prefix)?;
+    }
+    
+    let mut inputs = Vec::new();
+    for path in args.reports {
+        let report = report::load_json_report(&path)?;
+        info!("Loaded {} ({} repositories, scanned {})", path.display(), report.total_repos, report.scan_time);
+        inputs.push((path, report));
+    }
+    let merged = merge::merge_reports(inputs).context("Failed to merge reports")?;
+    
+    let artifacts = [report::ReportArtifact::DetailJson, report::ReportArtifact::DetailCsv];
+    let written = report::ReportWriter::new(&args.output, &artifacts, report::CsvOptions::default())
+        .with_prefix(args.output_prefix.as_deref())
+        .write(&merged)
+        .context("Failed to write reports")?;
+    info!("Merged report written to: {}", args.output.display());
+    for path in &written {
+        info!("  {}", path.display());
+    }
+    
+    report::print_summary(&merged, None, args.verbose >= 2);
+    Ok(())
+}
+
+/// Run the enrich-report subcommand
+///
+/// Enrichment stages, budgets and per-repository keys work as in scan. With
+/// --config, the configuration's policies and endpoint rules are applied again
+/// afterwards, since they depend on what enrichment found.
+fn run_enrich_report(args: EnrichReportArgs) -> Result<()> {
+    init_logging(args.verbose + 1);
+    let api_key = query_api_key(args.ngc_api_key, args.ngc_api_key_file.as_deref())?;
+    let config = args.config.as_deref()
+        .map(|path| {
+            let config = config::load_config(path).context("Failed to load configuration")?;
+            config::validate_config(&config).context("Configuration validation failed")?;
+            Ok::<_, anyhow::Error>(config)
+        })
+        .transpose()?;
+
+    let mut stages = match args.enrich.is_empty() {
+        false => args.enrich,
+        true => config.as_ref().and_then(|c| c.scan.enrich.clone())
+            .unwrap_or_else(|| models::EnrichStage::DEFAULT_SET.to_vec()),
+    };
+    if args.enrich_image_meta && !stages.contains(&models::EnrichStage::ImageMeta) {
+        stages.push(models::EnrichStage::ImageMeta);
+    }
+    if args.verify_tags && !stages.contains(&models::EnrichStage::Tags) {
+        stages.push(models::EnrichStage::Tags);
+    }
+    let (repos, rules) = match config {
+        Some(ref config) => {
+            let repos = config::apply_defaults(config)
+                .iter()
+                .filter_map(|repo| Some((repo.name.clone(), ngc_api::RepoEnrichment::from_config(repo)?)))
+                .collect();
+            let rules = (policy::PolicyEngine::new(&config.policies)?, policy::EndpointRules::new(&config.endpoint_rules)?);
+            (repos, Some(rules))
+        }
+        None => (HashMap::new(), None),
+    };
+    let settings = enrich_report::EnrichSettings {
+        api_key: Some(api_key),
+        repos,
+        stages,
+        budget: ngc_api::EnrichmentBudget::from_limits(args.enrichment_budget_secs, args.enrichment_max_requests),
+        batch_size: args.batch_size,
+    };
+
+    let mut job = enrich_report::EnrichJob::open(&args.report, &args.output, &settings.stages, args.resume)?;
+    info!("Enriching {} finding(s) from {}", job.pending(), args.report.display());
+    job.run(&settings, |key| ngc_api::NgcClient::new(key.to_string()))?;
+    let pending = job.pending();
+    let report = job.finish(|report, carried| {
+        if args.enrich_model_cards {
+            carried.model_cards.extend(ngc_api::fetch_model_cards(&[&report.source_code, &report.actions_workflow]));
+        }
+        if let Some((ref policy_engine, ref endpoint_rules)) = rules {
+            endpoint_rules.apply(&mut report.source_code);
+            endpoint_rules.apply(&mut report.actions_workflow);
+            policy_engine.apply(&mut report.source_code, "source_code");
+            policy_engine.apply(&mut report.actions_workflow, "actions_workflow");
+        }
+    })?;
+    info!("Enriched report written to {}", args.output.display());
+    if pending > 0 {
+        warn!("{} finding(s) not looked up within the budget; continue with --resume ({})",
+              pending, enrich_report::progress_path(&args.output).display());
+    }
+    report::print_summary(&report, None, args.verbose >= 2);
+    Ok(())
+}
+
+/// Run the init subcommand
+fn run_init(args: InitArgs) -> Result<()> {
+    init_logging(1);
+    
+    let discovered = match args.github_org {
+        Some(ref org) => {
+            let token = args.github_token.as_deref().filter(|t| !t.is_empty())
+                .context("--github-org needs a GitHub token (--github-token or GITHUB_TOKEN)")?;
+            git_ops::discover_org_repos(org, token)?
+        }
+        None => Vec::new(),
+    };
+    config::write_starter_config(&args.output, &discovered, args.force)?;
+    println!("Next: edit {}, then run: nim-usage-scanner config check {}",
+             args.output.display(), args.output.display());
+    Ok(())
+}
+
+/// Run the clean subcommand
+///
+/// Only checkouts recorded by a scan in the workdir are removed; anything else
+/// in it is left alone, and a directory without that record is refused.
+fn run_clean(args: CleanArgs) -> Result<()> {
+    init_logging(0);
+    
+    let removals = paths::workdir_removals(&args.workdir)?;
+    let size_mb = |bytes: u64| bytes as f64 / (1024.0 * 1024.0);
+    let mut total = 0;
+    for path in &removals {
+        let size = match std::fs::metadata(path) {
+            Ok(m) if m.is_dir() => git_ops::dir_size(path),
+            Ok(m) => m.len(),
+            Err(_) => 0,
+        };
+        total += size;
+        println!("{:>10.1} MB  {}", size_mb(size), path.display());
+    }
+    if !args.yes {
+        println!("Would remove {} entries ({:.1} MB); run again with --yes to delete them",
+                 removals.len(), size_mb(total));
+        return Ok(());
+    }
     
+    for path in &removals {
+        let removed = if path.is_dir() {
+            std::fs::remove_dir_all(path)
+        } else {
+            std::fs::remove_file(path)
+        };
+        removed.with_context(|| format!("Failed to remove {}", path.display()))?;
+    }
+    // The workdir itself goes too when nothing else was in it
+    if std::fs::read_dir(&args.workdir).is_ok_and(|mut entries| entries.next().is_none()) {
+        std::fs::remove_dir(&args.workdir)
+            .with_context(|| format!("Failed to remove {}", args.workdir.display()))?;
+    }
+    println!("Removed {} entries ({:.1} MB)", removals.len(), size_mb(total));
     Ok(())
 }
 
+/// Run verify-output
+fn run_verify_output(args: VerifyOutputArgs) -> Result<()> {
+    init_logging(0);
+    
+    if let Some(ref prefix) = args.output_prefix {
+        report::validate_output_prefix(prefix)?;
+    }
+    let public_key = args.public_key.as_deref().map(attest::load_verifying_key).transpose()?;
+    let manifest = report::ReportWriter::new(&args.dir, &[], report::CsvOptions::default())
+        .with_prefix(args.output_prefix.as_deref())
+        .artifact_path(attest::MANIFEST_FILE);
+    let verification = attest::verify_manifest(&manifest, public_key.as_ref())?;
+    println!("{}: {} file(s) match", manifest.display(), verification.verified);
+    if verification.signature_checked {
+        println!("{}: signature valid", attest::signature_path(&manifest).display());
+    }
+    Ok(())
+}
+
+/// Run config check
+fn run_config_check(args: ConfigCheckArgs) -> Result<()> {
+    init_logging(0);
+    println!("{}", config::check_config(&args.path)?);
+    Ok(())
+}
+
+/// Run report hotspots
+///
+/// The rollup is rebuilt from the findings, so reports written before it existed work too.
+fn run_report_hotspots(args: HotspotsArgs) -> Result<()> {
+    init_logging(0);
+    let scan = report::load_json_report(&args.report)?;
+    let hotspots = analysis::compute_hotspots(&scan.source_code, &scan.actions_workflow);
+    println!("{}", report::format_records(&report::hotspot_rows(&hotspots), args.format)?);
+    Ok(())
+}
+
+/// API key of a query subcommand: the key file's content when one is given, else --ngc-api-key / NVIDIA_API_KEY
+///
+/// Checked before any request, so an empty `NVIDIA_API_KEY=""` fails here
+/// instead of as a 401 after retries.
+fn query_api_key(key: Option<String>, key_file: Option<&Path>) -> Result<String> {
+    let key = match key_file {
+        Some(path) => {
+            let content = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read NGC API key file: {}", path.display()))?;
+            let key = content.trim_end_matches(['\n', '\r']).to_string();
+            ngc_api::check_api_key(&key).with_context(|| format!("Invalid NGC API key in {}", path.display()))?;
+            key
+        }
+        None => key.unwrap_or_default(),
+    };
+    ngc_api::check_api_key(&key)?;
+    Ok(key)
+}
+
 /// Run the query subcommand
 fn run_query(args: QueryArgs) -> Result<()> {
     match args.query_type {
         QueryType::HostedNim(hosted_args) => run_query_hosted_nim(hosted_args),
         QueryType::LocalNim(local_args) => run_query_local_nim(local_args),
+        QueryType::FunctionsByImage(image_args) => run_query_functions_by_image(image_args),
     }
 }
 
@@ -338,17 +1778,14 @@ fn run_query_hosted_nim(args: HostedNimQueryArgs) -> Result<()> {
     info!("Querying Hosted NIM information for model: {}", args.model);
     
     // Create NGC client
-    let mut client = ngc_api::NgcClient::new(args.ngc_api_key)
+    let api_key = query_api_key(args.ngc_api_key, args.ngc_api_key_file.as_deref())?;
+    let mut client = ngc_api::NgcClient::new(api_key)
         .context("Failed to create NGC client")?;
     
     // Query the model
     let result = client.query_hosted_nim(&args.model)?;
     
-    // Output as JSON
-    let json = serde_json::to_string_pretty(&result)
-        .context("Failed to serialize result to JSON")?;
-    
-    println!("{}", json);
+    println!("{}", report::format_records(&[result], args.format)?);
     
     Ok(())
 }
@@ -361,7 +1798,8 @@ fn run_query_local_nim(args: LocalNimQueryArgs) -> Result<()> {
     info!("Querying Local NIM information for image: {}", args.image);
     
     // Create NGC client
-    let mut client = ngc_api::NgcClient::new(args.ngc_api_key)
+    let api_key = query_api_key(args.ngc_api_key, args.ngc_api_key_file.as_deref())?;
+    let mut client = ngc_api::NgcClient::new(api_key)
         .context("Failed to create NGC client")?;
     
     // Build full image URL for query
@@ -372,13 +1810,784 @@ fn run_query_local_nim(args: LocalNimQueryArgs) -> Result<()> {
     };
     
     // Query the image
-    let result = client.query_local_nim(&image_url)?;
+    let mut result = client.query_local_nim(&image_url)?;
+    if args.tags {
+        result.tags = Some(client.list_tags(&image_url)?.to_vec());
+    }
+    
+    println!("{}", report::format_records(&[result], args.format)?);
+    
+    Ok(())
+}
+
+/// Find the functions running a container image
+fn run_query_functions_by_image(args: FunctionsByImageQueryArgs) -> Result<()> {
+    // Initialize logging
+    init_logging(args.verbose);
+    
+    // Create NGC client
+    let api_key = query_api_key(args.ngc_api_key, args.ngc_api_key_file.as_deref())?;
+    let mut client = ngc_api::NgcClient::new(api_key)
+        .context("Failed to create NGC client")?;
     
-    // Output as JSON
-    let json = serde_json::to_string_pretty(&result)
-        .context("Failed to serialize result to JSON")?;
+    let results = client.query_functions_by_image(&args.image, args.ignore_tag)?;
+    if results.is_empty() {
+        warn!("No functions found running image: {}", args.image);
+    }
     
-    println!("{}", json);
+    println!("{}", report::format_records(&results, args.format)?);
     
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_query_api_key() {
+        let temp_dir = TempDir::new().unwrap();
+        let key_file = temp_dir.path().join("ngc.key");
+        std::fs::write(&key_file, "nvapi-abc\n").unwrap();
+
+        assert_eq!(query_api_key(Some("nvapi-xyz".to_string()), None).unwrap(), "nvapi-xyz");
+        // The file wins over an empty NVIDIA_API_KEY
+        assert_eq!(query_api_key(Some(String::new()), Some(&key_file)).unwrap(), "nvapi-abc");
+
+        let err = query_api_key(Some(String::new()), None).unwrap_err();
+        assert!(format!("{:#}", err).contains("NGC API key is empty"), "{:#}", err);
+        assert!(query_api_key(Some("   ".to_string()), None).is_err());
+        assert!(query_api_key(None, None).is_err());
+        let err = query_api_key(Some("nvapi-abc\nnvapi-def".to_string()), None).unwrap_err();
+        assert!(format!("{:#}", err).contains("line break"), "{:#}", err);
+
+        std::fs::write(&key_file, "\n").unwrap();
+        let err = query_api_key(None, Some(&key_file)).unwrap_err();
+        assert!(format!("{:#}", err).contains("Invalid NGC API key in"), "{:#}", err);
+        assert!(query_api_key(None, Some(&temp_dir.path().join("missing"))).is_err());
+
+        // Either source satisfies the required key argument
+        let cli = Cli::try_parse_from([
+            "nim-usage-scanner", "query", "local-nim", "--image", "meta/llama3-8b-instruct",
+            "--ngc-api-key-file", "ngc.key",
+        ]);
+        assert!(cli.is_ok());
+    }
+
+    #[test]
+    fn test_run_scan_twice_with_different_jobs() {
+        test_log::install();
+        let temp_dir = TempDir::new().unwrap();
+        let config = temp_dir.path().join("repos.yaml");
+        std::fs::write(&config, "\
+version: \"1.0\"
+repos:
+  - name: test/repo
+    url: https://github.com/test/repo.git
+").unwrap();
+        let checkout = temp_dir.path().join("work").join("test_repo");
+        std::fs::create_dir_all(&checkout).unwrap();
+        std::fs::write(checkout.join("Dockerfile"), "FROM nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3.3\n").unwrap();
+
+        // Another component already owns the global pool
+        let _ = rayon::ThreadPoolBuilder::new().num_threads(1).build_global();
+
+        for jobs in ["1", "3"] {
+            let output = temp_dir.path().join(format!("out-{}", jobs));
+            let args = ScanArgs::parse_from([
+                "scan",
+                "--config", config.to_str().unwrap(),
+                "--output", output.to_str().unwrap(),
+                "--workdir", temp_dir.path().join("work").to_str().unwrap(),
+                "--no-clone",
+                "--ngc-api-key", "",
+                "--jobs", jobs,
+            ]);
+            run_scan(args).unwrap();
+
+            let report: serde_json::Value = serde_json::from_str(
+                &std::fs::read_to_string(output.join("report.json")).unwrap(),
+            ).unwrap();
+            assert_eq!(report["summary"]["total_local_nim"], 1);
+        }
+    }
+
+    #[test]
+    fn test_run_scan_output_prefix() {
+        test_log::install();
+        let temp_dir = TempDir::new().unwrap();
+        let config = temp_dir.path().join("repos.yaml");
+        std::fs::write(&config, "\
+version: \"1.0\"
+repos:
+  - name: test/repo
+    url: https://github.com/test/repo.git
+").unwrap();
+        let checkout = temp_dir.path().join("work").join("test_repo");
+        std::fs::create_dir_all(&checkout).unwrap();
+        std::fs::write(checkout.join("Dockerfile"), "FROM nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3.3\n").unwrap();
+
+        let output = temp_dir.path().join("out");
+        let args = ScanArgs::parse_from([
+            "scan",
+            "--config", config.to_str().unwrap(),
+            "--output", output.to_str().unwrap(),
+            "--workdir", temp_dir.path().join("work").to_str().unwrap(),
+            "--no-clone",
+            "--ngc-api-key", "",
+            "--output-prefix", "acme",
+            "--reports", "detail-json,detail-csv,detail-tsv,aggregate-json,aggregate-csv",
+        ]);
+        run_scan(args).unwrap();
+
+        let mut files: Vec<String> = std::fs::read_dir(&output)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        files.sort();
+        assert_eq!(files, vec![
+            "acme_report.csv",
+            "acme_report.json",
+            "acme_report.schema.json",
+            "acme_report.tsv",
+            "acme_report_aggregate.csv",
+            "acme_report_aggregate.json",
+            "acme_result.json",
+            "acme_status.json",
+        ]);
+        let schema: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string(output.join("acme_report.schema.json")).unwrap(),
+        ).unwrap();
+        assert_eq!(schema["file"], "acme_report.csv");
+        let status: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string(output.join("acme_status.json")).unwrap(),
+        ).unwrap();
+        assert_eq!(status["phase"], "done");
+        assert_eq!(status["repos_completed"], 1);
+        assert_eq!(status["findings"], 1);
+
+        let args = ScanArgs::parse_from([
+            "scan",
+            "--config", config.to_str().unwrap(),
+            "--output", output.to_str().unwrap(),
+            "--workdir", temp_dir.path().join("work").to_str().unwrap(),
+            "--no-clone",
+            "--output-prefix", "../acme",
+        ]);
+        assert!(run_scan(args).unwrap_err().to_string().contains("Output prefix"));
+    }
+
+    #[test]
+    fn test_run_scan_workflows_scope() {
+        test_log::install();
+        let temp_dir = TempDir::new().unwrap();
+        let config = temp_dir.path().join("repos.yaml");
+        std::fs::write(&config, "\
+version: \"1.0\"
+repos:
+  - name: test/repo
+    url: https://github.com/test/repo.git
+").unwrap();
+        let checkout = temp_dir.path().join("work").join("test_repo");
+        let image = "image: nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3.3\n";
+        for (path, content) in [
+            (".github/workflows/ci.yml", format!("on: push\njobs:\n  t:\n    container:\n      {}", image)),
+            ("deploy/values.yaml", image.to_string()),
+            ("Dockerfile", "FROM nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3.3\n".to_string()),
+        ] {
+            let path = checkout.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+
+        let output = temp_dir.path().join("out");
+        let args = ScanArgs::parse_from([
+            "scan",
+            "--config", config.to_str().unwrap(),
+            "--output", output.to_str().unwrap(),
+            "--workdir", temp_dir.path().join("work").to_str().unwrap(),
+            "--no-clone",
+            "--ngc-api-key", "",
+            "--scope", "ci",
+        ]);
+        run_scan(args).unwrap();
+
+        let report: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string(output.join("report.json")).unwrap(),
+        ).unwrap();
+        assert_eq!(report["scope"], "workflows");
+        assert_eq!(report["checkouts"][0]["repository"], "test/repo");
+        assert_eq!(report["checkouts"][0]["acquisition"], "local");
+        assert_eq!(report["scope_paths"][0], "/.github/");
+        // The root Dockerfile is outside the scope paths
+        assert_eq!(report["summary"]["actions_workflow"]["local_nim"], 1);
+        assert_eq!(report["summary"]["source_code"]["local_nim"], 1);
+        assert_eq!(report["source_code"]["local_nim"][0]["file_path"], "deploy/values.yaml");
+    }
+
+    #[test]
+    fn test_run_merge_reports() {
+        test_log::install();
+        let temp_dir = TempDir::new().unwrap();
+        let work = temp_dir.path().join("work");
+        let mut reports = Vec::new();
+        for (org, file) in [("blueprints", "Dockerfile"), ("partner", "compose.yaml")] {
+            let config = temp_dir.path().join(format!("{}.yaml", org));
+            std::fs::write(&config, format!("\
+version: \"1.0\"
+repos:
+  - name: {org}/app
+    url: https://github.com/{org}/app.git
+")).unwrap();
+            let checkout = work.join(format!("{}_app", org));
+            std::fs::create_dir_all(&checkout).unwrap();
+            std::fs::write(checkout.join(file), "image: nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3.3\n").unwrap();
+
+            let output = temp_dir.path().join(org);
+            let args = ScanArgs::parse_from([
+                "scan",
+                "--config", config.to_str().unwrap(),
+                "--output", output.to_str().unwrap(),
+                "--workdir", work.to_str().unwrap(),
+                "--no-clone",
+                "--ngc-api-key", "",
+            ]);
+            run_scan(args).unwrap();
+            reports.push(output.join("report.json"));
+        }
+
+        let output = temp_dir.path().join("merged");
+        let cli = Cli::parse_from([
+            "nim-usage-scanner", "merge-reports",
+            reports[0].to_str().unwrap(),
+            reports[1].to_str().unwrap(),
+            "--output", output.to_str().unwrap(),
+        ]);
+        let args = match cli.command {
+            Commands::MergeReports(args) => args,
+            other => panic!("unexpected command: {:?}", other),
+        };
+        run_merge_reports(args).unwrap();
+
+        let merged = report::load_json_report(&output.join("report.json")).unwrap();
+        assert_eq!(merged.total_repos, 2);
+        assert_eq!(merged.merged_from.len(), 2);
+        assert!(merged.repository_collisions.is_empty());
+        assert_eq!(merged.aggregated.local_nim.len(), 1);
+        assert_eq!(merged.aggregated.local_nim[0].locations.len(), 2);
+        let csv = std::fs::read_to_string(output.join("report.csv")).unwrap();
+        assert_eq!(csv.lines().count(), 3);
+
+        // A single report is not a merge
+        assert!(Cli::try_parse_from(["nim-usage-scanner", "merge-reports", reports[0].to_str().unwrap()]).is_err());
+    }
+
+    #[test]
+    fn test_run_scan_warnings_name_their_repository() {
+        test_log::install();
+        let temp_dir = TempDir::new().unwrap();
+        let config = temp_dir.path().join("repos.yaml");
+        std::fs::write(&config, "\
+version: \"1.0\"
+repos:
+  - name: logtest/alpha
+    url: https://github.com/logtest/alpha.git
+  - name: logtest/beta
+    url: https://github.com/logtest/beta.git
+").unwrap();
+        let work = temp_dir.path().join("work");
+        for repo in ["alpha", "beta"] {
+            let checkout = work.join(format!("logtest_{}", repo));
+            std::fs::create_dir_all(&checkout).unwrap();
+            // Not UTF-8, but it passes the prefilter
+            let mut broken = b"image: nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3.3\n".to_vec();
+            broken.extend([0xff, 0xfe, b'\n']);
+            std::fs::write(checkout.join(format!("{}-broken.yaml", repo)), broken).unwrap();
+            std::fs::write(
+                checkout.join(format!("{}-weights.json", repo)),
+                "version https://git-lfs.github.com/spec/v1\noid sha256:abc\nsize 12\n",
+            ).unwrap();
+        }
+
+        let args = ScanArgs::parse_from([
+            "scan",
+            "--config", config.to_str().unwrap(),
+            "--output", temp_dir.path().join("output").to_str().unwrap(),
+            "--workdir", work.to_str().unwrap(),
+            "--no-clone",
+            "--ngc-api-key", "",
+            "--jobs", "2",
+        ]);
+        run_scan(args).unwrap();
+
+        for repo in ["alpha", "beta"] {
+            let about_repo: Vec<String> = test_log::warnings()
+                .into_iter()
+                .filter(|w| w.contains(&format!("{}-", repo)))
+                .collect();
+            assert_eq!(about_repo.len(), 2, "{:?}", about_repo);
+            let prefix = format!("[logtest/{}] ", repo);
+            assert!(about_repo.iter().all(|w| w.starts_with(&prefix)), "{:?}", about_repo);
+        }
+    }
+
+    #[test]
+    fn test_run_scan_fail_on_empty() {
+        test_log::install();
+        let temp_dir = TempDir::new().unwrap();
+        let config = temp_dir.path().join("repos.yaml");
+        std::fs::write(&config, "\
+version: \"1.0\"
+repos:
+  - name: acme/app
+    url: https://github.com/acme/app.git
+").unwrap();
+        let work = temp_dir.path().join("work");
+        std::fs::create_dir_all(work.join("acme_app")).unwrap();
+        std::fs::write(work.join("acme_app/README.md"), "No NIMs here\n").unwrap();
+        let output = temp_dir.path().join("output");
+        let scan = |extra: &[&str]| {
+            let mut cli = vec![
+                "scan",
+                "--config", config.to_str().unwrap(),
+                "--output", output.to_str().unwrap(),
+                "--workdir", work.to_str().unwrap(),
+                "--no-clone",
+                "--ngc-api-key", "",
+            ];
+            cli.extend(extra);
+            let outcome = run_scan(ScanArgs::parse_from(cli));
+            let result = std::fs::read_to_string(output.join("result.json")).unwrap();
+            (outcome, serde_json::from_str::<models::RunResult>(&result).unwrap())
+        };
+
+        // An empty result is success by default
+        let (outcome, result) = scan(&[]);
+        assert!(outcome.is_ok());
+        assert_eq!(result.exit_code, 0);
+
+        let (outcome, result) = scan(&["--fail-on-empty"]);
+        let err = outcome.unwrap_err().to_string();
+        assert!(err.contains("no NIM findings in 1 repositories"), "{}", err);
+        assert_eq!(result.exit_code, 1);
+        assert_eq!(result.status, models::RunStatus::ChecksFailed);
+        // Reports are still written, with their usual shape
+        let report = std::fs::read_to_string(output.join("report.json")).unwrap();
+        assert!(report.contains("\"by_nim\": []"), "{}", report);
+
+        // --empty-ok wins over the configuration
+        std::fs::write(&config, format!("{}scan:\n  fail_on:\n    empty: true\n", std::fs::read_to_string(&config).unwrap())).unwrap();
+        assert_eq!(scan(&[]).1.status, models::RunStatus::ChecksFailed);
+        let (outcome, result) = scan(&["--empty-ok"]);
+        assert!(outcome.is_ok());
+        assert_eq!(result.status, models::RunStatus::Ok);
+    }
+
+    #[test]
+    fn test_run_scan_writes_result() {
+        test_log::install();
+        let temp_dir = TempDir::new().unwrap();
+        let config = temp_dir.path().join("repos.yaml");
+        std::fs::write(&config, "\
+version: \"1.0\"
+repos:
+  - name: acme/app
+    url: https://github.com/acme/app.git
+").unwrap();
+        let work = temp_dir.path().join("work");
+        let checkout = work.join("acme_app");
+        std::fs::create_dir_all(&checkout).unwrap();
+        std::fs::write(checkout.join("Dockerfile"), "FROM nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3.3\n").unwrap();
+        let output = temp_dir.path().join("output");
+        let scan = |extra: &[&str]| {
+            let mut cli = vec![
+                "scan",
+                "--config", config.to_str().unwrap(),
+                "--output", output.to_str().unwrap(),
+                "--workdir", work.to_str().unwrap(),
+                "--no-clone",
+                "--ngc-api-key", "",
+            ];
+            cli.extend(extra);
+            let outcome = run_scan(ScanArgs::parse_from(cli));
+            let result = std::fs::read_to_string(output.join("result.json")).unwrap();
+            (outcome, serde_json::from_str::<models::RunResult>(&result).unwrap())
+        };
+
+        let (outcome, result) = scan(&[]);
+        assert!(outcome.is_ok());
+        assert_eq!(result.exit_code, 0);
+        assert_eq!(result.status, models::RunStatus::Ok);
+        assert_eq!(result.error, None);
+        assert_eq!(result.repos, models::RunRepoCounts { configured: 1, cloned: 1, failed: 0, skipped_oversize: 0, scanned: 1 });
+        assert_eq!(result.findings, models::RunFindingCounts { local_nim: 1, hosted_nim: 0 });
+        assert!(result.artifacts.contains(&output.join("report.json").display().to_string()), "{:?}", result.artifacts);
+        assert!(!output.join(attest::MANIFEST_FILE).exists());
+
+        // The manifest covers the reports and result.json, but not itself
+        let (outcome, _) = scan(&["--manifest"]);
+        assert!(outcome.is_ok());
+        let manifest = std::fs::read_to_string(output.join(attest::MANIFEST_FILE)).unwrap();
+        let listed: Vec<&str> = manifest.lines().map(|l| l.split_once("  ").unwrap().1).collect();
+        assert_eq!(listed, vec![
+            "report.csv", "report.json", "report.schema.json", "report_aggregate.json", "result.json", "triage.json", "triage.md",
+        ]);
+        let verify = || match Cli::parse_from(["nim-usage-scanner", "verify-output", output.to_str().unwrap()]).command {
+            Commands::VerifyOutput(args) => run_verify_output(args),
+            other => panic!("unexpected command: {:?}", other),
+        };
+        verify().unwrap();
+        std::fs::write(output.join("report.csv"), "edited\n").unwrap();
+        assert!(verify().unwrap_err().to_string().contains("report.csv (modified)"));
+
+        // Reports are written, then a --fail-on condition fails the run
+        let (outcome, result) = scan(&["--fail-on-severity", "info"]);
+        assert!(outcome.is_err());
+        assert_eq!(result.exit_code, 1);
+        assert_eq!(result.status, models::RunStatus::ChecksFailed);
+        assert!(result.error.unwrap().contains("severity info or higher"));
+        assert_eq!(result.findings.local_nim, 1);
+
+        // Without any flag, an error-severity finding fails the run
+        let clean_config = std::fs::read_to_string(&config).unwrap();
+        std::fs::write(&config, format!("{}policies:\n  - name: no-llama\n    severity: error\n    when: \"image_url =~ llama\"\n", clean_config)).unwrap();
+        let (outcome, result) = scan(&[]);
+        assert!(outcome.is_err());
+        assert_eq!(result.status, models::RunStatus::ChecksFailed);
+        assert!(result.error.unwrap().contains("severity error or higher"));
+        assert!(scan(&["--no-fail-on-severity"]).0.is_ok());
+        std::fs::write(&config, clean_config).unwrap();
+
+        // A bad configuration stops the run before anything is scanned
+        std::fs::write(&config, "version: \"1.0\"\nrepos:\n  - name: acme/app\n    url: not-a-url\n").unwrap();
+        let (outcome, result) = scan(&[]);
+        assert!(outcome.is_err());
+        assert_eq!(result.exit_code, 1);
+        assert_eq!(result.status, models::RunStatus::Error);
+        let error = result.error.unwrap();
+        assert!(error.contains("Configuration validation failed") && error.contains("not-a-url"), "{}", error);
+        assert_eq!(result.repos, models::RunRepoCounts::default());
+        assert!(result.artifacts.is_empty());
+    }
+
+    #[test]
+    fn test_scan_panic_leaves_other_repos() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = temp_dir.path().join("repos.yaml");
+        std::fs::write(&config, format!("\
+version: \"1.0\"
+repos:
+  - name: acme/app
+    url: https://github.com/acme/app.git
+  - name: {}
+    url: https://github.com/test/panics-on-scan.git
+  - name: acme/api
+    url: https://github.com/acme/api.git
+", PANICKING_TEST_REPO)).unwrap();
+        let work = temp_dir.path().join("work");
+        for name in ["acme_app", "test_panics-on-scan", "acme_api"] {
+            let checkout = work.join(name);
+            std::fs::create_dir_all(&checkout).unwrap();
+            std::fs::write(checkout.join("Dockerfile"), "FROM nvcr.io/nim/meta/llama-3.1-8b-instruct:latest\n").unwrap();
+        }
+        let output = temp_dir.path().join("output");
+        let args = ScanArgs::parse_from([
+            "scan",
+            "--config", config.to_str().unwrap(),
+            "--output", output.to_str().unwrap(),
+            "--workdir", work.to_str().unwrap(),
+            "--no-clone",
+            "--ngc-api-key", "",
+        ]);
+        let mut run = models::RunResult::default();
+        let status = status::StatusReporter::new(output.join(status::STATUS_FILE));
+        scan_pipeline(args, &mut run, &telemetry::Telemetry::default(), &status).unwrap();
+        assert_eq!(run.repos.scanned, 2);
+
+        let report = report::load_json_report(&output.join("report.json")).unwrap();
+        let mut repositories: Vec<&str> = report.source_code.local_nim.iter().map(|m| m.repository.as_str()).collect();
+        repositories.sort();
+        assert_eq!(repositories, vec!["acme/api", "acme/app"]);
+        assert_eq!(report.panicked, vec![models::RepoPanic {
+            repository: PANICKING_TEST_REPO.to_string(),
+            stage: models::PanicStage::Scan,
+            message: format!("injected panic scanning {}", PANICKING_TEST_REPO),
+        }]);
+    }
+
+    #[test]
+    fn test_scan_sample() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = temp_dir.path().join("repos.yaml");
+        let work = temp_dir.path().join("work");
+        let mut yaml = "version: \"1.0\"\nrepos:\n".to_string();
+        for i in 0..6 {
+            yaml.push_str(&format!("  - name: acme/app{}\n    url: https://github.com/acme/app{}.git\n", i, i));
+            let checkout = work.join(format!("acme_app{}", i));
+            std::fs::create_dir_all(&checkout).unwrap();
+            std::fs::write(checkout.join("Dockerfile"), "FROM nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3.3\n").unwrap();
+        }
+        std::fs::write(&config, yaml).unwrap();
+        let output = temp_dir.path().join("output");
+        let scan = |seed: &str| {
+            let args = ScanArgs::parse_from([
+                "scan",
+                "--config", config.to_str().unwrap(),
+                "--output", output.to_str().unwrap(),
+                "--workdir", work.to_str().unwrap(),
+                "--no-clone",
+                "--ngc-api-key", "",
+                "--sample", "2",
+                "--sample-seed", seed,
+            ]);
+            let status = status::StatusReporter::new(output.join(status::STATUS_FILE));
+            let mut run = models::RunResult::default();
+            scan_pipeline(args, &mut run, &telemetry::Telemetry::default(), &status).unwrap();
+            (run, report::load_json_report(&output.join("report.json")).unwrap())
+        };
+
+        let (run, report) = scan("42");
+        assert_eq!((run.repos.configured, run.repos.scanned), (6, 2));
+        assert_eq!(report.total_repos, 2);
+        let sampling = report.sampling.unwrap();
+        assert_eq!((sampling.seed, sampling.population), (42, 6));
+        let expected: Vec<String> = sampling::select(6, 2, 42).iter().map(|i| format!("acme/app{}", i)).collect();
+        assert_eq!(sampling.repositories, expected);
+        // Only the sampled repositories have findings; the summary is scaled by 6 / 2
+        let mut found: Vec<String> = report.source_code.local_nim.iter().map(|m| m.repository.clone()).collect();
+        found.sort();
+        assert_eq!(found, expected);
+        assert_eq!(report.summary.total_local_nim, 2);
+        assert_eq!(sampling.estimated.total_local_nim, 6.0);
+        assert_eq!(sampling.estimated.repos_with_nim, 6.0);
+
+        assert_eq!(scan("42").1.sampling.unwrap().repositories, expected);
+    }
+
+    #[cfg(feature = "otel")]
+    #[test]
+    fn test_scan_pipeline_spans() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = temp_dir.path().join("repos.yaml");
+        std::fs::write(&config, "\
+version: \"1.0\"
+repos:
+  - name: acme/app
+    url: https://github.com/acme/app.git
+").unwrap();
+        let work = temp_dir.path().join("work");
+        let checkout = work.join("acme_app");
+        std::fs::create_dir_all(&checkout).unwrap();
+        std::fs::write(checkout.join("Dockerfile"), "FROM nvcr.io/nim/meta/llama-3.1-8b-instruct:latest\n").unwrap();
+        std::fs::write(checkout.join("README.md"), "No NIMs here\n").unwrap();
+        let output = temp_dir.path().join("output");
+        let args = ScanArgs::parse_from([
+            "scan",
+            "--config", config.to_str().unwrap(),
+            "--output", output.to_str().unwrap(),
+            "--workdir", work.to_str().unwrap(),
+            "--no-clone",
+            "--ngc-api-key", "",
+        ]);
+        let collected = telemetry::CollectedSpans::default();
+        let telemetry = collected.telemetry();
+        let status = status::StatusReporter::new(output.join(status::STATUS_FILE));
+        scan_pipeline(args, &mut models::RunResult::default(), &telemetry, &status).unwrap();
+
+        // Enrichment failures become events (no NGC key here, so mark one by hand)
+        let mut report = report::load_json_report(&output.join("report.json")).unwrap();
+        report.source_code.local_nim[0].enrichment_error = Some(models::EnrichmentError::NotFound);
+        let mut span = telemetry.span("enrich");
+        record_enrichment_failures(&mut span, &[&report.source_code, &report.actions_workflow]);
+        span.end();
+        telemetry.shutdown();
+
+        let spans = collected.spans();
+        let names: Vec<&str> = spans.iter().map(|s| s.name.as_ref()).collect();
+        assert_eq!(names, vec!["clone", "scan_repository", "scan", "enrich", "report", "scan", "enrich"]);
+        let attribute = |span: &opentelemetry_sdk::trace::SpanData, key: &str| {
+            span.attributes.iter().find(|kv| kv.key.as_str() == key).map(|kv| kv.value.to_string())
+        };
+        let root = &spans[5];
+        assert_eq!(attribute(root, "repos.configured").as_deref(), Some("1"));
+        for span in &spans[..5] {
+            assert_eq!(span.span_context.trace_id(), root.span_context.trace_id());
+        }
+        assert_eq!(spans[1].parent_span_id, spans[2].span_context.span_id());
+        assert_eq!(spans[2].parent_span_id, root.span_context.span_id());
+        assert_eq!(attribute(&spans[0], "repos.cloned").as_deref(), Some("1"));
+        let repo = &spans[1];
+        assert_eq!(attribute(repo, "repository").as_deref(), Some("acme/app"));
+        assert_eq!(attribute(repo, "files.scanned").as_deref(), Some("2"));
+        assert_eq!(attribute(repo, "findings.local_nim").as_deref(), Some("1"));
+        assert_eq!(attribute(repo, "findings.hosted_nim").as_deref(), Some("0"));
+        assert!(attribute(repo, "duration_ms").is_some());
+        assert_eq!(attribute(&spans[3], "findings.failed").as_deref(), Some("0"));
+        assert_eq!(attribute(&spans[4], "findings.local_nim").as_deref(), Some("1"));
+
+        let failures = &spans[6];
+        assert_eq!(attribute(failures, "findings.failed").as_deref(), Some("1"));
+        let event = &failures.events.events[0];
+        assert_eq!(event.name, "enrichment_failure");
+        let event_attribute = |key: &str| event.attributes.iter().find(|kv| kv.key.as_str() == key).map(|kv| kv.value.to_string());
+        assert_eq!(event_attribute("repository").as_deref(), Some("acme/app"));
+        assert_eq!(event_attribute("file").as_deref(), Some("Dockerfile"));
+        assert_eq!(event_attribute("error").as_deref(), Some("not_found"));
+    }
+
+    #[test]
+    fn test_run_clean() {
+        test_log::install();
+        let temp_dir = TempDir::new().unwrap();
+        let work = temp_dir.path().join("work");
+        for (path, content) in [
+            ("org_a/Dockerfile", "FROM scratch\n"),
+            ("org_b/.git/HEAD", "ref: refs/heads/main\n"),
+            ("org_b.partial-77/README.md", "partial\n"),
+        ] {
+            let path = work.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        let clean = |workdir: &std::path::Path, yes: bool| {
+            let mut argv = vec!["nim-usage-scanner", "clean", "--workdir", workdir.to_str().unwrap()];
+            if yes {
+                argv.push("--yes");
+            }
+            match Cli::parse_from(argv).command {
+                Commands::Clean(args) => run_clean(args),
+                other => panic!("unexpected command: {:?}", other),
+            }
+        };
+
+        // Not recorded by a scan (e.g. pre-provisioned): refused, even with --yes
+        assert!(clean(&work, true).unwrap_err().to_string().contains("Refusing to clean"));
+        assert!(work.join("org_a").exists());
+
+        paths::record_checkouts(&work, &[work.join("org_a"), work.join("org_b")]).unwrap();
+        std::fs::write(work.join("notes.txt"), "mine\n").unwrap();
+
+        // Dry run by default
+        clean(&work, false).unwrap();
+        assert!(work.join("org_a").exists() && work.join(paths::WORKDIR_MARKER).exists());
+
+        // Checkouts, partial clones and the record go; other files stay
+        clean(&work, true).unwrap();
+        let mut left: Vec<_> = std::fs::read_dir(&work).unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect();
+        left.sort();
+        assert_eq!(left, vec!["notes.txt"]);
+
+        // An emptied workdir is removed as well
+        std::fs::remove_file(work.join("notes.txt")).unwrap();
+        std::fs::create_dir(work.join("org_a")).unwrap();
+        paths::record_checkouts(&work, &[work.join("org_a")]).unwrap();
+        clean(&work, true).unwrap();
+        assert!(!work.exists());
+    }
+
+    #[test]
+    fn test_effective_options_precedence() {
+        use models::{EnrichStage, FailOn, ScanScope, ScanSettings, Severity};
+        use report::ReportArtifact;
+
+        let file = ScanSettings {
+            output_formats: Some(vec![ReportArtifact::AggregateCsv]),
+            enrich: Some(vec![EnrichStage::Local]),
+            capture_call_params: Some(true),
+            scope: Some(ScanScope::Workflows),
+            max_depth: Some(10),
+            max_files_per_repo: Some(0),
+            max_context_chars: Some(500),
+            generated_files: vec!["*.gen.py".to_string()],
+            bare_models: vec![" Nemo-Custom-7B ".to_string()],
+            test_fixture_globs: Some(Vec::new()),
+            skip_docs: Some(true),
+            hosted_hosts: vec![models::HostedHostEntry::Host("api.nvidia.eu".to_string())],
+            triage_weights: Some(models::TriageWeights { stale_tag: 0, ..Default::default() }),
+            fail_on: FailOn { severity: Some(Severity::Warning), staging_in_source: Some(true), policy_violations: None, empty: Some(true) },
+        };
+        let resolve = |cli: &[&str], file: &ScanSettings| {
+            let args = ScanArgs::parse_from(["scan", "--config", "repos.yaml"].iter().chain(cli));
+            EffectiveOptions::resolve(args, file)
+        };
+
+        // Built-in defaults
+        let opts = resolve(&[], &ScanSettings::default()).unwrap();
+        assert_eq!(opts.reports, report::ReportArtifact::DEFAULT_SET.to_vec());
+        assert_eq!(opts.enrich, EnrichStage::DEFAULT_SET.to_vec());
+        assert!(!opts.capture_call_params);
+        assert_eq!(opts.scope, ScanScope::Full);
+        assert_eq!(opts.max_depth, Some(scanner::DEFAULT_MAX_DEPTH));
+        assert_eq!(opts.max_files_per_repo, Some(scanner::DEFAULT_MAX_FILES_PER_REPO));
+        assert_eq!(opts.max_context_chars, Some(scanner::DEFAULT_MAX_CONTEXT_CHARS));
+        assert_eq!(opts.fail_on_severity, Some(Severity::Error));
+        assert!(!opts.fail_on_staging_in_source);
+        assert!(!opts.fail_on_empty);
+        assert_eq!(opts.test_fixture_globs, vec!["tests/fixtures/**", "**/testdata/**"]);
+        assert!(!opts.skip_docs);
+
+        // Config over defaults
+        let opts = resolve(&[], &file).unwrap();
+        assert_eq!(opts.reports, vec![ReportArtifact::AggregateCsv]);
+        assert_eq!(opts.enrich, vec![EnrichStage::Local]);
+        assert!(opts.capture_call_params);
+        assert_eq!(opts.scope, ScanScope::Workflows);
+        assert_eq!(opts.max_depth, Some(10));
+        assert_eq!(opts.max_files_per_repo, None);
+        assert_eq!(opts.max_context_chars, Some(500));
+        assert_eq!(opts.generated_files, vec!["*.gen.py"]);
+        assert_eq!(opts.bare_models, vec!["nemo-custom-7b"]);
+        assert!(opts.test_fixture_globs.is_empty());
+        assert!(opts.skip_docs);
+        assert_eq!(opts.triage_weights.stale_tag, 0);
+        assert_eq!(opts.fail_on_severity, Some(Severity::Warning));
+        assert!(opts.fail_on_staging_in_source);
+        assert!(opts.fail_on_empty);
+
+        // Command line over config
+        let opts = resolve(&[
+            "--reports", "detail-json", "--tsv",
+            "--enrich", "hosted", "--enrich-image-meta", "--verify-tags", "--enrich-model-cards",
+            "--scope", "full",
+            "--max-depth", "0",
+            "--max-files-per-repo", "50",
+            "--max-context-chars", "0",
+            "--fail-on-severity", "error",
+            "--empty-ok",
+            "--extra-hosted-host", "api.stg.nvidia.eu=staging",
+        ], &file).unwrap();
+        assert_eq!(opts.reports, vec![ReportArtifact::DetailJson, ReportArtifact::DetailTsv]);
+        assert_eq!(opts.enrich, vec![EnrichStage::Hosted, EnrichStage::ImageMeta, EnrichStage::Tags]);
+        assert!(opts.enrich_model_cards);
+        assert_eq!(opts.scope, ScanScope::Full);
+        assert_eq!(opts.max_depth, None);
+        assert_eq!(opts.max_files_per_repo, Some(50));
+        assert_eq!(opts.max_context_chars, None);
+        assert_eq!(opts.fail_on_severity, Some(Severity::Error));
+        assert!(!opts.fail_on_empty);
+        assert!(ScanArgs::try_parse_from(["scan", "--fail-on-empty", "--empty-ok"]).is_err());
+        // The severity gate is on unless turned off, also over the configuration
+        assert_eq!(resolve(&["--no-fail-on-severity"], &file).unwrap().fail_on_severity, None);
+        assert!(ScanArgs::try_parse_from(["scan", "--fail-on-severity", "error", "--no-fail-on-severity"]).is_err());
+        // Hosts of the command line add to the configured ones
+        let environment = |url: &str| opts.hosted_hosts.environment(Some(url), None);
+        assert_eq!(environment("https://api.stg.nvidia.eu/v1"), models::Environment::Staging);
+        assert_eq!(environment("https://api.nvidia.eu/v1"), models::Environment::Production);
+        for bad in ["https://api.nvidia.eu", "api.nvidia.eu=prod", "localhost", "-bad.example.com", "api..nvidia.eu"] {
+            let err = resolve(&[&format!("--extra-hosted-host={}", bad)], &file).err().unwrap();
+            assert!(err.to_string().contains(bad.split('=').next().unwrap()), "{}", err);
+        }
+
+        // A config-only policy_violations still needs a policy file
+        let file = ScanSettings {
+            fail_on: FailOn { policy_violations: Some(true), ..FailOn::default() },
+            ..ScanSettings::default()
+        };
+        let err = resolve(&[], &file).err().unwrap();
+        assert!(err.to_string().contains("needs --model-policy"), "{}", err);
+        assert!(resolve(&["--model-policy", "policy.yaml"], &file).unwrap().fail_on_policy_violations);
+    }
+}
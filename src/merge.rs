@@ -0,0 +1,439 @@
+//! Combining report.json files from separate scans
+//!
+//! Findings from every input are concatenated and the aggregated view, summary,
+//! version skew and detector statistics are recomputed over them, so a NIM found
+//! by two scans becomes one aggregated entry with the locations of both.
+//! Enrichment results recorded in the inputs (image metadata, function
+//! resolution, possible retirements) are carried over.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::PathBuf;
+use anyhow::{bail, Result};
+use log::warn;
+
+use crate::models::{
+    LocalImageMeta, MergedInput, ModelCard, NimFindings, Resolution, ResolutionCounts, ScanReport, ScanScope,
+};
+use crate::report::REPORT_SCHEMA_VERSION;
+use crate::scanner;
+
+// ============================================================================
+// Schema Version Check
+// ============================================================================
+
+/// Parse a `MAJOR.MINOR` schema version
+fn parse_schema_version(version: &str) -> Option<(u32, u32)> {
+    let (major, minor) = version.split_once('.')?;
+    Some((major.parse().ok()?, minor.parse().ok()?))
+}
+
+/// Check that a report can be read by this scanner without losing fields
+///
+/// Its major version must match REPORT_SCHEMA_VERSION and its minor version
+/// must not be newer (fields this scanner doesn't know would be dropped).
+pub fn check_schema_version(report: &ScanReport, path: &str) -> Result<()> {
+    let (major, minor) = parse_schema_version(REPORT_SCHEMA_VERSION)
+        .expect("REPORT_SCHEMA_VERSION is MAJOR.MINOR");
+    if report.schema_version.is_empty() {
+        bail!("{} has no schema_version (written by an older scanner); re-run the scan", path);
+    }
+    match parse_schema_version(&report.schema_version) {
+        Some((m, n)) if m == major && n <= minor => Ok(()),
+        Some((m, _)) if m == major => bail!(
+            "{} has schema_version {}, newer than this scanner's {}; upgrade the scanner",
+            path, report.schema_version, REPORT_SCHEMA_VERSION
+        ),
+        _ => bail!(
+            "{} has schema_version {}, incompatible with this scanner's {}",
+            path, report.schema_version, REPORT_SCHEMA_VERSION
+        ),
+    }
+}
+
+// ============================================================================
+// Merging
+// ============================================================================
+
+/// Repository names a report covers: from its findings, checkouts and truncations
+fn report_repositories(report: &ScanReport) -> BTreeSet<&str> {
+    let findings = [&report.source_code, &report.actions_workflow];
+    findings.iter().flat_map(|f| f.local_nim.iter().map(|m| m.repository.as_str()))
+        .chain(findings.iter().flat_map(|f| f.hosted_nim.iter().map(|m| m.repository.as_str())))
+        .chain(report.checkouts.iter().map(|c| c.repository.as_str()))
+        .chain(report.truncated.iter().map(|t| t.repository.as_str()))
+        .collect()
+}
+
+/// Order for keeping the worse of two resolutions: unresolved in any input stays unresolved
+fn resolution_rank(resolution: Resolution) -> u8 {
+    match resolution {
+        Resolution::Resolved => 0,
+        Resolution::NeverResolved => 1,
+        Resolution::NewlyUnresolved => 2,
+    }
+}
+
+/// Enrichment results kept in reports' aggregated views, carried into a report
+/// whose aggregated view is recomputed
+#[derive(Debug, Default)]
+pub struct CarriedEnrichment {
+    /// Registry metadata per Local NIM image
+    pub image_meta: HashMap<String, LocalImageMeta>,
+    /// Model card per Hosted NIM model
+    pub model_cards: HashMap<String, ModelCard>,
+    /// Function resolution per aggregation key
+    resolutions: HashMap<String, Resolution>,
+}
+
+impl CarriedEnrichment {
+    /// Add what `report` records; the first report to record an image or model
+    /// wins, and a model unresolved in any report stays unresolved
+    pub fn collect(&mut self, report: &ScanReport) {
+        for entry in &report.aggregated.local_nim {
+            if entry.display_name.is_some() || entry.publisher.is_some() {
+                self.image_meta.entry(entry.image_url.clone()).or_insert_with(|| LocalImageMeta {
+                    display_name: entry.display_name.clone(),
+                    publisher: entry.publisher.clone(),
+                });
+            }
+        }
+        for entry in &report.aggregated.hosted_nim {
+            let has_card = entry.publisher.is_some() || !entry.modalities.is_empty() || entry.max_context.is_some();
+            if let (Some(model), true) = (&entry.model_name, has_card) {
+                self.model_cards.entry(model.clone()).or_insert_with(|| ModelCard {
+                    publisher: entry.publisher.clone(),
+                    modalities: entry.modalities.clone(),
+                    max_context: entry.max_context,
+                });
+            }
+            if let (Some(key), Some(resolution)) = (entry.aggregation_key(), entry.resolution) {
+                let merged = self.resolutions.entry(key.to_string()).or_insert(resolution);
+                if resolution_rank(resolution) > resolution_rank(*merged) {
+                    *merged = resolution;
+                }
+            }
+        }
+    }
+
+    /// Set the carried results on `report`'s aggregated view and count them in its summary
+    pub fn apply(&self, report: &mut ScanReport) {
+        report.aggregated.apply_image_meta(&self.image_meta);
+        report.aggregated.apply_model_cards(&self.model_cards);
+        report.summary.modality = report.aggregated.modality_counts();
+
+        let mut counts = ResolutionCounts::default();
+        for entry in &mut report.aggregated.hosted_nim {
+            entry.resolution = entry.aggregation_key().and_then(|key| self.resolutions.get(key).copied());
+            match entry.resolution {
+                Some(Resolution::Resolved) => counts.resolved += 1,
+                Some(Resolution::NeverResolved) => counts.never_resolved += 1,
+                Some(Resolution::NewlyUnresolved) => counts.newly_unresolved += 1,
+                None => {}
+            }
+        }
+        report.summary.resolution = counts;
+    }
+}
+
+/// Merge reports (each with the path it was loaded from) into one
+///
+/// Repositories present in more than one input are listed in
+/// `repository_collisions` and counted once; their duplicate findings
+/// (same file, line and model) are dropped.
+pub fn merge_reports(inputs: Vec<(PathBuf, ScanReport)>) -> Result<ScanReport> {
+    if inputs.len() < 2 {
+        bail!("merge-reports needs at least two reports");
+    }
+    for (path, report) in &inputs {
+        check_schema_version(report, &path.display().to_string())?;
+        if let Some(ref sampling) = report.sampling {
+            warn!("{} covers a sample of {} of {} repositories; its estimates are not carried into the merged report",
+                  path.display(), sampling.repositories.len(), sampling.population);
+        }
+    }
+
+    // Repository names seen in more than one input
+    let mut seen_in: BTreeMap<&str, usize> = BTreeMap::new();
+    for (_, report) in &inputs {
+        for repo in report_repositories(report) {
+            *seen_in.entry(repo).or_default() += 1;
+        }
+    }
+    let collisions: Vec<String> = seen_in.iter()
+        .filter(|(_, count)| **count > 1)
+        .map(|(repo, _)| repo.to_string())
+        .collect();
+    let duplicate_repos: usize = seen_in.values().map(|count| count - 1).sum();
+    for repo in &collisions {
+        warn!("Repository {} appears in more than one report; its findings are merged", repo);
+    }
+
+    let mut source_code = NimFindings::new();
+    let mut actions_workflow = NimFindings::new();
+    let mut carried = CarriedEnrichment::default();
+    let mut merged_from = Vec::new();
+    let mut total_repos = 0;
+    let mut scopes = Vec::new();
+    let mut scope_paths = BTreeSet::new();
+    let mut truncated = Vec::new();
+    let mut lfs_pointers = Vec::new();
+    let mut listed_file_errors = Vec::new();
+    let mut checkouts = Vec::new();
+    let mut retirements = Vec::new();
+    let mut metadata_hints = Vec::new();
+    let mut panicked = Vec::new();
+
+    for (path, report) in inputs {
+        merged_from.push(MergedInput {
+            report: path.display().to_string(),
+            scan_time: report.scan_time.clone(),
+            schema_version: report.schema_version.clone(),
+            total_repos: report.total_repos,
+        });
+        total_repos += report.total_repos;
+        carried.collect(&report);
+
+        scopes.push(report.scope);
+        scope_paths.extend(report.scope_paths);
+        truncated.extend(report.truncated);
+        lfs_pointers.extend(report.lfs_pointers);
+        listed_file_errors.extend(report.listed_file_errors);
+        checkouts.extend(report.checkouts);
+        retirements.extend(report.possible_retirements);
+        metadata_hints.extend(report.metadata_hints);
+        panicked.extend(report.panicked);
+        source_code.local_nim.extend(report.source_code.local_nim);
+        source_code.hosted_nim.extend(report.source_code.hosted_nim);
+        actions_workflow.local_nim.extend(report.actions_workflow.local_nim);
+        actions_workflow.hosted_nim.extend(report.actions_workflow.hosted_nim);
+        source_code.sdk_dependencies.extend(report.source_code.sdk_dependencies);
+        actions_workflow.sdk_dependencies.extend(report.actions_workflow.sdk_dependencies);
+    }
+
+    // A colliding repository scanned by two inputs yields the same findings twice
+    scanner::deduplicate_results(&mut source_code);
+    scanner::deduplicate_results(&mut actions_workflow);
+
+    let mut merged = ScanReport::new(total_repos.saturating_sub(duplicate_repos), source_code, actions_workflow);
+    carried.apply(&mut merged);
+    // One entry per model, with the locations of every input
+    retirements.sort_by(|a, b| a.model.cmp(&b.model));
+    retirements.dedup_by(|a, b| a.model == b.model);
+    for retirement in &mut retirements {
+        if let Some(entry) = merged.aggregated.hosted_nim.iter()
+            .find(|e| e.aggregation_key() == Some(retirement.model.as_str()))
+        {
+            retirement.locations = entry.locations.clone();
+        }
+    }
+    merged.possible_retirements = retirements;
+
+    // Mixed scopes: the merged numbers don't cover every repository in full
+    if scopes.iter().any(|scope| *scope != scopes[0]) {
+        warn!("Merging reports scanned with different --scope values; the merged report is marked as workflows-only");
+    }
+    merged.scope = if scopes.contains(&ScanScope::Workflows) { ScanScope::Workflows } else { ScanScope::Full };
+    merged.scope_paths = scope_paths.into_iter().collect();
+    merged.truncated = truncated;
+    merged.lfs_pointers = lfs_pointers;
+    merged.listed_file_errors = listed_file_errors;
+    merged.checkouts = checkouts;
+    merged.merged_from = merged_from;
+    merged.repository_collisions = collisions;
+    merged.metadata_hints = metadata_hints;
+    merged.panicked = panicked;
+    Ok(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Environment, FileKind, Framework, HostedNimMatch, InvokeStyle, LocalIntent, LocalNimMatch, RepoCheckout, Acquisition, UsageKind};
+
+    fn local(repository: &str, image: &str, file_path: &str) -> LocalNimMatch {
+        LocalNimMatch {
+            repository: repository.to_string(),
+            image_url: format!("nvcr.io/nim/{}", image),
+            tag: "1.3.0".to_string(),
+            resolved_tag: None,
+            tag_exists: None,
+            file_path: file_path.to_string(),
+            line_number: 1,
+            match_context: String::new(),
+            policy: None,
+            enrichment_error: None,
+            enriched: false,
+            detection_rule: "local_nim_full".to_string(),
+            workflow_triggers: Vec::new(),
+            nim_identity: String::new(),
+            enrichment_skipped: None,
+            context_truncated: None,
+            compose: None,
+            intent: LocalIntent::Reference,
+            usage_kind: UsageKind::Other,
+            blame: None,
+            notebook_cell: None,
+            file_kind: FileKind::Source,
+            resolved_from: None,
+            fixture: false,
+            fingerprint: String::new(),
+        }
+    }
+
+    fn hosted(repository: &str, model: &str) -> HostedNimMatch {
+        HostedNimMatch {
+            repository: repository.to_string(),
+            endpoint_url: None,
+            model_name: Some(model.to_string()),
+            file_path: "client.py".to_string(),
+            line_number: 3,
+            match_context: String::new(),
+            function_id: None,
+            status: None,
+            container_image: None,
+            environment: Environment::Production,
+            endpoint_host: None,
+            invoke_style: InvokeStyle::Unknown,
+            framework: Framework::Unknown,
+            call_params: None,
+            policy: None,
+            model_policy: None,
+            enrichment_error: None,
+            enriched: false,
+            detection_rule: "model_assign".to_string(),
+            workflow_triggers: Vec::new(),
+            nim_identity: None,
+            enrichment_skipped: None,
+            browser_exposed: false,
+            did_you_mean: Vec::new(),
+            credential_source: None,
+            context_truncated: None,
+            canonical_endpoint: None,
+            endpoint_mismatch: false,
+            endpoint_status: None,
+            suggested_endpoint: None,
+            blame: None,
+            notebook_cell: None,
+            file_kind: FileKind::Source,
+            resolved_from: None,
+            fixture: false,
+            fingerprint: String::new(),
+        }
+    }
+
+    fn checkout(repository: &str) -> RepoCheckout {
+        RepoCheckout {
+            repository: repository.to_string(),
+            acquisition: Acquisition::Git,
+            commit_sha: None,
+            scan_ms: None,
+            reset: None,
+            stale: None,
+        }
+    }
+
+    #[test]
+    fn test_check_schema_version() {
+        let mut report = ScanReport::new(0, NimFindings::new(), NimFindings::new());
+        assert!(check_schema_version(&report, "a.json").is_ok());
+
+        let (major, minor) = parse_schema_version(REPORT_SCHEMA_VERSION).unwrap();
+        report.schema_version = format!("{}.0", major);
+        assert!(check_schema_version(&report, "a.json").is_ok());
+
+        for version in ["", "x", &format!("{}.{}", major + 1, minor), &format!("{}.{}", major, minor + 1)] {
+            report.schema_version = version.to_string();
+            assert!(check_schema_version(&report, "a.json").is_err(), "{}", version);
+        }
+    }
+
+    #[test]
+    fn test_merge_reports() {
+        let image = "meta/llama-3.1-8b-instruct";
+        let mut blueprints = ScanReport::new(
+            2,
+            NimFindings {
+                local_nim: vec![local("blueprints/rag", image, "deploy/compose.yaml")],
+                hosted_nim: vec![hosted("blueprints/rag", "nvidia/nv-embedqa-e5-v5")],
+                sdk_dependencies: vec![],
+            },
+            NimFindings::new(),
+        );
+        blueprints.aggregated.local_nim[0].display_name = Some("Llama 3.1 8B Instruct".to_string());
+        blueprints.aggregated.hosted_nim[0].resolution = Some(Resolution::Resolved);
+        blueprints.checkouts = vec![checkout("blueprints/rag"), checkout("shared/tools")];
+
+        let mut partners = ScanReport::new(
+            2,
+            NimFindings {
+                local_nim: vec![local("partner/agent", image, "helm/values.yaml")],
+                hosted_nim: vec![hosted("shared/tools", "nvidia/nv-embedqa-e5-v5")],
+                sdk_dependencies: vec![],
+            },
+            NimFindings {
+                local_nim: vec![],
+                hosted_nim: vec![hosted("partner/agent", "meta/llama-3.3-70b-instruct")],
+                sdk_dependencies: vec![],
+            },
+        );
+        for entry in &mut partners.aggregated.hosted_nim {
+            if entry.model_name.as_deref() == Some("nvidia/nv-embedqa-e5-v5") {
+                entry.resolution = Some(Resolution::NeverResolved);
+            }
+        }
+        partners.checkouts = vec![checkout("partner/agent"), checkout("shared/tools")];
+
+        let merged = merge_reports(vec![
+            (PathBuf::from("blueprints/report.json"), blueprints),
+            (PathBuf::from("partners/report.json"), partners),
+        ]).unwrap();
+
+        // The overlapping image is re-aggregated into one entry with locations from both inputs
+        assert_eq!(merged.aggregated.local_nim.len(), 1);
+        let entry = &merged.aggregated.local_nim[0];
+        assert_eq!(entry.image_url, format!("nvcr.io/nim/{}", image));
+        let locations: Vec<(&str, &str)> = entry.locations.iter()
+            .map(|l| (l.repository.as_str(), l.file_path.as_str()))
+            .collect();
+        assert_eq!(locations.len(), 2);
+        assert!(locations.contains(&("blueprints/rag", "deploy/compose.yaml")));
+        assert!(locations.contains(&("partner/agent", "helm/values.yaml")));
+        assert_eq!(entry.display_name.as_deref(), Some("Llama 3.1 8B Instruct"));
+
+        // Summary is recomputed over the combined findings
+        assert_eq!(merged.summary.total_local_nim, 2);
+        assert_eq!(merged.summary.total_hosted_nim, 3);
+        assert_eq!(merged.summary.repos_with_nim, 3);
+        assert_eq!(merged.aggregated.hosted_nim.len(), 2);
+
+        // shared/tools was scanned by both: reported once, and counted once
+        assert_eq!(merged.repository_collisions, vec!["shared/tools"]);
+        assert_eq!(merged.total_repos, 3);
+        assert_eq!(merged.checkouts.len(), 4);
+
+        // The worse resolution wins for a model both inputs looked up
+        let embed = merged.aggregated.hosted_nim.iter()
+            .find(|e| e.model_name.as_deref() == Some("nvidia/nv-embedqa-e5-v5"))
+            .unwrap();
+        assert_eq!(embed.resolution, Some(Resolution::NeverResolved));
+        assert_eq!(merged.summary.resolution.never_resolved, 1);
+
+        assert_eq!(merged.schema_version, REPORT_SCHEMA_VERSION);
+        assert_eq!(merged.merged_from.iter().map(|i| i.report.as_str()).collect::<Vec<_>>(),
+                   vec!["blueprints/report.json", "partners/report.json"]);
+    }
+
+    #[test]
+    fn test_merge_reports_rejects_unversioned_input() {
+        let mut old = ScanReport::new(1, NimFindings::new(), NimFindings::new());
+        old.schema_version = String::new();
+        let current = ScanReport::new(1, NimFindings::new(), NimFindings::new());
+
+        let err = merge_reports(vec![
+            (PathBuf::from("old.json"), old),
+            (PathBuf::from("new.json"), current.clone()),
+        ]).unwrap_err();
+        assert!(err.to_string().contains("old.json has no schema_version"));
+        assert!(merge_reports(vec![(PathBuf::from("new.json"), current)]).is_err());
+    }
+}
@@ -3,6 +3,7 @@
 //! This module defines all data structures used throughout the scanner,
 //! including configuration, scan results, and API responses.
 
+use std::collections::{BTreeMap, HashMap};
 use serde::{Deserialize, Serialize};
 
 // ============================================================================
@@ -34,6 +35,110 @@ pub struct Config {
     pub defaults: Defaults,
     /// List of repositories to scan
     pub repos: Vec<RepoConfig>,
+    /// Policy rules evaluated before the built-in defaults (first match wins)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub policies: Vec<PolicyRule>,
+    /// Endpoint path rules checked before the built-in table (first match wins)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub endpoint_rules: Vec<EndpointRule>,
+    /// Scan options; the matching command-line flags take precedence
+    #[serde(default, skip_serializing_if = "ScanSettings::is_empty")]
+    pub scan: ScanSettings,
+}
+
+/// A policy rule from repos.yaml
+///
+/// ```yaml
+/// policies:
+///   - name: no-latest-in-workflows
+///     severity: error
+///     when: "nim_type == local_nim && source_type == actions_workflow && tag == latest"
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyRule {
+    /// Rule name recorded on matching findings
+    pub name: String,
+    /// Severity assigned to matching findings
+    pub severity: Severity,
+    /// Condition: `field == value`, `field != value` or `field =~ regex`, joined with `&&`
+    /// (empty matches every finding)
+    #[serde(default)]
+    pub when: String,
+}
+
+/// Status of a Hosted NIM endpoint path in the NVIDIA API
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EndpointStatus {
+    /// A path the API serves today
+    Current,
+    /// Still served, but superseded by another path
+    Deprecated,
+    /// No longer served
+    Removed,
+}
+
+impl EndpointStatus {
+    /// snake_case name as used in reports
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EndpointStatus::Current => "current",
+            EndpointStatus::Deprecated => "deprecated",
+            EndpointStatus::Removed => "removed",
+        }
+    }
+}
+
+/// An endpoint path rule from repos.yaml
+///
+/// ```yaml
+/// endpoint_rules:
+///   - path: "^/v1/completions$"
+///     model: "(?i)instruct|chat"
+///     status: deprecated
+///     suggested: /v1/chat/completions
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EndpointRule {
+    /// Regex matched against the path of the endpoint URL (without query)
+    pub path: String,
+    /// Regex the finding's model name must match too (findings without a model never match)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    pub status: EndpointStatus,
+    /// Path to use instead; `$1`, `$2`... are groups captured by `path`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggested: Option<String>,
+}
+
+/// Approved/forbidden Hosted NIM models (--model-policy)
+///
+/// ```yaml
+/// allowed:
+///   - "meta/llama-3.*"
+///   - "/^nvidia/nv-embed/"
+/// denied:
+///   - "meta/llama-3.1-405b-instruct"
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ModelPolicyFile {
+    /// Approved model patterns; when non-empty, any other model is a violation
+    #[serde(default)]
+    pub allowed: Vec<String>,
+    /// Forbidden model patterns (take precedence over `allowed`)
+    #[serde(default)]
+    pub denied: Vec<String>,
+}
+
+/// A Hosted NIM finding whose model breaks the model policy
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelPolicyViolation {
+    /// Always true; lets report consumers filter on a single field
+    pub policy_violation: bool,
+    /// The denied pattern that matched, or `not-allowed` when no allowed pattern matched
+    pub policy_violation_rule: String,
 }
 
 /// Default configuration values
@@ -45,6 +150,15 @@ pub struct Defaults {
     /// Default clone depth
     #[serde(default = "default_depth")]
     pub depth: u32,
+    /// Replacement for the built-in skip-directory list (None = built-in list)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skip_dirs: Option<Vec<String>>,
+    /// Directory names to skip in addition to the base list
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skip_dirs_add: Vec<String>,
+    /// Directory names to scan even though the base list skips them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skip_dirs_remove: Vec<String>,
 }
 
 fn default_branch() -> String {
@@ -55,6 +169,178 @@ fn default_depth() -> u32 {
     1
 }
 
+/// Scan options from repos.yaml; unset fields fall back to the built-in defaults
+///
+/// ```yaml
+/// scan:
+///   output_formats: [detail-json, aggregate-csv]
+///   enrich: [local, hosted, image-meta]
+///   capture_call_params: true
+///   scope: workflows
+///   max_depth: 16
+///   max_files_per_repo: 50000
+///   fail_on:
+///     severity: error
+///     staging_in_source: true
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScanSettings {
+    /// Report artifacts to write (as --reports)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_formats: Option<Vec<crate::report::ReportArtifact>>,
+    /// NGC enrichment stages to run when an API key is set (as --enrich)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enrich: Option<Vec<EnrichStage>>,
+    /// Record literal sampling parameters next to Hosted NIM calls (as --capture-call-params)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capture_call_params: Option<bool>,
+    /// Parts of each repository to scan (as --scope)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<ScanScope>,
+    /// Deepest directory level scanned, 0 = unlimited (as --max-depth)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_depth: Option<usize>,
+    /// File limit per repository, 0 = unlimited (as --max-files-per-repo)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_files_per_repo: Option<usize>,
+    /// match_context length limit in characters, 0 = whole line (as --max-context-chars)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_context_chars: Option<usize>,
+    /// File name patterns (gitignore syntax) skipped as generated, on top of the built-in lockfiles and generated sources
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub generated_files: Vec<String>,
+    /// Model names without an `org/` prefix detected in `model=` assignments, on top of the built-in list
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bare_models: Vec<String>,
+    /// Path patterns (gitignore syntax) whose findings are marked `fixture`, replacing the defaults
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub test_fixture_globs: Option<Vec<String>>,
+    /// Leave out documentation files and notebook Markdown cells (as --skip-docs)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skip_docs: Option<bool>,
+    /// Hosts of Hosted NIM endpoints on top of the built-in NVIDIA API hosts (see --extra-hosted-host)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hosted_hosts: Vec<HostedHostEntry>,
+    /// Ranking of the triage categories in triage.md / triage.json
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub triage_weights: Option<TriageWeights>,
+    /// Conditions that fail the scan after the reports are written
+    #[serde(default, skip_serializing_if = "FailOn::is_empty")]
+    pub fail_on: FailOn,
+}
+
+impl ScanSettings {
+    /// True when no option is set
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// A Hosted NIM host in `scan.hosted_hosts`: a host name, serving production,
+/// or a host name with its environment
+///
+/// ```yaml
+/// hosted_hosts:
+///   - api.nvidia.eu
+///   - host: api.stg.nvidia.eu
+///     environment: staging
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum HostedHostEntry {
+    Host(String),
+    WithEnvironment { host: String, environment: Environment },
+}
+
+impl HostedHostEntry {
+    /// Host name as written and the environment it serves
+    pub fn host_and_environment(&self) -> (String, Environment) {
+        match self {
+            HostedHostEntry::Host(host) => (host.clone(), Environment::Production),
+            HostedHostEntry::WithEnvironment { host, environment } => (host.clone(), *environment),
+        }
+    }
+}
+
+/// Ranking of triage categories (`scan.triage_weights` in repos.yaml)
+///
+/// Items of a heavier category come first; 0 leaves the category out. Unset
+/// categories keep their default weight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TriageWeights {
+    /// Images and models that don't exist in NGC / NVCF
+    pub broken_reference: u32,
+    /// Model policy violations and `error` policy verdicts
+    pub policy_violation: u32,
+    /// Local NIM images tagged `latest`
+    pub explicit_latest: u32,
+    /// Hosted NIM endpoints on a deprecated or removed path
+    pub deprecated_endpoint: u32,
+    /// Staging endpoints or models outside tests, examples and docs
+    pub staging_in_production: u32,
+    /// Pinned tags older than the current latest of the image
+    pub stale_tag: u32,
+}
+
+impl Default for TriageWeights {
+    fn default() -> Self {
+        Self {
+            broken_reference: 50,
+            policy_violation: 40,
+            explicit_latest: 30,
+            deprecated_endpoint: 25,
+            staging_in_production: 20,
+            stale_tag: 10,
+        }
+    }
+}
+
+/// Conditions that fail the scan (`scan.fail_on` in repos.yaml)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FailOn {
+    /// Any finding at or above this severity (as --fail-on-severity)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub severity: Option<Severity>,
+    /// Staging endpoints or models in source code (as --fail-on-staging-in-source)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub staging_in_source: Option<bool>,
+    /// Model policy violations (as --fail-on-policy-violations)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy_violations: Option<bool>,
+    /// No finding at all (as --fail-on-empty; --empty-ok turns it off)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub empty: Option<bool>,
+}
+
+impl FailOn {
+    /// True when no condition is set
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// An NGC enrichment stage
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum EnrichStage {
+    /// Resolve `latest` and untagged Local NIM images to a version
+    Local,
+    /// Look up the NVCF function behind each Hosted NIM
+    Hosted,
+    /// Display name and publisher per Local NIM image (one registry call per unique image)
+    ImageMeta,
+    /// Check that pinned Local NIM tags exist (one tag-list fetch per unique image)
+    Tags,
+}
+
+impl EnrichStage {
+    /// Stages run when neither --enrich nor `scan.enrich` is given
+    pub const DEFAULT_SET: [EnrichStage; 2] = [EnrichStage::Local, EnrichStage::Hosted];
+}
+
 /// Configuration for a single repository
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RepoConfig {
@@ -69,12 +355,71 @@ pub struct RepoConfig {
     /// Whether this repo is enabled for scanning
     #[serde(default = "default_enabled")]
     pub enabled: bool,
+    /// Replacement for the skip-directory list (overrides defaults)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skip_dirs: Option<Vec<String>>,
+    /// Directory names to skip in addition to the base list (appended to defaults)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skip_dirs_add: Vec<String>,
+    /// Directory names to scan even though the base list skips them (appended to defaults)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skip_dirs_remove: Vec<String>,
+    /// How to fetch this repository (overrides --acquire)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub acquire: Option<AcquireMode>,
+    /// Skip this repository when it is larger than this many MB (overrides --max-repo-size-mb; 0 = unlimited)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_repo_size_mb: Option<u64>,
+    /// Scan exactly these files (paths relative to the repository root) instead of walking the repository
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub only_files: Vec<String>,
+    /// Whether this repository's findings are enriched from NGC (false: no NGC request is made for it)
+    #[serde(default = "default_enabled", skip_serializing_if = "is_true")]
+    pub enrich: bool,
+    /// Environment variable holding the NGC API key to enrich this repository with (instead of the run's key)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ngc_api_key_env: Option<String>,
+}
+
+/// How repositories are fetched before scanning
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum AcquireMode {
+    /// Shallow `git clone`
+    #[default]
+    Git,
+    /// Download and unpack the branch tarball over HTTPS (GitHub URLs only; others use git)
+    Tarball,
 }
 
 fn default_enabled() -> bool {
     true
 }
 
+fn is_true(value: &bool) -> bool {
+    *value
+}
+
+impl Default for RepoConfig {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            url: String::new(),
+            branch: None,
+            depth: None,
+            enabled: default_enabled(),
+            skip_dirs: None,
+            skip_dirs_add: Vec::new(),
+            skip_dirs_remove: Vec::new(),
+            acquire: None,
+            max_repo_size_mb: None,
+            only_files: Vec::new(),
+            enrich: default_enabled(),
+            ngc_api_key_env: None,
+        }
+    }
+}
+
 impl RepoConfig {
     /// Apply default values from Defaults struct
     pub fn with_defaults(mut self, defaults: &Defaults) -> Self {
@@ -84,6 +429,11 @@ impl RepoConfig {
         if self.depth.is_none() {
             self.depth = Some(defaults.depth);
         }
+        if self.skip_dirs.is_none() {
+            self.skip_dirs = defaults.skip_dirs.clone();
+        }
+        self.skip_dirs_add.splice(0..0, defaults.skip_dirs_add.iter().cloned());
+        self.skip_dirs_remove.splice(0..0, defaults.skip_dirs_remove.iter().cloned());
         self
     }
 
@@ -102,6 +452,313 @@ impl RepoConfig {
 // Scan Result Structures
 // ============================================================================
 
+/// Policy severity of a finding (ordered: info < warning < error)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    /// Lowercase name as used in config and reports
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+}
+
+/// Outcome of policy evaluation for one finding
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyVerdict {
+    /// Assigned severity
+    pub severity: Severity,
+    /// Name of the rule that assigned it
+    pub policy_rule: String,
+}
+
+/// Why NGC enrichment failed for a finding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EnrichmentError {
+    /// The registry repository or NVCF function doesn't exist (HTTP 404)
+    NotFound,
+    /// Any other failure (server error, bad response, ...)
+    Failed,
+}
+
+impl EnrichmentError {
+    /// Name as used in reports
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EnrichmentError::NotFound => "not_found",
+            EnrichmentError::Failed => "failed",
+        }
+    }
+}
+
+/// Why NGC enrichment was skipped for a finding it would otherwise look up
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EnrichmentSkip {
+    /// --enrichment-budget-secs or --enrichment-max-requests ran out first
+    BudgetExhausted,
+    /// The repository's config sets `enrich: false`
+    RepoOptedOut,
+    /// The variable named by the repository's `ngc_api_key_env` is unset or empty
+    RepoKeyMissing,
+}
+
+impl EnrichmentSkip {
+    /// snake_case name as used in reports
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EnrichmentSkip::BudgetExhausted => "budget_exhausted",
+            EnrichmentSkip::RepoOptedOut => "repo_opted_out",
+            EnrichmentSkip::RepoKeyMissing => "repo_key_missing",
+        }
+    }
+}
+
+/// Where a Hosted NIM call site gets its API key from (never the key itself)
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CredentialSource {
+    /// Environment variable (`os.environ["NVIDIA_API_KEY"]`, `process.env.NGC_API_KEY`, `$NVIDIA_API_KEY`)
+    EnvVar { name: String },
+    /// Secrets-manager reference (`${{ secrets.NVIDIA_API_KEY }}`)
+    Secret { name: String },
+    /// A key written into the file; its value is redacted
+    Literal,
+}
+
+impl CredentialSource {
+    /// Key for per-credential counts: `env:NAME`, `secret:NAME` or `literal`
+    pub fn label(&self) -> String {
+        match self {
+            CredentialSource::EnvVar { name } => format!("env:{}", name),
+            CredentialSource::Secret { name } => format!("secret:{}", name),
+            CredentialSource::Literal => "literal".to_string(),
+        }
+    }
+}
+
+/// How NGC enrichment went for a finding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EnrichmentOutcome {
+    /// Enrichment filled in the finding (resolved tag, function details)
+    Enriched,
+    /// No lookup was made (pinned tag, staging, no API key, rejected key)
+    NotAttempted,
+    /// The lookup failed or found nothing
+    Failed,
+}
+
+impl EnrichmentOutcome {
+    fn of(enriched: bool, error: Option<EnrichmentError>) -> Self {
+        if error.is_some() {
+            EnrichmentOutcome::Failed
+        } else if enriched {
+            EnrichmentOutcome::Enriched
+        } else {
+            EnrichmentOutcome::NotAttempted
+        }
+    }
+}
+
+/// NVIDIA API environment a Hosted NIM finding targets
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Environment {
+    #[default]
+    Production,
+    /// NVIDIA-internal staging hosts (stg.api.nvidia.com, api.stg.nvidia.com, ...) or `stg/` models
+    Staging,
+}
+
+impl Environment {
+    /// Lowercase name as used in reports
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Environment::Production => "production",
+            Environment::Staging => "staging",
+        }
+    }
+}
+
+/// How a Hosted NIM is invoked, from the shape of its endpoint URL
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InvokeStyle {
+    /// OpenAI-compatible routes: `/v1`, `/v1/chat/completions`, `/v1/embeddings`, ...
+    OpenaiCompatible,
+    /// Per-model paths such as `ai.api.nvidia.com/v1/<category>/<org>/<model>`
+    ModelPath,
+    /// NVCF invocation API (`api.nvcf.nvidia.com/v2/nvcf/pexec/functions/<id>`)
+    NvcfPexec,
+    /// No endpoint, or one whose shape isn't recognised
+    #[default]
+    Unknown,
+}
+
+impl InvokeStyle {
+    /// snake_case name as used in reports
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InvokeStyle::OpenaiCompatible => "openai_compatible",
+            InvokeStyle::ModelPath => "model_path",
+            InvokeStyle::NvcfPexec => "nvcf_pexec",
+            InvokeStyle::Unknown => "unknown",
+        }
+    }
+}
+
+/// Kind of file a finding is in, from its path and content (finer than [`SourceType`])
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileKind {
+    /// `Dockerfile`, `Dockerfile.*`, `*.dockerfile`, `Containerfile`
+    Dockerfile,
+    /// `compose*.y(a)ml`, `docker-compose*.y(a)ml`
+    Compose,
+    /// YAML / JSON manifest with `apiVersion` and `kind`
+    Kubernetes,
+    /// Helm chart: `Chart.yaml`, `values*.yaml`, or a template under `templates/`
+    Helm,
+    /// Jupyter notebook
+    Notebook,
+    /// Markdown, reStructuredText, HTML and text files
+    Docs,
+    /// CI pipeline: GitHub Actions, GitLab CI, Jenkins, CircleCI, Azure Pipelines, Buildkite
+    Ci,
+    /// Anything else: application code and configuration
+    #[default]
+    Source,
+}
+
+impl FileKind {
+    /// snake_case name as used in reports
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FileKind::Dockerfile => "dockerfile",
+            FileKind::Compose => "compose",
+            FileKind::Kubernetes => "kubernetes",
+            FileKind::Helm => "helm",
+            FileKind::Notebook => "notebook",
+            FileKind::Docs => "docs",
+            FileKind::Ci => "ci",
+            FileKind::Source => "source",
+        }
+    }
+}
+
+/// Library or tool a Hosted NIM is called through, from the code around the call
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Framework {
+    /// LangChain: `ChatNVIDIA`, `NVIDIAEmbeddings`, `NVIDIARerank` or a `langchain` import
+    Langchain,
+    /// LlamaIndex: a `llama_index` / `llamaindex` import
+    LlamaIndex,
+    /// Haystack: a `haystack` import
+    Haystack,
+    /// OpenAI SDK client: `OpenAI(base_url=...)`, `new OpenAI({...})`
+    OpenaiSdk,
+    /// HTTP client library: `requests.post`, `httpx`, `aiohttp`, `fetch`, `axios`
+    RawHttp,
+    /// `curl` command
+    Curl,
+    /// Nothing around the call tells
+    #[default]
+    Unknown,
+}
+
+impl Framework {
+    /// snake_case name as used in reports
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Framework::Langchain => "langchain",
+            Framework::LlamaIndex => "llama_index",
+            Framework::Haystack => "haystack",
+            Framework::OpenaiSdk => "openai_sdk",
+            Framework::RawHttp => "raw_http",
+            Framework::Curl => "curl",
+            Framework::Unknown => "unknown",
+        }
+    }
+}
+
+/// What a Local NIM reference does with the image, from the syntax around it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LocalIntent {
+    /// Built on: `FROM` (or `COPY --from=`) in a Dockerfile
+    BaseImage,
+    /// Deployed: `image:` key in a Compose file, Kubernetes manifest or Helm values
+    Deploy,
+    /// Downloaded: `docker pull`, `podman pull`, `apptainer pull`, `enroot import`, ...
+    Pull,
+    /// Started: `docker run`, `podman run`, `kubectl run`, `containers.run(...)`
+    Run,
+    /// Mentioned in prose or a string with nothing telling how it is used
+    #[default]
+    Reference,
+}
+
+impl LocalIntent {
+    /// snake_case name as used in reports
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LocalIntent::BaseImage => "base_image",
+            LocalIntent::Deploy => "deploy",
+            LocalIntent::Pull => "pull",
+            LocalIntent::Run => "run",
+            LocalIntent::Reference => "reference",
+        }
+    }
+}
+
+/// Kind of construct a Local NIM reference is in, from the syntax around it
+///
+/// Finer than [`LocalIntent`]: a `deploy` can be a Compose service, a
+/// Kubernetes container or a CI job's service container.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageKind {
+    /// Base image: `FROM` (or `COPY --from=`) in a Dockerfile
+    DockerfileFrom,
+    /// Container started with `docker run`, `podman run`, `containers.run(...)`, ...
+    DockerRun,
+    /// `image:` of a Compose service
+    ComposeImage,
+    /// Container of a Kubernetes workload, NIM Operator resource, Helm values or `kubectl run`
+    K8sContainer,
+    /// Job container or service container of a CI pipeline (`container:`, `services:`, `image:`)
+    CiService,
+    /// Pulls, downloads and plain mentions
+    #[default]
+    Other,
+}
+
+impl UsageKind {
+    /// snake_case name as used in reports
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UsageKind::DockerfileFrom => "dockerfile_from",
+            UsageKind::DockerRun => "docker_run",
+            UsageKind::ComposeImage => "compose_image",
+            UsageKind::K8sContainer => "k8s_container",
+            UsageKind::CiService => "ci_service",
+            UsageKind::Other => "other",
+        }
+    }
+}
+
 /// A detected Local NIM reference (Docker image from nvcr.io/nim/*)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LocalNimMatch {
@@ -114,12 +771,84 @@ pub struct LocalNimMatch {
     /// Resolved tag if original was 'latest' (from NGC API)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub resolved_tag: Option<String>,
+    /// Whether the pinned tag is in the image's NGC tag list (--verify-tags)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag_exists: Option<bool>,
     /// File path relative to repository root
     pub file_path: String,
     /// Line number where the match was found (1-indexed)
     pub line_number: usize,
     /// The actual line content that matched
     pub match_context: String,
+    /// Severity and rule from policy evaluation (after enrichment)
+    #[serde(default, flatten, skip_serializing_if = "Option::is_none")]
+    pub policy: Option<PolicyVerdict>,
+    /// Why NGC enrichment failed for this finding
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enrichment_error: Option<EnrichmentError>,
+    /// NGC enrichment succeeded for this finding
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub enriched: bool,
+    /// Pattern that produced the finding (e.g. `chatnvidia`, `local_nim_full`)
+    #[serde(default)]
+    pub detection_rule: String,
+    /// `on:` triggers of the workflow this finding is in (e.g. `push`, `schedule: 0 2 * * *`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub workflow_triggers: Vec<String>,
+    /// NIM this image is (path after `nvcr.io/nim/`, e.g. meta/llama-3.1-70b-instruct)
+    #[serde(default)]
+    pub nim_identity: String,
+    /// Why NGC enrichment was skipped for this finding
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enrichment_skipped: Option<EnrichmentSkip>,
+    /// Length in characters of the matched line when `match_context` was cut to --max-context-chars
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_truncated: Option<usize>,
+    /// Compose service this image belongs to, when its directory has several Compose files
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compose: Option<ComposeService>,
+    /// How the image is used: base image, deployment, pull, run or a plain reference
+    #[serde(default)]
+    pub intent: LocalIntent,
+    /// Construct the image is used in: Dockerfile `FROM`, `docker run`, Compose, Kubernetes, CI service
+    #[serde(default)]
+    pub usage_kind: UsageKind,
+    /// Who added the line and when (--blame)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blame: Option<LineBlame>,
+    /// Notebook cell the line is in (`.ipynb` files; `line_number` is the line of the file)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notebook_cell: Option<NotebookCell>,
+    /// What kind of file the finding is in (Dockerfile, Compose, Kubernetes, Helm, notebook, docs, CI, source)
+    #[serde(default)]
+    pub file_kind: FileKind,
+    /// Variable(s) substituted into the line before matching (`image: ${NIM_IMAGE}`), comma-separated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_from: Option<String>,
+    /// In a test fixture or in the scanner's own repository; left out of the summary counts
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub fixture: bool,
+    /// Line-independent identity of the finding (see `fingerprint.rs`), set when the report is built
+    #[serde(default)]
+    pub fingerprint: String,
+}
+
+/// A Compose service after merging the Compose files of one directory by service name
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComposeService {
+    /// Service name
+    pub service: String,
+    /// Compose files merged, in merge order (later files override earlier ones)
+    pub merged_files: Vec<String>,
+    /// `profiles` of the merged service
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub profiles: Vec<String>,
+    /// `deploy.replicas` (or `scale`) of the merged service
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replicas: Option<u64>,
+    /// File each of the hints above came from (e.g. `profiles` -> `docker-compose.yaml`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub hint_sources: BTreeMap<String, String>,
 }
 
 /// A detected Hosted NIM reference (API endpoint to *.api.nvidia.com)
@@ -146,6 +875,179 @@ pub struct HostedNimMatch {
     /// Underlying container image
     #[serde(skip_serializing_if = "Option::is_none")]
     pub container_image: Option<String>,
+    /// Production or staging, from the endpoint host and model prefix
+    #[serde(default)]
+    pub environment: Environment,
+    /// Host of the endpoint, when it is a built-in or configured Hosted NIM host (lowercase)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endpoint_host: Option<String>,
+    /// Protocol used to call the NIM, from the endpoint URL shape
+    #[serde(default)]
+    pub invoke_style: InvokeStyle,
+    /// Library or tool the call goes through (LangChain, OpenAI SDK, requests, curl, ...)
+    #[serde(default)]
+    pub framework: Framework,
+    /// Literal sampling parameters next to the call (temperature, max_tokens, stream, top_p);
+    /// only with --capture-call-params
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub call_params: Option<serde_json::Value>,
+    /// Severity and rule from policy evaluation (after enrichment)
+    #[serde(default, flatten, skip_serializing_if = "Option::is_none")]
+    pub policy: Option<PolicyVerdict>,
+    /// Model policy violation (--model-policy)
+    #[serde(default, flatten, skip_serializing_if = "Option::is_none")]
+    pub model_policy: Option<ModelPolicyViolation>,
+    /// Why NGC enrichment failed for this finding
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enrichment_error: Option<EnrichmentError>,
+    /// NGC enrichment succeeded for this finding
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub enriched: bool,
+    /// Pattern that produced the finding (e.g. `chatnvidia`, `local_nim_full`)
+    #[serde(default)]
+    pub detection_rule: String,
+    /// `on:` triggers of the workflow this finding is in (e.g. `push`, `schedule: 0 2 * * *`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub workflow_triggers: Vec<String>,
+    /// NIM this model is (canonical model name, shared with the matching Local NIM image)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nim_identity: Option<String>,
+    /// Why NGC enrichment was skipped for this finding
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enrichment_skipped: Option<EnrichmentSkip>,
+    /// The endpoint is assigned to a variable bundled into browser code (`NEXT_PUBLIC_*`, `VITE_*`)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub browser_exposed: bool,
+    /// Close matches from the NVCF function list when no function serves `model_name` (likely a typo)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub did_you_mean: Vec<String>,
+    /// Credential the call authenticates with, from `api_key=` / `Authorization: Bearer` nearby
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credential_source: Option<CredentialSource>,
+    /// Length in characters of the matched line when `match_context` was cut to --max-context-chars
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_truncated: Option<usize>,
+    /// Inference URL NVCF reports for the function (`inferenceUrl`, populated by NGC API)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub canonical_endpoint: Option<String>,
+    /// The endpoint in code points somewhere other than `canonical_endpoint`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub endpoint_mismatch: bool,
+    /// Status of the endpoint's path by the endpoint rules (None when no rule knows the path)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endpoint_status: Option<EndpointStatus>,
+    /// Endpoint to move to when the path is deprecated or removed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggested_endpoint: Option<String>,
+    /// Who added the line and when (--blame)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blame: Option<LineBlame>,
+    /// Notebook cell the line is in (`.ipynb` files; `line_number` is the line of the file)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notebook_cell: Option<NotebookCell>,
+    /// What kind of file the finding is in (Dockerfile, Compose, Kubernetes, Helm, notebook, docs, CI, source)
+    #[serde(default)]
+    pub file_kind: FileKind,
+    /// Variable(s) substituted into the line before matching (`image: ${NIM_IMAGE}`), comma-separated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_from: Option<String>,
+    /// In a test fixture or in the scanner's own repository; left out of the summary counts
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub fixture: bool,
+    /// Line-independent identity of the finding (see `fingerprint.rs`), set when the report is built
+    #[serde(default)]
+    pub fingerprint: String,
+}
+
+/// Package registry a dependency comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PackageEcosystem {
+    /// PyPI: requirements files, `pyproject.toml`, `setup.cfg`, the `pip:` list of a Conda environment
+    Pypi,
+    /// npm: `package.json`
+    Npm,
+    /// Conda: the `dependencies:` of `environment.yml`
+    Conda,
+}
+
+impl PackageEcosystem {
+    /// snake_case name as used in reports
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PackageEcosystem::Pypi => "pypi",
+            PackageEcosystem::Npm => "npm",
+            PackageEcosystem::Conda => "conda",
+        }
+    }
+}
+
+/// An NVIDIA AI endpoint SDK declared in a dependency manifest (see `dependencies.rs`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SdkDependencyMatch {
+    /// Repository name
+    pub repository: String,
+    /// Package name as declared (`langchain-nvidia-ai-endpoints`)
+    pub package_name: String,
+    pub ecosystem: PackageEcosystem,
+    /// Version specifier as written (`>=0.3`, `^0.3.0`); none when the package is unpinned
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_constraint: Option<String>,
+    /// Extras requested (`package[extra]`, Poetry `extras = [...]`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extras: Vec<String>,
+    /// Key path of the list declaring it (`project.optional-dependencies.nvidia`,
+    /// `tool.poetry.group.dev.dependencies`, `devDependencies`); none in a requirements file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub section: Option<String>,
+    /// Library the package belongs to
+    pub framework: Framework,
+    /// File path relative to repository root
+    pub file_path: String,
+    /// Line number of the declaration (1-indexed, best effort for parsed formats)
+    pub line_number: usize,
+    /// The declaring line, trimmed
+    pub match_context: String,
+    /// In a test fixture or in the scanner's own repository; left out of the summary counts
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub fixture: bool,
+}
+
+/// Where in a Jupyter notebook a finding is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotebookCell {
+    /// Position of the cell in the notebook's `cells` list (0-indexed)
+    pub index: usize,
+    /// Line within the cell's source (1-indexed)
+    pub line: usize,
+    pub kind: NotebookCellKind,
+}
+
+/// Type of a notebook cell scanned for NIM references
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotebookCellKind {
+    /// Code, scanned like a source file in the kernel's language
+    Code,
+    /// Markdown, scanned like a README
+    Markdown,
+}
+
+/// Last change to a finding's line, from `git blame` (--blame)
+///
+/// The fields are empty, with a `note`, when the change isn't in the fetched
+/// history (a shallow clone) or blame failed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineBlame {
+    /// Author email, without angle brackets
+    pub author_email: Option<String>,
+    /// Commit that last changed the line
+    pub commit_sha: Option<String>,
+    /// Author date of that commit (RFC 3339, in the author's time zone)
+    pub author_date: Option<String>,
+    /// Why the fields are empty
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
 }
 
 /// Collection of NIM findings for a specific source type
@@ -155,6 +1057,9 @@ pub struct NimFindings {
     pub local_nim: Vec<LocalNimMatch>,
     /// Hosted NIM matches (API endpoints)
     pub hosted_nim: Vec<HostedNimMatch>,
+    /// NVIDIA AI endpoint SDKs in dependency manifests; a usage signal, not counted as NIM findings
+    #[serde(default)]
+    pub sdk_dependencies: Vec<SdkDependencyMatch>,
 }
 
 impl NimFindings {
@@ -170,7 +1075,6 @@ impl NimFindings {
     }
 
     /// Get the total count of findings
-    #[allow(dead_code)]
     pub fn total_count(&self) -> usize {
         self.local_nim.len() + self.hosted_nim.len()
     }
@@ -183,6 +1087,9 @@ impl NimFindings {
 /// Complete scan report with results categorized by source type
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScanReport {
+    /// Report layout version (REPORT_SCHEMA_VERSION; empty for reports written before it was recorded)
+    #[serde(default)]
+    pub schema_version: String,
     /// Timestamp when the scan was performed
     pub scan_time: String,
     /// Total number of repositories scanned
@@ -193,8 +1100,501 @@ pub struct ScanReport {
     pub actions_workflow: NimFindings,
     /// Aggregated view: NIMs grouped with all their locations
     pub aggregated: AggregatedFindings,
+    /// Local image vs hosted function container tag comparisons (post-enrichment)
+    pub version_skew: Vec<VersionSkew>,
+    /// Tags in use per Local NIM image, most fragmented first
+    #[serde(default)]
+    pub version_spread: Vec<VersionSpread>,
     /// Summary statistics
     pub summary: Summary,
+    /// Changes relative to a baseline report (--baseline)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delta: Option<ReportDelta>,
+    /// Repositories only partially scanned because of --max-depth / --max-files-per-repo
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub truncated: Vec<ScanTruncation>,
+    /// Which parts of each repository were scanned (--scope)
+    #[serde(default)]
+    pub scope: ScanScope,
+    /// Path patterns scanned when the scope is not `full`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scope_paths: Vec<String>,
+    /// Hosted NIMs that resolved in the baseline but have no function now (--baseline)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub possible_retirements: Vec<PossibleRetirement>,
+    /// How each scanned repository was obtained, and at which commit
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub checkouts: Vec<RepoCheckout>,
+    /// Repositories not scanned because they exceed --max-repo-size-mb
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skipped_oversize: Vec<OversizeSkip>,
+    /// Files not scanned because only their Git LFS pointer was checked out
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub lfs_pointers: Vec<LfsPointerFile>,
+    /// Files listed by `only_files` or --files-from that could not be scanned
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub listed_file_errors: Vec<ListedFileError>,
+    /// Enrichment outcomes per detection rule (post-enrichment)
+    #[serde(default)]
+    pub detector_stats: BTreeMap<String, DetectorStats>,
+    /// Hosted NIM findings grouped by the framework calling them, most repositories first
+    #[serde(default)]
+    pub framework_breakdown: Vec<FrameworkUsage>,
+    /// Findings per top- and second-level directory of each repository
+    #[serde(default)]
+    pub hotspots: Vec<RepoHotspots>,
+    /// Reports this one was merged from (merge-reports)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub merged_from: Vec<MergedInput>,
+    /// Repository names found in more than one merged report
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub repository_collisions: Vec<String>,
+    /// NIM hints in GitHub descriptions, topics and homepages (--github-metadata); not counted in the summary
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub metadata_hints: Vec<MetadataHint>,
+    /// Repositories left out because checking them out or scanning them panicked
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub panicked: Vec<RepoPanic>,
+    /// Sample of repositories this scan covered, with extrapolated counts (--sample)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sampling: Option<Sampling>,
+}
+
+/// Repositories a `--sample` scan picked, and its summary scaled to all of them
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Sampling {
+    /// Seed of the selection (the same seed and configuration pick the same repositories)
+    pub seed: u64,
+    /// Enabled repositories the sample was drawn from
+    pub population: usize,
+    /// Repositories scanned, in configuration order
+    pub repositories: Vec<String>,
+    /// Estimates for the whole population: observed count × population / sample size
+    pub estimated: SampleEstimates,
+}
+
+/// Summary counts extrapolated from a sample (estimates, not observations)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SampleEstimates {
+    pub total_local_nim: f64,
+    pub total_hosted_nim: f64,
+    pub repos_with_nim: f64,
+}
+
+/// One input of a merged report
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MergedInput {
+    /// Path of the input report.json
+    pub report: String,
+    /// scan_time of the input
+    pub scan_time: String,
+    /// schema_version of the input
+    pub schema_version: String,
+    /// Repositories the input scanned
+    pub total_repos: usize,
+}
+
+/// Hosted NIM use through one framework
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FrameworkUsage {
+    pub framework: Framework,
+    /// Models called through it (sorted, distinct)
+    pub models: Vec<String>,
+    /// Repositories with at least one finding through it
+    pub repo_count: usize,
+    /// Hosted NIM findings through it
+    pub findings: usize,
+}
+
+/// Tags of one Local NIM image in use across the scanned repositories
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionSpread {
+    /// Image URL without tag
+    pub image_url: String,
+    /// Number of distinct tags in use (the fragmentation metric)
+    pub distinct_tags: usize,
+    /// More than [`crate::analysis::FRAGMENTED_TAG_COUNT`] tags in use at once
+    pub fragmented: bool,
+    /// Repositories using any tag of the image
+    pub repo_count: usize,
+    /// Each tag with the repositories using it, most repositories first
+    pub tags: Vec<TagUsage>,
+}
+
+/// One tag of an image and how many repositories use it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TagUsage {
+    /// Tag as written (`latest` and unresolved variables included)
+    pub tag: String,
+    /// Repositories with at least one finding of this tag
+    pub repo_count: usize,
+}
+
+/// Findings in the files under one directory of a repository
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DirectoryCount {
+    /// Top-level (`deploy`) or second-level (`deploy/helm`) directory; `.` for files at the root
+    pub directory: String,
+    pub local_count: usize,
+    pub hosted_count: usize,
+}
+
+/// Where one repository's findings are, by directory (sorted by path)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RepoHotspots {
+    pub repository: String,
+    pub directories: Vec<DirectoryCount>,
+}
+
+/// Findings of one detection rule, by enrichment outcome
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DetectorStats {
+    /// Counts over all files
+    #[serde(flatten)]
+    pub counts: OutcomeCounts,
+    /// The same counts per file extension (`none` for files without one)
+    pub by_extension: BTreeMap<String, OutcomeCounts>,
+}
+
+/// Number of findings per enrichment outcome
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutcomeCounts {
+    pub total: usize,
+    pub enriched: usize,
+    pub not_attempted: usize,
+    pub failed: usize,
+}
+
+impl OutcomeCounts {
+    /// Count one finding
+    pub fn record(&mut self, outcome: EnrichmentOutcome) {
+        self.total += 1;
+        match outcome {
+            EnrichmentOutcome::Enriched => self.enriched += 1,
+            EnrichmentOutcome::NotAttempted => self.not_attempted += 1,
+            EnrichmentOutcome::Failed => self.failed += 1,
+        }
+    }
+}
+
+/// How a repository's files were obtained for scanning
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Acquisition {
+    /// Cloned (or updated) with git
+    Git,
+    /// Unpacked from a downloaded tarball
+    Tarball,
+    /// Pre-provisioned checkout in the workdir (--no-clone)
+    Local,
+}
+
+/// Where a repository's size was measured
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SizeSource {
+    /// GitHub REST API `size`, before cloning
+    GithubApi,
+    /// Files on disk after cloning (the checkout was then removed)
+    Checkout,
+}
+
+/// A repository skipped because it exceeds the size limit (--max-repo-size-mb)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OversizeSkip {
+    /// Repository name
+    pub repository: String,
+    /// Measured size in MB
+    pub size_mb: u64,
+    /// Limit that applied to the repository, in MB
+    pub limit_mb: u64,
+    /// Where the size came from
+    pub measured: SizeSource,
+}
+
+/// One scanned repository's checkout
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RepoCheckout {
+    /// Repository name
+    pub repository: String,
+    /// How the files were obtained
+    pub acquisition: Acquisition,
+    /// Commit that was scanned, when known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit_sha: Option<String>,
+    /// Wall-clock time spent scanning the checkout, in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scan_ms: Option<u64>,
+    /// Set when a reused checkout was reset because its branch was rewritten upstream
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reset: Option<CheckoutReset>,
+    /// Set when a reused checkout could not be updated and was scanned at its old commit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stale: Option<StaleCheckout>,
+}
+
+/// A reused checkout moved to a force-pushed branch (`git reset --hard origin/<branch>`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheckoutReset {
+    /// HEAD before the reset (from an earlier run)
+    pub old_head: String,
+    /// HEAD after the reset
+    pub new_head: String,
+}
+
+/// Why a reused checkout was scanned at the commit an earlier run left it at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StaleCheckout {
+    /// The configured branch no longer exists on the remote (renamed or deleted)
+    BranchMissing,
+    /// The branch was rewritten upstream and --allow-reset is off
+    Diverged,
+}
+
+impl StaleCheckout {
+    /// Name as used in reports
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StaleCheckout::BranchMissing => "branch_missing",
+            StaleCheckout::Diverged => "diverged",
+        }
+    }
+}
+
+/// Which parts of each repository a scan covers
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ScanScope {
+    /// Every file in the repository
+    #[default]
+    Full,
+    /// Only CI configuration and deployment manifests (sparse checkout); source code is not scanned
+    #[value(alias = "ci")]
+    #[serde(alias = "ci")]
+    Workflows,
+}
+
+/// What the scan limits cut off in one repository
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScanTruncation {
+    /// Repository name
+    pub repository: String,
+    /// Depth limit, when directories below it were skipped
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_depth: Option<usize>,
+    /// Directories at the depth limit whose contents were not scanned
+    pub dirs_beyond_max_depth: usize,
+    /// File limit, when more files were left unscanned
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_files: Option<usize>,
+}
+
+/// Which part of a repository's GitHub metadata a hint comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MetadataField {
+    /// The repository description ("about" text)
+    Description,
+    /// One of the repository's topics
+    Topic,
+    /// The homepage URL shown next to the description
+    Homepage,
+}
+
+impl MetadataField {
+    /// Name as used in reports
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MetadataField::Description => "description",
+            MetadataField::Topic => "topic",
+            MetadataField::Homepage => "homepage",
+        }
+    }
+}
+
+/// A low-confidence NIM mention in a repository's GitHub metadata (--github-metadata)
+///
+/// Informational only: hints are never counted in the summary, enriched or
+/// checked against policies, since nothing shows the code actually uses them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MetadataHint {
+    /// Repository name
+    pub repository: String,
+    /// Where the mention is
+    pub field: MetadataField,
+    /// The metadata text it was found in
+    pub text: String,
+    /// Model name, for a Hosted NIM pattern
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_name: Option<String>,
+    /// Image, for a Local NIM pattern
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image_url: Option<String>,
+    /// Detection rule that matched
+    pub detection_rule: String,
+}
+
+/// Stage of a repository's processing that panicked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PanicStage {
+    Checkout,
+    Scan,
+}
+
+impl PanicStage {
+    /// Name as used in reports
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PanicStage::Checkout => "checkout",
+            PanicStage::Scan => "scan",
+        }
+    }
+}
+
+/// A repository whose checkout or scan panicked; the rest of the run went on without it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RepoPanic {
+    /// Repository name
+    pub repository: String,
+    pub stage: PanicStage,
+    /// Panic message
+    pub message: String,
+}
+
+/// A file whose checkout holds a Git LFS pointer, so its content was not scanned
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LfsPointerFile {
+    /// Repository name
+    pub repository: String,
+    /// File path relative to the repository root
+    pub file_path: String,
+}
+
+/// A file listed by `only_files` or --files-from that could not be scanned
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ListedFileError {
+    /// Repository name
+    pub repository: String,
+    /// File path as listed
+    pub file_path: String,
+    /// Why it was not scanned (e.g. "not found")
+    pub reason: String,
+}
+
+/// How a `scan` run ended (result.json)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    /// Reports written, no --fail-on condition met
+    #[default]
+    Ok,
+    /// Reports written, but a --fail-on condition was met
+    ChecksFailed,
+    /// The run stopped before finishing (bad configuration, unwritable output, ...)
+    Error,
+}
+
+/// Repository counts of a `scan` run (result.json)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunRepoCounts {
+    /// Enabled repositories in the configuration
+    pub configured: usize,
+    /// Checkouts ready to scan (cloned, downloaded or pre-provisioned)
+    pub cloned: usize,
+    /// Repositories that could not be checked out
+    pub failed: usize,
+    /// Repositories skipped over the size limit
+    pub skipped_oversize: usize,
+    /// Repositories scanned
+    pub scanned: usize,
+}
+
+/// Finding counts of a `scan` run after deduplication (result.json)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunFindingCounts {
+    pub local_nim: usize,
+    pub hosted_nim: usize,
+}
+
+/// Machine-readable outcome of a `scan` run, written as result.json even when the run fails
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunResult {
+    /// Exit code the process returns
+    pub exit_code: i32,
+    pub status: RunStatus,
+    /// Why the run failed (full error chain)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Wall-clock time of the run
+    pub duration_ms: u64,
+    /// Counts as far as the run got (zero for stages it never reached)
+    pub repos: RunRepoCounts,
+    pub findings: RunFindingCounts,
+    /// Files written by the run (reports, badges, audit log)
+    pub artifacts: Vec<String>,
+}
+
+/// Movement between a baseline report and the current one (identifiers sorted)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReportDelta {
+    /// scan_time of the baseline report
+    pub baseline_scan_time: String,
+    /// Local NIM images (without tag) found now but not in the baseline
+    pub new_local_images: Vec<String>,
+    /// Local NIM images found in the baseline but not now
+    pub removed_local_images: Vec<String>,
+    /// Images in both whose tags changed, as `image: old tags -> new tags`
+    pub local_tag_changes: Vec<String>,
+    /// Hosted NIM models found now but not in the baseline
+    pub new_hosted_models: Vec<String>,
+    /// Hosted NIM models found in the baseline but not now
+    pub removed_hosted_models: Vec<String>,
+    /// Repositories with Hosted NIM usage now but none in the baseline
+    pub new_hosted_repos: Vec<String>,
+    /// Findings whose fingerprint is not in the baseline
+    #[serde(default)]
+    pub new_findings: usize,
+    /// Baseline findings whose fingerprint is gone
+    #[serde(default)]
+    pub removed_findings: usize,
+}
+
+/// A Local NIM image and a Hosted NIM backed by the same image, in one repository
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionSkew {
+    /// Repository where both were found
+    pub repository: String,
+    /// Shared image name (last path component, e.g. llama-3.3-70b-instruct)
+    pub image_name: String,
+    /// Local NIM image URL (without tag)
+    pub local_image: String,
+    /// Tag the repository pins (resolved tag when the original was 'latest')
+    pub local_tag: String,
+    /// Hosted model name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hosted_model: Option<String>,
+    /// Container image backing the hosted function (from NGC API)
+    pub hosted_container_image: String,
+    /// Tag of the hosted container image
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hosted_tag: Option<String>,
+    /// Whether the hosted function runs a newer tag (None if tags aren't comparable)
+    pub hosted_uses_newer: Option<bool>,
+}
+
+impl VersionSkew {
+    /// Whether local and hosted tags differ (by version when comparable, else textually)
+    pub fn is_skewed(&self) -> bool {
+        match self.hosted_tag.as_deref() {
+            Some(hosted) => match crate::analysis::compare_versions(hosted, &self.local_tag) {
+                Some(ord) => ord != std::cmp::Ordering::Equal,
+                None => hosted != self.local_tag,
+            },
+            None => true,
+        }
+    }
 }
 
 /// Summary statistics for the scan
@@ -210,6 +1610,131 @@ pub struct Summary {
     pub source_code: CategorySummary,
     /// Statistics for workflow findings
     pub actions_workflow: CategorySummary,
+    /// Findings per policy severity
+    #[serde(default)]
+    pub severity: SeverityCounts,
+    /// Hosted NIM findings per API environment
+    #[serde(default)]
+    pub environment: EnvironmentCounts,
+    /// Hosted NIM findings per invoke style
+    #[serde(default)]
+    pub invoke_style: InvokeStyleCounts,
+    /// Local NIM findings per usage intent
+    #[serde(default)]
+    pub local_intent: LocalIntentCounts,
+    /// Findings per kind of file (Dockerfile, Compose, notebook, docs, ...)
+    #[serde(default)]
+    pub file_kind: FileKindCounts,
+    /// Model policy violations per repository (--model-policy)
+    #[serde(default)]
+    pub policy_violations: BTreeMap<String, usize>,
+    /// Distinct NIMs found in a workflow with a `schedule` trigger
+    #[serde(default)]
+    pub scheduled_ci_nims: usize,
+    /// Aggregated Hosted NIMs per resolution (filled in after enrichment)
+    #[serde(default)]
+    pub resolution: ResolutionCounts,
+    /// Aggregated Hosted NIMs per model modality (filled in with --enrich-model-cards)
+    #[serde(default)]
+    pub modality: BTreeMap<String, usize>,
+    /// Distinct NIMs by identity, counting an image and its hosted model once
+    #[serde(default)]
+    pub distinct_nims: usize,
+    /// Findings left unenriched: the enrichment budget ran out or their repository isn't enriched
+    #[serde(default)]
+    pub enrichment_skipped: usize,
+    /// Hosted NIM endpoints assigned to browser-bundled variables (`NEXT_PUBLIC_*`, `VITE_*`)
+    #[serde(default)]
+    pub browser_exposed_endpoints: usize,
+    /// Hosted NIM call sites per credential (`env:NVIDIA_API_KEY`, `secret:NGC_API_KEY`, `literal`)
+    #[serde(default)]
+    pub credential_sources: BTreeMap<String, usize>,
+    /// Hosted NIM findings whose endpoint in code differs from the NVCF inference URL
+    #[serde(default)]
+    pub endpoint_mismatches: usize,
+    /// Hosted NIM findings per endpoint path status (findings no rule knows are not counted)
+    #[serde(default)]
+    pub endpoint_status: EndpointStatusCounts,
+    /// Findings marked `fixture` and left out of every other count (0 with --include-fixtures)
+    #[serde(default)]
+    pub fixtures_excluded: usize,
+    /// Repositories declaring each NVIDIA AI endpoint SDK package (lowercase name) as a dependency
+    #[serde(default)]
+    pub sdk_dependencies: BTreeMap<String, usize>,
+}
+
+/// Number of aggregated Hosted NIMs per resolution
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResolutionCounts {
+    pub resolved: usize,
+    pub never_resolved: usize,
+    pub newly_unresolved: usize,
+}
+
+/// Number of Hosted NIM findings per invoke style
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InvokeStyleCounts {
+    pub openai_compatible: usize,
+    pub model_path: usize,
+    pub nvcf_pexec: usize,
+    pub unknown: usize,
+}
+
+/// Number of findings (Local and Hosted) per file kind
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FileKindCounts {
+    pub dockerfile: usize,
+    pub compose: usize,
+    pub kubernetes: usize,
+    pub helm: usize,
+    pub notebook: usize,
+    pub docs: usize,
+    pub ci: usize,
+    pub source: usize,
+}
+
+/// Number of Hosted NIM findings per endpoint path status
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EndpointStatusCounts {
+    pub current: usize,
+    pub deprecated: usize,
+    pub removed: usize,
+}
+
+/// Number of Local NIM findings per usage intent
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LocalIntentCounts {
+    pub base_image: usize,
+    pub deploy: usize,
+    pub pull: usize,
+    pub run: usize,
+    pub reference: usize,
+}
+
+/// Number of Hosted NIM findings per API environment
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EnvironmentCounts {
+    pub production: usize,
+    pub staging: usize,
+}
+
+/// Number of findings per policy severity
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SeverityCounts {
+    pub error: usize,
+    pub warning: usize,
+    pub info: usize,
+}
+
+impl SeverityCounts {
+    /// Findings with at least the given severity
+    pub fn at_least(&self, severity: Severity) -> usize {
+        match severity {
+            Severity::Error => self.error,
+            Severity::Warning => self.error + self.warning,
+            Severity::Info => self.error + self.warning + self.info,
+        }
+    }
 }
 
 /// Summary for a single category (source_code or actions_workflow)
@@ -250,6 +1775,21 @@ pub struct AggregatedLocalNim {
     /// Resolved tag if original was 'latest' (from NGC API)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub resolved_tag: Option<String>,
+    /// Human-readable image name (from NGC API, with --enrich-image-meta)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    /// Image publisher (from NGC API, with --enrich-image-meta)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub publisher: Option<String>,
+    /// Distinct usage intents seen across locations
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub intents: Vec<LocalIntent>,
+    /// Distinct usage kinds seen across locations
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub usage_kinds: Vec<UsageKind>,
+    /// Exercised in CI on a schedule (found in a workflow with a `schedule` trigger)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub scheduled_ci: bool,
     /// All locations where this NIM was found
     pub locations: Vec<NimLocation>,
 }
@@ -272,10 +1812,57 @@ pub struct AggregatedHostedNim {
     /// Container image from NGC API
     #[serde(skip_serializing_if = "Option::is_none")]
     pub container_image: Option<String>,
+    /// Inference URL NVCF reports for the function
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub canonical_endpoint: Option<String>,
+    /// Distinct literal max_tokens values seen across locations (--capture-call-params)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub max_tokens: Vec<u64>,
+    /// Distinct invoke styles seen across locations (`unknown` is left out)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub invoke_styles: Vec<InvokeStyle>,
+    /// Exercised in CI on a schedule (found in a workflow with a `schedule` trigger)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub scheduled_ci: bool,
+    /// Whether enrichment resolved this model to an NVCF function (set when enrichment ran)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolution: Option<Resolution>,
+    /// Model publisher (from the model catalog, with --enrich-model-cards)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publisher: Option<String>,
+    /// Input and output modalities of the model (from the model catalog, with --enrich-model-cards)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub modalities: Vec<String>,
+    /// Maximum context length in tokens (from the model catalog, with --enrich-model-cards)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_context: Option<u64>,
     /// All locations where this NIM was found
     pub locations: Vec<NimLocation>,
 }
 
+/// Whether enrichment resolved a Hosted NIM to an NVCF function
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Resolution {
+    /// A function was found
+    Resolved,
+    /// No function now, and none in the baseline either
+    NeverResolved,
+    /// No function now, but the baseline had one: the model was probably retired
+    NewlyUnresolved,
+}
+
+/// A Hosted NIM whose function existed in the baseline but is gone now
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PossibleRetirement {
+    /// Model name (or function ID / endpoint when there is no model name)
+    pub model: String,
+    /// Function the model resolved to in the baseline
+    pub baseline_function_id: String,
+    /// Locations still referencing the model
+    pub locations: Vec<NimLocation>,
+}
+
 /// Aggregated view of all NIM findings grouped by NIM
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AggregatedFindings {
@@ -283,6 +1870,35 @@ pub struct AggregatedFindings {
     pub local_nim: Vec<AggregatedLocalNim>,
     /// All unique Hosted NIMs with their locations
     pub hosted_nim: Vec<AggregatedHostedNim>,
+    /// Local and hosted usage grouped by NIM identity
+    #[serde(default)]
+    pub by_nim: Vec<NimIdentityUsage>,
+}
+
+/// Usage of one NIM, as a self-hosted image and as a hosted model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NimIdentityUsage {
+    /// Canonical NIM identity (e.g. meta/llama-3.1-70b-instruct)
+    pub nim_identity: String,
+    /// Repositories using the NIM in either form
+    pub repositories: Vec<String>,
+    /// Local NIM image usage
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub local: Option<NimKindUsage>,
+    /// Hosted NIM model usage
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hosted: Option<NimKindUsage>,
+}
+
+/// One usage kind (local or hosted) of a NIM identity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NimKindUsage {
+    /// Number of findings
+    pub references: usize,
+    /// Number of repositories with a finding
+    pub repositories: usize,
+    /// Distinct spellings as found (`image:tag` for images, model names for hosted)
+    pub variants: Vec<String>,
 }
 
 // ============================================================================
@@ -303,6 +1919,66 @@ pub struct NgcRepoResponse {
     pub latest_version_id: Option<String>,
     /// Repository description
     pub description: Option<String>,
+    /// Human-readable name
+    #[serde(rename = "displayName")]
+    pub display_name: Option<String>,
+    /// Publisher name
+    pub publisher: Option<String>,
+}
+
+/// One page of an NGC registry repository's images (tags)
+#[derive(Debug, Clone, Deserialize)]
+pub struct NgcImageListResponse {
+    #[serde(default)]
+    pub images: Vec<NgcImage>,
+    #[serde(rename = "paginationInfo")]
+    pub pagination_info: Option<NgcPaginationInfo>,
+}
+
+/// An image in an NGC registry repository
+#[derive(Debug, Clone, Deserialize)]
+pub struct NgcImage {
+    /// Tag of the image (e.g., "1.5.0")
+    pub tag: Option<String>,
+}
+
+/// Paging of an NGC list response
+#[derive(Debug, Clone, Deserialize)]
+pub struct NgcPaginationInfo {
+    /// Total number of pages
+    #[serde(rename = "totalPages")]
+    pub total_pages: Option<usize>,
+}
+
+/// Registry metadata for a Local NIM image, attached to the aggregated view
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LocalImageMeta {
+    /// Human-readable name (e.g., "Llama 3.2 NV EmbedQA 1B v2")
+    pub display_name: Option<String>,
+    /// Publisher name (e.g., "NVIDIA")
+    pub publisher: Option<String>,
+}
+
+impl From<&NgcRepoResponse> for LocalImageMeta {
+    fn from(resp: &NgcRepoResponse) -> Self {
+        Self {
+            display_name: resp.display_name.clone(),
+            publisher: resp.publisher.clone(),
+        }
+    }
+}
+
+/// Model card of a Hosted NIM model from the public model catalog, attached to the aggregated view
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct ModelCard {
+    /// Publisher name (e.g., "Meta")
+    pub publisher: Option<String>,
+    /// Modalities (e.g., "text", "image")
+    #[serde(default)]
+    pub modalities: Vec<String>,
+    /// Maximum context length in tokens
+    #[serde(rename = "maxContext", alias = "max_context")]
+    pub max_context: Option<u64>,
 }
 
 /// Response from NVCF Functions List API
@@ -343,6 +2019,15 @@ pub struct NgcFunctionDetails {
     /// Container image used by the function
     #[serde(rename = "containerImage")]
     pub container_image: Option<String>,
+    /// Model name served by the function (models[0].name in the versions response)
+    #[serde(default)]
+    pub model_name: Option<String>,
+    /// Inference URL of the function (versions response)
+    #[serde(default, rename = "inferenceUrl")]
+    pub inference_url: Option<String>,
+    /// Health check URI of the function (versions response)
+    #[serde(default, rename = "healthUri")]
+    pub health_uri: Option<String>,
 }
 
 // ============================================================================
@@ -353,30 +2038,158 @@ impl ScanReport {
     /// Create a new ScanReport with the given data
     pub fn new(
         total_repos: usize,
-        source_code: NimFindings,
-        actions_workflow: NimFindings,
+        mut source_code: NimFindings,
+        mut actions_workflow: NimFindings,
     ) -> Self {
+        crate::analysis::assign_nim_identities(&mut source_code);
+        crate::analysis::assign_nim_identities(&mut actions_workflow);
+        crate::fingerprint::assign_fingerprints(&mut source_code);
+        crate::fingerprint::assign_fingerprints(&mut actions_workflow);
         let summary = Summary::calculate(&source_code, &actions_workflow);
         let aggregated = AggregatedFindings::from_findings(&source_code, &actions_workflow);
+        let version_skew = crate::analysis::compute_version_skew(&source_code, &actions_workflow);
+        let version_spread = crate::analysis::compute_version_spread(&aggregated.local_nim);
+        let detector_stats = crate::analysis::compute_detector_stats(&source_code, &actions_workflow);
+        let framework_breakdown = crate::analysis::compute_framework_breakdown(&source_code, &actions_workflow);
+        let hotspots = crate::analysis::compute_hotspots(&source_code, &actions_workflow);
         
         Self {
+            schema_version: crate::report::REPORT_SCHEMA_VERSION.to_string(),
             scan_time: chrono::Utc::now().to_rfc3339(),
             total_repos,
             source_code,
             actions_workflow,
             aggregated,
+            version_skew,
+            version_spread,
             summary,
+            delta: None,
+            truncated: Vec::new(),
+            scope: ScanScope::Full,
+            scope_paths: Vec::new(),
+            possible_retirements: Vec::new(),
+            checkouts: Vec::new(),
+            skipped_oversize: Vec::new(),
+            lfs_pointers: Vec::new(),
+            listed_file_errors: Vec::new(),
+            detector_stats,
+            framework_breakdown,
+            hotspots,
+            merged_from: Vec::new(),
+            repository_collisions: Vec::new(),
+            metadata_hints: Vec::new(),
+            panicked: Vec::new(),
+            sampling: None,
+        }
+    }
+}
+
+impl AggregatedHostedNim {
+    /// Key the entry was aggregated under (None for findings with no model, function or endpoint)
+    pub fn aggregation_key(&self) -> Option<&str> {
+        self.model_name.as_deref()
+            .or(self.function_id.as_deref())
+            .or(self.endpoint_url.as_deref())
+    }
+
+    /// Add a location's captured max_tokens to the distinct values (kept sorted)
+    fn record_call_params(&mut self, call_params: Option<&serde_json::Value>) {
+        let max_tokens = call_params.and_then(|p| p.get("max_tokens")).and_then(|v| v.as_u64());
+        if let Some(n) = max_tokens {
+            if let Err(pos) = self.max_tokens.binary_search(&n) {
+                self.max_tokens.insert(pos, n);
+            }
+        }
+    }
+
+    /// Add a location's invoke style to the distinct styles (kept sorted; `unknown` is skipped)
+    fn record_invoke_style(&mut self, style: InvokeStyle) {
+        if style == InvokeStyle::Unknown {
+            return;
+        }
+        if let Err(pos) = self.invoke_styles.binary_search(&style) {
+            self.invoke_styles.insert(pos, style);
         }
     }
 }
 
+impl AggregatedLocalNim {
+    /// Add a location's intent to the distinct intents (kept sorted)
+    fn record_intent(&mut self, intent: LocalIntent) {
+        if let Err(pos) = self.intents.binary_search(&intent) {
+            self.intents.insert(pos, intent);
+        }
+    }
+
+    /// Add a location's usage kind to the distinct usage kinds (kept sorted)
+    fn record_usage_kind(&mut self, kind: UsageKind) {
+        if let Err(pos) = self.usage_kinds.binary_search(&kind) {
+            self.usage_kinds.insert(pos, kind);
+        }
+    }
+}
+
+impl LocalNimMatch {
+    /// How NGC enrichment went for this finding
+    pub fn enrichment_outcome(&self) -> EnrichmentOutcome {
+        EnrichmentOutcome::of(self.enriched, self.enrichment_error)
+    }
+
+    /// Fingerprint over repository, file, NIM identity, tag and detection rule
+    pub fn fingerprint(&self) -> String {
+        crate::fingerprint::fingerprint(&crate::fingerprint::FingerprintFields {
+            nim_type: "local_nim",
+            repository: &self.repository,
+            file_path: &self.file_path,
+            nim_identity: &crate::analysis::nim_identity_for_image(&self.image_url),
+            tag_or_model: &self.tag,
+            detection_rule: &self.detection_rule,
+        })
+    }
+}
+
+impl HostedNimMatch {
+    /// How NGC enrichment went for this finding
+    pub fn enrichment_outcome(&self) -> EnrichmentOutcome {
+        EnrichmentOutcome::of(self.enriched, self.enrichment_error)
+    }
+
+    /// Key this finding is aggregated under: model_name, else function_id, else endpoint_url
+    pub fn aggregation_key(&self) -> String {
+        self.model_name.clone()
+            .or_else(|| self.function_id.clone())
+            .or_else(|| self.endpoint_url.clone())
+            .unwrap_or_else(|| format!("unknown-{}", self.line_number))
+    }
+
+    /// Fingerprint over repository, file, NIM identity, model (else function ID,
+    /// else endpoint) and detection rule
+    pub fn fingerprint(&self) -> String {
+        let nim_identity = self.model_name.as_deref()
+            .map(crate::analysis::nim_identity_for_model)
+            .unwrap_or_default();
+        let target = self.model_name.as_deref()
+            .or(self.function_id.as_deref())
+            .or(self.endpoint_url.as_deref())
+            .unwrap_or_default();
+        crate::fingerprint::fingerprint(&crate::fingerprint::FingerprintFields {
+            nim_type: "hosted_nim",
+            repository: &self.repository,
+            file_path: &self.file_path,
+            nim_identity: &nim_identity,
+            tag_or_model: target,
+            detection_rule: &self.detection_rule,
+        })
+    }
+}
+
 impl AggregatedFindings {
     /// Create aggregated view from source_code and actions_workflow findings
+    ///
+    /// Entries are ordered by their key, so the same findings always give the same report.
     pub fn from_findings(source_code: &NimFindings, actions_workflow: &NimFindings) -> Self {
-        use std::collections::HashMap;
-        
         // Aggregate Local NIMs by (image_url, tag)
-        let mut local_map: HashMap<(String, String), AggregatedLocalNim> = HashMap::new();
+        let mut local_map: BTreeMap<(String, String), AggregatedLocalNim> = BTreeMap::new();
         
         for m in &source_code.local_nim {
             let key = (m.image_url.clone(), m.tag.clone());
@@ -384,8 +2197,15 @@ impl AggregatedFindings {
                 image_url: m.image_url.clone(),
                 tag: m.tag.clone(),
                 resolved_tag: m.resolved_tag.clone(),
+                display_name: None,
+                publisher: None,
+                intents: Vec::new(),
+                usage_kinds: Vec::new(),
+                scheduled_ci: false,
                 locations: Vec::new(),
             });
+            entry.record_intent(m.intent);
+            entry.record_usage_kind(m.usage_kind);
             entry.locations.push(NimLocation {
                 source_type: "source_code".to_string(),
                 repository: m.repository.clone(),
@@ -401,8 +2221,16 @@ impl AggregatedFindings {
                 image_url: m.image_url.clone(),
                 tag: m.tag.clone(),
                 resolved_tag: m.resolved_tag.clone(),
+                display_name: None,
+                publisher: None,
+                intents: Vec::new(),
+                usage_kinds: Vec::new(),
+                scheduled_ci: false,
                 locations: Vec::new(),
             });
+            entry.record_intent(m.intent);
+            entry.record_usage_kind(m.usage_kind);
+            entry.scheduled_ci |= crate::scanner::has_schedule_trigger(&m.workflow_triggers);
             entry.locations.push(NimLocation {
                 source_type: "actions_workflow".to_string(),
                 repository: m.repository.clone(),
@@ -412,22 +2240,30 @@ impl AggregatedFindings {
             });
         }
         
-        // Aggregate Hosted NIMs by model_name (or endpoint_url if no model)
-        let mut hosted_map: HashMap<String, AggregatedHostedNim> = HashMap::new();
+        // Aggregate Hosted NIMs by model_name (or function_id / endpoint_url if no model)
+        let mut hosted_map: BTreeMap<String, AggregatedHostedNim> = BTreeMap::new();
         
         for m in &source_code.hosted_nim {
-            let key = m.model_name.clone()
-                .or_else(|| m.endpoint_url.clone())
-                .unwrap_or_else(|| format!("unknown-{}", m.line_number));
-            
+            let key = m.aggregation_key();
+
             let entry = hosted_map.entry(key).or_insert_with(|| AggregatedHostedNim {
                 endpoint_url: m.endpoint_url.clone(),
                 model_name: m.model_name.clone(),
                 function_id: m.function_id.clone(),
                 status: m.status.clone(),
                 container_image: m.container_image.clone(),
+                canonical_endpoint: m.canonical_endpoint.clone(),
+                max_tokens: Vec::new(),
+                invoke_styles: Vec::new(),
+                scheduled_ci: false,
+                resolution: None,
+                publisher: None,
+                modalities: Vec::new(),
+                max_context: None,
                 locations: Vec::new(),
             });
+            entry.record_call_params(m.call_params.as_ref());
+            entry.record_invoke_style(m.invoke_style);
             entry.locations.push(NimLocation {
                 source_type: "source_code".to_string(),
                 repository: m.repository.clone(),
@@ -438,18 +2274,27 @@ impl AggregatedFindings {
         }
         
         for m in &actions_workflow.hosted_nim {
-            let key = m.model_name.clone()
-                .or_else(|| m.endpoint_url.clone())
-                .unwrap_or_else(|| format!("unknown-{}", m.line_number));
-            
+            let key = m.aggregation_key();
+
             let entry = hosted_map.entry(key).or_insert_with(|| AggregatedHostedNim {
                 endpoint_url: m.endpoint_url.clone(),
                 model_name: m.model_name.clone(),
                 function_id: m.function_id.clone(),
                 status: m.status.clone(),
                 container_image: m.container_image.clone(),
+                canonical_endpoint: m.canonical_endpoint.clone(),
+                max_tokens: Vec::new(),
+                invoke_styles: Vec::new(),
+                scheduled_ci: false,
+                resolution: None,
+                publisher: None,
+                modalities: Vec::new(),
+                max_context: None,
                 locations: Vec::new(),
             });
+            entry.record_call_params(m.call_params.as_ref());
+            entry.record_invoke_style(m.invoke_style);
+            entry.scheduled_ci |= crate::scanner::has_schedule_trigger(&m.workflow_triggers);
             entry.locations.push(NimLocation {
                 source_type: "actions_workflow".to_string(),
                 repository: m.repository.clone(),
@@ -462,13 +2307,75 @@ impl AggregatedFindings {
         Self {
             local_nim: local_map.into_values().collect(),
             hosted_nim: hosted_map.into_values().collect(),
+            by_nim: crate::analysis::compute_nim_usage(source_code, actions_workflow),
+        }
+    }
+}
+
+impl AggregatedFindings {
+    /// Attach registry metadata (keyed by image_url) to aggregated Local NIM entries
+    pub fn apply_image_meta(&mut self, meta: &HashMap<String, LocalImageMeta>) {
+        for entry in &mut self.local_nim {
+            if let Some(m) = meta.get(&entry.image_url) {
+                entry.display_name = m.display_name.clone();
+                entry.publisher = m.publisher.clone();
+            }
+        }
+    }
+
+    /// Attach model cards (keyed by model name) to aggregated Hosted NIM entries
+    pub fn apply_model_cards(&mut self, cards: &HashMap<String, ModelCard>) {
+        for entry in &mut self.hosted_nim {
+            if let Some(card) = entry.model_name.as_ref().and_then(|model| cards.get(model)) {
+                entry.publisher = card.publisher.clone();
+                entry.modalities = card.modalities.clone();
+                entry.max_context = card.max_context;
+            }
+        }
+    }
+
+    /// Aggregated Hosted NIMs per modality of their model card
+    pub fn modality_counts(&self) -> BTreeMap<String, usize> {
+        let mut counts = BTreeMap::new();
+        for entry in &self.hosted_nim {
+            for modality in &entry.modalities {
+                *counts.entry(modality.clone()).or_insert(0) += 1;
+            }
         }
+        counts
+    }
+
+    /// Look up the display name for an image URL, if enrichment provided one
+    pub fn local_display_name(&self, image_url: &str) -> Option<&str> {
+        self.local_nim
+            .iter()
+            .find(|e| e.image_url == image_url)
+            .and_then(|e| e.display_name.as_deref())
     }
 }
 
 impl Summary {
-    /// Calculate summary statistics from findings
+    /// Calculate summary statistics from findings, leaving out fixture findings
     pub fn calculate(source_code: &NimFindings, actions_workflow: &NimFindings) -> Self {
+        let fixtures = source_code.local_nim.iter().chain(&actions_workflow.local_nim).filter(|m| m.fixture).count()
+            + source_code.hosted_nim.iter().chain(&actions_workflow.hosted_nim).filter(|m| m.fixture).count();
+        let fixture_dependencies = source_code.sdk_dependencies.iter().chain(&actions_workflow.sdk_dependencies).any(|m| m.fixture);
+        if fixtures == 0 && !fixture_dependencies {
+            return Self::calculate_all(source_code, actions_workflow);
+        }
+        let counted = |findings: &NimFindings| NimFindings {
+            local_nim: findings.local_nim.iter().filter(|m| !m.fixture).cloned().collect(),
+            hosted_nim: findings.hosted_nim.iter().filter(|m| !m.fixture).cloned().collect(),
+            sdk_dependencies: findings.sdk_dependencies.iter().filter(|m| !m.fixture).cloned().collect(),
+        };
+        Self {
+            fixtures_excluded: fixtures,
+            ..Self::calculate_all(&counted(source_code), &counted(actions_workflow))
+        }
+    }
+
+    /// Calculate summary statistics from every finding, fixtures included (--include-fixtures)
+    pub fn calculate_all(source_code: &NimFindings, actions_workflow: &NimFindings) -> Self {
         use std::collections::HashSet;
         
         // Collect all unique repositories
@@ -487,6 +2394,126 @@ impl Summary {
             repos.insert(&m.repository);
         }
         
+        let mut severity = SeverityCounts::default();
+        let verdicts = source_code.local_nim.iter().map(|m| &m.policy)
+            .chain(source_code.hosted_nim.iter().map(|m| &m.policy))
+            .chain(actions_workflow.local_nim.iter().map(|m| &m.policy))
+            .chain(actions_workflow.hosted_nim.iter().map(|m| &m.policy));
+        for verdict in verdicts.flatten() {
+            match verdict.severity {
+                Severity::Error => severity.error += 1,
+                Severity::Warning => severity.warning += 1,
+                Severity::Info => severity.info += 1,
+            }
+        }
+        
+        let mut local_intent = LocalIntentCounts::default();
+        for m in source_code.local_nim.iter().chain(&actions_workflow.local_nim) {
+            match m.intent {
+                LocalIntent::BaseImage => local_intent.base_image += 1,
+                LocalIntent::Deploy => local_intent.deploy += 1,
+                LocalIntent::Pull => local_intent.pull += 1,
+                LocalIntent::Run => local_intent.run += 1,
+                LocalIntent::Reference => local_intent.reference += 1,
+            }
+        }
+        
+        let mut file_kind = FileKindCounts::default();
+        let kinds = source_code.local_nim.iter().map(|m| m.file_kind)
+            .chain(source_code.hosted_nim.iter().map(|m| m.file_kind))
+            .chain(actions_workflow.local_nim.iter().map(|m| m.file_kind))
+            .chain(actions_workflow.hosted_nim.iter().map(|m| m.file_kind));
+        for kind in kinds {
+            match kind {
+                FileKind::Dockerfile => file_kind.dockerfile += 1,
+                FileKind::Compose => file_kind.compose += 1,
+                FileKind::Kubernetes => file_kind.kubernetes += 1,
+                FileKind::Helm => file_kind.helm += 1,
+                FileKind::Notebook => file_kind.notebook += 1,
+                FileKind::Docs => file_kind.docs += 1,
+                FileKind::Ci => file_kind.ci += 1,
+                FileKind::Source => file_kind.source += 1,
+            }
+        }
+        
+        let mut environment = EnvironmentCounts::default();
+        let mut invoke_style = InvokeStyleCounts::default();
+        let mut policy_violations = BTreeMap::new();
+        for m in source_code.hosted_nim.iter().chain(&actions_workflow.hosted_nim) {
+            if m.model_policy.is_some() {
+                *policy_violations.entry(m.repository.clone()).or_insert(0) += 1;
+            }
+            match m.environment {
+                Environment::Production => environment.production += 1,
+                Environment::Staging => environment.staging += 1,
+            }
+            match m.invoke_style {
+                InvokeStyle::OpenaiCompatible => invoke_style.openai_compatible += 1,
+                InvokeStyle::ModelPath => invoke_style.model_path += 1,
+                InvokeStyle::NvcfPexec => invoke_style.nvcf_pexec += 1,
+                InvokeStyle::Unknown => invoke_style.unknown += 1,
+            }
+        }
+        
+        // Same keys as the aggregated view, so this matches its `scheduled_ci` entries
+        let scheduled_local: HashSet<(&str, &str)> = actions_workflow.local_nim.iter()
+            .filter(|m| crate::scanner::has_schedule_trigger(&m.workflow_triggers))
+            .map(|m| (m.image_url.as_str(), m.tag.as_str()))
+            .collect();
+        let scheduled_hosted: HashSet<String> = actions_workflow.hosted_nim.iter()
+            .filter(|m| crate::scanner::has_schedule_trigger(&m.workflow_triggers))
+            .map(HostedNimMatch::aggregation_key)
+            .collect();
+        
+        let identities: HashSet<String> = source_code.local_nim.iter()
+            .chain(&actions_workflow.local_nim)
+            .map(|m| crate::analysis::nim_identity_for_image(&m.image_url))
+            .chain(source_code.hosted_nim.iter()
+                .chain(&actions_workflow.hosted_nim)
+                .filter_map(|m| m.model_name.as_deref())
+                .map(crate::analysis::nim_identity_for_model))
+            .collect();
+        
+        let enrichment_skipped = source_code.local_nim.iter().map(|m| m.enrichment_skipped)
+            .chain(source_code.hosted_nim.iter().map(|m| m.enrichment_skipped))
+            .chain(actions_workflow.local_nim.iter().map(|m| m.enrichment_skipped))
+            .chain(actions_workflow.hosted_nim.iter().map(|m| m.enrichment_skipped))
+            .flatten()
+            .count();
+        let browser_exposed_endpoints = source_code.hosted_nim.iter()
+            .chain(&actions_workflow.hosted_nim)
+            .filter(|m| m.browser_exposed)
+            .count();
+        let mut credential_sources = BTreeMap::new();
+        for m in source_code.hosted_nim.iter().chain(&actions_workflow.hosted_nim) {
+            if let Some(ref source) = m.credential_source {
+                *credential_sources.entry(source.label()).or_insert(0) += 1;
+            }
+        }
+        
+        let endpoint_mismatches = source_code.hosted_nim.iter()
+            .chain(&actions_workflow.hosted_nim)
+            .filter(|m| m.endpoint_mismatch)
+            .count();
+        let mut endpoint_status = EndpointStatusCounts::default();
+        for m in source_code.hosted_nim.iter().chain(&actions_workflow.hosted_nim) {
+            match m.endpoint_status {
+                Some(EndpointStatus::Current) => endpoint_status.current += 1,
+                Some(EndpointStatus::Deprecated) => endpoint_status.deprecated += 1,
+                Some(EndpointStatus::Removed) => endpoint_status.removed += 1,
+                None => {}
+            }
+        }
+        
+        let mut sdk_repos: HashSet<(String, &str)> = HashSet::new();
+        for m in source_code.sdk_dependencies.iter().chain(&actions_workflow.sdk_dependencies) {
+            sdk_repos.insert((m.package_name.to_lowercase(), &m.repository));
+        }
+        let mut sdk_dependencies = BTreeMap::new();
+        for (package, _) in sdk_repos {
+            *sdk_dependencies.entry(package).or_insert(0) += 1;
+        }
+        
         Self {
             total_local_nim: source_code.local_nim.len() + actions_workflow.local_nim.len(),
             total_hosted_nim: source_code.hosted_nim.len() + actions_workflow.hosted_nim.len(),
@@ -499,6 +2526,23 @@ impl Summary {
                 local_nim: actions_workflow.local_nim.len(),
                 hosted_nim: actions_workflow.hosted_nim.len(),
             },
+            severity,
+            environment,
+            invoke_style,
+            local_intent,
+            file_kind,
+            policy_violations,
+            scheduled_ci_nims: scheduled_local.len() + scheduled_hosted.len(),
+            resolution: ResolutionCounts::default(),
+            modality: BTreeMap::new(),
+            distinct_nims: identities.len(),
+            enrichment_skipped,
+            browser_exposed_endpoints,
+            credential_sources,
+            endpoint_mismatches,
+            endpoint_status,
+            fixtures_excluded: 0,
+            sdk_dependencies,
         }
     }
 }
@@ -524,6 +2568,7 @@ mod tests {
         let defaults = Defaults {
             branch: "develop".to_string(),
             depth: 5,
+            ..Default::default()
         };
         
         let config = RepoConfig {
@@ -532,6 +2577,7 @@ mod tests {
             branch: None,
             depth: None,
             enabled: true,
+            ..Default::default()
         };
         
         let config = config.with_defaults(&defaults);
@@ -555,12 +2601,31 @@ mod tests {
                     image_url: "nvcr.io/nim/nvidia/test".to_string(),
                     tag: "1.0.0".to_string(),
                     resolved_tag: None,
+                    tag_exists: None,
                     file_path: "Dockerfile".to_string(),
                     line_number: 1,
                     match_context: "FROM nvcr.io/nim/nvidia/test:1.0.0".to_string(),
+                    policy: None,
+                    enrichment_error: None,
+                    enriched: false,
+                    detection_rule: String::new(),
+                    workflow_triggers: Vec::new(),
+                    nim_identity: String::new(),
+                    enrichment_skipped: None,
+                    context_truncated: None,
+                    compose: None,
+                    intent: LocalIntent::Reference,
+                    usage_kind: UsageKind::Other,
+                    blame: None,
+                    notebook_cell: None,
+                    file_kind: FileKind::Source,
+                    resolved_from: None,
+                    fixture: false,
+                    fingerprint: String::new(),
                 },
             ],
             hosted_nim: vec![],
+            sdk_dependencies: vec![],
         };
         
         let actions_workflow = NimFindings {
@@ -576,8 +2641,36 @@ mod tests {
                     function_id: None,
                     status: None,
                     container_image: None,
+                    environment: Environment::Production,
+                    endpoint_host: None,
+                    invoke_style: InvokeStyle::Unknown,
+                    framework: Framework::Unknown,
+                    call_params: None,
+                    policy: None,
+                    model_policy: None,
+                    enrichment_error: None,
+                    enriched: false,
+                    detection_rule: String::new(),
+                    workflow_triggers: Vec::new(),
+                    nim_identity: None,
+                    enrichment_skipped: None,
+                    browser_exposed: false,
+                    did_you_mean: Vec::new(),
+                    credential_source: None,
+                    context_truncated: None,
+                    canonical_endpoint: None,
+                    endpoint_mismatch: false,
+                    endpoint_status: None,
+                    suggested_endpoint: None,
+                    blame: None,
+                    notebook_cell: None,
+                    file_kind: FileKind::Source,
+                    resolved_from: None,
+                    fixture: false,
+                    fingerprint: String::new(),
                 },
             ],
+            sdk_dependencies: vec![],
         };
         
         let summary = Summary::calculate(&source_code, &actions_workflow);
@@ -587,4 +2680,491 @@ mod tests {
         assert_eq!(summary.source_code.local_nim, 1);
         assert_eq!(summary.actions_workflow.hosted_nim, 1);
     }
+
+    #[test]
+    fn test_image_meta_propagates_to_aggregate_json() {
+        // Shape of GET /v2/org/nim/team/{team}/repos/{model}
+        let registry_json = r#"{
+            "name": "llama-3.2-nv-embedqa-1b-v2",
+            "displayName": "Llama 3.2 NV EmbedQA 1B v2",
+            "publisher": "NVIDIA",
+            "latestTag": "1.10.0",
+            "description": "Embedding model"
+        }"#;
+        let resp: NgcRepoResponse = serde_json::from_str(registry_json).unwrap();
+
+        let mut meta = HashMap::new();
+        meta.insert(
+            "nvcr.io/nim/nvidia/llama-3.2-nv-embedqa-1b-v2".to_string(),
+            LocalImageMeta::from(&resp),
+        );
+
+        let source_code = NimFindings {
+            local_nim: vec![
+                LocalNimMatch {
+                    repository: "repo1".to_string(),
+                    image_url: "nvcr.io/nim/nvidia/llama-3.2-nv-embedqa-1b-v2".to_string(),
+                    tag: "1.10.0".to_string(),
+                    resolved_tag: None,
+                    tag_exists: None,
+                    file_path: "docker-compose.yaml".to_string(),
+                    line_number: 3,
+                    match_context: "image: nvcr.io/nim/nvidia/llama-3.2-nv-embedqa-1b-v2:1.10.0".to_string(),
+                    policy: None,
+                    enrichment_error: None,
+                    enriched: false,
+                    detection_rule: String::new(),
+                    workflow_triggers: Vec::new(),
+                    nim_identity: String::new(),
+                    enrichment_skipped: None,
+                    context_truncated: None,
+                    compose: None,
+                    intent: LocalIntent::Reference,
+                    usage_kind: UsageKind::Other,
+                    blame: None,
+                    notebook_cell: None,
+                    file_kind: FileKind::Source,
+                    resolved_from: None,
+                    fixture: false,
+                    fingerprint: String::new(),
+                },
+            ],
+            hosted_nim: vec![],
+            sdk_dependencies: vec![],
+        };
+
+        let mut report = ScanReport::new(1, source_code, NimFindings::default());
+        report.aggregated.apply_image_meta(&meta);
+
+        let json = serde_json::to_value(&report).unwrap();
+        let entry = &json["aggregated"]["local_nim"][0];
+        assert_eq!(entry["display_name"], "Llama 3.2 NV EmbedQA 1B v2");
+        assert_eq!(entry["publisher"], "NVIDIA");
+        // Per-location structs stay lean
+        assert!(json["source_code"]["local_nim"][0].get("display_name").is_none());
+        assert_eq!(
+            report.aggregated.local_display_name("nvcr.io/nim/nvidia/llama-3.2-nv-embedqa-1b-v2"),
+            Some("Llama 3.2 NV EmbedQA 1B v2")
+        );
+    }
+
+    #[test]
+    fn test_aggregate_distinct_max_tokens() {
+        let hosted = |line_number: usize, call_params: Option<serde_json::Value>| HostedNimMatch {
+            repository: "repo1".to_string(),
+            endpoint_url: None,
+            model_name: Some("meta/llama-3.1-8b-instruct".to_string()),
+            file_path: "client.py".to_string(),
+            line_number,
+            match_context: "model=\"meta/llama-3.1-8b-instruct\"".to_string(),
+            function_id: None,
+            status: None,
+            container_image: None,
+            environment: Environment::Production,
+            endpoint_host: None,
+            invoke_style: InvokeStyle::Unknown,
+            framework: Framework::Unknown,
+            call_params,
+            policy: None,
+            model_policy: None,
+            enrichment_error: None,
+            enriched: false,
+            detection_rule: String::new(),
+            workflow_triggers: Vec::new(),
+            nim_identity: None,
+            enrichment_skipped: None,
+            browser_exposed: false,
+            did_you_mean: Vec::new(),
+            credential_source: None,
+            context_truncated: None,
+            canonical_endpoint: None,
+            endpoint_mismatch: false,
+            endpoint_status: None,
+            suggested_endpoint: None,
+            blame: None,
+            notebook_cell: None,
+            file_kind: FileKind::Source,
+            resolved_from: None,
+            fixture: false,
+            fingerprint: String::new(),
+        };
+        let source_code = NimFindings {
+            local_nim: vec![],
+            hosted_nim: vec![
+                hosted(1, Some(serde_json::json!({"max_tokens": 1024, "stream": true}))),
+                hosted(2, Some(serde_json::json!({"max_tokens": 256}))),
+                hosted(3, Some(serde_json::json!({"max_tokens": 1024}))),
+                hosted(4, None),
+            ],
+            sdk_dependencies: vec![],
+        };
+
+        let report = ScanReport::new(1, source_code, NimFindings::default());
+        assert_eq!(report.aggregated.hosted_nim.len(), 1);
+        assert_eq!(report.aggregated.hosted_nim[0].max_tokens, vec![256, 1024]);
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["source_code"]["hosted_nim"][0]["call_params"]["stream"], true);
+        assert!(json["source_code"]["hosted_nim"][3].get("call_params").is_none());
+    }
+
+    #[test]
+    fn test_policy_verdict_in_json_and_summary() {
+        let local = |tag: &str, severity: Option<Severity>| LocalNimMatch {
+            repository: "repo1".to_string(),
+            image_url: "nvcr.io/nim/meta/llama-3.1-8b-instruct".to_string(),
+            tag: tag.to_string(),
+            resolved_tag: None,
+            tag_exists: None,
+            file_path: "docker-compose.yaml".to_string(),
+            line_number: 3,
+            match_context: "image: nvcr.io/nim/meta/llama-3.1-8b-instruct".to_string(),
+            policy: severity.map(|severity| PolicyVerdict { severity, policy_rule: "rule".to_string() }),
+            enrichment_error: None,
+            enriched: false,
+            detection_rule: String::new(),
+            workflow_triggers: Vec::new(),
+            nim_identity: String::new(),
+            enrichment_skipped: None,
+            context_truncated: None,
+            compose: None,
+            intent: LocalIntent::Reference,
+            usage_kind: UsageKind::Other,
+            blame: None,
+            notebook_cell: None,
+            file_kind: FileKind::Source,
+            resolved_from: None,
+            fixture: false,
+            fingerprint: String::new(),
+        };
+        let source_code = NimFindings {
+            local_nim: vec![local("latest", Some(Severity::Error)), local("1.3.0", Some(Severity::Info)), local("1.2.0", None)],
+            hosted_nim: vec![],
+            sdk_dependencies: vec![],
+        };
+
+        let report = ScanReport::new(1, source_code, NimFindings::default());
+        assert_eq!(report.summary.severity.error, 1);
+        assert_eq!(report.summary.severity.info, 1);
+        assert_eq!(report.summary.severity.at_least(Severity::Warning), 1);
+        assert_eq!(report.summary.severity.at_least(Severity::Info), 2);
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["source_code"]["local_nim"][0]["severity"], "error");
+        assert_eq!(json["source_code"]["local_nim"][0]["policy_rule"], "rule");
+        assert!(json["source_code"]["local_nim"][2].get("severity").is_none());
+
+        let back: ScanReport = serde_json::from_value(json).unwrap();
+        assert_eq!(back.source_code.local_nim[0].policy.as_ref().map(|p| p.severity), Some(Severity::Error));
+        assert!(back.source_code.local_nim[2].policy.is_none());
+    }
+
+    #[test]
+    fn test_summary_environment_counts() {
+        let hosted = |environment: Environment| HostedNimMatch {
+            repository: "repo1".to_string(),
+            endpoint_url: Some("https://api.stg.nvidia.com/v1".to_string()),
+            model_name: None,
+            file_path: "client.py".to_string(),
+            line_number: 1,
+            match_context: "base_url=\"https://api.stg.nvidia.com/v1\"".to_string(),
+            function_id: None,
+            status: None,
+            container_image: None,
+            environment,
+            endpoint_host: None,
+            invoke_style: InvokeStyle::OpenaiCompatible,
+            framework: Framework::Unknown,
+            call_params: None,
+            policy: None,
+            model_policy: None,
+            enrichment_error: None,
+            enriched: false,
+            detection_rule: String::new(),
+            workflow_triggers: Vec::new(),
+            nim_identity: None,
+            enrichment_skipped: None,
+            browser_exposed: false,
+            did_you_mean: Vec::new(),
+            credential_source: None,
+            context_truncated: None,
+            canonical_endpoint: None,
+            endpoint_mismatch: false,
+            endpoint_status: None,
+            suggested_endpoint: None,
+            blame: None,
+            notebook_cell: None,
+            file_kind: FileKind::Source,
+            resolved_from: None,
+            fixture: false,
+            fingerprint: String::new(),
+        };
+        let source_code = NimFindings {
+            local_nim: vec![],
+            hosted_nim: vec![hosted(Environment::Staging), hosted(Environment::Production)],
+            sdk_dependencies: vec![],
+        };
+        let actions_workflow = NimFindings { local_nim: vec![], hosted_nim: vec![hosted(Environment::Staging)], sdk_dependencies: vec![] };
+
+        let report = ScanReport::new(1, source_code, actions_workflow);
+        assert_eq!(report.summary.environment.production, 1);
+        assert_eq!(report.summary.environment.staging, 2);
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["source_code"]["hosted_nim"][0]["environment"], "staging");
+        // Reports written before the field existed still load
+        let mut legacy = json["source_code"]["hosted_nim"][0].clone();
+        legacy.as_object_mut().unwrap().remove("environment");
+        let m: HostedNimMatch = serde_json::from_value(legacy).unwrap();
+        assert_eq!(m.environment, Environment::Production);
+    }
+
+    #[test]
+    fn test_summary_policy_violations() {
+        let hosted = |repository: &str, violation: Option<&str>| HostedNimMatch {
+            repository: repository.to_string(),
+            endpoint_url: None,
+            model_name: Some("deepseek-ai/deepseek-r1".to_string()),
+            file_path: "client.py".to_string(),
+            line_number: 1,
+            match_context: String::new(),
+            function_id: None,
+            status: None,
+            container_image: None,
+            environment: Environment::Production,
+            endpoint_host: None,
+            invoke_style: InvokeStyle::Unknown,
+            framework: Framework::Unknown,
+            call_params: None,
+            policy: None,
+            model_policy: violation.map(|rule| ModelPolicyViolation {
+                policy_violation: true,
+                policy_violation_rule: rule.to_string(),
+            }),
+            enrichment_error: None,
+            enriched: false,
+            detection_rule: String::new(),
+            workflow_triggers: Vec::new(),
+            nim_identity: None,
+            enrichment_skipped: None,
+            browser_exposed: false,
+            did_you_mean: Vec::new(),
+            credential_source: None,
+            context_truncated: None,
+            canonical_endpoint: None,
+            endpoint_mismatch: false,
+            endpoint_status: None,
+            suggested_endpoint: None,
+            blame: None,
+            notebook_cell: None,
+            file_kind: FileKind::Source,
+            resolved_from: None,
+            fixture: false,
+            fingerprint: String::new(),
+        };
+        let source_code = NimFindings {
+            local_nim: vec![],
+            hosted_nim: vec![hosted("repo1", Some("deepseek-ai/*")), hosted("repo2", None)],
+            sdk_dependencies: vec![],
+        };
+        let actions_workflow = NimFindings { local_nim: vec![], hosted_nim: vec![hosted("repo1", Some("not-allowed"))], sdk_dependencies: vec![] };
+
+        let report = ScanReport::new(2, source_code, actions_workflow);
+        assert_eq!(report.summary.policy_violations, BTreeMap::from([("repo1".to_string(), 2)]));
+
+        let json = serde_json::to_value(&report).unwrap();
+        let flagged = &json["source_code"]["hosted_nim"][0];
+        assert_eq!(flagged["policy_violation"], true);
+        assert_eq!(flagged["policy_violation_rule"], "deepseek-ai/*");
+        assert!(json["source_code"]["hosted_nim"][1].get("policy_violation").is_none());
+        let m: HostedNimMatch = serde_json::from_value(flagged.clone()).unwrap();
+        assert_eq!(m.model_policy.unwrap().policy_violation_rule, "deepseek-ai/*");
+    }
+
+    #[test]
+    fn test_scheduled_ci_nims() {
+        let hosted = |model: &str, file_path: &str, triggers: &[&str]| HostedNimMatch {
+            repository: "repo1".to_string(),
+            endpoint_url: None,
+            model_name: Some(model.to_string()),
+            file_path: file_path.to_string(),
+            line_number: 1,
+            match_context: String::new(),
+            function_id: None,
+            status: None,
+            container_image: None,
+            environment: Environment::Production,
+            endpoint_host: None,
+            invoke_style: InvokeStyle::Unknown,
+            framework: Framework::Unknown,
+            call_params: None,
+            policy: None,
+            model_policy: None,
+            enrichment_error: None,
+            enriched: false,
+            detection_rule: String::new(),
+            workflow_triggers: triggers.iter().map(|t| t.to_string()).collect(),
+            nim_identity: None,
+            enrichment_skipped: None,
+            browser_exposed: false,
+            did_you_mean: Vec::new(),
+            credential_source: None,
+            context_truncated: None,
+            canonical_endpoint: None,
+            endpoint_mismatch: false,
+            endpoint_status: None,
+            suggested_endpoint: None,
+            blame: None,
+            notebook_cell: None,
+            file_kind: FileKind::Source,
+            resolved_from: None,
+            fixture: false,
+            fingerprint: String::new(),
+        };
+        let source_code = NimFindings {
+            local_nim: vec![],
+            hosted_nim: vec![hosted("nvidia/manual", "client.py", &[])],
+            sdk_dependencies: vec![],
+        };
+        let actions_workflow = NimFindings {
+            local_nim: vec![],
+            hosted_nim: vec![
+                hosted("nvidia/nightly", ".github/workflows/nightly.yml", &["schedule: 0 2 * * *"]),
+                hosted("nvidia/nightly", ".github/workflows/weekly.yml", &["schedule: 0 0 * * 0"]),
+                hosted("nvidia/manual", ".github/workflows/manual.yml", &["workflow_dispatch"]),
+            ],
+            sdk_dependencies: vec![],
+        };
+
+        let report = ScanReport::new(1, source_code, actions_workflow);
+        assert_eq!(report.summary.scheduled_ci_nims, 1);
+        let scheduled: Vec<(&str, bool)> = {
+            let mut v: Vec<_> = report.aggregated.hosted_nim.iter()
+                .map(|e| (e.model_name.as_deref().unwrap(), e.scheduled_ci))
+                .collect();
+            v.sort();
+            v
+        };
+        assert_eq!(scheduled, vec![("nvidia/manual", false), ("nvidia/nightly", true)]);
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["actions_workflow"]["hosted_nim"][0]["workflow_triggers"][0], "schedule: 0 2 * * *");
+        assert!(json["source_code"]["hosted_nim"][0].get("workflow_triggers").is_none());
+    }
+
+    #[test]
+    fn test_aggregate_invoke_styles() {
+        let hosted = |line_number: usize, invoke_style: InvokeStyle| HostedNimMatch {
+            repository: "repo1".to_string(),
+            endpoint_url: None,
+            model_name: Some("nvidia/llama-3.2-nv-rerankqa-1b-v2".to_string()),
+            file_path: "client.py".to_string(),
+            line_number,
+            match_context: String::new(),
+            function_id: None,
+            status: None,
+            container_image: None,
+            environment: Environment::Production,
+            endpoint_host: None,
+            invoke_style,
+            framework: Framework::Unknown,
+            call_params: None,
+            policy: None,
+            model_policy: None,
+            enrichment_error: None,
+            enriched: false,
+            detection_rule: String::new(),
+            workflow_triggers: Vec::new(),
+            nim_identity: None,
+            enrichment_skipped: None,
+            browser_exposed: false,
+            did_you_mean: Vec::new(),
+            credential_source: None,
+            context_truncated: None,
+            canonical_endpoint: None,
+            endpoint_mismatch: false,
+            endpoint_status: None,
+            suggested_endpoint: None,
+            blame: None,
+            notebook_cell: None,
+            file_kind: FileKind::Source,
+            resolved_from: None,
+            fixture: false,
+            fingerprint: String::new(),
+        };
+        let source_code = NimFindings {
+            local_nim: vec![],
+            hosted_nim: vec![
+                hosted(1, InvokeStyle::NvcfPexec),
+                hosted(2, InvokeStyle::ModelPath),
+                hosted(3, InvokeStyle::Unknown),
+            ],
+            sdk_dependencies: vec![],
+        };
+        let actions_workflow = NimFindings { local_nim: vec![], hosted_nim: vec![hosted(4, InvokeStyle::ModelPath)], sdk_dependencies: vec![] };
+
+        let report = ScanReport::new(1, source_code, actions_workflow);
+        assert_eq!(report.aggregated.hosted_nim.len(), 1);
+        assert_eq!(report.aggregated.hosted_nim[0].invoke_styles, vec![InvokeStyle::ModelPath, InvokeStyle::NvcfPexec]);
+        assert_eq!(report.summary.invoke_style.model_path, 2);
+        assert_eq!(report.summary.invoke_style.nvcf_pexec, 1);
+        assert_eq!(report.summary.invoke_style.unknown, 1);
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["aggregated"]["hosted_nim"][0]["invoke_styles"], serde_json::json!(["model_path", "nvcf_pexec"]));
+        assert_eq!(json["source_code"]["hosted_nim"][0]["invoke_style"], "nvcf_pexec");
+    }
+
+    #[test]
+    fn test_aggregate_local_intents() {
+        let local = |line_number: usize, intent: LocalIntent| LocalNimMatch {
+            repository: "repo1".to_string(),
+            image_url: "nvcr.io/nim/meta/llama-3.1-8b-instruct".to_string(),
+            tag: "1.3.3".to_string(),
+            resolved_tag: None,
+            tag_exists: None,
+            file_path: "deploy.sh".to_string(),
+            line_number,
+            match_context: String::new(),
+            policy: None,
+            enrichment_error: None,
+            enriched: false,
+            detection_rule: String::new(),
+            workflow_triggers: Vec::new(),
+            nim_identity: String::new(),
+            enrichment_skipped: None,
+            context_truncated: None,
+            compose: None,
+            intent,
+            usage_kind: match intent {
+                LocalIntent::Run => UsageKind::DockerRun,
+                _ => UsageKind::Other,
+            },
+            blame: None,
+            notebook_cell: None,
+            file_kind: FileKind::Source,
+            resolved_from: None,
+            fixture: false,
+            fingerprint: String::new(),
+        };
+        let source_code = NimFindings {
+            local_nim: vec![local(1, LocalIntent::Run), local(2, LocalIntent::Pull), local(3, LocalIntent::Run)],
+            hosted_nim: vec![],
+            sdk_dependencies: vec![],
+        };
+        let actions_workflow = NimFindings { local_nim: vec![local(4, LocalIntent::Reference)], hosted_nim: vec![], sdk_dependencies: vec![] };
+
+        let report = ScanReport::new(1, source_code, actions_workflow);
+        assert_eq!(report.aggregated.local_nim[0].intents, vec![LocalIntent::Pull, LocalIntent::Run, LocalIntent::Reference]);
+        assert_eq!(report.summary.local_intent.run, 2);
+        assert_eq!(report.summary.local_intent.pull, 1);
+        assert_eq!(report.summary.local_intent.reference, 1);
+        assert_eq!(report.summary.local_intent.base_image, 0);
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["aggregated"]["local_nim"][0]["intents"], serde_json::json!(["pull", "run", "reference"]));
+        assert_eq!(json["source_code"]["local_nim"][0]["intent"], "run");
+        assert_eq!(json["aggregated"]["local_nim"][0]["usage_kinds"], serde_json::json!(["docker_run", "other"]));
+        assert_eq!(json["source_code"]["local_nim"][0]["usage_kind"], "docker_run");
+    }
 }
@@ -3,16 +3,25 @@
 //! This module handles API calls to NGC to:
 //! 1. Resolve "latest" tags for Local NIMs
 //! 2. Get Function details for Hosted NIMs
+//! 3. Find the functions running a given container image
+//! 4. List an image's tags, and check pinned tags against them
+//! 5. Fetch model cards of Hosted NIM models from the public model catalog
 
-use std::collections::HashMap;
-use std::time::Duration;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
 use anyhow::{Context, Result, bail};
 use log::{debug, warn, info};
+use rayon::prelude::*;
 use reqwest::blocking::Client;
 use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
 
 use crate::models::{
-    NimFindings, NgcRepoResponse, NgcFunctionListResponse, NgcFunctionDetails,
+    EnrichStage, EnrichmentError, EnrichmentSkip, Environment, HostedNimMatch, LocalImageMeta, LocalNimMatch, ModelCard, NimFindings, NgcImageListResponse, NgcRepoResponse, NgcFunctionListResponse,
+    NgcFunctionDetails, RepoConfig,
 };
 
 // ============================================================================
@@ -21,8 +30,242 @@ use crate::models::{
 
 const NGC_REGISTRY_API_BASE: &str = "https://api.ngc.nvidia.com/v2/org/nim/team";
 const NVCF_API_BASE: &str = "https://api.nvcf.nvidia.com/v2/nvcf";
+/// Public model catalog behind build.nvidia.com; model cards need no API key
+const MODEL_CARD_API_BASE: &str = "https://api.ngc.nvidia.com/v2/endpoints/qc69jvmznzxy/models";
 const REQUEST_TIMEOUT_SECS: u64 = 30;
 const MAX_RETRIES: u32 = 3;
+/// Maximum concurrent /versions requests when fetching details for several functions
+const MAX_CONCURRENT_DETAILS: usize = 4;
+/// Characters of a response body kept in an error
+const BODY_SNIPPET_CHARS: usize = 200;
+/// Tags requested per page when listing an image's tags
+const TAG_PAGE_SIZE: usize = 100;
+/// Pages fetched at most per image (a repository with more tags is cut off)
+const MAX_TAG_PAGES: usize = 50;
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+/// A failed NGC/NVCF HTTP request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NgcHttpError {
+    /// HTTP status (None when no response was received)
+    pub status: Option<u16>,
+    /// Start of the response body, or the transport error
+    pub body_snippet: String,
+    /// Whether retrying could help (429, 5xx, connection errors)
+    pub retriable: bool,
+}
+
+impl NgcHttpError {
+    fn from_response(resp: reqwest::blocking::Response) -> Self {
+        let status = resp.status();
+        let body = resp.text().unwrap_or_default();
+        Self {
+            status: Some(status.as_u16()),
+            body_snippet: body.chars().take(BODY_SNIPPET_CHARS).collect(),
+            retriable: status.as_u16() == 429 || status.is_server_error(),
+        }
+    }
+}
+
+impl fmt::Display for NgcHttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.status {
+            Some(status) => write!(f, "HTTP {}: {}", status, self.body_snippet),
+            None => write!(f, "request failed: {}", self.body_snippet),
+        }
+    }
+}
+
+impl std::error::Error for NgcHttpError {}
+
+/// NGC/NVCF request failure, by what the caller should do about it
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum NgcError {
+    /// 401/403: the API key is invalid or lacks access; no further request will succeed
+    #[error("NGC API key rejected ({0})")]
+    Auth(NgcHttpError),
+    /// 404: this registry repository or function doesn't exist
+    #[error("not found ({0})")]
+    NotFound(NgcHttpError),
+    /// Anything else (server errors after retries, connection failures, ...)
+    #[error("{0}")]
+    Http(NgcHttpError),
+    /// The enrichment budget ran out before the request was sent
+    #[error("enrichment budget exhausted")]
+    Budget,
+}
+
+impl From<NgcHttpError> for NgcError {
+    fn from(error: NgcHttpError) -> Self {
+        match error.status {
+            Some(401 | 403) => NgcError::Auth(error),
+            Some(404) => NgcError::NotFound(error),
+            _ => NgcError::Http(error),
+        }
+    }
+}
+
+/// What to record on a finding whose enrichment failed; None when the API key
+/// was rejected or the budget ran out (enrichment stops and `enrich_all` warns once)
+fn enrichment_error(what: &str, e: &anyhow::Error) -> Option<EnrichmentError> {
+    match e.downcast_ref::<NgcError>() {
+        Some(NgcError::Auth(_) | NgcError::Budget) => None,
+        Some(NgcError::NotFound(_)) => {
+            debug!("{}: not found", what);
+            Some(EnrichmentError::NotFound)
+        }
+        _ => {
+            warn!("{}: {}", what, e);
+            Some(EnrichmentError::Failed)
+        }
+    }
+}
+
+// ============================================================================
+// Enrichment Budget
+// ============================================================================
+
+/// Limits on one enrichment run (--enrichment-budget-secs, --enrichment-max-requests)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EnrichmentBudget {
+    /// Wall-clock time enrichment may take (None: unlimited)
+    pub max_duration: Option<Duration>,
+    /// HTTP requests enrichment may send, retries included (None: unlimited)
+    pub max_requests: Option<usize>,
+}
+
+impl EnrichmentBudget {
+    /// Budget from the CLI values, where 0 means unlimited
+    pub fn from_limits(secs: u64, max_requests: usize) -> Self {
+        Self {
+            max_duration: (secs > 0).then(|| Duration::from_secs(secs)),
+            max_requests: (max_requests > 0).then_some(max_requests),
+        }
+    }
+
+    /// What is left after an earlier client spent `elapsed` and `requests`
+    pub fn remaining(self, elapsed: Duration, requests: usize) -> Self {
+        Self {
+            max_duration: self.max_duration.map(|max| max.saturating_sub(elapsed)),
+            max_requests: self.max_requests.map(|max| max.saturating_sub(requests)),
+        }
+    }
+
+    /// Whether no time or no requests are left
+    pub fn is_spent(self) -> bool {
+        self.max_duration.is_some_and(|max| max.is_zero()) || self.max_requests == Some(0)
+    }
+}
+
+/// What one enrichment run produced
+#[derive(Debug, Default)]
+pub struct EnrichmentRun {
+    /// Registry metadata per Local NIM image (empty without the `image-meta` stage)
+    pub image_meta: HashMap<String, LocalImageMeta>,
+    /// HTTP requests sent, retries included
+    pub requests: usize,
+    /// Whether an API key was rejected (401/403), leaving its findings unenriched
+    pub key_rejected: bool,
+}
+
+/// Positions `(category, index)` to enrich, most-referenced key first
+///
+/// Findings sharing a key stay together so the first lookup fills the cache for
+/// the rest, and a budget is spent on the most-used images and models. Ties keep
+/// scan order.
+fn by_frequency(keyed: Vec<((usize, usize), String)>) -> Vec<(usize, usize)> {
+    let mut counts: HashMap<&str, (usize, usize)> = HashMap::new();
+    for (i, (_, key)) in keyed.iter().enumerate() {
+        counts.entry(key).or_insert((0, i)).0 += 1;
+    }
+    let mut order: Vec<usize> = (0..keyed.len()).collect();
+    order.sort_by_key(|&i| {
+        let (count, first_seen) = counts[keyed[i].1.as_str()];
+        (Reverse(count), first_seen, i)
+    });
+    order.into_iter().map(|i| keyed[i].0).collect()
+}
+
+/// Reject an API key no request could succeed with: empty, only whitespace, or
+/// holding a line break (pasted with a newline)
+pub fn check_api_key(key: &str) -> Result<()> {
+    if key.trim().is_empty() {
+        bail!("NGC API key is empty; set --ngc-api-key, NVIDIA_API_KEY or --ngc-api-key-file");
+    }
+    if key.contains(['\n', '\r']) {
+        bail!("NGC API key contains a line break; check for a pasted newline");
+    }
+    Ok(())
+}
+
+/// Whether a Local NIM finding needs a registry lookup (floating tag)
+fn needs_tag_resolution(m: &LocalNimMatch) -> bool {
+    is_registry_image(m) && (m.tag == "latest" || m.tag.is_empty())
+}
+
+/// Whether a Local NIM finding names a container image (an `ngc registry
+/// resource download-version` names a resource, which the image APIs don't know)
+pub fn is_registry_image(m: &LocalNimMatch) -> bool {
+    m.detection_rule != "ngc_resource_download"
+}
+
+/// Whether a Local NIM finding's tag is checked by the `tags` stage (pinned,
+/// and not an unresolved `${VAR}` reference)
+fn needs_tag_check(m: &LocalNimMatch) -> bool {
+    is_registry_image(m) && !needs_tag_resolution(m) && !m.tag.contains('$') && m.tag_exists.is_none()
+}
+
+/// What a Hosted NIM finding is looked up by (model name, else function ID);
+/// None for staging findings and findings with neither
+fn hosted_lookup_key(m: &HostedNimMatch) -> Option<String> {
+    if m.environment != Environment::Production {
+        return None;
+    }
+    m.model_name.clone().or_else(|| m.function_id.clone())
+}
+
+/// Record the function's inference URL next to the endpoint found in code,
+/// which is kept as written
+fn set_canonical_endpoint(m: &mut HostedNimMatch, inference_url: Option<String>) {
+    m.endpoint_mismatch = match (&m.endpoint_url, &inference_url) {
+        (Some(code), Some(canonical)) => endpoints_disagree(code, canonical),
+        _ => false,
+    };
+    m.canonical_endpoint = inference_url;
+}
+
+/// Host (lowercased, when the URL has one) and path segments of an endpoint
+fn endpoint_parts(url: &str) -> (Option<String>, Vec<&str>) {
+    let url = url.split(['?', '#']).next().unwrap_or("");
+    let (host, path) = match url.split_once("://") {
+        Some((_, rest)) => match rest.split_once('/') {
+            Some((host, path)) => (Some(host.to_lowercase()), path),
+            None => (Some(rest.to_lowercase()), ""),
+        },
+        None => (None, url),
+    };
+    (host, path.split('/').filter(|s| !s.is_empty()).collect())
+}
+
+/// Whether an endpoint in code and the NVCF inference URL point to different places
+///
+/// A different host disagrees. Paths agree when one is a prefix of the other,
+/// so a `/v1` base URL agrees with `/v1/chat/completions`. An inference URL that
+/// is only a path is compared by path.
+fn endpoints_disagree(code: &str, canonical: &str) -> bool {
+    let (code_host, code_path) = endpoint_parts(code);
+    let (canonical_host, canonical_path) = endpoint_parts(canonical);
+    if let (Some(code_host), Some(canonical_host)) = (&code_host, &canonical_host) {
+        if code_host != canonical_host {
+            return true;
+        }
+    }
+    let shared = code_path.len().min(canonical_path.len());
+    code_path[..shared] != canonical_path[..shared]
+}
 
 // ============================================================================
 // NGC Client
@@ -34,17 +277,34 @@ pub struct NgcClient {
     client: Client,
     /// API key
     api_key: String,
-    /// Cache for Local NIM latest tag resolution
-    local_nim_cache: HashMap<String, String>,
+    /// Cache for Local NIM registry repo info (latest tag, display name, publisher)
+    local_nim_cache: HashMap<String, NgcRepoResponse>,
+    /// Cache for Local NIM tag lists, by repository (`team/model`)
+    tag_list_cache: HashMap<String, Vec<String>>,
+    /// Tag-existence checks answered from tag_list_cache instead of a request
+    tag_checks_avoided: usize,
     /// Cache for Hosted NIM function details
     hosted_nim_cache: HashMap<String, NgcFunctionDetails>,
     /// Cached function list
     function_list_cache: Option<Vec<NgcFunctionDetails>>,
+    /// NGC registry API base URL (overridable for tests)
+    registry_api_base: String,
+    /// NVCF API base URL (overridable for tests)
+    nvcf_api_base: String,
+    /// First 401/403 response; once set, no further requests are sent
+    auth_failure: OnceLock<NgcHttpError>,
+    /// Limits on time and requests; once exceeded, no further requests are sent
+    budget: EnrichmentBudget,
+    /// When the budget started counting
+    started: Instant,
+    /// HTTP requests sent so far, retries included
+    requests: AtomicUsize,
 }
 
 impl NgcClient {
-    /// Create a new NGC client
+    /// Create a new NGC client; fails for a key [`check_api_key`] rejects
     pub fn new(api_key: String) -> Result<Self> {
+        check_api_key(&api_key)?;
         let client = Client::builder()
             .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
             .build()
@@ -54,11 +314,83 @@ impl NgcClient {
             client,
             api_key,
             local_nim_cache: HashMap::new(),
+            tag_list_cache: HashMap::new(),
+            tag_checks_avoided: 0,
             hosted_nim_cache: HashMap::new(),
             function_list_cache: None,
+            registry_api_base: NGC_REGISTRY_API_BASE.to_string(),
+            nvcf_api_base: NVCF_API_BASE.to_string(),
+            auth_failure: OnceLock::new(),
+            budget: EnrichmentBudget::default(),
+            started: Instant::now(),
+            requests: AtomicUsize::new(0),
         })
     }
     
+    /// Create a client that talks to the given registry/NVCF base URLs (e.g. a local mock server)
+    #[cfg(test)]
+    pub fn with_base_urls(api_key: String, registry_api_base: &str, nvcf_api_base: &str) -> Result<Self> {
+        let mut client = Self::new(api_key)?;
+        client.client = Client::builder()
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .no_proxy()
+            .build()
+            .context("Failed to create HTTP client")?;
+        client.registry_api_base = registry_api_base.to_string();
+        client.nvcf_api_base = nvcf_api_base.to_string();
+        Ok(client)
+    }
+    
+    /// Limit this client's time and requests; the time budget starts now
+    pub fn with_budget(mut self, budget: EnrichmentBudget) -> Self {
+        self.budget = budget;
+        self.started = Instant::now();
+        self
+    }
+    
+    /// HTTP requests sent so far, retries included
+    pub fn requests_sent(&self) -> usize {
+        self.requests.load(Ordering::SeqCst)
+    }
+    
+    /// Tag-existence checks answered from a cached tag list, each a request not sent
+    pub fn tag_checks_avoided(&self) -> usize {
+        self.tag_checks_avoided
+    }
+    
+    /// Whether the time or request budget has run out
+    pub fn budget_exhausted(&self) -> bool {
+        self.out_of_time() || self.budget.max_requests.is_some_and(|max| self.requests_sent() >= max)
+    }
+    
+    /// Whether the time budget has run out
+    fn out_of_time(&self) -> bool {
+        self.budget.max_duration.is_some_and(|max| self.started.elapsed() >= max)
+    }
+    
+    /// Whether enrichment should stop before the next finding (rejected API key
+    /// or out of time); the request cap is enforced per request, so findings
+    /// answered from the cache are still filled in
+    fn stopped(&self) -> bool {
+        self.auth_failure().is_some() || self.out_of_time()
+    }
+    
+    /// Count one request against the budget; false when none are left
+    fn take_request(&self) -> bool {
+        if self.out_of_time() {
+            return false;
+        }
+        match self.budget.max_requests {
+            Some(max) => self.requests
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| (n < max).then_some(n + 1))
+                .is_ok(),
+            None => {
+                self.requests.fetch_add(1, Ordering::SeqCst);
+                true
+            }
+        }
+    }
+    
     /// Build authorization headers
     fn auth_headers(&self) -> Result<HeaderMap> {
         let mut headers = HeaderMap::new();
@@ -70,12 +402,30 @@ impl NgcClient {
         Ok(headers)
     }
     
+    /// The rejection that stopped all requests, if the API key was rejected
+    pub fn auth_failure(&self) -> Option<&NgcHttpError> {
+        self.auth_failure.get()
+    }
+    
     /// Make a GET request with retries
-    fn get_with_retry(&self, url: &str) -> Result<reqwest::blocking::Response> {
-        let headers = self.auth_headers()?;
+    ///
+    /// Rate limits (429), server errors and connection failures are retried;
+    /// other statuses fail immediately. After a 401/403 every later call fails
+    /// with the same `NgcError::Auth` without touching the network; once the
+    /// budget is spent every attempt fails with `NgcError::Budget`.
+    fn get_with_retry(&self, url: &str) -> Result<reqwest::blocking::Response, NgcError> {
+        if let Some(error) = self.auth_failure.get() {
+            return Err(NgcError::Auth(error.clone()));
+        }
+        let headers = self.auth_headers().map_err(|e| {
+            NgcError::Auth(NgcHttpError { status: None, body_snippet: e.to_string(), retriable: false })
+        })?;
         
         let mut last_error = None;
         for attempt in 1..=MAX_RETRIES {
+            if !self.take_request() {
+                return Err(NgcError::Budget);
+            }
             debug!("GET {} (attempt {})", url, attempt);
             
             match self.client.get(url).headers(headers.clone()).send() {
@@ -83,97 +433,196 @@ impl NgcClient {
                     let status = resp.status();
                     if status.is_success() {
                         return Ok(resp);
-                    } else if status.as_u16() == 429 {
+                    }
+                    let error = NgcHttpError::from_response(resp);
+                    if !error.retriable {
+                        // Client error - don't retry
+                        let error = NgcError::from(error);
+                        if let NgcError::Auth(ref rejected) = error {
+                            let _ = self.auth_failure.set(rejected.clone());
+                        }
+                        return Err(error);
+                    }
+                    if status.as_u16() == 429 {
                         // Rate limited - wait and retry
                         warn!("Rate limited, waiting before retry...");
                         std::thread::sleep(Duration::from_secs(2u64.pow(attempt)));
-                        last_error = Some(format!("Rate limited (429)"));
-                        continue;
-                    } else if status.is_server_error() {
+                    } else {
                         // Server error - retry
                         warn!("Server error {}, retrying...", status);
                         std::thread::sleep(Duration::from_secs(1));
-                        last_error = Some(format!("Server error ({})", status));
-                        continue;
-                    } else {
-                        // Client error - don't retry
-                        bail!("HTTP error {}: {}", status, resp.text().unwrap_or_default());
                     }
+                    last_error = Some(error);
                 }
                 Err(e) => {
                     warn!("Request failed: {}", e);
-                    last_error = Some(e.to_string());
+                    last_error = Some(NgcHttpError { status: None, body_snippet: e.to_string(), retriable: true });
                     std::thread::sleep(Duration::from_secs(1));
                 }
             }
         }
         
-        bail!("Request failed after {} retries: {:?}", MAX_RETRIES, last_error);
+        Err(NgcError::Http(last_error.expect("at least one attempt")))
     }
     
     // ========================================================================
     // Local NIM: Latest Tag Resolution
     // ========================================================================
     
-    /// Parse image URL to extract team and model name
+    /// Parse image URL to extract team and model name, plus any tag or digest
     /// 
-    /// Input: nvcr.io/nim/nvidia/llama-3.2-nv-embedqa-1b-v2
-    /// Output: ("nvidia", "llama-3.2-nv-embedqa-1b-v2")
-    fn parse_image_url(image_url: &str) -> Option<(String, String)> {
-        let stripped = image_url
-            .strip_prefix("nvcr.io/nim/")
-            .or_else(|| image_url.strip_prefix("nvcr.io/nim/"))?;
+    /// Input: nvcr.io/nim/nvidia/llama-3.2-nv-embedqa-1b-v2:1.5.0
+    /// Output: team "nvidia", model "llama-3.2-nv-embedqa-1b-v2", tag "1.5.0"
+    fn parse_image_url(image_url: &str) -> Option<ImageRef> {
+        let stripped = image_url.strip_prefix("nvcr.io/nim/")?;
+        
+        // Digest first: it contains a ':' of its own (@sha256:...)
+        let (name, digest) = match stripped.split_once('@') {
+            Some((name, digest)) => (name, Some(digest)),
+            None => (stripped, None),
+        };
+        let (name, tag) = match name.split_once(':') {
+            Some((name, tag)) => (name, Some(tag)),
+            None => (name, None),
+        };
+        if tag.is_some_and(|t| t.is_empty() || t.contains('/'))
+            || digest.is_some_and(|d| !d.contains(':') || d.contains('/'))
+        {
+            return None;
+        }
         
-        let parts: Vec<&str> = stripped.split('/').collect();
-        if parts.len() >= 2 {
-            Some((parts[0].to_string(), parts[1].to_string()))
-        } else {
-            None
+        // Exactly <team>/<model>; anything deeper is not a NIM repository
+        let parts: Vec<&str> = name.split('/').collect();
+        match parts.as_slice() {
+            [team, model] if !team.is_empty() && !model.is_empty() => Some(ImageRef {
+                team: team.to_string(),
+                model: model.to_string(),
+                tag: tag.map(|t| t.to_string()),
+                digest: digest.map(|d| d.to_string()),
+            }),
+            _ => None,
         }
     }
     
-    /// Resolve latest tag for a Local NIM image
-    pub fn resolve_latest_tag(&mut self, image_url: &str) -> Result<String> {
+    /// Error for an image URL parse_image_url can't handle
+    fn invalid_image_url(image_url: &str) -> anyhow::Error {
+        anyhow::anyhow!(
+            "Invalid image URL format: {}. Expected: nvcr.io/nim/<team>/<model>[:<tag>][@sha256:<digest>]",
+            image_url
+        )
+    }
+    
+    /// Fetch registry repo info for a Local NIM image (cached per image)
+    fn fetch_repo_info(&mut self, image_url: &str) -> Result<NgcRepoResponse> {
         // Check cache
-        if let Some(tag) = self.local_nim_cache.get(image_url) {
+        if let Some(info) = self.local_nim_cache.get(image_url) {
             debug!("Cache hit for {}", image_url);
-            return Ok(tag.clone());
+            return Ok(info.clone());
         }
         
         // Parse image URL
-        let (team, model) = Self::parse_image_url(image_url)
-            .context(format!("Failed to parse image URL: {}", image_url))?;
+        let image = Self::parse_image_url(image_url)
+            .ok_or_else(|| Self::invalid_image_url(image_url))?;
         
-        // Build API URL
-        let url = format!("{}/{}/repos/{}", NGC_REGISTRY_API_BASE, team, model);
-        debug!("Resolving latest tag for {}: {}", image_url, url);
+        // Build API URL (repository level; any tag/digest is not part of it)
+        let url = format!("{}/{}/repos/{}", self.registry_api_base, image.team, image.model);
+        debug!("Fetching repo info for {}: {}", image_url, url);
         
         // Make request
         let resp = self.get_with_retry(&url)?;
         let repo_info: NgcRepoResponse = resp.json()
             .context("Failed to parse NGC repo response")?;
         
+        // Cache result
+        self.local_nim_cache.insert(image_url.to_string(), repo_info.clone());
+        Ok(repo_info)
+    }
+    
+    /// Resolve latest tag for a Local NIM image
+    pub fn resolve_latest_tag(&mut self, image_url: &str) -> Result<String> {
+        let repo_info = self.fetch_repo_info(image_url)?;
+        
         let latest_tag = repo_info.latest_tag
             .ok_or_else(|| anyhow::anyhow!("No latestTag in response for {}", image_url))?;
         
-        // Cache result
-        self.local_nim_cache.insert(image_url.to_string(), latest_tag.clone());
-        
         info!("Resolved {} latest tag: {}", image_url, latest_tag);
         Ok(latest_tag)
     }
     
+    /// Fetch display name and publisher for a Local NIM image
+    pub fn fetch_image_meta(&mut self, image_url: &str) -> Result<LocalImageMeta> {
+        let repo_info = self.fetch_repo_info(image_url)?;
+        Ok(LocalImageMeta::from(&repo_info))
+    }
+    
+    // ========================================================================
+    // Local NIM: Tag Lists
+    // ========================================================================
+    
+    /// List every tag of a Local NIM image (cached per repository; any tag in
+    /// `image_url` is ignored)
+    ///
+    /// API: GET {registry}/{team}/repos/{model}/images?page-size=..&page-number=..
+    /// The pages are fetched in order until `paginationInfo.totalPages` or an empty page.
+    pub fn list_tags(&mut self, image_url: &str) -> Result<&[String]> {
+        let image = Self::parse_image_url(image_url)
+            .ok_or_else(|| Self::invalid_image_url(image_url))?;
+        let repository = format!("{}/{}", image.team, image.model);
+        if !self.tag_list_cache.contains_key(&repository) {
+            let tags = self.fetch_tags(&image)?;
+            info!("Listed {} tag(s) of nvcr.io/nim/{}", tags.len(), repository);
+            self.tag_list_cache.insert(repository.clone(), tags);
+        }
+        Ok(&self.tag_list_cache[&repository])
+    }
+    
+    /// Fetch every page of an image's tag list
+    fn fetch_tags(&self, image: &ImageRef) -> Result<Vec<String>> {
+        let mut tags = Vec::new();
+        for page in 0..MAX_TAG_PAGES {
+            let url = format!(
+                "{}/{}/repos/{}/images?page-size={}&page-number={}",
+                self.registry_api_base, image.team, image.model, TAG_PAGE_SIZE, page
+            );
+            debug!("Fetching tags of {}/{}: {}", image.team, image.model, url);
+            let resp = self.get_with_retry(&url)?;
+            let list: NgcImageListResponse = resp.json()
+                .context("Failed to parse NGC image list response")?;
+            let total_pages = list.pagination_info.and_then(|p| p.total_pages);
+            let empty = list.images.is_empty();
+            tags.extend(list.images.into_iter().filter_map(|i| i.tag));
+            if empty || total_pages.is_none_or(|total| page + 1 >= total) {
+                return Ok(tags);
+            }
+        }
+        warn!("{}/{} has more than {} pages of tags; the rest are not checked", image.team, image.model, MAX_TAG_PAGES);
+        Ok(tags)
+    }
+    
+    /// Whether `tag` is one of a Local NIM image's tags
+    ///
+    /// The image's tag list is fetched once; every further check of a tag of the
+    /// same image is answered from it and counted in [`Self::tag_checks_avoided`].
+    pub fn tag_exists(&mut self, image_url: &str, tag: &str) -> Result<bool> {
+        let cached = Self::parse_image_url(image_url)
+            .is_some_and(|image| self.tag_list_cache.contains_key(&format!("{}/{}", image.team, image.model)));
+        if cached {
+            self.tag_checks_avoided += 1;
+        }
+        Ok(self.list_tags(image_url)?.iter().any(|t| t == tag))
+    }
+    
     // ========================================================================
     // Hosted NIM: Function Details
     // ========================================================================
     
     /// Fetch and cache the function list
     fn fetch_function_list(&mut self) -> Result<&Vec<NgcFunctionDetails>> {
-        if self.function_list_cache.is_some() {
-            return Ok(self.function_list_cache.as_ref().unwrap());
+        if let Some(ref functions) = self.function_list_cache {
+            return Ok(functions);
         }
         
-        let url = format!("{}/functions", NVCF_API_BASE);
+        let url = format!("{}/functions", self.nvcf_api_base);
         debug!("Fetching function list from {}", url);
         
         let resp = self.get_with_retry(&url)?;
@@ -188,6 +637,9 @@ impl NgcClient {
                 name: f.name,
                 status: f.status,
                 container_image: None, // Will be fetched on demand
+                model_name: None,
+                inference_url: None,
+                health_uri: None,
             })
             .collect();
         
@@ -204,42 +656,8 @@ impl NgcClient {
     pub fn find_function_by_model(&mut self, model_name: &str) -> Result<Option<String>> {
         let functions = self.fetch_function_list()?;
         
-        // Normalize model name for matching:
-        // 1. Remove prefix (meta/, nvidia/, stg/, stg/nvidia/, etc.)
-        // 2. Convert to lowercase
-        // 3. Replace . with _ (NVCF uses _ instead of .)
-        let model_parts: Vec<&str> = model_name.split('/').collect();
-        let short_name = model_parts.last().unwrap_or(&model_name);
-        let short_name_lower = short_name.to_lowercase();
-        
-        // Create normalized version: replace . with _
-        let normalized_name = short_name_lower.replace('.', "_");
-        
-        // Also try with ai- prefix (NVCF naming convention)
-        let ai_prefixed = format!("ai-{}", normalized_name);
-        
-        debug!("Looking for function matching model '{}' (normalized: '{}', ai-prefixed: '{}')", 
-               model_name, normalized_name, ai_prefixed);
-        
-        // Try to find a matching function
         for func in functions {
-            let func_name_lower = func.name.to_lowercase();
-            
-            // Try various matching strategies (ordered by specificity)
-            let is_match = 
-                // Exact match with ai- prefix
-                func_name_lower == ai_prefixed ||
-                // Function name starts with ai-{normalized_name}
-                func_name_lower.starts_with(&ai_prefixed) ||
-                // Exact match with normalized name
-                func_name_lower == normalized_name ||
-                // Function name contains normalized name
-                func_name_lower.contains(&normalized_name) ||
-                // Original matching strategies
-                func_name_lower.contains(&short_name_lower) ||
-                short_name_lower.contains(&func_name_lower.replace("ai-", ""));
-            
-            if is_match {
+            if function_name_matches(&func.name, model_name) {
                 debug!("Found function {} ('{}') for model '{}'", func.id, func.name, model_name);
                 return Ok(Some(func.id.clone()));
             }
@@ -249,6 +667,62 @@ impl NgcClient {
         Ok(None)
     }
     
+    /// `org/name` for a model name found without its org, trying BARE_MODEL_ORGS in order
+    ///
+    /// A candidate counts when a function in the list serves it; the org the
+    /// function's details name wins over the candidate's. None when no function
+    /// serves the name under any of the orgs.
+    pub fn qualify_bare_model(&mut self, name: &str) -> Result<Option<String>> {
+        let suffix = format!("/{}", name.to_lowercase());
+        for org in crate::scanner::BARE_MODEL_ORGS {
+            let candidate = format!("{}/{}", org, name);
+            let function_id = match self.find_function_by_model(&candidate)? {
+                Some(id) => id,
+                None => continue,
+            };
+            let served = self.get_function_details(&function_id)?.model_name;
+            match served {
+                Some(served) if served.eq_ignore_ascii_case(&candidate) => return Ok(Some(candidate)),
+                Some(served) if served.to_lowercase().ends_with(&suffix) => return Ok(Some(served)),
+                // Details without a model name: the function list match is all there is
+                None => return Ok(Some(candidate)),
+                Some(_) => continue,
+            }
+        }
+        Ok(None)
+    }
+    
+    /// Model names close to one no function serves, best first (at most MAX_SUGGESTIONS)
+    ///
+    /// Candidates are the model names of functions whose details were fetched,
+    /// plus every function name turned back into a model name under the queried
+    /// org (`ai-llama-3_1-70b-instruct` -> `meta/llama-3.1-70b-instruct`).
+    pub fn suggest_model_names(&mut self, model_name: &str) -> Result<Vec<String>> {
+        let org = model_name.rsplit_once('/').map(|(org, _)| org);
+        let mut candidates: Vec<String> = self.hosted_nim_cache.values()
+            .filter_map(|details| details.model_name.clone())
+            .collect();
+        candidates.extend(self.fetch_function_list()?.iter().map(|func| {
+            let lower = func.name.to_lowercase();
+            let short = crate::scanner::canonicalize_url_model(lower.strip_prefix("ai-").unwrap_or(&lower));
+            match org {
+                Some(org) => format!("{}/{}", org, short),
+                None => short,
+            }
+        }));
+        Ok(suggest_similar(model_name, &candidates))
+    }
+    
+    /// All functions whose name matches the model name (from the cached function list)
+    pub fn find_functions_by_model(&mut self, model_name: &str) -> Result<Vec<NgcFunctionDetails>> {
+        let functions = self.fetch_function_list()?;
+        Ok(functions
+            .iter()
+            .filter(|func| function_name_matches(&func.name, model_name))
+            .cloned()
+            .collect())
+    }
+    
     /// Get function details by ID using /versions endpoint
     /// 
     /// API: GET https://api.nvcf.nvidia.com/v2/nvcf/functions/{functionId}/versions
@@ -260,8 +734,53 @@ impl NgcClient {
             return Ok(details.clone());
         }
         
+        let details = self.fetch_function_details(function_id)?;
+        self.hosted_nim_cache.insert(function_id.to_string(), details.clone());
+        Ok(details)
+    }
+    
+    /// Get details for several functions, fetching uncached ones concurrently
+    /// (at most MAX_CONCURRENT_DETAILS requests in flight)
+    ///
+    /// Results are cached like `get_function_details`. Functions whose details
+    /// can't be fetched are logged and left out.
+    pub fn get_function_details_many(&mut self, function_ids: &[String]) -> Result<Vec<NgcFunctionDetails>> {
+        let mut missing: Vec<&String> = function_ids
+            .iter()
+            .filter(|id| !self.hosted_nim_cache.contains_key(id.as_str()))
+            .collect();
+        missing.sort();
+        missing.dedup();
+        
+        if !missing.is_empty() {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(MAX_CONCURRENT_DETAILS.min(missing.len()))
+                .build()
+                .context("Failed to build thread pool")?;
+            let this = &*self;
+            let fetched: Vec<(&String, Result<NgcFunctionDetails>)> = pool.install(|| {
+                missing.par_iter().map(|id| (*id, this.fetch_function_details(id))).collect()
+            });
+            for (id, result) in fetched {
+                match result {
+                    Ok(details) => {
+                        self.hosted_nim_cache.insert(id.clone(), details);
+                    }
+                    Err(e) => warn!("Failed to get function details for {}: {}", id, e),
+                }
+            }
+        }
+        
+        Ok(function_ids
+            .iter()
+            .filter_map(|id| self.hosted_nim_cache.get(id).cloned())
+            .collect())
+    }
+    
+    /// Fetch the latest version of a function (uncached)
+    fn fetch_function_details(&self, function_id: &str) -> Result<NgcFunctionDetails> {
         // Use /versions endpoint instead of direct function access
-        let url = format!("{}/functions/{}/versions", NVCF_API_BASE, function_id);
+        let url = format!("{}/functions/{}/versions", self.nvcf_api_base, function_id);
         debug!("Fetching function versions from {}", url);
         
         let resp = self.get_with_retry(&url)?;
@@ -306,18 +825,24 @@ impl NgcClient {
             .and_then(|n| n.as_str())
             .map(|s| s.to_string());
         
+        let url_field = |key: &str| latest_version.get(key)
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string());
+        
         let details = NgcFunctionDetails {
             id,
-            name: model_name.unwrap_or(name),
+            name: model_name.clone().unwrap_or(name),
             status,
             container_image,
+            model_name,
+            inference_url: url_field("inferenceUrl"),
+            health_uri: url_field("healthUri"),
         };
         
         info!("Got function details: id={}, status={:?}, containerImage={:?}", 
               details.id, details.status, details.container_image);
-        
-        // Cache result
-        self.hosted_nim_cache.insert(function_id.to_string(), details.clone());
+        debug!("Function {} inferenceUrl={:?}, healthUri={:?}",
+               details.id, details.inference_url, details.health_uri);
         
         Ok(details)
     }
@@ -327,60 +852,313 @@ impl NgcClient {
     // ========================================================================
     
     /// Enrich Local NIM matches by resolving latest tags
-    pub fn enrich_local_nim_matches(&mut self, findings: &mut NimFindings) {
-        for m in &mut findings.local_nim {
-            if m.tag == "latest" || m.tag.is_empty() {
-                match self.resolve_latest_tag(&m.image_url) {
-                    Ok(actual_tag) => {
-                        info!("Resolved {}: latest -> {}", m.image_url, actual_tag);
-                        // Keep original tag, set resolved_tag to actual version
-                        m.resolved_tag = Some(actual_tag);
-                    }
-                    Err(e) => {
-                        warn!("Failed to resolve latest tag for {}: {}", m.image_url, e);
-                        // Keep "latest" and resolved_tag as None
+    ///
+    /// Most-referenced images go first. Stops at the first rejected API key or when
+    /// the budget runs out; a missing repository is recorded on the finding.
+    pub fn enrich_local_nim_matches(&mut self, findings: &mut [&mut NimFindings]) {
+        let keyed = findings.iter().enumerate()
+            .flat_map(|(c, f)| f.local_nim.iter().enumerate()
+                .filter(|(_, m)| needs_tag_resolution(m))
+                .map(move |(i, m)| ((c, i), m.image_url.clone())))
+            .collect();
+        for (c, i) in by_frequency(keyed) {
+            if self.stopped() || !self.enrich_local_match(&mut findings[c].local_nim[i]) {
+                return;
+            }
+        }
+    }
+    
+    /// Resolve one Local NIM's latest tag; false when enrichment must stop
+    fn enrich_local_match(&mut self, m: &mut LocalNimMatch) -> bool {
+        match self.resolve_latest_tag(&m.image_url) {
+            Ok(actual_tag) => {
+                info!("Resolved {}: latest -> {}", m.image_url, actual_tag);
+                // Keep original tag, set resolved_tag to actual version
+                m.resolved_tag = Some(actual_tag);
+                m.enriched = true;
+            }
+            Err(e) => {
+                // Keep "latest" and resolved_tag as None
+                let what = format!("Failed to resolve latest tag for {}", m.image_url);
+                match enrichment_error(&what, &e) {
+                    Some(error) => m.enrichment_error = Some(error),
+                    None => return false,
+                }
+            }
+        }
+        true
+    }
+    
+    /// Check every pinned Local NIM tag against its image's tag list
+    ///
+    /// Each image's tags are listed once, however many of its tags are checked.
+    /// Most-referenced images go first. Stops at the first rejected API key or when
+    /// the budget runs out; a missing repository is recorded on the finding.
+    pub fn verify_local_tags(&mut self, findings: &mut [&mut NimFindings]) {
+        let keyed = findings.iter().enumerate()
+            .flat_map(|(c, f)| f.local_nim.iter().enumerate()
+                .filter(|(_, m)| needs_tag_check(m))
+                .map(move |(i, m)| ((c, i), m.image_url.clone())))
+            .collect();
+        for (c, i) in by_frequency(keyed) {
+            if self.stopped() || !self.verify_local_tag(&mut findings[c].local_nim[i]) {
+                return;
+            }
+        }
+    }
+    
+    /// Check one Local NIM's pinned tag; false when enrichment must stop
+    fn verify_local_tag(&mut self, m: &mut LocalNimMatch) -> bool {
+        match self.tag_exists(&m.image_url, &m.tag) {
+            Ok(exists) => {
+                if !exists {
+                    info!("{}:{} is not a tag of the image", m.image_url, m.tag);
+                }
+                m.tag_exists = Some(exists);
+                m.enriched = true;
+            }
+            Err(e) => {
+                let what = format!("Failed to list tags of {}", m.image_url);
+                match enrichment_error(&what, &e) {
+                    Some(error) => m.enrichment_error = Some(error),
+                    None => return false,
+                }
+            }
+        }
+        true
+    }
+    
+    /// Collect registry metadata for every unique Local NIM image, keyed by image_url
+    ///
+    /// Most-referenced images go first.
+    pub fn enrich_local_image_meta(
+        &mut self,
+        findings: &[&NimFindings],
+        meta: &mut HashMap<String, LocalImageMeta>,
+    ) {
+        let keyed = findings.iter().enumerate()
+            .flat_map(|(c, f)| f.local_nim.iter().enumerate()
+                .filter(|(_, m)| is_registry_image(m))
+                .map(move |(i, m)| ((c, i), m.image_url.clone())))
+            .collect();
+        for (c, i) in by_frequency(keyed) {
+            let m = &findings[c].local_nim[i];
+            if meta.contains_key(&m.image_url) {
+                continue;
+            }
+            if self.stopped() {
+                return;
+            }
+            match self.fetch_image_meta(&m.image_url) {
+                Ok(image_meta) => {
+                    debug!("Image meta for {}: {:?}", m.image_url, image_meta);
+                    meta.insert(m.image_url.clone(), image_meta);
+                }
+                Err(e) => {
+                    let what = format!("Failed to fetch image metadata for {}", m.image_url);
+                    if enrichment_error(&what, &e).is_none() {
+                        return;
                     }
+                    // Remember the miss so we don't retry for every location
+                    meta.insert(m.image_url.clone(), LocalImageMeta::default());
                 }
             }
         }
     }
     
     /// Enrich Hosted NIM matches by fetching function details
-    pub fn enrich_hosted_nim_matches(&mut self, findings: &mut NimFindings) {
-        for m in &mut findings.hosted_nim {
-            // Skip if we don't have a model name
-            let model_name = match &m.model_name {
-                Some(name) => name.clone(),
-                None => continue,
-            };
-            
-            // Find function ID
-            let function_id = match self.find_function_by_model(&model_name) {
-                Ok(Some(id)) => id,
+    ///
+    /// Staging findings are skipped: the staging API needs different credentials.
+    /// Most-referenced models go first. Stops at the first rejected API key or when
+    /// the budget runs out; a missing function is recorded on the finding.
+    pub fn enrich_hosted_nim_matches(&mut self, findings: &mut [&mut NimFindings]) {
+        let staging = findings.iter()
+            .flat_map(|f| &f.hosted_nim)
+            .filter(|m| m.environment == Environment::Staging)
+            .count();
+        if staging > 0 {
+            info!("Skipping enrichment for {} staging Hosted NIM reference(s)", staging);
+        }
+        let keyed = findings.iter().enumerate()
+            .flat_map(|(c, f)| f.hosted_nim.iter().enumerate()
+                .filter_map(move |(i, m)| hosted_lookup_key(m).map(|key| ((c, i), key))))
+            .collect();
+        for (c, i) in by_frequency(keyed) {
+            if self.stopped() || !self.enrich_hosted_match(&mut findings[c].hosted_nim[i]) {
+                return;
+            }
+        }
+    }
+    
+    /// Fetch function details for one Hosted NIM; false when enrichment must stop
+    fn enrich_hosted_match(&mut self, m: &mut HostedNimMatch) -> bool {
+        // Without a model name we can still resolve a function ID found in source
+        let model_name = match &m.model_name {
+            Some(name) => name.clone(),
+            None => {
+                if let Some(function_id) = m.function_id.clone() {
+                    match self.get_function_details(&function_id) {
+                        Ok(details) => {
+                            m.model_name = details.model_name;
+                            m.status = details.status;
+                            m.container_image = details.container_image;
+                            set_canonical_endpoint(m, details.inference_url);
+                            m.enriched = true;
+                            info!("Resolved function {} to model {:?}", function_id, m.model_name);
+                        }
+                        Err(e) => {
+                            let what = format!("Failed to get function details for {}", function_id);
+                            match enrichment_error(&what, &e) {
+                                Some(error) => m.enrichment_error = Some(error),
+                                None => return false,
+                            }
+                        }
+                    }
+                }
+                return true;
+            }
+        };
+        
+        // Bare short name (`nemotron-4-340b-instruct`): qualify it with the org whose function serves it
+        let model_name = match model_name.contains('/') {
+            true => model_name,
+            false => match self.qualify_bare_model(&model_name) {
+                Ok(Some(qualified)) => {
+                    info!("Resolved bare model name {} to {}", model_name, qualified);
+                    m.model_name = Some(qualified.clone());
+                    qualified
+                }
                 Ok(None) => {
-                    debug!("No function found for model {}", model_name);
-                    continue;
+                    debug!("No function found for bare model name {}", model_name);
+                    m.enrichment_error = Some(EnrichmentError::NotFound);
+                    return true;
                 }
                 Err(e) => {
-                    warn!("Failed to find function for {}: {}", model_name, e);
-                    continue;
+                    let what = format!("Failed to find function for {}", model_name);
+                    match enrichment_error(&what, &e) {
+                        Some(error) => m.enrichment_error = Some(error),
+                        None => return false,
+                    }
+                    return true;
                 }
-            };
-            
-            // Get function details
-            match self.get_function_details(&function_id) {
-                Ok(details) => {
-                    m.function_id = Some(details.id);
-                    m.status = details.status;
-                    m.container_image = details.container_image;
-                    info!("Enriched hosted NIM {}: function={}", model_name, function_id);
+            },
+        };
+        
+        // Find function ID
+        let function_id = match self.find_function_by_model(&model_name) {
+            Ok(Some(id)) => id,
+            Ok(None) => {
+                debug!("No function found for model {}", model_name);
+                m.enrichment_error = Some(EnrichmentError::NotFound);
+                // The function list is cached by now, so suggestions cost no request
+                m.did_you_mean = self.suggest_model_names(&model_name).unwrap_or_default();
+                return true;
+            }
+            Err(e) => {
+                let what = format!("Failed to find function for {}", model_name);
+                match enrichment_error(&what, &e) {
+                    Some(error) => m.enrichment_error = Some(error),
+                    None => return false,
                 }
-                Err(e) => {
-                    warn!("Failed to get function details for {}: {}", function_id, e);
-                    m.function_id = Some(function_id); // At least set the ID
+                return true;
+            }
+        };
+        
+        // Get function details
+        match self.get_function_details(&function_id) {
+            Ok(details) => {
+                m.function_id = Some(details.id);
+                m.status = details.status;
+                m.container_image = details.container_image;
+                set_canonical_endpoint(m, details.inference_url);
+                m.enriched = true;
+                info!("Enriched hosted NIM {}: function={}", model_name, function_id);
+            }
+            Err(e) => {
+                m.function_id = Some(function_id.clone()); // At least set the ID
+                let what = format!("Failed to get function details for {}", function_id);
+                match enrichment_error(&what, &e) {
+                    Some(error) => m.enrichment_error = Some(error),
+                    None => return false,
+                }
+            }
+        }
+        true
+    }
+    
+    /// Mark findings left unenriched in the given stages with `reason`; returns how many
+    fn mark_skipped(findings: &mut [&mut NimFindings], stages: &[EnrichStage], reason: EnrichmentSkip) -> usize {
+        let local = stages.contains(&EnrichStage::Local);
+        let hosted = stages.contains(&EnrichStage::Hosted);
+        let tags = stages.contains(&EnrichStage::Tags);
+        let mut skipped = 0;
+        for f in findings.iter_mut() {
+            let looked_up = |m: &LocalNimMatch| (local && needs_tag_resolution(m)) || (tags && needs_tag_check(m));
+            for m in f.local_nim.iter_mut().filter(|m| looked_up(m)) {
+                if !m.enriched && m.enrichment_error.is_none() {
+                    m.enrichment_skipped = Some(reason);
+                    skipped += 1;
                 }
             }
+            for m in f.hosted_nim.iter_mut().filter(|m| hosted && hosted_lookup_key(m).is_some()) {
+                if !m.enriched && m.enrichment_error.is_none() {
+                    m.enrichment_skipped = Some(reason);
+                    skipped += 1;
+                }
+            }
+        }
+        skipped
+    }
+    
+    /// Run the given enrichment stages over both categories
+    ///
+    /// Returns registry metadata per Local NIM image when `stages` includes
+    /// `image-meta` (empty otherwise). If the API key is rejected, the remaining stages
+    /// send no requests and a single warning is logged. If the budget runs out,
+    /// the findings not yet looked up are marked `budget_exhausted`.
+    pub fn enrich_all(
+        &mut self,
+        source_code: &mut NimFindings,
+        actions_workflow: &mut NimFindings,
+        stages: &[EnrichStage],
+    ) -> HashMap<String, LocalImageMeta> {
+        let mut image_meta = HashMap::new();
+        
+        // Enrich Local NIMs
+        if stages.contains(&EnrichStage::Local) {
+            self.enrich_local_nim_matches(&mut [&mut *source_code, &mut *actions_workflow]);
+        }
+        
+        // Enrich Hosted NIMs
+        if stages.contains(&EnrichStage::Hosted) {
+            self.enrich_hosted_nim_matches(&mut [&mut *source_code, &mut *actions_workflow]);
+        }
+        
+        // Registry metadata for Local NIM images (display name, publisher)
+        if stages.contains(&EnrichStage::ImageMeta) {
+            self.enrich_local_image_meta(&[source_code, actions_workflow], &mut image_meta);
+        }
+        
+        // Pinned Local NIM tags, checked against each image's tag list
+        if stages.contains(&EnrichStage::Tags) {
+            self.verify_local_tags(&mut [&mut *source_code, &mut *actions_workflow]);
+            if self.tag_checks_avoided > 0 {
+                info!("Tag checks: {} registry request(s) avoided by cached tag lists", self.tag_checks_avoided);
+            }
         }
+        
+        if let Some(error) = self.auth_failure() {
+            warn!("NGC API key rejected ({}); enrichment stopped, remaining findings are not enriched", error);
+        } else if self.budget_exhausted() {
+            let skipped = Self::mark_skipped(&mut [source_code, actions_workflow], stages, EnrichmentSkip::BudgetExhausted);
+            if skipped > 0 {
+                warn!(
+                    "Enrichment budget exhausted after {} request(s) in {:.1}s; {} finding(s) not enriched",
+                    self.requests_sent(), self.started.elapsed().as_secs_f64(), skipped
+                );
+            }
+        }
+        image_meta
     }
     
     // ========================================================================
@@ -397,12 +1175,12 @@ impl NgcClient {
     pub fn query_local_nim(&mut self, image_url: &str) -> Result<LocalNimQueryResult> {
         info!("Querying Local NIM: {}", image_url);
         
-        // Parse image URL to extract team and model name
-        let (team, model) = Self::parse_image_url(image_url)
-            .ok_or_else(|| anyhow::anyhow!("Invalid image URL format: {}. Expected: nvcr.io/nim/<team>/<model>", image_url))?;
+        // Parse image URL to extract team and model name (and the requested tag, if any)
+        let ImageRef { team, model, tag, digest } = Self::parse_image_url(image_url)
+            .ok_or_else(|| Self::invalid_image_url(image_url))?;
         
-        // Build API URL
-        let url = format!("{}/{}/repos/{}", NGC_REGISTRY_API_BASE, team, model);
+        // Build API URL (repository-level metadata, whatever tag was asked for)
+        let url = format!("{}/{}/repos/{}", self.registry_api_base, team, model);
         debug!("Fetching Local NIM info from {}", url);
         
         let resp = self.get_with_retry(&url)?;
@@ -414,6 +1192,8 @@ impl NgcClient {
             query_image: image_url.to_string(),
             team: team.clone(),
             model: model.clone(),
+            tag,
+            digest,
             name: raw_json.get("name")
                 .and_then(|v| v.as_str())
                 .map(|s| s.to_string()),
@@ -438,6 +1218,7 @@ impl NgcClient {
                 .and_then(|v| v.as_str())
                 .map(|s| s.to_string()),
             repository_url: format!("nvcr.io/nim/{}/{}", team, model),
+            tags: None,
             raw_response: raw_json,
         };
         
@@ -458,13 +1239,21 @@ impl NgcClient {
         info!("Querying Hosted NIM: {}", model_name);
         
         // Find function ID by model name
-        let function_id = self.find_function_by_model(model_name)?
-            .ok_or_else(|| anyhow::anyhow!("No function found for model: {}", model_name))?;
+        let function_id = match self.find_function_by_model(model_name)? {
+            Some(id) => id,
+            None => {
+                let suggestions = self.suggest_model_names(model_name)?;
+                if suggestions.is_empty() {
+                    anyhow::bail!("No function found for model: {}", model_name);
+                }
+                anyhow::bail!("No function found for model: {} (did you mean: {}?)", model_name, suggestions.join(", "));
+            }
+        };
         
         info!("Found function ID: {}", function_id);
         
         // Get function versions (full details)
-        let url = format!("{}/functions/{}/versions", NVCF_API_BASE, function_id);
+        let url = format!("{}/functions/{}/versions", self.nvcf_api_base, function_id);
         debug!("Fetching full function details from {}", url);
         
         let resp = self.get_with_retry(&url)?;
@@ -522,13 +1311,205 @@ impl NgcClient {
         
         Ok(result)
     }
+    
+    /// Find the functions running a container image (the reverse of `query_hosted_nim`)
+    ///
+    /// Candidates are functions whose name matches the image's model name; their
+    /// latest versions are fetched and kept when `containerImage` matches. With
+    /// `ignore_tag`, only the image repository is compared; otherwise the full
+    /// reference (tag and digest included) must match. Comparison ignores case.
+    pub fn query_functions_by_image(&mut self, image: &str, ignore_tag: bool) -> Result<Vec<FunctionByImageResult>> {
+        let repository = image_repository(image.trim());
+        let model_name = repository.rsplit('/').next().unwrap_or(repository);
+        if model_name.is_empty() {
+            bail!("Invalid image reference: {}", image);
+        }
+        info!("Querying functions running image: {}", image);
+        
+        let candidates = self.find_functions_by_model(model_name)?;
+        debug!("{} candidate function(s) for model name '{}'", candidates.len(), model_name);
+        let ids: Vec<String> = candidates.iter().map(|f| f.id.clone()).collect();
+        let details = self.get_function_details_many(&ids)?;
+        
+        let results = candidates
+            .iter()
+            .filter_map(|candidate| {
+                let details = details.iter().find(|d| d.id == candidate.id)?;
+                let container_image = details.container_image.as_deref()?;
+                image_matches(container_image, image, ignore_tag).then(|| FunctionByImageResult {
+                    query_image: image.to_string(),
+                    function_id: candidate.id.clone(),
+                    name: candidate.name.clone(),
+                    status: details.status.clone().or_else(|| candidate.status.clone()),
+                    container_image: container_image.to_string(),
+                })
+            })
+            .collect();
+        Ok(results)
+    }
 }
 
-/// Result of querying a Local NIM by image name
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct LocalNimQueryResult {
-    /// The image URL that was queried
+// ============================================================================
+// Model Name Suggestions
+// ============================================================================
+
+/// Most suggestions attached to a finding or printed for a query
+pub const MAX_SUGGESTIONS: usize = 3;
+
+/// Minimum similarity (0..=1) for a candidate to be suggested
+const MIN_SIMILARITY: f64 = 0.8;
+
+/// Candidates similar enough to `name` to be what was meant, best first
+///
+/// Similarity is the better of two scores: edit distance relative to the
+/// longer name (catches typos such as `instrcut`) and the overlap of the
+/// `/`, `-`, `_`, `.` separated tokens (catches `llama_3.1` for `llama-3.1`).
+/// Comparison ignores case; an exact match is not a suggestion.
+pub fn suggest_similar(name: &str, candidates: &[String]) -> Vec<String> {
+    let name = name.to_lowercase();
+    let mut scored: Vec<(f64, &String)> = candidates
+        .iter()
+        .filter(|c| c.to_lowercase() != name)
+        .map(|c| (similarity(&name, &c.to_lowercase()), c))
+        .filter(|(score, _)| *score >= MIN_SIMILARITY)
+        .collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.cmp(b.1)));
+    let mut suggestions: Vec<String> = Vec::new();
+    for (_, candidate) in scored {
+        if !suggestions.contains(candidate) {
+            suggestions.push(candidate.clone());
+        }
+        if suggestions.len() == MAX_SUGGESTIONS {
+            break;
+        }
+    }
+    suggestions
+}
+
+/// Similarity of two lowercased names, 0 (unrelated) to 1 (same)
+fn similarity(a: &str, b: &str) -> f64 {
+    let longest = a.chars().count().max(b.chars().count());
+    if longest == 0 {
+        return 1.0;
+    }
+    let edit = 1.0 - levenshtein(a, b) as f64 / longest as f64;
+    
+    let tokens = |s: &str| -> HashSet<String> {
+        s.split(['/', '-', '_', '.']).filter(|t| !t.is_empty()).map(String::from).collect()
+    };
+    let (ta, tb) = (tokens(a), tokens(b));
+    let union = ta.union(&tb).count();
+    let overlap = if union == 0 { 0.0 } else { ta.intersection(&tb).count() as f64 / union as f64 };
+    edit.max(overlap)
+}
+
+/// Number of single-character insertions, deletions or substitutions turning `a` into `b`
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Whether an NVCF function name plausibly serves a model
+///
+/// Model names are compared by their last segment, lowercased, with `.` as `_`
+/// and an optional `ai-` prefix (NVCF naming convention).
+fn function_name_matches(func_name: &str, model_name: &str) -> bool {
+    // Normalize model name for matching:
+    // 1. Remove prefix (meta/, nvidia/, stg/, stg/nvidia/, etc.)
+    // 2. Convert to lowercase
+    // 3. Replace . with _ (NVCF uses _ instead of .)
+    let short_name = model_name.rsplit('/').next().unwrap_or(model_name);
+    let short_name_lower = short_name.to_lowercase();
+    
+    // Create normalized version: replace . with _
+    let normalized_name = short_name_lower.replace('.', "_");
+    
+    // Also try with ai- prefix (NVCF naming convention)
+    let ai_prefixed = format!("ai-{}", normalized_name);
+    
+    let func_name_lower = func_name.to_lowercase();
+    
+    // Try various matching strategies (ordered by specificity)
+    // Exact match with ai- prefix
+    func_name_lower == ai_prefixed ||
+    // Function name starts with ai-{normalized_name}
+    func_name_lower.starts_with(&ai_prefixed) ||
+    // Exact match with normalized name
+    func_name_lower == normalized_name ||
+    // Function name contains normalized name
+    func_name_lower.contains(&normalized_name) ||
+    // Original matching strategies
+    func_name_lower.contains(&short_name_lower) ||
+    short_name_lower.contains(&func_name_lower.replace("ai-", ""))
+}
+
+/// Image reference without its `@digest` and `:tag`
+fn image_repository(image: &str) -> &str {
+    let image = image.split('@').next().unwrap_or(image);
+    match image.rfind(':') {
+        Some(colon) if !image[colon..].contains('/') => &image[..colon],
+        _ => image,
+    }
+}
+
+/// Whether a function's container image is the queried image
+fn image_matches(container_image: &str, query: &str, ignore_tag: bool) -> bool {
+    let (container_image, query) = (container_image.trim(), query.trim());
+    if ignore_tag {
+        image_repository(container_image).eq_ignore_ascii_case(image_repository(query))
+    } else {
+        container_image.eq_ignore_ascii_case(query)
+    }
+}
+
+/// A function running the queried container image
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionByImageResult {
+    /// The image reference that was queried
+    pub query_image: String,
+    
+    /// NVCF Function ID
+    pub function_id: String,
+    
+    /// Function name
+    pub name: String,
+    
+    /// Status of the latest version (ACTIVE, INACTIVE, DEPLOYING, etc.)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    
+    /// Exact container image the latest version runs
+    pub container_image: String,
+}
+
+/// A parsed nvcr.io/nim image reference
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageRef {
+    /// Team/namespace (e.g., "nvidia")
+    pub team: String,
+    /// Model name (e.g., "llama-3.2-nv-embedqa-1b-v2")
+    pub model: String,
+    /// Tag suffix (`:1.5.0`), if present
+    pub tag: Option<String>,
+    /// Digest suffix (`@sha256:...`), if present
+    pub digest: Option<String>,
+}
+
+/// Result of querying a Local NIM by image name
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalNimQueryResult {
+    /// The image URL that was queried
     pub query_image: String,
     
     /// Team/namespace (e.g., "nvidia")
@@ -537,6 +1518,14 @@ pub struct LocalNimQueryResult {
     /// Model name (e.g., "llama-3.2-nv-embedqa-1b-v2")
     pub model: String,
     
+    /// Tag given in the query (e.g., "1.5.0"), if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    
+    /// Digest given in the query (e.g., "sha256:..."), if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub digest: Option<String>,
+    
     /// Repository name
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
@@ -572,6 +1561,10 @@ pub struct LocalNimQueryResult {
     /// Full repository URL for docker pull
     pub repository_url: String,
     
+    /// Every tag of the image (with --tags)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    
     /// Raw API response for additional fields
     pub raw_response: serde_json::Value,
 }
@@ -635,44 +1628,313 @@ pub struct HostedNimQueryResult {
     pub raw_response: serde_json::Value,
 }
 
+// ============================================================================
+// Per-Repository Keys
+// ============================================================================
+
+/// How a repository is enriched when its config says otherwise than the run
+/// (`enrich: false`, `ngc_api_key_env`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RepoEnrichment {
+    /// Never enriched: no NGC request is made for its findings
+    OptedOut,
+    /// Enriched with its own key, read from the variable `env` (None when unset or empty)
+    OwnKey { env: String, key: Option<String> },
+}
+
+impl RepoEnrichment {
+    /// Setting of a configured repository; None when it is enriched with the run's key
+    pub fn from_config(repo: &RepoConfig) -> Option<Self> {
+        if !repo.enrich {
+            return Some(Self::OptedOut);
+        }
+        let env = repo.ngc_api_key_env.clone()?;
+        let key = std::env::var(&env).ok().filter(|key| !key.trim().is_empty());
+        Some(Self::OwnKey { env, key })
+    }
+}
+
+/// Repositories enriched the same way
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RepoGroup<'a> {
+    /// With this API key (one client, and so one cache, per distinct key)
+    Key(&'a str),
+    /// Not enriched, and its findings marked with the reason
+    Skipped(EnrichmentSkip),
+    /// Not enriched: no key at all
+    NoKey,
+}
+
+/// Move each item into the group `group_of` picks, remembering its position
+fn split_by_group<T>(items: Vec<T>, groups: usize, group_of: impl Fn(&T) -> usize) -> Vec<(Vec<usize>, Vec<T>)> {
+    let mut split: Vec<(Vec<usize>, Vec<T>)> = (0..groups).map(|_| (Vec::new(), Vec::new())).collect();
+    for (position, item) in items.into_iter().enumerate() {
+        let (positions, group) = &mut split[group_of(&item)];
+        positions.push(position);
+        group.push(item);
+    }
+    split
+}
+
+/// Put items split by [`split_by_group`] back in their original order
+fn join_groups<T>(split: Vec<(Vec<usize>, Vec<T>)>) -> Vec<T> {
+    let mut items: Vec<(usize, T)> = split
+        .into_iter()
+        .flat_map(|(positions, group)| positions.into_iter().zip(group))
+        .collect();
+    items.sort_by_key(|(position, _)| *position);
+    items.into_iter().map(|(_, item)| item).collect()
+}
+
 /// Enrich all findings using NGC API
+///
+/// Repositories in `repos` are enriched with their own key or not at all; the
+/// rest use `api_key`. The result holds registry metadata per Local NIM image
+/// when `stages` includes `image-meta`; callers attach it to the aggregated view.
 pub fn enrich_all_findings(
     api_key: Option<&str>,
+    repos: &HashMap<String, RepoEnrichment>,
     source_code: &mut NimFindings,
     actions_workflow: &mut NimFindings,
-) {
-    let api_key = match api_key {
-        Some(key) if !key.is_empty() => key,
-        _ => {
-            info!("No NGC API key provided, skipping enrichment");
-            return;
+    stages: &[EnrichStage],
+    budget: EnrichmentBudget,
+) -> EnrichmentRun {
+    enrich_by_repo(api_key, repos, source_code, actions_workflow, stages, budget, |key| NgcClient::new(key.to_string()))
+}
+
+/// [`enrich_all_findings`] with the client for each key made by `new_client`
+///
+/// Findings are split into one group per distinct key, each enriched by its
+/// own client, and put back in scan order. The run's key goes first; later
+/// groups get what is left of the budget. Findings of opted-out repositories,
+/// and of repositories whose key variable is unset, are marked
+/// `enrichment_skipped` without any request being made.
+pub fn enrich_by_repo(
+    api_key: Option<&str>,
+    repos: &HashMap<String, RepoEnrichment>,
+    source_code: &mut NimFindings,
+    actions_workflow: &mut NimFindings,
+    stages: &[EnrichStage],
+    budget: EnrichmentBudget,
+    new_client: impl Fn(&str) -> Result<NgcClient>,
+) -> EnrichmentRun {
+    let api_key = api_key.filter(|key| !key.is_empty());
+    let own_keys = repos.values().any(|repo| matches!(repo, RepoEnrichment::OwnKey { key: Some(_), .. }));
+    if api_key.is_none() && !own_keys {
+        info!("No NGC API key provided, skipping enrichment");
+        return EnrichmentRun::default();
+    }
+    if stages.is_empty() {
+        info!("No enrichment stages selected, skipping enrichment");
+        return EnrichmentRun::default();
+    }
+
+    let mut groups = vec![api_key.map_or(RepoGroup::NoKey, RepoGroup::Key)];
+    let mut group_of_repo: HashMap<String, usize> = HashMap::new();
+    let repositories = source_code.local_nim.iter().map(|m| &m.repository)
+        .chain(source_code.hosted_nim.iter().map(|m| &m.repository))
+        .chain(actions_workflow.local_nim.iter().map(|m| &m.repository))
+        .chain(actions_workflow.hosted_nim.iter().map(|m| &m.repository));
+    for repository in repositories {
+        if group_of_repo.contains_key(repository.as_str()) {
+            continue;
         }
-    };
-    
-    let mut client = match NgcClient::new(api_key.to_string()) {
-        Ok(c) => c,
+        let group = match repos.get(repository) {
+            None => groups[0],
+            Some(RepoEnrichment::OptedOut) => {
+                info!("{}: enrich is false, not enriching its findings", repository);
+                RepoGroup::Skipped(EnrichmentSkip::RepoOptedOut)
+            }
+            Some(RepoEnrichment::OwnKey { key: Some(key), .. }) => RepoGroup::Key(key),
+            Some(RepoEnrichment::OwnKey { env, key: None }) => {
+                warn!("{}: {} (its ngc_api_key_env) is not set; its findings are not enriched", repository, env);
+                RepoGroup::Skipped(EnrichmentSkip::RepoKeyMissing)
+            }
+        };
+        let index = groups.iter().position(|g| *g == group).unwrap_or_else(|| {
+            groups.push(group);
+            groups.len() - 1
+        });
+        group_of_repo.insert(repository.clone(), index);
+    }
+
+    let count = groups.len();
+    let group_of = |repository: &String| group_of_repo[repository];
+    let mut source_local = split_by_group(std::mem::take(&mut source_code.local_nim), count, |m| group_of(&m.repository));
+    let mut source_hosted = split_by_group(std::mem::take(&mut source_code.hosted_nim), count, |m| group_of(&m.repository));
+    let mut workflow_local = split_by_group(std::mem::take(&mut actions_workflow.local_nim), count, |m| group_of(&m.repository));
+    let mut workflow_hosted = split_by_group(std::mem::take(&mut actions_workflow.hosted_nim), count, |m| group_of(&m.repository));
+
+    let mut image_meta = HashMap::new();
+    let started = Instant::now();
+    let mut requests = 0;
+    let mut avoided = 0;
+    let mut key_rejected = false;
+    for (i, group) in groups.iter().enumerate() {
+        let mut source = NimFindings {
+            local_nim: std::mem::take(&mut source_local[i].1),
+            hosted_nim: std::mem::take(&mut source_hosted[i].1),
+            sdk_dependencies: vec![],
+        };
+        let mut workflow = NimFindings {
+            local_nim: std::mem::take(&mut workflow_local[i].1),
+            hosted_nim: std::mem::take(&mut workflow_hosted[i].1),
+            sdk_dependencies: vec![],
+        };
+        match *group {
+            RepoGroup::Key(key) => match new_client(key) {
+                Ok(client) => {
+                    let mut client = client.with_budget(budget.remaining(started.elapsed(), requests));
+                    info!("Enriching findings with NGC API...");
+                    image_meta.extend(client.enrich_all(&mut source, &mut workflow, stages));
+                    requests += client.requests_sent();
+                    avoided += client.tag_checks_avoided();
+                    key_rejected |= client.auth_failure().is_some();
+                }
+                Err(e) => warn!("Failed to create NGC client: {}", e),
+            },
+            RepoGroup::Skipped(reason) => {
+                NgcClient::mark_skipped(&mut [&mut source, &mut workflow], stages, reason);
+            }
+            RepoGroup::NoKey => {}
+        }
+        source_local[i].1 = source.local_nim;
+        source_hosted[i].1 = source.hosted_nim;
+        workflow_local[i].1 = workflow.local_nim;
+        workflow_hosted[i].1 = workflow.hosted_nim;
+    }
+    source_code.local_nim = join_groups(source_local);
+    source_code.hosted_nim = join_groups(source_hosted);
+    actions_workflow.local_nim = join_groups(workflow_local);
+    actions_workflow.hosted_nim = join_groups(workflow_hosted);
+    match avoided {
+        0 => info!("Enrichment complete ({} request(s))", requests),
+        _ => info!("Enrichment complete ({} request(s), {} avoided by cached tag lists)", requests, avoided),
+    }
+    EnrichmentRun { image_meta, requests, key_rejected }
+}
+
+// ============================================================================
+// Model Cards
+// ============================================================================
+
+/// Client for the public model catalog (--enrich-model-cards)
+///
+/// One request per model, cached. The first failure other than a 404 marks the
+/// catalog unavailable: one warning, and no further requests.
+pub struct ModelCardClient {
+    client: Client,
+    /// Catalog API base URL (overridable for tests)
+    api_base: String,
+    /// Card per model; None when the catalog has no card for it
+    cache: HashMap<String, Option<ModelCard>>,
+    /// Set after the first failed request
+    unavailable: bool,
+}
+
+impl ModelCardClient {
+    /// Create a client for the public catalog
+    pub fn new() -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .build()
+            .context("Failed to create HTTP client")?;
+        Ok(Self { client, api_base: MODEL_CARD_API_BASE.to_string(), cache: HashMap::new(), unavailable: false })
+    }
+
+    /// Create a client that talks to the given catalog base URL (e.g. a local mock server)
+    #[cfg(test)]
+    pub fn with_base_url(api_base: &str) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .no_proxy()
+            .build()
+            .context("Failed to create HTTP client")?;
+        Ok(Self { client, api_base: api_base.to_string(), cache: HashMap::new(), unavailable: false })
+    }
+
+    /// Model card of `model` (`org/name`); None when there is none or the catalog is unavailable
+    ///
+    /// API: GET {catalog}/{org}/{name}
+    pub fn model_card(&mut self, model: &str) -> Option<ModelCard> {
+        if let Some(card) = self.cache.get(model) {
+            return card.clone();
+        }
+        if self.unavailable {
+            return None;
+        }
+        let url = format!("{}/{}", self.api_base, model);
+        debug!("Fetching model card for {}: {}", model, url);
+        let card = match self.fetch(&url) {
+            Ok(card) => card,
+            Err(e) => {
+                warn!("Model catalog unavailable ({:#}); publisher, modalities and max_context are left empty", e);
+                self.unavailable = true;
+                return None;
+            }
+        };
+        if card.is_none() {
+            debug!("No model card for {}", model);
+        }
+        self.cache.insert(model.to_string(), card.clone());
+        card
+    }
+
+    /// The card at `url`, Ok(None) on a 404
+    fn fetch(&self, url: &str) -> Result<Option<ModelCard>> {
+        let resp = self.client.get(url).send()
+            .map_err(|e| NgcHttpError { status: None, body_snippet: e.to_string(), retriable: true })?;
+        if resp.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !resp.status().is_success() {
+            return Err(NgcHttpError::from_response(resp).into());
+        }
+        let card = resp.json().context("Failed to parse model card")?;
+        Ok(Some(card))
+    }
+
+    /// Look up each distinct model of `findings`, adding the cards found to `cards`
+    fn fill_model_cards(&mut self, findings: &[&NimFindings], cards: &mut HashMap<String, ModelCard>) {
+        let keyed = findings.iter().enumerate()
+            .flat_map(|(c, f)| f.hosted_nim.iter().enumerate()
+                .filter_map(move |(i, m)| Some(((c, i), m.model_name.clone()?))))
+            .collect();
+        for (c, i) in by_frequency(keyed) {
+            let Some(model) = findings[c].hosted_nim[i].model_name.as_deref() else {
+                continue;
+            };
+            if self.unavailable {
+                return;
+            }
+            if let Some(card) = self.model_card(model) {
+                cards.insert(model.to_string(), card);
+            }
+        }
+    }
+}
+
+/// Model cards of every Hosted NIM model, keyed by model name
+///
+/// Most-referenced models go first. Models without a card are left out.
+pub fn fetch_model_cards(findings: &[&NimFindings]) -> HashMap<String, ModelCard> {
+    let mut cards = HashMap::new();
+    let mut client = match ModelCardClient::new() {
+        Ok(client) => client,
         Err(e) => {
-            warn!("Failed to create NGC client: {}", e);
-            return;
+            warn!("Failed to create model catalog client: {}", e);
+            return cards;
         }
     };
-    
-    info!("Enriching findings with NGC API...");
-    
-    // Enrich Local NIMs
-    client.enrich_local_nim_matches(source_code);
-    client.enrich_local_nim_matches(actions_workflow);
-    
-    // Enrich Hosted NIMs
-    client.enrich_hosted_nim_matches(source_code);
-    client.enrich_hosted_nim_matches(actions_workflow);
-    
-    info!("Enrichment complete");
+    client.fill_model_cards(findings, &mut cards);
+    info!("Model cards: {} model(s) found in the catalog", cards.len());
+    cards
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::{EnrichmentOutcome, FileKind, Framework};
 
     // =========================================================================
     // Unit Tests (no API key required)
@@ -682,18 +1944,20 @@ mod tests {
     fn test_parse_image_url() {
         let result = NgcClient::parse_image_url("nvcr.io/nim/nvidia/llama-3.2-nv-embedqa-1b-v2");
         assert!(result.is_some());
-        let (team, model) = result.unwrap();
-        assert_eq!(team, "nvidia");
-        assert_eq!(model, "llama-3.2-nv-embedqa-1b-v2");
+        let image = result.unwrap();
+        assert_eq!(image.team, "nvidia");
+        assert_eq!(image.model, "llama-3.2-nv-embedqa-1b-v2");
+        assert_eq!(image.tag, None);
+        assert_eq!(image.digest, None);
     }
 
     #[test]
     fn test_parse_image_url_meta() {
         let result = NgcClient::parse_image_url("nvcr.io/nim/meta/llama-3.3-70b-instruct");
         assert!(result.is_some());
-        let (team, model) = result.unwrap();
-        assert_eq!(team, "meta");
-        assert_eq!(model, "llama-3.3-70b-instruct");
+        let image = result.unwrap();
+        assert_eq!(image.team, "meta");
+        assert_eq!(image.model, "llama-3.3-70b-instruct");
     }
 
     #[test]
@@ -702,6 +1966,53 @@ mod tests {
         assert!(NgcClient::parse_image_url("invalid").is_none());
     }
 
+    #[test]
+    fn test_parse_image_url_tag() {
+        let image = NgcClient::parse_image_url("nvcr.io/nim/nvidia/llama-3.2-nv-embedqa-1b-v2:1.5.0").unwrap();
+        assert_eq!(image.team, "nvidia");
+        assert_eq!(image.model, "llama-3.2-nv-embedqa-1b-v2");
+        assert_eq!(image.tag.as_deref(), Some("1.5.0"));
+        assert_eq!(image.digest, None);
+    }
+
+    #[test]
+    fn test_parse_image_url_digest() {
+        let digest = "sha256:4f1c2e9a0b8d7c6e5f4a3b2c1d0e9f8a7b6c5d4e3f2a1b0c9d8e7f6a5b4c3d2e";
+        let image = NgcClient::parse_image_url(&format!("nvcr.io/nim/meta/llama-3.1-8b-instruct@{}", digest)).unwrap();
+        assert_eq!(image.model, "llama-3.1-8b-instruct");
+        assert_eq!(image.tag, None);
+        assert_eq!(image.digest.as_deref(), Some(digest));
+
+        let image = NgcClient::parse_image_url(&format!("nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3.0@{}", digest)).unwrap();
+        assert_eq!(image.model, "llama-3.1-8b-instruct");
+        assert_eq!(image.tag.as_deref(), Some("1.3.0"));
+        assert_eq!(image.digest.as_deref(), Some(digest));
+    }
+
+    #[test]
+    fn test_parse_image_url_extra_segments() {
+        assert!(NgcClient::parse_image_url("nvcr.io/nim/nvidia/llama-3.2-nv-embedqa-1b-v2/extra").is_none());
+        assert!(NgcClient::parse_image_url("nvcr.io/nim/nvidia/llama-3.2-nv-embedqa-1b-v2:1.5.0/extra").is_none());
+        assert!(NgcClient::parse_image_url("nvcr.io/nim/nvidia/").is_none());
+        assert!(NgcClient::parse_image_url("nvcr.io/nim/nvidia/model:").is_none());
+        assert!(NgcClient::parse_image_url("nvcr.io/nim/nvidia/model@latest").is_none());
+
+        let err = NgcClient::invalid_image_url("nvcr.io/nim/nvidia/a/b").to_string();
+        assert!(err.contains("nvcr.io/nim/<team>/<model>[:<tag>]"), "{}", err);
+    }
+
+    #[test]
+    fn test_query_local_nim_echoes_tag() {
+        let base = serve_json_once(r#"{ "name": "llama-3.2-nv-embedqa-1b-v2", "latestTag": "1.6.0" }"#);
+        let mut client = NgcClient::with_base_urls("test-key".to_string(), &base, &base).unwrap();
+
+        let result = client.query_local_nim("nvcr.io/nim/nvidia/llama-3.2-nv-embedqa-1b-v2:1.5.0").unwrap();
+        assert_eq!(result.model, "llama-3.2-nv-embedqa-1b-v2");
+        assert_eq!(result.tag.as_deref(), Some("1.5.0"));
+        assert_eq!(result.latest_tag.as_deref(), Some("1.6.0"));
+        assert_eq!(result.repository_url, "nvcr.io/nim/nvidia/llama-3.2-nv-embedqa-1b-v2");
+    }
+
     #[test]
     fn test_model_name_normalization() {
         // Test that model names are correctly normalized for NVCF matching
@@ -746,6 +2057,20 @@ mod tests {
         assert_eq!(ai_prefixed, "ai-llama-3_3-nemotron-super-49b-v1");
     }
 
+    #[test]
+    fn test_model_name_normalization_catalog_slug() {
+        // A Build Page slug turned into a model name still finds the function the slug names
+        for (slug, function) in [
+            ("llama-3_1-405b-instruct", "ai-llama-3_1-405b-instruct"),
+            ("llama-3_3-nemotron-super-49b-v1_5", "ai-llama-3_3-nemotron-super-49b-v1_5"),
+            ("qwen2_5-coder-32b-instruct", "ai-qwen2_5-coder-32b-instruct"),
+        ] {
+            let model = crate::scanner::catalog_model_name("meta", slug).unwrap();
+            assert!(!model.contains('_'), "{}", model);
+            assert!(function_name_matches(function, &model), "{} -> {}", model, function);
+        }
+    }
+
     #[test]
     fn test_model_name_normalization_deepseek() {
         // Test stg/deepseek-ai/deepseek-r1
@@ -759,6 +2084,871 @@ mod tests {
         assert_eq!(normalized, "deepseek-r1");
     }
 
+    /// Serve one canned HTTP 200 JSON response on a local port; returns the base URL
+    fn serve_json_once(body: &'static str) -> String {
+        use std::io::{Read, Write};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 4096];
+            let mut request = Vec::new();
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                let n = stream.read(&mut buf).unwrap();
+                if n == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..n]);
+            }
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).unwrap();
+        });
+        format!("http://{}", addr)
+    }
+
+    /// Serve canned JSON by request path (`fallback` status line otherwise) until
+    /// the test exits, recording every requested path
+    fn serve_json_routes(
+        routes: Vec<(&'static str, &'static str)>,
+        fallback: &'static str,
+    ) -> (String, std::sync::Arc<std::sync::Mutex<Vec<String>>>) {
+        serve_slow_json_routes(routes, fallback, Duration::ZERO)
+    }
+
+    /// `serve_json_routes`, waiting `delay` before each response
+    fn serve_slow_json_routes(
+        routes: Vec<(&'static str, &'static str)>,
+        fallback: &'static str,
+        delay: Duration,
+    ) -> (String, std::sync::Arc<std::sync::Mutex<Vec<String>>>) {
+        serve_recording(routes, fallback, delay, |_, path| path.to_string())
+    }
+
+    /// `serve_json_routes`, recording `<Authorization header> <path>` for every request
+    fn serve_json_routes_with_auth(
+        routes: Vec<(&'static str, &'static str)>,
+        fallback: &'static str,
+    ) -> (String, std::sync::Arc<std::sync::Mutex<Vec<String>>>) {
+        serve_recording(routes, fallback, Duration::ZERO, |request, path| {
+            let authorization = request
+                .lines()
+                .find_map(|line| line.strip_prefix("authorization: ").or_else(|| line.strip_prefix("Authorization: ")))
+                .unwrap_or("-");
+            format!("{} {}", authorization, path)
+        })
+    }
+
+    /// Canned JSON server recording what `record` makes of each request and its path
+    fn serve_recording(
+        routes: Vec<(&'static str, &'static str)>,
+        fallback: &'static str,
+        delay: Duration,
+        record: fn(&str, &str) -> String,
+    ) -> (String, std::sync::Arc<std::sync::Mutex<Vec<String>>>) {
+        use std::io::{Read, Write};
+        use std::net::TcpListener;
+        use std::sync::{Arc, Mutex};
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let hits = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&hits);
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut buf = [0u8; 4096];
+                let mut request = Vec::new();
+                while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                    let n = stream.read(&mut buf).unwrap();
+                    if n == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buf[..n]);
+                }
+                let request = String::from_utf8_lossy(&request);
+                let path = request.split_whitespace().nth(1).unwrap_or("").to_string();
+                let (status, body) = match routes.iter().find(|(p, _)| *p == path) {
+                    Some((_, body)) => ("200 OK", *body),
+                    None => (fallback, r#"{"detail": "canned error"}"#),
+                };
+                recorded.lock().unwrap().push(record(&request, &path));
+                std::thread::sleep(delay);
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        (format!("http://{}", addr), hits)
+    }
+
+    #[test]
+    fn test_query_functions_by_image() {
+        let (base, hits) = serve_json_routes(vec![
+            ("/functions", r#"{"functions": [
+                {"id": "fn-nim", "name": "ai-llama-3_1-70b-instruct", "status": "ACTIVE"},
+                {"id": "fn-custom", "name": "ai-llama-3_1-70b-instruct-custom", "status": "ACTIVE"},
+                {"id": "fn-mistral", "name": "ai-mistral-7b-instruct", "status": "ACTIVE"}
+            ]}"#),
+            ("/functions/fn-nim/versions", r#"{"functions": [
+                {"id": "fn-nim", "name": "ai-llama-3_1-70b-instruct", "status": "ACTIVE",
+                 "containerImage": "nvcr.io/nim/meta/llama-3.1-70b-instruct:1.1"}
+            ]}"#),
+            ("/functions/fn-custom/versions", r#"{"functions": [
+                {"id": "fn-custom", "name": "ai-llama-3_1-70b-instruct-custom", "status": "INACTIVE",
+                 "containerImage": "nvcr.io/qc69jvmznzxy/custom/llama-3.1-70b-instruct-ft:0.3"}
+            ]}"#),
+        ], "404 Not Found");
+        let mut client = NgcClient::with_base_urls("test-key".to_string(), &base, &base).unwrap();
+
+        let results = client.query_functions_by_image("nvcr.io/nim/meta/llama-3.1-70b-instruct:1.1", false).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].function_id, "fn-nim");
+        assert_eq!(results[0].name, "ai-llama-3_1-70b-instruct");
+        assert_eq!(results[0].status.as_deref(), Some("ACTIVE"));
+        assert_eq!(results[0].container_image, "nvcr.io/nim/meta/llama-3.1-70b-instruct:1.1");
+
+        // Other tag: only found when the tag is ignored
+        assert!(client.query_functions_by_image("nvcr.io/nim/meta/llama-3.1-70b-instruct:1.2", false).unwrap().is_empty());
+        let any_tag = client.query_functions_by_image("nvcr.io/nim/meta/llama-3.1-70b-instruct", true).unwrap();
+        assert_eq!(any_tag.iter().map(|r| r.function_id.as_str()).collect::<Vec<_>>(), vec!["fn-nim"]);
+
+        // Function list and versions fetched once; the non-candidate is never fetched
+        let mut hits = hits.lock().unwrap().clone();
+        hits.sort();
+        assert_eq!(hits, vec!["/functions", "/functions/fn-custom/versions", "/functions/fn-nim/versions"]);
+    }
+
+    fn local_latest(image: &str) -> crate::models::LocalNimMatch {
+        crate::models::LocalNimMatch {
+            repository: "test/repo".to_string(),
+            image_url: format!("nvcr.io/nim/{}", image),
+            tag: "latest".to_string(),
+            resolved_tag: None,
+            tag_exists: None,
+            file_path: "docker-compose.yaml".to_string(),
+            line_number: 1,
+            match_context: String::new(),
+            policy: None,
+            enrichment_error: None,
+            enriched: false,
+            detection_rule: String::new(),
+            workflow_triggers: Vec::new(),
+            nim_identity: String::new(),
+            enrichment_skipped: None,
+            context_truncated: None,
+            compose: None,
+            intent: crate::models::LocalIntent::Reference,
+            usage_kind: crate::models::UsageKind::Other,
+            blame: None,
+            notebook_cell: None,
+            file_kind: FileKind::Source,
+            resolved_from: None,
+            fixture: false,
+            fingerprint: String::new(),
+        }
+    }
+
+    fn hosted_call(model_name: Option<&str>, function_id: Option<&str>) -> crate::models::HostedNimMatch {
+        crate::models::HostedNimMatch {
+            repository: "test/repo".to_string(),
+            endpoint_url: None,
+            model_name: model_name.map(|m| m.to_string()),
+            file_path: "client.py".to_string(),
+            line_number: 1,
+            match_context: String::new(),
+            function_id: function_id.map(|f| f.to_string()),
+            status: None,
+            container_image: None,
+            environment: Environment::Production,
+            endpoint_host: None,
+            invoke_style: crate::models::InvokeStyle::Unknown,
+            framework: Framework::Unknown,
+            call_params: None,
+            policy: None,
+            model_policy: None,
+            enrichment_error: None,
+            enriched: false,
+            detection_rule: String::new(),
+            workflow_triggers: Vec::new(),
+            nim_identity: None,
+            enrichment_skipped: None,
+            browser_exposed: false,
+            did_you_mean: Vec::new(),
+            credential_source: None,
+            context_truncated: None,
+            canonical_endpoint: None,
+            endpoint_mismatch: false,
+            endpoint_status: None,
+            suggested_endpoint: None,
+            blame: None,
+            notebook_cell: None,
+            file_kind: FileKind::Source,
+            resolved_from: None,
+            fixture: false,
+            fingerprint: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_get_with_retry_classifies_errors() {
+        let classify = |status: &'static str| {
+            let (base, _) = serve_json_routes(vec![], status);
+            let client = NgcClient::with_base_urls("test-key".to_string(), &base, &base).unwrap();
+            client.get_with_retry(&format!("{}/anything", base)).unwrap_err()
+        };
+
+        let expected = |status| NgcHttpError {
+            status: Some(status),
+            body_snippet: r#"{"detail": "canned error"}"#.to_string(),
+            retriable: false,
+        };
+        assert_eq!(classify("401 Unauthorized"), NgcError::Auth(expected(401)));
+        assert_eq!(classify("403 Forbidden"), NgcError::Auth(expected(403)));
+        assert_eq!(classify("404 Not Found"), NgcError::NotFound(expected(404)));
+        assert_eq!(classify("400 Bad Request"), NgcError::Http(expected(400)));
+
+        // Typed errors survive anyhow propagation through the public methods
+        let (base, _) = serve_json_routes(vec![], "404 Not Found");
+        let mut client = NgcClient::with_base_urls("test-key".to_string(), &base, &base).unwrap();
+        let err = client.resolve_latest_tag("nvcr.io/nim/nvidia/missing").unwrap_err();
+        assert!(matches!(err.downcast_ref::<NgcError>(), Some(NgcError::NotFound(_))));
+        let err = client.get_function_details("fn-missing").unwrap_err();
+        assert!(matches!(err.downcast_ref::<NgcError>(), Some(NgcError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_enrich_all_stops_after_auth_error() {
+        let (base, hits) = serve_json_routes(vec![], "401 Unauthorized");
+        let mut client = NgcClient::with_base_urls("bad-key".to_string(), &base, &base).unwrap();
+        let mut source_code = NimFindings {
+            local_nim: vec![local_latest("nvidia/a"), local_latest("nvidia/b")],
+            hosted_nim: vec![hosted_call(Some("meta/llama-3.1-8b-instruct"), None)],
+            sdk_dependencies: vec![],
+        };
+        let mut actions_workflow = NimFindings {
+            local_nim: vec![local_latest("nvidia/c")],
+            hosted_nim: vec![hosted_call(None, Some("fn-1"))],
+            sdk_dependencies: vec![],
+        };
+
+        let meta = client.enrich_all(&mut source_code, &mut actions_workflow, &[EnrichStage::Local, EnrichStage::Hosted, EnrichStage::ImageMeta]);
+        assert!(meta.is_empty());
+        assert_eq!(hits.lock().unwrap().len(), 1);
+        assert_eq!(client.auth_failure().and_then(|e| e.status), Some(401));
+        // Not the findings' fault: nothing recorded on them
+        assert!(source_code.local_nim.iter().chain(&actions_workflow.local_nim)
+            .all(|m| m.resolved_tag.is_none() && m.enrichment_error.is_none()));
+        assert!(source_code.hosted_nim.iter().chain(&actions_workflow.hosted_nim)
+            .all(|m| m.enrichment_outcome() == EnrichmentOutcome::NotAttempted));
+    }
+
+    #[test]
+    fn test_enrich_by_repo_keys_and_opt_out() {
+        let (base, hits) = serve_json_routes_with_auth(vec![
+            ("/nvidia/repos/a", r#"{"name": "a", "latestTag": "1.0.0"}"#),
+        ], "404 Not Found");
+        let in_repo = |repository: &str| {
+            let mut m = local_latest("nvidia/a");
+            m.repository = repository.to_string();
+            m
+        };
+        let mut opted_out_call = hosted_call(Some("meta/llama-3.1-8b-instruct"), None);
+        opted_out_call.repository = "contract/app".to_string();
+        let mut source_code = NimFindings {
+            local_nim: vec![in_repo("contract/app"), in_repo("test/repo"), in_repo("partner/app"), in_repo("missing/app")],
+            hosted_nim: vec![opted_out_call],
+            sdk_dependencies: vec![],
+        };
+        let mut actions_workflow = NimFindings { local_nim: vec![in_repo("partner/app")], hosted_nim: vec![], sdk_dependencies: vec![] };
+        let repos = HashMap::from([
+            ("contract/app".to_string(), RepoEnrichment::OptedOut),
+            ("partner/app".to_string(), RepoEnrichment::OwnKey { env: "PARTNER_NGC_KEY".to_string(), key: Some("partner-key".to_string()) }),
+            ("missing/app".to_string(), RepoEnrichment::OwnKey { env: "MISSING_NGC_KEY".to_string(), key: None }),
+        ]);
+
+        enrich_by_repo(
+            Some("service-key"), &repos, &mut source_code, &mut actions_workflow,
+            &[EnrichStage::Local, EnrichStage::Hosted], EnrichmentBudget::default(),
+            |key| NgcClient::with_base_urls(key.to_string(), &base, &base),
+        );
+        // One request per key (the partner's two findings share its client's cache); none for the opted-out repo
+        assert_eq!(*hits.lock().unwrap(), vec![
+            "Bearer service-key /nvidia/repos/a",
+            "Bearer partner-key /nvidia/repos/a",
+        ]);
+
+        // Findings stay in scan order
+        fn outcome(m: &crate::models::LocalNimMatch) -> (&str, bool, Option<EnrichmentSkip>) {
+            (m.repository.as_str(), m.enriched, m.enrichment_skipped)
+        }
+        assert_eq!(source_code.local_nim.iter().map(outcome).collect::<Vec<_>>(), vec![
+            ("contract/app", false, Some(EnrichmentSkip::RepoOptedOut)),
+            ("test/repo", true, None),
+            ("partner/app", true, None),
+            ("missing/app", false, Some(EnrichmentSkip::RepoKeyMissing)),
+        ]);
+        assert_eq!(outcome(&actions_workflow.local_nim[0]), ("partner/app", true, None));
+        assert_eq!(source_code.hosted_nim[0].enrichment_skipped, Some(EnrichmentSkip::RepoOptedOut));
+
+        // Config: enrich: false wins over a key; the key comes from the named variable
+        let repo = |enrich: bool, env: Option<&str>| RepoConfig {
+            enrich,
+            ngc_api_key_env: env.map(|e| e.to_string()),
+            ..Default::default()
+        };
+        assert_eq!(RepoEnrichment::from_config(&repo(true, None)), None);
+        assert_eq!(RepoEnrichment::from_config(&repo(false, Some("PATH"))), Some(RepoEnrichment::OptedOut));
+        let unset = "NIM_USAGE_SCANNER_TEST_UNSET_KEY";
+        assert_eq!(
+            RepoEnrichment::from_config(&repo(true, Some(unset))),
+            Some(RepoEnrichment::OwnKey { env: unset.to_string(), key: None })
+        );
+    }
+
+    #[test]
+    fn test_enrichment_request_budget_prioritizes_frequent_images() {
+        let (base, hits) = serve_json_routes(vec![
+            ("/nvidia/repos/once", r#"{"name": "once", "latestTag": "1.0.0"}"#),
+            ("/nvidia/repos/thrice", r#"{"name": "thrice", "latestTag": "3.0.0"}"#),
+            ("/nvidia/repos/twice", r#"{"name": "twice", "latestTag": "2.0.0"}"#),
+        ], "404 Not Found");
+        let budget = EnrichmentBudget { max_duration: None, max_requests: Some(2) };
+        let mut client = NgcClient::with_base_urls("test-key".to_string(), &base, &base).unwrap()
+            .with_budget(budget);
+        let mut source_code = NimFindings {
+            local_nim: vec![
+                local_latest("nvidia/once"), local_latest("nvidia/twice"),
+                local_latest("nvidia/thrice"), local_latest("nvidia/thrice"),
+            ],
+            hosted_nim: vec![hosted_call(Some("meta/llama-3.1-8b-instruct"), None)],
+            sdk_dependencies: vec![],
+        };
+        let mut actions_workflow = NimFindings {
+            local_nim: vec![local_latest("nvidia/thrice"), local_latest("nvidia/twice")],
+            hosted_nim: vec![],
+            sdk_dependencies: vec![],
+        };
+
+        client.enrich_all(&mut source_code, &mut actions_workflow, &[EnrichStage::Local, EnrichStage::Hosted, EnrichStage::ImageMeta]);
+        // Most-referenced first, and never more than the cap
+        assert_eq!(*hits.lock().unwrap(), vec!["/nvidia/repos/thrice", "/nvidia/repos/twice"]);
+        assert_eq!(client.requests_sent(), 2);
+        assert!(client.budget_exhausted());
+
+        let skipped = |m: &crate::models::LocalNimMatch| m.enrichment_skipped;
+        assert_eq!(source_code.local_nim.iter().map(skipped).collect::<Vec<_>>(), vec![
+            Some(EnrichmentSkip::BudgetExhausted), None, None, None,
+        ]);
+        assert!(actions_workflow.local_nim.iter().all(|m| m.enriched && m.enrichment_skipped.is_none()));
+        assert_eq!(source_code.hosted_nim[0].enrichment_skipped, Some(EnrichmentSkip::BudgetExhausted));
+        assert_eq!(source_code.hosted_nim[0].enrichment_outcome(), EnrichmentOutcome::NotAttempted);
+
+        let report = crate::models::ScanReport::new(1, source_code, actions_workflow);
+        assert_eq!(report.summary.enrichment_skipped, 2);
+        let json = serde_json::to_string(&report).unwrap();
+        assert!(json.contains(r#""enrichment_skipped":"budget_exhausted""#));
+    }
+
+    #[test]
+    fn test_enrichment_time_budget_stops_between_items() {
+        let (base, hits) = serve_slow_json_routes(vec![
+            ("/nvidia/repos/a", r#"{"name": "a", "latestTag": "1.0.0"}"#),
+            ("/nvidia/repos/b", r#"{"name": "b", "latestTag": "1.0.0"}"#),
+        ], "404 Not Found", Duration::from_millis(300));
+        let budget = EnrichmentBudget { max_duration: Some(Duration::from_millis(100)), max_requests: None };
+        let mut client = NgcClient::with_base_urls("test-key".to_string(), &base, &base).unwrap()
+            .with_budget(budget);
+        let mut source_code = NimFindings {
+            local_nim: vec![local_latest("nvidia/a"), local_latest("nvidia/b")],
+            hosted_nim: vec![],
+            sdk_dependencies: vec![],
+        };
+
+        client.enrich_all(&mut source_code, &mut NimFindings::default(), &EnrichStage::DEFAULT_SET);
+        // The slow first lookup finishes; the next one is never sent
+        assert_eq!(*hits.lock().unwrap(), vec!["/nvidia/repos/a"]);
+        assert_eq!(source_code.local_nim[0].resolved_tag.as_deref(), Some("1.0.0"));
+        assert_eq!(source_code.local_nim[0].enrichment_skipped, None);
+        assert_eq!(source_code.local_nim[1].resolved_tag, None);
+        assert_eq!(source_code.local_nim[1].enrichment_error, None);
+        assert_eq!(source_code.local_nim[1].enrichment_skipped, Some(EnrichmentSkip::BudgetExhausted));
+    }
+
+    #[test]
+    fn test_suggest_similar() {
+        let catalog: Vec<String> = [
+            "meta/llama-3.1-70b-instruct",
+            "meta/llama-3.1-8b-instruct",
+            "nvidia/nv-embedqa-e5-v5",
+            "nvidia/llama-3.2-nv-rerankqa-1b-v2",
+        ].iter().map(|s| s.to_string()).collect();
+
+        // One-character typo: the intended model ranks first
+        let suggestions = suggest_similar("meta/llama-3.1-70b-instrcut", &catalog);
+        assert_eq!(suggestions.first().map(String::as_str), Some("meta/llama-3.1-70b-instruct"));
+        assert!(suggestions.len() <= MAX_SUGGESTIONS);
+
+        // Swapped separators, any case
+        assert_eq!(suggest_similar("NVIDIA/nv_embedqa_e5_v5", &catalog), vec!["nvidia/nv-embedqa-e5-v5"]);
+
+        // Nothing like it, and an exact match is no suggestion
+        assert!(suggest_similar("acme/totally-unknown-model", &catalog).is_empty());
+        assert!(suggest_similar("nvidia/nv-embedqa-e5-v5", &catalog).is_empty());
+
+        assert_eq!(levenshtein("instrcut", "instruct"), 2);
+        assert_eq!(levenshtein("", "abc"), 3);
+    }
+
+    #[test]
+    fn test_bare_model_resolved_with_org_prefix() {
+        let (base, _) = serve_json_routes(vec![
+            ("/functions", r#"{"functions": [
+                {"id": "fn-nemotron", "name": "ai-nemotron-4-340b-instruct", "status": "ACTIVE"},
+                {"id": "fn-70b", "name": "ai-llama-3_1-70b-instruct", "status": "ACTIVE"}
+            ]}"#),
+            ("/functions/fn-nemotron/versions", r#"{"functions": [
+                {"id": "fn-nemotron", "name": "ai-nemotron-4-340b-instruct", "status": "ACTIVE",
+                 "models": [{"name": "nvidia/nemotron-4-340b-instruct"}]}
+            ]}"#),
+        ], "404 Not Found");
+        let mut client = NgcClient::with_base_urls("test-key".to_string(), &base, &base).unwrap();
+        let mut bare = hosted_call(Some("nemotron-4-340b-instruct"), None);
+        bare.detection_rule = "bare_model_assign".to_string();
+        let mut source_code = NimFindings {
+            local_nim: Vec::new(),
+            hosted_nim: vec![bare, hosted_call(Some("nemotron-9-unknown"), None)],
+            sdk_dependencies: vec![],
+        };
+
+        client.enrich_all(&mut source_code, &mut NimFindings::default(), &EnrichStage::DEFAULT_SET);
+        let resolved = &source_code.hosted_nim[0];
+        assert_eq!(resolved.model_name.as_deref(), Some("nvidia/nemotron-4-340b-instruct"));
+        assert_eq!(resolved.function_id.as_deref(), Some("fn-nemotron"));
+        assert!(resolved.enriched);
+        assert_eq!(source_code.hosted_nim[1].model_name.as_deref(), Some("nemotron-9-unknown"));
+        assert_eq!(source_code.hosted_nim[1].enrichment_error, Some(EnrichmentError::NotFound));
+    }
+
+    #[test]
+    fn test_unknown_model_suggestions_from_function_list() {
+        let (base, _) = serve_json_routes(vec![
+            ("/functions", r#"{"functions": [
+                {"id": "fn-70b", "name": "ai-llama-3_1-70b-instruct", "status": "ACTIVE"},
+                {"id": "fn-embed", "name": "ai-nv-embedqa-e5-v5", "status": "ACTIVE"}
+            ]}"#),
+        ], "404 Not Found");
+        let mut client = NgcClient::with_base_urls("test-key".to_string(), &base, &base).unwrap();
+        let mut source_code = NimFindings {
+            local_nim: Vec::new(),
+            hosted_nim: vec![
+                hosted_call(Some("meta/llama-3.1-70b-instrcut"), None),
+                hosted_call(Some("acme/unrelated-model"), None),
+            ],
+            sdk_dependencies: vec![],
+        };
+
+        client.enrich_all(&mut source_code, &mut NimFindings::default(), &EnrichStage::DEFAULT_SET);
+        assert_eq!(source_code.hosted_nim[0].enrichment_error, Some(EnrichmentError::NotFound));
+        assert_eq!(source_code.hosted_nim[0].did_you_mean, vec!["meta/llama-3.1-70b-instruct"]);
+        assert_eq!(source_code.hosted_nim[1].enrichment_error, Some(EnrichmentError::NotFound));
+        assert!(source_code.hosted_nim[1].did_you_mean.is_empty());
+
+        let err = client.query_hosted_nim("meta/llama-3.1-70b-instrcut").unwrap_err().to_string();
+        assert!(err.contains("did you mean: meta/llama-3.1-70b-instruct?"), "{}", err);
+        let err = client.query_hosted_nim("acme/unrelated-model").unwrap_err().to_string();
+        assert_eq!(err, "No function found for model: acme/unrelated-model");
+    }
+
+    #[test]
+    fn test_verify_tags_lists_each_image_once() {
+        let (base, hits) = serve_json_routes(vec![
+            ("/meta/repos/llama-3.1-8b-instruct/images?page-size=100&page-number=0",
+             r#"{"images": [{"tag": "1.5.0"}, {"tag": "1.3.3"}], "paginationInfo": {"index": 0, "totalPages": 2}}"#),
+            ("/meta/repos/llama-3.1-8b-instruct/images?page-size=100&page-number=1",
+             r#"{"images": [{"tag": "1.0.0"}], "paginationInfo": {"index": 1, "totalPages": 2}}"#),
+            ("/nvidia/repos/nv-embedqa-e5-v5/images?page-size=100&page-number=0",
+             r#"{"images": [{"tag": "1.1.0"}]}"#),
+        ], "404 Not Found");
+        let mut client = NgcClient::with_base_urls("test-key".to_string(), &base, &base).unwrap();
+        let pinned = |image: &str, tag: &str| LocalNimMatch { tag: tag.to_string(), ..local_latest(image) };
+        // Docs listing many historical tags of one image
+        let mut local_nim: Vec<LocalNimMatch> = ["1.0.0", "1.3.3", "1.4.0", "1.5.0", "1.0.0"]
+            .iter()
+            .map(|tag| pinned("meta/llama-3.1-8b-instruct", tag))
+            .collect();
+        local_nim.push(pinned("nvidia/nv-embedqa-e5-v5", "1.1.0"));
+        local_nim.push(pinned("nvidia/nv-embedqa-e5-v5", "2.0.0"));
+        local_nim.push(pinned("nvidia/gone", "1.0.0"));
+        // Not pinned: left to the local stage
+        local_nim.push(local_latest("meta/llama-3.1-8b-instruct"));
+        let mut source_code = NimFindings { local_nim, hosted_nim: Vec::new(), sdk_dependencies: vec![] };
+
+        client.enrich_all(&mut source_code, &mut NimFindings::default(), &[EnrichStage::Tags]);
+        let exists: Vec<(&str, Option<bool>)> = source_code.local_nim.iter().map(|m| (m.tag.as_str(), m.tag_exists)).collect();
+        assert_eq!(exists, vec![
+            ("1.0.0", Some(true)), ("1.3.3", Some(true)), ("1.4.0", Some(false)), ("1.5.0", Some(true)), ("1.0.0", Some(true)),
+            ("1.1.0", Some(true)), ("2.0.0", Some(false)),
+            ("1.0.0", None),
+            ("latest", None),
+        ]);
+        assert!(source_code.local_nim[2].enriched);
+        assert_eq!(source_code.local_nim[7].enrichment_error, Some(EnrichmentError::NotFound));
+
+        // One tag list per image (two pages for the first), whatever the number of tags checked
+        let hits = hits.lock().unwrap().clone();
+        let listed = |repo: &str| hits.iter().filter(|h| h.starts_with(&format!("/{}/images?", repo))).count();
+        assert_eq!(listed("meta/repos/llama-3.1-8b-instruct"), 2);
+        assert_eq!(listed("nvidia/repos/nv-embedqa-e5-v5"), 1);
+        assert_eq!(hits.len(), 4);
+        assert_eq!(client.tag_checks_avoided(), 5);
+
+        // The query path reuses the cached list
+        let tags = client.list_tags("nvcr.io/nim/meta/llama-3.1-8b-instruct:1.5.0").unwrap().to_vec();
+        assert_eq!(tags, vec!["1.5.0", "1.3.3", "1.0.0"]);
+        assert_eq!(client.requests_sent(), 4);
+    }
+
+    #[test]
+    fn test_enrich_all_records_not_found_per_finding() {
+        let (base, _) = serve_json_routes(vec![
+            ("/nvidia/repos/present", r#"{"name": "present", "latestTag": "1.2.0"}"#),
+            ("/functions", r#"{"functions": [
+                {"id": "fn-llama", "name": "ai-llama-3_1-8b-instruct", "status": "ACTIVE"}
+            ]}"#),
+            ("/functions/fn-llama/versions", r#"{"functions": [
+                {"id": "fn-llama", "status": "ACTIVE", "containerImage": "nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3.0"}
+            ]}"#),
+        ], "404 Not Found");
+        let mut client = NgcClient::with_base_urls("test-key".to_string(), &base, &base).unwrap();
+        let mut source_code = NimFindings {
+            local_nim: vec![local_latest("nvidia/missing"), local_latest("nvidia/present")],
+            hosted_nim: vec![
+                hosted_call(None, Some("fn-deleted")),
+                hosted_call(Some("meta/llama-3.1-8b-instruct"), None),
+            ],
+            sdk_dependencies: vec![],
+        };
+
+        client.enrich_all(&mut source_code, &mut NimFindings::default(), &EnrichStage::DEFAULT_SET);
+        assert_eq!(client.auth_failure(), None);
+
+        assert_eq!(source_code.local_nim[0].enrichment_error, Some(EnrichmentError::NotFound));
+        assert_eq!(source_code.local_nim[0].resolved_tag, None);
+        assert_eq!(source_code.local_nim[1].enrichment_error, None);
+        assert_eq!(source_code.local_nim[1].resolved_tag.as_deref(), Some("1.2.0"));
+
+        assert_eq!(source_code.hosted_nim[0].enrichment_error, Some(EnrichmentError::NotFound));
+        assert_eq!(source_code.hosted_nim[1].enrichment_error, None);
+        assert_eq!(source_code.hosted_nim[1].function_id.as_deref(), Some("fn-llama"));
+        assert_eq!(source_code.hosted_nim[1].status.as_deref(), Some("ACTIVE"));
+
+        let outcomes: Vec<EnrichmentOutcome> = source_code.local_nim.iter().map(|m| m.enrichment_outcome())
+            .chain(source_code.hosted_nim.iter().map(|m| m.enrichment_outcome()))
+            .collect();
+        assert_eq!(outcomes, vec![
+            EnrichmentOutcome::Failed, EnrichmentOutcome::Enriched,
+            EnrichmentOutcome::Failed, EnrichmentOutcome::Enriched,
+        ]);
+    }
+
+    #[test]
+    fn test_model_without_function_since_baseline_is_newly_unresolved() {
+        use crate::models::{Resolution, ScanReport};
+
+        let (base, _) = serve_json_routes(vec![
+            ("/functions", r#"{"functions": [
+                {"id": "fn-other", "name": "ai-mistral-7b-instruct", "status": "ACTIVE"}
+            ]}"#),
+        ], "404 Not Found");
+        let mut client = NgcClient::with_base_urls("test-key".to_string(), &base, &base).unwrap();
+
+        // The baseline resolved llama; the other model never had a function
+        let baseline = ScanReport::new(1, NimFindings {
+            local_nim: vec![],
+            hosted_nim: vec![hosted_call(Some("meta/llama-3.1-8b-instruct"), Some("fn-llama"))],
+            sdk_dependencies: vec![],
+        }, NimFindings::default());
+
+        let mut source_code = NimFindings {
+            local_nim: vec![],
+            hosted_nim: vec![
+                hosted_call(Some("meta/llama-3.1-8b-instruct"), None),
+                hosted_call(Some("nvidia/never-deployed"), None),
+            ],
+            sdk_dependencies: vec![],
+        };
+        client.enrich_all(&mut source_code, &mut NimFindings::default(), &EnrichStage::DEFAULT_SET);
+        assert!(source_code.hosted_nim.iter().all(|m| m.enrichment_error == Some(EnrichmentError::NotFound)));
+
+        let mut report = ScanReport::new(1, source_code, NimFindings::default());
+        crate::analysis::classify_resolution(&mut report, Some(&baseline));
+
+        let resolution = |model: &str| report.aggregated.hosted_nim.iter()
+            .find(|e| e.model_name.as_deref() == Some(model))
+            .and_then(|e| e.resolution);
+        assert_eq!(resolution("meta/llama-3.1-8b-instruct"), Some(Resolution::NewlyUnresolved));
+        assert_eq!(resolution("nvidia/never-deployed"), Some(Resolution::NeverResolved));
+        assert_eq!(report.summary.resolution.newly_unresolved, 1);
+        assert_eq!(report.summary.resolution.never_resolved, 1);
+
+        assert_eq!(report.possible_retirements.len(), 1);
+        let retirement = &report.possible_retirements[0];
+        assert_eq!(retirement.model, "meta/llama-3.1-8b-instruct");
+        assert_eq!(retirement.baseline_function_id, "fn-llama");
+        assert_eq!(retirement.locations[0].file_path, "client.py");
+
+        // Without a baseline nothing is "newly" unresolved
+        let mut report = ScanReport::new(1, report.source_code.clone(), NimFindings::default());
+        crate::analysis::classify_resolution(&mut report, None);
+        assert_eq!(report.summary.resolution.never_resolved, 2);
+        assert!(report.possible_retirements.is_empty());
+    }
+
+    #[test]
+    fn test_resource_downloads_are_not_looked_up() {
+        let mut m = local_latest("meta/llama-3.1-8b-instruct-weights");
+        assert!(needs_tag_resolution(&m));
+        m.detection_rule = "ngc_resource_download".to_string();
+        assert!(!needs_tag_resolution(&m));
+        m.tag = "1.0".to_string();
+        assert!(!needs_tag_check(&m));
+    }
+
+    #[test]
+    fn test_model_cards_cached_per_model() {
+        let (base, hits) = serve_json_routes(vec![
+            ("/meta/llama-3.1-8b-instruct", r#"{"publisher": "Meta", "modalities": ["text"], "maxContext": 131072}"#),
+            ("/nvidia/llama-3.2-nv-embedqa-1b-v2", r#"{"publisher": "NVIDIA", "modalities": ["text", "embedding"]}"#),
+        ], "404 Not Found");
+        let findings = NimFindings {
+            local_nim: Vec::new(),
+            hosted_nim: vec![
+                hosted_call(Some("nvidia/llama-3.2-nv-embedqa-1b-v2"), None),
+                hosted_call(Some("meta/llama-3.1-8b-instruct"), None),
+                hosted_call(Some("meta/llama-3.1-8b-instruct"), None),
+                hosted_call(Some("acme/unlisted"), None),
+                hosted_call(None, Some("fn-1")),
+            ],
+            sdk_dependencies: vec![],
+        };
+        let mut client = ModelCardClient::with_base_url(&base).unwrap();
+        let mut cards = HashMap::new();
+        client.fill_model_cards(&[&findings], &mut cards);
+
+        assert_eq!(cards.len(), 2);
+        assert_eq!(cards["meta/llama-3.1-8b-instruct"], ModelCard {
+            publisher: Some("Meta".to_string()),
+            modalities: vec!["text".to_string()],
+            max_context: Some(131072),
+        });
+        assert_eq!(cards["nvidia/llama-3.2-nv-embedqa-1b-v2"].max_context, None);
+        // Most-referenced model first, each model once; a 404 is no outage
+        assert_eq!(*hits.lock().unwrap(), vec![
+            "/meta/llama-3.1-8b-instruct",
+            "/nvidia/llama-3.2-nv-embedqa-1b-v2",
+            "/acme/unlisted",
+        ]);
+        assert!(!client.unavailable);
+        assert_eq!(client.model_card("acme/unlisted"), None);
+        assert_eq!(hits.lock().unwrap().len(), 3);
+
+        let mut report = crate::models::ScanReport::new(1, findings, NimFindings::new());
+        report.aggregated.apply_model_cards(&cards);
+        let embed = report.aggregated.hosted_nim.iter()
+            .find(|e| e.model_name.as_deref() == Some("nvidia/llama-3.2-nv-embedqa-1b-v2"))
+            .unwrap();
+        assert_eq!(embed.publisher.as_deref(), Some("NVIDIA"));
+        let unlisted = serde_json::to_value(report.aggregated.hosted_nim.iter()
+            .find(|e| e.model_name.as_deref() == Some("acme/unlisted"))
+            .unwrap()).unwrap();
+        assert!(unlisted.get("publisher").is_none());
+        let modality = report.aggregated.modality_counts();
+        assert_eq!(modality.into_iter().collect::<Vec<_>>(), vec![("embedding".to_string(), 1), ("text".to_string(), 2)]);
+    }
+
+    #[test]
+    fn test_model_cards_catalog_unavailable() {
+        let (base, hits) = serve_json_routes(Vec::new(), "503 Service Unavailable");
+        let findings = NimFindings {
+            local_nim: Vec::new(),
+            hosted_nim: vec![
+                hosted_call(Some("meta/llama-3.1-8b-instruct"), None),
+                hosted_call(Some("nvidia/llama-3.2-nv-embedqa-1b-v2"), None),
+            ],
+            sdk_dependencies: vec![],
+        };
+        let mut client = ModelCardClient::with_base_url(&base).unwrap();
+        let mut cards = HashMap::new();
+        client.fill_model_cards(&[&findings], &mut cards);
+        // One failed request, then none: the fields stay empty
+        assert!(cards.is_empty());
+        assert!(client.unavailable);
+        assert_eq!(hits.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_enrich_hosted_nim_canonical_endpoint() {
+        use crate::models::ScanReport;
+
+        let (base, _) = serve_json_routes(vec![
+            ("/functions", r#"{"functions": [
+                {"id": "fn-llama", "name": "ai-llama-3_1-8b-instruct", "status": "ACTIVE"},
+                {"id": "fn-flux", "name": "ai-flux_1-dev", "status": "ACTIVE"}
+            ]}"#),
+            ("/functions/fn-llama/versions", r#"{"functions": [
+                {"id": "fn-llama", "status": "ACTIVE", "models": [{"name": "meta/llama-3.1-8b-instruct"}],
+                 "inferenceUrl": "https://integrate.api.nvidia.com/v1/chat/completions", "healthUri": "/v1/health/ready"}
+            ]}"#),
+            ("/functions/fn-flux/versions", r#"{"functions": [
+                {"id": "fn-flux", "status": "ACTIVE", "models": [{"name": "black-forest-labs/flux.1-dev"}],
+                 "inferenceUrl": "https://ai.api.nvidia.com/v1/genai/black-forest-labs/flux.1-dev"}
+            ]}"#),
+        ], "404 Not Found");
+        let mut client = NgcClient::with_base_urls("test-key".to_string(), &base, &base).unwrap();
+
+        let with_endpoint = |model: &str, endpoint: &str| {
+            let mut m = hosted_call(Some(model), None);
+            m.endpoint_url = Some(endpoint.to_string());
+            m
+        };
+        let mut source_code = NimFindings {
+            local_nim: vec![],
+            hosted_nim: vec![
+                // Model name only: the inference URL is backfilled
+                hosted_call(Some("meta/llama-3.1-8b-instruct"), None),
+                // OpenAI-compatible base URL of the same route
+                with_endpoint("meta/llama-3.1-8b-instruct", "https://integrate.api.nvidia.com/v1"),
+                // Code calls integrate.api, NVCF serves it from ai.api
+                with_endpoint("black-forest-labs/flux.1-dev", "https://integrate.api.nvidia.com/v1/chat/completions"),
+            ],
+            sdk_dependencies: vec![],
+        };
+        client.enrich_hosted_nim_matches(&mut [&mut source_code]);
+
+        let hosted = &source_code.hosted_nim;
+        assert_eq!(hosted[0].endpoint_url, None);
+        assert_eq!(hosted[0].canonical_endpoint.as_deref(), Some("https://integrate.api.nvidia.com/v1/chat/completions"));
+        assert!(!hosted[0].endpoint_mismatch);
+        assert!(!hosted[1].endpoint_mismatch);
+        assert_eq!(hosted[2].endpoint_url.as_deref(), Some("https://integrate.api.nvidia.com/v1/chat/completions"));
+        assert_eq!(hosted[2].canonical_endpoint.as_deref(), Some("https://ai.api.nvidia.com/v1/genai/black-forest-labs/flux.1-dev"));
+        assert!(hosted[2].endpoint_mismatch);
+
+        let report = ScanReport::new(1, source_code, NimFindings::default());
+        assert_eq!(report.summary.endpoint_mismatches, 1);
+        let flux = report.aggregated.hosted_nim.iter()
+            .find(|e| e.model_name.as_deref() == Some("black-forest-labs/flux.1-dev"))
+            .unwrap();
+        assert_eq!(flux.canonical_endpoint.as_deref(), Some("https://ai.api.nvidia.com/v1/genai/black-forest-labs/flux.1-dev"));
+
+        // A path-only inference URL is compared by path
+        assert!(!endpoints_disagree("https://integrate.api.nvidia.com/v1/", "/v1/chat/completions"));
+        assert!(endpoints_disagree("https://ai.api.nvidia.com/v1/cv/nvidia/x", "/v1/chat/completions"));
+        assert!(!endpoints_disagree("https://Integrate.API.nvidia.com/v1/chat/completions?x=1", "https://integrate.api.nvidia.com/v1"));
+    }
+
+    #[test]
+    fn test_image_matches() {
+        assert!(image_matches("nvcr.io/nim/meta/llama:1.1", "NVCR.io/nim/meta/llama:1.1", false));
+        assert!(!image_matches("nvcr.io/nim/meta/llama:1.1", "nvcr.io/nim/meta/llama", false));
+        assert!(image_matches("nvcr.io/nim/meta/llama@sha256:abc", "nvcr.io/nim/meta/llama:1.1", true));
+        assert!(image_matches("localhost:5000/meta/llama:2", "localhost:5000/meta/llama", true));
+        assert!(!image_matches("nvcr.io/nim/meta/llama-ft:1.1", "nvcr.io/nim/meta/llama:1.1", true));
+    }
+
+    #[test]
+    fn test_enrich_hosted_nim_from_function_id() {
+        use crate::models::{HostedNimMatch, InvokeStyle};
+
+        // Only a function ID is known, so its details come from the versions
+        // endpoint (latest version first); the function list is never needed
+        let (base, hits) = serve_json_routes(vec![
+            ("/v2/nvcf/functions/0c5a7e4b-2f3d-4c8e-9a1b-6d7e8f9a0b1c/versions", r#"{
+                "functions": [{
+                    "id": "0c5a7e4b-2f3d-4c8e-9a1b-6d7e8f9a0b1c",
+                    "name": "ai-llama-3_3-70b-instruct",
+                    "status": "ACTIVE",
+                    "containerImage": "nvcr.io/nvidia/nim/llama-3.3-70b-instruct:1.8.0",
+                    "models": [{ "name": "meta/llama-3.3-70b-instruct" }]
+                }, {
+                    "id": "0c5a7e4b-2f3d-4c8e-9a1b-6d7e8f9a0b1c",
+                    "name": "ai-llama-3_3-70b-instruct",
+                    "status": "INACTIVE",
+                    "containerImage": "nvcr.io/nvidia/nim/llama-3.3-70b-instruct:1.7.0",
+                    "models": [{ "name": "meta/llama-3.3-70b-instruct" }]
+                }]
+            }"#),
+        ], "404 Not Found");
+        let nvcf_base = format!("{}/v2/nvcf", base);
+        let mut client = NgcClient::with_base_urls("test-key".to_string(), &base, &nvcf_base).unwrap();
+
+        let mut findings = NimFindings {
+            local_nim: vec![],
+            hosted_nim: vec![HostedNimMatch {
+                repository: "test/repo".to_string(),
+                endpoint_url: None,
+                model_name: None,
+                file_path: "client.py".to_string(),
+                line_number: 3,
+                match_context: r#"FUNCTION_ID = "0c5a7e4b-2f3d-4c8e-9a1b-6d7e8f9a0b1c""#.to_string(),
+                function_id: Some("0c5a7e4b-2f3d-4c8e-9a1b-6d7e8f9a0b1c".to_string()),
+                status: None,
+                container_image: None,
+                environment: Environment::Production,
+                endpoint_host: None,
+                invoke_style: InvokeStyle::Unknown,
+                framework: Framework::Unknown,
+                call_params: None,
+                policy: None,
+                model_policy: None,
+                enrichment_error: None,
+                enriched: false,
+                detection_rule: String::new(),
+                workflow_triggers: Vec::new(),
+                nim_identity: None,
+                enrichment_skipped: None,
+                browser_exposed: false,
+                did_you_mean: Vec::new(),
+                credential_source: None,
+                context_truncated: None,
+                canonical_endpoint: None,
+                endpoint_mismatch: false,
+                endpoint_status: None,
+                suggested_endpoint: None,
+                blame: None,
+                notebook_cell: None,
+                file_kind: FileKind::Source,
+                resolved_from: None,
+                fixture: false,
+                fingerprint: String::new(),
+            }],
+            sdk_dependencies: vec![],
+        };
+
+        client.enrich_hosted_nim_matches(&mut [&mut findings]);
+
+        let m = &findings.hosted_nim[0];
+        assert_eq!(m.model_name.as_deref(), Some("meta/llama-3.3-70b-instruct"));
+        assert_eq!(m.status.as_deref(), Some("ACTIVE"));
+        assert_eq!(m.container_image.as_deref(), Some("nvcr.io/nvidia/nim/llama-3.3-70b-instruct:1.8.0"));
+        assert_eq!(m.function_id.as_deref(), Some("0c5a7e4b-2f3d-4c8e-9a1b-6d7e8f9a0b1c"));
+        assert!(m.enriched);
+        assert_eq!(
+            *hits.lock().unwrap(),
+            vec!["/v2/nvcf/functions/0c5a7e4b-2f3d-4c8e-9a1b-6d7e8f9a0b1c/versions".to_string()]
+        );
+    }
+
     // =========================================================================
     // Integration Tests - Query Hosted NIM
     // Run with: NVIDIA_API_KEY=<key> cargo test --release -- --ignored --nocapture
@@ -815,7 +3005,7 @@ mod tests {
         assert!(info.container_image.is_some(), "Should have container_image");
         
         // Verify function name matches expected pattern
-        assert!(info.name.as_ref().map_or(false, |n| n.contains("llama-3_3-70b")),
+        assert!(info.name.as_ref().is_some_and(|n| n.contains("llama-3_3-70b")),
                 "Function name should contain llama-3_3-70b");
     }
 
@@ -875,7 +3065,7 @@ mod tests {
         assert!(info.function_id.is_some(), "Should have function_id");
         assert_eq!(info.status.as_deref(), Some("ACTIVE"), "Should be ACTIVE");
         assert!(info.container_image.is_some(), "Should have container_image");
-        assert!(info.container_image.as_ref().map_or(false, |c| c.contains("paddleocr")),
+        assert!(info.container_image.as_ref().is_some_and(|c| c.contains("paddleocr")),
                 "Container image should contain paddleocr");
     }
 
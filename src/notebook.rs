@@ -0,0 +1,238 @@
+//! Jupyter notebooks scanned cell by cell
+//!
+//! An `.ipynb` file is JSON: scanned as text, its code shows up as escaped
+//! strings (`"llm = ChatNVIDIA(model=\"meta/...\")\n",`) next to cell outputs
+//! and metadata. Here the notebook is parsed and its code cells are joined
+//! into one document and its Markdown cells into another, so each is scanned
+//! like a source file or a README. Every line of those documents remembers the
+//! cell it came from and the line of the `.ipynb` file holding it, so findings
+//! keep the notebook's path with a line number that opens at the right place.
+//! Raw cells and outputs are not scanned.
+
+use serde_json::Value;
+
+use crate::models::{NotebookCell, NotebookCellKind};
+
+/// One scanned document of a notebook: its text and where each line came from
+#[derive(Debug, Default)]
+pub struct CellDocument {
+    /// Cell sources, a blank line between cells
+    pub text: String,
+    /// Origin of each line of `text`, by 0-indexed line
+    origins: Vec<LineOrigin>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct LineOrigin {
+    /// 1-indexed line of the `.ipynb` file
+    file_line: usize,
+    cell: NotebookCell,
+}
+
+impl CellDocument {
+    fn push_line(&mut self, line: &str, origin: LineOrigin) {
+        self.text.push_str(line);
+        self.text.push('\n');
+        self.origins.push(origin);
+    }
+
+    /// `.ipynb` file line and cell of a 1-indexed line of `text`
+    pub fn locate(&self, line_number: usize) -> Option<(usize, NotebookCell)> {
+        let origin = self.origins.get(line_number.checked_sub(1)?)?;
+        Some((origin.file_line, origin.cell))
+    }
+}
+
+/// Code and Markdown cells of a notebook
+#[derive(Debug, Default)]
+pub struct Notebook {
+    /// Extension of the kernel language's source files (`py` when the notebook doesn't say)
+    pub code_extension: String,
+    pub code: CellDocument,
+    pub markdown: CellDocument,
+}
+
+/// Source of a cell as its list of strings (nbformat allows one string too)
+fn source_parts(cell: &Value) -> Vec<&str> {
+    match cell.get("source") {
+        Some(Value::String(source)) => vec![source.as_str()],
+        Some(Value::Array(parts)) => parts.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Finds each source string in the raw file, in order, to number lines as the file does
+struct RawLines<'a> {
+    lines: Vec<&'a str>,
+    cursor: usize,
+    /// Line of the last string found (where strings not found are placed)
+    last: usize,
+}
+
+impl<'a> RawLines<'a> {
+    fn new(content: &'a str) -> Self {
+        Self { lines: content.lines().collect(), cursor: 0, last: 1 }
+    }
+
+    /// Move to the next cell's `"source"` key, past its outputs (written before it)
+    fn seek_source(&mut self) {
+        let start = self.cursor.min(self.lines.len());
+        if let Some(offset) = self.lines[start..].iter().position(|line| line.contains("\"source\"")) {
+            self.cursor = start + offset;
+        }
+    }
+
+    /// 1-indexed file line holding `part`, as JSON-encoded by the notebook writer
+    fn find(&mut self, part: &str) -> usize {
+        let Ok(encoded) = serde_json::to_string(part) else {
+            return self.last;
+        };
+        let found = self.lines[self.cursor.min(self.lines.len())..]
+            .iter()
+            .position(|line| line.contains(&encoded));
+        if let Some(offset) = found {
+            let index = self.cursor + offset;
+            // Pretty-printed notebooks hold one string per line; a minified one holds them all on one
+            let trimmed = self.lines[index].trim();
+            let alone = trimmed == encoded || trimmed.strip_suffix(',') == Some(&encoded);
+            self.cursor = if alone { index + 1 } else { index };
+            self.last = index + 1;
+        }
+        self.last
+    }
+}
+
+impl Notebook {
+    /// Parse an `.ipynb` file (nbformat 4)
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let doc: Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+        let cells = doc.get("cells").and_then(Value::as_array).ok_or("no `cells` list")?;
+        let code_extension = doc
+            .pointer("/metadata/language_info/file_extension")
+            .and_then(Value::as_str)
+            .map(|ext| ext.trim_start_matches('.'))
+            .filter(|ext| !ext.is_empty())
+            .unwrap_or("py")
+            .to_string();
+
+        let mut notebook = Notebook { code_extension, ..Default::default() };
+        let mut raw = RawLines::new(content);
+        for (index, cell) in cells.iter().enumerate() {
+            let kind = match cell.get("cell_type").and_then(Value::as_str) {
+                Some("code") => NotebookCellKind::Code,
+                Some("markdown") => NotebookCellKind::Markdown,
+                _ => continue,
+            };
+            raw.seek_source();
+            let document = match kind {
+                NotebookCellKind::Code => &mut notebook.code,
+                NotebookCellKind::Markdown => &mut notebook.markdown,
+            };
+
+            // A string usually is one line, ending in "\n"; split or joined ones are re-cut into lines
+            let mut lines: Vec<(String, usize)> = Vec::new();
+            let mut at_line_start = true;
+            for part in source_parts(cell) {
+                let file_line = raw.find(part);
+                for piece in part.split_inclusive('\n') {
+                    match lines.last_mut() {
+                        Some((line, _)) if !at_line_start => line.push_str(piece),
+                        _ => lines.push((piece.to_string(), file_line)),
+                    }
+                    at_line_start = piece.ends_with('\n');
+                }
+            }
+            for (i, (line, file_line)) in lines.into_iter().enumerate() {
+                let cell = NotebookCell { index, line: i + 1, kind };
+                document.push_line(line.trim_end_matches(['\n', '\r']), LineOrigin { file_line, cell });
+            }
+            // Keep cells apart (a fence left open in one Markdown cell still leaks into the next)
+            if let Some(&last) = document.origins.last() {
+                document.push_line("", last);
+            }
+        }
+        Ok(notebook)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_notebook() {
+        let content = r##"{
+ "cells": [
+  {
+   "cell_type": "markdown",
+   "metadata": {},
+   "source": [
+    "# RAG with meta/llama-3.1-70b-instruct\n",
+    "\n",
+    "Uses the hosted API."
+   ]
+  },
+  {
+   "cell_type": "code",
+   "execution_count": 1,
+   "metadata": {},
+   "outputs": [
+    {
+     "name": "stdout",
+     "output_type": "stream",
+     "text": [
+      "llm = ChatNVIDIA(model=\"meta/llama-3.1-70b-instruct\")"
+     ]
+    }
+   ],
+   "source": [
+    "from langchain_nvidia_ai_endpoints import ChatNVIDIA\n",
+    "\n",
+    "llm = ChatNVIDIA(model=\"meta/llama-3.1-70b-instruct\")"
+   ]
+  },
+  {
+   "cell_type": "raw",
+   "metadata": {},
+   "source": ["image: nvcr.io/nim/meta/llama-3.1-8b-instruct:1.0.0"]
+  },
+  {
+   "cell_type": "code",
+   "metadata": {},
+   "outputs": [],
+   "source": "!docker pull nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3.3\nprint('pulled')\n"
+  }
+ ],
+ "metadata": {"language_info": {"name": "python", "file_extension": ".py"}},
+ "nbformat": 4,
+ "nbformat_minor": 5
+}
+"##;
+        let notebook = Notebook::parse(content).unwrap();
+        assert_eq!(notebook.code_extension, "py");
+        assert_eq!(notebook.code.text, "\
+from langchain_nvidia_ai_endpoints import ChatNVIDIA
+
+llm = ChatNVIDIA(model=\"meta/llama-3.1-70b-instruct\")
+
+!docker pull nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3.3
+print('pulled')
+
+");
+        // Lines of the .ipynb file, not of the output that repeats the call
+        let code = NotebookCellKind::Code;
+        assert_eq!(notebook.code.locate(3), Some((28, NotebookCell { index: 1, line: 3, kind: code })));
+        assert_eq!(notebook.code.locate(5), Some((40, NotebookCell { index: 3, line: 1, kind: code })));
+        assert_eq!(notebook.code.locate(6), Some((40, NotebookCell { index: 3, line: 2, kind: code })));
+        assert_eq!(notebook.markdown.locate(1).map(|(line, cell)| (line, cell.index)), Some((7, 0)));
+        assert!(!notebook.markdown.text.contains("nvcr.io"));
+
+        // Minified: every line is on line 1
+        let minified: Value = serde_json::from_str(content).unwrap();
+        let notebook = Notebook::parse(&minified.to_string()).unwrap();
+        assert_eq!(notebook.code.locate(3).map(|(line, cell)| (line, cell.line)), Some((1, 3)));
+
+        assert!(Notebook::parse("{\"cells\": [").is_err());
+        assert_eq!(Notebook::parse("{\"nbformat\": 4}").unwrap_err(), "no `cells` list");
+    }
+}
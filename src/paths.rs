@@ -0,0 +1,131 @@
+//! On-disk layout of scanner workdirs
+//!
+//! Cloning and `clean` resolve checkout locations the same way: each
+//! repository lives at `<workdir>/<sanitized name>`, an in-progress clone or
+//! tarball download at `<name>.partial-<pid>` next to it, and the marker file
+//! lists every checkout the scanner created so `clean` never touches anything
+//! else in the directory.
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use anyhow::{Context, Result, bail};
+
+/// File in a workdir listing the checkouts the scanner created (one directory name per line)
+pub const WORKDIR_MARKER: &str = ".nim-usage-scanner-checkouts";
+
+/// Filesystem-safe name for a repository (e.g. `NVIDIA/repo` -> `NVIDIA_repo`)
+///
+/// Used for clone directories and any per-repository output files.
+pub fn sanitize_repo_name(name: &str) -> String {
+    name.replace(['/', '\\'], "_")
+}
+
+/// Where a repository is checked out in a workdir
+pub fn checkout_dir(workdir: &Path, repo_name: &str) -> PathBuf {
+    workdir.join(sanitize_repo_name(repo_name))
+}
+
+/// Where this process builds a checkout before renaming it into place
+pub fn partial_dir(workdir: &Path, repo_name: &str) -> PathBuf {
+    workdir.join(format!("{}.partial-{}", sanitize_repo_name(repo_name), std::process::id()))
+}
+
+/// Whether a workdir entry is a partial checkout (`<name>.partial-<pid>`)
+fn is_partial_dir_name(name: &str) -> bool {
+    match name.rsplit_once(".partial-") {
+        Some((repo, pid)) => !repo.is_empty() && !pid.is_empty() && pid.bytes().all(|b| b.is_ascii_digit()),
+        None => false,
+    }
+}
+
+/// Checkout directory names recorded in a workdir's marker (None if there is no marker)
+fn recorded_checkouts(workdir: &Path) -> Option<BTreeSet<String>> {
+    let content = std::fs::read_to_string(workdir.join(WORKDIR_MARKER)).ok()?;
+    Some(content.lines().map(str::trim).filter(|l| !l.is_empty()).map(String::from).collect())
+}
+
+/// Add checkouts to the workdir's marker, keeping those recorded by earlier runs
+pub fn record_checkouts(workdir: &Path, checkouts: &[PathBuf]) -> Result<()> {
+    let mut names = recorded_checkouts(workdir).unwrap_or_default();
+    names.extend(checkouts.iter()
+        .filter_map(|path| path.file_name())
+        .map(|name| name.to_string_lossy().into_owned()));
+    let marker = workdir.join(WORKDIR_MARKER);
+    let content: String = names.iter().map(|name| format!("{}\n", name)).collect();
+    std::fs::write(&marker, content)
+        .with_context(|| format!("Failed to write {}", marker.display()))
+}
+
+/// What `clean` would remove from a workdir: the recorded checkouts that still
+/// exist, leftover partial checkouts and the marker itself
+///
+/// Refuses a directory without the marker, since nothing proves the scanner
+/// created its contents (pre-provisioned `--no-clone` checkouts never have one).
+pub fn workdir_removals(workdir: &Path) -> Result<Vec<PathBuf>> {
+    let recorded = match recorded_checkouts(workdir) {
+        Some(names) => names,
+        None => bail!(
+            "Refusing to clean {}: no {} file, so it is not a workdir the scanner cloned into",
+            workdir.display(), WORKDIR_MARKER
+        ),
+    };
+    let entries = std::fs::read_dir(workdir)
+        .with_context(|| format!("Failed to read {}", workdir.display()))?;
+    let mut removals: Vec<PathBuf> = entries
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            recorded.contains(&name) || is_partial_dir_name(&name)
+        })
+        .map(|entry| entry.path())
+        .collect();
+    removals.sort();
+    removals.push(workdir.join(WORKDIR_MARKER));
+    Ok(removals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_sanitize_repo_name() {
+        assert_eq!(sanitize_repo_name("NVIDIA-AI-Blueprints/rag"), "NVIDIA-AI-Blueprints_rag");
+        assert_eq!(sanitize_repo_name("org\\repo"), "org_repo");
+        assert_eq!(sanitize_repo_name("plain"), "plain");
+    }
+
+    #[test]
+    fn test_partial_dir_names() {
+        assert!(is_partial_dir_name("NVIDIA_repo.partial-4242"));
+        assert!(!is_partial_dir_name("NVIDIA_repo.partial-"));
+        assert!(!is_partial_dir_name("NVIDIA_repo.partial-old"));
+        assert!(!is_partial_dir_name("NVIDIA_repo"));
+        let partial = partial_dir(Path::new("work"), "NVIDIA/repo");
+        assert!(is_partial_dir_name(partial.file_name().unwrap().to_str().unwrap()));
+    }
+
+    #[test]
+    fn test_workdir_removals() {
+        let temp_dir = TempDir::new().unwrap();
+        let workdir = temp_dir.path();
+        for dir in ["org_a", "org_b", "org_c.partial-123", "user-notes"] {
+            std::fs::create_dir_all(workdir.join(dir)).unwrap();
+        }
+
+        // No marker: nothing proves these are scanner checkouts
+        let err = workdir_removals(workdir).unwrap_err().to_string();
+        assert!(err.contains("Refusing to clean"), "{}", err);
+
+        record_checkouts(workdir, &[checkout_dir(workdir, "org/a")]).unwrap();
+        record_checkouts(workdir, &[checkout_dir(workdir, "org/b"), checkout_dir(workdir, "org/gone")]).unwrap();
+        assert_eq!(workdir_removals(workdir).unwrap(), vec![
+            workdir.join("org_a"),
+            workdir.join("org_b"),
+            workdir.join("org_c.partial-123"),
+            workdir.join(WORKDIR_MARKER),
+        ]);
+    }
+}
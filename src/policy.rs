@@ -0,0 +1,803 @@
+//! Policy evaluation for findings
+//!
+//! After enrichment every finding is matched against the policy rules from
+//! repos.yaml followed by the built-in defaults. The first matching rule sets
+//! the finding's severity (`error`, `warning` or `info`) and rule name.
+//!
+//! Separately, a model policy file (`--model-policy`) flags Hosted NIM
+//! findings whose model is denied or missing from the allowlist.
+//!
+//! Before any of that, the path of each Hosted NIM endpoint is looked up in
+//! the endpoint rules (`endpoint_rules` from repos.yaml, then a built-in
+//! table), which mark it current, deprecated or removed and suggest the path
+//! to move to.
+
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Context, Result};
+use regex::Regex;
+
+use crate::models::{
+    EndpointRule, EndpointStatus, HostedNimMatch, LocalNimMatch, ModelPolicyFile, ModelPolicyViolation,
+    NimFindings, PolicyRule, PolicyVerdict, Severity,
+};
+
+/// Fields a condition can reference
+pub const CONDITION_FIELDS: &[&str] = &[
+    "nim_type",
+    "source_type",
+    "repository",
+    "file_path",
+    "image_url",
+    "tag",
+    "resolved_tag",
+    "endpoint_url",
+    "model_name",
+    "function_id",
+    "status",
+    "container_image",
+    "environment",
+    "invoke_style",
+    "intent",
+    "file_kind",
+    "usage_kind",
+    "endpoint_status",
+    "match_context",
+];
+
+/// Rules applied after the configured ones; the last one matches everything
+pub fn builtin_rules() -> Vec<PolicyRule> {
+    let rule = |name: &str, severity, when: &str| PolicyRule {
+        name: name.to_string(),
+        severity,
+        when: when.to_string(),
+    };
+    vec![
+        rule("local-latest-tag", Severity::Warning, "nim_type == local_nim && tag == latest"),
+        rule("hosted-inactive", Severity::Warning, "nim_type == hosted_nim && status == INACTIVE"),
+        rule("default", Severity::Info, ""),
+    ]
+}
+
+// ============================================================================
+// Conditions
+// ============================================================================
+
+#[derive(Debug)]
+enum Op {
+    Eq(String),
+    Ne(String),
+    Matches(Regex),
+}
+
+#[derive(Debug)]
+struct Clause {
+    field: &'static str,
+    op: Op,
+}
+
+/// A parsed `when` expression: all clauses must hold
+#[derive(Debug)]
+pub struct Condition {
+    clauses: Vec<Clause>,
+}
+
+impl Condition {
+    /// Parse `field == value && field != value && field =~ regex`
+    ///
+    /// Values may be wrapped in single or double quotes; a quoted value may
+    /// hold `&&` and operators. Missing finding fields compare as the empty string.
+    pub fn parse(expr: &str) -> Result<Self> {
+        let mut clauses = Vec::new();
+        for part in split_clauses(expr).into_iter().map(str::trim).filter(|p| !p.is_empty()) {
+            // The first operator ends the field name; anything after it is the value
+            let (at, op) = ["=~", "!=", "=="]
+                .iter()
+                .filter_map(|op| part.find(op).map(|at| (at, *op)))
+                .min_by_key(|(at, _)| *at)
+                .ok_or_else(|| anyhow!("expected 'field == value', 'field != value' or 'field =~ regex' in '{}'", part))?;
+            let field = part[..at].trim();
+            let value = unquote(part[at + op.len()..].trim());
+
+            let field = CONDITION_FIELDS
+                .iter()
+                .find(|f| **f == field)
+                .copied()
+                .ok_or_else(|| anyhow!("unknown field '{}' (expected one of: {})", field, CONDITION_FIELDS.join(", ")))?;
+
+            let op = match op {
+                "==" => Op::Eq(value.to_string()),
+                "!=" => Op::Ne(value.to_string()),
+                _ => Op::Matches(Regex::new(value).map_err(|e| anyhow!("invalid regex '{}': {}", value, e))?),
+            };
+            clauses.push(Clause { field, op });
+        }
+        Ok(Self { clauses })
+    }
+
+    fn matches(&self, finding: &Finding) -> bool {
+        self.clauses.iter().all(|clause| {
+            let actual = finding.field(clause.field);
+            match clause.op {
+                Op::Eq(ref v) => actual == v,
+                Op::Ne(ref v) => actual != v,
+                Op::Matches(ref re) => re.is_match(actual),
+            }
+        })
+    }
+}
+
+/// Split a `when` expression on the `&&`s outside quoted values
+///
+/// A quote opens a value only right after an operator, so an apostrophe
+/// inside an unquoted value is plain text.
+fn split_clauses(expr: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut quote: Option<char> = None;
+    let mut start = 0;
+    let mut previous = ' ';
+    let mut chars = expr.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if (c == '"' || c == '\'') && matches!(previous, '=' | '~') => quote = Some(c),
+            None if c == '&' && chars.peek().is_some_and(|&(_, next)| next == '&') => {
+                chars.next();
+                parts.push(&expr[start..i]);
+                start = i + 2;
+            }
+            None => {}
+        }
+        if !c.is_whitespace() {
+            previous = c;
+        }
+    }
+    parts.push(&expr[start..]);
+    parts
+}
+
+fn unquote(value: &str) -> &str {
+    for q in ['"', '\''] {
+        if let Some(inner) = value.strip_prefix(q).and_then(|v| v.strip_suffix(q)) {
+            return inner;
+        }
+    }
+    value
+}
+
+/// Borrowed view of a finding for condition evaluation
+enum Finding<'a> {
+    Local(&'a str, &'a LocalNimMatch),
+    Hosted(&'a str, &'a HostedNimMatch),
+}
+
+impl Finding<'_> {
+    fn field(&self, name: &str) -> &str {
+        match *self {
+            Finding::Local(source_type, m) => match name {
+                "nim_type" => "local_nim",
+                "source_type" => source_type,
+                "repository" => &m.repository,
+                "file_path" => &m.file_path,
+                "image_url" => &m.image_url,
+                "tag" => &m.tag,
+                "resolved_tag" => m.resolved_tag.as_deref().unwrap_or(""),
+                "intent" => m.intent.as_str(),
+                "file_kind" => m.file_kind.as_str(),
+                "usage_kind" => m.usage_kind.as_str(),
+                "match_context" => &m.match_context,
+                _ => "",
+            },
+            Finding::Hosted(source_type, m) => match name {
+                "nim_type" => "hosted_nim",
+                "source_type" => source_type,
+                "repository" => &m.repository,
+                "file_path" => &m.file_path,
+                "endpoint_url" => m.endpoint_url.as_deref().unwrap_or(""),
+                "model_name" => m.model_name.as_deref().unwrap_or(""),
+                "function_id" => m.function_id.as_deref().unwrap_or(""),
+                "status" => m.status.as_deref().unwrap_or(""),
+                "container_image" => m.container_image.as_deref().unwrap_or(""),
+                "environment" => m.environment.as_str(),
+                "invoke_style" => m.invoke_style.as_str(),
+                "file_kind" => m.file_kind.as_str(),
+                "endpoint_status" => m.endpoint_status.map_or("", |status| status.as_str()),
+                "match_context" => &m.match_context,
+                _ => "",
+            },
+        }
+    }
+}
+
+// ============================================================================
+// Engine
+// ============================================================================
+
+/// Compiled policy rules (configured rules first, then built-ins)
+pub struct PolicyEngine {
+    rules: Vec<(PolicyRule, Condition)>,
+}
+
+impl PolicyEngine {
+    /// Compile the configured rules followed by the built-in defaults
+    pub fn new(configured: &[PolicyRule]) -> Result<Self> {
+        let mut rules = Vec::new();
+        for rule in configured.iter().cloned().chain(builtin_rules()) {
+            let condition = match Condition::parse(&rule.when) {
+                Ok(c) => c,
+                Err(e) => bail!("Invalid condition for policy '{}': {}", rule.name, e),
+            };
+            rules.push((rule, condition));
+        }
+        Ok(Self { rules })
+    }
+
+    fn evaluate(&self, finding: &Finding) -> Option<PolicyVerdict> {
+        self.rules
+            .iter()
+            .find(|(_, condition)| condition.matches(finding))
+            .map(|(rule, _)| PolicyVerdict {
+                severity: rule.severity,
+                policy_rule: rule.name.clone(),
+            })
+    }
+
+    /// Assign a verdict to every finding in the category
+    pub fn apply(&self, findings: &mut NimFindings, source_type: &str) {
+        for m in &mut findings.local_nim {
+            m.policy = self.evaluate(&Finding::Local(source_type, m));
+        }
+        for m in &mut findings.hosted_nim {
+            m.policy = self.evaluate(&Finding::Hosted(source_type, m));
+        }
+    }
+}
+
+// ============================================================================
+// Endpoint Rules
+// ============================================================================
+
+/// Endpoint rules applied after the configured ones
+///
+/// Paths no rule matches get no status: an unknown path is neither current nor retired.
+pub fn builtin_endpoint_rules() -> Vec<EndpointRule> {
+    let rule = |path: &str, model: Option<&str>, status, suggested: Option<&str>| EndpointRule {
+        path: path.to_string(),
+        model: model.map(str::to_string),
+        status,
+        suggested: suggested.map(str::to_string),
+    };
+    vec![
+        // Chat and instruct models are served by chat completions only
+        rule("^/v1/completions$", Some("(?i)instruct|chat"), EndpointStatus::Deprecated, Some("/v1/chat/completions")),
+        rule("^/v1(/chat/completions|/completions|/embeddings|/ranking|/models)?$", None, EndpointStatus::Current, None),
+        rule("^/v1/retrieval/[^/]+(/[^/]+)?/reranking$", None, EndpointStatus::Current, None),
+        rule("^/v2/nvcf/", None, EndpointStatus::Current, None),
+        // The experimental v2 routes, and OpenAI's engine-scoped paths
+        rule("^/v2/(.+)$", None, EndpointStatus::Removed, Some("/v1/$1")),
+        rule("^/v1/engines/[^/]+/(.+)$", None, EndpointStatus::Removed, Some("/v1/$1")),
+    ]
+}
+
+/// Split an endpoint into what comes before its path (scheme and host, or a
+/// `${VAR}` base URL) and the path without query or trailing `/`
+fn split_endpoint(url: &str) -> Option<(&str, &str)> {
+    let url = url.split(['?', '#']).next().unwrap_or("");
+    let host_start = url.find("://").map_or(0, |i| i + 3);
+    let (base, path) = url.split_at(host_start + url[host_start..].find('/')?);
+    Some((base, path.trim_end_matches('/')))
+}
+
+/// Compiled regexes of an endpoint rule
+#[derive(Debug)]
+pub struct EndpointPattern {
+    path: Regex,
+    model: Option<Regex>,
+}
+
+impl EndpointPattern {
+    /// Compile the `path` and `model` regexes of a rule
+    pub fn compile(rule: &EndpointRule) -> Result<Self> {
+        let compile = |re: &str| Regex::new(re).map_err(|e| anyhow!("invalid regex '{}': {}", re, e));
+        Ok(Self {
+            path: compile(&rule.path)?,
+            model: rule.model.as_deref().map(compile).transpose()?,
+        })
+    }
+}
+
+/// Compiled endpoint rules (configured rules first, then built-ins)
+pub struct EndpointRules {
+    rules: Vec<(EndpointRule, EndpointPattern)>,
+}
+
+impl EndpointRules {
+    /// Compile the configured rules followed by the built-in table
+    pub fn new(configured: &[EndpointRule]) -> Result<Self> {
+        let mut rules = Vec::new();
+        for rule in configured.iter().cloned().chain(builtin_endpoint_rules()) {
+            let pattern = match EndpointPattern::compile(&rule) {
+                Ok(p) => p,
+                Err(e) => bail!("Invalid endpoint rule '{}': {}", rule.path, e),
+            };
+            rules.push((rule, pattern));
+        }
+        Ok(Self { rules })
+    }
+
+    /// Status of an endpoint and the endpoint to use instead, by the first
+    /// matching rule; the matched part of the path is replaced by `suggested`
+    pub fn evaluate(&self, endpoint: &str, model: Option<&str>) -> Option<(EndpointStatus, Option<String>)> {
+        let (base, path) = split_endpoint(endpoint)?;
+        let (rule, pattern) = self.rules.iter().find(|(_, pattern)| {
+            pattern.path.is_match(path)
+                && pattern.model.as_ref().is_none_or(|re| model.is_some_and(|model| re.is_match(model)))
+        })?;
+        let suggested = rule
+            .suggested
+            .as_deref()
+            .map(|suggested| format!("{}{}", base, pattern.path.replace(path, suggested)));
+        Some((rule.status, suggested))
+    }
+
+    /// Set `endpoint_status` and `suggested_endpoint` on every Hosted NIM finding with an endpoint
+    pub fn apply(&self, findings: &mut NimFindings) {
+        for m in &mut findings.hosted_nim {
+            let verdict = m
+                .endpoint_url
+                .as_deref()
+                .and_then(|endpoint| self.evaluate(endpoint, m.model_name.as_deref()));
+            (m.endpoint_status, m.suggested_endpoint) = match verdict {
+                Some((status, suggested)) => (Some(status), suggested),
+                None => (None, None),
+            };
+        }
+    }
+}
+
+// ============================================================================
+// Model Policy
+// ============================================================================
+
+/// Rule recorded when an allowlist exists and no allowed pattern matches
+pub const NOT_ALLOWED_RULE: &str = "not-allowed";
+
+/// Model name as compared against the policy: trimmed, lowercase, without a `stg/` prefix
+pub fn normalize_model_name(model: &str) -> String {
+    let model = model.trim().to_ascii_lowercase();
+    match model.strip_prefix("stg/") {
+        Some(rest) => rest.to_string(),
+        None => model,
+    }
+}
+
+/// Compile `/regex/` as a regex and anything else as a glob (`*` any run of
+/// characters, `?` one character); both match the whole name, case-insensitively
+fn compile_model_pattern(pattern: &str) -> Result<Regex> {
+    let pattern = pattern.trim();
+    if pattern.is_empty() {
+        bail!("empty pattern");
+    }
+    let source = match pattern.strip_prefix('/').and_then(|p| p.strip_suffix('/')) {
+        Some(re) if !re.is_empty() => format!("(?i){}", re),
+        _ => {
+            let glob: String = pattern
+                .split('*')
+                .map(|part| part.split('?').map(regex::escape).collect::<Vec<_>>().join("."))
+                .collect::<Vec<_>>()
+                .join(".*");
+            format!("(?i)^{}$", glob)
+        }
+    };
+    Regex::new(&source).map_err(|e| anyhow!("invalid regex '{}': {}", pattern, e))
+}
+
+fn compile_model_patterns(list: &str, patterns: &[String]) -> Result<Vec<(String, Regex)>> {
+    patterns
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            compile_model_pattern(p)
+                .map(|re| (p.clone(), re))
+                .map_err(|e| anyhow!("{}[{}]: {}", list, i, e))
+        })
+        .collect()
+}
+
+/// Compiled model allowlist/denylist
+#[derive(Debug)]
+pub struct ModelPolicy {
+    allowed: Vec<(String, Regex)>,
+    denied: Vec<(String, Regex)>,
+}
+
+impl ModelPolicy {
+    /// Load and compile a model policy YAML file
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read model policy file: {}", path.display()))?;
+        let file: ModelPolicyFile = serde_yaml::from_str(&content)
+            .with_context(|| format!("Failed to parse model policy file: {}", path.display()))?;
+        Self::new(&file).with_context(|| format!("Invalid model policy file: {}", path.display()))
+    }
+
+    /// Compile the patterns of a parsed policy file
+    pub fn new(file: &ModelPolicyFile) -> Result<Self> {
+        if file.allowed.is_empty() && file.denied.is_empty() {
+            bail!("expected at least one pattern under 'allowed' or 'denied'");
+        }
+        Ok(Self {
+            allowed: compile_model_patterns("allowed", &file.allowed)?,
+            denied: compile_model_patterns("denied", &file.denied)?,
+        })
+    }
+
+    /// Check one model name: a matching denied pattern wins, then an allowlist
+    /// (when present) must match
+    pub fn check(&self, model: &str) -> Option<ModelPolicyViolation> {
+        let model = normalize_model_name(model);
+        let rule = if let Some((pattern, _)) = self.denied.iter().find(|(_, re)| re.is_match(&model)) {
+            pattern.clone()
+        } else if !self.allowed.is_empty() && !self.allowed.iter().any(|(_, re)| re.is_match(&model)) {
+            NOT_ALLOWED_RULE.to_string()
+        } else {
+            return None;
+        };
+        Some(ModelPolicyViolation {
+            policy_violation: true,
+            policy_violation_rule: rule,
+        })
+    }
+
+    /// Flag Hosted NIM findings whose model breaks the policy (findings without a model are skipped)
+    pub fn apply(&self, findings: &mut NimFindings) {
+        for m in &mut findings.hosted_nim {
+            m.model_policy = m.model_name.as_deref().and_then(|model| self.check(model));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Environment, FileKind, Framework, InvokeStyle, LocalIntent, UsageKind};
+
+    fn local(tag: &str) -> LocalNimMatch {
+        LocalNimMatch {
+            repository: "test/repo".to_string(),
+            image_url: "nvcr.io/nim/meta/llama-3.1-8b-instruct".to_string(),
+            tag: tag.to_string(),
+            resolved_tag: None,
+            tag_exists: None,
+            file_path: "docker-compose.yaml".to_string(),
+            line_number: 3,
+            match_context: format!("image: nvcr.io/nim/meta/llama-3.1-8b-instruct:{}", tag),
+            policy: None,
+            enrichment_error: None,
+            enriched: false,
+            detection_rule: String::new(),
+            workflow_triggers: Vec::new(),
+            nim_identity: String::new(),
+            enrichment_skipped: None,
+            context_truncated: None,
+            compose: None,
+            intent: LocalIntent::Reference,
+            usage_kind: UsageKind::Other,
+            blame: None,
+            notebook_cell: None,
+            file_kind: FileKind::Source,
+            resolved_from: None,
+            fixture: false,
+            fingerprint: String::new(),
+        }
+    }
+
+    fn hosted(status: Option<&str>) -> HostedNimMatch {
+        HostedNimMatch {
+            repository: "test/repo".to_string(),
+            endpoint_url: None,
+            model_name: Some("meta/llama-3.1-8b-instruct".to_string()),
+            file_path: "client.py".to_string(),
+            line_number: 7,
+            match_context: r#"model="meta/llama-3.1-8b-instruct""#.to_string(),
+            function_id: None,
+            status: status.map(|s| s.to_string()),
+            container_image: None,
+            environment: Environment::Production,
+            endpoint_host: None,
+            invoke_style: InvokeStyle::Unknown,
+            framework: Framework::Unknown,
+            call_params: None,
+            policy: None,
+            model_policy: None,
+            enrichment_error: None,
+            enriched: false,
+            detection_rule: String::new(),
+            workflow_triggers: Vec::new(),
+            nim_identity: None,
+            enrichment_skipped: None,
+            browser_exposed: false,
+            did_you_mean: Vec::new(),
+            credential_source: None,
+            context_truncated: None,
+            canonical_endpoint: None,
+            endpoint_mismatch: false,
+            endpoint_status: None,
+            suggested_endpoint: None,
+            blame: None,
+            notebook_cell: None,
+            file_kind: FileKind::Source,
+            resolved_from: None,
+            fixture: false,
+            fingerprint: String::new(),
+        }
+    }
+
+    fn verdict(m: &Option<PolicyVerdict>) -> (Severity, &str) {
+        let v = m.as_ref().expect("every finding gets a verdict");
+        (v.severity, v.policy_rule.as_str())
+    }
+
+    #[test]
+    fn test_builtin_rules() {
+        let engine = PolicyEngine::new(&[]).unwrap();
+        let mut findings = NimFindings {
+            local_nim: vec![local("latest"), local("1.3.0")],
+            hosted_nim: vec![hosted(Some("INACTIVE")), hosted(Some("ACTIVE"))],
+            sdk_dependencies: vec![],
+        };
+        engine.apply(&mut findings, "source_code");
+
+        assert_eq!(verdict(&findings.local_nim[0].policy), (Severity::Warning, "local-latest-tag"));
+        assert_eq!(verdict(&findings.local_nim[1].policy), (Severity::Info, "default"));
+        assert_eq!(verdict(&findings.hosted_nim[0].policy), (Severity::Warning, "hosted-inactive"));
+        assert_eq!(verdict(&findings.hosted_nim[1].policy), (Severity::Info, "default"));
+    }
+
+    #[test]
+    fn test_configured_rule_and_precedence() {
+        let configured: Vec<PolicyRule> = serde_yaml::from_str(r#"
+- name: no-latest-in-workflows
+  severity: error
+  when: "nim_type == local_nim && source_type == actions_workflow && tag == 'latest'"
+- name: llama-anywhere
+  severity: info
+  when: "image_url =~ llama-3\\.1"
+"#).unwrap();
+        let engine = PolicyEngine::new(&configured).unwrap();
+
+        // Workflow: first configured rule wins over the later configured and built-in ones
+        let mut workflow = NimFindings { local_nim: vec![local("latest")], hosted_nim: vec![], sdk_dependencies: vec![] };
+        engine.apply(&mut workflow, "actions_workflow");
+        assert_eq!(verdict(&workflow.local_nim[0].policy), (Severity::Error, "no-latest-in-workflows"));
+
+        // Source code: configured regex rule beats the built-in latest-tag warning
+        let mut source = NimFindings { local_nim: vec![local("latest")], hosted_nim: vec![hosted(None)], sdk_dependencies: vec![] };
+        engine.apply(&mut source, "source_code");
+        assert_eq!(verdict(&source.local_nim[0].policy), (Severity::Info, "llama-anywhere"));
+        assert_eq!(verdict(&source.hosted_nim[0].policy), (Severity::Info, "default"));
+    }
+
+    #[test]
+    fn test_intent_rule() {
+        let configured: Vec<PolicyRule> = serde_yaml::from_str(r#"
+- name: no-nim-base-images
+  severity: error
+  when: "intent == base_image"
+"#).unwrap();
+        let engine = PolicyEngine::new(&configured).unwrap();
+        let mut base = local("1.0.0");
+        base.intent = LocalIntent::BaseImage;
+        let mut findings = NimFindings { local_nim: vec![base, local("1.0.0")], hosted_nim: vec![hosted(None)], sdk_dependencies: vec![] };
+        engine.apply(&mut findings, "source_code");
+        assert_eq!(verdict(&findings.local_nim[0].policy), (Severity::Error, "no-nim-base-images"));
+        assert_eq!(verdict(&findings.local_nim[1].policy), (Severity::Info, "default"));
+        assert_eq!(verdict(&findings.hosted_nim[0].policy), (Severity::Info, "default"));
+    }
+
+    #[test]
+    fn test_file_kind_rule() {
+        let configured: Vec<PolicyRule> = serde_yaml::from_str(r#"
+- name: no-nim-in-kubernetes
+  severity: error
+  when: "file_kind == kubernetes"
+"#).unwrap();
+        let engine = PolicyEngine::new(&configured).unwrap();
+        let mut manifest = local("1.0.0");
+        manifest.file_kind = FileKind::Kubernetes;
+        let mut findings = NimFindings { local_nim: vec![manifest, local("1.0.0")], hosted_nim: vec![], sdk_dependencies: vec![] };
+        engine.apply(&mut findings, "source_code");
+        assert_eq!(verdict(&findings.local_nim[0].policy), (Severity::Error, "no-nim-in-kubernetes"));
+        assert_eq!(verdict(&findings.local_nim[1].policy), (Severity::Info, "default"));
+    }
+
+    #[test]
+    fn test_builtin_endpoint_rules() {
+        let rules = EndpointRules::new(&[]).unwrap();
+        let with_endpoint = |endpoint: &str, model: &str| {
+            let mut m = hosted(None);
+            m.endpoint_url = Some(endpoint.to_string());
+            m.model_name = Some(model.to_string());
+            m
+        };
+        let mut findings = NimFindings {
+            local_nim: vec![],
+            hosted_nim: vec![
+                // Deprecated: text completions on an instruct model
+                with_endpoint("https://integrate.api.nvidia.com/v1/completions", "meta/llama-3.1-8b-instruct"),
+                // Current
+                with_endpoint("https://integrate.api.nvidia.com/v1/chat/completions", "meta/llama-3.1-8b-instruct"),
+                with_endpoint("https://integrate.api.nvidia.com/v1/completions", "bigcode/starcoder2-15b"),
+                // Unknown path: no annotation
+                with_endpoint("https://ai.api.nvidia.com/v1/cv/nvidia/nv-grounding-dino", "nvidia/nv-grounding-dino"),
+                // Removed, with the captured rest of the path carried over
+                with_endpoint("${NVIDIA_BASE_URL}/v2/embeddings?x=1", "nvidia/nv-embedqa-e5-v5"),
+                with_endpoint("https://api.nvcf.nvidia.com/v2/nvcf/pexec/functions/abc", "meta/llama-3.1-8b-instruct"),
+                hosted(None),
+            ],
+            sdk_dependencies: vec![],
+        };
+        rules.apply(&mut findings);
+        let annotations: Vec<(Option<EndpointStatus>, Option<&str>)> = findings
+            .hosted_nim
+            .iter()
+            .map(|m| (m.endpoint_status, m.suggested_endpoint.as_deref()))
+            .collect();
+        assert_eq!(annotations, vec![
+            (Some(EndpointStatus::Deprecated), Some("https://integrate.api.nvidia.com/v1/chat/completions")),
+            (Some(EndpointStatus::Current), None),
+            (Some(EndpointStatus::Current), None),
+            (None, None),
+            (Some(EndpointStatus::Removed), Some("${NVIDIA_BASE_URL}/v1/embeddings")),
+            (Some(EndpointStatus::Current), None),
+            (None, None),
+        ]);
+        assert_eq!(rules.evaluate("https://integrate.api.nvidia.com/v1/", None), Some((EndpointStatus::Current, None)));
+        assert_eq!(rules.evaluate("integrate.api.nvidia.com", None), None);
+    }
+
+    #[test]
+    fn test_configured_endpoint_rules() {
+        let configured: Vec<EndpointRule> = serde_yaml::from_str(r#"
+- path: "^/v1/ranking$"
+  status: deprecated
+  suggested: /v1/retrieval/nvidia/reranking
+- path: "^/v2/nvcf/exec/"
+  status: removed
+  suggested: /v2/nvcf/pexec/
+"#).unwrap();
+        let rules = EndpointRules::new(&configured).unwrap();
+        assert_eq!(
+            rules.evaluate("https://ai.api.nvidia.com/v1/ranking", None),
+            Some((EndpointStatus::Deprecated, Some("https://ai.api.nvidia.com/v1/retrieval/nvidia/reranking".to_string())))
+        );
+        // Only the matched part of the path is replaced
+        assert_eq!(
+            rules.evaluate("https://api.nvcf.nvidia.com/v2/nvcf/exec/functions/abc", None),
+            Some((EndpointStatus::Removed, Some("https://api.nvcf.nvidia.com/v2/nvcf/pexec/functions/abc".to_string())))
+        );
+
+        // Endpoint status is a condition field
+        let engine = PolicyEngine::new(&serde_yaml::from_str::<Vec<PolicyRule>>(r#"
+- name: retired-endpoint
+  severity: error
+  when: "endpoint_status == removed"
+"#).unwrap()).unwrap();
+        let mut findings = NimFindings { local_nim: vec![], hosted_nim: vec![hosted(None)], sdk_dependencies: vec![] };
+        findings.hosted_nim[0].endpoint_url = Some("https://api.nvcf.nvidia.com/v2/nvcf/exec/functions/abc".to_string());
+        rules.apply(&mut findings);
+        engine.apply(&mut findings, "source_code");
+        assert_eq!(verdict(&findings.hosted_nim[0].policy), (Severity::Error, "retired-endpoint"));
+
+        let bad: Vec<EndpointRule> = serde_yaml::from_str("- {path: \"^/v1/(\", status: removed}").unwrap();
+        let err = EndpointRules::new(&bad).err().unwrap().to_string();
+        assert!(err.starts_with("Invalid endpoint rule '^/v1/('"), "{}", err);
+        assert!(serde_yaml::from_str::<Vec<EndpointRule>>("- {path: /v1, status: retired}").is_err());
+    }
+
+    #[test]
+    fn test_condition_parse_errors() {
+        assert!(Condition::parse("").unwrap().clauses.is_empty());
+        assert!(Condition::parse("status != ACTIVE && model_name =~ ^meta/").is_ok());
+        assert!(Condition::parse("colour == red").unwrap_err().to_string().contains("unknown field"));
+        assert!(Condition::parse("tag latest").is_err());
+        assert!(Condition::parse("model_name =~ (").is_err());
+
+        let bad = PolicyRule { name: "broken".to_string(), severity: Severity::Error, when: "tag >> 1".to_string() };
+        let err = PolicyEngine::new(&[bad]).err().unwrap().to_string();
+        assert!(err.contains("broken"));
+    }
+
+    #[test]
+    fn test_condition_quoted_values() {
+        // `&&` inside a quoted regex doesn't end the clause
+        let condition = Condition::parse(r#"match_context =~ "a&&b|c" && nim_type == local_nim"#).unwrap();
+        assert_eq!(condition.clauses.len(), 2);
+        let mut m = local("1.0");
+        m.match_context = "x a&&b y".to_string();
+        assert!(condition.matches(&Finding::Local("source_code", &m)));
+        m.match_context = "a".to_string();
+        assert!(!condition.matches(&Finding::Local("source_code", &m)));
+
+        // The first operator in the clause splits it, not the first in the list
+        let condition = Condition::parse(r#"match_context == "a != b""#).unwrap();
+        m.match_context = "a != b".to_string();
+        assert!(condition.matches(&Finding::Local("source_code", &m)));
+        let condition = Condition::parse("file_path != a==b").unwrap();
+        assert!(matches!(&condition.clauses[0].op, Op::Ne(v) if v == "a==b"));
+
+        // An apostrophe in an unquoted value is text
+        let condition = Condition::parse("file_path =~ it's && tag == latest").unwrap();
+        assert_eq!(condition.clauses.len(), 2);
+    }
+
+    fn model_policy(yaml: &str) -> ModelPolicy {
+        ModelPolicy::new(&serde_yaml::from_str(yaml).unwrap()).unwrap()
+    }
+
+    fn rule(policy: &ModelPolicy, model: &str) -> Option<String> {
+        policy.check(model).map(|v| v.policy_violation_rule)
+    }
+
+    #[test]
+    fn test_model_policy_allowlist_only() {
+        let policy = model_policy("allowed: ['meta/llama-3.?-*', '/^nvidia/nv-embed/']");
+        assert_eq!(rule(&policy, "meta/llama-3.1-8b-instruct"), None);
+        assert_eq!(rule(&policy, "Meta/Llama-3.3-70B-Instruct"), None);
+        assert_eq!(rule(&policy, "stg/nvidia/nv-embedqa-e5-v5"), None);
+        assert_eq!(rule(&policy, "meta/llama-3.10-8b"), Some(NOT_ALLOWED_RULE.to_string()));
+        assert_eq!(rule(&policy, "mistralai/mixtral-8x7b-instruct-v0.1"), Some(NOT_ALLOWED_RULE.to_string()));
+    }
+
+    #[test]
+    fn test_model_policy_denylist_only() {
+        let policy = model_policy("denied: ['deepseek-ai/*', 'meta/llama-3.1-405b-instruct']");
+        assert_eq!(rule(&policy, "deepseek-ai/deepseek-r1"), Some("deepseek-ai/*".to_string()));
+        assert_eq!(rule(&policy, "meta/llama-3.1-405b-instruct"), Some("meta/llama-3.1-405b-instruct".to_string()));
+        assert_eq!(rule(&policy, "meta/llama-3.1-70b-instruct"), None);
+    }
+
+    #[test]
+    fn test_model_policy_denied_takes_precedence() {
+        let policy = model_policy("allowed: ['meta/*']\ndenied: ['meta/*-405b-*']");
+        assert_eq!(rule(&policy, "meta/llama-3.1-8b-instruct"), None);
+        assert_eq!(rule(&policy, "meta/llama-3.1-405b-instruct"), Some("meta/*-405b-*".to_string()));
+        assert_eq!(rule(&policy, "google/gemma-2-9b-it"), Some(NOT_ALLOWED_RULE.to_string()));
+
+        let mut findings = NimFindings {
+            local_nim: vec![],
+            hosted_nim: vec![hosted(None), HostedNimMatch { model_name: None, ..hosted(None) }],
+            sdk_dependencies: vec![],
+        };
+        findings.hosted_nim[0].model_name = Some("meta/llama-3.1-405b-instruct".to_string());
+        policy.apply(&mut findings);
+        assert_eq!(
+            findings.hosted_nim[0].model_policy,
+            Some(ModelPolicyViolation { policy_violation: true, policy_violation_rule: "meta/*-405b-*".to_string() })
+        );
+        assert_eq!(findings.hosted_nim[1].model_policy, None);
+    }
+
+    #[test]
+    fn test_model_policy_errors() {
+        let err = |yaml: &str| {
+            serde_yaml::from_str::<ModelPolicyFile>(yaml)
+                .map_err(anyhow::Error::from)
+                .and_then(|f| ModelPolicy::new(&f))
+                .unwrap_err()
+                .to_string()
+        };
+        assert!(err("allow: ['meta/*']").contains("unknown field `allow`"));
+        assert!(err("denied: ['meta/*', '/(/']").starts_with("denied[1]: invalid regex '/(/'"));
+        assert_eq!(err("allowed: ['meta/*', '  ']"), "allowed[1]: empty pattern");
+        assert!(err("allowed: []").contains("at least one pattern"));
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("models.yaml");
+        std::fs::write(&path, "denied: meta/*").unwrap();
+        let err = format!("{:#}", ModelPolicy::load(&path).unwrap_err());
+        assert!(err.starts_with("Failed to parse model policy file"));
+        assert!(err.contains("line 1"));
+    }
+}
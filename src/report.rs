@@ -2,17 +2,18 @@
 //!
 //! This module handles generating JSON and CSV reports from scan results.
 
-use std::collections::{HashMap, HashSet};
-use std::path::Path;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::fs::File;
 use std::io::Write;
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use log::info;
+use once_cell::sync::Lazy;
+use regex::bytes::Regex as BytesRegex;
 
-use crate::models::ScanReport;
-
-#[cfg(test)]
-use crate::models::{LocalNimMatch, HostedNimMatch};
+use crate::paths;
+use crate::models::{AggregatedHostedNim, EndpointStatus, EnrichmentSkip, FrameworkUsage, HostedNimMatch, LocalNimMatch, RepoHotspots, ReportDelta, ResolutionCounts, RunResult, ScanReport, ScanScope, SdkDependencyMatch, TriageWeights, VersionSpread};
+use crate::ngc_api::{FunctionByImageResult, HostedNimQueryResult, LocalNimQueryResult};
 
 // ============================================================================
 // JSON Report Generation
@@ -35,6 +36,260 @@ pub fn generate_json_report(report: &ScanReport, output_path: &Path) -> Result<(
     Ok(())
 }
 
+/// Load a previously written report.json (e.g. as a --baseline)
+pub fn load_json_report(path: &Path) -> Result<ScanReport> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read report: {}", path.display()))?;
+    serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse report: {}", path.display()))
+}
+
+// ============================================================================
+// Report Artifact Selection
+// ============================================================================
+
+/// An output file (or file pair) the scan can write
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReportArtifact {
+    /// report.json: full per-location report
+    DetailJson,
+    /// report.csv (+ report.schema.json): one row per finding
+    DetailCsv,
+    /// report.tsv: same rows and columns as report.csv, tab-separated
+    DetailTsv,
+    /// report_aggregate.json: unique NIMs per repository
+    AggregateJson,
+    /// report_aggregate.csv: unique NIMs per repository
+    AggregateCsv,
+    /// triage.md + triage.json: actionable findings, most urgent first
+    Triage,
+    /// hotspots.csv: findings per top- and second-level directory of each repository
+    Hotspots,
+}
+
+impl ReportArtifact {
+    /// Artifacts written when --reports is not given
+    pub const DEFAULT_SET: [ReportArtifact; 4] = [
+        ReportArtifact::DetailJson,
+        ReportArtifact::DetailCsv,
+        ReportArtifact::AggregateJson,
+        ReportArtifact::Triage,
+    ];
+}
+
+/// Check that an --output-prefix is safe to use in file names
+///
+/// Allows ASCII letters, digits, `.`, `_` and `-`, not starting with `.` or `-`.
+pub fn validate_output_prefix(prefix: &str) -> Result<()> {
+    if prefix.is_empty() {
+        bail!("Output prefix must not be empty");
+    }
+    if prefix.len() > 64 {
+        bail!("Output prefix is longer than 64 characters: {}", prefix);
+    }
+    if prefix.starts_with('.') || prefix.starts_with('-') {
+        bail!("Output prefix must not start with '.' or '-': {}", prefix);
+    }
+    if let Some(c) = prefix.chars().find(|c| !(c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))) {
+        bail!("Output prefix may only contain letters, digits, '.', '_' and '-' (found {:?}): {}", c, prefix);
+    }
+    Ok(())
+}
+
+/// Writes the selected report artifacts for a scan into one output directory
+pub struct ReportWriter<'a> {
+    output_dir: &'a Path,
+    artifacts: &'a [ReportArtifact],
+    csv_options: CsvOptions,
+    prefix: Option<&'a str>,
+    triage_weights: TriageWeights,
+}
+
+impl<'a> ReportWriter<'a> {
+    /// Create a writer for the given output directory and artifact selection
+    pub fn new(output_dir: &'a Path, artifacts: &'a [ReportArtifact], csv_options: CsvOptions) -> Self {
+        Self { output_dir, artifacts, csv_options, prefix: None, triage_weights: TriageWeights::default() }
+    }
+
+    /// Rank the triage artifact with these weights instead of the defaults
+    pub fn with_triage_weights(mut self, weights: TriageWeights) -> Self {
+        self.triage_weights = weights;
+        self
+    }
+
+    /// Prefix every artifact file name with `<prefix>_` (e.g. `acme_report.json`)
+    pub fn with_prefix(mut self, prefix: Option<&'a str>) -> Self {
+        self.prefix = prefix;
+        self
+    }
+
+    /// Path of an artifact file in the output directory, with the prefix applied
+    pub fn artifact_path(&self, file_name: &str) -> PathBuf {
+        match self.prefix {
+            Some(prefix) => self.output_dir.join(format!("{}_{}", prefix, file_name)),
+            None => self.output_dir.join(file_name),
+        }
+    }
+
+    /// Write every selected artifact (each at most once), returning the files written
+    pub fn write(&self, report: &ScanReport) -> Result<Vec<PathBuf>> {
+        std::fs::create_dir_all(self.output_dir)
+            .with_context(|| format!("Failed to create output directory: {}", self.output_dir.display()))?;
+
+        let selected: HashSet<ReportArtifact> = self.artifacts.iter().copied().collect();
+        let mut written = Vec::new();
+
+        if selected.contains(&ReportArtifact::DetailJson) {
+            let path = self.artifact_path("report.json");
+            generate_json_report(report, &path)
+                .context("Failed to generate JSON report")?;
+            written.push(path);
+        }
+        if selected.contains(&ReportArtifact::DetailCsv) {
+            let path = self.artifact_path("report.csv");
+            generate_csv_reports(report, &path, &self.csv_options)
+                .context("Failed to generate CSV reports")?;
+            written.push(csv_schema_path(&path));
+            written.push(path);
+        }
+        if selected.contains(&ReportArtifact::DetailTsv) {
+            let path = self.artifact_path("report.tsv");
+            generate_tsv_report(report, &path, &self.csv_options)
+                .context("Failed to generate TSV report")?;
+            written.push(path);
+        }
+        if selected.contains(&ReportArtifact::AggregateJson) {
+            let path = self.artifact_path("report_aggregate.json");
+            generate_aggregate_report(report, &path)
+                .context("Failed to generate aggregate report")?;
+            written.push(path);
+        }
+        if selected.contains(&ReportArtifact::AggregateCsv) {
+            let path = self.artifact_path("report_aggregate.csv");
+            generate_aggregate_csv(report, &path, &self.csv_options)
+                .context("Failed to generate aggregate CSV report")?;
+            written.push(path);
+        }
+        if selected.contains(&ReportArtifact::Triage) {
+            let (markdown, json) = (self.artifact_path("triage.md"), self.artifact_path("triage.json"));
+            generate_triage(report, &self.triage_weights, &markdown, &json)
+                .context("Failed to generate triage files")?;
+            written.push(markdown);
+            written.push(json);
+        }
+        if selected.contains(&ReportArtifact::Hotspots) {
+            let path = self.artifact_path("hotspots.csv");
+            generate_hotspots_csv(&report.hotspots, &path, &self.csv_options)
+                .context("Failed to generate hotspots CSV report")?;
+            written.push(path);
+        }
+
+        Ok(written)
+    }
+
+    /// Write result.json (the run's outcome for wrappers), returning its path
+    pub fn write_run_result(&self, run: &RunResult) -> Result<PathBuf> {
+        std::fs::create_dir_all(self.output_dir)
+            .with_context(|| format!("Failed to create output directory: {}", self.output_dir.display()))?;
+        let path = self.artifact_path("result.json");
+        let json = serde_json::to_string_pretty(run)
+            .context("Failed to serialize run result to JSON")?;
+        std::fs::write(&path, json)
+            .with_context(|| format!("Failed to write run result: {}", path.display()))?;
+        Ok(path)
+    }
+}
+
+// ============================================================================
+// Triage
+// ============================================================================
+
+/// Write triage.md and its JSON twin
+pub fn generate_triage(report: &ScanReport, weights: &TriageWeights, markdown_path: &Path, json_path: &Path) -> Result<()> {
+    let items = crate::triage::build_triage(report, weights);
+    info!("Writing triage ({} item(s)): {}", items.len(), markdown_path.display());
+    let mut markdown = crate::triage::render_markdown(&items);
+    if !report.framework_breakdown.is_empty() {
+        if !markdown.ends_with("\n\n") {
+            markdown.push('\n');
+        }
+        markdown.push_str(&framework_markdown(&report.framework_breakdown));
+    }
+    if !report.version_spread.is_empty() {
+        if !markdown.ends_with("\n\n") {
+            markdown.push('\n');
+        }
+        markdown.push_str(&version_spread_markdown(&report.version_spread));
+    }
+    let carded: Vec<&AggregatedHostedNim> = report.aggregated.hosted_nim.iter()
+        .filter(|e| e.publisher.is_some() || !e.modalities.is_empty() || e.max_context.is_some())
+        .collect();
+    if !carded.is_empty() {
+        if !markdown.ends_with("\n\n") {
+            markdown.push('\n');
+        }
+        markdown.push_str(&model_card_markdown(&carded));
+    }
+    std::fs::write(markdown_path, markdown)
+        .with_context(|| format!("Failed to write triage: {}", markdown_path.display()))?;
+    let json = serde_json::to_string_pretty(&items)
+        .context("Failed to serialize triage to JSON")?;
+    std::fs::write(json_path, json)
+        .with_context(|| format!("Failed to write triage: {}", json_path.display()))
+}
+
+/// "Framework adoption" table of triage.md: repositories, findings and models per framework
+fn framework_markdown(breakdown: &[FrameworkUsage]) -> String {
+    let mut out = String::from("## Framework adoption\n\n");
+    out.push_str("| Framework | Repositories | Findings | Models |\n");
+    out.push_str("|---|---|---|---|\n");
+    for usage in breakdown {
+        let models: Vec<String> = usage.models.iter().map(|m| format!("`{}`", m)).collect();
+        out.push_str(&format!(
+            "| {} | {} | {} | {} |\n",
+            usage.framework.as_str(), usage.repo_count, usage.findings, models.join(", ")
+        ));
+    }
+    out
+}
+
+/// "NIM version spread" table of triage.md: tags in use per Local NIM image
+fn version_spread_markdown(spread: &[VersionSpread]) -> String {
+    let mut out = String::from("## NIM version spread\n\n");
+    out.push_str("| Image | Tags in use | Repositories | Tags (repositories) |\n");
+    out.push_str("|---|---|---|---|\n");
+    for image in spread {
+        let tags: Vec<String> = image.tags.iter().map(|t| format!("`{}` ({})", t.tag, t.repo_count)).collect();
+        let distinct = match image.fragmented {
+            true => format!("**{}**", image.distinct_tags),
+            false => image.distinct_tags.to_string(),
+        };
+        out.push_str(&format!(
+            "| `{}` | {} | {} | {} |\n",
+            image.image_url, distinct, image.repo_count, tags.join(", ")
+        ));
+    }
+    out
+}
+
+/// "Hosted models" table of triage.md: publisher, modalities and context length per model card
+fn model_card_markdown(entries: &[&AggregatedHostedNim]) -> String {
+    let mut out = String::from("## Hosted models\n\n");
+    out.push_str("| Model | Publisher | Modalities | Max context |\n");
+    out.push_str("|---|---|---|---|\n");
+    for entry in entries {
+        out.push_str(&format!(
+            "| `{}` | {} | {} | {} |\n",
+            entry.model_name.as_deref().unwrap_or_default(),
+            entry.publisher.as_deref().unwrap_or(""),
+            entry.modalities.join(", "),
+            entry.max_context.map(|n| n.to_string()).unwrap_or_default()
+        ));
+    }
+    out
+}
+
 // ============================================================================
 // Aggregate Report Generation
 // ============================================================================
@@ -47,34 +302,18 @@ struct RepoAggregate {
     local_nims: Vec<String>,
 }
 
-/// Generate an aggregate report grouped by repository
-pub fn generate_aggregate_report(report: &ScanReport, output_path: &Path) -> Result<()> {
-    info!("Generating aggregate report: {}", output_path.display());
-
+/// Group unique NIMs by repository: repo -> (hosted model names, local image:tag)
+fn collect_repo_nims(report: &ScanReport) -> HashMap<String, (HashSet<String>, HashSet<String>)> {
     let mut repo_map: HashMap<String, (HashSet<String>, HashSet<String>)> = HashMap::new();
 
-    for m in &report.source_code.local_nim {
-        let entry = repo_map
-            .entry(m.repository.clone())
-            .or_insert_with(|| (HashSet::new(), HashSet::new()));
-        entry.1.insert(format!("{}:{}", m.image_url, m.tag));
-    }
-    for m in &report.actions_workflow.local_nim {
+    for m in report.source_code.local_nim.iter().chain(&report.actions_workflow.local_nim) {
         let entry = repo_map
             .entry(m.repository.clone())
             .or_insert_with(|| (HashSet::new(), HashSet::new()));
         entry.1.insert(format!("{}:{}", m.image_url, m.tag));
     }
 
-    for m in &report.source_code.hosted_nim {
-        if let Some(name) = m.model_name.as_ref() {
-            let entry = repo_map
-                .entry(m.repository.clone())
-                .or_insert_with(|| (HashSet::new(), HashSet::new()));
-            entry.0.insert(name.clone());
-        }
-    }
-    for m in &report.actions_workflow.hosted_nim {
+    for m in report.source_code.hosted_nim.iter().chain(&report.actions_workflow.hosted_nim) {
         if let Some(name) = m.model_name.as_ref() {
             let entry = repo_map
                 .entry(m.repository.clone())
@@ -83,6 +322,13 @@ pub fn generate_aggregate_report(report: &ScanReport, output_path: &Path) -> Res
         }
     }
 
+    repo_map
+}
+
+/// Build per-repository aggregates, sorted by repository name
+fn build_repo_aggregates(report: &ScanReport) -> Vec<RepoAggregate> {
+    let repo_map = collect_repo_nims(report);
+
     let mut aggregates: Vec<RepoAggregate> = repo_map
         .into_iter()
         .map(|(repo, (models, images))| {
@@ -101,6 +347,14 @@ pub fn generate_aggregate_report(report: &ScanReport, output_path: &Path) -> Res
         .collect();
 
     aggregates.sort_by(|a, b| a.repository.cmp(&b.repository));
+    aggregates
+}
+
+/// Generate an aggregate report grouped by repository
+pub fn generate_aggregate_report(report: &ScanReport, output_path: &Path) -> Result<()> {
+    info!("Generating aggregate report: {}", output_path.display());
+
+    let aggregates = build_repo_aggregates(report);
 
     let json = serde_json::to_string_pretty(&aggregates)
         .context("Failed to serialize aggregate report to JSON")?;
@@ -115,166 +369,897 @@ pub fn generate_aggregate_report(report: &ScanReport, output_path: &Path) -> Res
     Ok(())
 }
 
+/// Generate the aggregate report as CSV (NIM lists joined with `;`)
+pub fn generate_aggregate_csv(report: &ScanReport, output_path: &Path, opts: &CsvOptions) -> Result<()> {
+    info!("Generating aggregate CSV report: {}", output_path.display());
+
+    let mut writer = csv::Writer::from_path(output_path)
+        .with_context(|| format!("Failed to create CSV file: {}", output_path.display()))?;
+
+    writer.write_record(["repository", "repository_url", "hosted_nims", "local_nims"])?;
+    for a in build_repo_aggregates(report) {
+        writer.write_record([
+            opts.cell(&a.repository),
+            opts.cell(&a.repository_url),
+            opts.cell(&a.hosted_nims.join(";")),
+            opts.cell(&a.local_nims.join(";")),
+        ])?;
+    }
+
+    writer.flush()?;
+    info!("Aggregate CSV report written to {}", output_path.display());
+    Ok(())
+}
+
 // ============================================================================
-// CSV Report Generation
+// Directory Hotspots
 // ============================================================================
 
-/// Generate a unified CSV report file
-pub fn generate_csv_reports(report: &ScanReport, output_dir: &Path) -> Result<()> {
-    // Ensure output directory exists
-    std::fs::create_dir_all(output_dir)
-        .with_context(|| format!("Failed to create output directory: {}", output_dir.display()))?;
-    
-    let output_path = output_dir.join("report.csv");
-    info!("Generating unified CSV report: {}", output_path.display());
-    
-    let mut writer = csv::Writer::from_path(&output_path)
+/// One directory of one repository, flattened for CSV and table output
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct HotspotRow {
+    pub repository: String,
+    pub directory: String,
+    pub local_count: usize,
+    pub hosted_count: usize,
+}
+
+/// Flatten per-repository hotspots into rows, repository by repository
+pub fn hotspot_rows(hotspots: &[RepoHotspots]) -> Vec<HotspotRow> {
+    hotspots
+        .iter()
+        .flat_map(|repo| {
+            repo.directories.iter().map(|dir| HotspotRow {
+                repository: repo.repository.clone(),
+                directory: dir.directory.clone(),
+                local_count: dir.local_count,
+                hosted_count: dir.hosted_count,
+            })
+        })
+        .collect()
+}
+
+/// Write hotspots.csv: one row per repository directory with findings
+pub fn generate_hotspots_csv(hotspots: &[RepoHotspots], output_path: &Path, opts: &CsvOptions) -> Result<()> {
+    info!("Generating hotspots CSV report: {}", output_path.display());
+
+    let mut writer = csv::Writer::from_path(output_path)
         .with_context(|| format!("Failed to create CSV file: {}", output_path.display()))?;
-    
+
+    writer.write_record(HotspotRow::COLUMNS)?;
+    for row in hotspot_rows(hotspots) {
+        writer.write_record(row.row().iter().map(|cell| opts.cell(cell)))?;
+    }
+
+    writer.flush()?;
+    info!("Hotspots CSV report written to {}", output_path.display());
+    Ok(())
+}
+
+// ============================================================================
+// Badge Generation
+// ============================================================================
+
+/// Thresholds (on unique NIMs per repository) that pick the badge color
+#[derive(Debug, Clone, Copy)]
+pub struct BadgeThresholds {
+    /// Below this count the badge is grey
+    pub low: usize,
+    /// At or above this count the badge is bright green (in between: yellow-green)
+    pub high: usize,
+}
+
+impl Default for BadgeThresholds {
+    fn default() -> Self {
+        Self { low: 1, high: 5 }
+    }
+}
+
+impl BadgeThresholds {
+    /// Pick a shields.io color name for a total NIM count
+    pub fn color(&self, total: usize) -> &'static str {
+        if total < self.low {
+            "lightgrey"
+        } else if total < self.high {
+            "yellowgreen"
+        } else {
+            "brightgreen"
+        }
+    }
+}
+
+/// shields.io endpoint badge (https://shields.io/badges/endpoint-badge)
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ShieldsBadge {
+    schema_version: u32,
+    label: String,
+    message: String,
+    color: String,
+}
+
+/// Write one shields.io endpoint JSON per repository plus an index.json (repo -> badge file)
+///
+/// `repositories` lists every scanned repository so repos without findings still get a badge.
+/// Returns the files written.
+pub fn generate_badges(
+    report: &ScanReport,
+    repositories: &[String],
+    badges_dir: &Path,
+    thresholds: &BadgeThresholds,
+) -> Result<Vec<PathBuf>> {
+    info!("Generating badges in {}", badges_dir.display());
+
+    std::fs::create_dir_all(badges_dir)
+        .with_context(|| format!("Failed to create badges directory: {}", badges_dir.display()))?;
+
+    let repo_map = collect_repo_nims(report);
+    let mut index: BTreeMap<String, String> = BTreeMap::new();
+    let mut written = Vec::new();
+
+    let mut all_repos: BTreeSet<&str> = repositories.iter().map(|r| r.as_str()).collect();
+    all_repos.extend(repo_map.keys().map(|r| r.as_str()));
+
+    for repo in all_repos {
+        let (hosted, local) = repo_map
+            .get(repo)
+            .map(|(h, l)| (h.len(), l.len()))
+            .unwrap_or((0, 0));
+
+        let badge = ShieldsBadge {
+            schema_version: 1,
+            label: "NIM usage".to_string(),
+            message: format!("{} local, {} hosted", local, hosted),
+            color: thresholds.color(local + hosted).to_string(),
+        };
+
+        let file_name = format!("{}.json", paths::sanitize_repo_name(repo));
+        let path = badges_dir.join(&file_name);
+        let json = serde_json::to_string_pretty(&badge)
+            .context("Failed to serialize badge to JSON")?;
+        std::fs::write(&path, json)
+            .with_context(|| format!("Failed to write badge: {}", path.display()))?;
+        written.push(path);
+
+        index.insert(repo.to_string(), file_name);
+    }
+
+    let index_path = badges_dir.join("index.json");
+    let json = serde_json::to_string_pretty(&index)
+        .context("Failed to serialize badge index to JSON")?;
+    std::fs::write(&index_path, json)
+        .with_context(|| format!("Failed to write badge index: {}", index_path.display()))?;
+    written.push(index_path);
+
+    info!("Wrote {} badges to {}", index.len(), badges_dir.display());
+    Ok(written)
+}
+
+// ============================================================================
+// CSV Report Generation
+// ============================================================================
+
+/// Version of the report.csv / report.json layout. Bump when columns or fields change.
+pub const REPORT_SCHEMA_VERSION: &str = "1.15";
+
+/// CSV column contract: (name, type, description). Shared by the header row of report.csv and
+/// report.tsv and by report.schema.json.
+///
+/// Consumers index columns by position, so this list is append-only: new columns go at the end
+/// (with a REPORT_SCHEMA_VERSION bump) and existing ones are never renamed, removed or reordered.
+const CSV_COLUMNS: &[(&str, &str, &str)] = &[
+    ("source_type", "string", "source_code or actions_workflow"),
+    ("nim_type", "string", "local_nim, hosted_nim or sdk_dependency"),
+    ("repository", "string", "Repository name from repos.yaml"),
+    ("file_path", "string", "File path relative to the repository root"),
+    ("line_number", "integer", "1-indexed line number of the match"),
+    ("image_url", "string", "Local NIM only: image URL without tag"),
+    ("tag", "string", "Local NIM only: tag as written in the file"),
+    ("resolved_tag", "string", "Local NIM only: actual version for 'latest' (from NGC API)"),
+    ("endpoint_url", "string", "Hosted NIM only: API endpoint URL"),
+    ("model_name", "string", "Hosted NIM only: org/model"),
+    ("function_id", "string", "Hosted NIM only: NVCF function ID (from NGC API)"),
+    ("status", "string", "Hosted NIM only: function status (from NGC API)"),
+    ("container_image", "string", "Hosted NIM only: backing container image (from NGC API)"),
+    ("match_context", "string", "Trimmed line content that matched"),
+    ("severity", "string", "Policy severity: error, warning or info"),
+    ("policy_rule", "string", "Name of the policy rule that set the severity"),
+    ("environment", "string", "Hosted NIM only: production or staging"),
+    ("invoke_style", "string", "Hosted NIM only: openai_compatible, model_path, nvcf_pexec or unknown"),
+    ("policy_violation", "string", "Hosted NIM only: model policy rule the model violates (--model-policy); empty if none"),
+    ("author_email", "string", "Author of the last change to the line (--blame); empty without blame"),
+    ("author_date", "string", "Author date of that change, RFC 3339 (--blame); empty without blame"),
+    ("file_kind", "string", "dockerfile, compose, kubernetes, helm, notebook, docs, ci or source"),
+    ("fixture", "boolean", "true for test fixtures and the scanner's own repository, left out of the summary"),
+    ("usage_kind", "string", "Local NIM only: dockerfile_from, docker_run, compose_image, k8s_container, ci_service or other"),
+    ("fingerprint", "string", "Line-independent identity of the finding, `fp<version>:<hex>`; stable across scans"),
+    ("package_name", "string", "SDK dependency only: package name as declared"),
+    ("package_ecosystem", "string", "SDK dependency only: pypi, npm or conda"),
+    ("version_constraint", "string", "SDK dependency only: version specifier as written; empty if unpinned"),
+];
+
+/// Options controlling how cell values are written to CSV
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CsvOptions {
+    /// Replace CR/LF in match_context with literal `\r` / `\n`
+    pub flatten_newlines: bool,
+    /// Prefix cells starting with a formula trigger (`=`, `+`, `-`, `@`) with `'`
+    pub safe_formulas: bool,
+}
+
+impl CsvOptions {
+    /// Apply formula protection to a cell value
+    fn cell(&self, value: &str) -> String {
+        if self.safe_formulas && value.starts_with(['=', '+', '-', '@']) {
+            format!("'{}", value)
+        } else {
+            value.to_string()
+        }
+    }
+
+    /// Apply newline flattening (if enabled) and formula protection to match_context
+    fn context_cell(&self, value: &str) -> String {
+        if self.flatten_newlines {
+            self.cell(&value.replace('\r', "\\r").replace('\n', "\\n"))
+        } else {
+            self.cell(value)
+        }
+    }
+}
+
+/// Build a CSV row for a Local NIM match
+fn local_nim_row(source_type: &str, m: &LocalNimMatch, opts: &CsvOptions) -> Vec<String> {
+    vec![
+        source_type.to_string(),
+        "local_nim".to_string(),
+        opts.cell(&m.repository),
+        opts.cell(&m.file_path),
+        m.line_number.to_string(),
+        opts.cell(&m.image_url),
+        opts.cell(&m.tag),
+        opts.cell(m.resolved_tag.as_deref().unwrap_or("")),
+        String::new(), // endpoint_url
+        String::new(), // model_name
+        String::new(), // function_id
+        String::new(), // status
+        String::new(), // container_image
+        opts.context_cell(&m.match_context),
+        m.policy.as_ref().map(|p| p.severity.as_str()).unwrap_or("").to_string(),
+        opts.cell(m.policy.as_ref().map(|p| p.policy_rule.as_str()).unwrap_or("")),
+        String::new(), // environment
+        String::new(), // invoke_style
+        String::new(), // policy_violation
+        opts.cell(m.blame.as_ref().and_then(|b| b.author_email.as_deref()).unwrap_or("")),
+        m.blame.as_ref().and_then(|b| b.author_date.as_deref()).unwrap_or("").to_string(),
+        m.file_kind.as_str().to_string(),
+        m.fixture.to_string(),
+        m.usage_kind.as_str().to_string(),
+        m.fingerprint.clone(),
+        String::new(), // package_name
+        String::new(), // package_ecosystem
+        String::new(), // version_constraint
+    ]
+}
+
+/// Build a CSV row for a Hosted NIM match
+fn hosted_nim_row(source_type: &str, m: &HostedNimMatch, opts: &CsvOptions) -> Vec<String> {
+    vec![
+        source_type.to_string(),
+        "hosted_nim".to_string(),
+        opts.cell(&m.repository),
+        opts.cell(&m.file_path),
+        m.line_number.to_string(),
+        String::new(), // image_url
+        String::new(), // tag
+        String::new(), // resolved_tag
+        opts.cell(m.endpoint_url.as_deref().unwrap_or("")),
+        opts.cell(m.model_name.as_deref().unwrap_or("")),
+        opts.cell(m.function_id.as_deref().unwrap_or("")),
+        opts.cell(m.status.as_deref().unwrap_or("")),
+        opts.cell(m.container_image.as_deref().unwrap_or("")),
+        opts.context_cell(&m.match_context),
+        m.policy.as_ref().map(|p| p.severity.as_str()).unwrap_or("").to_string(),
+        opts.cell(m.policy.as_ref().map(|p| p.policy_rule.as_str()).unwrap_or("")),
+        m.environment.as_str().to_string(),
+        m.invoke_style.as_str().to_string(),
+        opts.cell(m.model_policy.as_ref().map(|v| v.policy_violation_rule.as_str()).unwrap_or("")),
+        opts.cell(m.blame.as_ref().and_then(|b| b.author_email.as_deref()).unwrap_or("")),
+        m.blame.as_ref().and_then(|b| b.author_date.as_deref()).unwrap_or("").to_string(),
+        m.file_kind.as_str().to_string(),
+        m.fixture.to_string(),
+        String::new(), // usage_kind
+        m.fingerprint.clone(),
+        String::new(), // package_name
+        String::new(), // package_ecosystem
+        String::new(), // version_constraint
+    ]
+}
+
+/// Build a CSV row for an SDK dependency declared in a manifest
+fn sdk_dependency_row(source_type: &str, m: &SdkDependencyMatch, opts: &CsvOptions) -> Vec<String> {
+    vec![
+        source_type.to_string(),
+        "sdk_dependency".to_string(),
+        opts.cell(&m.repository),
+        opts.cell(&m.file_path),
+        m.line_number.to_string(),
+        String::new(), // image_url
+        String::new(), // tag
+        String::new(), // resolved_tag
+        String::new(), // endpoint_url
+        String::new(), // model_name
+        String::new(), // function_id
+        String::new(), // status
+        String::new(), // container_image
+        opts.context_cell(&m.match_context),
+        String::new(), // severity
+        String::new(), // policy_rule
+        String::new(), // environment
+        String::new(), // invoke_style
+        String::new(), // policy_violation
+        String::new(), // author_email
+        String::new(), // author_date
+        String::new(), // file_kind
+        m.fixture.to_string(),
+        String::new(), // usage_kind
+        String::new(), // fingerprint
+        opts.cell(&m.package_name),
+        m.ecosystem.as_str().to_string(),
+        opts.cell(m.version_constraint.as_deref().unwrap_or("")),
+    ]
+}
+
+/// Field layout of the unified per-finding report
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Delimited {
+    /// report.csv: RFC 4180 quoting, cells kept verbatim
+    Csv,
+    /// report.tsv: tab-delimited, never quoted; tabs and line breaks in cells become spaces
+    Tsv,
+}
+
+impl Delimited {
+    fn writer(self, output_path: &Path) -> Result<csv::Writer<File>> {
+        let mut builder = csv::WriterBuilder::new();
+        if self == Delimited::Tsv {
+            builder.delimiter(b'\t').quote_style(csv::QuoteStyle::Never);
+        }
+        builder
+            .from_path(output_path)
+            .with_context(|| format!("Failed to create file: {}", output_path.display()))
+    }
+
+    /// Make a row safe for the layout; TSV cells must not contain separators
+    fn sanitize(self, mut row: Vec<String>) -> Vec<String> {
+        if self == Delimited::Tsv {
+            for cell in &mut row {
+                if cell.contains(['\t', '\r', '\n']) {
+                    *cell = cell.replace("\r\n", " ").replace(['\t', '\r', '\n'], " ");
+                }
+            }
+        }
+        row
+    }
+}
+
+/// Write the unified per-finding report (header from CSV_COLUMNS, one row per finding)
+///
+/// CSV and TSV both go through here so their columns can never drift apart.
+fn write_unified_report(report: &ScanReport, output_path: &Path, layout: Delimited, opts: &CsvOptions) -> Result<()> {
+    let mut writer = layout.writer(output_path)?;
+
     // Write header with all columns
-    writer.write_record([
-        "source_type",      // source_code or actions_workflow
-        "nim_type",         // local_nim or hosted_nim
-        "repository",
-        "file_path",
-        "line_number",
-        "image_url",        // Local NIM only
-        "tag",              // Local NIM only
-        "resolved_tag",     // Local NIM only (from NGC API)
-        "endpoint_url",     // Hosted NIM only
-        "model_name",       // Hosted NIM only
-        "function_id",      // Hosted NIM only (from NGC API)
-        "status",           // Hosted NIM only (from NGC API)
-        "container_image",  // Hosted NIM only (from NGC API)
-        "match_context",
-    ])?;
-    
-    // Write source_code local_nim
+    writer.write_record(CSV_COLUMNS.iter().map(|(name, _, _)| *name))?;
+
     for m in &report.source_code.local_nim {
-        writer.write_record([
-            "source_code",
-            "local_nim",
-            &m.repository,
-            &m.file_path,
-            &m.line_number.to_string(),
-            &m.image_url,
-            &m.tag,
-            m.resolved_tag.as_deref().unwrap_or(""),
-            "",  // endpoint_url
-            "",  // model_name
-            "",  // function_id
-            "",  // status
-            "",  // container_image
-            &m.match_context,
-        ])?;
+        writer.write_record(layout.sanitize(local_nim_row("source_code", m, opts)))?;
     }
-    
-    // Write source_code hosted_nim
     for m in &report.source_code.hosted_nim {
-        writer.write_record([
-            "source_code",
-            "hosted_nim",
-            &m.repository,
-            &m.file_path,
-            &m.line_number.to_string(),
-            "",  // image_url
-            "",  // tag
-            "",  // resolved_tag
-            m.endpoint_url.as_deref().unwrap_or(""),
-            m.model_name.as_deref().unwrap_or(""),
-            m.function_id.as_deref().unwrap_or(""),
-            m.status.as_deref().unwrap_or(""),
-            m.container_image.as_deref().unwrap_or(""),
-            &m.match_context,
-        ])?;
+        writer.write_record(layout.sanitize(hosted_nim_row("source_code", m, opts)))?;
     }
-    
-    // Write actions_workflow local_nim
     for m in &report.actions_workflow.local_nim {
-        writer.write_record([
-            "actions_workflow",
-            "local_nim",
-            &m.repository,
-            &m.file_path,
-            &m.line_number.to_string(),
-            &m.image_url,
-            &m.tag,
-            m.resolved_tag.as_deref().unwrap_or(""),
-            "",  // endpoint_url
-            "",  // model_name
-            "",  // function_id
-            "",  // status
-            "",  // container_image
-            &m.match_context,
-        ])?;
+        writer.write_record(layout.sanitize(local_nim_row("actions_workflow", m, opts)))?;
     }
-    
-    // Write actions_workflow hosted_nim
     for m in &report.actions_workflow.hosted_nim {
-        writer.write_record([
-            "actions_workflow",
-            "hosted_nim",
-            &m.repository,
-            &m.file_path,
-            &m.line_number.to_string(),
-            "",  // image_url
-            "",  // tag
-            "",  // resolved_tag
-            m.endpoint_url.as_deref().unwrap_or(""),
-            m.model_name.as_deref().unwrap_or(""),
-            m.function_id.as_deref().unwrap_or(""),
-            m.status.as_deref().unwrap_or(""),
-            m.container_image.as_deref().unwrap_or(""),
-            &m.match_context,
-        ])?;
+        writer.write_record(layout.sanitize(hosted_nim_row("actions_workflow", m, opts)))?;
     }
-    
+    for m in &report.source_code.sdk_dependencies {
+        writer.write_record(layout.sanitize(sdk_dependency_row("source_code", m, opts)))?;
+    }
+    for m in &report.actions_workflow.sdk_dependencies {
+        writer.write_record(layout.sanitize(sdk_dependency_row("actions_workflow", m, opts)))?;
+    }
+
     writer.flush()?;
+    Ok(())
+}
+
+/// Schema descriptor written next to a CSV report (`report.csv` -> `report.schema.json`)
+fn csv_schema_path(csv_path: &Path) -> PathBuf {
+    csv_path.with_extension("schema.json")
+}
+
+/// Generate a unified CSV report file and its `.schema.json` descriptor next to it
+pub fn generate_csv_reports(report: &ScanReport, output_path: &Path, opts: &CsvOptions) -> Result<()> {
+    // Ensure output directory exists
+    if let Some(output_dir) = output_path.parent() {
+        std::fs::create_dir_all(output_dir)
+            .with_context(|| format!("Failed to create output directory: {}", output_dir.display()))?;
+    }
+    
+    info!("Generating unified CSV report: {}", output_path.display());
+    write_unified_report(report, output_path, Delimited::Csv, opts)?;
     info!("CSV report written to {}", output_path.display());
+
+    generate_csv_schema(output_path, opts)
+}
+
+/// Generate the unified report as tab-separated values (same columns as report.csv)
+pub fn generate_tsv_report(report: &ScanReport, output_path: &Path, opts: &CsvOptions) -> Result<()> {
+    info!("Generating unified TSV report: {}", output_path.display());
+    write_unified_report(report, output_path, Delimited::Tsv, opts)?;
+    info!("TSV report written to {}", output_path.display());
     Ok(())
 }
 
+/// Write the schema descriptor for `csv_path`: CSV columns, types, and schema version
+fn generate_csv_schema(csv_path: &Path, opts: &CsvOptions) -> Result<()> {
+    let output_path = &csv_schema_path(csv_path);
+    let csv_file = csv_path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+    let columns: Vec<serde_json::Value> = CSV_COLUMNS
+        .iter()
+        .map(|(name, ty, description)| {
+            serde_json::json!({
+                "name": name,
+                "type": ty,
+                "description": description,
+            })
+        })
+        .collect();
+
+    let schema = serde_json::json!({
+        "schema_version": REPORT_SCHEMA_VERSION,
+        "file": csv_file,
+        "columns": columns,
+        "flatten_newlines": opts.flatten_newlines,
+        "safe_formulas": opts.safe_formulas,
+    });
+
+    let json = serde_json::to_string_pretty(&schema)
+        .context("Failed to serialize CSV schema to JSON")?;
+
+    let mut file = File::create(output_path)
+        .with_context(|| format!("Failed to create file: {}", output_path.display()))?;
+
+    file.write_all(json.as_bytes())
+        .with_context(|| format!("Failed to write to file: {}", output_path.display()))?;
+
+    info!("CSV schema written to {}", output_path.display());
+    Ok(())
+}
+
+// ============================================================================
+// Own Report Recognition
+// ============================================================================
+
+/// How many bytes of a file are inspected to recognise one of our reports
+const OWN_REPORT_PEEK_BYTES: usize = 4096;
+
+/// Leading columns a header must repeat to count as ours (CSV_COLUMNS is append-only, so
+/// reports written by older versions carry a shorter prefix of the current header)
+const OWN_REPORT_MIN_COLUMNS: usize = 5;
+
+/// Start of report.json: `scan_time` is the first key (after `schema_version` since 1.4)
+static OWN_JSON_HEAD: Lazy<BytesRegex> = Lazy::new(|| {
+    BytesRegex::new(r#"^\s*\{\s*(?:"schema_version"\s*:\s*"[^"]*"\s*,\s*)?"scan_time"\s*:"#)
+        .expect("Invalid OWN_JSON_HEAD regex")
+});
+
+static OWN_JSON_AGGREGATED: Lazy<BytesRegex> = Lazy::new(|| {
+    BytesRegex::new(r#""aggregated"\s*:\s*\{"#)
+        .expect("Invalid OWN_JSON_AGGREGATED regex")
+});
+
+/// Whether `content` is a report.json, report.csv or report.tsv written by this scanner
+///
+/// Only the first bytes (the JSON's leading key or the CSV header row) are inspected, so
+/// unrelated files that merely mention `scan_time` or a model name are never matched.
+pub fn is_own_report(content: &[u8]) -> bool {
+    let head = &content[..content.len().min(OWN_REPORT_PEEK_BYTES)];
+    if OWN_JSON_HEAD.is_match(head) {
+        return OWN_JSON_AGGREGATED.is_match(content);
+    }
+
+    let first_line = head.split(|&b| b == b'\n').next().unwrap_or_default();
+    let first_line = match std::str::from_utf8(first_line) {
+        Ok(line) => line.trim_end_matches('\r'),
+        Err(_) => return false,
+    };
+    [',', '\t'].iter().any(|&delimiter| {
+        let names: Vec<&str> = first_line.split(delimiter).collect();
+        names.len() >= OWN_REPORT_MIN_COLUMNS
+            && names.len() <= CSV_COLUMNS.len()
+            && names.iter().zip(CSV_COLUMNS).all(|(name, (column, _, _))| name == column)
+    })
+}
+
+// ============================================================================
+// Record Formatting (stdout)
+// ============================================================================
+
+/// Output format for records printed to stdout
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Pretty-printed JSON (all fields, including raw_response)
+    #[default]
+    Json,
+    /// Aligned columns with the key fields
+    Table,
+    /// Header plus one row per record with the key fields
+    Csv,
+}
+
+/// A record with a fixed set of key columns for table/CSV output
+pub trait TabularRecord: serde::Serialize {
+    /// Column names, in output order
+    const COLUMNS: &'static [&'static str];
+
+    /// Cell values, aligned with COLUMNS (empty string for missing values)
+    fn row(&self) -> Vec<String>;
+}
+
+/// Maximum width of a table cell before it is truncated with "..."
+const MAX_TABLE_CELL: usize = 60;
+
+impl TabularRecord for LocalNimQueryResult {
+    const COLUMNS: &'static [&'static str] = &["image", "latest_tag", "display_name", "publisher"];
+
+    fn row(&self) -> Vec<String> {
+        vec![
+            self.repository_url.clone(),
+            self.latest_tag.clone().unwrap_or_default(),
+            self.display_name.clone().unwrap_or_default(),
+            self.publisher.clone().unwrap_or_default(),
+        ]
+    }
+}
+
+impl TabularRecord for HostedNimQueryResult {
+    const COLUMNS: &'static [&'static str] = &["model", "function_id", "status", "container_image"];
+
+    fn row(&self) -> Vec<String> {
+        vec![
+            self.query_model.clone(),
+            self.function_id.clone().unwrap_or_default(),
+            self.status.clone().unwrap_or_default(),
+            self.container_image.clone().unwrap_or_default(),
+        ]
+    }
+}
+
+impl TabularRecord for HotspotRow {
+    const COLUMNS: &'static [&'static str] = &["repository", "directory", "local_count", "hosted_count"];
+
+    fn row(&self) -> Vec<String> {
+        vec![
+            self.repository.clone(),
+            self.directory.clone(),
+            self.local_count.to_string(),
+            self.hosted_count.to_string(),
+        ]
+    }
+}
+
+impl TabularRecord for FunctionByImageResult {
+    const COLUMNS: &'static [&'static str] = &["function_id", "name", "status", "container_image"];
+
+    fn row(&self) -> Vec<String> {
+        vec![
+            self.function_id.clone(),
+            self.name.clone(),
+            self.status.clone().unwrap_or_default(),
+            self.container_image.clone(),
+        ]
+    }
+}
+
+/// Render records in the given format
+///
+/// JSON prints a single record as an object and several as an array.
+pub fn format_records<T: TabularRecord>(records: &[T], format: OutputFormat) -> Result<String> {
+    match format {
+        OutputFormat::Json => {
+            let json = match records {
+                [single] => serde_json::to_string_pretty(single),
+                _ => serde_json::to_string_pretty(records),
+            };
+            json.context("Failed to serialize result to JSON")
+        }
+        OutputFormat::Table => Ok(format_table(T::COLUMNS, records.iter().map(|r| r.row()))),
+        OutputFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(Vec::new());
+            writer.write_record(T::COLUMNS)?;
+            for record in records {
+                writer.write_record(record.row())?;
+            }
+            let bytes = writer.into_inner().context("Failed to write CSV")?;
+            let text = String::from_utf8(bytes).context("CSV output is not valid UTF-8")?;
+            Ok(text.trim_end_matches('\n').to_string())
+        }
+    }
+}
+
+fn truncate_cell(value: &str) -> String {
+    if value.chars().count() <= MAX_TABLE_CELL {
+        value.to_string()
+    } else {
+        let kept: String = value.chars().take(MAX_TABLE_CELL - 3).collect();
+        format!("{}...", kept)
+    }
+}
+
+/// Left-aligned columns separated by two spaces, with a header and a dashed rule
+fn format_table(columns: &[&str], rows: impl Iterator<Item = Vec<String>>) -> String {
+    let rows: Vec<Vec<String>> = rows
+        .map(|row| row.iter().map(|cell| truncate_cell(cell)).collect())
+        .collect();
+
+    let mut widths: Vec<usize> = columns.iter().map(|c| c.len()).collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let render = |cells: Vec<String>| -> String {
+        let line = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ");
+        line.trim_end().to_string()
+    };
+
+    let mut lines = vec![
+        render(columns.iter().map(|c| c.to_string()).collect()),
+        render(widths.iter().map(|w| "-".repeat(*w)).collect()),
+    ];
+    lines.extend(rows.into_iter().map(render));
+    lines.join("\n")
+}
 
 // ============================================================================
 // Summary Printing
 // ============================================================================
 
-/// Print a summary of the scan results to stdout
-pub fn print_summary(report: &ScanReport) {
+/// Label for a Local NIM in the summary: display name (when enriched) plus image:tag
+fn local_nim_label(report: &ScanReport, m: &LocalNimMatch) -> String {
+    match report.aggregated.local_display_name(&m.image_url) {
+        Some(name) => format!("{} ({}:{})", name, m.image_url, m.tag),
+        None => format!("{}:{}", m.image_url, m.tag),
+    }
+}
+
+/// Identifiers listed per delta bucket before truncating
+const MAX_DELTA_ITEMS: usize = 10;
+
+/// Fragmented images listed in the console summary
+const MAX_SPREAD_ITEMS: usize = 5;
+
+fn counted(n: usize, singular: &str, plural: &str) -> String {
+    format!("{} {}", n, if n == 1 { singular } else { plural })
+}
+
+/// One-line description of a delta, e.g.
+/// "+2 new local NIM images, -1 removed, 3 tag changes, +1 new repo with hosted usage"
+pub fn delta_headline(delta: &ReportDelta) -> String {
+    [
+        format!("+{}", counted(delta.new_local_images.len(), "new local NIM image", "new local NIM images")),
+        format!("-{} removed", delta.removed_local_images.len()),
+        counted(delta.local_tag_changes.len(), "tag change", "tag changes"),
+        format!("+{}", counted(delta.new_hosted_models.len(), "new hosted model", "new hosted models")),
+        format!("-{} removed", delta.removed_hosted_models.len()),
+        format!("+{}", counted(delta.new_hosted_repos.len(), "new repo with hosted usage", "new repos with hosted usage")),
+    ]
+    .join(", ")
+}
+
+fn print_delta_bucket(title: &str, items: &[String]) {
+    if items.is_empty() {
+        return;
+    }
+    println!("{}:", title);
+    for item in items.iter().take(MAX_DELTA_ITEMS) {
+        println!("  {}", item);
+    }
+    if items.len() > MAX_DELTA_ITEMS {
+        println!("  ... and {} more", items.len() - MAX_DELTA_ITEMS);
+    }
+}
+
+/// Print a summary of the scan results to stdout, including changes since
+/// the baseline when one was loaded and, with `show_detector_stats`, the
+/// enrichment outcomes per detection rule
+/// Authors shown in the console summary (--blame)
+const MAX_BLAME_AUTHORS: usize = 10;
+
+/// Authors of blamed findings by finding count (most first), with the date of their oldest line
+fn blame_authors(report: &ScanReport) -> Vec<(&str, usize, &str)> {
+    let mut authors: HashMap<&str, (usize, &str)> = HashMap::new();
+    let blames = [&report.source_code, &report.actions_workflow]
+        .into_iter()
+        .flat_map(|f| f.local_nim.iter().map(|m| &m.blame).chain(f.hosted_nim.iter().map(|m| &m.blame)))
+        .flatten();
+    for blame in blames {
+        if let (Some(email), Some(date)) = (blame.author_email.as_deref(), blame.author_date.as_deref()) {
+            let entry = authors.entry(email).or_insert((0, date));
+            entry.0 += 1;
+            let instant = |date: &str| chrono::DateTime::parse_from_rfc3339(date).ok();
+            if instant(date) < instant(entry.1) {
+                entry.1 = date;
+            }
+        }
+    }
+    let mut authors: Vec<(&str, usize, &str)> = authors.into_iter().map(|(email, (n, date))| (email, n, date)).collect();
+    authors.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    authors.truncate(MAX_BLAME_AUTHORS);
+    authors
+}
+
+pub fn print_summary(report: &ScanReport, delta: Option<&ReportDelta>, show_detector_stats: bool) {
     println!("\n========================================");
     println!("         NIM Usage Scanner Report       ");
     println!("========================================\n");
     
-    println!("Scan Time: {}", report.scan_time);
-    println!("Total Repositories: {}", report.total_repos);
+    println!("Scan Time: {}", report.scan_time);
+    println!("Total Repositories: {}", report.total_repos);
+    if report.scope != ScanScope::Full {
+        println!("Scope: CI workflows and manifests only ({})", report.scope_paths.join(", "));
+    }
+    println!();
+    
+    println!("--- Summary ---");
+    println!("Total Local NIM references:  {}", report.summary.total_local_nim);
+    println!("Total Hosted NIM references: {}", report.summary.total_hosted_nim);
+    println!("Repositories with NIM:       {}", report.summary.repos_with_nim);
+    println!("Distinct NIMs (by identity): {}", report.summary.distinct_nims);
+    if report.summary.fixtures_excluded > 0 {
+        println!("Fixture findings (not counted, see --include-fixtures): {}", report.summary.fixtures_excluded);
+    }
+    println!();
+    
+    if let Some(ref sampling) = report.sampling {
+        println!("--- Estimates (sample of {} of {} repositories, seed {}) ---",
+                 sampling.repositories.len(), sampling.population, sampling.seed);
+        println!("Counts in this report cover the sampled repositories only. Extrapolated to all {}:", sampling.population);
+        println!("Local NIM references (estimated):  {:.1}", sampling.estimated.total_local_nim);
+        println!("Hosted NIM references (estimated): {:.1}", sampling.estimated.total_hosted_nim);
+        println!("Repositories with NIM (estimated): {:.1}", sampling.estimated.repos_with_nim);
+        println!();
+    }
+    
+    println!("--- By Severity ---");
+    println!("Error:   {}", report.summary.severity.error);
+    println!("Warning: {}", report.summary.severity.warning);
+    println!("Info:    {}", report.summary.severity.info);
+    println!();
+    
+    println!("--- Hosted NIM By Environment ---");
+    println!("Production: {}", report.summary.environment.production);
+    println!("Staging:    {}", report.summary.environment.staging);
+    println!();
+    
+    println!("--- Hosted NIM By Invoke Style ---");
+    println!("OpenAI-compatible: {}", report.summary.invoke_style.openai_compatible);
+    println!("Model path:        {}", report.summary.invoke_style.model_path);
+    println!("NVCF pexec:        {}", report.summary.invoke_style.nvcf_pexec);
+    println!("Unknown:           {}", report.summary.invoke_style.unknown);
+    println!();
+    
+    if !report.framework_breakdown.is_empty() {
+        println!("--- Hosted NIM By Framework ---");
+        for usage in &report.framework_breakdown {
+            println!("  {}: {} repo(s), {} finding(s)", usage.framework.as_str(), usage.repo_count, usage.findings);
+            if !usage.models.is_empty() {
+                println!("    {}", usage.models.join(", "));
+            }
+        }
+        println!();
+    }
+    
+    let fragmented: Vec<&VersionSpread> = report.version_spread.iter().filter(|s| s.fragmented).collect();
+    if !fragmented.is_empty() {
+        println!("--- NIM Version Spread (more than {} tags in use) ---", crate::analysis::FRAGMENTED_TAG_COUNT);
+        for image in fragmented.iter().take(MAX_SPREAD_ITEMS) {
+            let tags: Vec<String> = image.tags.iter().map(|t| format!("{} ({})", t.tag, t.repo_count)).collect();
+            println!("  {}: {} tags across {} repo(s)", image.image_url, image.distinct_tags, image.repo_count);
+            println!("    {}", tags.join(", "));
+        }
+        if fragmented.len() > MAX_SPREAD_ITEMS {
+            println!("  ... and {} more", fragmented.len() - MAX_SPREAD_ITEMS);
+        }
+        println!();
+    }
+    
+    println!("--- Local NIM By Intent ---");
+    println!("Base image: {}", report.summary.local_intent.base_image);
+    println!("Deploy:     {}", report.summary.local_intent.deploy);
+    println!("Pull:       {}", report.summary.local_intent.pull);
+    println!("Run:        {}", report.summary.local_intent.run);
+    println!("Reference:  {}", report.summary.local_intent.reference);
     println!();
     
-    println!("--- Summary ---");
-    println!("Total Local NIM references:  {}", report.summary.total_local_nim);
-    println!("Total Hosted NIM references: {}", report.summary.total_hosted_nim);
-    println!("Repositories with NIM:       {}", report.summary.repos_with_nim);
+    let kinds = &report.summary.file_kind;
+    println!("--- By File Kind ---");
+    println!("Dockerfile: {}", kinds.dockerfile);
+    println!("Compose:    {}", kinds.compose);
+    println!("Kubernetes: {}", kinds.kubernetes);
+    println!("Helm:       {}", kinds.helm);
+    println!("Notebook:   {}", kinds.notebook);
+    println!("Docs:       {}", kinds.docs);
+    println!("CI:         {}", kinds.ci);
+    println!("Source:     {}", kinds.source);
     println!();
     
+    let resolution = &report.summary.resolution;
+    if *resolution != ResolutionCounts::default() {
+        println!("--- Hosted NIM Function Resolution ---");
+        println!("Resolved:         {}", resolution.resolved);
+        println!("Never resolved:   {}", resolution.never_resolved);
+        println!("Newly unresolved: {}", resolution.newly_unresolved);
+        println!();
+    }
+    
+    if !report.summary.modality.is_empty() {
+        println!("--- Hosted NIM Modalities (--enrich-model-cards) ---");
+        for (modality, count) in &report.summary.modality {
+            println!("  {}: {}", modality, count);
+        }
+        println!();
+    }
+    
+    if !report.summary.policy_violations.is_empty() {
+        println!("--- Model Policy Violations By Repository ---");
+        for (repo, count) in &report.summary.policy_violations {
+            println!("  {}: {}", repo, count);
+        }
+        println!();
+    }
+    
+    let authors = blame_authors(report);
+    if !authors.is_empty() {
+        println!("--- Top Authors By Finding Count (--blame) ---");
+        for (email, count, oldest) in authors {
+            println!("  {}: {} (oldest line {})", email, count, oldest.get(..10).unwrap_or(oldest));
+        }
+        println!();
+    }
+    
     println!("--- By Source Type ---");
     println!("Source Code:");
+    if report.scope != ScanScope::Full {
+        println!("  Not scanned (--scope workflows): counts cover manifests under the scope paths only");
+    }
     println!("  Local NIM:  {}", report.summary.source_code.local_nim);
     println!("  Hosted NIM: {}", report.summary.source_code.hosted_nim);
     println!();
     println!("Actions Workflow:");
     println!("  Local NIM:  {}", report.summary.actions_workflow.local_nim);
     println!("  Hosted NIM: {}", report.summary.actions_workflow.hosted_nim);
+    println!("  NIMs exercised in CI on a schedule: {}", report.summary.scheduled_ci_nims);
     println!();
     
     // Print some sample findings
     if !report.source_code.local_nim.is_empty() || !report.actions_workflow.local_nim.is_empty() {
         println!("--- Sample Local NIM Findings ---");
         for m in report.source_code.local_nim.iter().take(3) {
-            println!("  [source] {}:{} - {}:{}", 
-                     m.repository, m.file_path, m.image_url, m.tag);
+            println!("  [source] {}:{} - {}", 
+                     m.repository, m.file_path, local_nim_label(report, m));
         }
         for m in report.actions_workflow.local_nim.iter().take(3) {
-            println!("  [workflow] {}:{} - {}:{}",
-                     m.repository, m.file_path, m.image_url, m.tag);
+            println!("  [workflow] {}:{} - {}",
+                     m.repository, m.file_path, local_nim_label(report, m));
         }
         println!();
     }
@@ -292,6 +1277,227 @@ pub fn print_summary(report: &ScanReport) {
         println!();
     }
     
+    let skewed: Vec<_> = report.version_skew.iter().filter(|s| s.is_skewed()).collect();
+    if !skewed.is_empty() {
+        println!("--- Version Skew (local pin vs hosted function) ---");
+        for s in skewed {
+            let direction = match s.hosted_uses_newer {
+                Some(true) => "hosted is newer",
+                Some(false) => "hosted is older",
+                None => "not comparable",
+            };
+            println!("  {} - {}: local {} / hosted {} ({})",
+                     s.repository, s.image_name, s.local_tag,
+                     s.hosted_tag.as_deref().unwrap_or("?"), direction);
+        }
+        println!();
+    }
+    
+    if !report.truncated.is_empty() {
+        println!("--- Scan Limits Reached (partially scanned) ---");
+        for t in &report.truncated {
+            let mut limits = Vec::new();
+            if let Some(depth) = t.max_depth {
+                limits.push(format!("{} directories below depth {} skipped", t.dirs_beyond_max_depth, depth));
+            }
+            if let Some(files) = t.max_files {
+                limits.push(format!("stopped after {} files", files));
+            }
+            println!("  {}: {}", t.repository, limits.join(", "));
+        }
+        println!();
+    }
+    
+    if !report.skipped_oversize.is_empty() {
+        println!("--- Skipped (over --max-repo-size-mb, not scanned) ---");
+        for skip in &report.skipped_oversize {
+            println!("  {}: {} MB (limit {} MB)", skip.repository, skip.size_mb, skip.limit_mb);
+        }
+        println!();
+    }
+    
+    if report.summary.enrichment_skipped > 0 {
+        println!("--- Not Enriched ---");
+        let mut skipped: BTreeMap<&str, (usize, &str)> = BTreeMap::new();
+        for nim in [&report.source_code, &report.actions_workflow] {
+            let reasons = nim.local_nim.iter().map(|m| m.enrichment_skipped)
+                .chain(nim.hosted_nim.iter().map(|m| m.enrichment_skipped));
+            for reason in reasons.flatten() {
+                let hint = match reason {
+                    EnrichmentSkip::BudgetExhausted => "see --enrichment-budget-secs, --enrichment-max-requests",
+                    EnrichmentSkip::RepoOptedOut => "enrich: false in the repository config",
+                    EnrichmentSkip::RepoKeyMissing => "the repository's ngc_api_key_env variable is not set",
+                };
+                skipped.entry(reason.as_str()).or_insert((0, hint)).0 += 1;
+            }
+        }
+        for (reason, (count, hint)) in skipped {
+            println!("{} finding(s) marked enrichment_skipped: {} ({})", count, reason, hint);
+        }
+        println!();
+    }
+    
+    if !report.summary.sdk_dependencies.is_empty() {
+        println!("--- NVIDIA SDK Dependencies ---");
+        for (package, repos) in &report.summary.sdk_dependencies {
+            println!("  {}: {} repo(s)", package, repos);
+        }
+        println!();
+    }
+    
+    if report.summary.browser_exposed_endpoints > 0 {
+        println!("--- Browser-Exposed NIM Endpoints (NEXT_PUBLIC_*, VITE_*) ---");
+        for m in report.source_code.hosted_nim.iter().filter(|m| m.browser_exposed) {
+            println!("  {}: {}:{} {}", m.repository, m.file_path, m.line_number, m.endpoint_url.as_deref().unwrap_or(""));
+        }
+        println!();
+    }
+    
+    if report.summary.endpoint_mismatches > 0 {
+        println!("--- Hosted NIM Endpoints Differing From NVCF inferenceUrl ---");
+        let hosted = report.source_code.hosted_nim.iter().chain(&report.actions_workflow.hosted_nim);
+        for m in hosted.filter(|m| m.endpoint_mismatch) {
+            println!("  {}: {}:{} calls {}, canonical is {}", m.repository, m.file_path, m.line_number,
+                     m.endpoint_url.as_deref().unwrap_or(""), m.canonical_endpoint.as_deref().unwrap_or(""));
+        }
+        println!();
+    }
+    
+    let retired = report.summary.endpoint_status.deprecated + report.summary.endpoint_status.removed;
+    if retired > 0 {
+        println!("--- Deprecated Hosted NIM Endpoints ---");
+        let hosted = report.source_code.hosted_nim.iter().chain(&report.actions_workflow.hosted_nim);
+        for m in hosted.filter(|m| matches!(m.endpoint_status, Some(EndpointStatus::Deprecated | EndpointStatus::Removed))) {
+            let status = m.endpoint_status.map_or("", |status| status.as_str());
+            match m.suggested_endpoint {
+                Some(ref suggested) => println!("  {}: {}:{} calls {} ({}), use {}", m.repository, m.file_path,
+                                                m.line_number, m.endpoint_url.as_deref().unwrap_or(""), status, suggested),
+                None => println!("  {}: {}:{} calls {} ({})", m.repository, m.file_path, m.line_number,
+                                 m.endpoint_url.as_deref().unwrap_or(""), status),
+            }
+        }
+        println!();
+    }
+    
+    if !report.summary.credential_sources.is_empty() {
+        println!("--- Hosted NIM Credentials (call sites) ---");
+        for (credential, count) in &report.summary.credential_sources {
+            println!("  {}: {}", credential, count);
+        }
+        println!();
+    }
+    
+    let mut suggestions: BTreeMap<&str, &[String]> = BTreeMap::new();
+    for m in report.source_code.hosted_nim.iter().chain(&report.actions_workflow.hosted_nim) {
+        if let (Some(model), false) = (m.model_name.as_deref(), m.did_you_mean.is_empty()) {
+            suggestions.insert(model, &m.did_you_mean);
+        }
+    }
+    if !suggestions.is_empty() {
+        println!("--- Unknown Hosted Models: No NVCF Function (did you mean) ---");
+        for (model, names) in suggestions {
+            println!("  {} -> {}", model, names.join(", "));
+        }
+        println!();
+    }
+    
+    if !report.lfs_pointers.is_empty() {
+        println!("--- Content Not Scanned: Stored in LFS (see --fetch-lfs) ---");
+        for pointer in &report.lfs_pointers {
+            println!("  {}: {}", pointer.repository, pointer.file_path);
+        }
+        println!();
+    }
+    
+    if !report.listed_file_errors.is_empty() {
+        println!("--- Listed Files Not Scanned (only_files / --files-from) ---");
+        for error in &report.listed_file_errors {
+            println!("  {}: {} ({})", error.repository, error.file_path, error.reason);
+        }
+        println!();
+    }
+    
+    let stale: Vec<_> = report.checkouts.iter().filter_map(|c| c.stale.map(|s| (c, s))).collect();
+    if !stale.is_empty() {
+        println!("--- Checkouts Not Updated (scanned at the commit an earlier run left) ---");
+        for (checkout, reason) in stale {
+            println!("  {}: {} ({})", checkout.repository, reason.as_str(),
+                     checkout.commit_sha.as_deref().unwrap_or("unknown commit"));
+        }
+        println!();
+    }
+    
+    let resets: Vec<_> = report.checkouts.iter().filter_map(|c| c.reset.as_ref().map(|r| (c, r))).collect();
+    if !resets.is_empty() {
+        println!("--- Checkouts Reset To A Rewritten Branch ---");
+        for (checkout, reset) in resets {
+            println!("  {}: {} -> {}", checkout.repository, reset.old_head, reset.new_head);
+        }
+        println!();
+    }
+    
+    if !report.panicked.is_empty() {
+        println!("--- Repositories Left Out After A Panic ---");
+        for panic in &report.panicked {
+            println!("  {}: {} panicked: {}", panic.repository, panic.stage.as_str(), panic.message);
+        }
+        println!();
+    }
+    
+    if !report.metadata_hints.is_empty() {
+        println!("--- GitHub Metadata Hints (low confidence, not counted) ---");
+        for hint in &report.metadata_hints {
+            let subject = hint.model_name.as_deref().or(hint.image_url.as_deref()).unwrap_or("");
+            println!("  {}: {} in {}: {}", hint.repository, subject, hint.field.as_str(), hint.text);
+        }
+        println!();
+    }
+    
+    if let Some(delta) = delta {
+        println!("--- Changes Since Baseline ({}) ---", delta.baseline_scan_time);
+        println!("{}", delta_headline(delta));
+        println!("Findings: +{} new, -{} gone (by fingerprint)", delta.new_findings, delta.removed_findings);
+        print_delta_bucket("New Local NIM images", &delta.new_local_images);
+        print_delta_bucket("Removed Local NIM images", &delta.removed_local_images);
+        print_delta_bucket("Tag changes", &delta.local_tag_changes);
+        print_delta_bucket("New Hosted NIM models", &delta.new_hosted_models);
+        print_delta_bucket("Removed Hosted NIM models", &delta.removed_hosted_models);
+        print_delta_bucket("New repositories with Hosted NIM usage", &delta.new_hosted_repos);
+        println!();
+    }
+    
+    if !report.possible_retirements.is_empty() {
+        println!("--- Possible Retirements (function gone since baseline) ---");
+        for r in &report.possible_retirements {
+            println!("  {} (was {})", r.model, r.baseline_function_id);
+            for loc in &r.locations {
+                println!("    {}:{}:{}", loc.repository, loc.file_path, loc.line_number);
+            }
+        }
+        println!();
+    }
+    
+    if !report.merged_from.is_empty() {
+        println!("--- Merged Reports ---");
+        for input in &report.merged_from {
+            println!("  {} ({} repositories, scanned {})", input.report, input.total_repos, input.scan_time);
+        }
+        if !report.repository_collisions.is_empty() {
+            println!("Repositories in more than one report: {}", report.repository_collisions.join(", "));
+        }
+        println!();
+    }
+    
+    if show_detector_stats && !report.detector_stats.is_empty() {
+        println!("--- Detector Effectiveness ---");
+        println!("{:<24} {:>6} {:>9} {:>8} {:>7}", "Rule", "Total", "Enriched", "Skipped", "Failed");
+        for (rule, stats) in &report.detector_stats {
+            let c = &stats.counts;
+            println!("{:<24} {:>6} {:>9} {:>8} {:>7}", rule, c.total, c.enriched, c.not_attempted, c.failed);
+        }
+        println!();
+    }
+    
     println!("========================================\n");
 }
 
@@ -299,7 +1505,7 @@ pub fn print_summary(report: &ScanReport) {
 mod tests {
     use super::*;
     use tempfile::TempDir;
-    use crate::models::NimFindings;
+    use crate::models::{Environment, FileKind, Framework, InvokeStyle, LocalIntent, NimFindings, UsageKind};
 
     fn create_test_report() -> ScanReport {
         let source_code = NimFindings {
@@ -309,9 +1515,27 @@ mod tests {
                     image_url: "nvcr.io/nim/nvidia/test".to_string(),
                     tag: "1.0.0".to_string(),
                     resolved_tag: None,
+                    tag_exists: None,
                     file_path: "Dockerfile".to_string(),
                     line_number: 1,
                     match_context: "FROM nvcr.io/nim/nvidia/test:1.0.0".to_string(),
+                    policy: None,
+                    enrichment_error: None,
+                    enriched: false,
+                    detection_rule: String::new(),
+                    workflow_triggers: Vec::new(),
+                    nim_identity: String::new(),
+                    enrichment_skipped: None,
+                    context_truncated: None,
+                    compose: None,
+                    intent: LocalIntent::Reference,
+                    usage_kind: UsageKind::Other,
+                    blame: None,
+                    notebook_cell: None,
+                    file_kind: FileKind::Source,
+                    resolved_from: None,
+                    fixture: false,
+                    fingerprint: String::new(),
                 },
             ],
             hosted_nim: vec![
@@ -325,8 +1549,36 @@ mod tests {
                     function_id: Some("test-id".to_string()),
                     status: Some("ACTIVE".to_string()),
                     container_image: None,
+                    environment: Environment::Production,
+                    endpoint_host: None,
+                    invoke_style: InvokeStyle::Unknown,
+                    framework: Framework::Unknown,
+                    call_params: None,
+                    policy: None,
+                    model_policy: None,
+                    enrichment_error: None,
+                    enriched: false,
+                    detection_rule: String::new(),
+                    workflow_triggers: Vec::new(),
+                    nim_identity: None,
+                    enrichment_skipped: None,
+                    browser_exposed: false,
+                    did_you_mean: Vec::new(),
+                    credential_source: None,
+                    context_truncated: None,
+                    canonical_endpoint: None,
+                    endpoint_mismatch: false,
+                    endpoint_status: None,
+                    suggested_endpoint: None,
+                    blame: None,
+                    notebook_cell: None,
+                    file_kind: FileKind::Source,
+                    resolved_from: None,
+                    fixture: false,
+                    fingerprint: String::new(),
                 },
             ],
+            sdk_dependencies: vec![],
         };
         let actions_workflow = NimFindings::default();
         
@@ -353,9 +1605,22 @@ mod tests {
     #[test]
     fn test_generate_csv_reports() {
         let temp_dir = TempDir::new().unwrap();
-        let report = create_test_report();
+        let mut report = create_test_report();
+        report.source_code.sdk_dependencies.push(SdkDependencyMatch {
+            repository: "test/repo".to_string(),
+            package_name: "langchain-nvidia-ai-endpoints".to_string(),
+            ecosystem: crate::models::PackageEcosystem::Pypi,
+            version_constraint: Some(">=0.3,<0.4".to_string()),
+            extras: vec![],
+            section: None,
+            framework: crate::models::Framework::Langchain,
+            file_path: "requirements.txt".to_string(),
+            line_number: 2,
+            match_context: "langchain-nvidia-ai-endpoints>=0.3,<0.4".to_string(),
+            fixture: false,
+        });
         
-        let result = generate_csv_reports(&report, temp_dir.path());
+        let result = generate_csv_reports(&report, &temp_dir.path().join("report.csv"), &CsvOptions::default());
         assert!(result.is_ok());
         
         // Verify unified CSV file exists
@@ -369,5 +1634,566 @@ mod tests {
         assert!(csv_content.contains("nvcr.io/nim/nvidia/test"));
         assert!(csv_content.contains("source_code,hosted_nim"));
         assert!(csv_content.contains("nvidia/test-model"));
+        let row = csv_content.lines().find(|l| l.starts_with("source_code,sdk_dependency,")).unwrap();
+        assert!(row.starts_with("source_code,sdk_dependency,test/repo,requirements.txt,2,"), "{}", row);
+        assert!(row.ends_with(",false,,,langchain-nvidia-ai-endpoints,pypi,\">=0.3,<0.4\""), "{}", row);
+
+        // Schema descriptor is written next to the CSV
+        let schema_path = temp_dir.path().join("report.schema.json");
+        assert!(schema_path.exists());
+    }
+
+    #[test]
+    fn test_generate_triage_framework_table() {
+        let temp_dir = TempDir::new().unwrap();
+        let (markdown, json) = (temp_dir.path().join("triage.md"), temp_dir.path().join("triage.json"));
+        let mut report = create_test_report();
+        generate_triage(&report, &TriageWeights::default(), &markdown, &json).unwrap();
+        assert!(!std::fs::read_to_string(&markdown).unwrap().contains("Framework adoption"));
+
+        report.source_code.hosted_nim[0].framework = Framework::OpenaiSdk;
+        let report = ScanReport::new(report.total_repos, report.source_code, report.actions_workflow);
+        generate_triage(&report, &TriageWeights::default(), &markdown, &json).unwrap();
+        let content = std::fs::read_to_string(&markdown).unwrap();
+        assert!(content.contains("\n\n## Framework adoption\n\n| Framework | Repositories | Findings | Models |\n"), "{}", content);
+        assert!(content.contains("| openai_sdk | 1 | 1 | `nvidia/test-model` |\n\n## NIM version spread\n\n"), "{}", content);
+        assert!(content.ends_with("\
+| Image | Tags in use | Repositories | Tags (repositories) |
+|---|---|---|---|
+| `nvcr.io/nim/nvidia/test` | 1 | 1 | `1.0.0` (1) |
+"), "{}", content);
+
+        // More than three tags of an image in use: its count is highlighted
+        let mut source_code = report.source_code.clone();
+        for (repository, tag) in [("org/a", "1.1.0"), ("org/b", "1.2.0"), ("org/b", "latest")] {
+            let mut m = source_code.local_nim[0].clone();
+            (m.repository, m.tag) = (repository.to_string(), tag.to_string());
+            source_code.local_nim.push(m);
+        }
+        let spread_report = ScanReport::new(3, source_code, report.actions_workflow.clone());
+        generate_triage(&spread_report, &TriageWeights::default(), &markdown, &json).unwrap();
+        let content = std::fs::read_to_string(&markdown).unwrap();
+        assert!(content.contains("| `nvcr.io/nim/nvidia/test` | **4** | 3 | `1.0.0` (1), `1.1.0` (1), `1.2.0` (1), `latest` (1) |\n"), "{}", content);
+
+        // Model cards (--enrich-model-cards) add the publisher table after it
+        let mut report = report;
+        let card = crate::models::ModelCard {
+            publisher: Some("NVIDIA".to_string()),
+            modalities: vec!["text".to_string()],
+            max_context: Some(32768),
+        };
+        report.aggregated.apply_model_cards(&HashMap::from([("nvidia/test-model".to_string(), card)]));
+        generate_triage(&report, &TriageWeights::default(), &markdown, &json).unwrap();
+        let content = std::fs::read_to_string(&markdown).unwrap();
+        assert!(content.ends_with("\n\n## Hosted models\n\n| Model | Publisher | Modalities | Max context |\n|---|---|---|---|\n| `nvidia/test-model` | NVIDIA | text | 32768 |\n"), "{}", content);
+    }
+
+    /// report.json of a scan that found nothing (scan_time fixed)
+    const EMPTY_REPORT_JSON: &str = r#"{
+  "schema_version": "1.15",
+  "scan_time": "2025-01-21T10:30:00+00:00",
+  "total_repos": 2,
+  "source_code": {
+    "local_nim": [],
+    "hosted_nim": [],
+    "sdk_dependencies": []
+  },
+  "actions_workflow": {
+    "local_nim": [],
+    "hosted_nim": [],
+    "sdk_dependencies": []
+  },
+  "aggregated": {
+    "local_nim": [],
+    "hosted_nim": [],
+    "by_nim": []
+  },
+  "version_skew": [],
+  "version_spread": [],
+  "summary": {
+    "total_local_nim": 0,
+    "total_hosted_nim": 0,
+    "repos_with_nim": 0,
+    "source_code": {
+      "local_nim": 0,
+      "hosted_nim": 0
+    },
+    "actions_workflow": {
+      "local_nim": 0,
+      "hosted_nim": 0
+    },
+    "severity": {
+      "error": 0,
+      "warning": 0,
+      "info": 0
+    },
+    "environment": {
+      "production": 0,
+      "staging": 0
+    },
+    "invoke_style": {
+      "openai_compatible": 0,
+      "model_path": 0,
+      "nvcf_pexec": 0,
+      "unknown": 0
+    },
+    "local_intent": {
+      "base_image": 0,
+      "deploy": 0,
+      "pull": 0,
+      "run": 0,
+      "reference": 0
+    },
+    "file_kind": {
+      "dockerfile": 0,
+      "compose": 0,
+      "kubernetes": 0,
+      "helm": 0,
+      "notebook": 0,
+      "docs": 0,
+      "ci": 0,
+      "source": 0
+    },
+    "policy_violations": {},
+    "scheduled_ci_nims": 0,
+    "resolution": {
+      "resolved": 0,
+      "never_resolved": 0,
+      "newly_unresolved": 0
+    },
+    "modality": {},
+    "distinct_nims": 0,
+    "enrichment_skipped": 0,
+    "browser_exposed_endpoints": 0,
+    "credential_sources": {},
+    "endpoint_mismatches": 0,
+    "endpoint_status": {
+      "current": 0,
+      "deprecated": 0,
+      "removed": 0
+    },
+    "fixtures_excluded": 0,
+    "sdk_dependencies": {}
+  },
+  "scope": "full",
+  "detector_stats": {},
+  "framework_breakdown": [],
+  "hotspots": []
+}"#;
+
+    #[test]
+    fn test_empty_report_golden() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut report = ScanReport::new(2, NimFindings::new(), NimFindings::new());
+        report.scan_time = "2025-01-21T10:30:00+00:00".to_string();
+        let path = |name: &str| temp_dir.path().join(name);
+        generate_json_report(&report, &path("report.json")).unwrap();
+        generate_csv_reports(&report, &path("report.csv"), &CsvOptions::default()).unwrap();
+        generate_aggregate_report(&report, &path("report_aggregate.json")).unwrap();
+        generate_aggregate_csv(&report, &path("report_aggregate.csv"), &CsvOptions::default()).unwrap();
+
+        assert_eq!(std::fs::read_to_string(path("report.json")).unwrap(), EMPTY_REPORT_JSON);
+        assert_eq!(std::fs::read_to_string(path("report.csv")).unwrap(), format!("{}\n", EXPECTED_HEADER));
+        assert_eq!(std::fs::read_to_string(path("report_aggregate.json")).unwrap(), "[]");
+        assert_eq!(
+            std::fs::read_to_string(path("report_aggregate.csv")).unwrap(),
+            "repository,repository_url,hosted_nims,local_nims\n"
+        );
+        // The same shape after a round trip (as a --baseline or merge-reports input)
+        let reloaded = load_json_report(&path("report.json")).unwrap();
+        assert_eq!(serde_json::to_string_pretty(&reloaded).unwrap(), EMPTY_REPORT_JSON);
+    }
+
+    fn output_files(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_report_writer_default_set() {
+        let temp_dir = TempDir::new().unwrap();
+        let report = create_test_report();
+
+        ReportWriter::new(temp_dir.path(), &ReportArtifact::DEFAULT_SET, CsvOptions::default())
+            .write(&report)
+            .unwrap();
+
+        assert_eq!(
+            output_files(temp_dir.path()),
+            vec!["report.csv", "report.json", "report.schema.json", "report_aggregate.json", "triage.json", "triage.md"]
+        );
+    }
+
+    #[test]
+    fn test_validate_output_prefix() {
+        for ok in ["acme", "org-1", "team_a.v2"] {
+            assert!(validate_output_prefix(ok).is_ok(), "{}", ok);
+        }
+        for bad in ["", "../acme", "a/b", "a b", ".hidden", "-flag", "acme\\x"] {
+            assert!(validate_output_prefix(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_report_writer_aggregate_only() {
+        let temp_dir = TempDir::new().unwrap();
+        let report = create_test_report();
+
+        ReportWriter::new(temp_dir.path(), &[ReportArtifact::AggregateJson], CsvOptions::default())
+            .write(&report)
+            .unwrap();
+        assert_eq!(output_files(temp_dir.path()), vec!["report_aggregate.json"]);
+
+        let temp_dir = TempDir::new().unwrap();
+        ReportWriter::new(temp_dir.path(), &[ReportArtifact::AggregateCsv], CsvOptions::default())
+            .write(&report)
+            .unwrap();
+        assert_eq!(output_files(temp_dir.path()), vec!["report_aggregate.csv"]);
+
+        let csv_content = std::fs::read_to_string(temp_dir.path().join("report_aggregate.csv")).unwrap();
+        assert!(csv_content.starts_with("repository,repository_url,hosted_nims,local_nims"));
+        assert!(csv_content.contains("test/repo,https://github.com/test/repo,nvidia/test-model,nvcr.io/nim/nvidia/test:1.0.0"));
+
+        let temp_dir = TempDir::new().unwrap();
+        ReportWriter::new(temp_dir.path(), &[ReportArtifact::Hotspots], CsvOptions::default())
+            .write(&report)
+            .unwrap();
+        assert_eq!(output_files(temp_dir.path()), vec!["hotspots.csv"]);
+        let csv_content = std::fs::read_to_string(temp_dir.path().join("hotspots.csv")).unwrap();
+        assert_eq!(csv_content, "\
+repository,directory,local_count,hosted_count
+test/repo,.,1,0
+test/repo,src,0,1
+");
+    }
+
+    #[test]
+    fn test_badge_color_thresholds() {
+        let thresholds = BadgeThresholds { low: 1, high: 3 };
+        assert_eq!(thresholds.color(0), "lightgrey");
+        assert_eq!(thresholds.color(1), "yellowgreen");
+        assert_eq!(thresholds.color(2), "yellowgreen");
+        assert_eq!(thresholds.color(3), "brightgreen");
+    }
+
+    #[test]
+    fn test_generate_badges() {
+        let temp_dir = TempDir::new().unwrap();
+        let report = create_test_report();
+        let repos = vec!["test/repo".to_string(), "test/empty".to_string()];
+
+        generate_badges(&report, &repos, temp_dir.path(), &BadgeThresholds::default()).unwrap();
+
+        let badge: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string(temp_dir.path().join("test_repo.json")).unwrap(),
+        ).unwrap();
+        assert_eq!(badge["schemaVersion"], 1);
+        assert_eq!(badge["label"], "NIM usage");
+        assert_eq!(badge["message"], "1 local, 1 hosted");
+        assert_eq!(badge["color"], "yellowgreen");
+
+        let empty: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string(temp_dir.path().join("test_empty.json")).unwrap(),
+        ).unwrap();
+        assert_eq!(empty["message"], "0 local, 0 hosted");
+        assert_eq!(empty["color"], "lightgrey");
+
+        let index: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string(temp_dir.path().join("index.json")).unwrap(),
+        ).unwrap();
+        assert_eq!(index["test/repo"], "test_repo.json");
+        assert_eq!(index["test/empty"], "test_empty.json");
+    }
+
+    fn adversarial_report(match_context: &str) -> ScanReport {
+        let source_code = NimFindings {
+            local_nim: vec![
+                LocalNimMatch {
+                    repository: "test/repo".to_string(),
+                    image_url: "nvcr.io/nim/nvidia/test".to_string(),
+                    tag: "1.0.0".to_string(),
+                    resolved_tag: None,
+                    tag_exists: None,
+                    file_path: "docs/guide, \"final\".md".to_string(),
+                    line_number: 7,
+                    match_context: match_context.to_string(),
+                    policy: None,
+                    enrichment_error: None,
+                    enriched: false,
+                    detection_rule: String::new(),
+                    workflow_triggers: Vec::new(),
+                    nim_identity: String::new(),
+                    enrichment_skipped: None,
+                    context_truncated: None,
+                    compose: None,
+                    intent: LocalIntent::Reference,
+                    usage_kind: UsageKind::Other,
+                    blame: None,
+                    notebook_cell: None,
+                    file_kind: FileKind::Source,
+                    resolved_from: None,
+                    fixture: false,
+                    fingerprint: String::new(),
+                },
+            ],
+            hosted_nim: vec![],
+            sdk_dependencies: vec![],
+        };
+        ScanReport::new(1, source_code, NimFindings::default())
+    }
+
+    fn read_csv_rows(path: &Path) -> Vec<csv::StringRecord> {
+        let mut reader = csv::Reader::from_path(path).unwrap();
+        reader.records().map(|r| r.unwrap()).collect()
+    }
+
+    #[test]
+    fn test_csv_adversarial_content_roundtrip() {
+        let temp_dir = TempDir::new().unwrap();
+        let context = "image: \"nvcr.io/nim/nvidia/test:1.0.0\", # ünïcødé ✓\nsecond line\r\nthird";
+        let report = adversarial_report(context);
+
+        generate_csv_reports(&report, &temp_dir.path().join("report.csv"), &CsvOptions::default()).unwrap();
+
+        let rows = read_csv_rows(&temp_dir.path().join("report.csv"));
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].len(), CSV_COLUMNS.len());
+        assert_eq!(&rows[0][3], "docs/guide, \"final\".md");
+        assert_eq!(&rows[0][13], context);
+    }
+
+    #[test]
+    fn test_csv_flatten_newlines() {
+        let temp_dir = TempDir::new().unwrap();
+        let report = adversarial_report("first\r\nsecond\nthird");
+        let opts = CsvOptions { flatten_newlines: true, safe_formulas: false };
+
+        generate_csv_reports(&report, &temp_dir.path().join("report.csv"), &opts).unwrap();
+
+        let raw = std::fs::read_to_string(temp_dir.path().join("report.csv")).unwrap();
+        assert_eq!(raw.lines().count(), 2, "header + one physical row");
+        let rows = read_csv_rows(&temp_dir.path().join("report.csv"));
+        assert_eq!(&rows[0][13], "first\\r\\nsecond\\nthird");
+    }
+
+    #[test]
+    fn test_csv_safe_formulas() {
+        let temp_dir = TempDir::new().unwrap();
+        let report = adversarial_report("=HYPERLINK(\"http://evil\")");
+
+        generate_csv_reports(&report, &temp_dir.path().join("report.csv"), &CsvOptions::default()).unwrap();
+        let rows = read_csv_rows(&temp_dir.path().join("report.csv"));
+        assert_eq!(&rows[0][13], "=HYPERLINK(\"http://evil\")");
+
+        let opts = CsvOptions { flatten_newlines: false, safe_formulas: true };
+        generate_csv_reports(&report, &temp_dir.path().join("report.csv"), &opts).unwrap();
+        let rows = read_csv_rows(&temp_dir.path().join("report.csv"));
+        assert_eq!(&rows[0][13], "'=HYPERLINK(\"http://evil\")");
+        // Non-formula cells are untouched
+        assert_eq!(&rows[0][5], "nvcr.io/nim/nvidia/test");
+    }
+
+    #[test]
+    fn test_csv_schema_matches_header() {
+        let temp_dir = TempDir::new().unwrap();
+        let report = create_test_report();
+        let opts = CsvOptions { flatten_newlines: true, safe_formulas: false };
+
+        generate_csv_reports(&report, &temp_dir.path().join("report.csv"), &opts).unwrap();
+
+        let schema: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string(temp_dir.path().join("report.schema.json")).unwrap(),
+        ).unwrap();
+        assert_eq!(schema["schema_version"], REPORT_SCHEMA_VERSION);
+        assert_eq!(schema["flatten_newlines"], true);
+
+        let mut reader = csv::Reader::from_path(temp_dir.path().join("report.csv")).unwrap();
+        let header: Vec<String> = reader.headers().unwrap().iter().map(|h| h.to_string()).collect();
+        let schema_columns: Vec<String> = schema["columns"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| c["name"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(header, schema_columns);
+        assert_eq!(schema["columns"][4]["type"], "integer");
+    }
+
+    /// Snapshot of the column contract. Append new columns at the end; never reorder.
+    const EXPECTED_HEADER: &str = "source_type,nim_type,repository,file_path,line_number,image_url,tag,\
+        resolved_tag,endpoint_url,model_name,function_id,status,container_image,match_context,severity,policy_rule,environment,invoke_style,policy_violation,author_email,author_date,file_kind,fixture,usage_kind,fingerprint,\
+        package_name,package_ecosystem,version_constraint";
+
+    #[test]
+    fn test_report_header_contract() {
+        let temp_dir = TempDir::new().unwrap();
+        let report = create_test_report();
+
+        generate_csv_reports(&report, &temp_dir.path().join("report.csv"), &CsvOptions::default()).unwrap();
+        generate_tsv_report(&report, &temp_dir.path().join("report.tsv"), &CsvOptions::default()).unwrap();
+
+        let csv = std::fs::read_to_string(temp_dir.path().join("report.csv")).unwrap();
+        let tsv = std::fs::read_to_string(temp_dir.path().join("report.tsv")).unwrap();
+        assert_eq!(csv.lines().next().unwrap(), EXPECTED_HEADER);
+        assert_eq!(tsv.lines().next().unwrap(), EXPECTED_HEADER.replace(',', "\t"));
+    }
+
+    #[test]
+    fn test_tsv_roundtrip() {
+        let temp_dir = TempDir::new().unwrap();
+        let report = adversarial_report("model=\"a, b\",\tmax_tokens=1\r\nnext line\nlast");
+        let path = temp_dir.path().join("report.tsv");
+
+        generate_tsv_report(&report, &path, &CsvOptions::default()).unwrap();
+
+        // One physical line per row, so naive tab splitting lines up with the header
+        let raw = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(raw.lines().count(), 2);
+        assert!(raw.lines().all(|l| l.split('\t').count() == CSV_COLUMNS.len()));
+
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(b'\t')
+            .quoting(false)
+            .from_path(&path)
+            .unwrap();
+        let rows: Vec<csv::StringRecord> = reader.records().map(|r| r.unwrap()).collect();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(&rows[0][3], "docs/guide, \"final\".md");
+        assert_eq!(&rows[0][13], "model=\"a, b\", max_tokens=1 next line last");
+    }
+
+    #[test]
+    fn test_is_own_report() {
+        let temp_dir = TempDir::new().unwrap();
+        let report = create_test_report();
+        let json_path = temp_dir.path().join("report.json");
+        let csv_path = temp_dir.path().join("report.csv");
+        let tsv_path = temp_dir.path().join("report.tsv");
+        generate_json_report(&report, &json_path).unwrap();
+        generate_csv_reports(&report, &csv_path, &CsvOptions::default()).unwrap();
+        generate_tsv_report(&report, &tsv_path, &CsvOptions::default()).unwrap();
+        for path in [&json_path, &csv_path, &tsv_path] {
+            assert!(is_own_report(&std::fs::read(path).unwrap()), "{}", path.display());
+        }
+
+        // Reports from before schema_version existed, and CSVs with fewer (older) columns
+        assert!(is_own_report(b"{\n  \"scan_time\": \"2024-01-01T00:00:00Z\",\n  \"aggregated\": {}\n}"));
+        assert!(is_own_report(b"source_type,nim_type,repository,file_path,line_number,image_url\r\nsource_code,hosted_nim,a/b,x.py,1,\n"));
+
+        // Unrelated files that share a key, a column or a model name
+        assert!(!is_own_report(b"{\"scan_time\": \"2024-01-01\", \"model\": \"meta/llama-3.1-8b-instruct\"}"));
+        assert!(!is_own_report(b"{\"model\": \"meta/llama-3.1-8b-instruct\", \"scan_time\": 1, \"aggregated\": {}}"));
+        assert!(!is_own_report(b"source_type,nim_type,repository\n"));
+        assert!(!is_own_report(b"source_type,nim_type,repository,file_path,line_number,model\n"));
+        assert!(!is_own_report(b""));
+    }
+
+    fn hosted_query_result(container_image: &str) -> HostedNimQueryResult {
+        HostedNimQueryResult {
+            query_model: "meta/llama-3.1-8b-instruct".to_string(),
+            function_id: Some("fn-123".to_string()),
+            name: None,
+            status: Some("ACTIVE".to_string()),
+            container_image: Some(container_image.to_string()),
+            ncf_function_id: None,
+            version_id: None,
+            created_at: None,
+            description: None,
+            health_uri: None,
+            inference_url: None,
+            models: None,
+            api_body_format: None,
+            raw_response: serde_json::json!({"id": "fn-123"}),
+        }
+    }
+
+    #[test]
+    fn test_format_records_csv_header_stable() {
+        let records = vec![
+            hosted_query_result("nvcr.io/qc69jvmznzxy/meta/llama-3.1-8b-instruct:1.3"),
+            hosted_query_result("nvcr.io/qc69jvmznzxy/meta/llama-3.1-8b-instruct:1.4"),
+        ];
+        let csv = format_records(&records, OutputFormat::Csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "model,function_id,status,container_image");
+        assert_eq!(lines.len(), 3);
+        assert!(!csv.contains("raw_response"));
+
+        let local = LocalNimQueryResult {
+            query_image: "nvcr.io/nim/nvidia/nv-embedqa-e5-v5".to_string(),
+            team: "nvidia".to_string(),
+            model: "nv-embedqa-e5-v5".to_string(),
+            tag: None,
+            digest: None,
+            name: None,
+            latest_tag: Some("1.5.0".to_string()),
+            latest_version_id: None,
+            description: None,
+            short_description: None,
+            is_public: None,
+            publisher: Some("NVIDIA".to_string()),
+            display_name: None,
+            repository_url: "nvcr.io/nim/nvidia/nv-embedqa-e5-v5".to_string(),
+            tags: None,
+            raw_response: serde_json::Value::Null,
+        };
+        let csv = format_records(&[local], OutputFormat::Csv).unwrap();
+        assert_eq!(csv.lines().next(), Some("image,latest_tag,display_name,publisher"));
+        assert_eq!(csv.lines().nth(1), Some("nvcr.io/nim/nvidia/nv-embedqa-e5-v5,1.5.0,,NVIDIA"));
+    }
+
+    #[test]
+    fn test_format_records_table_truncates_long_cells() {
+        let long_image = format!("nvcr.io/qc69jvmznzxy/meta/{}:1.3", "x".repeat(100));
+        let table = format_records(&[hosted_query_result(&long_image)], OutputFormat::Table).unwrap();
+        let lines: Vec<&str> = table.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("model"));
+        assert!(!table.contains(&long_image));
+        assert!(lines[2].ends_with("..."));
+        let image_cell = lines[2].rsplit("  ").next().unwrap();
+        assert_eq!(image_cell.chars().count(), MAX_TABLE_CELL);
+        // Columns line up with the header
+        assert_eq!(lines[0].find("status"), lines[2].find("ACTIVE"));
+    }
+
+    #[test]
+    fn test_format_records_json_keeps_raw_response() {
+        let json = format_records(&[hosted_query_result("img:1")], OutputFormat::Json).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["rawResponse"]["id"], "fn-123");
+    }
+
+    #[test]
+    fn test_delta_headline() {
+        let delta = ReportDelta {
+            new_local_images: vec!["nvcr.io/nim/a/x".to_string(), "nvcr.io/nim/a/y".to_string()],
+            removed_local_images: vec!["nvcr.io/nim/a/z".to_string()],
+            local_tag_changes: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            new_hosted_repos: vec!["org/repo".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            delta_headline(&delta),
+            "+2 new local NIM images, -1 removed, 3 tag changes, +0 new hosted models, -0 removed, +1 new repo with hosted usage"
+        );
+    }
+
+    #[test]
+    fn test_baseline_roundtrip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("report.json");
+        let report = create_test_report();
+        generate_json_report(&report, &path).unwrap();
+
+        let baseline = load_json_report(&path).unwrap();
+        assert_eq!(baseline.scan_time, report.scan_time);
+        assert!(baseline.delta.is_none());
+        assert!(!std::fs::read_to_string(&path).unwrap().contains("\"delta\""));
     }
 }
@@ -0,0 +1,115 @@
+//! Random samples of repositories (`--sample`)
+//!
+//! A full scan of a large organization takes hours. A sample of N enabled
+//! repositories, picked pseudo-randomly from a seed, goes through the normal
+//! pipeline instead, and the summary counts are scaled up to every enabled
+//! repository as estimates. The same seed and configuration always pick the
+//! same repositories, so an estimate can be reproduced and compared.
+
+use crate::models::{SampleEstimates, Sampling, Summary};
+
+/// SplitMix64: small, fast and fully determined by its seed
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `0..bound` (`bound` > 0), without modulo bias
+    fn below(&mut self, bound: u64) -> u64 {
+        let zone = u64::MAX - (u64::MAX % bound);
+        loop {
+            let value = self.next();
+            if value < zone {
+                return value % bound;
+            }
+        }
+    }
+}
+
+/// Indices of `size` of `population` items, in ascending order
+///
+/// A partial Fisher-Yates shuffle, so every subset of that size is equally likely.
+pub fn select(population: usize, size: usize, seed: u64) -> Vec<usize> {
+    let mut indices: Vec<usize> = (0..population).collect();
+    let size = size.min(population);
+    let mut rng = SplitMix64(seed);
+    for i in 0..size {
+        let j = i + rng.below((population - i) as u64) as usize;
+        indices.swap(i, j);
+    }
+    indices.truncate(size);
+    indices.sort_unstable();
+    indices
+}
+
+/// Seed for a run that didn't set --sample-seed
+pub fn seed_from_clock() -> u64 {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as u64);
+    SplitMix64(nanos).next()
+}
+
+/// `observed` × `population` / `sampled`, to one decimal
+pub fn extrapolate(observed: usize, sampled: usize, population: usize) -> f64 {
+    if sampled == 0 {
+        return 0.0;
+    }
+    let estimate = observed as f64 * population as f64 / sampled as f64;
+    (estimate * 10.0).round() / 10.0
+}
+
+/// Sampling block of a report: the repositories scanned and the summary scaled to the population
+pub fn sampling(seed: u64, population: usize, repositories: Vec<String>, summary: &Summary) -> Sampling {
+    let sampled = repositories.len();
+    Sampling {
+        seed,
+        population,
+        estimated: SampleEstimates {
+            total_local_nim: extrapolate(summary.total_local_nim, sampled, population),
+            total_hosted_nim: extrapolate(summary.total_hosted_nim, sampled, population),
+            repos_with_nim: extrapolate(summary.repos_with_nim, sampled, population),
+        },
+        repositories,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::NimFindings;
+
+    #[test]
+    fn test_select_is_deterministic() {
+        let first = select(400, 25, 42);
+        assert_eq!(first.len(), 25);
+        assert_eq!(first, select(400, 25, 42));
+        assert!(first.windows(2).all(|w| w[0] < w[1]));
+        assert!(first.iter().all(|&i| i < 400));
+        assert_ne!(first, select(400, 25, 43));
+        // Stable across releases: reports name their seed
+        assert_eq!(select(10, 3, 42), vec![2, 3, 4]);
+
+        assert_eq!(select(3, 10, 7), vec![0, 1, 2]);
+        assert!(select(0, 5, 7).is_empty());
+    }
+
+    #[test]
+    fn test_extrapolate() {
+        assert_eq!(extrapolate(37, 20, 400), 740.0);
+        assert_eq!(extrapolate(10, 3, 400), 1333.3);
+        assert_eq!(extrapolate(0, 20, 400), 0.0);
+        assert_eq!(extrapolate(5, 0, 400), 0.0);
+
+        let mut summary = Summary::calculate(&NimFindings::default(), &NimFindings::default());
+        (summary.total_local_nim, summary.total_hosted_nim, summary.repos_with_nim) = (12, 3, 4);
+        let sampling = sampling(42, 50, vec!["acme/a".to_string(), "acme/b".to_string()], &summary);
+        assert_eq!(sampling.estimated, SampleEstimates { total_local_nim: 300.0, total_hosted_nim: 75.0, repos_with_nim: 100.0 });
+    }
+}
@@ -3,16 +3,27 @@
 //! This module implements the core scanning logic to detect Local NIM (Docker images)
 //! and Hosted NIM (API endpoints) references in source code.
 
-use std::collections::HashSet;
-use std::path::Path;
+use std::collections::{BTreeMap, HashSet};
+use std::io::Read;
+use std::ops::{Range, RangeInclusive};
+use std::path::{Component, Path, PathBuf};
 use regex::Regex;
 use once_cell::sync::Lazy;
 use log::{debug, warn, info};
+use anyhow::Context;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
 use ignore::WalkBuilder;
 use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use sha2::{Digest, Sha256};
 
-use crate::models::{LocalNimMatch, HostedNimMatch, NimFindings, SourceType};
+use crate::models::{
+    CredentialSource, Environment, FileKind, Framework, InvokeStyle, ListedFileError, LocalIntent, LocalNimMatch, HostedNimMatch, MetadataField,
+    MetadataHint, NimFindings, RepoConfig, ScanTruncation, SdkDependencyMatch, SourceType, UsageKind,
+};
+use crate::notebook::Notebook;
+use crate::variables::{RecipeSyntax, Variables};
 
 // ============================================================================
 // Regex Patterns
@@ -24,33 +35,244 @@ static LOCAL_NIM_FULL: Lazy<Regex> = Lazy::new(|| {
         .expect("Invalid LOCAL_NIM_FULL regex")
 });
 
+/// Words captured as a "tag" when prose goes on after an image and a colon
+/// (`see nvcr.io/nim/org/model:the docs`); no NIM is tagged with one
+const TAG_STOP_WORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "by", "can", "for", "from", "has", "have",
+    "if", "in", "into", "is", "it", "its", "not", "of", "on", "or", "see", "so", "such",
+    "than", "that", "the", "then", "these", "this", "to", "use", "was", "we", "which",
+    "will", "with", "you", "your",
+];
+
+/// Longest image tag a registry accepts
+const MAX_TAG_LEN: usize = 128;
+
 static LOCAL_NIM_NO_TAG: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"nvcr\.io/nim/([a-zA-Z0-9._-]+/[a-zA-Z0-9._-]+)(?:[^:a-zA-Z0-9._-]|$)")
         .expect("Invalid LOCAL_NIM_NO_TAG regex")
 });
 
-/// Hosted NIM patterns - matches NVIDIA API endpoints and model references
-static HOSTED_ENDPOINT: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r#"https://(?:integrate|ai|build)\.api\.nvidia\.com[^\s"'\)]*"#)
-        .expect("Invalid HOSTED_ENDPOINT regex")
+/// Local NIM image whose tag is a variable no file of the repository defines
+/// (`nvcr.io/nim/org/name:${NIM_TAG}`, `:${NIM_TAG:-1.0.0}`, `:$NIM_TAG`), or
+/// a Makefile / justfile variable its file doesn't assign (`:$(TAG)`, `:{{tag}}`)
+static LOCAL_NIM_VAR_TAG: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"nvcr\.io/nim/([a-zA-Z0-9._-]+/[a-zA-Z0-9._-]+):(\$\{[A-Za-z_][A-Za-z0-9_]*(?::?-([a-zA-Z0-9._-]+))?[^}]*\}|\$[A-Za-z_][A-Za-z0-9_]*|(\$\([A-Za-z_][A-Za-z0-9_]*\)|\{\{\s*[A-Za-z_][A-Za-z0-9_-]*\s*\}\}))")
+        .expect("Invalid LOCAL_NIM_VAR_TAG regex")
+});
+
+/// NGC CLI command on a NIM path - matches `ngc registry image pull|info ...`
+/// and `ngc registry resource download-version ...`; group 2 is the arguments
+static NGC_CLI_COMMAND: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\bngc\s+registry\s+(image\s+(?:pull|info)|resource\s+download-version)\s+(.*)")
+        .expect("Invalid NGC_CLI_COMMAND regex")
+});
+
+/// NGC CLI options that take a value (`--org nim`, `--dest ./models`)
+const NGC_CLI_VALUE_OPTIONS: &[&str] = &["--org", "--team", "--ace", "--format_type", "--dest", "--file", "--exclude"];
+
+/// NVIDIA API hosts recognised as Hosted NIM endpoints, with the environment each serves
+/// (extended by `scan.hosted_hosts` and --extra-hosted-host, see [`HostedHosts`])
+pub const NVIDIA_API_HOSTS: &[(&str, Environment)] = &[
+    ("integrate.api.nvidia.com", Environment::Production),
+    ("ai.api.nvidia.com", Environment::Production),
+    ("build.api.nvidia.com", Environment::Production),
+    ("stg.api.nvidia.com", Environment::Staging),
+    ("api.stg.nvidia.com", Environment::Staging),
+    ("integrate.api.stg.nvidia.com", Environment::Staging),
+    ("ai.api.stg.nvidia.com", Environment::Staging),
+];
+
+/// Hosts of NVIDIA_API_HOSTS alone
+static BUILTIN_HOSTED_HOSTS: Lazy<HostedHosts> = Lazy::new(|| {
+    HostedHosts::new(&[]).expect("Invalid built-in Hosted NIM hosts")
+});
+
+/// Hosts whose `https://` URLs are Hosted NIM endpoints: NVIDIA_API_HOSTS, then
+/// the configured ones, each with the environment it serves
+///
+/// A host may start with `*.` to take in every subdomain of the rest
+/// (`*.api.nvidia.com` matches `eu.api.nvidia.com`, not `api.nvidia.com`). The
+/// first host that matches an endpoint decides its environment.
+#[derive(Debug, Clone)]
+pub struct HostedHosts {
+    /// Lowercase host names, possibly with a leading `*.`
+    hosts: Vec<(String, Environment)>,
+    /// `https://` URL on any of the hosts
+    endpoint: Regex,
+}
+
+impl Default for HostedHosts {
+    fn default() -> Self {
+        BUILTIN_HOSTED_HOSTS.clone()
+    }
+}
+
+impl HostedHosts {
+    /// NVIDIA_API_HOSTS plus `extra`, whose host names are validated
+    pub fn new(extra: &[(String, Environment)]) -> anyhow::Result<Self> {
+        let mut hosts: Vec<(String, Environment)> = NVIDIA_API_HOSTS.iter()
+            .map(|(host, env)| (host.to_string(), *env))
+            .collect();
+        for (host, env) in extra {
+            let host = validate_host_name(host)?;
+            if !hosts.iter().any(|(known, _)| *known == host) {
+                hosts.push((host, *env));
+            }
+        }
+        let alternatives: Vec<String> = hosts.iter()
+            .map(|(host, _)| match host.strip_prefix("*.") {
+                Some(domain) => format!(r"(?:[a-z0-9-]+\.)+{}", regex::escape(domain)),
+                None => regex::escape(host),
+            })
+            .collect();
+        let endpoint = Regex::new(&format!(r#"https://(?:{})[^\s"'\)]*"#, alternatives.join("|")))
+            .context("Invalid Hosted NIM endpoint regex")?;
+        Ok(HostedHosts { hosts, endpoint })
+    }
+
+    /// First endpoint URL in `text` whose host is one of the hosts as a whole
+    /// (not the start of a longer name, `https://api.nvidia.eu.example.com`)
+    fn find<'t>(&self, text: &'t str) -> Option<regex::Match<'t>> {
+        self.endpoint.find_iter(text)
+            .find(|m| endpoint_host(m.as_str()).is_some_and(|host| self.environment_of_host(&host).is_some()))
+    }
+
+    /// Hosts added on top of NVIDIA_API_HOSTS, without a `*.` prefix (prefilter anchors)
+    fn extra_domains(&self) -> impl Iterator<Item = &str> {
+        self.hosts[NVIDIA_API_HOSTS.len()..].iter()
+            .map(|(host, _)| host.strip_prefix("*.").unwrap_or(host))
+    }
+
+    /// Environment of a host when it is one of the hosts
+    fn environment_of_host(&self, host: &str) -> Option<Environment> {
+        self.hosts.iter()
+            .find(|(known, _)| match known.strip_prefix("*.") {
+                Some(domain) => host.strip_suffix(domain).is_some_and(|sub| sub.len() > 1 && sub.ends_with('.')),
+                None => host == known,
+            })
+            .map(|(_, env)| *env)
+    }
+
+    /// Environment of a Hosted NIM finding: staging if the endpoint is on a staging host
+    /// or the model carries the `stg/` prefix, production otherwise
+    pub fn environment(&self, endpoint_url: Option<&str>, model_name: Option<&str>) -> Environment {
+        let staging_host = endpoint_url
+            .and_then(endpoint_host)
+            .and_then(|host| self.environment_of_host(&host))
+            == Some(Environment::Staging);
+        let staging_model = model_name.is_some_and(|name| name.trim().to_lowercase().starts_with("stg/"));
+
+        if staging_host || staging_model {
+            Environment::Staging
+        } else {
+            Environment::Production
+        }
+    }
+
+    /// Set a finding's `environment` and `endpoint_host` once its endpoint and model are final
+    fn classify(&self, m: &mut HostedNimMatch) {
+        m.environment = self.environment(m.endpoint_url.as_deref(), m.model_name.as_deref());
+        m.endpoint_host = m.endpoint_url.as_deref()
+            .and_then(endpoint_host)
+            .filter(|host| self.environment_of_host(host).is_some());
+    }
+}
+
+/// Lowercase host of an `https://` URL: the letters, digits, dots and hyphens
+/// after the scheme, without a trailing dot
+fn endpoint_host(url: &str) -> Option<String> {
+    let rest = url.strip_prefix("https://")?;
+    let end = rest.find(|c: char| !(c.is_ascii_alphanumeric() || c == '.' || c == '-')).unwrap_or(rest.len());
+    Some(rest[..end].trim_end_matches('.').to_lowercase()).filter(|host| !host.is_empty())
+}
+
+/// `host` trimmed and lowercased, when it is a DNS host name of at least two
+/// labels (optionally `*.` and one): letters, digits and inner hyphens, 63
+/// characters a label at most
+pub fn validate_host_name(host: &str) -> anyhow::Result<String> {
+    let host = host.trim().to_lowercase();
+    let name = host.strip_prefix("*.").unwrap_or(&host);
+    let labels: Vec<&str> = name.split('.').collect();
+    let valid = name.len() <= 253
+        && labels.len() >= 2
+        && labels.iter().all(|label| {
+            (1..=63).contains(&label.len())
+                && label.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-')
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+        });
+    if !valid {
+        anyhow::bail!("Invalid Hosted NIM host {:?}: expected a host name such as api.example.com or *.api.example.com", host);
+    }
+    Ok(host)
+}
+
+/// NVCF invocation URLs - matches https://api.nvcf.nvidia.com/v2/nvcf/pexec/functions/<uuid>
+/// (group 1 is the function ID when written literally rather than as a placeholder)
+static NVCF_ENDPOINT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"https://api\.nvcf\.nvidia\.com/v2/nvcf/(?:pexec|exec)/functions/(?:([0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12})[^\s"'\)]*|[^\s"'\)]*)"#)
+        .expect("Invalid NVCF_ENDPOINT regex")
+});
+
+/// Function ID assignment - matches FUNCTION_ID = "<uuid>", nvcf_function_id: '<uuid>', etc.
+static FUNCTION_ID_ASSIGN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?i)[a-z0-9_]*function_id[a-z0-9_]*["']?\s*[=:]\s*["']([0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12})["']"#)
+        .expect("Invalid FUNCTION_ID_ASSIGN regex")
 });
 
-/// Build Page links - matches https://build.nvidia.com/<org>/<model>
+/// Build Page links - matches https://build.nvidia.com/<org>/<model> (any later
+/// path such as `/modelcard` is left out; see [`catalog_model_name`])
 static BUILD_PAGE_URL: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r#"https?://build\.nvidia\.com/([a-zA-Z0-9._-]+)/([a-zA-Z0-9._-]+)"#)
         .expect("Invalid BUILD_PAGE_URL regex")
 });
 
-/// Model assignment pattern - matches model = "xxx" or model: "xxx"
+/// Model assignment pattern - matches model = "xxx", model: "xxx", JSON "model": "xxx"
+/// or shell-escaped JSON \"model\": \"xxx\"
 static MODEL_ASSIGN: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r#"model\s*[=:]\s*["']((nvidia|meta|mistralai|google|deepseek|stg)/[^"']+|[^"']+/[^"']+)["']"#)
+    Regex::new(r#"model\\?["']?\s*[=:]\s*\\?["']((nvidia|meta|mistralai|google|deepseek|stg)/[^"'\\]+|[^"'\\]+/[^"'\\]+)\\?["']"#)
         .expect("Invalid MODEL_ASSIGN regex")
 });
 
-/// model_name field (e.g. in YAML/docs) - matches model_name: "xxx" or model_name = "xxx"
+/// Model assignment without an org - matches model = "nemotron-4-340b-instruct" or "model": "nvolveqa_40k".
+/// Only names in KNOWN_BARE_MODELS (or `scan.bare_models`) or of BARE_MODEL_SHAPE count.
+static BARE_MODEL_ASSIGN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"\bmodel["']?\s*[=:]\s*["']([a-zA-Z0-9][a-zA-Z0-9._-]*)["']"#)
+        .expect("Invalid BARE_MODEL_ASSIGN regex")
+});
+
+/// Short names that are NIMs whatever the rest of the name: `nemotron-*`, `llama-*-instruct`
+static BARE_MODEL_SHAPE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?i:nemotron-[a-z0-9._-]+|llama-[a-z0-9._-]+-instruct)$")
+        .expect("Invalid BARE_MODEL_SHAPE regex")
+});
+
+/// NIM models code refers to without their `org/` prefix: API catalog short
+/// names and legacy playground names (extended by `scan.bare_models`)
+pub const KNOWN_BARE_MODELS: &[&str] = &[
+    "nvolveqa_40k",
+    "nv-embed-v1",
+    "nv-embedqa-e5-v5",
+    "nv-embedqa-mistral-7b-v2",
+    "nv-rerankqa-mistral-4b-v3",
+    "mixtral_8x7b",
+    "mixtral-8x7b-instruct-v0.1",
+    "mistral-7b-instruct-v0.2",
+    "llama2_70b",
+    "llama2_13b",
+    "llama2_code_70b",
+    "playground_llama2_70b",
+    "playground_mixtral_8x7b",
+    "playground_nvolveqa_40k",
+];
+
+/// Origins tried for a bare model name during enrichment, most likely first
+pub const BARE_MODEL_ORGS: &[&str] = &["nvidia", "meta", "mistralai"];
+
+/// model_name field (e.g. in YAML/docs/JSON) - matches model_name: "xxx", model_name = "xxx" or "model_name": "xxx"
 /// Org is any word; whitelist is applied by model_is_whitelisted() (from NGC filters API).
 static MODEL_NAME_ASSIGN: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r#"model_name\s*[=:]\s*["'](([a-zA-Z0-9_-]+)/[a-zA-Z0-9._-]+)["']"#)
+    Regex::new(r#"model_name["']?\s*[=:]\s*["'](([a-zA-Z0-9_-]+)/[a-zA-Z0-9._-]+)["']"#)
         .expect("Invalid MODEL_NAME_ASSIGN regex")
 });
 
@@ -70,6 +292,49 @@ static NVIDIA_RERANK: Lazy<Regex> = Lazy::new(|| {
         .expect("Invalid NVIDIA_RERANK regex")
 });
 
+/// LangChain NVIDIA constructor opening a call (its arguments may continue on later lines)
+static LANGCHAIN_CONSTRUCTOR: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"\b(ChatNVIDIA|NVIDIAEmbeddings|NVIDIARerank)\s*\("#)
+        .expect("Invalid LANGCHAIN_CONSTRUCTOR regex")
+});
+
+/// `model="..."` keyword argument
+static MODEL_KWARG: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"\bmodel\s*=\s*["']([^"']+)["']"#)
+        .expect("Invalid MODEL_KWARG regex")
+});
+
+/// OpenAI SDK client assigned to a name: `client = OpenAI(`, `self.llm: AsyncOpenAI = AsyncOpenAI(`,
+/// `const client = new OpenAI({`
+static OPENAI_CLIENT_ASSIGN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"([A-Za-z_$][\w$]*(?:\.[A-Za-z_$][\w$]*)*)(?:\s*:\s*[\w.\[\]]+)?\s*=\s*(?:new\s+)?(?:openai\.)?(?:Async)?OpenAI\s*\(")
+        .expect("Invalid OPENAI_CLIENT_ASSIGN regex")
+});
+
+/// Request made through a named client: `client.chat.completions.create(`, `self.llm.embeddings.create(`
+static OPENAI_CLIENT_CALL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"([A-Za-z_$][\w$]*(?:\.[A-Za-z_$][\w$]*)*?)\.(?:beta\.)?(?:chat\.completions|completions|embeddings|responses)\.(?:create|parse|stream)\s*\(")
+        .expect("Invalid OPENAI_CLIENT_CALL regex")
+});
+
+/// Longest constructor call followed across lines
+const MAX_CALL_LINES: usize = 50;
+
+/// Annotated assignment whose variable name mentions model/nim - matches
+/// `nim_model: str = "org/model"`, `NIM_LLM_MODEL: Final = "org/model"` and
+/// `llm_model: str = Field(default="org/model")` (pydantic settings).
+/// Name-based heuristic; whitelist is applied by model_is_whitelisted().
+static ANNOTATED_MODEL_ASSIGN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"\b(?i:[a-z0-9_]*(?:model|nim)[a-z0-9_]*)\s*:\s*[A-Za-z_][A-Za-z0-9_.\[\], ]*?\s*=\s*(?:Field\(\s*(?:default\s*=\s*)?)?["'](([a-zA-Z0-9_-]+)/[a-zA-Z0-9._-]+)["']"#)
+        .expect("Invalid ANNOTATED_MODEL_ASSIGN regex")
+});
+
+/// Pydantic field default - matches Field(default="org/model") or Field("org/model")
+static PYDANTIC_FIELD_DEFAULT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"\bField\(\s*(?:default\s*=\s*)?["'](([a-zA-Z0-9_-]+)/[a-zA-Z0-9._-]+)["']"#)
+        .expect("Invalid PYDANTIC_FIELD_DEFAULT regex")
+});
+
 /// Environment or config assignment - matches os.environ["KEY"] = "org/model" or key["..."] = "org/model"
 /// Org is any word; whitelist is applied by model_is_whitelisted() (from NGC filters API).
 static ENV_OR_CONFIG_MODEL: Lazy<Regex> = Lazy::new(|| {
@@ -77,6 +342,37 @@ static ENV_OR_CONFIG_MODEL: Lazy<Regex> = Lazy::new(|| {
         .expect("Invalid ENV_OR_CONFIG_MODEL regex")
 });
 
+/// dotenv assignment whose key mentions model - matches `NIM_MODEL=org/model` and
+/// `export LLM_MODEL=org/model` (value without spaces; optional matching quotes).
+/// Org is any word; whitelist is applied by model_is_whitelisted().
+static DOTENV_MODEL_ASSIGN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"^\s*(?:export\s+)?(?i:[a-z0-9_]*model[a-z0-9_]*)=["']?(([a-zA-Z0-9_-]+)/[a-zA-Z0-9._-]+)["']?\s*(?:#.*)?$"#)
+        .expect("Invalid DOTENV_MODEL_ASSIGN regex")
+});
+
+/// Secret manifests whose templates carry plain-text config (External Secrets Operator, sealed-secrets)
+static SECRET_MANIFEST_KIND: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?m)^kind:\s*["']?(?:ExternalSecret|ClusterExternalSecret|SealedSecret)["']?\s*$"#)
+        .expect("Invalid SECRET_MANIFEST_KIND regex")
+});
+
+/// `model:` / `model_name:` value near a YAML endpoint without a model (see `index_context_models`)
+static YAML_CONTEXT_MODEL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"model(?:_name)?\s*[:=]\s*["']?([a-zA-Z0-9_/-]+/[a-zA-Z0-9._-]+)["']?"#)
+        .expect("Invalid YAML_CONTEXT_MODEL regex")
+});
+
+/// `tag:` value after a YAML image without a tag (see `find_tag_in_context`)
+static YAML_CONTEXT_TAG: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"tag\s*[:=]\s*["']?([a-zA-Z0-9._-]+)["']?"#).expect("Invalid YAML_CONTEXT_TAG regex")
+});
+
+/// Env-style key in a secret template's `data` - matches `NIM_MODEL: org/model` or `LLM_MODEL: "org/model"`
+static SECRET_DATA_MODEL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"^\s*["']?(?i:[a-z0-9_.-]*model[a-z0-9_.-]*)["']?\s*:\s*["']?(([a-zA-Z0-9_-]+)/[a-zA-Z0-9._-]+)["']?\s*$"#)
+        .expect("Invalid SECRET_DATA_MODEL regex")
+});
+
 /// Prose / doc text - matches org/model in natural language (e.g. "for nvidia/llama-3.2-nv-embedqa-1b-v2 model" or typo "v2model").
 /// Org is any word; whitelist is applied by model_is_whitelisted() (from NGC filters API).
 static DOC_PROSE_ORG_MODEL: Lazy<Regex> = Lazy::new(|| {
@@ -84,6 +380,186 @@ static DOC_PROSE_ORG_MODEL: Lazy<Regex> = Lazy::new(|| {
         .expect("Invalid DOC_PROSE_ORG_MODEL regex")
 });
 
+/// JS/TS client option holding the API base URL - matches baseURL: "https://..." (axios, OpenAI SDK) or baseUrl: `https://...`
+static JS_BASE_URL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"\bbase(?:URL|Url)["']?\s*:\s*["'`]https://"#)
+        .expect("Invalid JS_BASE_URL regex")
+});
+
+/// `model:` key in a JS/TS object literal (request body, JSON.stringify payload) - matches
+/// model: "org/model", "model": 'org/model' or model: `org/model`.
+/// Org is any word; whitelist is applied by model_is_whitelisted().
+static JS_MODEL_KEY: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"\bmodel["']?\s*:\s*["'`](([a-zA-Z0-9_-]+)/[a-zA-Z0-9._-]+)["'`]"#)
+        .expect("Invalid JS_MODEL_KEY regex")
+});
+
+/// Quoted `model` key of a JSON payload or Python dict - matches "model": "org/model",
+/// 'model': 'org/model' and shell-escaped \"model\": \"org/model\".
+/// Org is any word; whitelist is applied by model_is_whitelisted().
+static JSON_PAYLOAD_MODEL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"\\?["']model\\?["']\s*:\s*\\?["'](([a-zA-Z0-9_-]+)/[a-zA-Z0-9._-]+)\\?["']"#)
+        .expect("Invalid JSON_PAYLOAD_MODEL regex")
+});
+
+/// Endpoint URL assigned to a name - matches URL = "https://...", url: str = '...',
+/// export NIM_URL=https://... and const url = "..."
+static ENDPOINT_ASSIGN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"^\s*(?:export\s+|const\s+|let\s+|var\s+)?[A-Za-z_][A-Za-z0-9_.]*\s*(?::\s*[A-Za-z_]\w*\s*)?=\s*f?["']?https?://"#)
+        .expect("Invalid ENDPOINT_ASSIGN regex")
+});
+
+/// Command-line HTTP client whose request spans the rest of its shell command - matches curl and wget
+static SHELL_HTTP_CLIENT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\b(?:curl|wget)\b")
+        .expect("Invalid SHELL_HTTP_CLIENT regex")
+});
+
+/// Env definition in next.config / vite.config whose key mentions model - matches
+/// `NIM_MODEL: "org/model"` and `'import.meta.env.VITE_LLM_MODEL': JSON.stringify("org/model")`.
+/// Org is any word; whitelist is applied by model_is_whitelisted().
+static JS_ENV_MODEL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"^\s*["']?(?:[A-Za-z0-9_]+\.)*(?i:[a-z0-9_]*model[a-z0-9_]*)["']?\s*:\s*(?:JSON\.stringify\(\s*)?["'`](([a-zA-Z0-9_-]+)/[a-zA-Z0-9._-]+)["'`]"#)
+        .expect("Invalid JS_ENV_MODEL regex")
+});
+
+/// Definition of a variable bundled into browser code - matches NEXT_PUBLIC_NIM_URL: ...,
+/// NEXT_PUBLIC_NIM_URL=... and 'import.meta.env.VITE_NIM_URL': ...
+static BROWSER_EXPOSED_VAR: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"\b(?:NEXT_PUBLIC|VITE)_[A-Z0-9_]+["']?\s*[:=]"#)
+        .expect("Invalid BROWSER_EXPOSED_VAR regex")
+});
+
+/// Environment variables of NIM deployments and their clients, as a regex alternation
+const NIM_ENV_VAR_NAMES: &str = "NIM_MODEL_NAME|NIM_SERVED_MODEL_NAME|NIM_MODEL_PROFILE|NGC_API_KEY|NVIDIA_API_KEY";
+
+/// NIM variables naming the model a NIM serves
+const NIM_ENV_MODEL_VARS: &[&str] = &["NIM_MODEL_NAME", "NIM_SERVED_MODEL_NAME"];
+
+/// NIM variables holding a credential; their values never reach `match_context`
+const NIM_ENV_SECRET_VARS: &[&str] = &["NGC_API_KEY", "NVIDIA_API_KEY"];
+
+/// Rule of findings made from NIM variable declarations
+const NIM_ENV_VAR_RULE: &str = "nim_env_var";
+
+/// Shell, dotenv, Dockerfile `ENV` or Compose list declaration of a NIM variable - matches
+/// `NIM_MODEL_NAME=org/model`, `export NGC_API_KEY=...`, `-e NGC_API_KEY=$NGC_API_KEY`
+/// and `- "NIM_SERVED_MODEL_NAME=org/model"` (group 1 the name, group 2 the value)
+static NIM_ENV_ASSIGN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(&format!(r#"(?:^|[\s"'])(?:export\s+)?({})=("[^"]*"|'[^']*'|[^\s"';&|]*)"#, NIM_ENV_VAR_NAMES))
+        .expect("Invalid NIM_ENV_ASSIGN regex")
+});
+
+/// YAML mapping entry of a NIM variable (Compose `environment:`, Actions `env:`) -
+/// matches `NIM_MODEL_NAME: org/model` and `NGC_API_KEY: ${{ secrets.NGC_API_KEY }}`
+static NIM_ENV_YAML_KEY: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(&format!(r#"^\s*["']?({})["']?\s*:(?:\s+("[^"]*"|'[^']*'|\$\{{\{{[^}}]*\}}\}}|[^\s#]+))?\s*(?:#.*)?$"#, NIM_ENV_VAR_NAMES))
+        .expect("Invalid NIM_ENV_YAML_KEY regex")
+});
+
+/// Kubernetes `env:` entry of a NIM variable - matches `- name: NIM_MODEL_NAME`
+/// (its `value:` or `valueFrom:` follows on the next lines)
+static NIM_ENV_K8S_NAME: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(&format!(r#"^\s*-\s+name:\s*["']?({})["']?\s*$"#, NIM_ENV_VAR_NAMES))
+        .expect("Invalid NIM_ENV_K8S_NAME regex")
+});
+
+/// `value:` of a Kubernetes `env:` entry (group 1)
+static K8S_ENV_VALUE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"^\s*value:\s*("[^"]*"|'[^']*'|\S+)"#).expect("Invalid K8S_ENV_VALUE regex")
+});
+
+/// Value of a credential variable written next to its name - matches the value in
+/// `NGC_API_KEY=nvapi-...` or `NVIDIA_API_KEY: "nvapi-..."` (groups 1-2 kept, group 3 replaced)
+static NIM_ENV_SECRET_VALUE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"\b(NGC_API_KEY|NVIDIA_API_KEY)(["']?=|["']?:\s*)(\$\{\{[^}]*\}\}|\$\{[^}]*\}|"[^"]*"|'[^']*'|[^\s"',;&|}]+)"#)
+        .expect("Invalid NIM_ENV_SECRET_VALUE regex")
+});
+
+/// Sibling call parameters - matches temperature=0.2, "max_tokens": 1024, stream: true, ...
+/// (group 1 is the key, group 2 the literal value)
+static CALL_PARAM: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"\b(temperature|max_tokens|stream|top_p)["']?\s*[:=]\s*("[^"]*"|'[^']*'|-?\d+(?:\.\d+)?\b|true\b|false\b|True\b|False\b)"#)
+        .expect("Invalid CALL_PARAM regex")
+});
+
+/// Line that passes an API key - api_key=..., apiKey: ..., NVIDIA_API_KEY: ..., Authorization: Bearer ...
+static CREDENTIAL_LINE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)api[_-]?key|authorization|bearer|nvapi-")
+        .expect("Invalid CREDENTIAL_LINE regex")
+});
+
+/// GitHub Actions expression - matches ${{ secrets.NVIDIA_API_KEY }} and ${{ env.NVIDIA_API_KEY }}
+/// (group 1 is the context, group 2 the name)
+static ACTIONS_CREDENTIAL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\$\{\{\s*(secrets|env)\.([A-Za-z_][A-Za-z0-9_]*)\s*\}\}")
+        .expect("Invalid ACTIONS_CREDENTIAL regex")
+});
+
+/// Environment variable read - matches os.environ["X"], os.environ.get("X"), os.getenv("X"),
+/// process.env.X, process.env["X"], import.meta.env.X, and upper-case $X / ${X}
+static ENV_VAR_REF: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?:os\.environ(?:\.get)?\s*[\[(]\s*|getenv\(\s*|process\.env\[\s*)["']([A-Za-z_][A-Za-z0-9_]*)["']|(?:process\.env|import\.meta\.env)\.([A-Za-z_][A-Za-z0-9_]*)|\$\{?([A-Z_][A-Z0-9_]*)\b"#)
+        .expect("Invalid ENV_VAR_REF regex")
+});
+
+/// API key written into the file - matches nvapi-..., api_key="<16+ chars>", Bearer <20+ chars>
+/// (group 1, 2 or 3 is the value to redact)
+static LITERAL_KEY: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"\b(nvapi-[A-Za-z0-9_-]{20,})|(?i:api[_-]?key|token)["']?\s*[:=]\s*["']([^"'\s${}<>]{16,})["']|\bBearer\s+([A-Za-z0-9._~+/-]{20,})"#)
+        .expect("Invalid LITERAL_KEY regex")
+});
+
+/// Dockerfile instruction building on an image - matches FROM ... and COPY --from=...
+static INTENT_BASE_IMAGE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^\s*(?:FROM\s|COPY\s.*--from=)")
+        .expect("Invalid INTENT_BASE_IMAGE regex")
+});
+
+/// Container start - matches docker/podman/nerdctl run|create, kubectl run,
+/// apptainer/singularity run|exec and the Docker SDK's containers.run(...)
+static INTENT_RUN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\b(?:docker|podman|nerdctl)\s+(?:container\s+)?(?:run|create)\b|\bkubectl\s+run\b|\b(?:apptainer|singularity)\s+(?:run|exec)\b|\bcontainers\.run\s*\(")
+        .expect("Invalid INTENT_RUN regex")
+});
+
+/// Image download - matches docker/podman/nerdctl/crictl/apptainer/singularity pull,
+/// enroot import, the Docker SDK's images.pull(...) and NGC CLI pulls and downloads
+static INTENT_PULL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\b(?:docker|podman|nerdctl|crictl|apptainer|singularity)\s+(?:image\s+)?pull\b|\benroot\s+import\b|\bimages\.pull\s*\(|\bngc\s+registry\s+(?:image\s+pull|resource\s+download-version)\b")
+        .expect("Invalid INTENT_PULL regex")
+});
+
+/// Manifest key holding an image - matches image: ..., - image: ..., "image": ... and Helm's repository: ...
+static INTENT_DEPLOY_KEY: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"^\s*(?:-\s+)?["']?(?:image|repository)["']?\s*:"#)
+        .expect("Invalid INTENT_DEPLOY_KEY regex")
+});
+
+/// Container start on Kubernetes - matches kubectl run
+static USAGE_KUBECTL_RUN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\bkubectl\s+run\b")
+        .expect("Invalid USAGE_KUBECTL_RUN regex")
+});
+
+/// YAML mapping key at the start of a line - matches key:, - key: and "key":
+static YAML_LINE_KEY: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"^\s*(-\s+)?["']?([\w.-]+)["']?\s*:(?:\s|$)"#)
+        .expect("Invalid YAML_LINE_KEY regex")
+});
+
+/// API version path segment in endpoint URLs (v1, v2, v1beta, v2alpha1)
+static URL_VERSION_SEGMENT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^v\d+(?:(?:alpha|beta)\d*)?$")
+        .expect("Invalid URL_VERSION_SEGMENT regex")
+});
+
+/// NVCF writes dotted versions with underscores in URLs (llama-3_2 for llama-3.2)
+static DIGIT_UNDERSCORE_DIGIT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(\d)_(\d)")
+        .expect("Invalid DIGIT_UNDERSCORE_DIGIT regex")
+});
+
 // ============================================================================
 // Source Type Classification
 // ============================================================================
@@ -103,6 +579,127 @@ pub fn determine_source_type(file_path: &str) -> SourceType {
     }
 }
 
+/// CI pipeline files besides GitHub Actions workflows (lowercase names)
+const CI_FILE_NAMES: &[&str] = &[
+    ".gitlab-ci.yml", ".gitlab-ci.yaml", "jenkinsfile", "azure-pipelines.yml", "azure-pipelines.yaml",
+    "bitbucket-pipelines.yml", ".travis.yml", "cloudbuild.yaml", "cloudbuild.yml",
+];
+
+/// Directories holding CI pipeline definitions
+const CI_DIRS: &[&str] = &[".github/workflows/", ".circleci/", ".buildkite/", ".gitlab/ci/"];
+
+/// Documentation by extension (notebooks are their own kind)
+const DOCS_EXTENSIONS: &[&str] = &["md", "markdown", "mdx", "rst", "adoc", "txt", "html", "htm"];
+
+/// Top-level `apiVersion:` of a Kubernetes manifest (YAML, possibly a list item, or JSON)
+static KUBERNETES_API_VERSION: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?m)^\s*(?:-\s+)?"?apiVersion"?\s*:\s*"?[a-z0-9./-]+"#).expect("Invalid KUBERNETES_API_VERSION regex")
+});
+
+/// `kind:` key of a Kubernetes manifest
+static KUBERNETES_KIND: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?m)^\s*"?kind"?\s*:\s*"?[A-Z][A-Za-z]+"#).expect("Invalid KUBERNETES_KIND regex")
+});
+
+/// Kind of file a finding is in, finer than [`determine_source_type`]
+///
+/// Checked in order: CI pipelines, notebooks, Dockerfiles, Compose files, Helm
+/// charts (`Chart.yaml`, `values*.yaml`, or a file under `templates/` using
+/// `{{ }}`), Kubernetes manifests (YAML / JSON with `apiVersion` and `kind`)
+/// and docs; anything else is source.
+pub fn classify_file_kind(file_path: &str, content: &str) -> FileKind {
+    let normalized = file_path.replace('\\', "/");
+    let path = Path::new(&normalized);
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("").to_lowercase();
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+    let is_manifest = matches!(extension.as_str(), "yaml" | "yml" | "json");
+    let in_dir = |dir: &str| normalized.starts_with(dir) || normalized.contains(&format!("/{}", dir));
+
+    if determine_source_type(&normalized) == SourceType::ActionsWorkflow
+        || CI_FILE_NAMES.contains(&name.as_str())
+        || CI_DIRS.iter().any(|dir| in_dir(dir))
+    {
+        FileKind::Ci
+    } else if extension == "ipynb" {
+        FileKind::Notebook
+    } else if name.starts_with("dockerfile") || name.starts_with("containerfile") || extension == "dockerfile" {
+        FileKind::Dockerfile
+    } else if crate::compose::is_compose_file(path) {
+        FileKind::Compose
+    } else if name == "chart.yaml"
+        || (name.starts_with("values") && matches!(extension.as_str(), "yaml" | "yml"))
+        || (in_dir("templates/") && content.contains("{{"))
+    {
+        FileKind::Helm
+    } else if is_manifest && KUBERNETES_API_VERSION.is_match(content) && KUBERNETES_KIND.is_match(content) {
+        FileKind::Kubernetes
+    } else if DOCS_EXTENSIONS.contains(&extension.as_str()) {
+        FileKind::Docs
+    } else {
+        FileKind::Source
+    }
+}
+
+// ============================================================================
+// Workflow Triggers
+// ============================================================================
+
+/// Parse the `on:` triggers of a GitHub Actions workflow
+///
+/// Handles the string (`on: push`), list (`on: [push, pull_request]`) and map
+/// (`on: {push: {branches: [main]}}`) forms. Each `schedule` cron becomes its own
+/// `schedule: <cron>` entry. Returns an empty list if the workflow doesn't parse.
+pub fn parse_workflow_triggers(content: &str) -> Vec<String> {
+    let doc: serde_yaml::Value = match serde_yaml::from_str(content) {
+        Ok(v) => v,
+        Err(e) => {
+            debug!("Failed to parse workflow triggers: {}", e);
+            return Vec::new();
+        }
+    };
+    // YAML 1.1 parsers read a bare `on` key as `true`
+    let on = match doc.get("on").or_else(|| doc.get(serde_yaml::Value::Bool(true))) {
+        Some(v) => v,
+        None => return Vec::new(),
+    };
+
+    match on {
+        serde_yaml::Value::String(s) => vec![s.clone()],
+        serde_yaml::Value::Sequence(events) => events
+            .iter()
+            .filter_map(|e| e.as_str().map(str::to_string))
+            .collect(),
+        serde_yaml::Value::Mapping(events) => {
+            let mut triggers = Vec::new();
+            for (event, config) in events {
+                let event = match event.as_str() {
+                    Some(e) => e,
+                    None => continue,
+                };
+                if event != "schedule" {
+                    triggers.push(event.to_string());
+                    continue;
+                }
+                let crons: Vec<&str> = config
+                    .as_sequence()
+                    .map(|entries| entries.iter().filter_map(|c| c.get("cron")?.as_str()).collect())
+                    .unwrap_or_default();
+                if crons.is_empty() {
+                    triggers.push(event.to_string());
+                }
+                triggers.extend(crons.into_iter().map(|cron| format!("schedule: {}", cron)));
+            }
+            triggers
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Whether any of the triggers is a `schedule` (with or without a cron string)
+pub fn has_schedule_trigger(triggers: &[String]) -> bool {
+    triggers.iter().any(|t| t == "schedule" || t.starts_with("schedule:"))
+}
+
 // ============================================================================
 // File Filtering
 // ============================================================================
@@ -111,16 +708,176 @@ pub fn determine_source_type(file_path: &str) -> SourceType {
 const SCAN_EXTENSIONS: &[&str] = &[
     "py", "yaml", "yml", "sh", "bash", "js", "ts", "jsx", "tsx",
     "dockerfile", "env", "json", "toml", "cfg", "ini", "conf",
-    "md", "ipynb",
+    "md", "markdown", "ipynb", "jsonc", "json5", "mjs", "cjs", "mts", "cts", "html", "htm",
 ];
 
-/// Directory names to skip (matched as path components, not substrings)
-const SKIP_DIRS: &[&str] = &[
+/// Directory names to skip (matched as path components, not substrings).
+/// Default for the `skip_dirs` config list; see `resolve_skip_dirs`.
+pub const SKIP_DIRS: &[&str] = &[
     "node_modules", "vendor", "__pycache__", ".venv", "venv",
     "target", "build", "dist", ".tox", ".pytest_cache", ".mypy_cache",
     "eggs", ".eggs",
 ];
 
+/// Resolve the skip-directory list for a repository: the configured `skip_dirs`
+/// replacement (or built-in SKIP_DIRS), plus `skip_dirs_add`, minus `skip_dirs_remove`
+pub fn resolve_skip_dirs(repo: &RepoConfig) -> Vec<String> {
+    let mut dirs: Vec<String> = match repo.skip_dirs {
+        Some(ref dirs) => dirs.clone(),
+        None => SKIP_DIRS.iter().map(|d| d.to_string()).collect(),
+    };
+    for d in &repo.skip_dirs_add {
+        if !dirs.contains(d) {
+            dirs.push(d.clone());
+        }
+    }
+    dirs.retain(|d| !repo.skip_dirs_remove.contains(d));
+    dirs
+}
+
+/// Directory depth limit when neither --max-depth nor `scan.max_depth` is given
+pub const DEFAULT_MAX_DEPTH: usize = 32;
+
+/// File limit per repository when neither --max-files-per-repo nor `scan.max_files_per_repo` is given
+pub const DEFAULT_MAX_FILES_PER_REPO: usize = 100_000;
+
+/// YAML context lookups (endpoint without a model -> nearby `model_name`) per file
+pub const MAX_CONTEXT_LOOKUPS_PER_FILE: usize = 10_000;
+
+/// match_context length limit in characters when neither --max-context-chars nor `scan.max_context_chars` is given
+pub const DEFAULT_MAX_CONTEXT_CHARS: usize = 2000;
+
+/// Settings for scanning one repository
+#[derive(Debug, Clone, Default)]
+pub struct ScanOptions {
+    /// Directory names to skip (see `resolve_skip_dirs`)
+    pub skip_dirs: Vec<String>,
+    /// Capture literal call parameters (temperature, max_tokens, ...) around Hosted NIM matches
+    pub capture_call_params: bool,
+    /// Run the line-by-line pass on every file, even those without anchor literals
+    pub disable_prefilter: bool,
+    /// Deepest directory level the walker descends into (repository root is 0; None = unlimited)
+    pub max_depth: Option<usize>,
+    /// Stop collecting files after this many (None = unlimited)
+    pub max_files: Option<usize>,
+    /// Only scan files matching these path patterns (--scope; None = every file)
+    pub scope_paths: Option<Gitignore>,
+    /// Scan report.json / report.csv files written by this scanner instead of skipping them
+    pub scan_own_reports: bool,
+    /// Cut match_context to this many characters around the match (None = whole line)
+    pub max_context_chars: Option<usize>,
+    /// Skip lockfiles and generated code matching these names or carrying a
+    /// generated-code marker (see `build_generated_matcher`; None = scan them)
+    pub generated_files: Option<Gitignore>,
+    /// Bare model names counted on top of KNOWN_BARE_MODELS (`scan.bare_models`, lowercase)
+    pub bare_models: Vec<String>,
+    /// Variables from the repository's .env and Compose files, substituted into
+    /// scanned lines (collected by `scan_directory` / `scan_files`)
+    pub variables: Variables,
+    /// Walk `values*.yaml` files as Helm values (set when the repository has a `Chart.yaml`)
+    pub helm_values: bool,
+    /// Files whose findings are marked `fixture` (see `build_fixture_matcher`; None = none)
+    pub fixture_files: Option<Gitignore>,
+    /// Leave documentation out: docs files aren't collected and notebooks' Markdown cells aren't scanned
+    pub skip_docs: bool,
+    /// Hosts of Hosted NIM endpoints (`scan.hosted_hosts` / --extra-hosted-host on top of the built-in ones)
+    pub hosted_hosts: HostedHosts,
+}
+
+/// Compile `--scope` path patterns (gitignore syntax, relative to the repository root)
+///
+/// The same patterns drive the sparse checkout, so a full clone (or a
+/// pre-provisioned checkout) is restricted to the same files.
+pub fn build_scope_matcher(patterns: &[String]) -> anyhow::Result<Gitignore> {
+    let mut builder = GitignoreBuilder::new("");
+    for pattern in patterns {
+        builder
+            .add_line(None, pattern)
+            .with_context(|| format!("Invalid scope path pattern: {}", pattern))?;
+    }
+    builder.build().context("Failed to build scope path matcher")
+}
+
+/// Lockfiles and generated sources skipped by default (gitignore syntax, matched
+/// in any directory); `scan.generated_files` adds patterns
+pub const GENERATED_FILES: &[&str] = &[
+    "package-lock.json", "npm-shrinkwrap.json", "yarn.lock", "pnpm-lock.yaml",
+    "poetry.lock", "Pipfile.lock", "uv.lock", "Cargo.lock", "go.sum",
+    "composer.lock", "Gemfile.lock",
+    "*_pb2.py", "*_pb2_grpc.py", "*.pb.go", "*.generated.ts", "*.generated.js",
+];
+
+/// Bytes at the start of a file searched for a generated-code marker
+const GENERATED_SNIFF_BYTES: u64 = 4096;
+
+/// Header markers code generators leave (`// Code generated ... DO NOT EDIT.`, `@generated`)
+static GENERATED_MARKER: Lazy<regex::bytes::Regex> = Lazy::new(|| {
+    regex::bytes::Regex::new("@generated|DO NOT EDIT")
+        .expect("Invalid GENERATED_MARKER regex")
+});
+
+/// Compile GENERATED_FILES plus extra patterns into the generated-file matcher
+pub fn build_generated_matcher(extra: &[String]) -> anyhow::Result<Gitignore> {
+    let mut builder = GitignoreBuilder::new("");
+    for pattern in GENERATED_FILES.iter().copied().chain(extra.iter().map(String::as_str)) {
+        builder
+            .add_line(None, pattern)
+            .with_context(|| format!("Invalid generated file pattern: {}", pattern))?;
+    }
+    builder.build().context("Failed to build generated file matcher")
+}
+
+/// Test data whose findings are marked `fixture` when `scan.test_fixture_globs` is not set
+pub const DEFAULT_TEST_FIXTURE_GLOBS: &[&str] = &["tests/fixtures/**", "**/testdata/**"];
+
+/// Compile `scan.test_fixture_globs` (gitignore syntax, relative to the repository root)
+pub fn build_fixture_matcher(patterns: &[String]) -> anyhow::Result<Gitignore> {
+    let mut builder = GitignoreBuilder::new("");
+    for pattern in patterns {
+        builder
+            .add_line(None, pattern)
+            .with_context(|| format!("Invalid test fixture pattern: {}", pattern))?;
+    }
+    builder.build().context("Failed to build test fixture matcher")
+}
+
+/// Whether a checkout is this scanner's own source (or a fork of it), going by
+/// the package name in its root `Cargo.toml` rather than the repository name
+fn is_scanner_repo(repo_path: &Path) -> bool {
+    let Ok(manifest) = std::fs::read_to_string(repo_path.join("Cargo.toml")) else {
+        return false;
+    };
+    let mut in_package = false;
+    for line in manifest.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_package = line == "[package]";
+        } else if let Some(value) = line.strip_prefix("name").map(str::trim_start).and_then(|rest| rest.strip_prefix('=')) {
+            if in_package {
+                return value.trim().trim_matches(|c| c == '"' || c == '\'') == env!("CARGO_PKG_NAME");
+            }
+        }
+    }
+    false
+}
+
+/// Whether a file is a lockfile or generated code: by its name, else by a
+/// marker in its first GENERATED_SNIFF_BYTES (the rest is never read)
+fn is_generated_file(matcher: &Gitignore, path: &Path, relative_path: &Path) -> bool {
+    if matcher.matched_path_or_any_parents(relative_path, false).is_ignore() {
+        return true;
+    }
+    let mut head = Vec::new();
+    match std::fs::File::open(path) {
+        Ok(file) => file.take(GENERATED_SNIFF_BYTES).read_to_end(&mut head).is_ok() && GENERATED_MARKER.is_match(&head),
+        Err(_) => false,
+    }
+}
+
+/// Whether a repository-relative file path is inside the scope patterns
+fn in_scope(scope: &Gitignore, relative_path: &Path) -> bool {
+    scope.matched_path_or_any_parents(relative_path, false).is_ignore()
+}
+
 /// Check if a file should be scanned based on its name/extension
 fn should_scan_file(path: &Path) -> bool {
     let file_name = path.file_name()
@@ -132,6 +889,21 @@ fn should_scan_file(path: &Path) -> bool {
         return true;
     }
     
+    // dotenv variants: .env.production, env.sample, .env-local, config.env.example
+    if is_env_file_name(file_name) {
+        return true;
+    }
+    
+    // Makefiles and justfiles, whose recipes often `docker run` a NIM
+    if RecipeSyntax::of(file_name).is_some() {
+        return true;
+    }
+    
+    // Dependency manifests, for the SDKs they declare (requirements.txt)
+    if crate::dependencies::is_manifest(path) {
+        return true;
+    }
+    
     // Check extension
     if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
         return SCAN_EXTENSIONS.contains(&ext.to_lowercase().as_str());
@@ -140,6 +912,16 @@ fn should_scan_file(path: &Path) -> bool {
     false
 }
 
+/// dotenv-style file names, whatever their extension (or lack of one)
+pub fn is_env_file_name(file_name: &str) -> bool {
+    let name = file_name.to_lowercase();
+    name.contains(".env") || name.starts_with("env.")
+}
+
+fn is_notebook_file(path: &Path) -> bool {
+    path.extension().and_then(|e| e.to_str()).is_some_and(|e| e.eq_ignore_ascii_case("ipynb"))
+}
+
 fn is_doc_like_file(path: &Path) -> bool {
     matches!(
         path.extension()
@@ -147,44 +929,329 @@ fn is_doc_like_file(path: &Path) -> bool {
             .unwrap_or("")
             .to_lowercase()
             .as_str(),
-        "md" | "ipynb"
+        "md" | "markdown" | "ipynb" | "html" | "htm"
     )
 }
 
-// ---------------------------------------------------------------------------
-// Build API catalog (blueprints): scripts/generate_repos_from_ngc.py uses
-// /v2/blueprints/ and /v2/blueprints/{orgName}/{name}/spec for --refresh-repos.
-// This constant is the legacy resources API URL (kept for reference). Do not use
-// for publisher whitelist; use PUBLISHER_FILTERS_API_URL below.
-// ---------------------------------------------------------------------------
-#[allow(dead_code)]
-const BUILD_CATALOG_RESOURCES_API_URL: &str = "https://api.ngc.nvidia.com/v2/search/catalog/resources/ENDPOINT";
+/// Markdown documents, scanned for their fenced code blocks and inline code only
+fn is_markdown_file(path: &Path) -> bool {
+    path.extension().and_then(|e| e.to_str()).is_some_and(|e| e.eq_ignore_ascii_case("md") || e.eq_ignore_ascii_case("markdown"))
+}
 
-/// NGC catalog filters API: used only to fetch the publisher whitelist for Hosted NIM detection.
-/// Decoded query: {"filters":[],"orderBy":[{"field":"score","value":"DESC"}],"page":0,"pageSize":1000,"query":"orgName:\"qc69jvmznzxy\"","scoredSize":1000}
-/// Response is an array of { filterCategory, filterValues: [{ filterValue, displayName?, ... }] }; we use only filterValue for publisher (e.g. nvidia, meta, deepseek_ai).
-const PUBLISHER_FILTERS_API_URL: &str = "https://api.ngc.nvidia.com/v2/search/catalog/filters/ENDPOINT?q=%7B%22filters%22%3A%5B%5D%2C%22orderBy%22%3A%5B%7B%22field%22%3A%22score%22%2C%22value%22%3A%22DESC%22%7D%5D%2C%22page%22%3A0%2C%22pageSize%22%3A1000%2C%22query%22%3A%22orgName%3A%5C%22qc69jvmznzxy%5C%22%22%2C%22scoredSize%22%3A1000%7D";
+/// Documentation files by extension (Markdown, reStructuredText, HTML, text), not collected with --skip-docs
+fn is_docs_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| DOCS_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+}
 
-static PUBLISHER_WHITELIST: Lazy<HashSet<String>> = Lazy::new(|| {
-    match fetch_publishers_from_filters_api() {
-        Ok(set) if !set.is_empty() => {
-            info!("Loaded {} publishers from NGC catalog filters API", set.len());
-            set
-        }
-        Ok(_) => {
-            warn!("NGC filters API returned no publishers, using fallback list");
-            default_publisher_whitelist()
-        }
-        Err(err) => {
-            warn!("Failed to load publishers from NGC filters API: {}", err);
-            default_publisher_whitelist()
-        }
-    }
-});
+/// JavaScript / TypeScript sources, where request bodies and client options are object literals
+fn is_js_file(path: &Path) -> bool {
+    matches!(
+        path.extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_lowercase()
+            .as_str(),
+        "js" | "jsx" | "ts" | "tsx" | "mjs" | "cjs" | "mts" | "cts"
+    )
+}
 
-fn default_publisher_whitelist() -> HashSet<String> {
-    [
-        "nvidia",
+/// next.config.* / vite.config.*, whose env blocks define build-time variables
+fn is_js_build_config(path: &Path) -> bool {
+    let file_name = path.file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    is_js_file(path) && (file_name.starts_with("next.config.") || file_name.starts_with("vite.config."))
+}
+
+/// JSON-with-comments files (VS Code jsonc, JSON5) whose comments are stripped before scanning
+fn is_jsonc_file(path: &Path) -> bool {
+    matches!(
+        path.extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_lowercase()
+            .as_str(),
+        "jsonc" | "json5"
+    )
+}
+
+/// Blank out `//` and `/* */` comments in JSONC/JSON5 content.
+///
+/// Comment characters are replaced with spaces and newlines are kept, so line
+/// numbers and columns of the remaining content are unchanged. Comment markers
+/// inside string literals (e.g. `"https://..."`) are left alone.
+fn strip_json_comments(content: &str) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut chars = content.chars().peekable();
+    let mut in_string: Option<char> = None;
+    let mut escaped = false;
+
+    while let Some(c) = chars.next() {
+        if let Some(quote) = in_string {
+            out.push(c);
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == quote || c == '\n' {
+                in_string = None;
+            }
+            continue;
+        }
+
+        match (c, chars.peek()) {
+            ('"', _) | ('\'', _) => {
+                in_string = Some(c);
+                out.push(c);
+            }
+            ('/', Some('/')) => {
+                // Line comment: blank until end of line
+                out.push(' ');
+                while let Some(&next) = chars.peek() {
+                    if next == '\n' {
+                        break;
+                    }
+                    out.push(' ');
+                    chars.next();
+                }
+            }
+            ('/', Some('*')) => {
+                // Block comment: blank until */, keeping newlines
+                chars.next();
+                out.push_str("  ");
+                let mut prev = '\0';
+                for next in chars.by_ref() {
+                    out.push(if next == '\n' { '\n' } else { ' ' });
+                    if prev == '*' && next == '/' {
+                        break;
+                    }
+                    prev = next;
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+
+    out
+}
+
+/// Rendered documentation (mkdocs / docusaurus output) whose code blocks are extracted before scanning
+fn is_html_file(path: &Path) -> bool {
+    matches!(
+        path.extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_lowercase()
+            .as_str(),
+        "html" | "htm"
+    )
+}
+
+/// HTML files larger than this are not scanned (generated search indexes, bundled pages)
+const MAX_HTML_BYTES: usize = 5 * 1024 * 1024;
+
+/// Elements whose text is scanned in HTML pages
+const HTML_CODE_TAGS: &[&str] = &["pre", "code"];
+
+/// Elements whose content is never text (`<` inside them is not a tag)
+const HTML_RAW_TAGS: &[&str] = &["script", "style"];
+
+/// HTML character reference - matches &quot; &#39; &#x2F;
+static HTML_ENTITY: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"&(#[0-9]{1,7}|#[xX][0-9a-fA-F]{1,6}|[a-zA-Z]+);")
+        .expect("Invalid HTML_ENTITY regex")
+});
+
+/// Keep only the text of `<pre>` / `<code>` blocks in an HTML page.
+///
+/// Tags are dropped, everything outside code blocks (navigation, scripts,
+/// prose) is blanked and entities are decoded. Every newline is kept, so
+/// findings keep the line numbers of the HTML file.
+fn extract_html_code(content: &str) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut depth = 0usize;
+    let mut rest = content;
+
+    while let Some(c) = rest.chars().next() {
+        if c != '<' {
+            if depth > 0 || c == '\n' {
+                out.push(c);
+            }
+            rest = &rest[c.len_utf8()..];
+            continue;
+        }
+        // A tag (possibly spanning lines): only its newlines survive
+        let end = rest.find('>').map_or(rest.len(), |i| i + 1);
+        let tag = &rest[..end];
+        out.extend(tag.chars().filter(|&ch| ch == '\n'));
+        rest = &rest[end..];
+
+        let (closing, tag_body) = match tag[1..].strip_prefix('/') {
+            Some(body) => (true, body),
+            None => (false, &tag[1..]),
+        };
+        let name: String = tag_body.chars()
+            .take_while(|ch| ch.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_ascii_lowercase();
+        if HTML_CODE_TAGS.contains(&name.as_str()) {
+            if closing {
+                depth = depth.saturating_sub(1);
+            } else if !tag.ends_with("/>") {
+                depth += 1;
+            }
+        } else if HTML_RAW_TAGS.contains(&name.as_str()) && !closing {
+            // Skip to the closing tag, keeping line breaks
+            let close = format!("</{}", name);
+            let skipped = rest.as_bytes()
+                .windows(close.len())
+                .position(|w| w.eq_ignore_ascii_case(close.as_bytes()))
+                .unwrap_or(rest.len());
+            out.extend(rest[..skipped].chars().filter(|&ch| ch == '\n'));
+            rest = &rest[skipped..];
+        }
+    }
+
+    decode_html_entities(&out)
+}
+
+/// Decode HTML character references (`&quot;`, `&#39;`, `&#x2F;`); unknown names are left as-is
+fn decode_html_entities(text: &str) -> String {
+    HTML_ENTITY.replace_all(text, |caps: &regex::Captures| {
+        let entity = &caps[1];
+        let decoded = match entity.strip_prefix('#') {
+            Some(num) => match num.strip_prefix(['x', 'X']) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                None => num.parse().ok(),
+            }
+            .and_then(char::from_u32)
+            .filter(|&ch| ch != '\n'),
+            None => match entity {
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "nbsp" => Some(' '),
+                _ => None,
+            },
+        };
+        decoded.map_or_else(|| caps[0].to_string(), String::from)
+    }).into_owned()
+}
+
+/// Keep only the code of a Markdown document: fenced blocks and inline code spans.
+///
+/// Lines of a ``` / ~~~ block are kept whole, and so are the fence lines
+/// themselves (`scan_content` follows them to tell commands from prose).
+/// Other lines keep only their inline code spans, so badge URLs, links and
+/// prose are blanked. Every newline is kept, so findings keep the line
+/// numbers of the Markdown file.
+fn extract_markdown_code(content: &str) -> String {
+    let mut out = Vec::new();
+    // Marker character and length of the open fence
+    let mut fence: Option<(char, usize)> = None;
+    for line in content.split('\n') {
+        let trimmed = line.trim_start();
+        let marker = trimmed.chars().next().filter(|c| matches!(c, '`' | '~'));
+        let run = marker.map_or(0, |c| trimmed.len() - trimmed.trim_start_matches(c).len());
+        match (fence, marker) {
+            (None, Some(c)) if run >= 3 => {
+                fence = Some((c, run));
+                out.push(line.to_string());
+            }
+            (Some((open, len)), Some(c)) if c == open && run >= len && trimmed[run..].trim().is_empty() => {
+                fence = None;
+                out.push(line.to_string());
+            }
+            (Some(_), _) => out.push(line.to_string()),
+            (None, _) => {
+                let mut kept = inline_code_spans(line);
+                kept.extend(build_page_links(line));
+                out.push(kept.join(" "))
+            }
+        }
+    }
+    out.join("\n")
+}
+
+/// Build Page links in Markdown prose: they name the model a README points
+/// readers to. A link wrapped around a badge image (`[![..](..)](link)`) is
+/// decoration and left out.
+fn build_page_links(line: &str) -> impl Iterator<Item = &str> {
+    BUILD_PAGE_URL.find_iter(line)
+        .filter(move |m| !line[..m.start()].ends_with(")]("))
+        .map(|m| m.as_str())
+}
+
+/// Contents of the `code` spans of a Markdown line (`` ``a `b` c`` `` included)
+fn inline_code_spans(line: &str) -> Vec<&str> {
+    let mut spans = Vec::new();
+    let mut rest = line;
+    while let Some(start) = rest.find('`') {
+        let after_open = &rest[start..];
+        let ticks = after_open.len() - after_open.trim_start_matches('`').len();
+        let body = &after_open[ticks..];
+        // Closed by the next run of exactly as many backticks; an unclosed run is literal text
+        let mut offset = 0;
+        let mut closed = None;
+        while let Some(i) = body[offset..].find('`') {
+            let at = offset + i;
+            let run = body[at..].len() - body[at..].trim_start_matches('`').len();
+            if run == ticks {
+                closed = Some(at);
+                break;
+            }
+            offset = at + run;
+        }
+        match closed {
+            Some(end) => {
+                spans.push(body[..end].trim());
+                rest = &body[end + ticks..];
+            }
+            None => rest = body,
+        }
+    }
+    spans
+}
+
+// ---------------------------------------------------------------------------
+// Build API catalog (blueprints): scripts/generate_repos_from_ngc.py uses
+// /v2/blueprints/ and /v2/blueprints/{orgName}/{name}/spec for --refresh-repos.
+// This constant is the legacy resources API URL (kept for reference). Do not use
+// for publisher whitelist; use PUBLISHER_FILTERS_API_URL below.
+// ---------------------------------------------------------------------------
+#[allow(dead_code)]
+const BUILD_CATALOG_RESOURCES_API_URL: &str = "https://api.ngc.nvidia.com/v2/search/catalog/resources/ENDPOINT";
+
+/// NGC catalog filters API: used only to fetch the publisher whitelist for Hosted NIM detection.
+/// Decoded query: {"filters":[],"orderBy":[{"field":"score","value":"DESC"}],"page":0,"pageSize":1000,"query":"orgName:\"qc69jvmznzxy\"","scoredSize":1000}
+/// Response is an array of { filterCategory, filterValues: [{ filterValue, displayName?, ... }] }; we use only filterValue for publisher (e.g. nvidia, meta, deepseek_ai).
+const PUBLISHER_FILTERS_API_URL: &str = "https://api.ngc.nvidia.com/v2/search/catalog/filters/ENDPOINT?q=%7B%22filters%22%3A%5B%5D%2C%22orderBy%22%3A%5B%7B%22field%22%3A%22score%22%2C%22value%22%3A%22DESC%22%7D%5D%2C%22page%22%3A0%2C%22pageSize%22%3A1000%2C%22query%22%3A%22orgName%3A%5C%22qc69jvmznzxy%5C%22%22%2C%22scoredSize%22%3A1000%7D";
+
+static PUBLISHER_WHITELIST: Lazy<HashSet<String>> = Lazy::new(|| {
+    match fetch_publishers_from_filters_api() {
+        Ok(set) if !set.is_empty() => {
+            info!("Loaded {} publishers from NGC catalog filters API", set.len());
+            set
+        }
+        Ok(_) => {
+            warn!("NGC filters API returned no publishers, using fallback list");
+            default_publisher_whitelist()
+        }
+        Err(err) => {
+            warn!("Failed to load publishers from NGC filters API: {}", err);
+            default_publisher_whitelist()
+        }
+    }
+});
+
+fn default_publisher_whitelist() -> HashSet<String> {
+    [
+        "nvidia",
         "meta",
         "mistralai",
         "google",
@@ -243,12 +1310,303 @@ fn model_is_whitelisted(model: &str) -> bool {
     PUBLISHER_WHITELIST.contains(&prefix)
 }
 
-fn find_endpoint_in_context(lines: &[&str], current_line: usize, range: usize) -> Option<String> {
+/// Literals at least one of which every detection rule needs (case-insensitive).
+/// Model-name rules only count whitelisted `<org>/` names, so the publisher
+/// whitelist is added to these at runtime (see PREFILTER).
+const PREFILTER_ANCHORS: &[&str] = &[
+    "nvcr.io/nim/",        // Local NIM images
+    "api.nvidia.com",      // integrate/ai/build endpoints (and stg.api)
+    "api.stg.nvidia.com",  // staging endpoints
+    "api.nvcf.nvidia.com", // direct NVCF invocation
+    "function_id",         // NVCF function ID assignments
+    "ngc registry",        // NGC CLI pulls (nim/<team>/<name>, or --org nim)
+    "nemotron-",           // bare model names (BARE_MODEL_SHAPE)
+    "llama-",
+];
+
+/// Byte-level prefilter: matches if a file contains any anchor or `<publisher>/`
+static PREFILTER: Lazy<regex::bytes::Regex> = Lazy::new(|| {
+    let mut publishers: Vec<&String> = PUBLISHER_WHITELIST.iter().collect();
+    publishers.sort();
+    let alternatives: Vec<String> = PREFILTER_ANCHORS
+        .iter()
+        .map(|a| regex::escape(a))
+        .chain(KNOWN_BARE_MODELS.iter().map(|name| regex::escape(name)))
+        .chain(publishers.iter().map(|p| format!(r"{}\s*/", regex::escape(p))))
+        .collect();
+    regex::bytes::Regex::new(&format!("(?i){}", alternatives.join("|")))
+        .expect("Invalid PREFILTER regex")
+});
+
+/// Whether content may hold a finding: an anchor literal, a `scan.bare_models` name or a configured Hosted NIM host
+fn passes_prefilter(bytes: &[u8], opts: &ScanOptions) -> bool {
+    PREFILTER.is_match(bytes)
+        || opts.bare_models.iter()
+            .map(String::as_str)
+            .chain(opts.hosted_hosts.extra_domains())
+            .filter(|name| !name.is_empty())
+            .any(|name| bytes.windows(name.len()).any(|w| w.eq_ignore_ascii_case(name.as_bytes())))
+}
+
+/// OpenAI-compatible routes, matched against the end of the path after the version segment
+const OPENAI_ROUTES: &[&str] = &["chat/completions", "completions", "embeddings", "models"];
+
+/// Invoke style of a Hosted NIM from its endpoint URL: NVCF invocation, an
+/// OpenAI-compatible route (or bare `/v1` base URL), or a per-model path
+pub fn classify_invoke_style(endpoint_url: Option<&str>) -> InvokeStyle {
+    let Some(url) = endpoint_url else {
+        return InvokeStyle::Unknown;
+    };
+    if NVCF_ENDPOINT.is_match(url) {
+        return InvokeStyle::NvcfPexec;
+    }
+    let Some(rest) = url.strip_prefix("https://") else {
+        return InvokeStyle::Unknown;
+    };
+
+    // Path segments after the host, without query string / fragment or trailing punctuation
+    let rest = rest.split(['?', '#']).next().unwrap_or("");
+    let mut parts: Vec<&str> = rest.split('/').skip(1).filter(|p| !p.is_empty()).collect();
+    if let Some(last) = parts.last_mut() {
+        *last = last.trim_end_matches(|c: char| !(c.is_ascii_alphanumeric() || "._-{}".contains(c)));
+    }
+
+    // NVIDIA API paths start with a version (v1, v2, v1beta, ...)
+    if !parts.first().is_some_and(|p| is_version_segment(p)) {
+        return InvokeStyle::Unknown;
+    }
+    while parts.first().is_some_and(|p| is_version_segment(p)) {
+        parts.remove(0);
+    }
+
+    let path = parts.join("/");
+    if path.is_empty() || OPENAI_ROUTES.iter().any(|r| path == *r || path.ends_with(&format!("/{}", r))) {
+        InvokeStyle::OpenaiCompatible
+    } else if parts.len() >= 2 {
+        InvokeStyle::ModelPath
+    } else {
+        InvokeStyle::Unknown
+    }
+}
+
+/// `curl` run as a command (shell, Dockerfile `RUN`, Makefile recipe, subprocess string)
+static CURL_COMMAND: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?:^|[\s;&|`("'])curl\s"#).expect("Invalid CURL_COMMAND regex")
+});
+
+/// OpenAI SDK clients: `OpenAI(`, `AsyncOpenAI(`, `new OpenAI({`, `openai.ChatCompletion.create(`
+static OPENAI_SDK_CALL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\b(?:Async)?OpenAI\s*\(|\bopenai\.(?:ChatCompletion|Completion|Embedding)\.create\s*\(")
+        .expect("Invalid OPENAI_SDK_CALL regex")
+});
+
+/// HTTP client calls: Python requests / httpx / aiohttp / urllib, JS fetch / axios
+static RAW_HTTP_CALL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\b(?:requests\.(?:post|get|put|request|Session)|httpx\.(?:post|get|stream|request|Client|AsyncClient)|aiohttp\.ClientSession|urlopen|fetch|axios(?:\.(?:post|get|request|create))?)\s*\(")
+        .expect("Invalid RAW_HTTP_CALL regex")
+});
+
+/// Package names whose import marks a file as using a framework (`@langchain/...` included)
+const FRAMEWORK_IMPORTS: &[(&str, Framework)] = &[
+    ("langchain", Framework::Langchain),
+    ("llama_index", Framework::LlamaIndex),
+    ("llamaindex", Framework::LlamaIndex),
+    ("haystack", Framework::Haystack),
+];
+
+/// LangChain / LlamaIndex / Haystack import in a file (the first one, if several)
+fn imported_framework(lines: &[&str]) -> Option<Framework> {
+    lines.iter().find_map(|line| {
+        let line = line.trim_start();
+        let is_import = line.starts_with("import ") || line.starts_with("from ") || line.contains("require(");
+        if !is_import {
+            return None;
+        }
+        FRAMEWORK_IMPORTS.iter().find(|(package, _)| line.contains(package)).map(|(_, framework)| *framework)
+    })
+}
+
+/// Framework named by one line: a curl command, an OpenAI SDK client or an HTTP client call
+fn line_framework(line: &str) -> Option<Framework> {
+    if CURL_COMMAND.is_match(line) {
+        Some(Framework::Curl)
+    } else if OPENAI_SDK_CALL.is_match(line) {
+        Some(Framework::OpenaiSdk)
+    } else if RAW_HTTP_CALL.is_match(line) {
+        Some(Framework::RawHttp)
+    } else {
+        None
+    }
+}
+
+/// Framework a Hosted NIM is called through
+///
+/// The LangChain NVIDIA classes decide by themselves. Otherwise the shell
+/// command the match is part of (continued with `\`), then the enclosing call,
+/// then lines within `range / 2` (an endpoint assigned just before
+/// `requests.post(url, ...)`) are searched for curl, an OpenAI SDK client or
+/// an HTTP client call, nearest line first; failing that, the file's
+/// LangChain, LlamaIndex or Haystack import tells.
+fn classify_framework(
+    rule: &str,
+    lines: &[&str],
+    current_line: usize,
+    range: usize,
+    imported: Option<Framework>,
+) -> Framework {
+    if matches!(rule, "chatnvidia" | "nvidia_embeddings" | "nvidia_rerank") {
+        return Framework::Langchain;
+    }
+    let by_distance = |start: usize, end: usize| {
+        let mut indices: Vec<usize> = (start..=end).collect();
+        indices.sort_by_key(|&i| (i.abs_diff(current_line), i));
+        indices
+    };
+    let (start, end) = call_window(lines, current_line, range);
+    let nearby_start = current_line.saturating_sub(range / 2);
+    let nearby_end = (current_line + range / 2).min(lines.len().saturating_sub(1));
+    line_framework(&continued_command(lines, current_line))
+        .or_else(|| {
+            by_distance(start, end).into_iter()
+                .chain(by_distance(nearby_start, nearby_end))
+                .find_map(|i| line_framework(lines[i]))
+        })
+        .or(imported)
+        .unwrap_or_default()
+}
+
+/// File-type hints for [`classify_local_intent`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IntentHints {
+    /// YAML / JSON manifest (or a code block in docs), where an `image:` key deploys the image
+    pub manifest: bool,
+    /// Documentation prose outside any code block, which only ever mentions an image
+    pub doc_prose: bool,
+}
+
+/// Usage intent of a Local NIM from the line it was found on
+///
+/// Strongest signal wins: a `FROM` line builds on the image, a `docker run`
+/// starts it (even after a `docker pull` on the same line), a pull only
+/// downloads it and an `image:` key in a manifest deploys it. Anything else,
+/// and any mention in doc prose, is a `reference`.
+pub fn classify_local_intent(line: &str, hints: IntentHints) -> LocalIntent {
+    if hints.doc_prose {
+        LocalIntent::Reference
+    } else if INTENT_BASE_IMAGE.is_match(line) {
+        LocalIntent::BaseImage
+    } else if INTENT_RUN.is_match(line) {
+        LocalIntent::Run
+    } else if INTENT_PULL.is_match(line) {
+        LocalIntent::Pull
+    } else if hints.manifest && INTENT_DEPLOY_KEY.is_match(line) {
+        LocalIntent::Deploy
+    } else {
+        LocalIntent::Reference
+    }
+}
+
+/// Usage kind of a Local NIM finding, from its intent and file kind and the lines around it
+///
+/// A base image is a Dockerfile `FROM`; a run is `kubectl run` (a Kubernetes
+/// container) or any other engine's run. In a CI pipeline, an image under a
+/// `services:` or `container:` key, or on an `image:` key, runs as a CI
+/// service. Another deployed image is a Compose service or a Kubernetes
+/// container by the file it is in, or, in a docs code block or plain YAML, by
+/// an enclosing `services:` or `containers:` key. Pulls and mentions are `other`.
+pub fn classify_usage_kind(lines: &[&str], line_idx: usize, m: &LocalNimMatch) -> UsageKind {
+    if line_idx >= lines.len() {
+        return UsageKind::Other;
+    }
+    let keys = || yaml_key_path(lines, line_idx);
+    match m.intent {
+        LocalIntent::BaseImage => UsageKind::DockerfileFrom,
+        LocalIntent::Run if USAGE_KUBECTL_RUN.is_match(&continued_command(lines, line_idx)) => UsageKind::K8sContainer,
+        LocalIntent::Run => UsageKind::DockerRun,
+        LocalIntent::Deploy | LocalIntent::Reference if m.file_kind == FileKind::Ci => {
+            let keys = keys();
+            let on_image_key = keys.first().is_some_and(|key| matches!(*key, "image" | "container"));
+            match on_image_key || keys.iter().any(|key| matches!(*key, "services" | "container")) {
+                true => UsageKind::CiService,
+                false => UsageKind::Other,
+            }
+        }
+        LocalIntent::Deploy => match m.file_kind {
+            FileKind::Compose => UsageKind::ComposeImage,
+            FileKind::Kubernetes | FileKind::Helm => UsageKind::K8sContainer,
+            _ if m.compose.is_some() => UsageKind::ComposeImage,
+            _ => {
+                let keys = keys();
+                if keys.iter().any(|key| matches!(*key, "containers" | "initContainers")) {
+                    UsageKind::K8sContainer
+                } else if keys.last() == Some(&"services") {
+                    UsageKind::ComposeImage
+                } else {
+                    UsageKind::Other
+                }
+            }
+        },
+        _ => UsageKind::Other,
+    }
+}
+
+/// YAML keys from a line up to the top level: the line's own key (if any), then
+/// the key of each enclosing mapping, found by indentation
+///
+/// A `- key:` list item counts as indented past its dash, so a compact list
+/// (`services:` directly followed by `- name: ...`) still nests under its key.
+fn yaml_key_path<'a>(lines: &[&'a str], line_idx: usize) -> Vec<&'a str> {
+    let key_indent = |line: &str, caps: &regex::Captures| {
+        let indent = line.len() - line.trim_start().len();
+        indent + caps.get(1).map_or(0, |dash| dash.len())
+    };
+    let mut keys = Vec::new();
+    let line = lines[line_idx];
+    let mut threshold = match YAML_LINE_KEY.captures(line) {
+        Some(caps) => {
+            keys.push(caps.get(2).map_or("", |key| key.as_str()));
+            key_indent(line, &caps)
+        }
+        None => line.len() - line.trim_start().len(),
+    };
+    for line in lines[..line_idx].iter().rev() {
+        if threshold == 0 {
+            break;
+        }
+        let Some(caps) = YAML_LINE_KEY.captures(line) else {
+            continue;
+        };
+        let indent = key_indent(line, &caps);
+        if indent < threshold {
+            keys.push(caps.get(2).map_or("", |key| key.as_str()));
+            threshold = indent;
+        }
+    }
+    keys
+}
+
+/// Shell continuation lines (ending in `\`) looked back over for a command's verb
+const MAX_CONTINUATION_LINES: usize = 20;
+
+/// A line joined with the shell continuation lines before it, so the image on
+/// the last line of a multi-line `docker run \` still sees its command
+fn continued_command(lines: &[&str], current_line: usize) -> String {
+    let mut start = current_line;
+    while start > 0
+        && current_line - start < MAX_CONTINUATION_LINES
+        && lines[start - 1].trim_end().ends_with('\\')
+    {
+        start -= 1;
+    }
+    lines[start..=current_line].join(" ")
+}
+
+fn find_endpoint_in_context(lines: &[&str], current_line: usize, range: usize, hosts: &HostedHosts) -> Option<String> {
     let start = current_line.saturating_sub(range);
     let end = (current_line + range + 1).min(lines.len());
     for i in start..end {
         if let Some(line) = lines.get(i) {
-            if let Some(m) = HOSTED_ENDPOINT.find(line) {
+            if let Some(m) = hosts.find(line) {
                 return Some(m.as_str().to_string());
             }
         }
@@ -260,55 +1618,282 @@ fn find_endpoint_in_context(lines: &[&str], current_line: usize, range: usize) -
 // Extraction Functions
 // ============================================================================
 
+/// Drop the punctuation a sentence adds after an image or tag (`model.`, `1.0.0.`, `_1.0_`)
+fn trim_sentence_punctuation(captured: &str) -> &str {
+    captured.trim_end_matches(['.', '-', '_'])
+}
+
+/// Whether a captured tag can't be a real one: an English stop-word, too
+/// long, or not starting with a letter, digit or underscore
+fn is_prose_tag(tag: &str) -> bool {
+    tag.len() > MAX_TAG_LEN
+        || !tag.starts_with(|c: char| c.is_ascii_alphanumeric() || c == '_')
+        || (tag.chars().all(|c| c.is_ascii_alphabetic()) && TAG_STOP_WORDS.contains(&tag.to_ascii_lowercase().as_str()))
+}
+
 /// Extract Local NIM reference from a line
 fn extract_local_nim(
     line: &str,
     line_number: usize,
     file_path: &str,
     repository: &str,
+    context_limit: Option<usize>,
 ) -> Option<LocalNimMatch> {
     // Try full pattern with tag first
     if let Some(caps) = LOCAL_NIM_FULL.captures(line) {
-        let namespace_name = caps.get(1).map(|m| m.as_str()).unwrap_or("");
-        let tag = caps.get(2).map(|m| m.as_str()).unwrap_or("latest");
+        let namespace_name = trim_sentence_punctuation(caps.get(1).map(|m| m.as_str()).unwrap_or(""));
+        let tag = trim_sentence_punctuation(caps.get(2).map(|m| m.as_str()).unwrap_or("latest"));
+        // A "tag" that is prose after a colon is no pinned version: reported untagged, as a reference
+        let (tag, detection_rule) = if tag.is_empty() {
+            ("latest", "local_nim_no_tag")
+        } else if is_prose_tag(tag) {
+            ("latest", "local_nim_prose_tag")
+        } else {
+            (tag, "local_nim_full")
+        };
+        let (match_context, context_truncated) = bounded_context(line, capture_span(&caps), context_limit);
         
         return Some(LocalNimMatch {
             repository: repository.to_string(),
             image_url: format!("nvcr.io/nim/{}", namespace_name),
             tag: tag.to_string(),
             resolved_tag: None,
+            tag_exists: None,
             file_path: file_path.to_string(),
             line_number,
-            match_context: line.trim().to_string(),
+            match_context,
+            policy: None,
+            enrichment_error: None,
+            enriched: false,
+            detection_rule: detection_rule.to_string(),
+            workflow_triggers: Vec::new(),
+            nim_identity: String::new(),
+            enrichment_skipped: None,
+            context_truncated,
+            compose: None,
+            intent: LocalIntent::Reference,
+            usage_kind: UsageKind::Other,
+            blame: None,
+            notebook_cell: None,
+            file_kind: FileKind::Source,
+            resolved_from: None,
+            fixture: false,
+            fingerprint: String::new(),
         });
     }
     
     // Try pattern without tag
     if let Some(caps) = LOCAL_NIM_NO_TAG.captures(line) {
-        let namespace_name = caps.get(1).map(|m| m.as_str()).unwrap_or("");
+        let namespace_name = trim_sentence_punctuation(caps.get(1).map(|m| m.as_str()).unwrap_or(""));
+        let (match_context, context_truncated) = bounded_context(line, capture_span(&caps), context_limit);
         
         return Some(LocalNimMatch {
             repository: repository.to_string(),
             image_url: format!("nvcr.io/nim/{}", namespace_name),
             tag: "latest".to_string(),
             resolved_tag: None,
+            tag_exists: None,
+            file_path: file_path.to_string(),
+            line_number,
+            match_context,
+            policy: None,
+            enrichment_error: None,
+            enriched: false,
+            detection_rule: "local_nim_no_tag".to_string(),
+            workflow_triggers: Vec::new(),
+            nim_identity: String::new(),
+            enrichment_skipped: None,
+            context_truncated,
+            compose: None,
+            intent: LocalIntent::Reference,
+            usage_kind: UsageKind::Other,
+            blame: None,
+            notebook_cell: None,
+            file_kind: FileKind::Source,
+            resolved_from: None,
+            fixture: false,
+            fingerprint: String::new(),
+        });
+    }
+    
+    // Tag from a variable that wasn't resolved: its default, else the reference as written
+    // (a Make or just variable has no default syntax: `latest`, noted in the context)
+    if let Some(caps) = LOCAL_NIM_VAR_TAG.captures(line) {
+        let namespace_name = caps.get(1).map(|m| m.as_str()).unwrap_or("");
+        let recipe_reference = caps.get(4).map(|m| m.as_str());
+        let tag = match recipe_reference {
+            Some(_) => "latest",
+            None => caps.get(3).or(caps.get(2)).map(|m| m.as_str()).unwrap_or("latest"),
+        };
+        let (mut match_context, context_truncated) = bounded_context(line, capture_span(&caps), context_limit);
+        if let Some(reference) = recipe_reference {
+            match_context.push_str(&format!(" (tag {} not assigned in this file)", reference));
+        }
+        
+        return Some(LocalNimMatch {
+            repository: repository.to_string(),
+            image_url: format!("nvcr.io/nim/{}", namespace_name),
+            tag: tag.to_string(),
+            resolved_tag: None,
+            tag_exists: None,
+            file_path: file_path.to_string(),
+            line_number,
+            match_context,
+            policy: None,
+            enrichment_error: None,
+            enriched: false,
+            detection_rule: "local_nim_unresolved_var".to_string(),
+            workflow_triggers: Vec::new(),
+            nim_identity: String::new(),
+            enrichment_skipped: None,
+            context_truncated,
+            compose: None,
+            intent: LocalIntent::Reference,
+            usage_kind: UsageKind::Other,
+            blame: None,
+            notebook_cell: None,
+            file_kind: FileKind::Source,
+            resolved_from: None,
+            fixture: false,
+            fingerprint: String::new(),
+        });
+    }
+    
+    extract_ngc_cli(line, line_number, file_path, repository, context_limit)
+}
+
+/// NIM path of an NGC CLI command: no nvcr.io prefix, the org is the path's first segment or `--org`
+fn extract_ngc_cli(
+    line: &str,
+    line_number: usize,
+    file_path: &str,
+    repository: &str,
+    context_limit: Option<usize>,
+) -> Option<LocalNimMatch> {
+    if let Some(caps) = NGC_CLI_COMMAND.captures(line) {
+        let (namespace_name, tag) = ngc_cli_target(caps.get(2).map_or("", |m| m.as_str()))?;
+        let detection_rule = match caps.get(1).is_some_and(|m| m.as_str().starts_with("resource")) {
+            true => "ngc_resource_download",
+            false => "local_nim_ngc_cli",
+        };
+        let span = caps.get(0).map_or(0..0, |m| m.range());
+        let (match_context, context_truncated) = bounded_context(line, span, context_limit);
+        
+        return Some(LocalNimMatch {
+            repository: repository.to_string(),
+            image_url: format!("nvcr.io/nim/{}", namespace_name),
+            tag: tag.unwrap_or_else(|| "latest".to_string()),
+            resolved_tag: None,
+            tag_exists: None,
             file_path: file_path.to_string(),
             line_number,
-            match_context: line.trim().to_string(),
+            match_context,
+            policy: None,
+            enrichment_error: None,
+            enriched: false,
+            detection_rule: detection_rule.to_string(),
+            workflow_triggers: Vec::new(),
+            nim_identity: String::new(),
+            enrichment_skipped: None,
+            context_truncated,
+            compose: None,
+            intent: LocalIntent::Reference,
+            usage_kind: UsageKind::Other,
+            blame: None,
+            notebook_cell: None,
+            file_kind: FileKind::Source,
+            resolved_from: None,
+            fixture: false,
+            fingerprint: String::new(),
         });
     }
     
     None
 }
 
+/// `<team>/<name>` and tag of the NIM path an NGC CLI command's arguments name
+///
+/// The path is `nim/<team>/<name>[:<tag>]`, or `<team>/<name>` with `--org nim`,
+/// or `<name>` with `--org nim --team <team>`. None for another org, or when
+/// the arguments name no path.
+fn ngc_cli_target(args: &str) -> Option<(String, Option<String>)> {
+    let unquote = |s: &str| s.trim_end_matches([';', ')']).trim_matches(['"', '\'', '`']).to_string();
+    let (mut org, mut team, mut target) = (None, None, None);
+    let mut tokens = args.split_whitespace();
+    while let Some(token) = tokens.next() {
+        if matches!(token, "&&" | "||" | "|" | ";") || token.starts_with('#') {
+            break;
+        }
+        // Line continuation of a command joined over several lines
+        if token == "\\" {
+            continue;
+        }
+        let token = unquote(token);
+        if let Some(option) = token.strip_prefix("--") {
+            let (name, value) = match option.split_once('=') {
+                Some((name, value)) => (name, Some(unquote(value))),
+                None if NGC_CLI_VALUE_OPTIONS.contains(&token.as_str()) => (option, tokens.next().map(unquote)),
+                None => (option, None),
+            };
+            match name {
+                "org" => org = value,
+                "team" => team = value,
+                _ => {}
+            }
+        } else if !token.starts_with('-') && target.is_none() {
+            target = Some(token.strip_prefix("nvcr.io/").unwrap_or(&token).to_string());
+        }
+    }
+    let target = target?;
+    let (path, tag) = match target.rsplit_once(':') {
+        Some((path, tag)) if !path.is_empty() && !tag.contains('/') => (path, Some(tag)),
+        _ => (target.as_str(), None),
+    };
+    let segments: Vec<&str> = path.split('/').collect();
+    let org_nim = org.as_deref() == Some("nim");
+    let (team, name) = match segments[..] {
+        ["nim", team, name] => (team.to_string(), name.to_string()),
+        [team, name] if org_nim => (team.to_string(), name.to_string()),
+        [name] if org_nim => (team?, name.to_string()),
+        _ => return None,
+    };
+    let valid = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'));
+    if !valid(&team) || !valid(&name) {
+        return None;
+    }
+    let tag = tag.map(trim_sentence_punctuation).filter(|t| valid(t)).map(str::to_string);
+    Some((format!("{}/{}", team, name), tag))
+}
+
+/// NGC CLI command continued over lines that ends on `current_line`, when its
+/// first line names no path by itself (`ngc registry image pull --org nim \`)
+fn extract_continued_ngc_cli(
+    lines: &[&str],
+    current_line: usize,
+    file_path: &str,
+    repository: &str,
+    context_limit: Option<usize>,
+) -> Option<LocalNimMatch> {
+    if lines[current_line].trim_end().ends_with('\\') {
+        return None;
+    }
+    let start = (0..current_line).rev()
+        .take_while(|&i| lines[i].trim_end().ends_with('\\'))
+        .last()?;
+    let command = continued_command(lines, current_line);
+    let first = lines[start..current_line].iter().find(|line| NGC_CLI_COMMAND.is_match(line))?;
+    if extract_ngc_cli(first, 0, file_path, repository, None).is_some() {
+        return None;
+    }
+    extract_ngc_cli(&command, current_line + 1, file_path, repository, context_limit)
+}
+
 /// Find tag in surrounding lines (for YAML context)
 fn find_tag_in_context(lines: &[&str], current_line: usize, range: usize) -> Option<String> {
-    let tag_re = regex::Regex::new(r#"tag\s*[:=]\s*["']?([a-zA-Z0-9._-]+)["']?"#).ok()?;
-
     let end = (current_line + range).min(lines.len());
     for i in (current_line + 1)..end {
         if let Some(line) = lines.get(i) {
-            if let Some(caps) = tag_re.captures(line) {
+            if let Some(caps) = YAML_CONTEXT_TAG.captures(line) {
                 if let Some(tag) = caps.get(1) {
                     return Some(tag.as_str().to_string());
                 }
@@ -319,173 +1904,637 @@ fn find_tag_in_context(lines: &[&str], current_line: usize, range: usize) -> Opt
     None
 }
 
-/// Extract Hosted NIM references from a line
-fn extract_hosted_nim(
-    line: &str,
-    line_number: usize,
-    file_path: &str,
-    repository: &str,
-) -> Vec<HostedNimMatch> {
-    let mut matches = Vec::new();
-    
-    // Extract endpoint URL
-    let endpoint = HOSTED_ENDPOINT.find(line).map(|m| m.as_str().to_string());
-    
-    // Extract model name from various patterns
-    let mut model_name: Option<String> = None;
-    
-    if let Some(caps) = MODEL_ASSIGN.captures(line) {
-        model_name = caps.get(1).map(|m| m.as_str().to_string());
-    }
-    if model_name.is_none() {
-        if let Some(caps) = MODEL_NAME_ASSIGN.captures(line) {
-            model_name = caps.get(1).map(|m| m.as_str().to_string());
-        }
-    }
-    if model_name.is_none() {
-        if let Some(caps) = CHATNVIDIA.captures(line) {
-            model_name = caps.get(1).map(|m| m.as_str().to_string());
-        }
-    }
-    
-    if model_name.is_none() {
-        if let Some(caps) = NVIDIA_EMBEDDINGS.captures(line) {
-            model_name = caps.get(1).map(|m| m.as_str().to_string());
-        }
+/// Find a model name on a line, trying the model patterns in priority order
+///
+/// Returns the detection rule (the pattern's name) with the model.
+/// `KEY=org/model` dotenv assignments are only tried when `dotenv` is set.
+fn find_model_name(line: &str, dotenv: bool) -> Option<(&'static str, String)> {
+    let capture = |rule: &'static str, re: &Regex| {
+        re.captures(line)
+            .and_then(|caps| caps.get(1))
+            .map(|m| (rule, m.as_str().to_string()))
+    };
+    let single: [(&'static str, &Regex); 7] = [
+        ("model_assign", &MODEL_ASSIGN),
+        ("model_name_assign", &MODEL_NAME_ASSIGN),
+        ("chatnvidia", &CHATNVIDIA),
+        ("nvidia_embeddings", &NVIDIA_EMBEDDINGS),
+        ("nvidia_rerank", &NVIDIA_RERANK),
+        ("annotated_model_assign", &ANNOTATED_MODEL_ASSIGN),
+        ("pydantic_field_default", &PYDANTIC_FIELD_DEFAULT),
+    ];
+    if let Some(found) = single.into_iter().find_map(|(rule, re)| capture(rule, re)) {
+        return Some(found);
     }
-    
-    if model_name.is_none() {
-        if let Some(caps) = NVIDIA_RERANK.captures(line) {
-            model_name = caps.get(1).map(|m| m.as_str().to_string());
+    if let Some(caps) = BUILD_PAGE_URL.captures(line) {
+        let org = caps.get(1).map(|m| m.as_str()).unwrap_or("");
+        let slug = caps.get(2).map(|m| m.as_str()).unwrap_or("");
+        if let Some(model) = catalog_model_name(org, slug) {
+            return Some(("build_page_url", model));
         }
     }
+    capture("env_or_config_model", &ENV_OR_CONFIG_MODEL)
+        .or_else(|| if dotenv { capture("dotenv_model_assign", &DOTENV_MODEL_ASSIGN) } else { None })
+}
 
-    if model_name.is_none() {
-        if let Some(caps) = BUILD_PAGE_URL.captures(line) {
-            let org = caps.get(1).map(|m| m.as_str()).unwrap_or("");
-            let model = caps.get(2).map(|m| m.as_str()).unwrap_or("");
-            if !org.is_empty() && !model.is_empty() {
-                model_name = Some(format!("{}/{}", org, model));
-            }
-        }
+/// `org/model` named by a Build Page slug
+///
+/// The catalog writes the `.` of a version as `_` (`llama-3_1-405b-instruct`,
+/// `v1_5`), so it is canonicalized like URL model names; NVCF function
+/// names spell versions with `_`, which enrichment's function matching
+/// accounts for. A trailing `.` (end of a sentence) is dropped. None for an
+/// empty org or slug.
+pub fn catalog_model_name(org: &str, slug: &str) -> Option<String> {
+    let slug = slug.trim_end_matches('.');
+    if org.is_empty() || slug.is_empty() {
+        return None;
     }
+    Some(format!("{}/{}", org, canonicalize_url_model(slug)))
+}
 
-    if model_name.is_none() {
-        if let Some(caps) = ENV_OR_CONFIG_MODEL.captures(line) {
-            model_name = caps.get(1).map(|m| m.as_str().to_string());
-        }
-    }
+/// Whether the `org/model` at `start` is the tail of a NIM image path
+/// (`nvcr.io/nim/meta/llama3-8b-instruct`, or NGC CLI's `nim/meta/...`): the
+/// Local NIM passes report it, so it is no prose mention of a hosted model
+fn in_nim_image_path(line: &str, start: usize) -> bool {
+    let before = line[..start].to_ascii_lowercase();
+    before.strip_suffix("nim/").is_some_and(|rest| {
+        !rest.ends_with(|c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    })
+}
+
+/// Extract Hosted NIM references from a line
+fn extract_hosted_nim(
+    line: &str,
+    line_number: usize,
+    file_path: &str,
+    repository: &str,
+    context_limit: Option<usize>,
+    hosts: &HostedHosts,
+) -> Vec<HostedNimMatch> {
+    let mut matches = Vec::new();
+    
+    // Extract endpoint URL (NVIDIA API or direct NVCF invocation)
+    let nvcf_caps = NVCF_ENDPOINT.captures(line);
+    let api_endpoint = hosts.find(line);
+    let endpoint = api_endpoint
+        .map(|m| m.as_str().to_string())
+        .or_else(|| nvcf_caps.as_ref().map(|c| c[0].to_string()));
+    
+    // Function ID written directly in source (pexec URL or *function_id* assignment)
+    let function_id_match = nvcf_caps
+        .as_ref()
+        .and_then(|c| c.get(1))
+        .or_else(|| FUNCTION_ID_ASSIGN.captures(line).and_then(|c| c.get(1)));
+    let function_id = function_id_match.map(|m| m.as_str().to_lowercase());
+    
+    // Extract model name from various patterns
+    let mut model = find_model_name(line, true);
     // If no explicit model name but we have an endpoint URL, try to extract model from URL path
-    if model_name.is_none() {
-        if let Some(ref url) = endpoint {
-            model_name = extract_model_from_url(url);
-        }
+    if model.is_none() {
+        model = endpoint.as_deref()
+            .and_then(extract_model_from_url)
+            .map(|name| ("endpoint_url_model", name));
     }
     // Prose in docs/comments: "for nvidia/xxx model" or "nvidia/xxxmodel" (typo)
-    if model_name.is_none() {
+    if model.is_none() {
         for caps in DOC_PROSE_ORG_MODEL.captures_iter(line) {
             if let Some(m) = caps.get(1) {
                 let mut name = m.as_str();
                 if name.ends_with("model") {
                     name = name.strip_suffix("model").unwrap_or(name);
                 }
-                if !name.is_empty() && model_is_whitelisted(name) {
+                if !name.is_empty() && !in_nim_image_path(line, m.start()) && model_is_whitelisted(name) {
+                    let (match_context, context_truncated) = bounded_context(line, m.range(), context_limit);
                     matches.push(HostedNimMatch {
                         repository: repository.to_string(),
                         endpoint_url: endpoint.clone(),
                         model_name: Some(name.to_string()),
                         file_path: file_path.to_string(),
                         line_number,
-                        match_context: line.trim().to_string(),
+                        match_context,
                         function_id: None,
                         status: None,
                         container_image: None,
+                        environment: Environment::Production,
+                        endpoint_host: None,
+                        invoke_style: InvokeStyle::Unknown,
+                        framework: Framework::Unknown,
+                        call_params: None,
+                        policy: None,
+                        model_policy: None,
+                        enrichment_error: None,
+                        enriched: false,
+                        detection_rule: "doc_prose_org_model".to_string(),
+                        workflow_triggers: Vec::new(),
+                        nim_identity: None,
+                        enrichment_skipped: None,
+                        browser_exposed: false,
+                        did_you_mean: Vec::new(),
+                        credential_source: None,
+                        context_truncated,
+                        canonical_endpoint: None,
+                        endpoint_mismatch: false,
+                        endpoint_status: None,
+                        suggested_endpoint: None,
+                        blame: None,
+                        notebook_cell: None,
+                        file_kind: FileKind::Source,
+                        resolved_from: None,
+                        fixture: false,
+                        fingerprint: String::new(),
                     });
                 }
             }
         }
     }
 
-    if let Some(ref name) = model_name {
-        if !model_is_whitelisted(name) {
-            model_name = None;
-        }
+    if model.as_ref().is_some_and(|(_, name)| !model_is_whitelisted(name)) {
+        model = None;
     }
+    let detection_rule = match model {
+        Some((rule, _)) => rule,
+        None if api_endpoint.is_some() => "hosted_endpoint",
+        None if nvcf_caps.is_some() => "nvcf_endpoint",
+        None => "function_id_assign",
+    };
+    let model_name = model.map(|(_, name)| name);
     
     // Only create a match if we found something (and we didn't already push from DOC_PROSE)
-    if (endpoint.is_some() || model_name.is_some()) && (matches.is_empty() || model_name.is_some()) {
+    if (endpoint.is_some() || model_name.is_some() || function_id.is_some()) &&
+       (matches.is_empty() || model_name.is_some()) {
+        // A long line's context is centered on the model, else the endpoint or function ID
+        let span = model_name.as_deref().and_then(|name| find_span(line, name))
+            .or_else(|| api_endpoint.map(|m| m.range()))
+            .or_else(|| nvcf_caps.as_ref().map(capture_span))
+            .or_else(|| function_id_match.map(|m| m.range()))
+            .unwrap_or_default();
+        let (match_context, context_truncated) = bounded_context(line, span, context_limit);
         matches.push(HostedNimMatch {
             repository: repository.to_string(),
             endpoint_url: endpoint,
             model_name,
             file_path: file_path.to_string(),
             line_number,
-            match_context: line.trim().to_string(),
-            function_id: None,
+            match_context,
+            function_id,
             status: None,
             container_image: None,
+            environment: Environment::Production,
+            endpoint_host: None,
+            invoke_style: InvokeStyle::Unknown,
+            framework: Framework::Unknown,
+            call_params: None,
+            policy: None,
+            model_policy: None,
+            enrichment_error: None,
+            enriched: false,
+            detection_rule: detection_rule.to_string(),
+            workflow_triggers: Vec::new(),
+            nim_identity: None,
+            enrichment_skipped: None,
+            browser_exposed: false,
+            did_you_mean: Vec::new(),
+            credential_source: None,
+            context_truncated,
+            canonical_endpoint: None,
+            endpoint_mismatch: false,
+            endpoint_status: None,
+            suggested_endpoint: None,
+            blame: None,
+            notebook_cell: None,
+            file_kind: FileKind::Source,
+            resolved_from: None,
+            fixture: false,
+            fingerprint: String::new(),
         });
     }
     
     matches
 }
 
+/// Extract a model from an env-style key in a secret manifest template
+/// (`NIM_MODEL: org/model` under ExternalSecret `target.template.data`)
+fn extract_secret_template_model(
+    line: &str,
+    line_number: usize,
+    file_path: &str,
+    repository: &str,
+    context_limit: Option<usize>,
+) -> Option<HostedNimMatch> {
+    let name = SECRET_DATA_MODEL.captures(line)?.get(1)?;
+    if !model_is_whitelisted(name.as_str()) {
+        return None;
+    }
+    let (match_context, context_truncated) = bounded_context(line, name.range(), context_limit);
+    Some(HostedNimMatch {
+        repository: repository.to_string(),
+        endpoint_url: None,
+        model_name: Some(name.as_str().to_string()),
+        file_path: file_path.to_string(),
+        line_number,
+        match_context,
+        function_id: None,
+        status: None,
+        container_image: None,
+        environment: Environment::Production,
+        endpoint_host: None,
+        invoke_style: InvokeStyle::Unknown,
+        framework: Framework::Unknown,
+        call_params: None,
+        policy: None,
+        model_policy: None,
+        enrichment_error: None,
+        enriched: false,
+        detection_rule: "secret_template_model".to_string(),
+        workflow_triggers: Vec::new(),
+        nim_identity: None,
+        enrichment_skipped: None,
+        browser_exposed: false,
+        did_you_mean: Vec::new(),
+        credential_source: None,
+        context_truncated,
+        canonical_endpoint: None,
+        endpoint_mismatch: false,
+        endpoint_status: None,
+        suggested_endpoint: None,
+        blame: None,
+        notebook_cell: None,
+        file_kind: FileKind::Source,
+        resolved_from: None,
+        fixture: false,
+        fingerprint: String::new(),
+    })
+}
+
+/// Extract a model from an env definition in next.config / vite.config
+/// (`NIM_MODEL: "org/model"` under `env:` or `define:`)
+fn extract_js_env_model(
+    line: &str,
+    line_number: usize,
+    file_path: &str,
+    repository: &str,
+    context_limit: Option<usize>,
+) -> Option<HostedNimMatch> {
+    let name = JS_ENV_MODEL.captures(line)?.get(1)?;
+    if !model_is_whitelisted(name.as_str()) {
+        return None;
+    }
+    let (match_context, context_truncated) = bounded_context(line, name.range(), context_limit);
+    Some(HostedNimMatch {
+        repository: repository.to_string(),
+        endpoint_url: None,
+        model_name: Some(name.as_str().to_string()),
+        file_path: file_path.to_string(),
+        line_number,
+        match_context,
+        function_id: None,
+        status: None,
+        container_image: None,
+        environment: Environment::Production,
+        endpoint_host: None,
+        invoke_style: InvokeStyle::Unknown,
+        framework: Framework::Unknown,
+        call_params: None,
+        policy: None,
+        model_policy: None,
+        enrichment_error: None,
+        enriched: false,
+        detection_rule: "js_env_model".to_string(),
+        workflow_triggers: Vec::new(),
+        nim_identity: None,
+        enrichment_skipped: None,
+        browser_exposed: false,
+        did_you_mean: Vec::new(),
+        credential_source: None,
+        context_truncated,
+        canonical_endpoint: None,
+        endpoint_mismatch: false,
+        endpoint_status: None,
+        suggested_endpoint: None,
+        blame: None,
+        notebook_cell: None,
+        file_kind: FileKind::Source,
+        resolved_from: None,
+        fixture: false,
+        fingerprint: String::new(),
+    })
+}
+
+/// Whether an org-less model name is a known NIM (KNOWN_BARE_MODELS, `extra` or BARE_MODEL_SHAPE)
+fn is_bare_model_name(name: &str, extra: &[String]) -> bool {
+    let lower = name.to_lowercase();
+    KNOWN_BARE_MODELS.contains(&lower.as_str()) || extra.contains(&lower) || BARE_MODEL_SHAPE.is_match(name)
+}
+
+/// Extract an org-less model from a `model=` assignment (`model="nemotron-4-340b-instruct"`)
+///
+/// Medium confidence: the name is a known NIM but nothing ties it to an org,
+/// so enrichment tries it under BARE_MODEL_ORGS.
+fn extract_bare_model(
+    line: &str,
+    line_number: usize,
+    file_path: &str,
+    repository: &str,
+    opts: &ScanOptions,
+) -> Option<HostedNimMatch> {
+    let name = BARE_MODEL_ASSIGN.captures(line)?.get(1)?;
+    if !is_bare_model_name(name.as_str(), &opts.bare_models) {
+        return None;
+    }
+    let (match_context, context_truncated) = bounded_context(line, name.range(), opts.max_context_chars);
+    Some(HostedNimMatch {
+        repository: repository.to_string(),
+        endpoint_url: None,
+        model_name: Some(name.as_str().to_string()),
+        file_path: file_path.to_string(),
+        line_number,
+        match_context,
+        function_id: None,
+        status: None,
+        container_image: None,
+        environment: Environment::Production,
+        endpoint_host: None,
+        invoke_style: InvokeStyle::Unknown,
+        framework: Framework::Unknown,
+        call_params: None,
+        policy: None,
+        model_policy: None,
+        enrichment_error: None,
+        enriched: false,
+        detection_rule: "bare_model_assign".to_string(),
+        workflow_triggers: Vec::new(),
+        nim_identity: None,
+        enrichment_skipped: None,
+        browser_exposed: false,
+        did_you_mean: Vec::new(),
+        credential_source: None,
+        context_truncated,
+        canonical_endpoint: None,
+        endpoint_mismatch: false,
+        endpoint_status: None,
+        suggested_endpoint: None,
+        blame: None,
+        notebook_cell: None,
+        file_kind: FileKind::Source,
+        resolved_from: None,
+        fixture: false,
+        fingerprint: String::new(),
+    })
+}
+
+/// Trailing path segments that name an action rather than a model
+/// (e.g. `.../nvidia/llama-3_2-nv-rerankqa-1b-v2/reranking`)
+const URL_ACTION_SEGMENTS: &[&str] = &[
+    "reranking", "embeddings", "completions", "chat", "infer", "generate",
+];
+
+/// Path segments that can never be an org (API categories and NVCF plumbing)
+const URL_NON_ORG_SEGMENTS: &[&str] = &[
+    "chat", "embeddings", "completions", "retrieval", "cv", "genai", "vlm",
+    "biology", "audio", "health", "models", "nvcf", "pexec", "functions", "status",
+];
+
+/// Check if a path segment is an API version (v1, v2, v1beta, v2alpha1, ...)
+fn is_version_segment(segment: &str) -> bool {
+    URL_VERSION_SEGMENT.is_match(segment)
+}
+
+/// Translate the NVCF `_`-for-`.` convention back to dotted versions (llama-3_2 -> llama-3.2)
+pub fn canonicalize_url_model(model: &str) -> String {
+    // Run twice so overlapping runs like 1_2_3 are fully converted
+    let once = DIGIT_UNDERSCORE_DIGIT.replace_all(model, "$1.$2");
+    DIGIT_UNDERSCORE_DIGIT.replace_all(&once, "$1.$2").into_owned()
+}
+
 /// Extract model name from NVIDIA API URL path
 /// 
 /// Examples:
 /// - https://ai.api.nvidia.com/v1/cv/baidu/paddleocr -> baidu/paddleocr
 /// - https://ai.api.nvidia.com/v1/cv/nvidia/nemoretriever-page-elements-v2 -> nvidia/nemoretriever-page-elements-v2
+/// - https://ai.api.nvidia.com/v1/retrieval/nvidia/llama-3_2-nv-rerankqa-1b-v2/reranking -> nvidia/llama-3.2-nv-rerankqa-1b-v2
 /// - https://integrate.api.nvidia.com/v1 -> None (no model in path)
 /// - https://ai.api.nvidia.com/v1/chat/completions -> None (generic endpoint)
 fn extract_model_from_url(url: &str) -> Option<String> {
     // Parse URL to get path
-    let path = url.strip_prefix("https://")
+    let rest = url.strip_prefix("https://")
         .or_else(|| url.strip_prefix("http://"))?;
     
-    // Remove domain
-    let path = path.split('/').skip(1).collect::<Vec<_>>().join("/");
+    // Drop query string / fragment
+    let rest = rest.split(['?', '#']).next().unwrap_or("");
+    
+    // Remove domain, keep non-empty path segments
+    let mut parts: Vec<&str> = rest.split('/').skip(1).filter(|p| !p.is_empty()).collect();
+    
+    // Strip trailing punctuation picked up from surrounding code (e.g. `,` or `}`)
+    if let Some(last) = parts.last_mut() {
+        *last = last.trim_end_matches(|c: char| !(c.is_ascii_alphanumeric() || c == '.' || c == '_' || c == '-'));
+    }
+    
+    // Skip leading version segments (v1, v2, v1beta, ...)
+    while parts.first().is_some_and(|p| is_version_segment(p)) {
+        parts.remove(0);
+    }
+    
+    // Skip trailing action segments (reranking, embeddings, chat/completions, ...)
+    while parts.last().is_some_and(|p| p.is_empty() || URL_ACTION_SEGMENTS.contains(p)) {
+        parts.pop();
+    }
     
-    // Skip generic endpoints
-    if path.is_empty() || 
-       path == "v1" || 
-       path.ends_with("/chat/completions") ||
-       path.ends_with("/embeddings") ||
-       path.ends_with("/completions") {
+    // Pattern: {category}/{org}/{model} or {org}/{model}
+    if parts.len() < 2 {
         return None;
     }
+    let org = parts[parts.len() - 2];
+    let model = parts[parts.len() - 1];
     
-    // Pattern: v1/{category}/{org}/{model} or v1/{org}/{model}
-    let parts: Vec<&str> = path.split('/').collect();
+    // Both segments must be plain identifiers (rejects {placeholders} and uuids-in-query)
+    let is_ident = |s: &str| {
+        !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '_' || c == '-')
+    };
+    if !is_ident(org) || !is_ident(model) ||
+       is_version_segment(org) || URL_NON_ORG_SEGMENTS.contains(&org) {
+        return None;
+    }
     
-    // Look for org/model pattern (last two non-empty segments)
-    if parts.len() >= 4 {
-        // v1/cv/baidu/paddleocr -> baidu/paddleocr
-        // v1/cv/nvidia/nemoretriever-page-elements-v2 -> nvidia/nemoretriever-page-elements-v2
-        let org = parts[parts.len() - 2];
-        let model = parts[parts.len() - 1];
-        
-        // Validate org looks like an organization name
-        if !org.is_empty() && !model.is_empty() && 
-           org != "v1" && org != "chat" && org != "embeddings" {
-            return Some(format!("{}/{}", org, model));
+    Some(format!("{}/{}", org, canonicalize_url_model(model)))
+}
+
+// ============================================================================
+// Scan Statistics
+// ============================================================================
+
+/// Rule name for Local NIM image extraction
+pub const RULE_LOCAL_NIM: &str = "local_nim";
+/// Rule name for Hosted NIM extraction in code/config files
+pub const RULE_HOSTED_NIM: &str = "hosted_nim";
+/// Rule name for Hosted NIM extraction in doc-like files (README, notebooks, ...)
+pub const RULE_HOSTED_NIM_DOCS: &str = "hosted_nim_docs";
+
+/// Counters for one extraction rule
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct RuleStats {
+    /// Lines the rule was evaluated against
+    pub lines_evaluated: usize,
+    /// Lines that produced at least one match
+    pub lines_matched: usize,
+    /// Matches produced (a line can yield several)
+    pub matches: usize,
+}
+
+impl RuleStats {
+    fn record(&mut self, matches: usize) {
+        self.lines_evaluated += 1;
+        if matches > 0 {
+            self.lines_matched += 1;
+            self.matches += matches;
         }
     }
-    
-    None
+
+    fn add(&mut self, other: &RuleStats) {
+        self.lines_evaluated += other.lines_evaluated;
+        self.lines_matched += other.lines_matched;
+        self.matches += other.matches;
+    }
+}
+
+/// What the scanner looked at in a single file
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FileStats {
+    /// File path relative to repository root
+    pub file_path: String,
+    /// SHA-256 of the file content (None if the file couldn't be read)
+    pub sha256: Option<String>,
+    /// Number of lines scanned
+    pub lines: usize,
+    /// Skipped by the anchor prefilter (no line-by-line pass)
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub prefiltered: bool,
+    /// Skipped as a report written by this scanner (see `report::is_own_report`)
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub own_report: bool,
+    /// Git LFS pointer: the real content is not in the checkout and was not scanned
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub lfs_pointer: bool,
+    /// Skipped as a lockfile or generated code (see `ScanOptions::generated_files`); not hashed
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub generated: bool,
+    /// Per-rule counters
+    pub rules: BTreeMap<&'static str, RuleStats>,
+    /// YAML endpoints without a model that were matched against nearby `model_name` lines
+    #[serde(skip_serializing_if = "is_zero")]
+    pub context_lookups: usize,
+    /// Such endpoints past `MAX_CONTEXT_LOOKUPS_PER_FILE`, left without a model
+    #[serde(skip_serializing_if = "is_zero")]
+    pub context_lookups_skipped: usize,
+}
+
+fn is_zero(n: &usize) -> bool {
+    *n == 0
+}
+
+/// Result of scanning a repository: findings plus per-file statistics
+#[derive(Debug, Clone, Default)]
+pub struct ScanOutcome {
+    /// Local NIM matches
+    pub local: Vec<LocalNimMatch>,
+    /// Hosted NIM matches
+    pub hosted: Vec<HostedNimMatch>,
+    /// NVIDIA AI endpoint SDKs declared in dependency manifests
+    pub sdk_dependencies: Vec<SdkDependencyMatch>,
+    /// One entry per scanned file
+    pub files: Vec<FileStats>,
+    /// Directories at `max_depth` whose contents were not walked
+    pub dirs_beyond_max_depth: usize,
+    /// File collection stopped at `max_files`
+    pub file_limit_reached: bool,
+    /// Listed files that could not be scanned (`scan_files` only)
+    pub listed_file_errors: Vec<ListedFileError>,
+}
+
+impl ScanOutcome {
+    /// Number of files skipped by the anchor prefilter
+    pub fn prefiltered_files(&self) -> usize {
+        self.files.iter().filter(|f| f.prefiltered).count()
+    }
+
+    /// Number of files skipped as reports written by this scanner
+    pub fn own_reports_skipped(&self) -> usize {
+        self.files.iter().filter(|f| f.own_report).count()
+    }
+
+    /// Number of files skipped as lockfiles or generated code
+    pub fn generated_skipped(&self) -> usize {
+        self.files.iter().filter(|f| f.generated).count()
+    }
+
+    /// Paths (relative to the repository root) of files that are Git LFS pointers
+    pub fn lfs_pointer_paths(&self) -> Vec<String> {
+        self.files.iter().filter(|f| f.lfs_pointer).map(|f| f.file_path.clone()).collect()
+    }
+
+    /// Scan the LFS pointer files again (after `git lfs pull` replaced them with their content)
+    ///
+    /// Their statistics are replaced; files that are still pointers stay flagged.
+    pub fn rescan_lfs_pointers(&mut self, repo_path: &Path, repository: &str, opts: &ScanOptions) {
+        let pointers = self.lfs_pointer_paths();
+        self.files.retain(|f| !f.lfs_pointer);
+        for file_path in pointers {
+            let (local, hosted, stats) = scan_file(&repo_path.join(&file_path), repository, repo_path, opts);
+            self.local.extend(local);
+            self.hosted.extend(hosted);
+            self.files.push(stats);
+        }
+    }
+
+    /// Per-rule counters summed over all scanned files
+    pub fn rule_totals(&self) -> BTreeMap<&'static str, RuleStats> {
+        let mut totals: BTreeMap<&'static str, RuleStats> = BTreeMap::new();
+        for file in &self.files {
+            for (rule, stats) in &file.rules {
+                totals.entry(rule).or_default().add(stats);
+            }
+        }
+        totals
+    }
+
+    /// What the scan limits cut off, if anything
+    pub fn truncation(&self, repository: &str, opts: &ScanOptions) -> Option<ScanTruncation> {
+        if self.dirs_beyond_max_depth == 0 && !self.file_limit_reached {
+            return None;
+        }
+        Some(ScanTruncation {
+            repository: repository.to_string(),
+            max_depth: opts.max_depth.filter(|_| self.dirs_beyond_max_depth > 0),
+            dirs_beyond_max_depth: self.dirs_beyond_max_depth,
+            max_files: opts.max_files.filter(|_| self.file_limit_reached),
+        })
+    }
 }
 
 // ============================================================================
 // File Scanning
 // ============================================================================
 
-/// Scan a single file for NIM references
+/// First line of every Git LFS pointer file
+const LFS_POINTER_PREFIX: &[u8] = b"version https://git-lfs.github.com/spec/";
+
+/// Git LFS pointer files are always smaller than this
+const LFS_POINTER_MAX_BYTES: usize = 1024;
+
+/// Whether a file's content is a Git LFS pointer rather than the file itself
+fn is_lfs_pointer(bytes: &[u8]) -> bool {
+    bytes.len() < LFS_POINTER_MAX_BYTES && bytes.starts_with(LFS_POINTER_PREFIX)
+}
+
+/// Scan a single file for NIM references, also returning what was evaluated
 pub fn scan_file(
     path: &Path,
     repository: &str,
     repo_root: &Path,
-) -> (Vec<LocalNimMatch>, Vec<HostedNimMatch>) {
-    let mut local_matches = Vec::new();
-    let mut hosted_matches = Vec::new();
+    opts: &ScanOptions,
+) -> (Vec<LocalNimMatch>, Vec<HostedNimMatch>, FileStats) {
+    let local_matches = Vec::new();
+    let hosted_matches = Vec::new();
     
     // Get relative path
     let relative_path = path
@@ -493,405 +2542,3770 @@ pub fn scan_file(
         .unwrap_or(path)
         .to_string_lossy()
         .to_string();
+    let mut stats = FileStats {
+        file_path: relative_path.clone(),
+        ..Default::default()
+    };
     
-    // Check if this is a YAML file (needs multi-line context)
-    let is_yaml = relative_path.ends_with(".yml") || relative_path.ends_with(".yaml");
-    let is_doc_like = is_doc_like_file(path);
+    let is_html = is_html_file(path);
+    
+    // Lockfiles and generated code hold no real usage, only URLs embedded by tooling
+    if let Some(ref generated) = opts.generated_files {
+        if is_generated_file(generated, path, path.strip_prefix(repo_root).unwrap_or(path)) {
+            debug!("[{}] Skipping {}: lockfile or generated code (use --include-generated to scan it)",
+                   repository, relative_path);
+            stats.generated = true;
+            return (local_matches, hosted_matches, stats);
+        }
+    }
     
     // Open file and read all lines for context-aware scanning
-    let content = match std::fs::read_to_string(path) {
+    let bytes = match std::fs::read(path) {
+        Ok(b) => b,
+        Err(e) => {
+            warn!("[{}] Failed to read file {}: {}", repository, relative_path, e);
+            return (local_matches, hosted_matches, stats);
+        }
+    };
+    stats.sha256 = Some(format!("{:x}", Sha256::digest(&bytes)));
+    
+    // A committed report.json / report.csv lists every finding again under its own path
+    if !opts.scan_own_reports && crate::report::is_own_report(&bytes) {
+        info!("[{}] Skipping {}: report written by this scanner (use --scan-own-reports to scan it)",
+              repository, relative_path);
+        stats.own_report = true;
+        return (local_matches, hosted_matches, stats);
+    }
+    
+    // Without LFS smudging the checkout holds a pointer instead of the file's content
+    if is_lfs_pointer(&bytes) {
+        warn!("[{}] {} is a Git LFS pointer; its content was not scanned", repository, relative_path);
+        stats.lfs_pointer = true;
+        return (local_matches, hosted_matches, stats);
+    }
+    
+    if is_html && bytes.len() > MAX_HTML_BYTES {
+        debug!("[{}] Skipping {}: HTML over {} bytes", repository, relative_path, MAX_HTML_BYTES);
+        return (local_matches, hosted_matches, stats);
+    }
+    
+    // Most files contain no anchor literal at all: skip them before the line-by-line pass
+    // (HTML is checked after extraction, since entities can hide the anchors,
+    // `image: ${NIM_IMAGE}` has none until the variable is substituted, and nor
+    // has a Compose or Helm image split into `registry: nvcr.io` and `repository: nim/...`)
+    let is_structured = crate::compose::is_compose_file(path) || (opts.helm_values && is_helm_values_file(path));
+    if !opts.disable_prefilter && !is_html && !is_structured && !passes_prefilter(&bytes, opts) && !opts.variables.referenced_in(&bytes) {
+        stats.prefiltered = true;
+        return (local_matches, hosted_matches, stats);
+    }
+    
+    let content = match String::from_utf8(bytes) {
         Ok(c) => c,
         Err(e) => {
-            warn!("Failed to read file {}: {}", path.display(), e);
-            return (local_matches, hosted_matches);
+            warn!("[{}] Failed to read file {}: {}", repository, relative_path, e);
+            return (local_matches, hosted_matches, stats);
+        }
+    };
+    
+    // Notebooks: the sources of code and Markdown cells, not outputs; one that doesn't parse is scanned as text
+    if is_notebook_file(path) {
+        match Notebook::parse(&content) {
+            Ok(notebook) => {
+                let (local_matches, hosted_matches) = scan_notebook(&notebook, &relative_path, repository, opts, &mut stats);
+                stats.lines = content.lines().count();
+                return (local_matches, hosted_matches, stats);
+            }
+            Err(e) => warn!("[{}] {} is not a valid notebook ({}); scanning it as text", repository, relative_path, e),
+        }
+    }
+    
+    // JSONC/JSON5: drop comments so commented-out models aren't reported
+    let content = if is_jsonc_file(path) {
+        strip_json_comments(&content)
+    } else if is_html {
+        // Rendered docs: scan the code blocks only, with entities decoded
+        extract_html_code(&content)
+    } else if is_markdown_file(path) {
+        // Code blocks and inline code only: prose mentions and badge URLs aren't usage
+        extract_markdown_code(&content)
+    } else {
+        content
+    };
+    if (is_html || is_markdown_file(path)) && !opts.disable_prefilter && !passes_prefilter(content.as_bytes(), opts) {
+        stats.prefiltered = true;
+        return (local_matches, hosted_matches, stats);
+    }
+    
+    let (local_matches, hosted_matches) = scan_content(&content, path, &relative_path, repository, opts, &mut stats);
+    (local_matches, hosted_matches, stats)
+}
+
+/// Scan a notebook's code cells as source in the kernel's language and its
+/// Markdown cells as a README, numbering findings by the lines of the `.ipynb` file
+fn scan_notebook(
+    notebook: &Notebook,
+    relative_path: &str,
+    repository: &str,
+    opts: &ScanOptions,
+    stats: &mut FileStats,
+) -> (Vec<LocalNimMatch>, Vec<HostedNimMatch>) {
+    let mut local_matches = Vec::new();
+    let mut hosted_matches = Vec::new();
+    let documents = [
+        (&notebook.code, format!("notebook.{}", notebook.code_extension)),
+        (&notebook.markdown, "notebook.md".to_string()),
+    ];
+    for (document, scanned_as) in documents {
+        if document.text.is_empty() || (opts.skip_docs && is_markdown_file(Path::new(&scanned_as))) {
+            continue;
+        }
+        let (local, hosted) = scan_content(&document.text, Path::new(&scanned_as), relative_path, repository, opts, stats);
+        for mut m in local {
+            if let Some((line, cell)) = document.locate(m.line_number) {
+                m.line_number = line;
+                m.notebook_cell = Some(cell);
+            }
+            local_matches.push(m);
         }
+        for mut m in hosted {
+            if let Some((line, cell)) = document.locate(m.line_number) {
+                m.line_number = line;
+                m.notebook_cell = Some(cell);
+            }
+            hosted_matches.push(m);
+        }
+    }
+    // In file order, as for any other file
+    local_matches.sort_by_key(|m| m.line_number);
+    hosted_matches.sort_by_key(|m| m.line_number);
+    (local_matches, hosted_matches)
+}
+
+/// Line-by-line pass over text from `path`, already read and decoded
+///
+/// `path` only decides how the text is treated (YAML, Markdown prose, JS, ...);
+/// it is never read, so short strings from elsewhere can go through the same
+/// detection as a file.
+fn scan_content(
+    content: &str,
+    path: &Path,
+    relative_path: &str,
+    repository: &str,
+    opts: &ScanOptions,
+    stats: &mut FileStats,
+) -> (Vec<LocalNimMatch>, Vec<HostedNimMatch>) {
+    let mut local_matches = Vec::new();
+    let mut hosted_matches = Vec::new();
+    
+    let as_written = content;
+    // `${VAR}` / `$VAR` replaced by values from the repository's .env and Compose files,
+    // or in a Makefile / justfile, references to the file's own assignments
+    let recipe_syntax = path.file_name().and_then(|n| n.to_str()).and_then(RecipeSyntax::of);
+    let substituted = match recipe_syntax {
+        Some(syntax) => Variables::substitute_recipe_file(content, syntax),
+        None => opts.variables.substitute(content, relative_path),
+    };
+    let written_lines: Vec<&str> = match substituted {
+        Some(_) => content.lines().collect(),
+        None => Vec::new(),
+    };
+    let content = substituted.as_ref().map_or(content, |s| s.text.as_str());
+    let resolution = |line_num: usize, values: &[Option<&str>]| {
+        let substitution = substituted.as_ref()?.line(line_num)?;
+        let written = written_lines[line_num];
+        values.iter().flatten().any(|value| !written.contains(value)).then(|| {
+            let (context, truncated) = bounded_context(written, substitution.span.clone(), opts.max_context_chars);
+            (substitution.names.join(","), context, truncated)
+        })
     };
     
+    // Check if this is a YAML file (needs multi-line context)
+    let file_name = path.to_string_lossy();
+    let is_yaml = file_name.ends_with(".yml") || file_name.ends_with(".yaml");
+    let is_doc_like = is_doc_like_file(path);
+    let is_js = is_js_file(path);
+    let is_js_build_config = is_js_build_config(path);
+    let is_markdown = is_markdown_file(path);
+    let is_manifest = is_yaml || file_name.ends_with(".json");
+    
     let lines: Vec<&str> = content.lines().collect();
+    stats.lines = lines.len();
+    let hosted_rule = if is_doc_like { RULE_HOSTED_NIM_DOCS } else { RULE_HOSTED_NIM };
+    // ExternalSecret / SealedSecret templates hold env-style keys (NIM_MODEL: org/model)
+    let is_secret_manifest = is_yaml && SECRET_MANIFEST_KIND.is_match(content);
+    let imported_framework = imported_framework(&lines);
+    let is_python = path.extension().is_some_and(|ext| ext == "py");
+    let multiline_calls = if is_python {
+        extract_multiline_langchain(&lines, relative_path, repository, opts.max_context_chars, &opts.hosted_hosts)
+    } else {
+        Vec::new()
+    };
+    // Built on the first YAML context lookup
+    let mut context_models: Option<BTreeMap<usize, &str>> = None;
+    // Inside a fenced code block of a Markdown file
+    let mut in_code_block = false;
     
     // Scan line by line
     for (line_num, line) in lines.iter().enumerate() {
         let line_number = line_num + 1; // 1-indexed
+        if is_markdown && (line.trim_start().starts_with("```") || line.trim_start().starts_with("~~~")) {
+            in_code_block = !in_code_block;
+        }
         
         // Extract Local NIM
-        if let Some(mut m) = extract_local_nim(line, line_number, &relative_path, repository) {
+        let local = extract_local_nim(line, line_number, relative_path, repository, opts.max_context_chars)
+            .or_else(|| extract_continued_ngc_cli(&lines, line_num, relative_path, repository, opts.max_context_chars));
+        stats.rules.entry(RULE_LOCAL_NIM).or_default().record(usize::from(local.is_some()));
+        if let Some(mut m) = local {
             if is_yaml && m.tag == "latest" {
                 if let Some(tag) = find_tag_in_context(&lines, line_num, 3) {
                     m.tag = tag;
                 }
             }
-            debug!("Found Local NIM in {}:{}: {}", relative_path, line_number, m.image_url);
+            let hints = IntentHints {
+                manifest: is_manifest || in_code_block,
+                doc_prose: is_markdown && !in_code_block,
+            };
+            if m.detection_rule != "local_nim_prose_tag" {
+                m.intent = classify_local_intent(&continued_command(&lines, line_num), hints);
+            }
+            if let Some((names, context, truncated)) = resolution(line_num, &[Some(&m.image_url), Some(&m.tag)]) {
+                (m.resolved_from, m.match_context, m.context_truncated) = (Some(names), context, truncated);
+            }
+            debug!("[{}] Found Local NIM in {}:{}: {}", repository, relative_path, line_number, m.image_url);
             local_matches.push(m);
         }
         
         // Extract Hosted NIM
         let mut hosted = if is_doc_like {
             let mut matches = Vec::new();
-            let mut model_name: Option<String> = None;
+            let model = find_model_name(line, false);
 
-            if let Some(caps) = MODEL_ASSIGN.captures(line) {
-                model_name = caps.get(1).map(|m| m.as_str().to_string());
-            }
-            if model_name.is_none() {
-                if let Some(caps) = MODEL_NAME_ASSIGN.captures(line) {
-                    model_name = caps.get(1).map(|m| m.as_str().to_string());
-                }
-            }
-            if model_name.is_none() {
-                if let Some(caps) = CHATNVIDIA.captures(line) {
-                    model_name = caps.get(1).map(|m| m.as_str().to_string());
-                }
-            }
-            if model_name.is_none() {
-                if let Some(caps) = NVIDIA_EMBEDDINGS.captures(line) {
-                    model_name = caps.get(1).map(|m| m.as_str().to_string());
-                }
-            }
-            if model_name.is_none() {
-                if let Some(caps) = NVIDIA_RERANK.captures(line) {
-                    model_name = caps.get(1).map(|m| m.as_str().to_string());
-                }
-            }
-            if model_name.is_none() {
-                if let Some(caps) = BUILD_PAGE_URL.captures(line) {
-                    let org = caps.get(1).map(|m| m.as_str()).unwrap_or("");
-                    let model = caps.get(2).map(|m| m.as_str()).unwrap_or("");
-                    if !org.is_empty() && !model.is_empty() {
-                        model_name = Some(format!("{}/{}", org, model));
-                    }
-                }
-            }
-            if model_name.is_none() {
-                if let Some(caps) = ENV_OR_CONFIG_MODEL.captures(line) {
-                    model_name = caps.get(1).map(|m| m.as_str().to_string());
-                }
-            }
             // Fallback for prose in docs: "for nvidia/xxx model" or "nvidia/xxxmodel" (typo)
-            if model_name.is_none() {
+            if model.is_none() {
                 for caps in DOC_PROSE_ORG_MODEL.captures_iter(line) {
                     if let Some(m) = caps.get(1) {
                         let mut name = m.as_str();
                         if name.ends_with("model") {
                             name = name.strip_suffix("model").unwrap_or(name);
                         }
-                        if !name.is_empty() && model_is_whitelisted(name) {
-                            let endpoint = find_endpoint_in_context(&lines, line_num, 10);
+                        if !name.is_empty() && !in_nim_image_path(line, m.start()) && model_is_whitelisted(name) {
+                            let endpoint = find_endpoint_in_context(&lines, line_num, 10, &opts.hosted_hosts);
+                            let (match_context, context_truncated) = bounded_context(line, m.range(), opts.max_context_chars);
                             matches.push(HostedNimMatch {
                                 repository: repository.to_string(),
                                 endpoint_url: endpoint,
                                 model_name: Some(name.to_string()),
-                                file_path: relative_path.clone(),
+                                file_path: relative_path.to_string(),
                                 line_number,
-                                match_context: line.trim().to_string(),
+                                match_context,
                                 function_id: None,
                                 status: None,
                                 container_image: None,
+                                environment: Environment::Production,
+                                endpoint_host: None,
+                                invoke_style: InvokeStyle::Unknown,
+                                framework: Framework::Unknown,
+                                call_params: None,
+                                policy: None,
+                                model_policy: None,
+                                enrichment_error: None,
+                                enriched: false,
+                                detection_rule: "doc_prose_org_model".to_string(),
+                                workflow_triggers: Vec::new(),
+                                nim_identity: None,
+                                enrichment_skipped: None,
+                                browser_exposed: false,
+                                did_you_mean: Vec::new(),
+                                credential_source: None,
+                                context_truncated,
+                                canonical_endpoint: None,
+                                endpoint_mismatch: false,
+                                endpoint_status: None,
+                                suggested_endpoint: None,
+                                blame: None,
+                                notebook_cell: None,
+                                file_kind: FileKind::Source,
+                                resolved_from: None,
+                                fixture: false,
+                                fingerprint: String::new(),
                             });
                         }
                     }
                 }
             }
 
-            if let Some(ref name) = model_name {
-                if model_is_whitelisted(name) {
-                    let endpoint = find_endpoint_in_context(&lines, line_num, 10);
+            if let Some((rule, name)) = model {
+                if model_is_whitelisted(&name) {
+                    let endpoint = find_endpoint_in_context(&lines, line_num, 10, &opts.hosted_hosts);
+                    let span = find_span(line, &name).unwrap_or_default();
+                    let (match_context, context_truncated) = bounded_context(line, span, opts.max_context_chars);
                     matches.push(HostedNimMatch {
                         repository: repository.to_string(),
                         endpoint_url: endpoint,
-                        model_name,
-                        file_path: relative_path.clone(),
+                        model_name: Some(name),
+                        file_path: relative_path.to_string(),
                         line_number,
-                        match_context: line.trim().to_string(),
+                        match_context,
                         function_id: None,
                         status: None,
                         container_image: None,
+                        environment: Environment::Production,
+                        endpoint_host: None,
+                        invoke_style: InvokeStyle::Unknown,
+                        framework: Framework::Unknown,
+                        call_params: None,
+                        policy: None,
+                        model_policy: None,
+                        enrichment_error: None,
+                        enriched: false,
+                        detection_rule: rule.to_string(),
+                        workflow_triggers: Vec::new(),
+                        nim_identity: None,
+                        enrichment_skipped: None,
+                        browser_exposed: false,
+                        did_you_mean: Vec::new(),
+                        credential_source: None,
+                        context_truncated,
+                        canonical_endpoint: None,
+                        endpoint_mismatch: false,
+                        endpoint_status: None,
+                        suggested_endpoint: None,
+                        blame: None,
+                        notebook_cell: None,
+                        file_kind: FileKind::Source,
+                        resolved_from: None,
+                        fixture: false,
+                        fingerprint: String::new(),
                     });
                 }
             }
 
             matches
         } else {
-            extract_hosted_nim(line, line_number, &relative_path, repository)
+            let context_limit = opts.max_context_chars;
+            let mut matches = extract_hosted_nim(line, line_number, relative_path, repository, context_limit, &opts.hosted_hosts);
+            if matches.is_empty() && is_secret_manifest {
+                matches.extend(extract_secret_template_model(line, line_number, relative_path, repository, context_limit));
+            }
+            if matches.is_empty() && is_js_build_config {
+                matches.extend(extract_js_env_model(line, line_number, relative_path, repository, context_limit));
+            }
+            if matches.is_empty() {
+                matches.extend(extract_bare_model(line, line_number, relative_path, repository, opts));
+            }
+            // A constructor call spread over lines is reported once, at the constructor
+            for (span, call) in multiline_calls.iter().filter(|(span, _)| span.contains(&line_num)) {
+                matches.retain(|m| !covered_by_call(m, call));
+                if line_num == *span.start() {
+                    matches.insert(0, call.clone());
+                }
+            }
+            matches
         };
         
         // For YAML files, if we found an endpoint but no model_name, look in nearby lines
         if is_yaml && !is_doc_like {
             for m in &mut hosted {
                 if m.model_name.is_none() && m.endpoint_url.is_some() {
-                    // Look up to 10 lines before and after for model_name
-                    m.model_name = find_model_name_in_context(&lines, line_num, 10);
+                    if stats.context_lookups >= MAX_CONTEXT_LOOKUPS_PER_FILE {
+                        stats.context_lookups_skipped += 1;
+                        continue;
+                    }
+                    stats.context_lookups += 1;
+                    // Look up to 10 lines before and after for model_name, within the endpoint's block
+                    let index = context_models.get_or_insert_with(|| index_context_models(&lines));
+                    m.model_name = find_model_name_in_context(index, &lines, line_num, 10);
                     if let Some(ref name) = m.model_name {
                         if !model_is_whitelisted(name) {
                             m.model_name = None;
                         } else {
-                            debug!("Found model_name from context: {:?}", name);
+                            debug!("[{}] Found model_name from context: {:?}", repository, name);
                         }
                     }
                 }
             }
         }
         
-        for m in hosted {
-            debug!("Found Hosted NIM in {}:{}: {:?} {:?}",
-                   relative_path, line_number, m.endpoint_url, m.model_name);
-            hosted_matches.push(m);
-        }
-    }
-    
-    (local_matches, hosted_matches)
-}
-
-/// Find model_name in surrounding lines (for YAML context)
-fn find_model_name_in_context(lines: &[&str], current_line: usize, range: usize) -> Option<String> {
-    // Regex pattern for model_name in YAML
-    let model_name_re = regex::Regex::new(
-        r#"model(?:_name)?\s*[:=]\s*["']?([a-zA-Z0-9_/-]+/[a-zA-Z0-9._-]+)["']?"#
-    ).ok()?;
-    
-    // Search backwards first (model_name usually comes before base_url)
-    let start = current_line.saturating_sub(range);
-    for i in (start..current_line).rev() {
-        if let Some(line) = lines.get(i) {
-            if let Some(caps) = model_name_re.captures(line) {
-                if let Some(model) = caps.get(1) {
-                    return Some(model.as_str().to_string());
+        // JS/TS: the model sits in the request body (fetch(url, {body: JSON.stringify({model})}))
+        // or next to baseURL in the client options
+        if is_js {
+            for m in hosted.iter_mut().filter(|m| m.model_name.is_none() && m.endpoint_url.is_some()) {
+                if let Some(name) = find_js_model_in_window(&lines, line_num, 10) {
+                    debug!("[{}] Found model in JS object literal: {:?}", repository, name);
+                    m.model_name = Some(name);
+                    m.detection_rule = "js_request_model".to_string();
+                } else if JS_BASE_URL.is_match(line) {
+                    m.detection_rule = "js_base_url".to_string();
                 }
             }
         }
-    }
-    
-    // Also search forward in case model comes after
-    let end = (current_line + range).min(lines.len());
-    for i in (current_line + 1)..end {
-        if let Some(line) = lines.get(i) {
-            if let Some(caps) = model_name_re.captures(line) {
-                if let Some(model) = caps.get(1) {
-                    return Some(model.as_str().to_string());
+        
+        // Endpoints handed to browser bundles are visible to every visitor
+        if !is_doc_like && BROWSER_EXPOSED_VAR.is_match(line) {
+            for m in hosted.iter_mut().filter(|m| m.endpoint_url.is_some()) {
+                m.browser_exposed = true;
+            }
+        }
+        
+        // NVCF invocation with a placeholder id: pick up the UUID assigned nearby
+        if !is_doc_like {
+            for m in &mut hosted {
+                let is_nvcf = m.endpoint_url.as_deref().is_some_and(|u| NVCF_ENDPOINT.is_match(u));
+                if is_nvcf && m.function_id.is_none() && m.model_name.is_none() {
+                    m.function_id = find_function_id_in_context(&lines, line_num, 10);
                 }
             }
         }
+        
+        // Found through a substituted variable: keep the line as written
+        for m in &mut hosted {
+            if let Some((names, context, truncated)) = resolution(line_num, &[m.endpoint_url.as_deref(), m.model_name.as_deref()]) {
+                (m.resolved_from, m.match_context, m.context_truncated) = (Some(names), context, truncated);
+            }
+        }
+        
+        // Which credential the call authenticates with; a literal key never reaches the report
+        for m in &mut hosted {
+            m.credential_source = find_credential_source(&lines, line_num, 10);
+            m.match_context = redact_literal_keys(&m.match_context);
+        }
+        
+        // Literal sampling parameters in the same call / payload
+        if opts.capture_call_params && !is_doc_like {
+            for m in &mut hosted {
+                m.call_params = find_call_params(&lines, line_num, 10);
+            }
+        }
+        
+        // Classify once endpoint and model are final (context lookups may have filled them in)
+        for m in &mut hosted {
+            opts.hosted_hosts.classify(m);
+            m.invoke_style = classify_invoke_style(m.endpoint_url.as_deref());
+            m.framework = classify_framework(&m.detection_rule, &lines, line_num, 10, imported_framework);
+        }
+        
+        stats.rules.entry(hosted_rule).or_default().record(hosted.len());
+        for m in hosted {
+            debug!("[{}] Found Hosted NIM in {}:{}: {:?} {:?}",
+                   repository, relative_path, line_number, m.endpoint_url, m.model_name);
+            hosted_matches.push(m);
+        }
     }
+    // Requests through an OpenAI client set up with an NVIDIA base_url earlier in the file
+    if !is_doc_like {
+        let clients = nvidia_openai_clients(&lines, &opts.hosted_hosts);
+        if !clients.is_empty() {
+            link_openai_client_calls(&lines, &clients, &opts.hosted_hosts, &mut hosted_matches);
+        }
+        link_payload_models(&lines, &opts.hosted_hosts, &mut hosted_matches);
+    }
+    // Compose images the line pass can't see whole (split keys, anchors, build args)
+    if crate::compose::is_compose_file(path) {
+        add_compose_images(as_written, relative_path, repository, opts, &mut local_matches);
+    }
+    // Helm values images split into `repository:` and `tag:` keys
+    if opts.helm_values && is_helm_values_file(path) {
+        add_helm_images(as_written, relative_path, repository, opts, &mut local_matches);
+    }
+    // Kubernetes manifests: each container with the resource it belongs to
+    if classify_file_kind(relative_path, content) == FileKind::Kubernetes {
+        add_manifest_images(as_written, relative_path, repository, opts, &mut local_matches);
+    }
+    // NIM_MODEL_NAME, NGC_API_KEY, ... declared for the NIMs found above
+    if !is_doc_like {
+        add_nim_env_vars(&lines, is_yaml, relative_path, repository, opts, &mut local_matches, &mut hosted_matches);
+    }
+    if stats.context_lookups_skipped > 0 {
+        warn!("[{}] {}: {} endpoint(s) past the first {} were not matched to a nearby model_name",
+              repository, relative_path, stats.context_lookups_skipped, MAX_CONTEXT_LOOKUPS_PER_FILE);
+    }
+
+    // Findings in a workflow carry the workflow's triggers (scheduled vs manual runs)
+    let has_matches = !local_matches.is_empty() || !hosted_matches.is_empty();
+    if has_matches && determine_source_type(relative_path) == SourceType::ActionsWorkflow {
+        let triggers = parse_workflow_triggers(content);
+        for m in &mut local_matches {
+            m.workflow_triggers = triggers.clone();
+        }
+        for m in &mut hosted_matches {
+            m.workflow_triggers = triggers.clone();
+        }
+    }
+    if has_matches {
+        let file_kind = classify_file_kind(relative_path, content);
+        for m in &mut local_matches {
+            m.file_kind = file_kind;
+            m.usage_kind = classify_usage_kind(&lines, m.line_number.saturating_sub(1), m);
+        }
+        for m in &mut hosted_matches {
+            m.file_kind = file_kind;
+        }
+    }
+
+    (local_matches, hosted_matches)
+}
+
+/// Merge the images of a parsed Compose file into the line pass's Local NIM findings
+///
+/// A finding the line pass made on the same line for the same image is kept,
+/// with the key path (and so the service) as its `match_context`; any other
+/// image becomes a `local_nim_compose` finding on the line best holding it. A
+/// file that doesn't parse as YAML keeps the line pass's findings as they are.
+fn add_compose_images(
+    content: &str,
+    relative_path: &str,
+    repository: &str,
+    opts: &ScanOptions,
+    local_matches: &mut Vec<LocalNimMatch>,
+) {
+    let Some(images) = crate::compose::compose_images(content) else {
+        debug!("[{}] {} doesn't parse as YAML; Compose images from the line pass only", repository, relative_path);
+        return;
+    };
+    let mut claimed = vec![false; local_matches.len()];
+    for found in images {
+        let Some(mut m) = extract_local_nim(&found.image, found.line, relative_path, repository, None) else {
+            continue;
+        };
+        let context = format!("{}: {}", found.key_path, found.image);
+        let span = context.len() - found.image.len()..context.len();
+        let (match_context, context_truncated) = bounded_context(&context, span, opts.max_context_chars);
+        let same = local_matches.iter().take(claimed.len()).enumerate().position(|(i, l)| {
+            !claimed[i] && l.line_number == found.line && l.image_url == m.image_url && l.tag == m.tag
+        });
+        if let Some(i) = same {
+            claimed[i] = true;
+            (local_matches[i].match_context, local_matches[i].context_truncated) = (match_context, context_truncated);
+            continue;
+        }
+        (m.match_context, m.context_truncated) = (match_context, context_truncated);
+        m.detection_rule = "local_nim_compose".to_string();
+        m.intent = match found.service.is_some() && found.key_path.ends_with(".image") {
+            true => LocalIntent::Deploy,
+            false => LocalIntent::Reference,
+        };
+        debug!("[{}] Found Local NIM in Compose {}:{}: {}", repository, relative_path, found.line, found.key_path);
+        local_matches.push(m);
+    }
+    local_matches.sort_by_key(|m| m.line_number);
+}
+
+/// Merge the container images of a Kubernetes manifest into the line pass's Local NIM findings
+///
+/// A finding the line pass made on the same line for the same image gets the
+/// resource and container as its `match_context` and the `deploy` intent; any
+/// other NIM image (one the line pass read differently, e.g. in a JSON
+/// manifest with escapes) becomes a `local_nim_kubernetes` finding. A file that doesn't parse keeps
+/// the line pass's findings as they are.
+///
+/// Images of NIM Operator resources (`NIMService`, `NIMCache`, `NIMPipeline`)
+/// get `local_nim_operator` instead; their tag is a key of its own, so a
+/// line-pass finding without a tag on the `repository:` line takes it.
+fn add_manifest_images(
+    content: &str,
+    relative_path: &str,
+    repository: &str,
+    opts: &ScanOptions,
+    local_matches: &mut Vec<LocalNimMatch>,
+) {
+    let Some(images) = crate::kubernetes::manifest_images(content) else {
+        debug!("[{}] {} doesn't parse as YAML; manifest images from the line pass only", repository, relative_path);
+        return;
+    };
+    let mut claimed = vec![false; local_matches.len()];
+    for found in images {
+        let Some(mut m) = extract_local_nim(&found.image, found.line, relative_path, repository, None) else {
+            continue;
+        };
+        let context = found.context();
+        let start = context.find(&found.image).unwrap_or(0);
+        let (match_context, context_truncated) = bounded_context(&context, start..start + found.image.len(), opts.max_context_chars);
+        // The line pass may have taken a tag from a nearby line, so the tag isn't compared
+        let same = local_matches.iter().take(claimed.len()).enumerate().position(|(i, l)| {
+            !claimed[i] && l.line_number == found.line && l.image_url == m.image_url
+        });
+        if let Some(i) = same {
+            claimed[i] = true;
+            let l = &mut local_matches[i];
+            (l.match_context, l.context_truncated) = (match_context, context_truncated);
+            // A container image is deployed, however the line reads (`containers: [{image: ...}]`)
+            l.intent = LocalIntent::Deploy;
+            if found.is_operator() && l.detection_rule == "local_nim_no_tag" {
+                (l.tag, l.detection_rule) = (m.tag, "local_nim_operator".to_string());
+            }
+            continue;
+        }
+        (m.match_context, m.context_truncated) = (match_context, context_truncated);
+        m.detection_rule = match found.is_operator() {
+            true => "local_nim_operator".to_string(),
+            false => "local_nim_kubernetes".to_string(),
+        };
+        m.intent = LocalIntent::Deploy;
+        debug!("[{}] Found Local NIM in manifest {}:{}: {}", repository, relative_path, found.line, found.resource);
+        local_matches.push(m);
+    }
+    local_matches.sort_by_key(|m| m.line_number);
+}
+
+/// Model-name candidates of a YAML file by 0-indexed line, built once per file
+///
+/// Machine-generated YAML can hold thousands of endpoint lines; indexing the
+/// file once keeps each context lookup to two range queries instead of
+/// matching every line in the window again.
+fn index_context_models<'a>(lines: &[&'a str]) -> BTreeMap<usize, &'a str> {
+    lines
+        .iter()
+        .enumerate()
+        .filter_map(|(i, line)| Some((i, YAML_CONTEXT_MODEL.captures(line)?.get(1)?.as_str())))
+        .collect()
+}
+
+/// Find model_name in surrounding lines of the same YAML block (see `yaml_block_bounds`)
+fn find_model_name_in_context(index: &BTreeMap<usize, &str>, lines: &[&str], current_line: usize, range: usize) -> Option<String> {
+    let (start, end) = yaml_block_bounds(lines, current_line, range);
+    // Search backwards first (model_name usually comes before base_url)
+    if let Some((_, model)) = index.range(start..current_line).next_back() {
+        return Some(model.to_string());
+    }
+    
+    // Also search forward in case model comes after
+    index.range(current_line + 1..end + 1).next().map(|(_, model)| model.to_string())
+}
+
+/// Column of a YAML line's content, counting list markers (`  - - key`) as indentation
+fn yaml_item_indent(line: &str) -> usize {
+    let mut at = line.len() - line.trim_start().len();
+    while line[at..].starts_with("- ") {
+        at += 2;
+        at += line[at..].len() - line[at..].trim_start().len();
+    }
+    at
+}
+
+/// First and last line (0-indexed) of the YAML block around `current_line`,
+/// at most `range` lines away
+///
+/// A multi-document file or a values file with several service blocks puts
+/// unrelated `model:` keys within a few lines of an endpoint, so the block
+/// ends at a `---` / `...` document marker and at a line indented less than
+/// the endpoint's line. The first line of the endpoint's list item
+/// (`- name: x` above `  base_url: ...`) is still in the block; a line that
+/// starts a list item has nothing of its item above it. A top-level endpoint
+/// is not bounded by indentation, so there a blank line ends the block.
+/// Comment lines never end a block.
+fn yaml_block_bounds(lines: &[&str], current_line: usize, range: usize) -> (usize, usize) {
+    let block = yaml_item_indent(lines[current_line]);
+    let indent = |line: &str| line.len() - line.trim_start().len();
+    let document_marker = |line: &str| line.starts_with("---") || line.trim_end() == "...";
+    
+    let mut start = current_line;
+    let earliest = match lines[current_line].trim_start().starts_with("- ") {
+        true => current_line,
+        false => current_line.saturating_sub(range),
+    };
+    for i in (earliest..current_line).rev() {
+        let line = lines[i];
+        let trimmed = line.trim();
+        if document_marker(line) || (trimmed.is_empty() && block == 0) {
+            break;
+        }
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if indent(line) < block {
+            if trimmed.starts_with("- ") && yaml_item_indent(line) == block {
+                start = i;
+            }
+            break;
+        }
+        start = i;
+    }
+    
+    let mut end = current_line;
+    for (i, line) in lines.iter().enumerate().take(current_line + range + 1).skip(current_line + 1) {
+        let trimmed = line.trim();
+        if document_marker(line) || (trimmed.is_empty() && block == 0) {
+            break;
+        }
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if indent(line) < block {
+            break;
+        }
+        end = i;
+    }
+    (start, end)
+}
+
+/// Find a whitelisted `model:` key in the call or object literal around a JS/TS line,
+/// falling back to nearby lines for a body object built before the call
+fn find_js_model_in_window(lines: &[&str], current_line: usize, range: usize) -> Option<String> {
+    let js_model = |line: &&str| {
+        JS_MODEL_KEY.captures(line)
+            .and_then(|caps| caps.get(1))
+            .map(|m| m.as_str())
+            .filter(|name| model_is_whitelisted(name))
+            .map(|name| name.to_string())
+    };
+    let (start, end) = call_window(lines, current_line, range);
+    let nearby_start = current_line.saturating_sub(range);
+    let nearby_end = (current_line + range).min(lines.len().saturating_sub(1));
+    lines[start..=end].iter().find_map(js_model)
+        .or_else(|| lines[nearby_start..=nearby_end].iter().find_map(js_model))
+}
+
+/// Line range of the call or payload enclosing `current_line`
+///
+/// Walks back (up to `range` lines) to the nearest unclosed `(`, `{` or `[`, then
+/// forward to its matching close. Without an enclosing bracket the window is the
+/// current line plus any bracket it opens. Brackets inside strings are not special-cased.
+fn call_window(lines: &[&str], current_line: usize, range: usize) -> (usize, usize) {
+    let mut start = (current_line, 0);
+    let mut depth = 0usize;
+    let earliest = current_line.saturating_sub(range);
+    'back: for i in (earliest..=current_line).rev() {
+        for (col, c) in lines[i].char_indices().rev() {
+            match c {
+                ')' | '}' | ']' => depth += 1,
+                '(' | '{' | '[' if depth == 0 => {
+                    start = (i, col);
+                    break 'back;
+                }
+                '(' | '{' | '[' => depth -= 1,
+                _ => {}
+            }
+        }
+    }
+
+    let latest = (current_line + range).min(lines.len().saturating_sub(1));
+    let mut depth = 0i32;
+    for (i, line) in lines.iter().enumerate().take(latest + 1).skip(start.0) {
+        let text = if i == start.0 { &line[start.1..] } else { line };
+        let mut opened = false;
+        for c in text.chars() {
+            match c {
+                '(' | '{' | '[' => {
+                    depth += 1;
+                    opened = true;
+                }
+                ')' | '}' | ']' => depth -= 1,
+                _ => {}
+            }
+        }
+        if depth <= 0 && (opened || i >= current_line) {
+            return (start.0, i.max(current_line));
+        }
+    }
+    (start.0, latest)
+}
+
+/// Credential passed on one line, if any
+///
+/// A reference after the `api_key` / `Bearer` keyword wins over one earlier on
+/// the line (`base_url=os.environ["URL"], api_key=os.environ["KEY"]`).
+fn credential_on_line(line: &str) -> Option<CredentialSource> {
+    let keyword = CREDENTIAL_LINE.find(line)?;
+    for text in [&line[keyword.start()..], line] {
+        if let Some(caps) = ACTIONS_CREDENTIAL.captures(text) {
+            let name = caps[2].to_string();
+            return Some(match &caps[1] {
+                "secrets" => CredentialSource::Secret { name },
+                _ => CredentialSource::EnvVar { name },
+            });
+        }
+        if let Some(caps) = ENV_VAR_REF.captures(text) {
+            let name = caps.get(1).or(caps.get(2)).or(caps.get(3))?.as_str().to_string();
+            return Some(CredentialSource::EnvVar { name });
+        }
+    }
+    LITERAL_KEY.captures(line)
+        .filter(|caps| !is_placeholder_key(caps.iter().skip(1).flatten().next().map_or("", |m| m.as_str())))
+        .map(|_| CredentialSource::Literal)
+}
+
+/// Example values such as `your-api-key-here` or `xxxxxxxx...` are not keys
+fn is_placeholder_key(value: &str) -> bool {
+    let lower = value.to_lowercase();
+    ["your", "xxxx", "placeholder", "changeme", "example", "<"].iter().any(|p| lower.contains(p))
+}
+
+/// Find the credential a Hosted NIM call authenticates with: the closest
+/// credential line in the enclosing call, else within `range / 2` lines
+/// (e.g. an `env:` block next to a workflow step)
+///
+/// That line decides: a placeholder or a plain variable there means unknown,
+/// not whatever key a neighbouring call uses.
+fn find_credential_source(lines: &[&str], current_line: usize, range: usize) -> Option<CredentialSource> {
+    let by_distance = |start: usize, end: usize| {
+        let mut indices: Vec<usize> = (start..=end).collect();
+        indices.sort_by_key(|&i| (i.abs_diff(current_line), i));
+        indices
+    };
+    let (start, end) = call_window(lines, current_line, range);
+    let nearby_start = current_line.saturating_sub(range / 2);
+    let nearby_end = (current_line + range / 2).min(lines.len().saturating_sub(1));
+    by_distance(start, end).into_iter()
+        .chain(by_distance(nearby_start, nearby_end))
+        .find(|&i| CREDENTIAL_LINE.is_match(lines[i]))
+        .and_then(|i| credential_on_line(lines[i]))
+}
+
+/// Byte range of a regex match as a whole
+fn capture_span(caps: &regex::Captures) -> Range<usize> {
+    caps.get(0).map(|m| m.range()).unwrap_or_default()
+}
+
+/// Byte range of the first occurrence of `needle` in `line`
+fn find_span(line: &str, needle: &str) -> Option<Range<usize>> {
+    line.find(needle).map(|start| start..start + needle.len())
+}
+
+/// Trimmed line as match context, cut to `limit` characters around `span` (a byte range in `line`)
+///
+/// Minified or generated single-line files would otherwise put the whole file
+/// into the report. The cut keeps about as much text before the match as after
+/// it, starts with `…` when the line's start is dropped and ends with
+/// `…[truncated]`. Returns the context and, when it was cut, the trimmed line's
+/// length in characters.
+fn bounded_context(line: &str, span: Range<usize>, limit: Option<usize>) -> (String, Option<usize>) {
+    let trimmed = line.trim();
+    let total = trimmed.chars().count();
+    let limit = match limit {
+        Some(limit) if total > limit => limit,
+        _ => return (trimmed.to_string(), None),
+    };
+    // Match position in characters of the trimmed line
+    let offset = line.len() - line.trim_start().len();
+    let start_byte = span.start.saturating_sub(offset).min(trimmed.len());
+    let end_byte = span.end.saturating_sub(offset).clamp(start_byte, trimmed.len());
+    let start = trimmed[..start_byte].chars().count();
+    let len = trimmed[start_byte..end_byte].chars().count();
     
+    let from = start.saturating_sub(limit.saturating_sub(len) / 2).min(total - limit);
+    let window: String = trimmed.chars().skip(from).take(limit).collect();
+    let lead = if from > 0 { "…" } else { "" };
+    (format!("{}{}…[truncated]", lead, window), Some(total))
+}
+
+/// Replace API keys written into a line with `REDACTED`
+fn redact_literal_keys(line: &str) -> String {
+    LITERAL_KEY.replace_all(line, |caps: &regex::Captures| {
+        let value = match caps.iter().skip(1).flatten().next() {
+            Some(m) => m,
+            None => return caps[0].to_string(),
+        };
+        if is_placeholder_key(value.as_str()) {
+            return caps[0].to_string();
+        }
+        let whole = caps.get(0).expect("group 0 always matches");
+        format!(
+            "{}REDACTED{}",
+            &line[whole.start()..value.start()],
+            &line[value.end()..whole.end()]
+        )
+    }).into_owned()
+}
+
+/// Capture literal temperature / max_tokens / stream / top_p values from the
+/// call or payload around a Hosted NIM match (no evaluation; first value per key)
+fn find_call_params(lines: &[&str], current_line: usize, range: usize) -> Option<Value> {
+    let (start, end) = call_window(lines, current_line, range);
+    let mut params = serde_json::Map::new();
+    for line in &lines[start..=end] {
+        for caps in CALL_PARAM.captures_iter(line) {
+            let key = caps[1].to_string();
+            if params.contains_key(&key) {
+                continue;
+            }
+            let raw = &caps[2];
+            let value = match raw {
+                "true" | "True" => Value::Bool(true),
+                "false" | "False" => Value::Bool(false),
+                _ if raw.starts_with('"') || raw.starts_with('\'') => {
+                    Value::String(raw[1..raw.len() - 1].to_string())
+                }
+                _ => match raw.parse::<i64>() {
+                    Ok(n) => Value::from(n),
+                    Err(_) => raw.parse::<f64>().map(Value::from).unwrap_or(Value::Null),
+                },
+            };
+            params.insert(key, value);
+        }
+    }
+    (!params.is_empty()).then_some(Value::Object(params))
+}
+
+/// A LangChain NVIDIA constructor call spread over several lines
+#[derive(Debug)]
+struct MultilineCall {
+    /// 0-indexed lines of the constructor and of its closing parenthesis
+    start: usize,
+    end: usize,
+    /// The whole call, its lines trimmed and joined
+    text: String,
+    /// The call with the contents of nested brackets left out, holding only its own arguments
+    top_level: String,
+}
+
+/// Follow a call opened at byte `open` (just past the `(`) of line `start` to its closing parenthesis
+///
+/// Brackets are counted outside string literals and `#` comments, so a `)` in
+/// a prompt or a nested `Handler(...)` argument doesn't end the call early.
+/// Returns `None` for a call that closes on its own line or is still open
+/// after `MAX_CALL_LINES` lines.
+fn follow_call(lines: &[&str], start: usize, open: usize) -> Option<MultilineCall> {
+    let mut top_level = lines[start][..open].trim_start().to_string();
+    let mut pieces = Vec::new();
+    let mut depth = 1usize;
+    for (i, line) in lines.iter().enumerate().skip(start).take(MAX_CALL_LINES) {
+        let from = if i == start { open } else { 0 };
+        let mut cut = line.len();
+        let mut closed = false;
+        let mut quote: Option<char> = None;
+        let mut escaped = false;
+        for (col, c) in line[from..].char_indices() {
+            let col = from + col;
+            match (quote, c) {
+                (Some(_), _) if escaped => escaped = false,
+                (Some(_), '\\') => escaped = true,
+                (Some(q), _) if c == q => quote = None,
+                (Some(_), _) => {}
+                (None, '"' | '\'') => quote = Some(c),
+                (None, '#') => {
+                    cut = col;
+                    break;
+                }
+                (None, '(' | '[' | '{') => {
+                    if depth == 1 {
+                        top_level.push(c);
+                    }
+                    depth += 1;
+                    continue;
+                }
+                (None, ')' | ']' | '}') => {
+                    depth -= 1;
+                    if depth == 0 {
+                        top_level.push(c);
+                        cut = col + 1;
+                        closed = true;
+                        break;
+                    }
+                }
+                _ => {}
+            }
+            if depth == 1 {
+                top_level.push(c);
+            }
+        }
+        pieces.push(line[..cut].trim());
+        if closed {
+            return (i > start).then(|| MultilineCall { start, end: i, text: join_call_lines(&pieces), top_level });
+        }
+        top_level.push(' ');
+    }
     None
 }
 
+/// Join trimmed lines of a call with spaces, except just inside its brackets
+fn join_call_lines(pieces: &[&str]) -> String {
+    let mut text = String::new();
+    for piece in pieces.iter().filter(|piece| !piece.is_empty()) {
+        if !text.is_empty() && !text.ends_with(['(', '[', '{']) && !piece.starts_with([')', ']', '}']) {
+            text.push(' ');
+        }
+        text.push_str(piece);
+    }
+    text
+}
+
+/// LangChain NVIDIA constructor calls that don't close on the line they open (Python),
+/// with the constructor's detection rule
+fn multiline_constructor_calls(lines: &[&str]) -> Vec<(&'static str, MultilineCall)> {
+    let mut calls = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let call = LANGCHAIN_CONSTRUCTOR.captures(lines[i]).and_then(|caps| {
+            let rule = match &caps[1] {
+                "ChatNVIDIA" => "chatnvidia",
+                "NVIDIAEmbeddings" => "nvidia_embeddings",
+                _ => "nvidia_rerank",
+            };
+            Some((rule, follow_call(lines, i, caps.get(0)?.end())?))
+        });
+        match call {
+            Some((rule, call)) => {
+                i = call.end + 1;
+                calls.push((rule, call));
+            }
+            None => i += 1,
+        }
+    }
+    calls
+}
+
+/// Hosted NIMs of LangChain constructor calls whose `model=` argument is on a later line
+///
+/// Black-formatted Python puts each argument on a line of its own. Each
+/// finding is reported at the constructor's line, with the joined call as
+/// `match_context`, and keeps any endpoint passed in the same call; the lines
+/// it covers are returned with it. A `model=` on the constructor's own line is
+/// left to the line scan.
+fn extract_multiline_langchain(
+    lines: &[&str],
+    file_path: &str,
+    repository: &str,
+    context_limit: Option<usize>,
+    hosts: &HostedHosts,
+) -> Vec<(RangeInclusive<usize>, HostedNimMatch)> {
+    let mut found = Vec::new();
+    for (rule, call) in multiline_constructor_calls(lines) {
+        if find_model_name(lines[call.start], false).is_some() {
+            continue;
+        }
+        let model = MODEL_KWARG.captures(&call.top_level).map(|caps| caps[1].to_string());
+        let Some(model) = model.filter(|name| model_is_whitelisted(name)) else {
+            continue;
+        };
+        let Some(mut m) = extract_hosted_nim(&call.top_level, call.start + 1, file_path, repository, None, hosts).into_iter().next() else {
+            continue;
+        };
+        let span = find_span(&call.text, &model).unwrap_or_default();
+        (m.match_context, m.context_truncated) = bounded_context(&call.text, span, context_limit);
+        m.model_name = Some(model);
+        m.detection_rule = rule.to_string();
+        found.push((call.start..=call.end, m));
+    }
+    found
+}
+
+/// Whether a line's match only repeats part of a multi-line call's match
+fn covered_by_call(m: &HostedNimMatch, call: &HostedNimMatch) -> bool {
+    let part_of = |part: &Option<String>, whole: &Option<String>| part.is_none() || part == whole;
+    (m.model_name.is_some() || m.endpoint_url.is_some())
+        && part_of(&m.model_name, &call.model_name)
+        && part_of(&m.endpoint_url, &call.endpoint_url)
+        && part_of(&m.function_id, &call.function_id)
+}
+
+/// OpenAI SDK client constructed with an NVIDIA `base_url`
+#[derive(Debug)]
+struct OpenAiClient {
+    /// Name it is assigned to (`client`, `self.llm`)
+    name: String,
+    /// 0-indexed lines of the constructor call
+    lines: RangeInclusive<usize>,
+    endpoint: String,
+}
+
+/// OpenAI SDK clients of a file pointed at an NVIDIA API host, in file order
+fn nvidia_openai_clients(lines: &[&str], hosts: &HostedHosts) -> Vec<OpenAiClient> {
+    lines
+        .iter()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let caps = OPENAI_CLIENT_ASSIGN.captures(line)?;
+            let (end, call) = match follow_call(lines, i, caps.get(0)?.end()) {
+                Some(call) => (call.end, call.text),
+                None => (i, line.to_string()),
+            };
+            let endpoint = hosts.find(&call)?.as_str().to_string();
+            Some(OpenAiClient { name: caps[1].to_string(), lines: i..=end, endpoint })
+        })
+        .collect()
+}
+
+/// Give the model of a request made through an NVIDIA-pointed OpenAI client the client's endpoint
+///
+/// `client = OpenAI(base_url="https://integrate.api.nvidia.com/v1", ...)` and
+/// `client.chat.completions.create(model="org/model", ...)` are usually far
+/// apart, so the line scan reports the endpoint and the model separately. A
+/// model finding inside a `<name>.chat.completions.create(` (or `completions`,
+/// `embeddings`, `responses`) call gets the endpoint of the client of that
+/// name defined last before it, or of the first one when the request comes
+/// first (a method using a client set up further down), and the rule
+/// `openai_client_call`. A client's own endpoint-only finding is dropped once
+/// a request is linked to it, so each request is counted once.
+fn link_openai_client_calls(lines: &[&str], clients: &[OpenAiClient], hosts: &HostedHosts, hosted: &mut Vec<HostedNimMatch>) {
+    let mut linked: Vec<&RangeInclusive<usize>> = Vec::new();
+    for m in hosted.iter_mut().filter(|m| m.model_name.is_some() && m.endpoint_url.is_none()) {
+        let line = m.line_number - 1;
+        let (start, _) = call_window(lines, line, 10);
+        let Some(name) = lines[start..=line].iter().rev().find_map(|l| OPENAI_CLIENT_CALL.captures(l).map(|caps| caps[1].to_string())) else {
+            continue;
+        };
+        let named = || clients.iter().filter(|client| client.name == name);
+        let Some(client) = named().rfind(|client| *client.lines.start() < line).or_else(|| named().next()) else {
+            continue;
+        };
+        m.endpoint_url = Some(client.endpoint.clone());
+        m.detection_rule = "openai_client_call".to_string();
+        hosts.classify(m);
+        m.invoke_style = classify_invoke_style(m.endpoint_url.as_deref());
+        m.framework = Framework::OpenaiSdk;
+        linked.push(&client.lines);
+    }
+    hosted.retain(|m| m.model_name.is_some() || !linked.iter().any(|lines| lines.contains(&(m.line_number - 1))));
+}
+
+/// Line range of the request an endpoint on `current_line` belongs to
+///
+/// For `curl` / `wget` that is the whole shell command: back over the
+/// continuation lines before it, and forward while a line ends in `\` or a
+/// single-quoted argument (`-d '{`) is still open. Anything else is a call in
+/// code, `requests.post("<endpoint>", json={...})`, and gets its enclosing call.
+fn request_window(lines: &[&str], current_line: usize) -> (usize, usize) {
+    if !SHELL_HTTP_CLIENT.is_match(&continued_command(lines, current_line)) {
+        return call_window(lines, current_line, 10);
+    }
+    let mut start = current_line;
+    while start > 0
+        && current_line - start < MAX_CONTINUATION_LINES
+        && lines[start - 1].trim_end().ends_with('\\')
+    {
+        start -= 1;
+    }
+    let mut end = start;
+    let mut quoted = false;
+    loop {
+        quoted ^= lines[end].matches('\'').count() % 2 == 1;
+        let continues = quoted || lines[end].trim_end().ends_with('\\');
+        if !continues || end + 1 == lines.len() || end - start >= MAX_CONTINUATION_LINES {
+            break;
+        }
+        end += 1;
+    }
+    (start, end.max(current_line))
+}
+
+/// First whitelisted `"model": "<org>/<name>"` of a JSON payload in `lines[start..=end]`
+fn find_payload_model(lines: &[&str], start: usize, end: usize) -> Option<(usize, String)> {
+    lines[start..=end].iter().enumerate().find_map(|(i, line)| {
+        JSON_PAYLOAD_MODEL.captures_iter(line)
+            .filter_map(|caps| caps.get(1))
+            .map(|m| m.as_str())
+            .find(|name| model_is_whitelisted(name))
+            .map(|name| (start + i, name.to_string()))
+    })
+}
+
+/// Pair HTTP requests to NVIDIA endpoints with the model in their JSON payload
+///
+/// `curl <endpoint> -d '{"model": "org/model", ...}'` spread over continuation
+/// lines, or `requests.post("<endpoint>", json={"model": ...})` spread over a
+/// call, is reported by the line scan as an endpoint and a model apart. An
+/// endpoint-only finding takes the first `"model"` of its request (see
+/// [`request_window`]); a model-only finding on a `"model":` line of a payload
+/// built apart from the call takes the nearest endpoint assigned to a name
+/// (`URL = "https://..."`) within 10 lines. Either
+/// way the finding gets the rule `json_payload_model`, and the other half's own
+/// finding is dropped, so each request is counted once.
+fn link_payload_models(lines: &[&str], hosts: &HostedHosts, hosted: &mut Vec<HostedNimMatch>) {
+    let endpoint_only = |m: &HostedNimMatch| m.model_name.is_none() && m.function_id.is_none()
+        && m.endpoint_url.as_deref().is_some_and(|url| hosts.find(url).is_some());
+    // 0-indexed lines of model-only findings and of endpoint-only findings that were paired
+    let mut paired_models: Vec<(usize, String)> = Vec::new();
+    let mut paired_endpoints: Vec<usize> = Vec::new();
+    for m in hosted.iter_mut().filter(|m| endpoint_only(m)) {
+        let line = m.line_number - 1;
+        let (start, end) = request_window(lines, line);
+        let Some((model_line, name)) = find_payload_model(lines, start, end) else {
+            continue;
+        };
+        m.model_name = Some(name.clone());
+        m.detection_rule = "json_payload_model".to_string();
+        hosts.classify(m);
+        paired_models.push((model_line, name));
+    }
+    for m in hosted.iter_mut().filter(|m| m.endpoint_url.is_none() && m.function_id.is_none()) {
+        let line = m.line_number - 1;
+        let Some(name) = m.model_name.clone() else {
+            continue;
+        };
+        if paired_models.contains(&(line, name.clone()))
+            || !JSON_PAYLOAD_MODEL.captures_iter(lines[line]).any(|caps| caps[1] == name)
+        {
+            continue;
+        }
+        let start = line.saturating_sub(10);
+        let end = (line + 10).min(lines.len() - 1);
+        let mut nearby: Vec<usize> = (start..=end).collect();
+        nearby.sort_by_key(|&i| (i.abs_diff(line), i));
+        let Some((endpoint_line, url)) = nearby.into_iter()
+            .filter(|&i| ENDPOINT_ASSIGN.is_match(lines[i]))
+            .find_map(|i| hosts.find(lines[i]).map(|url| (i, url.as_str().to_string())))
+        else {
+            continue;
+        };
+        m.endpoint_url = Some(url);
+        m.detection_rule = "json_payload_model".to_string();
+        hosts.classify(m);
+        m.invoke_style = classify_invoke_style(m.endpoint_url.as_deref());
+        paired_endpoints.push(endpoint_line);
+    }
+    hosted.retain(|m| {
+        let line = m.line_number - 1;
+        let paired_model = m.endpoint_url.is_none()
+            && m.model_name.as_ref().is_some_and(|name| paired_models.contains(&(line, name.clone())));
+        let paired_endpoint = endpoint_only(m) && paired_endpoints.contains(&line);
+        !paired_model && !paired_endpoint
+    });
+}
+
+/// A NIM variable declared in a file (see [`nim_env_vars`])
+#[derive(Debug, Clone, PartialEq)]
+struct NimEnvVar {
+    /// 0-indexed line of the declaration (the `- name:` line of a Kubernetes entry)
+    line: usize,
+    name: String,
+    /// Value without quotes; empty for `valueFrom:` and keys without a value
+    value: String,
+    /// 0-indexed line of the value (the `value:` line of a Kubernetes entry)
+    value_line: usize,
+}
+
+impl NimEnvVar {
+    fn is_secret(&self) -> bool {
+        NIM_ENV_SECRET_VARS.contains(&self.name.as_str())
+    }
+
+    /// Whitelisted `org/model` of a model variable
+    fn model(&self) -> Option<&str> {
+        let value = self.value.as_str();
+        let shaped = value.split_once('/').is_some_and(|(org, name)| !org.is_empty() && !name.is_empty())
+            && !value.contains("://")
+            && !value.contains(char::is_whitespace);
+        (NIM_ENV_MODEL_VARS.contains(&self.name.as_str()) && shaped && model_is_whitelisted(value)).then_some(value)
+    }
+}
+
+/// NIM variables declared in `lines`: `NAME=value` anywhere, and in YAML files
+/// `NAME: value` map entries and Kubernetes `- name: NAME` entries with the
+/// `value:` (or `valueFrom:`) of the next lines
+fn nim_env_vars(lines: &[&str], is_yaml: bool) -> Vec<NimEnvVar> {
+    let unquote = |value: &str| value.trim_matches(|c| c == '"' || c == '\'').to_string();
+    let mut vars = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        for caps in NIM_ENV_ASSIGN.captures_iter(line) {
+            vars.push(NimEnvVar { line: i, name: caps[1].to_string(), value: unquote(&caps[2]), value_line: i });
+        }
+        if !is_yaml {
+            continue;
+        }
+        if let Some(caps) = NIM_ENV_YAML_KEY.captures(line) {
+            let value = caps.get(2).map(|v| unquote(v.as_str())).unwrap_or_default();
+            vars.push(NimEnvVar { line: i, name: caps[1].to_string(), value, value_line: i });
+        } else if let Some(caps) = NIM_ENV_K8S_NAME.captures(line) {
+            // The entry's keys up to the next list item
+            let value = lines.iter().enumerate().skip(i + 1).take(3)
+                .take_while(|(_, next)| !next.trim_start().starts_with("- "))
+                .find_map(|(at, next)| K8S_ENV_VALUE.captures(next).map(|value| (at, unquote(&value[1]))));
+            let (value_line, value) = value.unwrap_or((i, String::new()));
+            vars.push(NimEnvVar { line: i, name: caps[1].to_string(), value, value_line });
+        }
+    }
+    vars
+}
+
+/// `text` with the values of credential variables replaced by `REDACTED`
+fn redact_nim_env_secrets(text: &str) -> String {
+    NIM_ENV_SECRET_VALUE.replace_all(text, "${1}${2}REDACTED").into_owned()
+}
+
+/// Report declarations of NIM environment variables with the NIM they configure
+///
+/// Deployment manifests often name the model a NIM serves (`NIM_MODEL_NAME`,
+/// `NIM_SERVED_MODEL_NAME`), its profile (`NIM_MODEL_PROFILE`) or the key it
+/// runs with (`NGC_API_KEY`, `NVIDIA_API_KEY`) without any URL. A line
+/// declaring one becomes a `nim_env_var` finding:
+///
+/// - in a file with a Local NIM image, a Local NIM finding of the nearest
+///   image (the deployment the variables configure), except `NVIDIA_API_KEY`,
+///   which goes with the nearest Hosted NIM when the file has one;
+/// - otherwise a model variable holding a whitelisted `org/model` is a Hosted
+///   NIM model finding, and a key goes with the nearest Hosted NIM finding.
+///
+/// A profile or key in a file without any NIM names no NIM and is not
+/// reported, nor is a line that already has a finding of the same kind. The
+/// values of key variables are replaced with `REDACTED` in every finding on
+/// their line.
+fn add_nim_env_vars(
+    lines: &[&str],
+    is_yaml: bool,
+    relative_path: &str,
+    repository: &str,
+    opts: &ScanOptions,
+    local: &mut Vec<LocalNimMatch>,
+    hosted: &mut Vec<HostedNimMatch>,
+) {
+    let vars = nim_env_vars(lines, is_yaml);
+    if vars.is_empty() {
+        return;
+    }
+    let mut by_line: BTreeMap<usize, Vec<&NimEnvVar>> = BTreeMap::new();
+    for var in &vars {
+        by_line.entry(var.line).or_default().push(var);
+    }
+    let images: Vec<LocalNimMatch> = local.clone();
+    let services: Vec<HostedNimMatch> = hosted.iter()
+        .filter(|m| m.model_name.is_some() || m.endpoint_url.is_some())
+        .cloned()
+        .collect();
+    let nearest = |line: usize, candidates: &[usize]| candidates.iter().copied().min_by_key(|&at| (at.abs_diff(line), at));
+    let image_lines: Vec<usize> = images.iter().map(|m| m.line_number - 1).collect();
+    let service_lines: Vec<usize> = services.iter().map(|m| m.line_number - 1).collect();
+
+    for (&line, declared) in &by_line {
+        let context = redact_nim_env_secrets(lines[line]);
+        let name = &declared[0].name;
+        let span = context.find(name.as_str()).map(|at| at..at + name.len()).unwrap_or_default();
+        let (match_context, context_truncated) = bounded_context(&context, span, opts.max_context_chars);
+        // Models with the line the value is on
+        let models: Vec<(usize, &str)> = declared.iter().filter_map(|var| Some((var.value_line, var.model()?))).collect();
+        let api_key_only = declared.iter().all(|var| var.name == "NVIDIA_API_KEY");
+        let has_secret = declared.iter().any(|var| var.is_secret());
+
+        let image = nearest(line, &image_lines).filter(|_| !api_key_only || service_lines.is_empty());
+        if let Some(at) = image {
+            // The model is the one the local NIM serves, not a Hosted NIM
+            hosted.retain(|m| !m.model_name.as_deref().is_some_and(|name| models.contains(&(m.line_number - 1, name))));
+            if local.iter().any(|m| m.line_number - 1 == line) {
+                continue;
+            }
+            let mut m = images[image_lines.iter().position(|&l| l == at).unwrap_or_default()].clone();
+            m.line_number = line + 1;
+            (m.match_context, m.context_truncated) = (match_context, context_truncated);
+            m.detection_rule = NIM_ENV_VAR_RULE.to_string();
+            m.resolved_from = None;
+            m.notebook_cell = None;
+            local.push(m);
+            continue;
+        }
+        if let Some(&(value_line, model)) = models.first() {
+            // The line pass's finding of the same model (`*MODEL*=org/model`, a prose match on `value:`) is replaced
+            hosted.retain(|m| m.line_number - 1 != value_line || m.model_name.as_deref() != Some(model));
+            let mut m = env_model_match(model, line + 1, relative_path, repository, match_context, context_truncated);
+            opts.hosted_hosts.classify(&mut m);
+            hosted.push(m);
+            continue;
+        }
+        let service = nearest(line, &service_lines).filter(|_| has_secret);
+        if let Some(at) = service {
+            if hosted.iter().any(|m| m.line_number - 1 == line) {
+                continue;
+            }
+            let mut m = services[service_lines.iter().position(|&l| l == at).unwrap_or_default()].clone();
+            m.line_number = line + 1;
+            (m.match_context, m.context_truncated) = (match_context, context_truncated);
+            m.detection_rule = NIM_ENV_VAR_RULE.to_string();
+            m.call_params = None;
+            m.credential_source = None;
+            m.resolved_from = None;
+            m.notebook_cell = None;
+            hosted.push(m);
+        }
+    }
+
+    // No key value survives in the context of any finding on a declaring line
+    let secret_lines: HashSet<usize> = vars.iter().filter(|var| var.is_secret()).map(|var| var.line + 1).collect();
+    for m in local.iter_mut().filter(|m| secret_lines.contains(&m.line_number)) {
+        m.match_context = redact_nim_env_secrets(&m.match_context);
+    }
+    for m in hosted.iter_mut().filter(|m| secret_lines.contains(&m.line_number)) {
+        m.match_context = redact_nim_env_secrets(&m.match_context);
+    }
+    local.sort_by_key(|m| m.line_number);
+    hosted.sort_by_key(|m| m.line_number);
+}
+
+/// Hosted NIM finding of a model variable (`NIM_SERVED_MODEL_NAME=org/model`)
+fn env_model_match(
+    model: &str,
+    line_number: usize,
+    file_path: &str,
+    repository: &str,
+    match_context: String,
+    context_truncated: Option<usize>,
+) -> HostedNimMatch {
+    HostedNimMatch {
+        repository: repository.to_string(),
+        endpoint_url: None,
+        model_name: Some(model.to_string()),
+        file_path: file_path.to_string(),
+        line_number,
+        match_context,
+        function_id: None,
+        status: None,
+        container_image: None,
+        environment: Environment::Production,
+        endpoint_host: None,
+        invoke_style: InvokeStyle::Unknown,
+        framework: Framework::Unknown,
+        call_params: None,
+        policy: None,
+        model_policy: None,
+        enrichment_error: None,
+        enriched: false,
+        detection_rule: NIM_ENV_VAR_RULE.to_string(),
+        workflow_triggers: Vec::new(),
+        nim_identity: None,
+        enrichment_skipped: None,
+        browser_exposed: false,
+        did_you_mean: Vec::new(),
+        credential_source: None,
+        context_truncated,
+        canonical_endpoint: None,
+        endpoint_mismatch: false,
+        endpoint_status: None,
+        suggested_endpoint: None,
+        blame: None,
+        notebook_cell: None,
+        file_kind: FileKind::Source,
+        resolved_from: None,
+        fixture: false,
+        fingerprint: String::new(),
+    }
+}
+
+/// Find a `*function_id* = "<uuid>"` assignment in surrounding lines (nearest first)
+fn find_function_id_in_context(lines: &[&str], current_line: usize, range: usize) -> Option<String> {
+    let start = current_line.saturating_sub(range);
+    let end = (current_line + range + 1).min(lines.len());
+    
+    let mut candidates: Vec<usize> = (start..end).filter(|&i| i != current_line).collect();
+    candidates.sort_by_key(|&i| i.abs_diff(current_line));
+    
+    candidates.into_iter().find_map(|i| {
+        FUNCTION_ID_ASSIGN
+            .captures(lines[i])
+            .and_then(|c| c.get(1))
+            .map(|m| m.as_str().to_lowercase())
+    })
+}
+
 /// Scan a directory for NIM references
 pub fn scan_directory(
     repo_path: &Path,
     repository: &str,
-) -> (Vec<LocalNimMatch>, Vec<HostedNimMatch>) {
-    let mut all_local: Vec<LocalNimMatch> = Vec::new();
-    let mut all_hosted: Vec<HostedNimMatch> = Vec::new();
+    opts: &ScanOptions,
+) -> ScanOutcome {
+    let mut outcome = ScanOutcome::default();
     
-    // Build walker with ignore rules
+    // Build walker with ignore rules; excluded directories and the depth limit
+    // prune the walk itself, so their contents are never listed
+    let skip_dirs = opts.skip_dirs.clone();
     let walker = WalkBuilder::new(repo_path)
         .hidden(false)  // Don't skip hidden files (we need .github/)
         .git_ignore(true)
         .git_global(false)
         .git_exclude(true)
+        .max_depth(opts.max_depth)
+        .filter_entry(move |entry| {
+            if entry.depth() == 0 {
+                return true;
+            }
+            // Skip .git directory but NOT .github; match by name, not substring
+            let name = entry.file_name().to_str().unwrap_or("");
+            name != ".git" && !skip_dirs.iter().any(|d| d == name)
+        })
         .build();
     
     // Collect files to scan
-    let files: Vec<_> = walker
-        .filter_map(|entry| entry.ok())
-        .filter(|entry| entry.file_type().map(|ft| ft.is_file()).unwrap_or(false))
-        .filter(|entry| {
-            let path = entry.path();
-            
-            // Skip files in excluded directories (match by path component, not substring)
-            for component in path.components() {
-                if let std::path::Component::Normal(name) = component {
-                    if let Some(name_str) = name.to_str() {
-                        // Skip .git directory but NOT .github
-                        if name_str == ".git" {
-                            return false;
-                        }
-                        // Skip other excluded directories
-                        if SKIP_DIRS.contains(&name_str) {
-                            return false;
-                        }
-                    }
-                }
+    let mut files = Vec::new();
+    for entry in walker.filter_map(|entry| entry.ok()) {
+        let file_type = match entry.file_type() {
+            Some(ft) => ft,
+            None => continue,
+        };
+        if file_type.is_dir() {
+            if entry.depth() > 0 && opts.max_depth.is_some_and(|max| entry.depth() >= max) {
+                outcome.dirs_beyond_max_depth += 1;
             }
-            
-            should_scan_file(path)
-        })
-        .map(|entry| entry.into_path())
-        .collect();
-    
-    debug!("Found {} files to scan in {}", files.len(), repo_path.display());
-    
-    // Scan files in parallel
-    let results: Vec<_> = files
+            continue;
+        }
+        if !file_type.is_file() || !should_scan_file(entry.path()) || (opts.skip_docs && is_docs_file(entry.path()) && !crate::dependencies::is_manifest(entry.path())) {
+            continue;
+        }
+        if let Some(ref scope) = opts.scope_paths {
+            let relative = entry.path().strip_prefix(repo_path).unwrap_or(entry.path());
+            if !in_scope(scope, relative) {
+                continue;
+            }
+        }
+        if opts.max_files.is_some_and(|max| files.len() >= max) {
+            warn!("[{}] More than {} files to scan; the rest of the repository is skipped", repository, files.len());
+            outcome.file_limit_reached = true;
+            break;
+        }
+        files.push(entry.into_path());
+    }
+    if outcome.dirs_beyond_max_depth > 0 {
+        warn!("[{}] {} directories at depth {} were not descended into",
+              repository, outcome.dirs_beyond_max_depth, opts.max_depth.unwrap_or_default());
+    }
+    
+    debug!("[{}] Found {} files to scan in {}", repository, files.len(), repo_path.display());
+    
+    scan_collected(&files, repository, repo_path, opts, &mut outcome);
+    outcome
+}
+
+/// Scan exactly the listed files (paths relative to the repository root)
+///
+/// The walker and its filters (skip_dirs, extensions, depth, file limit) are
+/// bypassed. A listed path that is missing, not a regular file or outside the
+/// repository is recorded in `listed_file_errors` and the rest are still scanned.
+pub fn scan_files(
+    repo_path: &Path,
+    repository: &str,
+    listed: &[String],
+    opts: &ScanOptions,
+) -> ScanOutcome {
+    let mut outcome = ScanOutcome::default();
+    let mut files = Vec::new();
+    for file_path in listed {
+        let path = repo_path.join(file_path);
+        let reason = if !is_repo_relative(file_path) {
+            "outside the repository"
+        } else if path.is_file() {
+            files.push(path);
+            continue;
+        } else if path.exists() {
+            "not a regular file"
+        } else {
+            "not found"
+        };
+        warn!("[{}] Listed file {} not scanned: {}", repository, file_path, reason);
+        outcome.listed_file_errors.push(ListedFileError {
+            repository: repository.to_string(),
+            file_path: file_path.clone(),
+            reason: reason.to_string(),
+        });
+    }
+    
+    debug!("[{}] Scanning {} listed files in {}", repository, files.len(), repo_path.display());
+    
+    scan_collected(&files, repository, repo_path, opts, &mut outcome);
+    outcome
+}
+
+/// NIM hints in a repository's GitHub metadata (--github-metadata)
+///
+/// Each field (description, one topic, homepage URL) goes through the same
+/// line-by-line pass as a file, read as Markdown prose. Hosted NIM matches
+/// without a model name are dropped: an endpoint alone says nothing here.
+pub fn scan_metadata(repository: &str, fields: &[(MetadataField, String)], opts: &ScanOptions) -> Vec<MetadataHint> {
+    let mut hints = Vec::new();
+    for (field, text) in fields {
+        let pseudo_path = format!("{}.md", field.as_str());
+        let mut stats = FileStats::default();
+        let (local, hosted) = scan_content(text, Path::new(&pseudo_path), &pseudo_path, repository, opts, &mut stats);
+        let hint = |model_name, image_url, detection_rule| MetadataHint {
+            repository: repository.to_string(),
+            field: *field,
+            text: text.clone(),
+            model_name,
+            image_url,
+            detection_rule,
+        };
+        for m in local {
+            hints.push(hint(None, Some(m.image_url), m.detection_rule));
+        }
+        for m in hosted {
+            if let Some(model_name) = m.model_name {
+                hints.push(hint(Some(model_name), None, m.detection_rule));
+            }
+        }
+    }
+    hints.dedup_by(|a, b| a.field == b.field && a.text == b.text && a.model_name == b.model_name && a.image_url == b.image_url);
+    hints
+}
+
+/// Whether a listed path is relative and cannot leave the repository (no `..`)
+pub fn is_repo_relative(file_path: &str) -> bool {
+    !file_path.trim().is_empty()
+        && Path::new(file_path).components().all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+}
+
+/// Scan collected files in parallel and add their findings and stats to `outcome`
+fn scan_collected(files: &[PathBuf], repository: &str, repo_path: &Path, opts: &ScanOptions, outcome: &mut ScanOutcome) {
+    let variables = Variables::collect(repo_path, files);
+    let has_chart = files.iter().any(|path| is_chart_file(path));
+    let with_repository;
+    let opts = match variables.is_empty() && !has_chart {
+        true => opts,
+        false => {
+            with_repository = ScanOptions { variables, helm_values: opts.helm_values || has_chart, ..opts.clone() };
+            &with_repository
+        }
+    };
+    let results: Vec<_> = files
         .par_iter()
-        .map(|path| scan_file(path, repository, repo_path))
+        .map(|path| scan_file(path, repository, repo_path, opts))
         .collect();
     
-    // Aggregate results
-    for (local, hosted) in results {
-        all_local.extend(local);
-        all_hosted.extend(hosted);
+    // The scanner's own tests and fixtures are full of NIM strings that nobody deploys
+    let own_source = is_scanner_repo(repo_path);
+    if own_source {
+        info!("[{}] is this scanner's own source; its findings are marked as fixtures", repository);
+    }
+    let is_fixture = |file_path: &str| {
+        own_source || opts.fixture_files.as_ref().is_some_and(|matcher| in_scope(matcher, Path::new(file_path)))
+    };
+    for (mut local, mut hosted, stats) in results {
+        if is_fixture(&stats.file_path) {
+            local.iter_mut().for_each(|m| m.fixture = true);
+            hosted.iter_mut().for_each(|m| m.fixture = true);
+        }
+        outcome.local.extend(local);
+        outcome.hosted.extend(hosted);
+        outcome.files.push(stats);
+    }
+    
+    let mut dependencies = crate::dependencies::scan_manifests(files, repository, repo_path, &outcome.hosted);
+    for m in &mut dependencies {
+        m.fixture = is_fixture(&m.file_path);
     }
+    outcome.sdk_dependencies.extend(dependencies);
     
-    (all_local, all_hosted)
+    // Compose overrides: tie each image to the service merged from its directory's files
+    crate::compose::annotate_services(repo_path, files, &mut outcome.local);
+}
+
+// ============================================================================
+// Helm Values
+// ============================================================================
+
+/// Whether a file is a Helm chart's `Chart.yaml`
+fn is_chart_file(path: &Path) -> bool {
+    path.file_name().and_then(|n| n.to_str()).is_some_and(|name| name.eq_ignore_ascii_case("chart.yaml"))
+}
+
+/// Whether a file is a values file (`values.yaml`, `values-prod.yaml`, ...) outside `templates/`
+fn is_helm_values_file(path: &Path) -> bool {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("").to_lowercase();
+    let in_templates = path.parent().and_then(|dir| dir.file_name()).is_some_and(|dir| dir == "templates");
+    name.starts_with("values") && (name.ends_with(".yaml") || name.ends_with(".yml")) && !in_templates
+}
+
+/// A NIM image joined from the `repository` and `tag` keys of one values map
+#[derive(Debug, PartialEq, Eq)]
+struct HelmImage {
+    /// Dotted key path of the map (`image`, `nim.image`; empty at the top level)
+    key_path: String,
+    image: String,
+    /// 1-indexed line of the `repository:` key, best effort
+    line: usize,
+}
+
+/// Registry prefix of an image reference, if it names one (`nvcr.io/...` does, `nim/meta/...` doesn't)
+fn names_registry(repository: &str) -> bool {
+    repository.split_once('/').is_some_and(|(host, _)| host.contains('.') || host.contains(':'))
+}
+
+/// Walks parsed Helm values for maps holding both `repository` and `tag`
+struct HelmWalk<'a> {
+    lines: Vec<&'a str>,
+    /// Lines (0-indexed) of the document being walked
+    document: std::ops::Range<usize>,
+    /// `global.imageRegistry` (or `global.registry`), prefixed to repositories without a registry
+    global_registry: Option<String>,
+    found: Vec<HelmImage>,
+}
+
+impl HelmWalk<'_> {
+    fn walk(&mut self, key_path: String, value: &serde_yaml::Value) {
+        use serde_yaml::Value;
+        match value {
+            Value::Mapping(entries) => {
+                let text = |key: &str| match entries.get(key) {
+                    Some(Value::String(s)) => Some(s.trim().to_string()),
+                    Some(Value::Number(n)) => Some(n.to_string()),
+                    _ => None,
+                };
+                if let (Some(repository), Some(tag)) = (text("repository"), text("tag")) {
+                    self.push(&key_path, &repository, text("registry"), &tag);
+                }
+                for (key, child) in entries {
+                    if let Some(key) = key.as_str() {
+                        let child_path = match key_path.is_empty() {
+                            true => key.to_string(),
+                            false => format!("{}.{}", key_path, key),
+                        };
+                        self.walk(child_path, child);
+                    }
+                }
+            }
+            Value::Sequence(items) => {
+                for (i, child) in items.iter().enumerate() {
+                    self.walk(format!("{}.{}", key_path, i), child);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Record the joined image when it is a NIM, on the first unclaimed `repository:` line holding it
+    fn push(&mut self, key_path: &str, repository: &str, registry: Option<String>, tag: &str) {
+        let prefix = match names_registry(repository) {
+            true => None,
+            false => registry.or_else(|| self.global_registry.clone()).filter(|r| !r.is_empty()),
+        };
+        let mut image = match prefix {
+            Some(registry) => format!("{}/{}", registry.trim_end_matches('/'), repository.trim_start_matches('/')),
+            None => repository.to_string(),
+        };
+        if !image.starts_with("nvcr.io/nim/") || tag.is_empty() {
+            return;
+        }
+        if !image.rsplit('/').next().is_some_and(|name| name.contains(':') || name.contains('@')) {
+            image = format!("{}:{}", image, tag);
+        }
+        let claimed: Vec<usize> = self.found.iter().map(|f| f.line).collect();
+        let line = self.document.clone()
+            .find(|&i| !claimed.contains(&(i + 1)) && self.lines[i].contains("repository") && self.lines[i].contains(repository))
+            .map_or(self.document.start + 1, |i| i + 1);
+        self.found.push(HelmImage { key_path: key_path.to_string(), image, line });
+    }
+}
+
+/// Line ranges (0-indexed) of the `---`-separated documents of a YAML file,
+/// one per document serde_yaml reads from it
+///
+/// Text before the first `---` is a document only when it holds more than
+/// comments and blank lines.
+fn yaml_document_lines(lines: &[&str]) -> Vec<std::ops::Range<usize>> {
+    let separators: Vec<usize> = lines.iter().enumerate()
+        .filter(|(_, line)| line.strip_prefix("---").is_some_and(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace)))
+        .map(|(i, _)| i)
+        .collect();
+    let mut documents = Vec::new();
+    let mut start = 0;
+    for &separator in &separators {
+        documents.push(start..separator);
+        start = separator + 1;
+    }
+    documents.push(start..lines.len());
+    let leading_is_content = lines[documents[0].clone()].iter()
+        .any(|line| !line.trim().is_empty() && !line.trim_start().starts_with('#'));
+    if !separators.is_empty() && !leading_is_content {
+        documents.remove(0);
+    }
+    documents
+}
+
+/// NIM images of a Helm values file whose repository and tag are separate keys
+///
+/// A map holding `repository` and `tag` (such as `image:`) is one image; its
+/// `registry` key, else `global.imageRegistry`, is prefixed to a repository
+/// that names no registry. Every `---` document is read, with its own
+/// `global` and its images on its own lines. None when the first document
+/// doesn't parse as YAML; later documents after one that doesn't are not read.
+fn helm_values_images(content: &str) -> Option<Vec<HelmImage>> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut documents = Vec::new();
+    for document in serde_yaml::Deserializer::from_str(content) {
+        match serde_yaml::Value::deserialize(document) {
+            Ok(doc) => documents.push(doc),
+            Err(_) if documents.is_empty() => return None,
+            Err(_) => break,
+        }
+    }
+    // Separators the parser reads differently (`...`, a `---` in a block scalar): search the whole file
+    let mut spans = yaml_document_lines(&lines);
+    if spans.len() != documents.len() {
+        debug!("YAML document boundaries not matched; Helm images searched in the whole file");
+        spans = vec![0..lines.len(); documents.len()];
+    }
+
+    let mut walk = HelmWalk { lines, document: 0..0, global_registry: None, found: Vec::new() };
+    for (mut doc, span) in documents.into_iter().zip(spans) {
+        if let Err(e) = doc.apply_merge() {
+            debug!("Helm values merge keys not applied: {}", e);
+        }
+        let global = doc.get("global");
+        walk.global_registry = ["imageRegistry", "registry"]
+            .iter()
+            .find_map(|key| global?.get(key)?.as_str())
+            .map(|registry| registry.trim().to_string());
+        walk.document = span;
+        walk.walk(String::new(), &doc);
+    }
+    Some(walk.found)
+}
+
+/// Merge the joined images of a Helm values file into the line pass's Local NIM findings
+///
+/// The line pass sees `repository: nvcr.io/nim/...` without its tag (at best
+/// taking a `tag:` from a nearby line): that finding gets the map's tag. Any other image (a repository completed
+/// by a registry key) becomes a new finding. Both are `local_nim_helm`.
+fn add_helm_images(
+    content: &str,
+    relative_path: &str,
+    repository: &str,
+    opts: &ScanOptions,
+    local_matches: &mut Vec<LocalNimMatch>,
+) {
+    let Some(images) = helm_values_images(content) else {
+        debug!("[{}] {} doesn't parse as YAML; Helm images from the line pass only", repository, relative_path);
+        return;
+    };
+    let line_pass = local_matches.len();
+    for found in images {
+        let Some(mut m) = extract_local_nim(&found.image, found.line, relative_path, repository, None) else {
+            continue;
+        };
+        let key = match found.key_path.is_empty() {
+            true => "repository",
+            false => found.key_path.as_str(),
+        };
+        let context = format!("{}: {}", key, found.image);
+        let span = context.len() - found.image.len()..context.len();
+        let (match_context, context_truncated) = bounded_context(&context, span, opts.max_context_chars);
+        let same = local_matches[..line_pass].iter_mut()
+            .find(|l| l.line_number == found.line && l.image_url == m.image_url);
+        if let Some(l) = same {
+            if l.detection_rule == "local_nim_no_tag" {
+                (l.tag, l.detection_rule) = (m.tag, "local_nim_helm".to_string());
+                (l.match_context, l.context_truncated) = (match_context, context_truncated);
+            }
+            continue;
+        }
+        (m.match_context, m.context_truncated) = (match_context, context_truncated);
+        m.detection_rule = "local_nim_helm".to_string();
+        m.intent = LocalIntent::Deploy;
+        debug!("[{}] Found Local NIM in Helm values {}:{}: {}", repository, relative_path, found.line, key);
+        local_matches.push(m);
+    }
+    local_matches.sort_by_key(|m| m.line_number);
+}
+
+// ============================================================================
+// Repository Scheduling
+// ============================================================================
+
+/// Order in which to scan repositories: indices into `work`, biggest first
+///
+/// A large repository started last keeps one thread busy while the rest sit
+/// idle; starting it first (longest-processing-time-first) shortens that tail.
+/// Repositories of equal size keep their input order.
+pub fn largest_first(work: &[u64]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..work.len()).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse(work[i]));
+    order
 }
 
 // ============================================================================
 // Result Categorization
 // ============================================================================
 
-/// Categorize scan results by source type
-pub fn categorize_results(
-    local_matches: Vec<LocalNimMatch>,
-    hosted_matches: Vec<HostedNimMatch>,
-) -> (NimFindings, NimFindings) {
-    let mut source_code = NimFindings::new();
-    let mut actions_workflow = NimFindings::new();
-    
-    for m in local_matches {
-        match determine_source_type(&m.file_path) {
-            SourceType::SourceCode => source_code.local_nim.push(m),
-            SourceType::ActionsWorkflow => actions_workflow.local_nim.push(m),
-        }
+/// Categorize scan results by source type
+pub fn categorize_results(
+    local_matches: Vec<LocalNimMatch>,
+    hosted_matches: Vec<HostedNimMatch>,
+) -> (NimFindings, NimFindings) {
+    let mut source_code = NimFindings::new();
+    let mut actions_workflow = NimFindings::new();
+    
+    for m in local_matches {
+        match determine_source_type(&m.file_path) {
+            SourceType::SourceCode => source_code.local_nim.push(m),
+            SourceType::ActionsWorkflow => actions_workflow.local_nim.push(m),
+        }
+    }
+    
+    for m in hosted_matches {
+        match determine_source_type(&m.file_path) {
+            SourceType::SourceCode => source_code.hosted_nim.push(m),
+            SourceType::ActionsWorkflow => actions_workflow.hosted_nim.push(m),
+        }
+    }
+    
+    (source_code, actions_workflow)
+}
+
+/// Deduplicate results based on (repository, file_path, line_number)
+pub fn deduplicate_results(findings: &mut NimFindings) {
+    use std::collections::HashSet;
+    
+    // Deduplicate local_nim
+    let mut seen: HashSet<(String, String, usize)> = HashSet::new();
+    findings.local_nim.retain(|m| {
+        let key = (m.repository.clone(), m.file_path.clone(), m.line_number);
+        seen.insert(key)
+    });
+    
+    // Deduplicate hosted_nim (key must include model_name so multiple models on the same line are all kept)
+    let mut seen_hosted: HashSet<(String, String, usize, String)> = HashSet::new();
+    findings.hosted_nim.retain(|m| {
+        let model_key = m.model_name.as_deref().unwrap_or("").to_string();
+        let key = (m.repository.clone(), m.file_path.clone(), m.line_number, model_key);
+        seen_hosted.insert(key)
+    });
+    
+    // Deduplicate sdk_dependencies (a line may declare a package in several lists)
+    let mut seen_dependencies: HashSet<(String, String, usize, String, Option<String>)> = HashSet::new();
+    findings.sdk_dependencies.retain(|m| {
+        let key = (m.repository.clone(), m.file_path.clone(), m.line_number, m.package_name.clone(), m.section.clone());
+        seen_dependencies.insert(key)
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::{NotebookCell, NotebookCellKind};
+    use super::*;
+
+    #[test]
+    fn test_determine_source_type() {
+        assert_eq!(
+            determine_source_type("src/main.py"),
+            SourceType::SourceCode
+        );
+        assert_eq!(
+            determine_source_type(".github/workflows/deploy.yml"),
+            SourceType::ActionsWorkflow
+        );
+        assert_eq!(
+            determine_source_type(".github/workflows/test.yaml"),
+            SourceType::ActionsWorkflow
+        );
+        assert_eq!(
+            determine_source_type(".github/actions/test.yml"),
+            SourceType::SourceCode  // Not in workflows/
+        );
+    }
+
+    #[test]
+    fn test_parse_workflow_triggers() {
+        assert_eq!(parse_workflow_triggers("on: push\njobs: {}\n"), vec!["push"]);
+        assert_eq!(
+            parse_workflow_triggers("on: [push, workflow_dispatch]\n"),
+            vec!["push", "workflow_dispatch"]
+        );
+        assert_eq!(
+            parse_workflow_triggers("on: {push: {branches: [main]}}\n"),
+            vec!["push"]
+        );
+        assert_eq!(
+            parse_workflow_triggers("\
+on:
+  pull_request:
+    branches: [main]
+  schedule:
+    - cron: '0 2 * * *'
+    - cron: '30 4 * * 1'
+  workflow_dispatch:
+"),
+            vec!["pull_request", "schedule: 0 2 * * *", "schedule: 30 4 * * 1", "workflow_dispatch"]
+        );
+        // Malformed or trigger-less workflows leave the field empty
+        assert!(parse_workflow_triggers("on: [push\n").is_empty());
+        assert!(parse_workflow_triggers("name: no triggers\n").is_empty());
+
+        assert!(has_schedule_trigger(&["push".to_string(), "schedule: 0 2 * * *".to_string()]));
+        assert!(!has_schedule_trigger(&["workflow_dispatch".to_string()]));
+    }
+
+    #[test]
+    fn test_scan_workflow_attaches_triggers() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let workflows = temp_dir.path().join(".github/workflows");
+        std::fs::create_dir_all(&workflows).unwrap();
+        let content = "\
+on:
+  schedule:
+    - cron: '0 2 * * *'
+jobs:
+  test:
+    services:
+      nim:
+        image: nvcr.io/nim/nvidia/llama-3.2-nv-embedqa-1b-v2:1.10.0
+";
+        let nightly = workflows.join("nightly.yml");
+        std::fs::write(&nightly, content).unwrap();
+        let (local, _, _) = scan_file(&nightly, "test/repo", temp_dir.path(), &ScanOptions::default());
+        assert_eq!(local.len(), 1);
+        assert_eq!(local[0].workflow_triggers, vec!["schedule: 0 2 * * *"]);
+
+        // The same content outside .github/workflows is not a workflow
+        let compose = temp_dir.path().join("compose.yml");
+        std::fs::write(&compose, content).unwrap();
+        let (local, _, _) = scan_file(&compose, "test/repo", temp_dir.path(), &ScanOptions::default());
+        assert_eq!(local.len(), 1);
+        assert!(local[0].workflow_triggers.is_empty());
+    }
+
+    #[test]
+    fn test_extract_local_nim_with_tag() {
+        let line = "image: nvcr.io/nim/nvidia/llama-3.2-nv-embedqa-1b-v2:1.10.0";
+        let result = extract_local_nim(line, 1, "docker-compose.yaml", "test/repo", None);
+        
+        assert!(result.is_some());
+        let m = result.unwrap();
+        assert_eq!(m.image_url, "nvcr.io/nim/nvidia/llama-3.2-nv-embedqa-1b-v2");
+        assert_eq!(m.tag, "1.10.0");
+    }
+
+    #[test]
+    fn test_extract_local_nim_without_tag() {
+        let line = "FROM nvcr.io/nim/nvidia/nemo-retriever";
+        let result = extract_local_nim(line, 1, "Dockerfile", "test/repo", None);
+        
+        assert!(result.is_some());
+        let m = result.unwrap();
+        assert_eq!(m.image_url, "nvcr.io/nim/nvidia/nemo-retriever");
+        assert_eq!(m.tag, "latest");
+    }
+
+    #[test]
+    fn test_extract_local_nim_prose_fragments() {
+        let long_tag = format!("see nvcr.io/nim/meta/llama3-8b-instruct:{}", "1".repeat(129));
+        let cases = [
+            ("see nvcr.io/nim/nvidia/nv-embedqa-e5-v5:the model card for details",
+             "nvcr.io/nim/nvidia/nv-embedqa-e5-v5", "latest", "local_nim_prose_tag"),
+            ("Pull nvcr.io/nim/meta/llama3-8b-instruct:1.0.0.", "nvcr.io/nim/meta/llama3-8b-instruct", "1.0.0", "local_nim_full"),
+            ("Pinned to _nvcr.io/nim/meta/llama3-8b-instruct:1.0.0_ for now", "nvcr.io/nim/meta/llama3-8b-instruct", "1.0.0", "local_nim_full"),
+            ("The image is nvcr.io/nim/nvidia/nemo-retriever.", "nvcr.io/nim/nvidia/nemo-retriever", "latest", "local_nim_no_tag"),
+            ("Tags of nvcr.io/nim/nvidia/nemo-retriever:...", "nvcr.io/nim/nvidia/nemo-retriever", "latest", "local_nim_no_tag"),
+            ("nvcr.io/nim/meta/llama3-8b-instruct:-rc1", "nvcr.io/nim/meta/llama3-8b-instruct", "latest", "local_nim_prose_tag"),
+            (long_tag.as_str(), "nvcr.io/nim/meta/llama3-8b-instruct", "latest", "local_nim_prose_tag"),
+            // Alphabetic tags that aren't stop-words are kept
+            ("image: nvcr.io/nim/meta/llama3-8b-instruct:Latest", "nvcr.io/nim/meta/llama3-8b-instruct", "Latest", "local_nim_full"),
+            ("image: nvcr.io/nim/meta/llama3-8b-instruct:stable", "nvcr.io/nim/meta/llama3-8b-instruct", "stable", "local_nim_full"),
+        ];
+        for (line, image_url, tag, rule) in cases {
+            let m = extract_local_nim(line, 1, "docs.txt", "test/repo", None).unwrap();
+            assert_eq!((m.image_url.as_str(), m.tag.as_str(), m.detection_rule.as_str()), (image_url, tag, rule), "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_extract_ngc_cli_commands() {
+        let cases = [
+            ("ngc registry image pull nim/nvidia/llama-3.1-8b-instruct:1.3.0",
+             Some(("nvcr.io/nim/nvidia/llama-3.1-8b-instruct", "1.3.0", "local_nim_ngc_cli"))),
+            ("ngc registry image pull --org nim meta/llama-3.1-8b-instruct:1.3.3 && echo done",
+             Some(("nvcr.io/nim/meta/llama-3.1-8b-instruct", "1.3.3", "local_nim_ngc_cli"))),
+            ("ngc registry image info --org=nim --team meta \"llama3-8b-instruct\"",
+             Some(("nvcr.io/nim/meta/llama3-8b-instruct", "latest", "local_nim_ngc_cli"))),
+            ("ngc registry image pull nim/nvidia/nv-embedqa-e5-v5 --format_type json",
+             Some(("nvcr.io/nim/nvidia/nv-embedqa-e5-v5", "latest", "local_nim_ngc_cli"))),
+            ("ngc registry resource download-version --dest /models nim/meta/llama-3.1-8b-instruct-weights:1.0",
+             Some(("nvcr.io/nim/meta/llama-3.1-8b-instruct-weights", "1.0", "ngc_resource_download"))),
+            ("ngc registry resource download-version --org nim nvidia/nemo-retriever-models",
+             Some(("nvcr.io/nim/nvidia/nemo-retriever-models", "latest", "ngc_resource_download"))),
+            // Another org, or no org for a path without one, is no NIM
+            ("ngc registry image pull --org nvidia nvidia/pytorch:24.01-py3", None),
+            ("ngc registry image pull meta/llama-3.1-8b-instruct:1.3.3", None),
+            ("ngc registry image info --org nim llama3-8b-instruct", None),
+            ("ngc registry image list nim/*", None),
+        ];
+        for (line, expected) in cases {
+            let m = extract_local_nim(line, 1, "pull.sh", "test/repo", None);
+            let found = m.as_ref().map(|m| (m.image_url.as_str(), m.tag.as_str(), m.detection_rule.as_str()));
+            assert_eq!(found, expected, "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_scan_ngc_cli_pull_intent() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("setup.sh");
+        std::fs::write(&path, "\
+ngc registry image info --org nim --team meta llama3-8b-instruct:1.0.0
+ngc registry image pull --org nim \\
+    --team meta llama3-8b-instruct:1.0.0
+ngc registry resource download-version nim/meta/llama3-8b-instruct-weights:1.0
+").unwrap();
+        let (local, _, _) = scan_file(&path, "test/repo", temp_dir.path(), &ScanOptions::default());
+        let found: Vec<(usize, &str, LocalIntent)> = local.iter().map(|m| (m.line_number, m.detection_rule.as_str(), m.intent)).collect();
+        // The continued pull is reported on its last line, where the path is
+        assert_eq!(found, vec![
+            (1, "local_nim_ngc_cli", LocalIntent::Reference),
+            (3, "local_nim_ngc_cli", LocalIntent::Pull),
+            (4, "ngc_resource_download", LocalIntent::Pull),
+        ]);
+        assert_eq!(local[1].image_url, "nvcr.io/nim/meta/llama3-8b-instruct");
+    }
+
+    #[test]
+    fn test_scan_prose_tag_is_reference() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("start.sh");
+        std::fs::write(&path, "# docker run nvcr.io/nim/meta/llama3-8b-instruct:the GPU flags below\ndocker run nvcr.io/nim/meta/llama3-8b-instruct:1.0.0.\n").unwrap();
+        let (local, _, _) = scan_file(&path, "test/repo", temp_dir.path(), &ScanOptions::default());
+        let found: Vec<(&str, &str, LocalIntent)> = local.iter().map(|m| (m.tag.as_str(), m.detection_rule.as_str(), m.intent)).collect();
+        assert_eq!(found, vec![
+            ("latest", "local_nim_prose_tag", LocalIntent::Reference),
+            ("1.0.0", "local_nim_full", LocalIntent::Run),
+        ]);
+    }
+
+    #[test]
+    fn test_extract_hosted_nim_endpoint() {
+        let line = r#"base_url = "https://ai.api.nvidia.com/v1/chat""#;
+        let result = extract_hosted_nim(line, 1, "client.py", "test/repo", None, &HostedHosts::default());
+        
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].endpoint_url.as_deref(), Some("https://ai.api.nvidia.com/v1/chat"));
+    }
+
+    #[test]
+    fn test_hosted_endpoint_hosts() {
+        for url in [
+            "https://integrate.api.nvidia.com/v1",
+            "https://ai.api.nvidia.com/v1/cv/baidu/paddleocr",
+            "https://build.api.nvidia.com/v1",
+            "https://stg.api.nvidia.com/v1/chat/completions",
+            "https://api.stg.nvidia.com/v1",
+            "https://integrate.api.stg.nvidia.com/v1",
+            "https://ai.api.stg.nvidia.com/v1/retrieval/nvidia/embeddings",
+        ] {
+            let line = format!(r#"base_url = "{}""#, url);
+            let result = extract_hosted_nim(&line, 1, "client.py", "test/repo", None, &HostedHosts::default());
+            assert_eq!(result.len(), 1, "{}", url);
+            assert_eq!(result[0].endpoint_url.as_deref(), Some(url));
+        }
+
+        // Unlisted NVIDIA hosts are not Hosted NIM endpoints
+        assert!(extract_hosted_nim(r#"url = "https://docs.api.nvidia.com/v1""#, 1, "a.py", "r", None, &HostedHosts::default()).is_empty());
+        assert!(extract_hosted_nim(r#"url = "https://stg.nvidia.com/v1""#, 1, "a.py", "r", None, &HostedHosts::default()).is_empty());
+    }
+
+    #[test]
+    fn test_classify_environment() {
+        let cases = [
+            (Some("https://integrate.api.nvidia.com/v1"), None, Environment::Production),
+            (Some("https://ai.api.nvidia.com/v1/cv/baidu/paddleocr"), None, Environment::Production),
+            (Some("https://api.nvcf.nvidia.com/v2/nvcf/pexec/functions/x"), None, Environment::Production),
+            (Some("https://stg.api.nvidia.com/v1"), None, Environment::Staging),
+            (Some("https://API.STG.NVIDIA.COM/v1"), None, Environment::Staging),
+            (Some("https://integrate.api.stg.nvidia.com/v1"), Some("meta/llama-3.1-8b-instruct"), Environment::Staging),
+            (Some("https://ai.api.stg.nvidia.com:443/v1"), None, Environment::Staging),
+            // Production host, staging model
+            (Some("https://integrate.api.nvidia.com/v1"), Some("stg/deepseek-ai/deepseek-r1"), Environment::Staging),
+            (None, Some("stg/nvidia/llama-3.3-nemotron-super-49b-v1"), Environment::Staging),
+            (None, Some("nvidia/llama-3.3-nemotron-super-49b-v1"), Environment::Production),
+            (None, None, Environment::Production),
+        ];
+        for (endpoint, model, expected) in cases {
+            assert_eq!(HostedHosts::default().environment(endpoint, model), expected, "{:?} {:?}", endpoint, model);
+        }
+    }
+
+    #[test]
+    fn test_scan_configured_hosted_hosts() {
+        let settings: crate::models::ScanSettings = serde_yaml::from_str(r#"
+hosted_hosts:
+  - API.nvidia.eu
+  - host: "*.sovereign.example.org"
+    environment: staging
+"#).unwrap();
+        let extra: Vec<_> = settings.hosted_hosts.iter().map(|entry| entry.host_and_environment()).collect();
+        let opts = ScanOptions { hosted_hosts: HostedHosts::new(&extra).unwrap(), ..Default::default() };
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("client.py");
+        std::fs::write(&path, r#"EU_URL = "https://api.nvidia.eu/v1/chat/completions"
+SOVEREIGN_URL = "https://gov-1.sovereign.example.org/v1"
+BARE_DOMAIN = "https://sovereign.example.org/v1"
+LOOKALIKE = "https://api.nvidia.eu.example.com/v1"
+OTHER = "https://api.example.eu/v1"
+"#).unwrap();
+        let (_, hosted, _) = scan_file(&path, "test/repo", temp_dir.path(), &opts);
+        let found: Vec<_> = hosted.iter()
+            .map(|m| (m.line_number, m.endpoint_host.as_deref(), m.environment))
+            .collect();
+        assert_eq!(found, vec![
+            (1, Some("api.nvidia.eu"), Environment::Production),
+            (2, Some("gov-1.sovereign.example.org"), Environment::Staging),
+        ]);
+
+        // Without the configuration none of them is an endpoint
+        let (_, hosted, _) = scan_file(&path, "test/repo", temp_dir.path(), &ScanOptions::default());
+        assert!(hosted.is_empty(), "{:?}", hosted);
+        assert!(validate_host_name("nvidia.eu/v1").is_err());
+    }
+
+    #[test]
+    fn test_scan_sdk_dependencies() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::write(root.join("requirements.txt"), "openai>=1.30\nlangchain-nvidia-ai-endpoints==0.3.5\n").unwrap();
+        std::fs::create_dir_all(root.join("web")).unwrap();
+        std::fs::write(root.join("web/package.json"), r#"{"dependencies": {"openai": "^4.52.0"}}"#).unwrap();
+        std::fs::write(root.join("app.py"), r#"from openai import OpenAI
+client = OpenAI(base_url="https://integrate.api.nvidia.com/v1", api_key=key)
+"#).unwrap();
+
+        // Dependency manifests are read even when documentation (`.txt`) is skipped
+        let opts = ScanOptions { skip_docs: true, ..Default::default() };
+        let outcome = scan_directory(root, "test/repo", &opts);
+        let mut found: Vec<(&str, usize, &str, Option<&str>)> = outcome.sdk_dependencies.iter()
+            .map(|m| (m.file_path.as_str(), m.line_number, m.package_name.as_str(), m.version_constraint.as_deref()))
+            .collect();
+        found.sort();
+        assert_eq!(found, vec![
+            ("requirements.txt", 1, "openai", Some(">=1.30")),
+            ("requirements.txt", 2, "langchain-nvidia-ai-endpoints", Some("==0.3.5")),
+            ("web/package.json", 1, "openai", Some("^4.52.0")),
+        ]);
+
+        // Without an NVIDIA-pointed client, `openai` says nothing about NVIDIA
+        std::fs::remove_file(root.join("app.py")).unwrap();
+        let outcome = scan_directory(root, "test/repo", &opts);
+        let packages: Vec<&str> = outcome.sdk_dependencies.iter().map(|m| m.package_name.as_str()).collect();
+        assert_eq!(packages, vec!["langchain-nvidia-ai-endpoints"]);
+    }
+
+    #[test]
+    fn test_classify_local_intent() {
+        use LocalIntent::*;
+        let code = IntentHints::default();
+        let manifest = IntentHints { manifest: true, doc_prose: false };
+        let prose = IntentHints { manifest: false, doc_prose: true };
+        let cases = [
+            // Dockerfiles
+            ("FROM nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3.3", code, BaseImage),
+            ("FROM --platform=linux/amd64 nvcr.io/nim/nvidia/nv-embedqa-e5-v5:1.0.0 AS nim", code, BaseImage),
+            ("COPY --from=nvcr.io/nim/nvidia/nv-rerankqa-mistral-4b-v3:1.0.2 /opt/nim /opt/nim", code, BaseImage),
+            // Shell, notebooks and workflow steps
+            ("docker pull nvcr.io/nim/meta/llama-3.1-8b-instruct:latest", code, Pull),
+            ("  run: docker pull nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3.3", manifest, Pull),
+            ("enroot import docker://nvcr.io#nim/meta/llama-3.1-8b-instruct:1.3.3", code, Pull),
+            ("docker run -it --rm --gpus all -p 8000:8000 nvcr.io/nim/meta/llama3-8b-instruct:1.0.0", code, Run),
+            ("\"!docker run -d --name nim nvcr.io/nim/nvidia/nv-embedqa-e5-v5:1.0.0\\n\",", code, Run),
+            ("docker pull nvcr.io/nim/meta/llama3-8b-instruct:1.0.0 && docker run nvcr.io/nim/meta/llama3-8b-instruct:1.0.0", code, Run),
+            ("client.containers.run(\"nvcr.io/nim/meta/llama3-8b-instruct:1.0.0\", detach=True)", code, Run),
+            // Manifests
+            ("    image: nvcr.io/nim/meta/llama-3.1-70b-instruct:1.3.3", manifest, Deploy),
+            ("        - image: \"nvcr.io/nim/nvidia/nv-embedqa-e5-v5:1.0.0\"", manifest, Deploy),
+            ("  \"image\": \"nvcr.io/nim/meta/llama3-8b-instruct:1.0.0\",", manifest, Deploy),
+            ("  repository: nvcr.io/nim/meta/llama-3.1-8b-instruct", manifest, Deploy),
+            // Ambiguous: plain strings, an image: key outside a manifest, prose
+            ("NIM_IMAGE = \"nvcr.io/nim/meta/llama3-8b-instruct:1.0.0\"", code, Reference),
+            ("image: nvcr.io/nim/meta/llama3-8b-instruct:1.0.0", code, Reference),
+            ("Run `docker run nvcr.io/nim/meta/llama3-8b-instruct:1.0.0` to start the NIM.", prose, Reference),
+        ];
+        for (line, hints, expected) in cases {
+            assert_eq!(classify_local_intent(line, hints), expected, "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_scan_local_intent_context() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("README.md"), "\
+Pull `nvcr.io/nim/meta/llama3-8b-instruct:1.0.0` first.
+
+```bash
+docker run -it --rm \\
+    --gpus all \\
+    nvcr.io/nim/meta/llama3-8b-instruct:1.0.0
+```
+").unwrap();
+
+        let outcome = scan_directory(temp_dir.path(), "test/repo", &ScanOptions::default());
+        let intents: Vec<(usize, LocalIntent)> = outcome.local.iter().map(|m| (m.line_number, m.intent)).collect();
+        assert_eq!(intents, vec![(1, LocalIntent::Reference), (6, LocalIntent::Run)]);
+    }
+
+    #[test]
+    fn test_scan_usage_kind() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join(".github/workflows")).unwrap();
+        std::fs::create_dir_all(root.join("deploy")).unwrap();
+        std::fs::write(root.join("Dockerfile"), "FROM nvcr.io/nim/meta/llama3-8b-instruct:1.0.0\n").unwrap();
+        std::fs::write(root.join("start.sh"), "\
+docker pull nvcr.io/nim/meta/llama3-8b-instruct:1.0.0
+docker run -d --gpus all \\
+    nvcr.io/nim/meta/llama3-8b-instruct:1.0.0
+kubectl run nim --image=nvcr.io/nim/meta/llama3-8b-instruct:1.0.0
+").unwrap();
+        std::fs::write(root.join("docker-compose.yml"), "\
+services:
+  nim:
+    image: nvcr.io/nim/meta/llama3-8b-instruct:1.0.0
+").unwrap();
+        std::fs::write(root.join("deploy/nim.yaml"), "\
+apiVersion: v1
+kind: Pod
+metadata:
+  name: nim
+spec:
+  containers:
+    - name: nim
+      image: nvcr.io/nim/meta/llama3-8b-instruct:1.0.0
+").unwrap();
+        std::fs::write(root.join(".github/workflows/ci.yml"), "\
+on: push
+jobs:
+  test:
+    runs-on: ubuntu-latest
+    container: nvcr.io/nim/meta/llama3-8b-instruct:1.0.0
+    services:
+      embedder:
+        image: nvcr.io/nim/nvidia/nv-embedqa-e5-v5:1.0.0
+    steps:
+      - run: docker run nvcr.io/nim/meta/llama3-8b-instruct:1.0.0
+").unwrap();
+        std::fs::write(root.join(".gitlab-ci.yml"), "\
+test:
+  services:
+  - name: nvcr.io/nim/nvidia/nv-embedqa-e5-v5:1.0.0
+    alias: embedder
+").unwrap();
+        std::fs::write(root.join("README.md"), "\
+Use `nvcr.io/nim/meta/llama3-8b-instruct:1.0.0`.
+
+```yaml
+services:
+  nim:
+    image: nvcr.io/nim/meta/llama3-8b-instruct:1.0.0
+```
+
+```yaml
+spec:
+  containers:
+  - image: nvcr.io/nim/meta/llama3-8b-instruct:1.0.0
+```
+").unwrap();
+
+        let outcome = scan_directory(root, "test/repo", &ScanOptions::default());
+        let mut kinds: Vec<(String, usize, UsageKind)> = outcome.local.iter()
+            .map(|m| (m.file_path.clone(), m.line_number, m.usage_kind))
+            .collect();
+        kinds.sort();
+        assert_eq!(kinds, vec![
+            (".github/workflows/ci.yml".to_string(), 5, UsageKind::CiService),
+            (".github/workflows/ci.yml".to_string(), 8, UsageKind::CiService),
+            (".github/workflows/ci.yml".to_string(), 10, UsageKind::DockerRun),
+            (".gitlab-ci.yml".to_string(), 3, UsageKind::CiService),
+            ("Dockerfile".to_string(), 1, UsageKind::DockerfileFrom),
+            ("README.md".to_string(), 1, UsageKind::Other),
+            ("README.md".to_string(), 6, UsageKind::ComposeImage),
+            ("README.md".to_string(), 12, UsageKind::K8sContainer),
+            ("deploy/nim.yaml".to_string(), 8, UsageKind::K8sContainer),
+            ("docker-compose.yml".to_string(), 3, UsageKind::ComposeImage),
+            ("start.sh".to_string(), 1, UsageKind::Other),
+            ("start.sh".to_string(), 3, UsageKind::DockerRun),
+            ("start.sh".to_string(), 4, UsageKind::K8sContainer),
+        ]);
+    }
+
+    #[test]
+    fn test_yaml_key_path() {
+        let lines = [
+            "jobs:",
+            "  test:",
+            "    services:",
+            "    - name: embedder",
+            "      alias: nim",
+            "    steps:",
+            "      - run: make test",
+        ];
+        assert_eq!(yaml_key_path(&lines, 4), vec!["alias", "services", "test", "jobs"]);
+        assert_eq!(yaml_key_path(&lines, 3), vec!["name", "services", "test", "jobs"]);
+        assert_eq!(yaml_key_path(&lines, 6), vec!["run", "steps", "test", "jobs"]);
+        assert_eq!(yaml_key_path(&["nvcr.io/nim/meta/llama3-8b-instruct"], 0), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn test_classify_invoke_style() {
+        use InvokeStyle::*;
+        let cases = [
+            // OpenAI-compatible base URLs and routes
+            ("https://integrate.api.nvidia.com/v1", OpenaiCompatible),
+            ("https://integrate.api.nvidia.com/v1/", OpenaiCompatible),
+            ("https://integrate.api.nvidia.com/v1/chat/completions", OpenaiCompatible),
+            ("https://integrate.api.nvidia.com/v1/completions", OpenaiCompatible),
+            ("https://integrate.api.nvidia.com/v1/embeddings", OpenaiCompatible),
+            ("https://integrate.api.nvidia.com/v1/models", OpenaiCompatible),
+            ("https://ai.api.nvidia.com/v1/retrieval/nvidia/embeddings", OpenaiCompatible),
+            ("https://stg.api.nvidia.com/v1/chat/completions", OpenaiCompatible),
+            ("https://integrate.api.nvidia.com/v1\",", OpenaiCompatible),
+            // Per-model paths
+            ("https://ai.api.nvidia.com/v1/cv/baidu/paddleocr", ModelPath),
+            ("https://ai.api.nvidia.com/v1/cv/nvidia/nemoretriever-page-elements-v2", ModelPath),
+            ("https://ai.api.nvidia.com/v1/retrieval/nvidia/llama-3_2-nv-rerankqa-1b-v2/reranking", ModelPath),
+            ("https://ai.api.nvidia.com/v1/genai/black-forest-labs/flux.1-dev", ModelPath),
+            ("https://ai.api.nvidia.com/v2/cv/nvidia/nv-grounding-dino?async=true", ModelPath),
+            ("https://ai.api.nvidia.com/v1beta/vlm/nvidia/llama-3_1-nemotron-nano-vl-8b-v1/infer", ModelPath),
+            ("https://ai.api.nvidia.com/v1/cv/nvidia/{model}", ModelPath),
+            // NVCF invocation
+            ("https://api.nvcf.nvidia.com/v2/nvcf/pexec/functions/0c5a7e4b-2f3d-4c8e-9a1b-6d7e8f9a0b1c", NvcfPexec),
+            ("https://api.nvcf.nvidia.com/v2/nvcf/pexec/functions/{FUNCTION_ID}", NvcfPexec),
+            ("https://api.nvcf.nvidia.com/v2/nvcf/exec/functions/0c5a7e4b-2f3d-4c8e-9a1b-6d7e8f9a0b1c", NvcfPexec),
+            // Shapes we can't tell
+            ("https://ai.api.nvidia.com/v1/chat", Unknown),
+            ("https://integrate.api.nvidia.com", Unknown),
+            ("https://ai.api.nvidia.com/cv/nvidia/model", Unknown),
+        ];
+        for (url, expected) in cases {
+            assert_eq!(classify_invoke_style(Some(url)), expected, "{}", url);
+        }
+        assert_eq!(classify_invoke_style(None), Unknown);
+    }
+
+    #[test]
+    fn test_scan_staging_environment() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("client.py");
+        std::fs::write(&path, "\
+prod = OpenAI(base_url=\"https://integrate.api.nvidia.com/v1\")
+stg = OpenAI(base_url=\"https://api.stg.nvidia.com/v1\")
+llm = ChatNVIDIA(model=\"stg/deepseek-ai/deepseek-r1\")
+").unwrap();
+
+        let (_, hosted, _) = scan_file(&path, "test/repo", temp_dir.path(), &ScanOptions::default());
+        let envs: Vec<Environment> = hosted.iter().map(|m| m.environment).collect();
+        assert_eq!(envs, vec![Environment::Production, Environment::Staging, Environment::Staging]);
+    }
+
+    #[test]
+    fn test_extract_hosted_nim_model() {
+        let line = r#"model = "nvidia/llama-3.1-nemotron-70b-instruct""#;
+        let result = extract_hosted_nim(line, 1, "client.py", "test/repo", None, &HostedHosts::default());
+        
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].model_name.as_deref(), Some("nvidia/llama-3.1-nemotron-70b-instruct"));
+    }
+
+    #[test]
+    fn test_extract_hosted_nim_build_page_url() {
+        let model = |line: &str| {
+            let result = extract_hosted_nim(line, 1, "README.md", "test/repo", None, &HostedHosts::default());
+            assert!(result.len() <= 1, "{:?}", result);
+            result.into_iter().next().map(|m| {
+                assert_eq!(m.detection_rule, "build_page_url");
+                assert_eq!(m.endpoint_url, None);
+                m.model_name.unwrap()
+            })
+        };
+        // Versions the catalog slug spells with `_`
+        assert_eq!(model("Try it at https://build.nvidia.com/meta/llama-3_1-405b-instruct").as_deref(),
+                   Some("meta/llama-3.1-405b-instruct"));
+        assert_eq!(model("[Nemotron Super](https://build.nvidia.com/nvidia/llama-3_3-nemotron-super-49b-v1_5)").as_deref(),
+                   Some("nvidia/llama-3.3-nemotron-super-49b-v1.5"));
+        assert_eq!(catalog_model_name("qwen", "qwen2_5-coder-32b-instruct").as_deref(), Some("qwen/qwen2.5-coder-32b-instruct"));
+        // Trailing path segments, query strings and sentence ends
+        assert_eq!(model("See https://build.nvidia.com/meta/llama-3_1-70b-instruct/modelcard for details").as_deref(),
+                   Some("meta/llama-3.1-70b-instruct"));
+        assert_eq!(model("https://build.nvidia.com/nvidia/nv-embedqa-e5-v5/deploy?environment=linux").as_deref(),
+                   Some("nvidia/nv-embedqa-e5-v5"));
+        assert_eq!(model("Get a key from https://build.nvidia.com/mistralai/mixtral-8x7b-instruct.").as_deref(),
+                   Some("mistralai/mixtral-8x7b-instruct"));
+        // An `_` that is no version separator stays
+        assert_eq!(catalog_model_name("nvidia", "nvolveqa_40k").as_deref(), Some("nvidia/nvolveqa_40k"));
+        assert_eq!(catalog_model_name("nvidia", ""), None);
+        // Catalog pages that are no model
+        assert_eq!(model("https://build.nvidia.com/explore/discover"), None);
+        assert_eq!(model("https://build.nvidia.com/models"), None);
+    }
+
+    #[test]
+    fn test_extract_hosted_nim_chatnvidia() {
+        let line = r#"llm = ChatNVIDIA(model="nvidia/llama-3.1-nemotron")"#;
+        let result = extract_hosted_nim(line, 1, "chain.py", "test/repo", None, &HostedHosts::default());
+        
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].model_name.as_deref(), Some("nvidia/llama-3.1-nemotron"));
+    }
+
+    #[test]
+    fn test_extract_hosted_nim_doc_prose() {
+        let line = "for nvidia/llama-3.2-nv-embedqa-1b-v2 model the Llama 3.2 Community License";
+        let result = extract_hosted_nim(line, 1, "deploy/README.md", "test/repo", None, &HostedHosts::default());
+        assert!(!result.is_empty());
+        assert_eq!(result[0].model_name.as_deref(), Some("nvidia/llama-3.2-nv-embedqa-1b-v2"));
+
+        let line2 = "nvidia/llama-3.2-nv-embedqa-1b-v2model the Llama"; // typo: v2model
+        let result2 = extract_hosted_nim(line2, 1, "README.md", "test/repo", None, &HostedHosts::default());
+        assert!(!result2.is_empty());
+        assert_eq!(result2[0].model_name.as_deref(), Some("nvidia/llama-3.2-nv-embedqa-1b-v2"));
+    }
+
+    #[test]
+    fn test_detection_rule() {
+        let rule = |line: &str| {
+            let result = extract_hosted_nim(line, 1, "app.py", "test/repo", None, &HostedHosts::default());
+            assert_eq!(result.len(), 1, "{}", line);
+            result[0].detection_rule.clone()
+        };
+        assert_eq!(rule(r#"llm = ChatNVIDIA(model_name="meta/llama-3.1-8b-instruct")"#), "model_name_assign");
+        assert_eq!(rule(r#"llm = ChatNVIDIA(model="meta/llama-3.1-8b-instruct")"#), "model_assign");
+        assert_eq!(rule(r#"nim_model: str = "meta/llama-3.1-8b-instruct""#), "annotated_model_assign");
+        assert_eq!(rule("url = 'https://ai.api.nvidia.com/v1/retrieval/nvidia/nv-rerankqa-mistral-4b-v3/reranking'"), "endpoint_url_model");
+        assert_eq!(rule(r#"base_url = "https://integrate.api.nvidia.com/v1""#), "hosted_endpoint");
+        assert_eq!(rule("https://api.nvcf.nvidia.com/v2/nvcf/pexec/functions/0a1b2c3d-4e5f-6789-abcd-ef0123456789"), "nvcf_endpoint");
+        assert_eq!(rule("for nvidia/llama-3.2-nv-embedqa-1b-v2 model the Llama 3.2 Community License"), "doc_prose_org_model");
+
+        // A model that isn't whitelisted falls back to the endpoint rule
+        assert_eq!(rule(r#"client(base_url="https://integrate.api.nvidia.com/v1", model="acme/private")"#), "hosted_endpoint");
+
+        let local = extract_local_nim("image: nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3.0", 1, "compose.yaml", "test/repo", None);
+        assert_eq!(local.unwrap().detection_rule, "local_nim_full");
+    }
+
+    #[test]
+    fn test_extract_hosted_nim_model_name_assign() {
+        let line = r#"      model_name: "nvidia/llama-3.2-nv-embedqa-1b-v2"#;
+        let result = extract_hosted_nim(line, 1, "docs/03-configuration.md", "test/data-flywheel", None, &HostedHosts::default());
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].model_name.as_deref(), Some("nvidia/llama-3.2-nv-embedqa-1b-v2"));
+    }
+
+    #[test]
+    fn test_extract_hosted_nim_env_or_config_model() {
+        // As in .ipynb JSON: os.environ["APP_EMBEDDINGS_MODELNAME"] = \"nvidia/llama-3.2-nv-embedqa-1b-v2\"
+        let line = r#"    "os.environ[\"APP_EMBEDDINGS_MODELNAME\"] = \"nvidia/llama-3.2-nv-embedqa-1b-v2\"\n","#;
+        let result = extract_hosted_nim(line, 1, "notebooks/get_started_nvidia_api.ipynb", "test/aiq", None, &HostedHosts::default());
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].model_name.as_deref(), Some("nvidia/llama-3.2-nv-embedqa-1b-v2"));
+
+        // Normal Python: ] = "nvidia/..."
+        let line2 = r#"os.environ["APP_EMBEDDINGS_MODELNAME"] = "nvidia/llama-3.2-nv-embedqa-1b-v2""#;
+        let result2 = extract_hosted_nim(line2, 1, "config.py", "test/repo", None, &HostedHosts::default());
+        assert_eq!(result2.len(), 1);
+        assert_eq!(result2[0].model_name.as_deref(), Some("nvidia/llama-3.2-nv-embedqa-1b-v2"));
+    }
+
+    #[test]
+    fn test_scan_python_annotated_model_settings() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("settings.py");
+        std::fs::write(&path, "\
+from typing import Final
+from pydantic import Field
+from pydantic_settings import BaseSettings
+
+NIM_LLM_MODEL: Final = \"nvidia/llama-3.1-nemotron-70b-instruct\"
+
+class Settings(BaseSettings):
+    nim_model: str = \"meta/llama-3.1-8b-instruct\"
+    embedder: str = Field(default=\"nvidia/nv-embedqa-e5-v5\", description=\"Embedding model\")
+    image_format: str = \"png/rgb\"
+    output_format: str = \"nvidia/rgb\"
+").unwrap();
+
+        let (_, hosted, _) = scan_file(&path, "test/repo", temp_dir.path(), &ScanOptions::default());
+        let found: Vec<(usize, Option<&str>)> = hosted
+            .iter()
+            .map(|m| (m.line_number, m.model_name.as_deref()))
+            .collect();
+        assert_eq!(found, vec![
+            (5, Some("nvidia/llama-3.1-nemotron-70b-instruct")),
+            (8, Some("meta/llama-3.1-8b-instruct")),
+            (9, Some("nvidia/nv-embedqa-e5-v5")),
+        ]);
+    }
+
+    #[test]
+    fn test_capture_call_params_chatnvidia_kwargs() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("chain.py");
+        std::fs::write(&path, "\
+from langchain_nvidia_ai_endpoints import ChatNVIDIA
+
+llm = ChatNVIDIA(model=\"meta/llama-3.1-70b-instruct\",
+                 temperature=0.2, max_tokens=512)
+max_tokens = 99
+").unwrap();
+
+        let (_, hosted, _) = scan_file(&path, "test/repo", temp_dir.path(), &ScanOptions::default());
+        assert_eq!(hosted.len(), 1);
+        assert!(hosted[0].call_params.is_none());
+
+        let opts = ScanOptions { capture_call_params: true, ..Default::default() };
+        let (_, hosted, _) = scan_file(&path, "test/repo", temp_dir.path(), &opts);
+        assert_eq!(hosted.len(), 1);
+        assert_eq!(
+            hosted[0].call_params,
+            Some(serde_json::json!({"temperature": 0.2, "max_tokens": 512}))
+        );
+    }
+
+    #[test]
+    fn test_scan_multiline_langchain_calls() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("chain.py");
+        std::fs::write(&path, "\
+from langchain_nvidia_ai_endpoints import ChatNVIDIA, NVIDIAEmbeddings, NVIDIARerank
+
+llm = ChatNVIDIA(
+    model=\"meta/llama-3.1-70b-instruct\")
+
+embedder = NVIDIAEmbeddings(
+    base_url=\"https://integrate.api.nvidia.com/v1\",
+    model=\"nvidia/nv-embedqa-e5-v5\",  # pinned (see docs)
+    truncate=\"END\",
+)
+
+reranker = NVIDIARerank(
+    callbacks=[Tracer(model=\"mistralai/mixtral-8x7b-instruct-v0.1\")],
+    prompt=\"Rank (most relevant first)\",
+    model=\"nvidia/nv-rerankqa-mistral-4b-v3\",
+)
+").unwrap();
+
+        let (_, hosted, _) = scan_file(&path, "test/repo", temp_dir.path(), &ScanOptions::default());
+        let found: Vec<_> = hosted
+            .iter()
+            .map(|m| (m.line_number, m.model_name.as_deref().unwrap(), m.detection_rule.as_str()))
+            .collect();
+        assert_eq!(found, vec![
+            // 2 lines
+            (3, "meta/llama-3.1-70b-instruct", "chatnvidia"),
+            // 5 lines: the base_url line is folded into the call's finding
+            (6, "nvidia/nv-embedqa-e5-v5", "nvidia_embeddings"),
+            // Nested parentheses: the tracer's model is not the reranker's
+            (12, "nvidia/nv-rerankqa-mistral-4b-v3", "nvidia_rerank"),
+            (13, "mistralai/mixtral-8x7b-instruct-v0.1", "model_assign"),
+        ]);
+        assert_eq!(hosted[0].match_context, r#"llm = ChatNVIDIA(model="meta/llama-3.1-70b-instruct")"#);
+        assert_eq!(
+            hosted[1].match_context,
+            r#"embedder = NVIDIAEmbeddings(base_url="https://integrate.api.nvidia.com/v1", model="nvidia/nv-embedqa-e5-v5", truncate="END",)"#
+        );
+        assert_eq!(hosted[1].endpoint_url.as_deref(), Some("https://integrate.api.nvidia.com/v1"));
+        assert!(hosted[..3].iter().all(|m| m.framework == Framework::Langchain));
+
+        // A long call is cut around the model like a long line
+        let opts = ScanOptions { max_context_chars: Some(40), ..Default::default() };
+        let (_, hosted, _) = scan_file(&path, "test/repo", temp_dir.path(), &opts);
+        assert_eq!(hosted[1].match_context, "… model=\"nvidia/nv-embedqa-e5-v5\", trunca…[truncated]");
+        assert_eq!(hosted[1].context_truncated, Some(125));
+    }
+
+    #[test]
+    fn test_scan_openai_client_requests() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("client.py");
+        std::fs::write(&path, "\
+import os
+from openai import OpenAI
+
+client = OpenAI(
+    base_url=\"https://integrate.api.nvidia.com/v1\",
+    api_key=os.environ[\"NVIDIA_API_KEY\"],
+)
+other = OpenAI(api_key=os.environ[\"OPENAI_API_KEY\"])
+
+
+def ask(prompt):
+    completion = client.chat.completions.create(
+        model=\"meta/llama-3.1-8b-instruct\",
+        messages=[{\"role\": \"user\", \"content\": prompt}],
+    )
+    return completion.choices[0].message.content
+
+
+def embed(text):
+    return client.embeddings.create(model=\"nvidia/nv-embedqa-e5-v5\", input=[text])
+
+def fallback(prompt):
+    return other.chat.completions.create(model=\"meta/llama-3.1-70b-instruct\", messages=[])
+").unwrap();
+
+        let (_, hosted, _) = scan_file(&path, "test/repo", temp_dir.path(), &ScanOptions::default());
+        let found: Vec<_> = hosted
+            .iter()
+            .map(|m| (m.line_number, m.model_name.as_deref().unwrap(), m.endpoint_url.as_deref(), m.detection_rule.as_str()))
+            .collect();
+        let nvidia = Some("https://integrate.api.nvidia.com/v1");
+        // The client's endpoint-only finding (line 5) is folded into its requests
+        assert_eq!(found, vec![
+            (13, "meta/llama-3.1-8b-instruct", nvidia, "openai_client_call"),
+            (20, "nvidia/nv-embedqa-e5-v5", nvidia, "openai_client_call"),
+            (23, "meta/llama-3.1-70b-instruct", None, "model_assign"),
+        ]);
+        assert_eq!(hosted[0].invoke_style, InvokeStyle::OpenaiCompatible);
+        assert_eq!(hosted[0].framework, Framework::OpenaiSdk);
+
+        // Without a request to link, the endpoint is still reported on its own
+        std::fs::write(&path, "client = OpenAI(base_url=\"https://integrate.api.nvidia.com/v1\")\n").unwrap();
+        let (_, hosted, _) = scan_file(&path, "test/repo", temp_dir.path(), &ScanOptions::default());
+        assert_eq!(hosted.len(), 1);
+        assert_eq!(hosted[0].detection_rule, "hosted_endpoint");
+    }
+
+    #[test]
+    fn test_scan_json_payload_models() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let found = |name: &str, content: &str| {
+            let path = temp_dir.path().join(name);
+            std::fs::write(&path, content).unwrap();
+            let (_, hosted, _) = scan_file(&path, "test/repo", temp_dir.path(), &ScanOptions::default());
+            hosted.iter()
+                .map(|m| (m.line_number, m.endpoint_url.clone().unwrap_or_default(), m.model_name.clone().unwrap_or_default(), m.detection_rule.clone()))
+                .collect::<Vec<_>>()
+        };
+        let chat = "https://integrate.api.nvidia.com/v1/chat/completions".to_string();
+        let embeddings = "https://integrate.api.nvidia.com/v1/embeddings".to_string();
+
+        let shell = found("call.sh", r#"curl https://integrate.api.nvidia.com/v1/chat/completions -d '{"model": "meta/llama-3.1-70b-instruct", "messages": []}'
+curl -X POST https://integrate.api.nvidia.com/v1/chat/completions \
+  -H "Content-Type: application/json" \
+  -d '{
+    "model": "meta/llama-3.1-8b-instruct",
+    "messages": [{"role": "user", "content": "hi"}]
+  }'
+curl https://integrate.api.nvidia.com/v1/embeddings -d "{\"model\": \"nvidia/nv-embedqa-e5-v5\", \"input\": [\"hi\"]}"
+curl -s https://integrate.api.nvidia.com/v1/models
+echo '{"model": "meta/llama3-8b-instruct"}' > payload.json
+"#);
+        assert_eq!(shell, vec![
+            (1, chat.clone(), "meta/llama-3.1-70b-instruct".to_string(), "model_assign".to_string()),
+            (2, chat.clone(), "meta/llama-3.1-8b-instruct".to_string(), "json_payload_model".to_string()),
+            (8, embeddings.clone(), "nvidia/nv-embedqa-e5-v5".to_string(), "model_assign".to_string()),
+            (9, "https://integrate.api.nvidia.com/v1/models".to_string(), String::new(), "hosted_endpoint".to_string()),
+            // Not a request to the endpoint above: left apart
+            (10, String::new(), "meta/llama3-8b-instruct".to_string(), "model_assign".to_string()),
+        ]);
+
+        let python = found("client.py", r#"import httpx
+import requests
+
+resp = requests.post("https://integrate.api.nvidia.com/v1/chat/completions", json={"model": "meta/llama-3.1-70b-instruct"})
+resp = httpx.post(
+    "https://integrate.api.nvidia.com/v1/chat/completions",
+    headers=headers,
+    json={
+        "model": "mistralai/mixtral-8x7b-instruct-v0.1",
+        "messages": messages,
+    },
+)
+
+URL = "https://integrate.api.nvidia.com/v1/embeddings"
+payload = {'model': 'nvidia/nv-embedqa-e5-v5', 'input': ['hi']}
+r = requests.post(URL, json=payload)
+"#);
+        assert_eq!(python, vec![
+            (4, chat.clone(), "meta/llama-3.1-70b-instruct".to_string(), "model_assign".to_string()),
+            (6, chat.clone(), "mistralai/mixtral-8x7b-instruct-v0.1".to_string(), "json_payload_model".to_string()),
+            (15, embeddings.clone(), "nvidia/nv-embedqa-e5-v5".to_string(), "json_payload_model".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_capture_call_params_json_payload() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("request.json");
+        std::fs::write(&path, r#"{
+  "max_tokens": 1024,
+  "model": "meta/llama-3.1-8b-instruct",
+  "messages": [{"role": "user", "content": "hi"}],
+  "stream": true,
+  "top_p": 0.7
+}
+"#).unwrap();
+
+        let opts = ScanOptions { capture_call_params: true, ..Default::default() };
+        let (_, hosted, _) = scan_file(&path, "test/repo", temp_dir.path(), &opts);
+        assert_eq!(hosted.len(), 1);
+        assert_eq!(hosted[0].line_number, 3);
+        assert_eq!(
+            hosted[0].call_params,
+            Some(serde_json::json!({"max_tokens": 1024, "stream": true, "top_p": 0.7}))
+        );
+    }
+
+    /// One file per detection rule plus files with no NIM references
+    fn write_prefilter_fixture(root: &Path) {
+        let files: &[(&str, &str)] = &[
+            ("docker-compose.yaml", "services:\n  llm:\n    image: nvcr.io/nim/meta/llama-3.1-8b-instruct\n    tag: 1.3.0\n"),
+            ("chain.py", "llm = ChatNVIDIA(model=\"meta/llama-3.1-70b-instruct\")\n"),
+            ("client.py", "FUNCTION_ID = \"0c5a7e4b-2f3d-4c8e-9a1b-6d7e8f9a0b1c\"\nurl = f\"https://api.nvcf.nvidia.com/v2/nvcf/pexec/functions/{FUNCTION_ID}\"\n"),
+            ("embed.py", "url = \"https://ai.api.nvidia.com/v1/retrieval/nvidia/llama-3_2-nv-rerankqa-1b-v2/reranking\"\n"),
+            ("settings.py", "NIM_LLM_MODEL: Final = \"NVIDIA/llama-3.1-nemotron-70b-instruct\"\n"),
+            ("README.md", "Uses the `nvidia/nv-embedqa-e5-v5` model.\n"),
+            ("config.jsonc", "{\n  // \"model\": \"meta/old\"\n  \"model\": \"mistralai/mixtral-8x7b-instruct\"\n}\n"),
+            ("util.py", "def add(a, b):\n    return a + b\n"),
+            ("notes.md", "Nothing to see here; the model is described elsewhere.\n"),
+            ("values.yaml", "replicas: 2\nimage: registry.example.com/app:1.0\n"),
+        ];
+        for (name, content) in files {
+            std::fs::write(root.join(name), content).unwrap();
+        }
+    }
+
+    fn findings_json(outcome: &ScanOutcome) -> (Value, Value) {
+        let mut local = outcome.local.clone();
+        let mut hosted = outcome.hosted.clone();
+        local.sort_by(|a, b| (&a.file_path, a.line_number).cmp(&(&b.file_path, b.line_number)));
+        hosted.sort_by(|a, b| (&a.file_path, a.line_number).cmp(&(&b.file_path, b.line_number)));
+        (serde_json::to_value(local).unwrap(), serde_json::to_value(hosted).unwrap())
+    }
+
+    #[test]
+    fn test_prefilter_keeps_findings_identical() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        write_prefilter_fixture(temp_dir.path());
+
+        let with = scan_directory(temp_dir.path(), "test/repo", &ScanOptions::default());
+        let without_opts = ScanOptions { disable_prefilter: true, ..Default::default() };
+        let without = scan_directory(temp_dir.path(), "test/repo", &without_opts);
+
+        assert_eq!(findings_json(&with), findings_json(&without));
+        assert!(with.local.len() + with.hosted.len() >= 7);
+        assert_eq!(with.prefiltered_files(), 3);
+        assert_eq!(without.prefiltered_files(), 0);
+        let util = with.files.iter().find(|f| f.file_path == "util.py").unwrap();
+        assert!(util.prefiltered && util.sha256.is_some() && util.rules.is_empty());
+    }
+
+    #[test]
+    fn test_own_reports_skipped() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        // A report.json committed by an older version (before schema_version)
+        std::fs::write(temp_dir.path().join("report.json"), r#"{
+  "scan_time": "2024-06-01T00:00:00Z",
+  "total_repos": 1,
+  "source_code": {
+    "local_nim": [],
+    "hosted_nim": [
+      {
+        "repository": "acme/app",
+        "model_name": "meta/llama-3.1-8b-instruct",
+        "match_context": "model = \"meta/llama-3.1-8b-instruct\""
+      }
+    ]
+  },
+  "aggregated": {"hosted_nim": [], "local_nim": []}
+}
+"#).unwrap();
+        std::fs::write(temp_dir.path().join("config.json"),
+                       "{\n  \"scan_time\": \"nightly\",\n  \"model\": \"meta/llama-3.1-8b-instruct\"\n}\n").unwrap();
+
+        let outcome = scan_directory(temp_dir.path(), "test/repo", &ScanOptions::default());
+        assert_eq!(outcome.own_reports_skipped(), 1);
+        let report = outcome.files.iter().find(|f| f.file_path == "report.json").unwrap();
+        assert!(report.own_report && report.sha256.is_some() && report.rules.is_empty());
+        assert!(outcome.hosted.iter().all(|m| m.file_path == "config.json"));
+        assert!(outcome.hosted.iter().any(|m| m.model_name.as_deref() == Some("meta/llama-3.1-8b-instruct")));
+
+        let opts = ScanOptions { scan_own_reports: true, ..Default::default() };
+        let outcome = scan_directory(temp_dir.path(), "test/repo", &opts);
+        assert_eq!(outcome.own_reports_skipped(), 0);
+        assert!(outcome.hosted.iter().any(|m| m.file_path == "report.json"));
+    }
+
+    #[test]
+    fn test_generated_file_names() {
+        let matcher = build_generated_matcher(&["openapi/".to_string()]).unwrap();
+        let names = [
+            "package-lock.json", "yarn.lock", "poetry.lock", "Cargo.lock", "pnpm-lock.yaml", "go.sum",
+            "web/package-lock.json", "proto/inference_pb2.py", "src/api/client.generated.ts", "openapi/models.py",
+        ];
+        for name in names {
+            // Not on disk: decided by name alone
+            assert!(is_generated_file(&matcher, Path::new(name), Path::new(name)), "{}", name);
+        }
+        for name in ["package.json", "values.yaml", "inference.py", "locks/main.py"] {
+            assert!(!is_generated_file(&matcher, Path::new(name), Path::new(name)), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_generated_files_skipped() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let image = "nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3.0";
+        let files = [
+            ("package-lock.json", format!("{{\"resolved\": \"https://{}\"}}\n", image)),
+            ("pnpm-lock.yaml", format!("packages:\n  x:\n    resolution: {{tarball: {}}}\n", image)),
+            ("proto/nim_pb2.py", format!("DESCRIPTOR = b'{}'\n", image)),
+            ("gen/client.ts", format!("// Code generated by openapi-generator. DO NOT EDIT.\nconst IMAGE = \"{}\";\n", image)),
+            ("lib/schema.py", format!("# @generated by schema-gen\nIMAGE = \"{}\"\n", image)),
+            // The marker past the first 4 KB is never read
+            ("late.py", format!("{}# DO NOT EDIT\nIMAGE = \"{}\"\n", "#\n".repeat(2100), image)),
+            ("Dockerfile", format!("FROM {}\n", image)),
+        ];
+        for (path, content) in &files {
+            let path = temp_dir.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+
+        let opts = ScanOptions { generated_files: Some(build_generated_matcher(&[]).unwrap()), ..Default::default() };
+        let outcome = scan_directory(temp_dir.path(), "test/repo", &opts);
+        assert_eq!(outcome.generated_skipped(), 5);
+        let mut found: Vec<&str> = outcome.local.iter().map(|m| m.file_path.as_str()).collect();
+        found.sort();
+        assert_eq!(found, vec!["Dockerfile", "late.py"]);
+        let lockfile = outcome.files.iter().find(|f| f.file_path == "package-lock.json").unwrap();
+        assert!(lockfile.generated && lockfile.sha256.is_none());
+
+        // --include-generated
+        let outcome = scan_directory(temp_dir.path(), "test/repo", &ScanOptions::default());
+        assert_eq!(outcome.generated_skipped(), 0);
+        assert_eq!(outcome.local.len(), files.len());
+    }
+
+    #[test]
+    fn test_lfs_pointer_detected() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let pointer = "version https://git-lfs.github.com/spec/v1\noid sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393\nsize 12345\n";
+        std::fs::write(temp_dir.path().join("values.yaml"), pointer).unwrap();
+        // Mentions the spec URL, but is a real file
+        std::fs::write(temp_dir.path().join("notes.md"),
+                       "See version https://git-lfs.github.com/spec/v1 for `nvidia/nv-embedqa-e5-v5`.\n").unwrap();
+
+        let outcome = scan_directory(temp_dir.path(), "test/repo", &ScanOptions::default());
+        assert_eq!(outcome.lfs_pointer_paths(), vec!["values.yaml"]);
+        let stats = outcome.files.iter().find(|f| f.file_path == "values.yaml").unwrap();
+        assert!(stats.lfs_pointer && !stats.prefiltered && stats.sha256.is_some());
+        assert!(outcome.hosted.iter().any(|m| m.file_path == "notes.md"));
+    }
+
+    /// Rough timing of the prefilter on a large synthetic repository:
+    /// cargo test --release prefilter_benchmark -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn test_prefilter_benchmark() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        for i in 0..200 {
+            let dir = temp_dir.path().join(format!("pkg{}", i));
+            std::fs::create_dir(&dir).unwrap();
+            write_prefilter_fixture(&dir);
+            let filler = "def handler(event):\n    return {\"status\": 200, \"body\": event}\n".repeat(500);
+            for j in 0..20 {
+                std::fs::write(dir.join(format!("module{}.py", j)), &filler).unwrap();
+            }
+        }
+
+        let start = std::time::Instant::now();
+        let with = scan_directory(temp_dir.path(), "bench/repo", &ScanOptions::default());
+        let with_elapsed = start.elapsed();
+        let start = std::time::Instant::now();
+        let without_opts = ScanOptions { disable_prefilter: true, ..Default::default() };
+        let without = scan_directory(temp_dir.path(), "bench/repo", &without_opts);
+        let without_elapsed = start.elapsed();
+
+        println!("{} files, {} prefiltered: {:?} with prefilter, {:?} without",
+                 with.files.len(), with.prefiltered_files(), with_elapsed, without_elapsed);
+        assert_eq!(findings_json(&with), findings_json(&without));
+    }
+
+    #[test]
+    fn test_extract_model_from_url() {
+        let cases: &[(&str, Option<&str>)] = &[
+            ("https://ai.api.nvidia.com/v1/cv/baidu/paddleocr", Some("baidu/paddleocr")),
+            ("https://ai.api.nvidia.com/v1/cv/nvidia/nemoretriever-page-elements-v2",
+             Some("nvidia/nemoretriever-page-elements-v2")),
+            ("https://ai.api.nvidia.com/v1/retrieval/nvidia/llama-3_2-nv-rerankqa-1b-v2/reranking",
+             Some("nvidia/llama-3.2-nv-rerankqa-1b-v2")),
+            ("https://ai.api.nvidia.com/v1/retrieval/nvidia/nv-rerankqa-mistral-4b-v3/reranking",
+             Some("nvidia/nv-rerankqa-mistral-4b-v3")),
+            ("https://ai.api.nvidia.com/v1/retrieval/nvidia/embeddings", None),
+            ("https://ai.api.nvidia.com/v1/genai/black-forest-labs/flux.1-dev",
+             Some("black-forest-labs/flux.1-dev")),
+            ("https://ai.api.nvidia.com/v1/genai/stabilityai/stable-diffusion-3-medium",
+             Some("stabilityai/stable-diffusion-3-medium")),
+            ("https://ai.api.nvidia.com/v1/biology/mit/diffdock", Some("mit/diffdock")),
+            ("https://health.api.nvidia.com/v1/biology/nvidia/molmim/generate", Some("nvidia/molmim")),
+            ("https://ai.api.nvidia.com/v2/cv/nvidia/nv-grounding-dino?async=true",
+             Some("nvidia/nv-grounding-dino")),
+            ("https://ai.api.nvidia.com/v1beta/vlm/nvidia/llama-3_1-nemotron-nano-vl-8b-v1/infer",
+             Some("nvidia/llama-3.1-nemotron-nano-vl-8b-v1")),
+            ("https://ai.api.nvidia.com/v1/cv/nvidia/nemoretriever-ocr-v1`,", Some("nvidia/nemoretriever-ocr-v1")),
+            ("https://integrate.api.nvidia.com/v1", None),
+            ("https://integrate.api.nvidia.com/v1/chat/completions", None),
+            ("https://integrate.api.nvidia.com/v1/embeddings", None),
+            ("https://integrate.api.nvidia.com/v1/models", None),
+            ("https://ai.api.nvidia.com/v1/cv/nvidia/{model}", None),
+        ];
+        for (url, expected) in cases {
+            assert_eq!(extract_model_from_url(url).as_deref(), *expected, "url: {}", url);
+        }
+    }
+
+    #[test]
+    fn test_strip_json_comments() {
+        let input = "{\n  // default model\n  \"url\": \"https://ai.api.nvidia.com/v1\", /* inline */ \"a\": 1\n  /* multi\n     line */\n}";
+        let stripped = strip_json_comments(input);
+        assert_eq!(stripped.lines().count(), input.lines().count());
+        assert!(!stripped.contains("default model"));
+        assert!(!stripped.contains("inline"));
+        assert!(!stripped.contains("multi"));
+        assert!(stripped.contains("\"https://ai.api.nvidia.com/v1\""));
+        assert!(stripped.contains("\"a\": 1"));
+    }
+
+    #[test]
+    fn test_scan_multi_document_yaml() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("manifests.yaml");
+        std::fs::write(&path, "\
+apiVersion: v1
+kind: ConfigMap
+metadata:
+  name: settings
+---
+apiVersion: apps/v1
+kind: Deployment
+spec:
+  template:
+    spec:
+      containers:
+        - image: nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3.3
+---
+apiVersion: v1
+kind: Service
+").unwrap();
+
+        let (local, _, _) = scan_file(&path, "test/repo", temp_dir.path(), &ScanOptions::default());
+        assert_eq!(local.len(), 1);
+        assert_eq!(local[0].image_url, "nvcr.io/nim/meta/llama-3.1-8b-instruct");
+        assert_eq!(local[0].tag, "1.3.3");
+        assert_eq!(local[0].line_number, 12);
+    }
+
+    #[test]
+    fn test_scan_jsonc_config() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("settings.jsonc");
+        std::fs::write(&path, "\
+{
+  // \"model\": \"meta/llama-3.1-8b-instruct\" (old default)
+  \"model\": \"nvidia/llama-3.1-nemotron-70b-instruct\"
+}
+").unwrap();
+
+        assert!(should_scan_file(&path));
+        let (_, hosted, _) = scan_file(&path, "test/repo", temp_dir.path(), &ScanOptions::default());
+        assert_eq!(hosted.len(), 1);
+        assert_eq!(hosted[0].model_name.as_deref(), Some("nvidia/llama-3.1-nemotron-70b-instruct"));
+        assert_eq!(hosted[0].line_number, 3);
+    }
+
+    #[test]
+    fn test_extract_hosted_nim_function_id_in_pexec_url() {
+        let line = r#"invoke_url = "https://api.nvcf.nvidia.com/v2/nvcf/pexec/functions/0C5A7E4B-2f3d-4c8e-9a1b-6d7e8f9a0b1c""#;
+        let result = extract_hosted_nim(line, 1, "client.py", "test/repo", None, &HostedHosts::default());
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].function_id.as_deref(), Some("0c5a7e4b-2f3d-4c8e-9a1b-6d7e8f9a0b1c"));
+        assert!(result[0].endpoint_url.as_deref().unwrap().starts_with("https://api.nvcf.nvidia.com/v2/nvcf/pexec/functions/"));
+        assert!(result[0].model_name.is_none());
+    }
+
+    #[test]
+    fn test_scan_function_id_defined_nearby() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("client.py");
+        std::fs::write(&path, "\
+import requests
+
+FUNCTION_ID = \"0c5a7e4b-2f3d-4c8e-9a1b-6d7e8f9a0b1c\"
+invoke_url = f\"https://api.nvcf.nvidia.com/v2/nvcf/pexec/functions/{FUNCTION_ID}\"
+").unwrap();
+
+        let (_, hosted, _) = scan_file(&path, "test/repo", temp_dir.path(), &ScanOptions::default());
+        assert_eq!(hosted.len(), 2);
+        assert!(hosted.iter().all(|m| m.function_id.as_deref() == Some("0c5a7e4b-2f3d-4c8e-9a1b-6d7e8f9a0b1c")));
+        assert_eq!(hosted[1].line_number, 4);
+        assert_eq!(hosted[1].invoke_style, InvokeStyle::NvcfPexec);
+    }
+
+    #[test]
+    fn test_resolve_skip_dirs() {
+        let repo = RepoConfig::default();
+        assert_eq!(resolve_skip_dirs(&repo).len(), SKIP_DIRS.len());
+
+        let repo = RepoConfig {
+            skip_dirs_add: vec!["fixtures".to_string()],
+            skip_dirs_remove: vec!["dist".to_string(), "vendor".to_string()],
+            ..Default::default()
+        };
+        let dirs = resolve_skip_dirs(&repo);
+        assert!(dirs.contains(&"fixtures".to_string()));
+        assert!(!dirs.contains(&"dist".to_string()));
+        assert!(!dirs.contains(&"vendor".to_string()));
+        assert!(dirs.contains(&"node_modules".to_string()));
+
+        let repo = RepoConfig {
+            skip_dirs: Some(vec!["only".to_string()]),
+            ..Default::default()
+        };
+        assert_eq!(resolve_skip_dirs(&repo), vec!["only".to_string()]);
+    }
+
+    #[test]
+    fn test_scan_directory_max_depth() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut dir = temp_dir.path().to_path_buf();
+        for level in 0..60 {
+            std::fs::write(dir.join("Dockerfile"),
+                           format!("FROM nvcr.io/nim/nvidia/level-{}:1.0.0\n", level)).unwrap();
+            dir = dir.join("site");
+            std::fs::create_dir(&dir).unwrap();
+        }
+
+        let opts = ScanOptions { max_depth: Some(10), ..Default::default() };
+        let outcome = scan_directory(temp_dir.path(), "test/repo", &opts);
+        // Files at depth 1..=10, i.e. the Dockerfiles of levels 0..=9
+        assert_eq!(outcome.files.len(), 10);
+        assert_eq!(outcome.local.len(), 10);
+        assert!(outcome.local.iter().all(|m| m.file_path.matches('/').count() < 10));
+        assert_eq!(outcome.dirs_beyond_max_depth, 1);
+        assert_eq!(
+            outcome.truncation("test/repo", &opts),
+            Some(ScanTruncation {
+                repository: "test/repo".to_string(),
+                max_depth: Some(10),
+                dirs_beyond_max_depth: 1,
+                max_files: None,
+            })
+        );
+
+        let unlimited = scan_directory(temp_dir.path(), "test/repo", &ScanOptions::default());
+        assert_eq!(unlimited.local.len(), 60);
+        assert_eq!(unlimited.truncation("test/repo", &ScanOptions::default()), None);
+    }
+
+    #[test]
+    fn test_scan_directory_max_files() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        for i in 0..25 {
+            std::fs::write(temp_dir.path().join(format!("page-{}.md", i)), "generated\n").unwrap();
+        }
+
+        let opts = ScanOptions { max_files: Some(10), ..Default::default() };
+        let outcome = scan_directory(temp_dir.path(), "test/repo", &opts);
+        assert_eq!(outcome.files.len(), 10);
+        assert!(outcome.file_limit_reached);
+        let truncation = outcome.truncation("test/repo", &opts).unwrap();
+        assert_eq!(truncation.max_files, Some(10));
+        assert_eq!(truncation.max_depth, None);
+
+        // Exactly at the limit is not a truncation
+        let opts = ScanOptions { max_files: Some(25), ..Default::default() };
+        let outcome = scan_directory(temp_dir.path(), "test/repo", &opts);
+        assert_eq!(outcome.files.len(), 25);
+        assert_eq!(outcome.truncation("test/repo", &opts), None);
+    }
+
+    #[test]
+    fn test_scan_directory_resolves_env_variables() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join(".env"),
+                       "NIM_IMAGE=nvcr.io/nim/nvidia/llama-3.2-nv-embedqa-1b-v2:1.5.0\n").unwrap();
+        std::fs::write(temp_dir.path().join("docker-compose.yaml"), "\
+services:
+  embed:
+    image: ${NIM_IMAGE}
+  chat:
+    image: nvcr.io/nim/meta/llama-3.1-8b-instruct:${CHAT_TAG}
+    environment:
+      - NIM_BASE_URL=https://integrate.api.nvidia.com/v1
+  rerank:
+    image: nvcr.io/nim/nvidia/llama-3.2-nv-rerankqa-1b-v2:${RERANK_TAG:-1.3.0}
+").unwrap();
+        std::fs::write(temp_dir.path().join("client.yaml"), "\
+llm:
+  base_url: $NIM_BASE_URL
+  model: meta/llama-3.1-8b-instruct
+").unwrap();
+
+        let outcome = scan_directory(temp_dir.path(), "test/repo", &ScanOptions::default());
+        let mut local: Vec<(&str, usize, &str, &str, Option<&str>)> = outcome.local.iter()
+            .map(|m| (m.file_path.as_str(), m.line_number, m.tag.as_str(), m.detection_rule.as_str(), m.resolved_from.as_deref()))
+            .collect();
+        local.sort();
+        assert_eq!(local, vec![
+            (".env", 1, "1.5.0", "local_nim_full", None),
+            ("docker-compose.yaml", 3, "1.5.0", "local_nim_full", Some("NIM_IMAGE")),
+            // Unresolved: reported with the default, else the reference as written
+            ("docker-compose.yaml", 5, "${CHAT_TAG}", "local_nim_unresolved_var", None),
+            ("docker-compose.yaml", 9, "1.3.0", "local_nim_unresolved_var", None),
+        ]);
+        let compose = outcome.local.iter().find(|m| m.resolved_from.is_some()).unwrap();
+        assert_eq!(compose.image_url, "nvcr.io/nim/nvidia/llama-3.2-nv-embedqa-1b-v2");
+        assert_eq!(compose.match_context, "image: ${NIM_IMAGE}");
+
+        let client = outcome.hosted.iter().find(|m| m.file_path == "client.yaml").unwrap();
+        assert_eq!(client.endpoint_url.as_deref(), Some("https://integrate.api.nvidia.com/v1"));
+        assert_eq!(client.model_name.as_deref(), Some("meta/llama-3.1-8b-instruct"));
+        assert_eq!(client.resolved_from.as_deref(), Some("NIM_BASE_URL"));
+    }
+
+    #[test]
+    fn test_bounded_context_long_line() {
+        let padding = "x".repeat(25_000);
+        let line = format!("  var a=\"{}\";image=\"nvcr.io/nim/meta/llama-3.1-8b-instruct:1.0.0\";b=\"{}\"", padding, padding);
+        let local = extract_local_nim(&line, 1, "dist/app.min.js", "test/repo", Some(2000)).unwrap();
+        assert!(local.match_context.contains("nvcr.io/nim/meta/llama-3.1-8b-instruct:1.0.0"));
+        assert!(local.match_context.starts_with('…'));
+        assert!(local.match_context.ends_with("…[truncated]"));
+        assert_eq!(local.match_context.chars().count(), 2000 + "……[truncated]".chars().count());
+        assert_eq!(local.context_truncated, Some(line.trim().chars().count()));
+
+        // The window is centered on the match, but never runs past either end of the line
+        let (context, truncated) = bounded_context("model=\"a/b\" é", 6..11, Some(8));
+        assert_eq!(context, "…=\"a/b\" é…[truncated]");
+        assert_eq!(truncated, Some(13));
+        let (context, _) = bounded_context("model=\"a/b\" é", 0..5, Some(8));
+        assert_eq!(context, "model=\"a…[truncated]");
+        // Short lines are untouched
+        let (context, truncated) = bounded_context("  FROM nvcr.io/nim/a/b:1  ", 7..24, Some(2000));
+        assert_eq!(context, "FROM nvcr.io/nim/a/b:1");
+        assert_eq!(truncated, None);
+        let unlimited = extract_local_nim(&line, 1, "dist/app.min.js", "test/repo", None).unwrap();
+        assert_eq!(unlimited.match_context, line.trim());
+    }
+
+    #[test]
+    fn test_scan_file_walks_compose_structure() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let compose = temp_dir.path().join("docker-compose.yml");
+        std::fs::write(&compose, "\
+x-nim: &nim
+  image: nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3.3
+services:
+  llm:
+    <<: *nim
+  rerank:
+    <<: *nim
+  embed:
+    image: nvcr.io/nim/nvidia/nv-embedqa-e5-v5:1.5.0 # pinned
+  parse:
+    build:
+      args:
+        NIM_REGISTRY: nvcr.io
+        NIM_REPOSITORY: nim/nvidia/nemoretriever-parse
+        NIM_TAG: \"1.2\"
+").unwrap();
+        let (local, _, _) = scan_file(&compose, "test/repo", temp_dir.path(), &ScanOptions::default());
+        let found: Vec<(usize, &str, &str, &str, &str)> = local.iter()
+            .map(|m| (m.line_number, m.image_url.as_str(), m.tag.as_str(), m.detection_rule.as_str(), m.match_context.as_str()))
+            .collect();
+        assert_eq!(found, vec![
+            (2, "nvcr.io/nim/meta/llama-3.1-8b-instruct", "1.3.3", "local_nim_full",
+             "services.llm.image: nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3.3"),
+            (2, "nvcr.io/nim/meta/llama-3.1-8b-instruct", "1.3.3", "local_nim_compose",
+             "services.rerank.image: nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3.3"),
+            (9, "nvcr.io/nim/nvidia/nv-embedqa-e5-v5", "1.5.0", "local_nim_full",
+             "services.embed.image: nvcr.io/nim/nvidia/nv-embedqa-e5-v5:1.5.0"),
+            (14, "nvcr.io/nim/nvidia/nemoretriever-parse", "1.2", "local_nim_compose",
+             "services.parse.build.args: nvcr.io/nim/nvidia/nemoretriever-parse:1.2"),
+        ]);
+        assert_eq!(local[1].intent, LocalIntent::Deploy);
+        assert!(local.iter().all(|m| m.file_kind == FileKind::Compose));
+
+        // Not YAML: the line pass's findings as they are
+        std::fs::write(&compose, "services:\n  llm:\n    image: nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3.3\n  bad: [\n").unwrap();
+        let (local, _, _) = scan_file(&compose, "test/repo", temp_dir.path(), &ScanOptions::default());
+        assert_eq!(local.len(), 1);
+        assert_eq!(local[0].match_context, "image: nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3.3");
     }
-    
-    for m in hosted_matches {
-        match determine_source_type(&m.file_path) {
-            SourceType::SourceCode => source_code.hosted_nim.push(m),
-            SourceType::ActionsWorkflow => actions_workflow.hosted_nim.push(m),
-        }
+
+    #[test]
+    fn test_scan_directory_merges_compose_overrides() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let deploy = temp_dir.path().join("deploy");
+        std::fs::create_dir_all(&deploy).unwrap();
+        std::fs::write(deploy.join("docker-compose.yaml"), "\
+services:
+  llm:
+    profiles: [gpu, nim]
+    environment:
+      - NGC_API_KEY
+    deploy:
+      replicas: 2
+").unwrap();
+        std::fs::write(deploy.join("docker-compose.gpu.yaml"), "\
+services:
+  llm:
+    image: nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3.3
+").unwrap();
+        // Not YAML: merged without it, its image is still found line by line
+        std::fs::write(deploy.join("docker-compose.override.yaml"), "\
+services:
+  embed: {{ template }}
+    image: nvcr.io/nim/nvidia/nv-embedqa-e5-v5:1.0.0
+").unwrap();
+
+        let outcome = scan_directory(temp_dir.path(), "test/repo", &ScanOptions::default());
+        assert_eq!(outcome.local.len(), 2);
+        let llm = outcome.local.iter().find(|m| m.file_path == "deploy/docker-compose.gpu.yaml").unwrap();
+        assert_eq!(llm.line_number, 3);
+        let compose = llm.compose.as_ref().unwrap();
+        assert_eq!(compose.service, "llm");
+        assert_eq!(compose.merged_files, vec!["deploy/docker-compose.yaml", "deploy/docker-compose.gpu.yaml"]);
+        assert_eq!(compose.profiles, vec!["gpu", "nim"]);
+        assert_eq!(compose.replicas, Some(2));
+        assert_eq!(compose.hint_sources.get("profiles").map(String::as_str), Some("deploy/docker-compose.yaml"));
+        assert_eq!(compose.hint_sources.get("replicas").map(String::as_str), Some("deploy/docker-compose.yaml"));
+
+        let embed = outcome.local.iter().find(|m| m.file_path == "deploy/docker-compose.override.yaml").unwrap();
+        assert_eq!(embed.image_url, "nvcr.io/nim/nvidia/nv-embedqa-e5-v5");
+        assert!(embed.compose.is_none());
     }
-    
-    (source_code, actions_workflow)
-}
 
-/// Deduplicate results based on (repository, file_path, line_number)
-pub fn deduplicate_results(findings: &mut NimFindings) {
-    use std::collections::HashSet;
-    
-    // Deduplicate local_nim
-    let mut seen: HashSet<(String, String, usize)> = HashSet::new();
-    findings.local_nim.retain(|m| {
-        let key = (m.repository.clone(), m.file_path.clone(), m.line_number);
-        seen.insert(key)
-    });
-    
-    // Deduplicate hosted_nim (key must include model_name so multiple models on the same line are all kept)
-    let mut seen_hosted: HashSet<(String, String, usize, String)> = HashSet::new();
-    findings.hosted_nim.retain(|m| {
-        let model_key = m.model_name.as_deref().unwrap_or("").to_string();
-        let key = (m.repository.clone(), m.file_path.clone(), m.line_number, model_key);
-        seen_hosted.insert(key)
-    });
-}
+    #[test]
+    fn test_scan_file_walks_kubernetes_manifests() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let manifest = temp_dir.path().join("nim.yaml");
+        std::fs::write(&manifest, "\
+apiVersion: apps/v1
+kind: Deployment
+metadata:
+  name: nim-embedder
+spec:
+  template:
+    spec:
+      imagePullSecrets: [{name: ngc-secret}]
+      containers:
+        - name: embedder
+          image: nvcr.io/nim/nvidia/nv-embedqa-e5-v5:1.0.0
+---
+apiVersion: apps/v1
+kind: StatefulSet
+metadata:
+  name: nim-embedder-canary
+spec:
+  template:
+    spec:
+      initContainers: [{name: cache, image: 'nvcr.io/nim/nvidia/nv-embedqa-e5-v5:1.0.0'}]
+      containers:
+        - name: embedder
+          image: nvcr.io/nim/nvidia/nv-embedqa-e5-v5:1.0.0
+").unwrap();
+        let (local, _, _) = scan_file(&manifest, "test/repo", temp_dir.path(), &ScanOptions::default());
+        let found: Vec<(usize, &str, &str)> = local
+            .iter()
+            .map(|m| (m.line_number, m.detection_rule.as_str(), m.match_context.as_str()))
+            .collect();
+        // One finding per container, across documents
+        assert_eq!(found, vec![
+            (11, "local_nim_full", "Deployment/nim-embedder containers[embedder]: nvcr.io/nim/nvidia/nv-embedqa-e5-v5:1.0.0 (imagePullSecrets: ngc-secret)"),
+            (20, "local_nim_full", "StatefulSet/nim-embedder-canary initContainers[cache]: nvcr.io/nim/nvidia/nv-embedqa-e5-v5:1.0.0"),
+            (23, "local_nim_full", "StatefulSet/nim-embedder-canary containers[embedder]: nvcr.io/nim/nvidia/nv-embedqa-e5-v5:1.0.0"),
+        ]);
+        assert!(local.iter().all(|m| m.file_kind == FileKind::Kubernetes && m.intent == LocalIntent::Deploy));
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn test_scan_nim_operator_resources() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let manifest = temp_dir.path().join("nim-operator.yaml");
+        std::fs::write(&manifest, "\
+apiVersion: apps.nvidia.com/v1alpha1
+kind: NIMCache
+metadata:
+  name: meta-llama3-8b-instruct
+spec:
+  source:
+    ngc:
+      modelPuller: nvcr.io/nim/meta/llama3-8b-instruct:1.0.3
+      pullSecret: ngc-secret
+      authSecret: ngc-api-secret
+      model:
+        engine: tensorrt_llm
+        tensorParallelism: \"1\"
+  storage:
+    pvc:
+      create: true
+      size: 50Gi
+---
+apiVersion: apps.nvidia.com/v1alpha1
+kind: NIMService
+metadata:
+  name: meta-llama3-8b-instruct
+spec:
+  image:
+    repository: nvcr.io/nim/meta/llama3-8b-instruct
+    tag: 1.0.3
+    pullPolicy: IfNotPresent
+    pullSecrets:
+      - ngc-secret
+  authSecret: ngc-api-secret
+  model: meta/llama3-8b-instruct
+  storage:
+    nimCache:
+      name: meta-llama3-8b-instruct
+---
+apiVersion: apps.nvidia.com/v1alpha1
+kind: NIMPipeline
+metadata:
+  name: rag
+spec:
+  services:
+    - name: embedder
+      enabled: true
+      spec:
+        image:
+          repository: nvcr.io/nim/nvidia/nv-embedqa-e5-v5
+          tag: \"1.0.0\"
+").unwrap();
+        let (local, _, _) = scan_file(&manifest, "test/repo", temp_dir.path(), &ScanOptions::default());
+        let found: Vec<(usize, &str, &str, &str, &str)> = local
+            .iter()
+            .map(|m| (m.line_number, m.image_url.as_str(), m.tag.as_str(), m.detection_rule.as_str(), m.match_context.as_str()))
+            .collect();
+        assert_eq!(found, vec![
+            (8, "nvcr.io/nim/meta/llama3-8b-instruct", "1.0.3", "local_nim_full",
+                "NIMCache/meta-llama3-8b-instruct spec.source.ngc.modelPuller: nvcr.io/nim/meta/llama3-8b-instruct:1.0.3 (imagePullSecrets: ngc-secret)"),
+            (25, "nvcr.io/nim/meta/llama3-8b-instruct", "1.0.3", "local_nim_operator",
+                "NIMService/meta-llama3-8b-instruct spec.image: nvcr.io/nim/meta/llama3-8b-instruct:1.0.3 (imagePullSecrets: ngc-secret) (model: meta/llama3-8b-instruct)"),
+            (46, "nvcr.io/nim/nvidia/nv-embedqa-e5-v5", "1.0.0", "local_nim_operator",
+                "NIMPipeline/rag spec.services[embedder]: nvcr.io/nim/nvidia/nv-embedqa-e5-v5:1.0.0"),
+        ]);
+        assert!(local.iter().all(|m| m.file_kind == FileKind::Kubernetes && m.intent == LocalIntent::Deploy));
+    }
 
     #[test]
-    fn test_determine_source_type() {
-        assert_eq!(
-            determine_source_type("src/main.py"),
-            SourceType::SourceCode
-        );
-        assert_eq!(
-            determine_source_type(".github/workflows/deploy.yml"),
-            SourceType::ActionsWorkflow
-        );
-        assert_eq!(
-            determine_source_type(".github/workflows/test.yaml"),
-            SourceType::ActionsWorkflow
-        );
-        assert_eq!(
-            determine_source_type(".github/actions/test.yml"),
-            SourceType::SourceCode  // Not in workflows/
-        );
+    fn test_scan_directory_marks_fixtures() {
+        // A fork of this scanner under another name: recognized by its Cargo.toml
+        let own = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(own.path().join("src")).unwrap();
+        std::fs::write(own.path().join("Cargo.toml"), format!("\
+[workspace]
+name = \"other\"
+
+[package]
+name = \"{}\"
+version = \"0.1.0\"
+", env!("CARGO_PKG_NAME"))).unwrap();
+        std::fs::write(own.path().join("README.md"), "\
+```bash
+docker pull nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3.0
+```
+").unwrap();
+        std::fs::write(own.path().join("src/chain.py"), "llm = ChatNVIDIA(model=\"meta/llama-3.1-70b-instruct\")\n").unwrap();
+        let outcome = scan_directory(own.path(), "someone/nim-scanner-fork", &ScanOptions::default());
+        assert_eq!((outcome.local.len(), outcome.hosted.len()), (1, 1));
+        assert!(outcome.local.iter().all(|m| m.fixture) && outcome.hosted.iter().all(|m| m.fixture));
+
+        let other = tempfile::TempDir::new().unwrap();
+        for dir in ["tests/fixtures", "pkg/testdata", "src"] {
+            std::fs::create_dir_all(other.path().join(dir)).unwrap();
+        }
+        std::fs::write(other.path().join("Cargo.toml"), "[package]\nname = \"nim-usage-dashboard\"\n").unwrap();
+        let image = "image: nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3.0\n";
+        for file in ["tests/fixtures/compose.yaml", "pkg/testdata/k8s.yaml", "src/deploy.yaml"] {
+            std::fs::write(other.path().join(file), image).unwrap();
+        }
+        let globs: Vec<String> = DEFAULT_TEST_FIXTURE_GLOBS.iter().map(|glob| glob.to_string()).collect();
+        let opts = ScanOptions { fixture_files: Some(build_fixture_matcher(&globs).unwrap()), ..Default::default() };
+        let outcome = scan_directory(other.path(), "test/repo", &opts);
+        let mut fixtures: Vec<_> = outcome.local.iter().map(|m| (m.file_path.as_str(), m.fixture)).collect();
+        fixtures.sort();
+        assert_eq!(fixtures, vec![("pkg/testdata/k8s.yaml", true), ("src/deploy.yaml", false), ("tests/fixtures/compose.yaml", true)]);
+
+        // Excluded from the summary unless counted on purpose
+        let (source_code, actions_workflow) = categorize_results(outcome.local, outcome.hosted);
+        let summary = crate::models::Summary::calculate(&source_code, &actions_workflow);
+        assert_eq!((summary.total_local_nim, summary.repos_with_nim, summary.fixtures_excluded), (1, 1, 2));
+        assert_eq!(summary.file_kind.source, 1);
+        let summary = crate::models::Summary::calculate_all(&source_code, &actions_workflow);
+        assert_eq!((summary.total_local_nim, summary.fixtures_excluded), (3, 0));
     }
 
     #[test]
-    fn test_extract_local_nim_with_tag() {
-        let line = "image: nvcr.io/nim/nvidia/llama-3.2-nv-embedqa-1b-v2:1.10.0";
-        let result = extract_local_nim(line, 1, "docker-compose.yaml", "test/repo");
-        
-        assert!(result.is_some());
-        let m = result.unwrap();
-        assert_eq!(m.image_url, "nvcr.io/nim/nvidia/llama-3.2-nv-embedqa-1b-v2");
-        assert_eq!(m.tag, "1.10.0");
+    fn test_scan_untagged_nim_images_are_not_hosted() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("chart")).unwrap();
+        std::fs::write(root.join("chart/Chart.yaml"), "apiVersion: v2\nname: llm\nversion: 0.1.0\n").unwrap();
+        std::fs::write(root.join("chart/values.yaml"), "image:\n  repository: nvcr.io/nim/meta/llama3-8b-instruct\n  tag: 1.0.3\n").unwrap();
+        std::fs::write(root.join("nimservice.yaml"), "\
+apiVersion: apps.nvidia.com/v1alpha1
+kind: NIMService
+metadata:
+  name: llm
+spec:
+  image:
+    repository: nvcr.io/nim/meta/llama3-8b-instruct
+    tag: 1.0.3
+").unwrap();
+        std::fs::write(root.join("pull.sh"), "ngc registry image info nim/meta/llama3-8b-instruct\n").unwrap();
+
+        let outcome = scan_directory(root, "test/repo", &ScanOptions::default());
+        let mut rules: Vec<&str> = outcome.local.iter().map(|m| m.detection_rule.as_str()).collect();
+        rules.sort();
+        assert_eq!(rules, vec!["local_nim_helm", "local_nim_ngc_cli", "local_nim_operator"]);
+        // The image's org/name is not a prose mention of a hosted model
+        let source = NimFindings { local_nim: outcome.local, hosted_nim: outcome.hosted, sdk_dependencies: vec![] };
+        let summary = crate::models::Summary::calculate_all(&source, &NimFindings::new());
+        assert_eq!(summary.total_hosted_nim, 0, "{:?}", source.hosted_nim);
+        assert_eq!(summary.total_local_nim, 3);
+        // A prose mention next to an image still counts
+        assert!(!in_nim_image_path("for meta/llama3-8b-instruct model", 4));
+        assert!(!in_nim_image_path("see anim/meta/x", 9));
+        assert!(in_nim_image_path("FROM NVCR.IO/NIM/meta/x", 17));
     }
 
     #[test]
-    fn test_extract_local_nim_without_tag() {
-        let line = "FROM nvcr.io/nim/nvidia/nemo-retriever";
-        let result = extract_local_nim(line, 1, "Dockerfile", "test/repo");
-        
-        assert!(result.is_some());
-        let m = result.unwrap();
-        assert_eq!(m.image_url, "nvcr.io/nim/nvidia/nemo-retriever");
-        assert_eq!(m.tag, "latest");
+    fn test_scan_directory_joins_helm_values() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let chart = temp_dir.path().join("deploy/nim-llm");
+        std::fs::create_dir_all(chart.join("templates")).unwrap();
+        std::fs::write(chart.join("Chart.yaml"), "apiVersion: v2\nname: nim-llm\nversion: 1.3.0\n").unwrap();
+        std::fs::write(chart.join("values.yaml"), "\
+image:
+  repository: nvcr.io/nim/meta/llama-3.1-8b-instruct
+  tag: 1.3.3
+  pullPolicy: IfNotPresent
+embed:
+  image:
+    registry: nvcr.io
+    repository: nim/nvidia/nv-embedqa-e5-v5
+    tag: \"1.0\"
+").unwrap();
+        std::fs::write(chart.join("values-prod.yaml"), "\
+global:
+  imageRegistry: nvcr.io
+image:
+  repository: nim/meta/llama-3.1-70b-instruct
+  tag: 1.5.0
+sidecar:
+  repository: docker.io/library/busybox
+  tag: latest
+").unwrap();
+        std::fs::write(chart.join("templates/values.yaml"), "image:\n  repository: nim/meta/skipped\n  tag: 1.0\n").unwrap();
+        // A templated NIM Operator resource: only the values carry the literal image
+        std::fs::write(chart.join("templates/nimservice.yaml"), "\
+apiVersion: apps.nvidia.com/v1alpha1
+kind: NIMService
+metadata:
+  name: {{ .Release.Name }}
+spec:
+  image:
+    repository: {{ .Values.image.repository }}
+    tag: {{ .Values.image.tag | quote }}
+").unwrap();
+
+        let outcome = scan_directory(temp_dir.path(), "test/repo", &ScanOptions::default());
+        let mut found: Vec<_> = outcome.local.iter()
+            .map(|m| (m.file_path.as_str(), m.line_number, m.image_url.as_str(), m.tag.as_str(), m.detection_rule.as_str()))
+            .collect();
+        found.sort();
+        assert_eq!(found, vec![
+            ("deploy/nim-llm/values-prod.yaml", 4, "nvcr.io/nim/meta/llama-3.1-70b-instruct", "1.5.0", "local_nim_helm"),
+            ("deploy/nim-llm/values.yaml", 2, "nvcr.io/nim/meta/llama-3.1-8b-instruct", "1.3.3", "local_nim_helm"),
+            ("deploy/nim-llm/values.yaml", 8, "nvcr.io/nim/nvidia/nv-embedqa-e5-v5", "1.0", "local_nim_helm"),
+        ]);
+        let embed = outcome.local.iter().find(|m| m.line_number == 8).unwrap();
+        assert_eq!(embed.match_context, "embed.image: nvcr.io/nim/nvidia/nv-embedqa-e5-v5:1.0");
+        assert_eq!((embed.intent, embed.file_kind), (LocalIntent::Deploy, FileKind::Helm));
+
+        // Without a Chart.yaml, values files are only scanned line by line (a nearby `tag:` at best)
+        std::fs::remove_file(chart.join("Chart.yaml")).unwrap();
+        let outcome = scan_directory(temp_dir.path(), "test/repo", &ScanOptions::default());
+        assert_eq!(outcome.local.len(), 1);
+        assert_eq!((outcome.local[0].tag.as_str(), outcome.local[0].detection_rule.as_str()), ("1.3.3", "local_nim_no_tag"));
     }
 
     #[test]
-    fn test_extract_hosted_nim_endpoint() {
-        let line = r#"base_url = "https://ai.api.nvidia.com/v1/chat""#;
-        let result = extract_hosted_nim(line, 1, "client.py", "test/repo");
-        
-        assert_eq!(result.len(), 1);
-        assert_eq!(result[0].endpoint_url.as_deref(), Some("https://ai.api.nvidia.com/v1/chat"));
+    fn test_helm_values_multi_document() {
+        // Three documents; the NIM image is in the second, and the first only mentions it
+        let content = "\
+# Defaults
+---
+# repository: nvcr.io/nim/meta/llama3-8b-instruct (uncomment to pin)
+replicas: 1
+---
+image:
+  repository: nvcr.io/nim/meta/llama3-8b-instruct
+  tag: 1.0.3
+--- # overrides
+global:
+  imageRegistry: nvcr.io
+embed:
+  repository: nim/nvidia/nv-embedqa-e5-v5
+  tag: \"1.0\"
+";
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(yaml_document_lines(&lines), vec![2..4, 5..8, 9..lines.len()]);
+        let found: Vec<(String, usize)> = helm_values_images(content).unwrap().into_iter().map(|f| (f.image, f.line)).collect();
+        assert_eq!(found, vec![
+            ("nvcr.io/nim/meta/llama3-8b-instruct:1.0.3".to_string(), 7),
+            ("nvcr.io/nim/nvidia/nv-embedqa-e5-v5:1.0".to_string(), 13),
+        ]);
+
+        // A leading document without `---`, and an empty values file
+        assert_eq!(yaml_document_lines(&["a: 1", "---", "b: 2"]), vec![0..1, 2..3]);
+        assert_eq!(helm_values_images("").unwrap().len(), 0);
+        assert!(helm_values_images("image: [").is_none());
     }
 
     #[test]
-    fn test_extract_hosted_nim_model() {
-        let line = r#"model = "nvidia/llama-3.1-nemotron-70b-instruct""#;
-        let result = extract_hosted_nim(line, 1, "client.py", "test/repo");
-        
-        assert_eq!(result.len(), 1);
-        assert_eq!(result[0].model_name.as_deref(), Some("nvidia/llama-3.1-nemotron-70b-instruct"));
+    fn test_scan_wide_yaml_context_lookups() {
+        // Machine-generated YAML: 20k lines, nearly all endpoints without a model on their line
+        let mut content = String::from("shards:\n");
+        for i in 0..20_000 {
+            if i % 10 == 0 {
+                content.push_str("  - model_name: meta/llama-3.1-8b-instruct\n");
+            } else {
+                content.push_str(&format!("    base_url: https://integrate.api.nvidia.com/v1  # shard {}\n", i));
+            }
+        }
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("shards.yaml");
+        std::fs::write(&path, &content).unwrap();
+
+        let started = std::time::Instant::now();
+        let (_, hosted, stats) = scan_file(&path, "test/repo", temp_dir.path(), &ScanOptions::default());
+        let elapsed = started.elapsed();
+        assert!(elapsed < std::time::Duration::from_secs(30), "took {:?}", elapsed);
+
+        let endpoints: Vec<_> = hosted.iter().filter(|m| m.endpoint_url.is_some()).collect();
+        assert_eq!(endpoints.len(), 18_000);
+        assert_eq!(stats.context_lookups, MAX_CONTEXT_LOOKUPS_PER_FILE);
+        assert_eq!(stats.context_lookups_skipped, 18_000 - MAX_CONTEXT_LOOKUPS_PER_FILE);
+        let (looked_up, skipped) = endpoints.split_at(MAX_CONTEXT_LOOKUPS_PER_FILE);
+        assert!(looked_up.iter().all(|m| m.model_name.as_deref() == Some("meta/llama-3.1-8b-instruct")));
+        assert!(skipped.iter().all(|m| m.model_name.is_none()));
+    }
+
+    /// (line, model) of each endpoint finding in a YAML file
+    fn yaml_endpoint_models(content: &str) -> Vec<(usize, Option<String>)> {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("values.yaml");
+        std::fs::write(&path, content).unwrap();
+        let (_, hosted, _) = scan_file(&path, "test/repo", temp_dir.path(), &ScanOptions::default());
+        hosted.into_iter().filter(|m| m.endpoint_url.is_some()).map(|m| (m.line_number, m.model_name)).collect()
     }
 
     #[test]
-    fn test_extract_hosted_nim_chatnvidia() {
-        let line = r#"llm = ChatNVIDIA(model="nvidia/llama-3.1-nemotron")"#;
-        let result = extract_hosted_nim(line, 1, "chain.py", "test/repo");
-        
-        assert_eq!(result.len(), 1);
-        assert_eq!(result[0].model_name.as_deref(), Some("nvidia/llama-3.1-nemotron"));
+    fn test_yaml_context_stays_in_block() {
+        // Sibling service blocks: the embedder's endpoint must not take the LLM's or the reranker's model
+        let values = "\
+llm:
+  model: meta/llama-3.1-70b-instruct
+  replicas: 1
+embedder:
+  replicas: 1
+  base_url: https://integrate.api.nvidia.com/v1
+reranker:
+  model: nvidia/llama-3.2-nv-rerankqa-1b-v2
+";
+        assert_eq!(yaml_endpoint_models(values), vec![(6, None)]);
+
+        // Documents of a multi-document file, and top-level keys separated by a blank line
+        let documents = "\
+model: meta/llama-3.1-70b-instruct
+---
+base_url: https://integrate.api.nvidia.com/v1
+...
+---
+model: nvidia/nv-embedqa-e5-v5
+";
+        assert_eq!(yaml_endpoint_models(documents), vec![(3, None)]);
+        let flat = "\
+LLM_ENDPOINT: https://integrate.api.nvidia.com/v1
+model: meta/llama-3.1-70b-instruct
+
+EMBEDDINGS_ENDPOINT: https://integrate.api.nvidia.com/v1
+";
+        assert_eq!(yaml_endpoint_models(flat), vec![
+            (1, Some("meta/llama-3.1-70b-instruct".to_string())),
+            (4, None),
+        ]);
+
+        // List items: the item's own first line is in its block, the previous item is not
+        let items = "\
+endpoints:
+  - name: chat
+    model: meta/llama-3.1-70b-instruct
+  - name: embed
+    url: https://integrate.api.nvidia.com/v1
+  - url: https://integrate.api.nvidia.com/v1
+    model: nvidia/nv-embedqa-e5-v5
+";
+        assert_eq!(yaml_endpoint_models(items), vec![
+            (5, None),
+            (6, Some("nvidia/nv-embedqa-e5-v5".to_string())),
+        ]);
     }
 
     #[test]
-    fn test_extract_hosted_nim_doc_prose() {
-        let line = "for nvidia/llama-3.2-nv-embedqa-1b-v2 model the Llama 3.2 Community License";
-        let result = extract_hosted_nim(line, 1, "deploy/README.md", "test/repo");
-        assert!(!result.is_empty());
-        assert_eq!(result[0].model_name.as_deref(), Some("nvidia/llama-3.2-nv-embedqa-1b-v2"));
+    fn test_yaml_context_same_block_nine_lines_away() {
+        // Deeper keys, a comment and a blank line inside the block don't end it
+        let values = "\
+services:
+  chat:
+    model_name: meta/llama-3.1-70b-instruct
+    # sampling
+    temperature: 0.2
+    top_p: 0.7
+    retries:
+      count: 3
+      backoff: 2
 
-        let line2 = "nvidia/llama-3.2-nv-embedqa-1b-v2model the Llama"; // typo: v2model
-        let result2 = extract_hosted_nim(line2, 1, "README.md", "test/repo");
-        assert!(!result2.is_empty());
-        assert_eq!(result2[0].model_name.as_deref(), Some("nvidia/llama-3.2-nv-embedqa-1b-v2"));
+    base_url: https://integrate.api.nvidia.com/v1
+  embed:
+    replicas: 1
+";
+        assert_eq!(yaml_endpoint_models(values), vec![(11, Some("meta/llama-3.1-70b-instruct".to_string()))]);
     }
 
     #[test]
-    fn test_extract_hosted_nim_model_name_assign() {
-        let line = r#"      model_name: "nvidia/llama-3.2-nv-embedqa-1b-v2"#;
-        let result = extract_hosted_nim(line, 1, "docs/03-configuration.md", "test/data-flywheel");
-        assert_eq!(result.len(), 1);
-        assert_eq!(result[0].model_name.as_deref(), Some("nvidia/llama-3.2-nv-embedqa-1b-v2"));
+    fn test_scan_files_listed() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join(".github/workflows")).unwrap();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join(".github/workflows/ci.yml"),
+                       "jobs:\n  test:\n    container: nvcr.io/nim/meta/llama-3.1-8b-instruct:1.0.0\n").unwrap();
+        std::fs::write(root.join("src/app.py"),
+                       "client.chat.completions.create(model=\"meta/llama-3.1-70b-instruct\")\n").unwrap();
+        // Not listed, so never scanned
+        std::fs::write(root.join("Dockerfile"), "FROM nvcr.io/nim/nvidia/other:1.0.0\n").unwrap();
+
+        let listed = vec![
+            ".github/workflows/ci.yml".to_string(),
+            "src/app.py".to_string(),
+            "src/missing.py".to_string(),
+            "../outside.py".to_string(),
+        ];
+        let outcome = scan_files(root, "test/repo", &listed, &ScanOptions::default());
+        assert_eq!(outcome.files.len(), 2);
+        assert_eq!(
+            outcome.listed_file_errors.iter().map(|e| (e.file_path.as_str(), e.reason.as_str())).collect::<Vec<_>>(),
+            vec![("src/missing.py", "not found"), ("../outside.py", "outside the repository")]
+        );
+        assert!(outcome.listed_file_errors.iter().all(|e| e.repository == "test/repo"));
+
+        // Findings go through the same categorization as a full scan
+        let (source_code, actions_workflow) = categorize_results(outcome.local, outcome.hosted);
+        assert_eq!(actions_workflow.local_nim.len(), 1);
+        assert_eq!(actions_workflow.local_nim[0].file_path, ".github/workflows/ci.yml");
+        assert_eq!(source_code.hosted_nim.len(), 1);
+        assert_eq!(source_code.hosted_nim[0].model_name.as_deref(), Some("meta/llama-3.1-70b-instruct"));
+        assert!(source_code.local_nim.is_empty());
     }
 
     #[test]
-    fn test_extract_hosted_nim_env_or_config_model() {
-        // As in .ipynb JSON: os.environ["APP_EMBEDDINGS_MODELNAME"] = \"nvidia/llama-3.2-nv-embedqa-1b-v2\"
-        let line = r#"    "os.environ[\"APP_EMBEDDINGS_MODELNAME\"] = \"nvidia/llama-3.2-nv-embedqa-1b-v2\"\n","#;
-        let result = extract_hosted_nim(line, 1, "notebooks/get_started_nvidia_api.ipynb", "test/aiq");
-        assert_eq!(result.len(), 1);
-        assert_eq!(result[0].model_name.as_deref(), Some("nvidia/llama-3.2-nv-embedqa-1b-v2"));
+    fn test_scan_directory_skip_dirs_config() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("dist")).unwrap();
+        std::fs::create_dir_all(root.join("fixtures")).unwrap();
+        std::fs::write(root.join("dist/docker-compose.yaml"),
+                       "image: nvcr.io/nim/nvidia/dist-model:1.0.0\n").unwrap();
+        std::fs::write(root.join("fixtures/docker-compose.yaml"),
+                       "image: nvcr.io/nim/nvidia/fixture-model:1.0.0\n").unwrap();
 
-        // Normal Python: ] = "nvidia/..."
-        let line2 = r#"os.environ["APP_EMBEDDINGS_MODELNAME"] = "nvidia/llama-3.2-nv-embedqa-1b-v2""#;
-        let result2 = extract_hosted_nim(line2, 1, "config.py", "test/repo");
-        assert_eq!(result2.len(), 1);
-        assert_eq!(result2[0].model_name.as_deref(), Some("nvidia/llama-3.2-nv-embedqa-1b-v2"));
+        // Built-in list skips dist/ but not fixtures/
+        let opts = ScanOptions { skip_dirs: resolve_skip_dirs(&RepoConfig::default()), ..Default::default() };
+        let local = scan_directory(root, "test/repo", &opts).local;
+        assert_eq!(local.len(), 1);
+        assert_eq!(local[0].image_url, "nvcr.io/nim/nvidia/fixture-model");
+
+        // Removing dist and adding fixtures flips that
+        let repo = RepoConfig {
+            skip_dirs_add: vec!["fixtures".to_string()],
+            skip_dirs_remove: vec!["dist".to_string()],
+            ..Default::default()
+        };
+        let opts = ScanOptions { skip_dirs: resolve_skip_dirs(&repo), ..Default::default() };
+        let local = scan_directory(root, "test/repo", &opts).local;
+        assert_eq!(local.len(), 1);
+        assert_eq!(local[0].image_url, "nvcr.io/nim/nvidia/dist-model");
     }
 
     #[test]
@@ -901,12 +6315,683 @@ mod tests {
         assert!(should_scan_file(Path::new("Dockerfile")));
         assert!(should_scan_file(Path::new("deploy/Dockerfile.prod")));
         assert!(should_scan_file(Path::new("script.sh")));
+        assert!(should_scan_file(Path::new("next.config.mjs")));
         
         assert!(!should_scan_file(Path::new("image.png")));
         assert!(!should_scan_file(Path::new("data.csv")));
         // Note: .json files are scanned (package-lock.json would match)
     }
 
+    #[test]
+    fn test_should_scan_env_files() {
+        for name in [".env", ".env.production", ".env.local.example", "env.sample", "env.example", "dev.env", "deploy/.ENV.staging"] {
+            assert!(should_scan_file(Path::new(name)), "{}", name);
+        }
+        assert!(!should_scan_file(Path::new("environment")));
+        assert!(!should_scan_file(Path::new("envoy.txt")));
+    }
+
+    #[test]
+    fn test_scan_makefile_and_justfile() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("Makefile"), "\
+NIM_IMAGE := nvcr.io/nim/meta/llama-3.1-8b-instruct
+TAG ?= 1.3.3 # pinned
+
+.PHONY: run-nim
+run-nim:
+\tdocker run --gpus all $(NIM_IMAGE):$(TAG)
+
+run-embed:
+\tdocker run --gpus all nvcr.io/nim/nvidia/nv-embedqa-e5-v5:$(EMBED_TAG)
+").unwrap();
+        std::fs::create_dir_all(temp_dir.path().join("deploy")).unwrap();
+        std::fs::write(temp_dir.path().join("deploy/justfile"), "\
+tag := \"1.0.0\"
+
+run:
+    docker run nvcr.io/nim/nvidia/nv-embedqa-e5-v5:{{ tag }}
+").unwrap();
+        for name in ["Makefile", "makefile", "GNUmakefile", "justfile"] {
+            assert!(should_scan_file(Path::new(name)), "{}", name);
+        }
+        assert!(!should_scan_file(Path::new("Makefile.am")));
+
+        let outcome = scan_directory(temp_dir.path(), "test/repo", &ScanOptions::default());
+        let mut local = outcome.local.clone();
+        local.sort_by(|a, b| (&a.file_path, a.line_number).cmp(&(&b.file_path, b.line_number)));
+        let found: Vec<(&str, usize, &str, &str, Option<&str>)> = local
+            .iter()
+            .map(|m| (m.file_path.as_str(), m.line_number, m.tag.as_str(), m.detection_rule.as_str(), m.resolved_from.as_deref()))
+            .collect();
+        assert_eq!(found, vec![
+            ("Makefile", 1, "latest", "local_nim_no_tag", None),
+            ("Makefile", 6, "1.3.3", "local_nim_full", Some("NIM_IMAGE,TAG")),
+            ("Makefile", 9, "latest", "local_nim_unresolved_var", None),
+            ("deploy/justfile", 4, "1.0.0", "local_nim_full", Some("tag")),
+        ]);
+        // Tab-indented recipe lines are commands like any other
+        assert!(local[1..].iter().all(|m| m.intent == LocalIntent::Run));
+        assert_eq!(local[1].match_context, "docker run --gpus all $(NIM_IMAGE):$(TAG)");
+        assert_eq!(
+            local[2].match_context,
+            "docker run --gpus all nvcr.io/nim/nvidia/nv-embedqa-e5-v5:$(EMBED_TAG) (tag $(EMBED_TAG) not assigned in this file)"
+        );
+    }
+
+    #[test]
+    fn test_scan_nim_env_vars_kubernetes() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("deploy").join("llm.yaml");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, r#"apiVersion: apps/v1
+kind: Deployment
+metadata:
+  name: llm
+spec:
+  template:
+    spec:
+      containers:
+        - name: nim
+          image: nvcr.io/nim/nvidia/llm-nim:1.12.0
+          env:
+            - name: NIM_MODEL_NAME
+              value: "ngc://nim/meta/llama-3.1-8b-instruct"
+            - name: NIM_SERVED_MODEL_NAME
+              value: meta/llama-3.1-8b-instruct
+            - name: NIM_MODEL_PROFILE
+              value: tensorrt_llm-h100-fp8-tp1-throughput
+            - name: NGC_API_KEY
+              valueFrom:
+                secretKeyRef:
+                  name: ngc-api
+                  key: NGC_API_KEY
+            - name: LOG_LEVEL
+              value: info
+"#).unwrap();
+
+        let (local, hosted, _) = scan_file(&path, "test/repo", temp_dir.path(), &ScanOptions::default());
+        let found: Vec<(usize, &str, &str, &str)> = local.iter()
+            .map(|m| (m.line_number, m.image_url.as_str(), m.tag.as_str(), m.detection_rule.as_str()))
+            .collect();
+        let image = "nvcr.io/nim/nvidia/llm-nim";
+        assert_eq!(found[1..], [
+            (12, image, "1.12.0", "nim_env_var"),
+            (14, image, "1.12.0", "nim_env_var"),
+            (16, image, "1.12.0", "nim_env_var"),
+            (18, image, "1.12.0", "nim_env_var"),
+        ]);
+        assert_eq!(local[0].line_number, 10);
+        assert_eq!(local[1].match_context, "- name: NIM_MODEL_NAME");
+        // The served model is the local NIM's, not a Hosted NIM
+        assert!(hosted.is_empty(), "{:?}", hosted);
+    }
+
+    #[test]
+    fn test_scan_nim_env_vars() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let env = temp_dir.path().join(".env");
+        std::fs::write(&env, "\
+NIM_ENDPOINT=https://integrate.api.nvidia.com/v1
+NIM_SERVED_MODEL_NAME=meta/llama-3.1-70b-instruct
+NVIDIA_API_KEY=nvapi-abcdefghijklmnopqrstuvwxyz0123456789
+NIM_MODEL_PROFILE=tensorrt_llm-h100
+").unwrap();
+        let (local, hosted, _) = scan_file(&env, "test/repo", temp_dir.path(), &ScanOptions::default());
+        assert!(local.is_empty());
+        let found: Vec<(usize, Option<&str>, Option<&str>, &str)> = hosted.iter()
+            .map(|m| (m.line_number, m.endpoint_url.as_deref(), m.model_name.as_deref(), m.detection_rule.as_str()))
+            .collect();
+        let endpoint = Some("https://integrate.api.nvidia.com/v1");
+        assert_eq!(found[1..], [
+            (2, None, Some("meta/llama-3.1-70b-instruct"), "nim_env_var"),
+            // The key goes with the nearest Hosted NIM; the profile names none
+            (3, None, Some("meta/llama-3.1-70b-instruct"), "nim_env_var"),
+        ]);
+        assert_eq!(found[0].1, endpoint);
+        assert_eq!(hosted[2].match_context, "NVIDIA_API_KEY=REDACTED");
+
+        // Shell: keys passed to a local NIM, redacted in the image's own finding too
+        let script = temp_dir.path().join("run.sh");
+        std::fs::write(&script, "\
+export NGC_API_KEY=nvapi-secret-value-0000
+docker run --gpus all -e NGC_API_KEY=nvapi-secret-value-0000 \\
+  -e NIM_MODEL_PROFILE=vllm-bf16-tp1 \\
+  nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3.3
+").unwrap();
+        let (local, hosted, _) = scan_file(&script, "test/repo", temp_dir.path(), &ScanOptions::default());
+        assert!(hosted.is_empty());
+        let lines: Vec<(usize, &str)> = local.iter().map(|m| (m.line_number, m.detection_rule.as_str())).collect();
+        assert_eq!(lines.iter().map(|(line, _)| *line).collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+        assert_eq!(lines[0].1, "nim_env_var");
+        for m in &local {
+            assert!(!m.match_context.contains("nvapi-secret"), "{}", m.match_context);
+            assert_eq!(m.image_url, "nvcr.io/nim/meta/llama-3.1-8b-instruct");
+        }
+        assert_eq!(local[0].match_context, "export NGC_API_KEY=REDACTED");
+
+        // Compose: map form under environment
+        let compose = temp_dir.path().join("docker-compose.yaml");
+        std::fs::write(&compose, "\
+services:
+  llm:
+    image: nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3.3
+    environment:
+      NGC_API_KEY: ${NGC_API_KEY}
+      NIM_SERVED_MODEL_NAME: \"meta/llama-3.1-8b-instruct\"
+").unwrap();
+        let (local, hosted, _) = scan_file(&compose, "test/repo", temp_dir.path(), &ScanOptions::default());
+        assert!(hosted.is_empty(), "{:?}", hosted);
+        assert_eq!(local.iter().map(|m| m.line_number).collect::<Vec<_>>(), vec![3, 5, 6]);
+        assert_eq!(local[1].match_context, "NGC_API_KEY: REDACTED");
+
+        // A key with no NIM in the file is not reported
+        let lone = temp_dir.path().join("keys.env");
+        std::fs::write(&lone, "NGC_API_KEY=nvapi-abc\\nNIM_MODEL_NAME=/models/llama\\n").unwrap();
+        let (local, hosted, _) = scan_file(&lone, "test/repo", temp_dir.path(), &ScanOptions::default());
+        assert!(local.is_empty() && hosted.is_empty());
+    }
+
+    #[test]
+    fn test_scan_dotenv_production() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join(".env.production");
+        std::fs::write(&path, "\
+# production settings
+NIM_MODEL=meta/llama-3.1-8b-instruct
+export EMBEDDING_MODEL=nvidia/nv-embedqa-e5-v5  # retriever
+NIM_ENDPOINT=https://integrate.api.nvidia.com/v1
+NIM_IMAGE=nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3.3
+OTHER_MODEL=local/not-a-nim
+").unwrap();
+
+        assert!(should_scan_file(&path));
+        let (local, hosted, _) = scan_file(&path, "test/repo", temp_dir.path(), &ScanOptions::default());
+        assert_eq!(local.len(), 1);
+        assert_eq!(local[0].tag, "1.3.3");
+        let found: Vec<(usize, Option<&str>, Option<&str>)> = hosted
+            .iter()
+            .map(|m| (m.line_number, m.model_name.as_deref(), m.endpoint_url.as_deref()))
+            .collect();
+        assert_eq!(found, vec![
+            (2, Some("meta/llama-3.1-8b-instruct"), None),
+            (3, Some("nvidia/nv-embedqa-e5-v5"), None),
+            (4, None, Some("https://integrate.api.nvidia.com/v1")),
+        ]);
+    }
+
+    #[test]
+    fn test_scan_js_fetch_with_separate_body() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("chat.mjs");
+        std::fs::write(&path, r#"export async function chat(messages) {
+  const body = {
+    model: "meta/llama-3.1-70b-instruct",
+    messages,
+    temperature: 0.2,
+  };
+  const res = await fetch("https://integrate.api.nvidia.com/v1/chat/completions", {
+    method: "POST",
+    headers: { Authorization: `Bearer ${process.env.NVIDIA_API_KEY}` },
+    body: JSON.stringify(body),
+  });
+  return res.json();
+}
+
+const client = axios.create({
+  baseURL: "https://ai.api.nvidia.com/v1",
+  timeout: 30000,
+});
+"#).unwrap();
+
+        assert!(should_scan_file(&path));
+        let (_, hosted, _) = scan_file(&path, "test/repo", temp_dir.path(), &ScanOptions::default());
+        let fetch = hosted.iter().find(|m| m.line_number == 7).expect("fetch endpoint");
+        assert_eq!(fetch.model_name.as_deref(), Some("meta/llama-3.1-70b-instruct"));
+        assert_eq!(fetch.detection_rule, "js_request_model");
+        assert!(!fetch.browser_exposed);
+        let base = hosted.iter().find(|m| m.line_number == 16).expect("baseURL endpoint");
+        assert_eq!(base.endpoint_url.as_deref(), Some("https://ai.api.nvidia.com/v1"));
+        assert_eq!(base.detection_rule, "js_base_url");
+    }
+
+    #[test]
+    fn test_scan_next_config_env_block() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("next.config.mjs");
+        std::fs::write(&path, r#"/** @type {import('next').NextConfig} */
+const nextConfig = {
+  env: {
+    NEXT_PUBLIC_NIM_URL: "https://integrate.api.nvidia.com/v1",
+    NIM_MODEL: "nvidia/llama-3.1-nemotron-70b-instruct",
+    SERVER_NIM_URL: "https://ai.api.nvidia.com/v1",
+    OTHER_MODEL: "local/not-a-nim",
+  },
+};
+
+export default nextConfig;
+"#).unwrap();
+        let vite = temp_dir.path().join("vite.config.ts");
+        std::fs::write(&vite, r#"export default defineConfig({
+  define: {
+    'import.meta.env.VITE_LLM_MODEL': JSON.stringify("meta/llama-3.1-8b-instruct"),
+  },
+});
+"#).unwrap();
+
+        let (_, hosted, _) = scan_file(&path, "test/repo", temp_dir.path(), &ScanOptions::default());
+        let found: Vec<(usize, Option<&str>, Option<&str>, bool)> = hosted
+            .iter()
+            .map(|m| (m.line_number, m.model_name.as_deref(), m.endpoint_url.as_deref(), m.browser_exposed))
+            .collect();
+        assert_eq!(found, vec![
+            (4, None, Some("https://integrate.api.nvidia.com/v1"), true),
+            (5, Some("nvidia/llama-3.1-nemotron-70b-instruct"), None, false),
+            (6, None, Some("https://ai.api.nvidia.com/v1"), false),
+        ]);
+
+        let (_, hosted, _) = scan_file(&vite, "test/repo", temp_dir.path(), &ScanOptions::default());
+        assert_eq!(hosted.len(), 1);
+        assert_eq!(hosted[0].model_name.as_deref(), Some("meta/llama-3.1-8b-instruct"));
+        assert_eq!(hosted[0].detection_rule, "js_env_model");
+    }
+
+    #[test]
+    fn test_scan_html_code_blocks() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("site/deploy/index.html");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, r#"<!DOCTYPE html>
+<html>
+<head><script>window.config = {model: "meta/llama-3.1-405b-instruct"};</script></head>
+<body>
+<nav><a href="/models">Models like nvidia/nv-embedqa-e5-v5</a></nav>
+<p>Start the container:</p>
+<pre><code class="language-bash"><span class="token">docker run --gpus all \
+  nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3.3</span>
+</code></pre>
+<div class="codeBlock"><pre><code>client.chat.completions.create(
+  model=<span class="token string">&quot;meta&#x2F;llama-3.1-70b-instruct&quot;</span>,
+)</code></pre></div>
+</body>
+</html>
+"#).unwrap();
+
+        assert!(should_scan_file(&path));
+        let (local, hosted, stats) = scan_file(&path, "test/repo", temp_dir.path(), &ScanOptions::default());
+        assert!(!stats.prefiltered);
+        assert_eq!(local.len(), 1);
+        assert_eq!((local[0].line_number, local[0].tag.as_str()), (8, "1.3.3"));
+        // Script and navigation text is not scanned; the entity-encoded model is
+        let found: Vec<(usize, Option<&str>)> = hosted
+            .iter()
+            .map(|m| (m.line_number, m.model_name.as_deref()))
+            .collect();
+        assert_eq!(found, vec![(11, Some("meta/llama-3.1-70b-instruct"))]);
+    }
+
+    #[test]
+    fn test_extract_html_code() {
+        let html = "<p>a</p>\n<PRE>x &lt; y &amp;&amp; &#39;q&#39;\n<b\nclass=\"z\">bold</b></PRE>\n<style>pre { }</style>&quot;\n";
+        assert_eq!(extract_html_code(html), "\nx < y && 'q'\n\nbold\n\n");
+        assert_eq!(decode_html_entities("&unknown; &#x2F;&#47;"), "&unknown; //");
+    }
+
+    #[test]
+    fn test_scan_markdown_code() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let readme = temp_dir.path().join("README.md");
+        std::fs::write(&readme, r#"# Embedding NIM [![NIM](https://img.shields.io/badge/nvcr.io%2Fnim%2Fnvidia-blue)](https://build.nvidia.com/nvidia/nv-embedqa-e5-v5)
+
+Serves nvcr.io/nim/nvidia/nv-embedqa-e5-v5:1.0.0 for the nvidia/nv-embedqa-e5-v5 model.
+Pin `nvcr.io/nim/nvidia/nv-embedqa-e5-v5:1.0.0` in production.
+
+```bash
+docker run --gpus all nvcr.io/nim/nvidia/nv-embedqa-e5-v5:1.0.0
+```
+
+~~~~
+curl https://integrate.api.nvidia.com/v1/chat/completions \
+  -d '{"model": "meta/llama-3.1-70b-instruct"}'
+~~~~
+"#).unwrap();
+
+        let (local, hosted, _) = scan_file(&readme, "test/repo", temp_dir.path(), &ScanOptions::default());
+        let found: Vec<(usize, LocalIntent)> = local.iter().map(|m| (m.line_number, m.intent)).collect();
+        assert_eq!(found, vec![(4, LocalIntent::Reference), (7, LocalIntent::Run)]);
+        let found: Vec<(usize, Option<&str>)> = hosted.iter().map(|m| (m.line_number, m.model_name.as_deref())).collect();
+        assert_eq!(found, vec![(12, Some("meta/llama-3.1-70b-instruct"))]);
+        assert!(local.iter().all(|m| m.file_kind == FileKind::Docs));
+
+        // Executable code only
+        let opts = ScanOptions { skip_docs: true, ..Default::default() };
+        std::fs::write(temp_dir.path().join("Dockerfile"), "FROM nvcr.io/nim/nvidia/nv-embedqa-e5-v5:1.0.0\n").unwrap();
+        let outcome = scan_directory(temp_dir.path(), "test/repo", &opts);
+        let files: Vec<&str> = outcome.files.iter().map(|f| f.file_path.as_str()).collect();
+        assert_eq!(files, vec!["Dockerfile"]);
+    }
+
+    #[test]
+    fn test_scan_markdown_build_page_links() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("README.md"), "\
+# Demo [![NIM](https://img.shields.io/badge/NIM-green)](https://build.nvidia.com/nvidia/nv-embedqa-e5-v5)
+
+Try it at https://build.nvidia.com/meta/llama-3_1-405b-instruct today.
+The reranker is [on the catalog](https://build.nvidia.com/nvidia/llama-3_2-nv-rerankqa-1b-v2/modelcard).
+").unwrap();
+
+        let outcome = scan_directory(temp_dir.path(), "test/repo", &ScanOptions::default());
+        let found: Vec<(usize, &str, Option<&str>)> = outcome.hosted.iter()
+            .map(|m| (m.line_number, m.detection_rule.as_str(), m.model_name.as_deref()))
+            .collect();
+        assert_eq!(found, vec![
+            (3, "build_page_url", Some("meta/llama-3.1-405b-instruct")),
+            (4, "build_page_url", Some("nvidia/llama-3.2-nv-rerankqa-1b-v2")),
+        ]);
+        assert!(outcome.hosted.iter().all(|m| m.file_kind == FileKind::Docs && m.endpoint_url.is_none()));
+    }
+
+    #[test]
+    fn test_extract_markdown_code() {
+        let markdown = "Run `a` or ``b `c` d`` but not `e\n```py\nx = 1\n```` \n```\nafter\n";
+        assert_eq!(extract_markdown_code(markdown), "a b `c` d\n```py\nx = 1\n```` \n```\nafter\n");
+        // Build Page links are kept from prose, unless a badge wraps them
+        let markdown = "See [the model](https://build.nvidia.com/meta/llama-3_1-8b-instruct) or `x`.\n\
+                        [![NIM](https://img.shields.io/badge/nim-blue)](https://build.nvidia.com/nvidia/nv-embedqa-e5-v5)\n";
+        assert_eq!(extract_markdown_code(markdown), "x https://build.nvidia.com/meta/llama-3_1-8b-instruct\n\n");
+        assert_eq!(inline_code_spans("no spans, just `` a lone run"), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn test_scan_credential_sources() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let app = temp_dir.path().join("app.py");
+        std::fs::write(&app, r#"import os
+client = OpenAI(
+    base_url="https://integrate.api.nvidia.com/v1",
+    api_key=os.environ["NVIDIA_API_KEY"],
+)
+legacy = OpenAI(base_url=os.environ["NIM_URL"], api_key=os.getenv("NGC_API_KEY"), model="meta/llama-3.1-8b-instruct")
+hardcoded = OpenAI(base_url="https://integrate.api.nvidia.com/v1", api_key="nvapi-AbCdEf0123456789xyzXYZ_abc")
+placeholder = OpenAI(base_url="https://integrate.api.nvidia.com/v1", api_key="your-api-key-goes-here")
+"#).unwrap();
+        let workflow = temp_dir.path().join(".github/workflows/eval.yml");
+        std::fs::create_dir_all(workflow.parent().unwrap()).unwrap();
+        std::fs::write(&workflow, r#"on: push
+jobs:
+  eval:
+    runs-on: ubuntu-latest
+    steps:
+      - run: |
+          curl https://integrate.api.nvidia.com/v1/chat/completions \
+            -H "Authorization: Bearer ${{ secrets.NVIDIA_API_KEY }}"
+"#).unwrap();
+
+        let (_, hosted, _) = scan_file(&app, "test/repo", temp_dir.path(), &ScanOptions::default());
+        let found: Vec<(usize, Option<String>)> = hosted
+            .iter()
+            .map(|m| (m.line_number, m.credential_source.as_ref().map(|c| c.label())))
+            .collect();
+        assert_eq!(found, vec![
+            (3, Some("env:NVIDIA_API_KEY".to_string())),
+            (6, Some("env:NGC_API_KEY".to_string())),
+            (7, Some("literal".to_string())),
+            (8, None),
+        ]);
+        // The key itself is never kept
+        assert!(hosted[2].match_context.contains(r#"api_key="REDACTED""#), "{}", hosted[2].match_context);
+        assert!(!hosted.iter().any(|m| m.match_context.contains("nvapi-")));
+
+        let (_, hosted, _) = scan_file(&workflow, "test/repo", temp_dir.path(), &ScanOptions::default());
+        assert_eq!(hosted.len(), 1);
+        assert_eq!(hosted[0].credential_source, Some(CredentialSource::Secret { name: "NVIDIA_API_KEY".to_string() }));
+    }
+
+    #[test]
+    fn test_scan_frameworks() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let files = [
+            ("raw.py", r#"import requests
+
+url = "https://integrate.api.nvidia.com/v1/chat/completions"
+response = requests.post(url, json={"model": "meta/llama-3.1-8b-instruct"})
+"#),
+            ("call.sh", r#"#!/bin/sh
+curl -s \
+  https://integrate.api.nvidia.com/v1/chat/completions \
+  -H "Authorization: Bearer $NVIDIA_API_KEY"
+"#),
+            ("client.ts", r#"import OpenAI from "openai";
+const client = new OpenAI({
+  baseURL: "https://integrate.api.nvidia.com/v1",
+});
+"#),
+            ("chain.py", r#"from langchain_nvidia_ai_endpoints import ChatNVIDIA
+llm = ChatNVIDIA(model="meta/llama-3.1-70b-instruct")
+"#),
+            ("index.py", r#"from llama_index.llms.nvidia import NVIDIA
+Settings.llm = NVIDIA(model="meta/llama-3.1-70b-instruct")
+"#),
+            ("settings.py", r#"model = "meta/llama-3.1-70b-instruct"
+"#),
+        ];
+        let mut found = Vec::new();
+        for (name, content) in files {
+            let path = temp_dir.path().join(name);
+            std::fs::write(&path, content).unwrap();
+            let (_, hosted, _) = scan_file(&path, "test/repo", temp_dir.path(), &ScanOptions::default());
+            found.extend(hosted.iter().map(|m| (name, m.framework)));
+        }
+        // The url and the request's payload model are one finding
+        assert_eq!(found, vec![
+            ("raw.py", Framework::RawHttp),
+            ("call.sh", Framework::Curl),
+            ("client.ts", Framework::OpenaiSdk),
+            ("chain.py", Framework::Langchain),
+            ("index.py", Framework::LlamaIndex),
+            ("settings.py", Framework::Unknown),
+        ]);
+    }
+
+    #[test]
+    fn test_classify_file_kind() {
+        let deployment = "apiVersion: apps/v1\nkind: Deployment\nspec:\n  template:\n    spec:\n      containers:\n        - image: nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3.3\n";
+        let cases = [
+            ("Dockerfile", "", FileKind::Dockerfile),
+            ("deploy/Dockerfile.gpu", "", FileKind::Dockerfile),
+            ("build/nim.dockerfile", "", FileKind::Dockerfile),
+            ("Containerfile", "", FileKind::Dockerfile),
+            ("docker-compose.gpu.yaml", "", FileKind::Compose),
+            ("deploy/compose.yml", "", FileKind::Compose),
+            ("deploy/helm/nim/values-prod.yaml", "", FileKind::Helm),
+            ("charts/nim/values.yaml", "", FileKind::Helm),
+            ("charts/nim/Chart.yaml", "", FileKind::Helm),
+            ("charts/nim/templates/deployment.yaml", "image: {{ .Values.image }}", FileKind::Helm),
+            ("k8s/deployment.yaml", deployment, FileKind::Kubernetes),
+            ("k8s/list.json", "{\n  \"apiVersion\": \"v1\",\n  \"kind\": \"List\"\n}", FileKind::Kubernetes),
+            ("notebooks/analysis.ipynb", "", FileKind::Notebook),
+            ("README.md", "", FileKind::Docs),
+            ("docs/site/index.html", "", FileKind::Docs),
+            ("docs/guide.rst", "", FileKind::Docs),
+            (".github/workflows/ci.yml", deployment, FileKind::Ci),
+            (".gitlab-ci.yml", "", FileKind::Ci),
+            ("ci/Jenkinsfile", "", FileKind::Ci),
+            (".circleci/config.yml", "", FileKind::Ci),
+            ("src/app.py", "", FileKind::Source),
+            ("config/settings.yaml", "model: meta/llama-3.1-8b-instruct", FileKind::Source),
+            // A template without template syntax, outside a chart, is just a manifest
+            ("templates/job.yaml", deployment, FileKind::Kubernetes),
+        ];
+        for (path, content, expected) in cases {
+            assert_eq!(classify_file_kind(path, content), expected, "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_scan_notebook() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let notebooks = temp_dir.path().join("notebooks");
+        std::fs::create_dir_all(&notebooks).unwrap();
+        std::fs::write(notebooks.join("rag.ipynb"), r##"{
+ "cells": [
+  {
+   "cell_type": "markdown",
+   "metadata": {},
+   "source": [
+    "# RAG with NIM\n",
+    "This notebook calls nvidia/llama-3.1-nemotron-70b-instruct through LangChain"
+   ]
+  },
+  {
+   "cell_type": "code",
+   "execution_count": 2,
+   "metadata": {},
+   "outputs": [
+    {
+     "data": {"text/plain": ["ChatNVIDIA(model='meta/llama-3.1-8b-instruct')"]},
+     "output_type": "execute_result"
+    }
+   ],
+   "source": [
+    "from langchain_nvidia_ai_endpoints import ChatNVIDIA\n",
+    "\n",
+    "llm = ChatNVIDIA(model=\"meta/llama-3.1-70b-instruct\")"
+   ]
+  }
+ ],
+ "metadata": {"language_info": {"name": "python", "file_extension": ".py"}},
+ "nbformat": 4,
+ "nbformat_minor": 5
+}
+"##).unwrap();
+        // Not valid JSON: scanned line by line as before
+        std::fs::write(notebooks.join("broken.ipynb"), "{\"cells\": [\n  \"llm = ChatNVIDIA(model='meta/llama-3.1-8b-instruct')\"\n").unwrap();
+
+        let files = scan_directory(temp_dir.path(), "test/repo", &ScanOptions::default());
+        let mut hosted = files.hosted;
+        hosted.sort_by(|a, b| (&a.file_path, a.line_number).cmp(&(&b.file_path, b.line_number)));
+        let found: Vec<(&str, usize, Option<&str>, Option<NotebookCell>)> = hosted
+            .iter()
+            .map(|m| (m.file_path.as_str(), m.line_number, m.model_name.as_deref(), m.notebook_cell))
+            .collect();
+        assert_eq!(found, vec![
+            ("notebooks/broken.ipynb", 2, Some("meta/llama-3.1-8b-instruct"), None),
+            ("notebooks/rag.ipynb", 8, Some("nvidia/llama-3.1-nemotron-70b-instruct"),
+             Some(NotebookCell { index: 0, line: 2, kind: NotebookCellKind::Markdown })),
+            ("notebooks/rag.ipynb", 24, Some("meta/llama-3.1-70b-instruct"),
+             Some(NotebookCell { index: 1, line: 3, kind: NotebookCellKind::Code })),
+        ]);
+        // Code cells are scanned as Python, outputs not at all
+        let code = &hosted[2];
+        assert_eq!(code.detection_rule, "model_assign");
+        assert_eq!(code.framework, Framework::Langchain);
+        assert_eq!(code.file_kind, FileKind::Notebook);
+        assert_eq!(code.match_context, r#"llm = ChatNVIDIA(model="meta/llama-3.1-70b-instruct")"#);
+    }
+
+    #[test]
+    fn test_scan_env_sample_without_extension() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("env.sample");
+        std::fs::write(&path, "LLM_MODEL='mistralai/mixtral-8x7b-instruct-v0.1'\n").unwrap();
+
+        let files = scan_directory(temp_dir.path(), "test/repo", &ScanOptions::default());
+        assert_eq!(files.hosted.len(), 1);
+        assert_eq!(files.hosted[0].model_name.as_deref(), Some("mistralai/mixtral-8x7b-instruct-v0.1"));
+        assert_eq!(files.hosted[0].file_path, "env.sample");
+    }
+
+    #[test]
+    fn test_bare_model_names() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("client.py");
+        std::fs::write(&path, "\
+llm = client.chat(model=\"nemotron-4-340b-instruct\")
+embedder = Embeddings(model='nvolveqa_40k')
+other = client.chat(model=\"gpt-4o-mini\")
+custom = client.chat(model=\"Nemo-Custom-7B\")
+").unwrap();
+        let (_, hosted, stats) = scan_file(&path, "test/repo", temp_dir.path(), &ScanOptions::default());
+        assert!(!stats.prefiltered);
+        let found: Vec<(&str, &str)> = hosted.iter()
+            .map(|m| (m.model_name.as_deref().unwrap(), m.detection_rule.as_str()))
+            .collect();
+        assert_eq!(found, vec![("nemotron-4-340b-instruct", "bare_model_assign"), ("nvolveqa_40k", "bare_model_assign")]);
+
+        // A configured name is counted too, and gets past the prefilter on its own
+        std::fs::write(&path, "custom = client.chat(model=\"Nemo-Custom-7B\")\n").unwrap();
+        let opts = ScanOptions { bare_models: vec!["nemo-custom-7b".to_string()], ..Default::default() };
+        let (_, hosted, _) = scan_file(&path, "test/repo", temp_dir.path(), &opts);
+        assert_eq!(hosted.len(), 1);
+        assert_eq!(hosted[0].model_name.as_deref(), Some("Nemo-Custom-7B"));
+        let (_, hosted, stats) = scan_file(&path, "test/repo", temp_dir.path(), &ScanOptions::default());
+        assert!(hosted.is_empty() && stats.prefiltered);
+
+        assert!(!is_bare_model_name("my-random-thing", &[]));
+        assert!(!is_bare_model_name("llama-guard", &[]));
+        assert!(is_bare_model_name("llama-3.1-8b-instruct", &[]));
+    }
+
+    #[test]
+    fn test_scan_metadata() {
+        let fields = vec![
+            (MetadataField::Description, "RAG blueprint built on the meta/llama-3.1-70b-instruct NIM".to_string()),
+            (MetadataField::Topic, "nvidia-nim".to_string()),
+            (MetadataField::Homepage, "https://build.nvidia.com/meta/llama-3.1-70b-instruct".to_string()),
+        ];
+        let hints = scan_metadata("acme/rag", &fields, &ScanOptions::default());
+        assert_eq!(hints.len(), 2, "{:?}", hints);
+        assert_eq!(hints[0].field, MetadataField::Description);
+        assert_eq!(hints[0].model_name.as_deref(), Some("meta/llama-3.1-70b-instruct"));
+        assert_eq!(hints[0].repository, "acme/rag");
+        assert_eq!(hints[1].field, MetadataField::Homepage);
+        assert_eq!(hints[1].model_name.as_deref(), Some("meta/llama-3.1-70b-instruct"));
+        assert_eq!(hints[1].detection_rule, "build_page_url");
+
+        let irrelevant = vec![(MetadataField::Description, "A chat UI for internal docs, written in Go".to_string())];
+        assert!(scan_metadata("acme/ui", &irrelevant, &ScanOptions::default()).is_empty());
+    }
+
+    #[test]
+    fn test_scan_external_secret_template() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("nim-secret.yaml");
+        std::fs::write(&path, "\
+apiVersion: external-secrets.io/v1beta1
+kind: ExternalSecret
+metadata:
+  name: nim-config
+spec:
+  target:
+    template:
+      data:
+        NIM_MODEL: \"meta/llama-3.1-8b-instruct\"
+        config.env: |
+          EMBED_MODEL=nvidia/nv-embedqa-e5-v5
+  data:
+    - secretKey: NGC_API_KEY
+      remoteRef:
+        key: ngc/api-key
+").unwrap();
+
+        let (_, hosted, _) = scan_file(&path, "test/repo", temp_dir.path(), &ScanOptions::default());
+        let found: Vec<(usize, Option<&str>)> = hosted.iter().map(|m| (m.line_number, m.model_name.as_deref())).collect();
+        assert_eq!(found, vec![(9, Some("meta/llama-3.1-8b-instruct")), (11, Some("nvidia/nv-embedqa-e5-v5"))]);
+
+        // The same quoted key outside a secret manifest is not picked up
+        let plain = temp_dir.path().join("values.yaml");
+        std::fs::write(&plain, "NIM_MODEL: \"meta/llama-3.1-8b-instruct\"\n").unwrap();
+        let (_, hosted, _) = scan_file(&plain, "test/repo", temp_dir.path(), &ScanOptions::default());
+        assert!(hosted.is_empty());
+    }
+
+    #[test]
+    fn test_largest_first() {
+        assert_eq!(largest_first(&[10, 5_000, 0, 300, 5_000]), vec![1, 4, 3, 0, 2]);
+        assert!(largest_first(&[]).is_empty());
+    }
+
     #[test]
     fn test_categorize_results() {
         let local = vec![
@@ -915,18 +7000,54 @@ mod tests {
                 image_url: "nvcr.io/nim/nvidia/test".to_string(),
                 tag: "1.0".to_string(),
                 resolved_tag: None,
+                tag_exists: None,
                 file_path: "Dockerfile".to_string(),
                 line_number: 1,
                 match_context: "FROM nvcr.io/nim/nvidia/test:1.0".to_string(),
+                policy: None,
+                enrichment_error: None,
+                enriched: false,
+                detection_rule: String::new(),
+                workflow_triggers: Vec::new(),
+                nim_identity: String::new(),
+                enrichment_skipped: None,
+                context_truncated: None,
+                compose: None,
+                intent: LocalIntent::Reference,
+                usage_kind: UsageKind::Other,
+                blame: None,
+                notebook_cell: None,
+                file_kind: FileKind::Source,
+                resolved_from: None,
+                fixture: false,
+                fingerprint: String::new(),
             },
             LocalNimMatch {
                 repository: "test".to_string(),
                 image_url: "nvcr.io/nim/nvidia/test2".to_string(),
                 tag: "2.0".to_string(),
                 resolved_tag: None,
+                tag_exists: None,
                 file_path: ".github/workflows/deploy.yml".to_string(),
                 line_number: 10,
                 match_context: "image: nvcr.io/nim/nvidia/test2:2.0".to_string(),
+                policy: None,
+                enrichment_error: None,
+                enriched: false,
+                detection_rule: String::new(),
+                workflow_triggers: Vec::new(),
+                nim_identity: String::new(),
+                enrichment_skipped: None,
+                context_truncated: None,
+                compose: None,
+                intent: LocalIntent::Reference,
+                usage_kind: UsageKind::Other,
+                blame: None,
+                notebook_cell: None,
+                file_kind: FileKind::Source,
+                resolved_from: None,
+                fixture: false,
+                fingerprint: String::new(),
             },
         ];
         
@@ -947,21 +7068,58 @@ mod tests {
                     image_url: "nvcr.io/nim/nvidia/test".to_string(),
                     tag: "1.0".to_string(),
                     resolved_tag: None,
+                    tag_exists: None,
                     file_path: "Dockerfile".to_string(),
                     line_number: 1,
                     match_context: "FROM nvcr.io/nim/nvidia/test:1.0".to_string(),
+                    policy: None,
+                    enrichment_error: None,
+                    enriched: false,
+                    detection_rule: String::new(),
+                    workflow_triggers: Vec::new(),
+                    nim_identity: String::new(),
+                    enrichment_skipped: None,
+                    context_truncated: None,
+                    compose: None,
+                    intent: LocalIntent::Reference,
+                    usage_kind: UsageKind::Other,
+                    blame: None,
+                    notebook_cell: None,
+                    file_kind: FileKind::Source,
+                    resolved_from: None,
+                    fixture: false,
+                    fingerprint: String::new(),
                 },
                 LocalNimMatch {
                     repository: "test".to_string(),
                     image_url: "nvcr.io/nim/nvidia/test".to_string(),
                     tag: "1.0".to_string(),
                     resolved_tag: None,
+                    tag_exists: None,
                     file_path: "Dockerfile".to_string(),
                     line_number: 1,  // Same line - duplicate
                     match_context: "FROM nvcr.io/nim/nvidia/test:1.0".to_string(),
+                    policy: None,
+                    enrichment_error: None,
+                    enriched: false,
+                    detection_rule: String::new(),
+                    workflow_triggers: Vec::new(),
+                    nim_identity: String::new(),
+                    enrichment_skipped: None,
+                    context_truncated: None,
+                    compose: None,
+                    intent: LocalIntent::Reference,
+                    usage_kind: UsageKind::Other,
+                    blame: None,
+                    notebook_cell: None,
+                    file_kind: FileKind::Source,
+                    resolved_from: None,
+                    fixture: false,
+                    fingerprint: String::new(),
                 },
             ],
             hosted_nim: vec![],
+            sdk_dependencies: vec![],
         };
         
         deduplicate_results(&mut findings);
@@ -0,0 +1,209 @@
+//! Progress file for external monitors
+//!
+//! A long scan is otherwise only visible through its log. `status.json` in the
+//! output directory holds the phase the run is in, how many repositories are
+//! done and how many findings they produced so far. It is rewritten at every
+//! phase change and, while repositories complete, at most every few seconds;
+//! each write goes to a temporary file renamed over the previous one, so a
+//! reader never sees a partial file. The last write has phase `done`.
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use anyhow::{Context, Result};
+use log::warn;
+use serde::Serialize;
+
+/// File name of the progress file in the output directory
+pub const STATUS_FILE: &str = "status.json";
+
+/// Time between writes while repositories complete within one phase
+const WRITE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Stage of the run a monitor sees
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    Starting,
+    Cloning,
+    Scanning,
+    Enriching,
+    Reporting,
+    Done,
+}
+
+/// Contents of status.json
+#[derive(Debug, Clone, Serialize)]
+struct Status {
+    phase: Phase,
+    repos_completed: usize,
+    repos_total: usize,
+    findings: usize,
+    elapsed_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_error: Option<String>,
+}
+
+struct State {
+    status: Status,
+    last_write: Option<Instant>,
+    /// Whether a failed write was already warned about
+    warned: bool,
+}
+
+/// Keeps status.json up to date; shared by reference with the parallel stages
+pub struct StatusReporter {
+    path: PathBuf,
+    started: Instant,
+    state: Mutex<State>,
+}
+
+impl StatusReporter {
+    /// Reporter writing to `path`, with the run starting now
+    pub fn new(path: PathBuf) -> Self {
+        StatusReporter {
+            path,
+            started: Instant::now(),
+            state: Mutex::new(State {
+                status: Status {
+                    phase: Phase::Starting,
+                    repos_completed: 0,
+                    repos_total: 0,
+                    findings: 0,
+                    elapsed_ms: 0,
+                    last_error: None,
+                },
+                last_write: None,
+                warned: false,
+            }),
+        }
+    }
+
+    /// Enter a phase, writing the file right away
+    pub fn phase(&self, phase: Phase) {
+        self.update(true, |status| status.phase = phase);
+    }
+
+    /// Number of repositories the run covers
+    pub fn set_total(&self, repos: usize) {
+        self.update(true, |status| status.repos_total = repos);
+    }
+
+    /// A repository is done (scanned, or failed), with its finding count
+    pub fn repo_done(&self, findings: usize) {
+        self.update(false, |status| {
+            status.repos_completed += 1;
+            status.findings += findings;
+        });
+    }
+
+    /// Findings after deduplication, replacing the running count
+    pub fn set_findings(&self, findings: usize) {
+        self.update(false, |status| status.findings = findings);
+    }
+
+    /// Record the latest error (written with the next update)
+    pub fn error(&self, message: impl Into<String>) {
+        let message = message.into();
+        self.update(false, |status| status.last_error = Some(message));
+    }
+
+    /// Final write with phase `done`, keeping the error the run ended with
+    pub fn finish(&self, error: Option<String>) {
+        self.update(true, |status| {
+            status.phase = Phase::Done;
+            if error.is_some() {
+                status.last_error = error;
+            }
+        });
+    }
+
+    /// Apply `change`, then write when forced or the interval has passed
+    ///
+    /// A file that can't be written is warned about once and never fails the run.
+    fn update(&self, force: bool, change: impl FnOnce(&mut Status)) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        change(&mut state.status);
+        let due = force || state.last_write.is_none_or(|at| at.elapsed() >= WRITE_INTERVAL);
+        if !due {
+            return;
+        }
+        state.status.elapsed_ms = self.started.elapsed().as_millis() as u64;
+        state.last_write = Some(Instant::now());
+        if let Err(e) = write_atomic(&self.path, &state.status) {
+            if !state.warned {
+                warn!("{:#}", e);
+                state.warned = true;
+            }
+        }
+    }
+}
+
+/// Write `status` to a temporary file next to `path`, then rename it over `path`
+fn write_atomic(path: &Path, status: &Status) -> Result<()> {
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create output directory: {}", dir.display()))?;
+    }
+    let json = serde_json::to_string_pretty(status).context("Failed to serialize status")?;
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    std::fs::write(&tmp, json)
+        .with_context(|| format!("Failed to write status file: {}", tmp.display()))?;
+    std::fs::rename(&tmp, path)
+        .with_context(|| format!("Failed to write status file: {}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn read(path: &Path) -> serde_json::Value {
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_status_reporter_phases() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("out").join(STATUS_FILE);
+        let reporter = StatusReporter::new(path.clone());
+        reporter.phase(Phase::Cloning);
+        reporter.set_total(3);
+        let status = read(&path);
+        assert_eq!(status["phase"], "cloning");
+        assert_eq!(status["repos_total"], 3);
+        assert_eq!(status["repos_completed"], 0);
+        assert!(status.get("last_error").is_none());
+
+        reporter.phase(Phase::Scanning);
+        // Updates from the scanning threads, within the write interval
+        std::thread::scope(|scope| {
+            for findings in [2, 5] {
+                let reporter = &reporter;
+                scope.spawn(move || reporter.repo_done(findings));
+            }
+        });
+        reporter.error("[acme/broken] Failed to check out");
+        assert_eq!(read(&path)["repos_completed"], 0);
+
+        reporter.phase(Phase::Enriching);
+        let status = read(&path);
+        assert_eq!(status["phase"], "enriching");
+        assert_eq!(status["repos_completed"], 2);
+        assert_eq!(status["findings"], 7);
+        assert_eq!(status["last_error"], "[acme/broken] Failed to check out");
+
+        reporter.finish(None);
+        let status = read(&path);
+        assert_eq!(status["phase"], "done");
+        assert_eq!(status["last_error"], "[acme/broken] Failed to check out");
+        assert!(status["elapsed_ms"].is_u64());
+        assert!(!temp_dir.path().join("out").join("status.json.tmp").exists());
+
+        reporter.finish(Some("Failed to write reports".to_string()));
+        assert_eq!(read(&path)["last_error"], "Failed to write reports");
+    }
+}
@@ -0,0 +1,245 @@
+//! OpenTelemetry traces of a scan (`otel` feature, `--otlp-endpoint`)
+//!
+//! Each pipeline phase is a span under one `scan` root: `clone`, `scan` with a
+//! `scan_repository` child per repository, `enrich` and `report`. Spans carry
+//! repository names, file and finding counts and durations; every enrichment
+//! failure is an `enrichment_failure` event on the `enrich` span. Spans are
+//! exported over OTLP/HTTP (protobuf) in batches and flushed when the run ends.
+//!
+//! Without the `otel` feature the types below are empty and every method is a
+//! no-op, so the default build pulls in no OpenTelemetry code.
+
+use anyhow::Result;
+
+#[cfg(feature = "otel")]
+use opentelemetry::trace::{Span as _, TraceContextExt, Tracer as _, TracerProvider as _};
+#[cfg(feature = "otel")]
+use opentelemetry::{Context, KeyValue};
+#[cfg(feature = "otel")]
+use opentelemetry_sdk::trace::{SdkTracer, SdkTracerProvider, Span};
+
+/// Instrumentation scope and `service.name` of exported spans
+#[cfg(feature = "otel")]
+const SERVICE_NAME: &str = "nim-usage-scanner";
+
+/// Where a scan's spans go (nowhere unless an endpoint was given)
+#[derive(Default)]
+pub struct Telemetry {
+    #[cfg(feature = "otel")]
+    tracing: Option<(SdkTracerProvider, SdkTracer)>,
+}
+
+/// OTLP/HTTP traces URL for an endpoint: a bare collector address gets `/v1/traces`
+#[cfg(feature = "otel")]
+fn traces_url(endpoint: &str) -> String {
+    let endpoint = endpoint.trim_end_matches('/');
+    match endpoint.ends_with("/v1/traces") {
+        true => endpoint.to_string(),
+        false => format!("{}/v1/traces", endpoint),
+    }
+}
+
+impl Telemetry {
+    /// Export spans to an OTLP collector when `endpoint` is set
+    pub fn init(endpoint: Option<&str>) -> Result<Self> {
+        match endpoint {
+            None => Ok(Self::default()),
+            #[cfg(feature = "otel")]
+            Some(endpoint) => {
+                use anyhow::Context as _;
+                use opentelemetry_otlp::WithExportConfig;
+                let exporter = opentelemetry_otlp::SpanExporter::builder()
+                    .with_http()
+                    .with_endpoint(traces_url(endpoint))
+                    .build()
+                    .with_context(|| format!("Failed to set up OTLP exporter for {}", endpoint))?;
+                let provider = SdkTracerProvider::builder()
+                    .with_batch_exporter(exporter)
+                    .with_resource(opentelemetry_sdk::Resource::builder().with_service_name(SERVICE_NAME).build())
+                    .build();
+                Ok(Self::with_provider(provider))
+            }
+            #[cfg(not(feature = "otel"))]
+            Some(_) => anyhow::bail!(
+                "--otlp-endpoint needs a scanner built with the `otel` feature (cargo build --features otel)"
+            ),
+        }
+    }
+
+    #[cfg(feature = "otel")]
+    fn with_provider(provider: SdkTracerProvider) -> Self {
+        let tracer = provider.tracer(SERVICE_NAME);
+        Self { tracing: Some((provider, tracer)) }
+    }
+
+    /// Start a top-level span
+    pub fn span(&self, name: &'static str) -> PhaseSpan {
+        #[cfg(feature = "otel")]
+        {
+            PhaseSpan {
+                inner: self.tracing.as_ref().map(|(_, tracer)| (tracer.clone(), tracer.start(name))),
+            }
+        }
+        #[cfg(not(feature = "otel"))]
+        {
+            let _ = name;
+            PhaseSpan {}
+        }
+    }
+
+    /// Flush spans not yet exported and stop the exporter
+    pub fn shutdown(self) {
+        #[cfg(feature = "otel")]
+        if let Some((provider, _)) = self.tracing {
+            if let Err(e) = provider.shutdown() {
+                log::warn!("Failed to export traces: {}", e);
+            }
+        }
+    }
+}
+
+/// One phase of a scan; the span ends when this is dropped
+pub struct PhaseSpan {
+    #[cfg(feature = "otel")]
+    inner: Option<(SdkTracer, Span)>,
+}
+
+impl PhaseSpan {
+    /// Start a span nested under this one
+    pub fn child(&self, name: &'static str) -> PhaseSpan {
+        #[cfg(feature = "otel")]
+        {
+            PhaseSpan {
+                inner: self.inner.as_ref().map(|(tracer, parent)| {
+                    let context = Context::new().with_remote_span_context(parent.span_context().clone());
+                    (tracer.clone(), tracer.start_with_context(name, &context))
+                }),
+            }
+        }
+        #[cfg(not(feature = "otel"))]
+        {
+            let _ = name;
+            PhaseSpan {}
+        }
+    }
+
+    /// Whether anything set on this span is exported (always false without `otel`)
+    pub fn is_recording(&self) -> bool {
+        #[cfg(feature = "otel")]
+        {
+            self.inner.is_some()
+        }
+        #[cfg(not(feature = "otel"))]
+        {
+            false
+        }
+    }
+
+    /// End the span now rather than when it goes out of scope
+    pub fn end(self) {}
+
+    /// Set a string attribute
+    pub fn set_str(&mut self, key: &'static str, value: &str) {
+        #[cfg(feature = "otel")]
+        if let Some((_, ref mut span)) = self.inner {
+            span.set_attribute(KeyValue::new(key, value.to_string()));
+        }
+        #[cfg(not(feature = "otel"))]
+        let _ = (key, value);
+    }
+
+    /// Set an integer attribute (counts, milliseconds)
+    pub fn set_int(&mut self, key: &'static str, value: usize) {
+        #[cfg(feature = "otel")]
+        if let Some((_, ref mut span)) = self.inner {
+            span.set_attribute(KeyValue::new(key, value as i64));
+        }
+        #[cfg(not(feature = "otel"))]
+        let _ = (key, value);
+    }
+
+    /// Record an event with string attributes
+    pub fn event(&mut self, name: &'static str, attributes: &[(&'static str, &str)]) {
+        #[cfg(feature = "otel")]
+        if let Some((_, ref mut span)) = self.inner {
+            let attributes = attributes.iter().map(|(key, value)| KeyValue::new(*key, value.to_string())).collect();
+            span.add_event(name, attributes);
+        }
+        #[cfg(not(feature = "otel"))]
+        let _ = (name, attributes);
+    }
+}
+
+/// Spans collected in memory, for tests
+#[cfg(all(test, feature = "otel"))]
+#[derive(Debug, Clone, Default)]
+pub struct CollectedSpans(std::sync::Arc<std::sync::Mutex<Vec<opentelemetry_sdk::trace::SpanData>>>);
+
+#[cfg(all(test, feature = "otel"))]
+impl opentelemetry_sdk::trace::SpanExporter for CollectedSpans {
+    fn export(
+        &self,
+        batch: Vec<opentelemetry_sdk::trace::SpanData>,
+    ) -> impl std::future::Future<Output = opentelemetry_sdk::error::OTelSdkResult> + Send {
+        self.0.lock().unwrap().extend(batch);
+        std::future::ready(Ok(()))
+    }
+}
+
+#[cfg(all(test, feature = "otel"))]
+impl CollectedSpans {
+    /// Telemetry exporting every span here as soon as it ends
+    pub fn telemetry(&self) -> Telemetry {
+        Telemetry::with_provider(SdkTracerProvider::builder().with_simple_exporter(self.clone()).build())
+    }
+
+    /// Ended spans, in the order they ended
+    pub fn spans(&self) -> Vec<opentelemetry_sdk::trace::SpanData> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "otel")]
+    #[test]
+    fn test_spans_nest_and_carry_attributes() {
+        assert_eq!(traces_url("http://localhost:4318"), "http://localhost:4318/v1/traces");
+        assert_eq!(traces_url("http://collector/v1/traces/"), "http://collector/v1/traces");
+
+        let collected = CollectedSpans::default();
+        let telemetry = collected.telemetry();
+        let root = telemetry.span("scan");
+        let mut child = root.child("clone");
+        assert!(child.is_recording());
+        child.set_str("repository", "acme/app");
+        child.set_int("repos.cloned", 2);
+        child.event("enrichment_failure", &[("error", "not_found")]);
+        child.end();
+        root.end();
+        telemetry.shutdown();
+
+        let spans = collected.spans();
+        assert_eq!(spans.iter().map(|s| s.name.as_ref()).collect::<Vec<_>>(), vec!["clone", "scan"]);
+        assert_eq!(spans[0].parent_span_id, spans[1].span_context.span_id());
+        assert_eq!(spans[0].span_context.trace_id(), spans[1].span_context.trace_id());
+        assert!(spans[0].attributes.contains(&KeyValue::new("repository", "acme/app")));
+        assert!(spans[0].attributes.contains(&KeyValue::new("repos.cloned", 2i64)));
+        assert_eq!(spans[0].events.events[0].name, "enrichment_failure");
+    }
+
+    #[test]
+    fn test_disabled_telemetry() {
+        let telemetry = Telemetry::init(None).unwrap();
+        let mut span = telemetry.span("scan").child("clone");
+        assert!(!span.is_recording());
+        span.set_int("repos.cloned", 1);
+        #[cfg(not(feature = "otel"))]
+        {
+            let err = Telemetry::init(Some("http://localhost:4318")).err().unwrap().to_string();
+            assert!(err.contains("`otel` feature"), "{}", err);
+        }
+    }
+}
@@ -0,0 +1,456 @@
+//! Triage list: what to fix first, ranked from an enriched report
+//!
+//! The report says what exists; triage.md (and triage.json) say what to do
+//! about it. Findings fall into six categories — broken references, policy
+//! violations, explicit `latest` tags, deprecated endpoint paths, staging
+//! endpoints outside tests and docs, and stale pinned tags — ranked by
+//! `scan.triage_weights`. Findings of
+//! one category, repository and subject are merged into one item listing
+//! every `file:line`, with a one-line suggested action.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::cmp::Ordering;
+use std::fmt::Write;
+use serde::Serialize;
+
+use crate::analysis::compare_versions;
+use crate::models::{EndpointStatus, EnrichmentError, Environment, HostedNimMatch, LocalNimMatch, ScanReport, Severity, TriageWeights};
+
+/// Why a finding needs attention
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TriageCategory {
+    /// The image, its tag or the model doesn't exist in NGC / NVCF
+    BrokenReference,
+    /// Blocked by the model policy, or an `error` policy verdict
+    PolicyViolation,
+    /// A Local NIM image pulled as `latest`
+    ExplicitLatest,
+    /// A Hosted NIM endpoint on a deprecated or removed path
+    DeprecatedEndpoint,
+    /// A staging endpoint or model outside tests, examples and docs
+    StagingInProduction,
+    /// A pinned tag older than the current latest of the image
+    StaleTag,
+}
+
+impl TriageCategory {
+    /// Heading used in triage.md
+    pub fn title(&self) -> &'static str {
+        match self {
+            TriageCategory::BrokenReference => "Broken reference",
+            TriageCategory::PolicyViolation => "Policy violation",
+            TriageCategory::ExplicitLatest => "Explicit latest tag",
+            TriageCategory::DeprecatedEndpoint => "Deprecated endpoint",
+            TriageCategory::StagingInProduction => "Staging in production path",
+            TriageCategory::StaleTag => "Stale tag",
+        }
+    }
+
+    fn weight(&self, weights: &TriageWeights) -> u32 {
+        match self {
+            TriageCategory::BrokenReference => weights.broken_reference,
+            TriageCategory::PolicyViolation => weights.policy_violation,
+            TriageCategory::ExplicitLatest => weights.explicit_latest,
+            TriageCategory::DeprecatedEndpoint => weights.deprecated_endpoint,
+            TriageCategory::StagingInProduction => weights.staging_in_production,
+            TriageCategory::StaleTag => weights.stale_tag,
+        }
+    }
+}
+
+/// One thing to fix, with every place it occurs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TriageItem {
+    /// Rank, 1 = fix first
+    pub priority: usize,
+    pub category: TriageCategory,
+    /// Weight the category was ranked by
+    pub weight: u32,
+    pub repository: String,
+    /// Image (`image:tag`) or model the item is about
+    pub subject: String,
+    /// Suggested fix, one line
+    pub action: String,
+    /// `file:line` of every finding in the item
+    pub locations: Vec<String>,
+}
+
+/// Directory names whose files aren't production code
+const NON_PRODUCTION_DIRS: &[&str] = &[
+    "test", "tests", "testing", "__tests__", "example", "examples", "sample", "samples", "docs", "doc", "notebooks",
+];
+
+/// Whether a file is a test, example or documentation rather than production code
+fn is_non_production_path(file_path: &str) -> bool {
+    let normalized = file_path.replace('\\', "/").to_lowercase();
+    let mut components: Vec<&str> = normalized.split('/').collect();
+    let file_name = components.pop().unwrap_or("");
+    components.iter().any(|c| NON_PRODUCTION_DIRS.contains(c))
+        || file_name.starts_with("test_")
+        || file_name.contains("_test.")
+        || file_name.contains(".test.")
+        || file_name.contains(".spec.")
+}
+
+/// Newest tag NGC resolved `latest` to, per image
+fn current_latest(local: &[&LocalNimMatch]) -> HashMap<String, String> {
+    let mut latest: HashMap<String, String> = HashMap::new();
+    for m in local {
+        if let Some(ref resolved) = m.resolved_tag {
+            let newer = latest.get(&m.image_url)
+                .is_none_or(|known| compare_versions(resolved, known) == Some(Ordering::Greater));
+            if newer {
+                latest.insert(m.image_url.clone(), resolved.clone());
+            }
+        }
+    }
+    latest
+}
+
+/// Categories a Local NIM finding belongs to, with the suggested action for each
+fn local_candidates(m: &LocalNimMatch, latest: &HashMap<String, String>) -> Vec<(TriageCategory, String)> {
+    let mut candidates = Vec::new();
+    if m.enrichment_error == Some(EnrichmentError::NotFound) {
+        candidates.push((
+            TriageCategory::BrokenReference,
+            format!("fix the image name: {} is not in the NGC registry", m.image_url),
+        ));
+    } else if m.tag_exists == Some(false) {
+        candidates.push((
+            TriageCategory::BrokenReference,
+            format!("fix the tag: {} is not a tag of {}", m.tag, m.image_url),
+        ));
+    }
+    if let Some(ref verdict) = m.policy {
+        if verdict.severity == Severity::Error {
+            candidates.push((TriageCategory::PolicyViolation, format!("resolve policy rule {}", verdict.policy_rule)));
+        }
+    }
+    if m.tag == "latest" {
+        let action = match m.resolved_tag.as_deref().or(latest.get(&m.image_url).map(String::as_str)) {
+            Some(tag) => format!("pin to {}, the current latest", tag),
+            None => "pin to a version tag instead of latest".to_string(),
+        };
+        candidates.push((TriageCategory::ExplicitLatest, action));
+    } else if let Some(newest) = latest.get(&m.image_url) {
+        if compare_versions(newest, &m.tag) == Some(Ordering::Greater) {
+            candidates.push((TriageCategory::StaleTag, format!("bump to {}, the current latest", newest)));
+        }
+    }
+    candidates
+}
+
+/// Categories a Hosted NIM finding belongs to, with the suggested action for each
+fn hosted_candidates(m: &HostedNimMatch) -> Vec<(TriageCategory, String)> {
+    let mut candidates = Vec::new();
+    let model = m.model_name.as_deref().or(m.function_id.as_deref()).unwrap_or("the endpoint");
+    if m.enrichment_error == Some(EnrichmentError::NotFound) {
+        let action = match m.did_you_mean.first() {
+            Some(suggestion) => format!("use {}: no NVCF function serves {}", suggestion, model),
+            None => format!("switch to a deployed model: no NVCF function serves {}", model),
+        };
+        candidates.push((TriageCategory::BrokenReference, action));
+    }
+    if let Some(ref violation) = m.model_policy {
+        candidates.push((
+            TriageCategory::PolicyViolation,
+            format!("replace {}: blocked by model policy ({})", model, violation.policy_violation_rule),
+        ));
+    } else if let Some(ref verdict) = m.policy {
+        if verdict.severity == Severity::Error {
+            candidates.push((TriageCategory::PolicyViolation, format!("resolve policy rule {}", verdict.policy_rule)));
+        }
+    }
+    if let Some(status @ (EndpointStatus::Deprecated | EndpointStatus::Removed)) = m.endpoint_status {
+        let endpoint = m.endpoint_url.as_deref().unwrap_or("");
+        let action = match m.suggested_endpoint {
+            Some(ref suggested) => format!("move to {}: {} is {}", suggested, endpoint, status.as_str()),
+            None => format!("replace {}: the path is {}", endpoint, status.as_str()),
+        };
+        candidates.push((TriageCategory::DeprecatedEndpoint, action));
+    }
+    let in_docs = m.detection_rule == crate::scanner::RULE_HOSTED_NIM_DOCS;
+    if m.environment == Environment::Staging && !in_docs && !is_non_production_path(&m.file_path) {
+        candidates.push((
+            TriageCategory::StagingInProduction,
+            "point at the production endpoint instead of staging".to_string(),
+        ));
+    }
+    candidates
+}
+
+/// Rank the actionable findings of a report
+///
+/// Each finding goes into its highest-weighted category only (a category with
+/// weight 0 is left out). Items are ordered by weight, then category, then
+/// repository and subject; locations are in file and line order.
+pub fn build_triage(report: &ScanReport, weights: &TriageWeights) -> Vec<TriageItem> {
+    let local: Vec<&LocalNimMatch> = report.source_code.local_nim.iter().chain(&report.actions_workflow.local_nim).collect();
+    let hosted: Vec<&HostedNimMatch> = report.source_code.hosted_nim.iter().chain(&report.actions_workflow.hosted_nim).collect();
+    let latest = current_latest(&local);
+
+    let best = |candidates: Vec<(TriageCategory, String)>| {
+        candidates.into_iter()
+            .filter(|(category, _)| category.weight(weights) > 0)
+            .max_by_key(|(category, _)| (category.weight(weights), std::cmp::Reverse(*category)))
+    };
+    type Key = (TriageCategory, String, String, String);
+    let mut grouped: BTreeMap<Key, BTreeSet<(String, usize)>> = BTreeMap::new();
+    for m in local {
+        if let Some((category, action)) = best(local_candidates(m, &latest)) {
+            let subject = format!("{}:{}", m.image_url, m.tag);
+            grouped.entry((category, m.repository.clone(), subject, action))
+                .or_default()
+                .insert((m.file_path.clone(), m.line_number));
+        }
+    }
+    for m in hosted {
+        if let Some((category, action)) = best(hosted_candidates(m)) {
+            let subject = m.model_name.clone()
+                .or_else(|| m.endpoint_url.clone())
+                .or_else(|| m.function_id.clone())
+                .unwrap_or_default();
+            grouped.entry((category, m.repository.clone(), subject, action))
+                .or_default()
+                .insert((m.file_path.clone(), m.line_number));
+        }
+    }
+
+    let mut items: Vec<TriageItem> = grouped
+        .into_iter()
+        .map(|((category, repository, subject, action), locations)| TriageItem {
+            priority: 0,
+            category,
+            weight: category.weight(weights),
+            repository,
+            subject,
+            action,
+            locations: locations.into_iter().map(|(file, line)| format!("{}:{}", file, line)).collect(),
+        })
+        .collect();
+    // Stable: ties keep the category / repository / subject order of the map
+    items.sort_by_key(|item| std::cmp::Reverse(item.weight));
+    for (i, item) in items.iter_mut().enumerate() {
+        item.priority = i + 1;
+    }
+    items
+}
+
+/// Render triage items as triage.md
+pub fn render_markdown(items: &[TriageItem]) -> String {
+    let mut out = String::from("# NIM Usage Triage\n\n");
+    if items.is_empty() {
+        out.push_str("Nothing to fix: no broken references, policy violations, `latest` tags, deprecated endpoints, staging endpoints in production paths or stale tags.\n");
+        return out;
+    }
+    for item in items {
+        let _ = writeln!(out, "## {}. {}: {} ({})", item.priority, item.category.title(), item.subject, item.repository);
+        let _ = writeln!(out);
+        let _ = writeln!(out, "Action: {}", item.action);
+        let _ = writeln!(out);
+        for location in &item.locations {
+            let _ = writeln!(out, "- `{}`", location);
+        }
+        let _ = writeln!(out);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{FileKind, Framework, LocalIntent, ModelPolicyViolation, NimFindings, PolicyVerdict, UsageKind};
+
+    fn local(repository: &str, image: &str, tag: &str, file: &str, line: usize) -> LocalNimMatch {
+        LocalNimMatch {
+            repository: repository.to_string(),
+            image_url: image.to_string(),
+            tag: tag.to_string(),
+            resolved_tag: None,
+            tag_exists: None,
+            file_path: file.to_string(),
+            line_number: line,
+            match_context: String::new(),
+            policy: None,
+            enrichment_error: None,
+            enriched: false,
+            detection_rule: "local_nim_full".to_string(),
+            workflow_triggers: Vec::new(),
+            nim_identity: String::new(),
+            enrichment_skipped: None,
+            context_truncated: None,
+            compose: None,
+            intent: LocalIntent::Reference,
+            usage_kind: UsageKind::Other,
+            blame: None,
+            notebook_cell: None,
+            file_kind: FileKind::Source,
+            resolved_from: None,
+            fixture: false,
+            fingerprint: String::new(),
+        }
+    }
+
+    fn hosted(repository: &str, model: &str, file: &str, line: usize) -> HostedNimMatch {
+        HostedNimMatch {
+            repository: repository.to_string(),
+            endpoint_url: Some("https://integrate.api.nvidia.com/v1".to_string()),
+            model_name: Some(model.to_string()),
+            file_path: file.to_string(),
+            line_number: line,
+            match_context: String::new(),
+            function_id: None,
+            status: None,
+            container_image: None,
+            environment: Environment::Production,
+            endpoint_host: None,
+            invoke_style: Default::default(),
+            framework: Framework::Unknown,
+            call_params: None,
+            policy: None,
+            model_policy: None,
+            enrichment_error: None,
+            enriched: false,
+            detection_rule: "hosted_nim".to_string(),
+            workflow_triggers: Vec::new(),
+            nim_identity: None,
+            enrichment_skipped: None,
+            browser_exposed: false,
+            did_you_mean: Vec::new(),
+            credential_source: None,
+            context_truncated: None,
+            canonical_endpoint: None,
+            endpoint_mismatch: false,
+            endpoint_status: None,
+            suggested_endpoint: None,
+            blame: None,
+            notebook_cell: None,
+            file_kind: FileKind::Source,
+            resolved_from: None,
+            fixture: false,
+            fingerprint: String::new(),
+        }
+    }
+
+    /// One finding per category, added in reverse priority order
+    fn synthetic_report() -> ScanReport {
+        let image = "nvcr.io/nim/meta/llama-3.1-8b-instruct";
+        let mut source_code = NimFindings::new();
+
+        source_code.local_nim.push(local("org/app", image, "1.3.0", "deploy/values.yaml", 7));
+        let mut staging = hosted("org/app", "stg/meta/llama-3.1-8b-instruct", "src/client.py", 12);
+        staging.environment = Environment::Staging;
+        source_code.hosted_nim.push(staging);
+        // Staging in tests is not production
+        let mut test_staging = hosted("org/app", "stg/meta/llama-3.1-8b-instruct", "tests/test_client.py", 3);
+        test_staging.environment = Environment::Staging;
+        source_code.hosted_nim.push(test_staging);
+
+        let mut retired = hosted("org/app", "meta/llama-3.3-70b-instruct", "src/legacy.py", 8);
+        retired.endpoint_url = Some("https://integrate.api.nvidia.com/v2/chat/completions".to_string());
+        retired.endpoint_status = Some(EndpointStatus::Removed);
+        retired.suggested_endpoint = Some("https://integrate.api.nvidia.com/v1/chat/completions".to_string());
+        source_code.hosted_nim.push(retired);
+
+        let mut latest = local("org/app", image, "latest", "Dockerfile", 1);
+        latest.resolved_tag = Some("1.5.0".to_string());
+        source_code.local_nim.push(latest.clone());
+        latest.line_number = 9;
+        latest.file_path = "compose.yaml".to_string();
+        source_code.local_nim.push(latest);
+
+        let mut denied = hosted("org/app", "meta/llama-3.1-405b-instruct", "src/agent.py", 40);
+        denied.model_policy = Some(ModelPolicyViolation {
+            policy_violation: true,
+            policy_violation_rule: "meta/llama-3.1-405b-instruct".to_string(),
+        });
+        source_code.hosted_nim.push(denied);
+
+        let mut typo = hosted("org/app", "meta/llama-3.1-8b-instruc", "src/chat.py", 5);
+        typo.enrichment_error = Some(EnrichmentError::NotFound);
+        typo.did_you_mean = vec!["meta/llama-3.1-8b-instruct".to_string()];
+        source_code.hosted_nim.push(typo);
+
+        // Fine as it is: pinned to the current latest
+        source_code.local_nim.push(local("org/app", image, "1.5.0", "k8s/nim.yaml", 3));
+        let mut ruled = local("org/ops", image, "1.5.0", "deploy.sh", 2);
+        ruled.policy = Some(PolicyVerdict { severity: Severity::Warning, policy_rule: "local-latest-tag".to_string() });
+        source_code.local_nim.push(ruled);
+
+        ScanReport::new(2, source_code, NimFindings::new())
+    }
+
+    #[test]
+    fn test_triage_order_and_actions() {
+        let items = build_triage(&synthetic_report(), &TriageWeights::default());
+        let summary: Vec<(usize, TriageCategory, &str, &str)> = items.iter()
+            .map(|i| (i.priority, i.category, i.subject.as_str(), i.action.as_str()))
+            .collect();
+        assert_eq!(summary, vec![
+            (1, TriageCategory::BrokenReference, "meta/llama-3.1-8b-instruc",
+             "use meta/llama-3.1-8b-instruct: no NVCF function serves meta/llama-3.1-8b-instruc"),
+            (2, TriageCategory::PolicyViolation, "meta/llama-3.1-405b-instruct",
+             "replace meta/llama-3.1-405b-instruct: blocked by model policy (meta/llama-3.1-405b-instruct)"),
+            (3, TriageCategory::ExplicitLatest, "nvcr.io/nim/meta/llama-3.1-8b-instruct:latest",
+             "pin to 1.5.0, the current latest"),
+            (4, TriageCategory::DeprecatedEndpoint, "meta/llama-3.3-70b-instruct",
+             "move to https://integrate.api.nvidia.com/v1/chat/completions: https://integrate.api.nvidia.com/v2/chat/completions is removed"),
+            (5, TriageCategory::StagingInProduction, "stg/meta/llama-3.1-8b-instruct",
+             "point at the production endpoint instead of staging"),
+            (6, TriageCategory::StaleTag, "nvcr.io/nim/meta/llama-3.1-8b-instruct:1.3.0",
+             "bump to 1.5.0, the current latest"),
+        ]);
+        assert_eq!(items[2].locations, vec!["Dockerfile:1", "compose.yaml:9"]);
+        assert_eq!(items[4].locations, vec!["src/client.py:12"]);
+
+        let markdown = render_markdown(&items);
+        assert!(markdown.contains("## 3. Explicit latest tag: nvcr.io/nim/meta/llama-3.1-8b-instruct:latest (org/app)\n\nAction: pin to 1.5.0, the current latest\n\n- `Dockerfile:1`\n- `compose.yaml:9`\n"), "{}", markdown);
+        assert_eq!(render_markdown(&[]).lines().count(), 3);
+    }
+
+    #[test]
+    fn test_triage_weights() {
+        // Stale tags first, staging left out
+        let weights = TriageWeights { stale_tag: 100, staging_in_production: 0, ..Default::default() };
+        let items = build_triage(&synthetic_report(), &weights);
+        let categories: Vec<TriageCategory> = items.iter().map(|i| i.category).collect();
+        assert_eq!(categories, vec![
+            TriageCategory::StaleTag,
+            TriageCategory::BrokenReference,
+            TriageCategory::PolicyViolation,
+            TriageCategory::ExplicitLatest,
+            TriageCategory::DeprecatedEndpoint,
+        ]);
+
+        // A finding in two categories is listed once, under the heavier one
+        let mut report = synthetic_report();
+        report.source_code.local_nim[1].enrichment_error = Some(EnrichmentError::NotFound);
+        let items = build_triage(&report, &TriageWeights::default());
+        let broken: Vec<&str> = items.iter()
+            .filter(|i| i.category == TriageCategory::BrokenReference)
+            .map(|i| i.subject.as_str())
+            .collect();
+        assert_eq!(broken, vec!["meta/llama-3.1-8b-instruc", "nvcr.io/nim/meta/llama-3.1-8b-instruct:latest"]);
+        let latest = items.iter().find(|i| i.category == TriageCategory::ExplicitLatest).unwrap();
+        assert_eq!(latest.locations, vec!["compose.yaml:9"]);
+
+        // A pinned tag missing from the image's tag list (--verify-tags)
+        let mut report = synthetic_report();
+        report.source_code.local_nim[0].tag_exists = Some(false);
+        let items = build_triage(&report, &TriageWeights::default());
+        let missing = items.iter().find(|i| i.subject.ends_with(":1.3.0")).unwrap();
+        assert_eq!(missing.category, TriageCategory::BrokenReference);
+        assert_eq!(missing.action, "fix the tag: 1.3.0 is not a tag of nvcr.io/nim/meta/llama-3.1-8b-instruct");
+    }
+
+    #[test]
+    fn test_is_non_production_path() {
+        for path in ["tests/test_client.py", "examples/chat.py", "docs/guide/setup.sh", "src/client.test.ts", "pkg/api_test.go"] {
+            assert!(is_non_production_path(path), "{}", path);
+        }
+        for path in ["src/client.py", "deploy/values.yaml", "contest/main.py"] {
+            assert!(!is_non_production_path(path), "{}", path);
+        }
+    }
+}
@@ -0,0 +1,360 @@
+//! Environment variables resolved across a repository's files
+//!
+//! Compose files often say `image: ${NIM_IMAGE}` and leave the value to a
+//! `.env` file next to them, so the image is only seen on the `.env` line.
+//! Before the line-by-line pass, `KEY=value` assignments are collected from
+//! the repository's dotenv files and from the `environment:` blocks of its
+//! Compose files. `${VAR}` and `$VAR` references to them in each scanned line
+//! are replaced by their value, so an image or endpoint is also matched where
+//! it is used. A variable defined in several places takes the definition from
+//! the nearest enclosing directory of the file, else the first one by path.
+//!
+//! Makefiles and justfiles are different: their recipes refer to variables the
+//! same file assigns (`TAG := 1.3.3`, then `$(TAG)` or `{{tag}}`), so those are
+//! resolved from the file alone.
+
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::compose::{is_compose_file, service_environment};
+
+/// `$$` (an escaped `$` in Compose), `${VAR}`, `${VAR:-default}`, `${VAR-default}`, `${VAR:?error}` or `$VAR`
+static VAR_REFERENCE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\$\$|\$\{([A-Za-z_][A-Za-z0-9_]*)(?::?[-?][^}]*)?\}|\$([A-Za-z_][A-Za-z0-9_]*)")
+        .expect("Invalid VAR_REFERENCE regex")
+});
+
+/// `$$` (an escaped `$`), `$(VAR)` or `${VAR}` in a Makefile
+static MAKE_REFERENCE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\$\$|\$\(([A-Za-z_][A-Za-z0-9_]*)\)|\$\{([A-Za-z_][A-Za-z0-9_]*)\}")
+        .expect("Invalid MAKE_REFERENCE regex")
+});
+
+/// `{{name}}` interpolation in a justfile
+static JUST_REFERENCE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_-]*)\s*\}\}")
+        .expect("Invalid JUST_REFERENCE regex")
+});
+
+/// Top-level assignment of a Makefile (`VAR = v`, `VAR := v`, `VAR ?= v`, optionally
+/// `export`ed or `override`n) or justfile (`name := "v"`); recipe lines are indented
+static RECIPE_FILE_ASSIGN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?:export\s+|override\s+)?([A-Za-z_][A-Za-z0-9_-]*)\s*(?::{0,3}|\?)=(.*)$")
+        .expect("Invalid RECIPE_FILE_ASSIGN regex")
+});
+
+/// `KEY=value` line of a dotenv file, optionally `export`ed
+static DOTENV_ASSIGN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*(?:export\s+)?([A-Za-z_][A-Za-z0-9_]*)\s*=(.*)$")
+        .expect("Invalid DOTENV_ASSIGN regex")
+});
+
+/// References inside values are followed this deep (`A=${B}`, `B=${C}`, ...)
+const MAX_DEPTH: usize = 4;
+
+#[derive(Debug, Clone)]
+struct Definition {
+    /// Repository-relative directory of the defining file (`/`-separated, "" for the root)
+    dir: String,
+    value: String,
+}
+
+/// Variables defined in one repository, by name
+#[derive(Debug, Clone, Default)]
+pub struct Variables {
+    definitions: BTreeMap<String, Vec<Definition>>,
+}
+
+/// A file's text with references replaced, and which lines changed
+#[derive(Debug)]
+pub struct Substituted {
+    pub text: String,
+    lines: BTreeMap<usize, LineSubstitution>,
+}
+
+/// What was replaced on one line
+#[derive(Debug)]
+pub struct LineSubstitution {
+    /// Variables replaced, in order
+    pub names: Vec<String>,
+    /// Span of the first reference in the original line
+    pub span: Range<usize>,
+}
+
+impl Substituted {
+    /// Substitution on a 0-indexed line, if any
+    pub fn line(&self, index: usize) -> Option<&LineSubstitution> {
+        self.lines.get(&index)
+    }
+}
+
+/// Value of a dotenv assignment: quotes removed, or an unquoted ` # comment` cut off
+fn dotenv_value(raw: &str) -> &str {
+    let raw = raw.trim();
+    for q in ['"', '\''] {
+        if let Some(inner) = raw.strip_prefix(q).and_then(|rest| rest.split_once(q)).map(|(inner, _)| inner) {
+            return inner;
+        }
+    }
+    raw.split_once(" #").map_or(raw, |(value, _)| value.trim_end())
+}
+
+fn parse_dotenv(content: &str) -> Vec<(String, String)> {
+    content
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .filter_map(|line| DOTENV_ASSIGN.captures(line))
+        .map(|caps| (caps[1].to_string(), dotenv_value(&caps[2]).to_string()))
+        .collect()
+}
+
+/// Directory of a repository-relative path (`/`-separated, "" for the root)
+fn parent_dir(relative_path: &str) -> &str {
+    relative_path.rsplit_once('/').map_or("", |(dir, _)| dir)
+}
+
+fn is_within(dir: &str, ancestor: &str) -> bool {
+    ancestor.is_empty() || dir.strip_prefix(ancestor).is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// Variable syntax of a file whose recipes use its own assignments
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecipeSyntax {
+    /// `Makefile`, `makefile`, `GNUmakefile`
+    Make,
+    /// `justfile`, `Justfile`, `.justfile`
+    Just,
+}
+
+impl RecipeSyntax {
+    /// Syntax of a file by its name, if it is a Makefile or justfile
+    pub fn of(file_name: &str) -> Option<Self> {
+        match file_name {
+            "Makefile" | "makefile" | "GNUmakefile" => Some(RecipeSyntax::Make),
+            "justfile" | "Justfile" | ".justfile" => Some(RecipeSyntax::Just),
+            _ => None,
+        }
+    }
+
+    fn reference(self) -> &'static Regex {
+        match self {
+            RecipeSyntax::Make => &MAKE_REFERENCE,
+            RecipeSyntax::Just => &JUST_REFERENCE,
+        }
+    }
+}
+
+impl Variables {
+    /// Collect the assignments of the dotenv and Compose files among `files`,
+    /// and of the `.env` next to each Compose file (which Compose always reads)
+    pub fn collect(repo_path: &Path, files: &[PathBuf]) -> Self {
+        let mut sources: Vec<&Path> = files.iter().map(PathBuf::as_path).collect();
+        let compose_dotenvs: Vec<PathBuf> = files
+            .iter()
+            .filter(|path| is_compose_file(path))
+            .filter_map(|path| Some(path.parent()?.join(".env")))
+            .filter(|dotenv| dotenv.is_file())
+            .collect();
+        sources.extend(compose_dotenvs.iter().map(PathBuf::as_path));
+        sources.sort();
+        sources.dedup();
+
+        let mut variables = Self::default();
+        for path in sources {
+            let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            let is_dotenv = crate::scanner::is_env_file_name(file_name);
+            if !is_dotenv && !is_compose_file(path) {
+                continue;
+            }
+            let Ok(content) = std::fs::read_to_string(path) else {
+                continue;
+            };
+            let pairs = if is_dotenv { parse_dotenv(&content) } else { service_environment(&content) };
+            let relative = path.strip_prefix(repo_path).unwrap_or(path).to_string_lossy().replace('\\', "/");
+            for (name, value) in pairs {
+                variables.define(name, parent_dir(&relative), value);
+            }
+        }
+        variables
+    }
+
+    /// Add a definition; the first one in a directory wins
+    fn define(&mut self, name: String, dir: &str, value: String) {
+        let definitions = self.definitions.entry(name).or_default();
+        if !definitions.iter().any(|d| d.dir == dir) {
+            // Scanned lines stay lines
+            let value = value.replace(['\r', '\n'], " ");
+            definitions.push(Definition { dir: dir.to_string(), value });
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.definitions.is_empty()
+    }
+
+    /// Definition seen from a file in `dir`: the nearest enclosing directory's, else the first
+    fn lookup(&self, name: &str, dir: &str) -> Option<&Definition> {
+        let definitions = self.definitions.get(name)?;
+        definitions
+            .iter()
+            .filter(|d| is_within(dir, &d.dir))
+            .max_by_key(|d| d.dir.len())
+            .or(definitions.first())
+    }
+
+    /// Replace the references in `text` that resolve, returning the new text,
+    /// the names replaced and the span of the first one (None when nothing resolves)
+    fn expand(&self, reference: &Regex, text: &str, dir: &str, depth: usize) -> Option<(String, Vec<String>, Range<usize>)> {
+        let mut expanded = String::new();
+        let mut names = Vec::new();
+        let mut first = None;
+        let mut last = 0;
+        for caps in reference.captures_iter(text) {
+            let Some(name) = caps.get(1).or(caps.get(2)) else {
+                continue;
+            };
+            let Some(definition) = self.lookup(name.as_str(), dir) else {
+                continue;
+            };
+            let value = match depth < MAX_DEPTH {
+                true => self.expand(reference, &definition.value, &definition.dir, depth + 1).map(|(value, _, _)| value),
+                false => None,
+            };
+            let reference = caps.get(0).map_or(0..0, |m| m.range());
+            expanded.push_str(&text[last..reference.start]);
+            expanded.push_str(value.as_deref().unwrap_or(&definition.value));
+            last = reference.end;
+            names.push(name.as_str().to_string());
+            first.get_or_insert(reference);
+        }
+        let span = first?;
+        expanded.push_str(&text[last..]);
+        Some((expanded, names, span))
+    }
+
+    /// Text of a file with every resolvable reference replaced (None when nothing changed)
+    pub fn substitute(&self, content: &str, relative_path: &str) -> Option<Substituted> {
+        if self.is_empty() || !content.contains('$') {
+            return None;
+        }
+        self.substitute_with(&VAR_REFERENCE, content, parent_dir(relative_path))
+    }
+
+    /// Text of a Makefile or justfile with the references to its own assignments
+    /// replaced (None when nothing changed)
+    pub fn substitute_recipe_file(content: &str, syntax: RecipeSyntax) -> Option<Substituted> {
+        let mut variables = Self::default();
+        for caps in content.lines().filter_map(|line| RECIPE_FILE_ASSIGN.captures(line)) {
+            variables.define(caps[1].to_string(), "", dotenv_value(&caps[2]).to_string());
+        }
+        if variables.is_empty() {
+            return None;
+        }
+        variables.substitute_with(syntax.reference(), content, "")
+    }
+
+    fn substitute_with(&self, reference: &Regex, content: &str, dir: &str) -> Option<Substituted> {
+        let mut text = String::with_capacity(content.len());
+        let mut lines = BTreeMap::new();
+        for (index, line) in content.lines().enumerate() {
+            match self.expand(reference, line, dir, 0) {
+                Some((expanded, names, span)) => {
+                    text.push_str(&expanded);
+                    lines.insert(index, LineSubstitution { names, span });
+                }
+                None => text.push_str(line),
+            }
+            text.push('\n');
+        }
+        (!lines.is_empty()).then_some(Substituted { text, lines })
+    }
+
+    /// Whether a file refers to a defined variable (so it is scanned even without NIM literals)
+    pub fn referenced_in(&self, bytes: &[u8]) -> bool {
+        if self.is_empty() || !bytes.contains(&b'$') {
+            return false;
+        }
+        VAR_REFERENCE
+            .captures_iter(&String::from_utf8_lossy(bytes))
+            .filter_map(|caps| caps.get(1).or(caps.get(2)))
+            .any(|name| self.definitions.contains_key(name.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_collect_and_substitute() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("deploy/gpu")).unwrap();
+        std::fs::write(root.join(".env"), "\
+# Shared
+export REGISTRY=nvcr.io/nim
+NIM_TAG=1.0.0
+NIM_IMAGE=\"${REGISTRY}/nvidia/llama-3.2-nv-embedqa-1b-v2:${NIM_TAG}\"
+").unwrap();
+        std::fs::write(root.join("deploy/nim.env"), "NIM_TAG=1.5.0 # pinned\n").unwrap();
+        std::fs::write(root.join("deploy/compose.yaml"), "\
+services:
+  chat:
+    environment:
+      - NIM_URL=https://integrate.api.nvidia.com/v1
+      - PASSTHROUGH
+  embed:
+    environment:
+      EMBED_MODEL: nvidia/nv-embedqa-e5-v5
+      REPLICAS: 2
+").unwrap();
+        let files = vec![root.join(".env"), root.join("deploy/nim.env"), root.join("deploy/compose.yaml")];
+        let variables = Variables::collect(root, &files);
+        assert!(!variables.definitions.contains_key("PASSTHROUGH"));
+        assert_eq!(variables.lookup("REPLICAS", "").map(|d| d.value.as_str()), Some("2"));
+
+        // Nearest directory wins: deploy/ pins its own tag, the root doesn't see it
+        // (a value's own references resolve where it is defined)
+        let content = "image: ${NIM_IMAGE}\ntag: ${NIM_TAG}\nurl: $NIM_URL/chat\ncost: $$5 for ${UNDEFINED:-x}\n";
+        let deep = variables.substitute(content, "deploy/gpu/compose.yaml").unwrap();
+        assert_eq!(deep.text, "\
+image: nvcr.io/nim/nvidia/llama-3.2-nv-embedqa-1b-v2:1.0.0
+tag: 1.5.0
+url: https://integrate.api.nvidia.com/v1/chat
+cost: $$5 for ${UNDEFINED:-x}
+");
+        let line = deep.line(0).unwrap();
+        assert_eq!((line.names.clone(), line.span.clone()), (vec!["NIM_IMAGE".to_string()], 7..19));
+        assert!(deep.line(3).is_none());
+        let top = variables.substitute(content, "compose.yaml").unwrap();
+        assert!(top.text.contains("\ntag: 1.0.0\n"), "{}", top.text);
+
+        assert!(variables.substitute("image: ${OTHER}\n", "compose.yaml").is_none());
+        assert!(variables.referenced_in(b"model: ${EMBED_MODEL}"));
+        assert!(!variables.referenced_in(b"model: ${OTHER} $$EMBED_MODEL"));
+    }
+
+    #[test]
+    fn test_substitute_recipe_file() {
+        let makefile = "\
+export REGISTRY = nvcr.io/nim
+IMAGE := ${REGISTRY}/meta/llama-3.1-8b-instruct
+FLAGS += --gpus all
+run:
+\tTAG=2.0.0 docker run $(FLAGS) $(IMAGE):$(TAG) $$HOME
+";
+        let substituted = Variables::substitute_recipe_file(makefile, RecipeSyntax::Make).unwrap();
+        assert_eq!(
+            substituted.text.lines().nth(4),
+            Some("\tTAG=2.0.0 docker run $(FLAGS) nvcr.io/nim/meta/llama-3.1-8b-instruct:$(TAG) $$HOME")
+        );
+        assert_eq!(substituted.line(4).unwrap().names, vec!["IMAGE"]);
+        assert!(Variables::substitute_recipe_file("run:\n\tdocker run $(IMAGE)\n", RecipeSyntax::Make).is_none());
+        assert_eq!(RecipeSyntax::of("GNUmakefile"), Some(RecipeSyntax::Make));
+        assert_eq!(RecipeSyntax::of("Makefile.in"), None);
+    }
+}